@@ -0,0 +1,1663 @@
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::time::Duration;
+
+use reqwest::header::{ACCEPT_LANGUAGE, CONTENT_TYPE, RETRY_AFTER};
+use reqwest::{Method, Response, StatusCode};
+use serde::de::{DeserializeOwned, IgnoredAny};
+use serde::Serialize;
+use serde_json::{json, Value};
+use xcloud_core::advisor::IndexRecommendation;
+use xcloud_core::alerts::Alert;
+use xcloud_core::capture::Capture;
+use xcloud_core::cluster::ClusterState;
+use xcloud_core::config::{Config, RetryConfig};
+use xcloud_core::consistency::CONSISTENCY_TOKEN_HEADER;
+use xcloud_core::console::ConsoleResult;
+use xcloud_core::db::{
+    Activity, AggregateView, ClientChange, Comment, ConsumerOffset, DryRunReport, Event, EventLog,
+    HistoryEntry, Integration, LegalHold, Lock, PoolState, Project, SearchHit, SeriesBucket,
+    SeriesPoint, Snapshot, SnapshotDiff, SyncOutcome, SyncResolution, TableInfo, TablePolicy,
+    TimeSeries, Transform, TreeNode, ViewGroup,
+};
+use xcloud_core::flags::{FeatureFlag, FlagState};
+use xcloud_core::health::ProbeResult;
+use xcloud_core::import::ImportProgress;
+use xcloud_core::keystats::KeyStat;
+use xcloud_core::maintenance::MaintenanceState;
+use xcloud_core::privacy::{PrivacyAction, PrivacyReport, SubjectArchive};
+use xcloud_core::response::ApiResponse;
+use xcloud_core::runtime::RuntimeStats;
+use xcloud_core::schema::{JsonSchema, TableSchema};
+use xcloud_core::server::LOCK_OWNER_HEADER;
+use xcloud_core::signing::{
+    SignedRequest, CLIENT_HEADER, REQUEST_SIGNATURE_HEADER, TIMESTAMP_HEADER,
+};
+use xcloud_core::slowlog::SlowQuery;
+use xcloud_core::tiering::TieringReport;
+
+use crate::errors::ClientError;
+use crate::platform;
+use crate::types::{
+    AdminStats, AppendedEvents, GetManyResult, NewTransform, Patch, RecycledPools, Regions,
+    SignedUrl, StreamedRow, SyncChanges,
+};
+
+/// The body of a request with its content type.
+type Body = (Vec<u8>, &'static str);
+
+/// A typed async client of the xCLOUD API.
+///
+/// Every method maps to one route and returns the data of its response. Responses with an
+/// error status are returned as [`ClientError::Api`], except where a missing key is part of
+/// the result. Requests answered with a `503` or `429`, and requests that could not reach the
+/// server, are retried with an exponential backoff with full jitter, honouring `Retry-After`;
+/// requests that may have reached the server are only retried if their method is safe.
+///
+/// The client sends the consistency token of its latest write with every request, so its
+/// reads see its own writes even when the server routes reads to replicas.
+pub struct Client {
+    http: reqwest::Client,
+    base_url: String,
+    token: Option<String>,
+    /// The id and secret of the client signing the mutating requests.
+    signing: Option<(String, String)>,
+    lock_owner: Option<String>,
+    /// The languages the error messages are preferred in, sent as `Accept-Language`.
+    language: Option<String>,
+    retry: RetryConfig,
+    /// The consistency token of the latest write, `0` before any.
+    consistency: AtomicI64,
+}
+
+/// Implementation of the `Client` struct.
+impl Client {
+    /// Creates a new client of the server at the given URL.
+    ///
+    /// # Arguments
+    ///
+    /// * `base_url` - The URL of the server, e.g. `http://127.0.0.1:8080`.
+    ///
+    /// # Returns
+    ///
+    /// * `Client` - A new instance of the Client.
+    pub fn new(base_url: &str) -> Self {
+        Client {
+            http: reqwest::Client::new(),
+            base_url: base_url.trim_end_matches('/').to_string(),
+            token: None,
+            signing: None,
+            lock_owner: None,
+            language: None,
+            retry: RetryConfig::default(),
+            consistency: AtomicI64::new(0),
+        }
+    }
+
+    /// Sends requests through the given HTTP client, e.g. one with timeouts or proxies set.
+    ///
+    /// # Arguments
+    ///
+    /// * `http` - The HTTP client.
+    ///
+    /// # Returns
+    ///
+    /// * `Client` - The client using the HTTP client.
+    pub fn with_http(mut self, http: reqwest::Client) -> Self {
+        self.http = http;
+        self
+    }
+
+    /// Sends a bearer token with every request, for servers behind an authenticating proxy.
+    ///
+    /// # Arguments
+    ///
+    /// * `token` - The token.
+    ///
+    /// # Returns
+    ///
+    /// * `Client` - The client sending the token.
+    pub fn with_token(mut self, token: &str) -> Self {
+        self.token = Some(token.to_string());
+        self
+    }
+
+    /// Signs the mutating requests, for servers enforcing request signing.
+    ///
+    /// # Arguments
+    ///
+    /// * `client` - The id of the client in the request signing configuration of the server.
+    /// * `secret` - The secret of the client.
+    ///
+    /// # Returns
+    ///
+    /// * `Client` - The client signing its requests.
+    pub fn with_signing(mut self, client: &str, secret: &str) -> Self {
+        self.signing = Some((client.to_string(), secret.to_string()));
+        self
+    }
+
+    /// Names the owner of the locks held by this client, so its writes pass its own locks.
+    ///
+    /// # Arguments
+    ///
+    /// * `owner` - The owner named when acquiring the locks.
+    ///
+    /// # Returns
+    ///
+    /// * `Client` - The client naming the owner.
+    pub fn with_lock_owner(mut self, owner: &str) -> Self {
+        self.lock_owner = Some(owner.to_string());
+        self
+    }
+
+    /// Asks the server for error messages in the given languages, e.g. to show them to users.
+    ///
+    /// # Arguments
+    ///
+    /// * `language` - The preferred languages, e.g. `de` or `de-CH, fr;q=0.5`.
+    ///
+    /// # Returns
+    ///
+    /// * `Client` - The client sending the languages.
+    pub fn with_language(mut self, language: &str) -> Self {
+        self.language = Some(language.to_string());
+        self
+    }
+
+    /// Makes the reads of this client see the writes behind a token, e.g. one handed over by
+    /// another client.
+    ///
+    /// # Arguments
+    ///
+    /// * `token` - The consistency token returned by the writes.
+    ///
+    /// # Returns
+    ///
+    /// * `Client` - The client sending the token.
+    pub fn with_consistency_token(self, token: i64) -> Self {
+        self.consistency.fetch_max(token, Ordering::Relaxed);
+        self
+    }
+
+    /// Returns the consistency token of the latest write made by this client, if any.
+    pub fn consistency_token(&self) -> Option<i64> {
+        Some(self.consistency.load(Ordering::Relaxed)).filter(|token| *token > 0)
+    }
+
+    /// Replaces the retry budget and delays, requests are not retried if at most one attempt.
+    ///
+    /// # Arguments
+    ///
+    /// * `retry` - The retry configuration.
+    ///
+    /// # Returns
+    ///
+    /// * `Client` - The client retrying with the configuration.
+    pub fn with_retry(mut self, retry: RetryConfig) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// Sets the value of a key.
+    pub async fn set_data(&self, table: &str, key: &str, value: &str) -> Result<(), ClientError> {
+        let body = json!({"table": table, "key": key, "value": value});
+        self.ignore(Method::POST, "/set_data", &body).await
+    }
+
+    /// Stores a value under a key generated by the server.
+    ///
+    /// # Returns
+    ///
+    /// * `String` - The generated key.
+    pub async fn append_data(&self, table: &str, value: &str) -> Result<String, ClientError> {
+        #[derive(serde::Deserialize)]
+        struct AppendedKey {
+            key: String,
+        }
+        let body = json!({"table": table, "value": value});
+        let appended: AppendedKey = self.call(Method::POST, "/append_data", &body).await?;
+        Ok(appended.key)
+    }
+
+    /// Retrieves the value of a key, typed by the value type of its table.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<Value>` - The value, or `None` if the key does not exist.
+    pub async fn get_data(&self, table: &str, key: &str) -> Result<Option<Value>, ClientError> {
+        let query = [("table", table.to_string()), ("key", key.to_string())];
+        Self::found(self.get("/get_data", &query).await)
+    }
+
+    /// Retrieves the selected fields of the JSON document stored under a key.
+    ///
+    /// # Arguments
+    ///
+    /// * `table` - The table holding the key.
+    /// * `key` - The key.
+    /// * `fields` - The dotted paths of the fields, e.g. `properties.height`.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<Value>` - The document with the selected fields only, or `None` if the key
+    ///   does not exist.
+    pub async fn get_fields(
+        &self,
+        table: &str,
+        key: &str,
+        fields: &[&str],
+    ) -> Result<Option<Value>, ClientError> {
+        let query = [
+            ("table", table.to_string()),
+            ("key", key.to_string()),
+            ("fields", fields.join(",")),
+        ];
+        Self::found(self.get("/get_data", &query).await)
+    }
+
+    /// Sets the value of a key to an integer.
+    pub async fn set_int(&self, table: &str, key: &str, value: i64) -> Result<(), ClientError> {
+        let body = json!({"table": table, "key": key, "value": value});
+        self.ignore(Method::POST, "/set_int", &body).await
+    }
+
+    /// Retrieves the value of a key as an integer.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<i64>` - The value, or `None` if the key does not exist.
+    ///
+    /// # Errors
+    ///
+    /// This function will return a `422` error if the value is not an integer.
+    pub async fn get_int(&self, table: &str, key: &str) -> Result<Option<i64>, ClientError> {
+        let query = [("table", table.to_string()), ("key", key.to_string())];
+        Self::found(self.get("/get_int", &query).await)
+    }
+
+    /// Sets the value of a key to a number.
+    pub async fn set_float(&self, table: &str, key: &str, value: f64) -> Result<(), ClientError> {
+        let body = json!({"table": table, "key": key, "value": value});
+        self.ignore(Method::POST, "/set_float", &body).await
+    }
+
+    /// Retrieves the value of a key as a number.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<f64>` - The value, or `None` if the key does not exist.
+    ///
+    /// # Errors
+    ///
+    /// This function will return a `422` error if the value is not a number.
+    pub async fn get_float(&self, table: &str, key: &str) -> Result<Option<f64>, ClientError> {
+        let query = [("table", table.to_string()), ("key", key.to_string())];
+        Self::found(self.get("/get_float", &query).await)
+    }
+
+    /// Sets the value of a key to a boolean.
+    pub async fn set_bool(&self, table: &str, key: &str, value: bool) -> Result<(), ClientError> {
+        let body = json!({"table": table, "key": key, "value": value});
+        self.ignore(Method::POST, "/set_bool", &body).await
+    }
+
+    /// Retrieves the value of a key as a boolean.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<bool>` - The value, or `None` if the key does not exist.
+    ///
+    /// # Errors
+    ///
+    /// This function will return a `422` error if the value is not `true` or `false`.
+    pub async fn get_bool(&self, table: &str, key: &str) -> Result<Option<bool>, ClientError> {
+        let query = [("table", table.to_string()), ("key", key.to_string())];
+        Self::found(self.get("/get_bool", &query).await)
+    }
+
+    /// Executes a GraphQL query or mutation.
+    ///
+    /// # Arguments
+    ///
+    /// * `query` - The GraphQL document.
+    /// * `variables` - The values of the variables of the document.
+    ///
+    /// # Returns
+    ///
+    /// * `Value` - The GraphQL response, holding the `data` and any `errors` of the resolvers.
+    pub async fn graphql(&self, query: &str, variables: &Value) -> Result<Value, ClientError> {
+        let body = json!({"query": query, "variables": variables});
+        let response = Self::checked(
+            self.send(Method::POST, "/graphql", &[], Some(Self::json(&body)?))
+                .await?,
+        )
+        .await?;
+        Ok(serde_json::from_slice(&response.bytes().await?)?)
+    }
+
+    /// Retrieves the value a key had at a point in time.
+    ///
+    /// # Arguments
+    ///
+    /// * `timestamp` - The unix timestamp in milliseconds.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<Value>` - The value, or `None` if the key did not exist then.
+    pub async fn get_data_at(
+        &self,
+        table: &str,
+        key: &str,
+        timestamp: i64,
+    ) -> Result<Option<Value>, ClientError> {
+        let query = [
+            ("table", table.to_string()),
+            ("key", key.to_string()),
+            ("timestamp", timestamp.to_string()),
+        ];
+        Self::found(self.get("/get_data_at", &query).await)
+    }
+
+    /// Retrieves the previous versions of a key, most recent first.
+    pub async fn history(
+        &self,
+        table: &str,
+        key: &str,
+        limit: Option<i64>,
+    ) -> Result<Vec<HistoryEntry>, ClientError> {
+        let mut query = vec![("table", table.to_string()), ("key", key.to_string())];
+        query.extend(limit.map(|limit| ("limit", limit.to_string())));
+        self.get("/history", &query).await
+    }
+
+    /// Collapses the versions of the keys of a table older than the given age, or the
+    /// configured one if `None`, returning the number of removed versions.
+    pub async fn compact_history(
+        &self,
+        table: &str,
+        max_age_secs: Option<u64>,
+    ) -> Result<u64, ClientError> {
+        let body = json!({"table": table, "max_age_secs": max_age_secs});
+        self.call(Method::POST, "/admin/history/compact", &body)
+            .await
+    }
+
+    /// Retrieves the values of many keys of a table in one round trip.
+    pub async fn get_many(&self, table: &str, keys: &[&str]) -> Result<GetManyResult, ClientError> {
+        let body = json!({"table": table, "keys": keys});
+        self.call(Method::POST, "/get_many", &body).await
+    }
+
+    /// Retrieves the selected fields of the values of many keys of a table in one round trip.
+    pub async fn get_many_fields(
+        &self,
+        table: &str,
+        keys: &[&str],
+        fields: &[&str],
+    ) -> Result<GetManyResult, ClientError> {
+        let body = json!({"table": table, "keys": keys, "fields": fields.join(",")});
+        self.call(Method::POST, "/get_many", &body).await
+    }
+
+    /// Replaces the value of an existing key.
+    pub async fn update_data(
+        &self,
+        table: &str,
+        key: &str,
+        value: &str,
+    ) -> Result<(), ClientError> {
+        let body = json!({"table": table, "key": key, "value": value});
+        self.ignore(Method::PUT, "/update_data", &body).await
+    }
+
+    /// Patches the value of a key.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<Value>` - The patched value, or `None` for text deltas.
+    pub async fn patch_data(
+        &self,
+        table: &str,
+        key: &str,
+        patch: Patch,
+    ) -> Result<Option<Value>, ClientError> {
+        let mut body = json!({"table": table, "key": key});
+        match patch {
+            Patch::Json(patch) => body["patch"] = patch,
+            Patch::Merge(merge) => body["merge"] = merge,
+            Patch::Delta(delta) => body["delta"] = serde_json::to_value(delta)?,
+        }
+        self.request(Method::PATCH, "/patch_data", &[], Some(Self::json(&body)?))
+            .await
+    }
+
+    /// Adds a delta to the integer value of a key, starting from zero.
+    ///
+    /// # Returns
+    ///
+    /// * `i64` - The new value.
+    pub async fn increment(&self, table: &str, key: &str, delta: i64) -> Result<i64, ClientError> {
+        let body = json!({"table": table, "key": key, "delta": delta});
+        self.call(Method::POST, "/increment", &body).await
+    }
+
+    /// Sets the value of a key if it still holds the expected value, or does not exist if
+    /// `None` is expected. A mismatch is returned as a `409`.
+    ///
+    /// # Arguments
+    ///
+    /// * `merge` - Whether to merge diverged JSON values field by field instead of failing.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<Value>` - The merged value if the values were merged.
+    pub async fn compare_and_set(
+        &self,
+        table: &str,
+        key: &str,
+        expected: Option<&str>,
+        value: &str,
+        merge: bool,
+    ) -> Result<Option<Value>, ClientError> {
+        let body = json!({
+            "table": table,
+            "key": key,
+            "expected": expected,
+            "value": value,
+            "merge": merge,
+        });
+        self.request(
+            Method::POST,
+            "/compare_and_set",
+            &[],
+            Some(Self::json(&body)?),
+        )
+        .await
+    }
+
+    /// Pushes a value to the back, or the front, of the list of a key.
+    ///
+    /// # Returns
+    ///
+    /// * `i64` - The new length of the list.
+    pub async fn list_push(
+        &self,
+        table: &str,
+        key: &str,
+        value: &str,
+        front: bool,
+    ) -> Result<i64, ClientError> {
+        let body = json!({"table": table, "key": key, "value": value, "front": front});
+        self.call(Method::POST, "/list/push", &body).await
+    }
+
+    /// Pops a value from the back, or the front, of the list of a key.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<String>` - The value, or `None` if the list is empty.
+    pub async fn list_pop(
+        &self,
+        table: &str,
+        key: &str,
+        front: bool,
+    ) -> Result<Option<String>, ClientError> {
+        let body = json!({"table": table, "key": key, "front": front});
+        Self::found(self.call(Method::POST, "/list/pop", &body).await)
+    }
+
+    /// Retrieves a range of the list of a key.
+    pub async fn list_range(
+        &self,
+        table: &str,
+        key: &str,
+        offset: i64,
+        limit: Option<i64>,
+    ) -> Result<Vec<String>, ClientError> {
+        let mut query = vec![
+            ("table", table.to_string()),
+            ("key", key.to_string()),
+            ("offset", offset.to_string()),
+        ];
+        query.extend(limit.map(|limit| ("limit", limit.to_string())));
+        self.get("/list/range", &query).await
+    }
+
+    /// Deletes a key.
+    pub async fn delete_data(&self, table: &str, key: &str) -> Result<(), ClientError> {
+        let body = json!({"table": table, "key": key});
+        self.ignore(Method::DELETE, "/delete_data", &body).await
+    }
+
+    /// Deletes the given keys, or the keys starting with a prefix, of a table.
+    ///
+    /// # Returns
+    ///
+    /// * `u64` - The number of deleted keys.
+    pub async fn delete_many(
+        &self,
+        table: &str,
+        prefix: Option<&str>,
+        keys: Option<&[&str]>,
+    ) -> Result<u64, ClientError> {
+        let body = json!({"table": table, "prefix": prefix, "keys": keys});
+        self.call(Method::POST, "/delete_many", &body).await
+    }
+
+    /// Reports what [`Client::delete_many`] would delete, without deleting anything.
+    pub async fn preview_delete_many(
+        &self,
+        table: &str,
+        prefix: Option<&str>,
+        keys: Option<&[&str]>,
+    ) -> Result<DryRunReport, ClientError> {
+        let body = json!({"table": table, "prefix": prefix, "keys": keys});
+        let query = [("dryRun", "true".to_string())];
+        Self::required(
+            self.request(
+                Method::POST,
+                "/delete_many",
+                &query,
+                Some(Self::json(&body)?),
+            )
+            .await?,
+        )
+    }
+
+    /// Searches the values of a table for all words of the query, ranked by relevance.
+    pub async fn fulltext(
+        &self,
+        table: &str,
+        q: &str,
+        offset: i64,
+        limit: i64,
+    ) -> Result<Vec<SearchHit>, ClientError> {
+        let query = [
+            ("table", table.to_string()),
+            ("q", q.to_string()),
+            ("offset", offset.to_string()),
+            ("limit", limit.to_string()),
+        ];
+        self.get("/fulltext", &query).await
+    }
+
+    /// Lists the keys of a table in order.
+    pub async fn list_keys(
+        &self,
+        table: &str,
+        offset: i64,
+        limit: Option<i64>,
+    ) -> Result<Vec<String>, ClientError> {
+        let mut query = vec![("table", table.to_string()), ("offset", offset.to_string())];
+        query.extend(limit.map(|limit| ("limit", limit.to_string())));
+        self.get("/keys", &query).await
+    }
+
+    /// Lists the children of a path of the keys of a table, split on `/`.
+    pub async fn tree(&self, table: &str, path: &str) -> Result<Vec<TreeNode>, ClientError> {
+        let query = [("table", table.to_string()), ("path", path.to_string())];
+        self.get("/tree", &query).await
+    }
+
+    /// Retrieves all rows of a table.
+    pub async fn stream_table(&self, table: &str) -> Result<Vec<StreamedRow>, ClientError> {
+        let path = format!("/stream_table/{}", table);
+        Self::ndjson(self.send(Method::GET, &path, &[], None).await?).await
+    }
+
+    /// Retrieves all rows of a table as of a single point in time.
+    pub async fn consistent_export(&self, table: &str) -> Result<Vec<StreamedRow>, ClientError> {
+        let query = [("table", table.to_string())];
+        Self::ndjson(
+            self.send(Method::GET, "/consistent_export", &query, None)
+                .await?,
+        )
+        .await
+    }
+
+    /// Starts importing rows, in the format of [`Client::stream_table`], into a table.
+    ///
+    /// # Arguments
+    ///
+    /// * `ndjson` - The rows, one JSON object per line.
+    /// * `dry_run` - Whether to only validate the rows without writing them.
+    ///
+    /// # Returns
+    ///
+    /// * `ImportProgress` - The progress of the started import.
+    pub async fn import_table(
+        &self,
+        table: &str,
+        ndjson: Vec<u8>,
+        dry_run: bool,
+    ) -> Result<ImportProgress, ClientError> {
+        let path = format!("/import/{}", table);
+        let query = [("dryRun", dry_run.to_string())];
+        let body = (ndjson, "application/x-ndjson");
+        Self::required(
+            self.request(Method::POST, &path, &query, Some(body))
+                .await?,
+        )
+    }
+
+    /// Starts importing the rows of a CSV file, whose first line names the columns, into a
+    /// table.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The column holding the keys.
+    /// * `columns` - The columns composing the JSON object values, each optionally renamed as
+    ///   `column:field`, or empty for all columns but the key.
+    /// * `csv` - The file.
+    /// * `dry_run` - Whether to only validate the rows without writing them.
+    ///
+    /// # Returns
+    ///
+    /// * `ImportProgress` - The progress of the started import.
+    pub async fn import_csv(
+        &self,
+        table: &str,
+        key: &str,
+        columns: &[&str],
+        csv: Vec<u8>,
+        dry_run: bool,
+    ) -> Result<ImportProgress, ClientError> {
+        let mut query = vec![
+            ("table", table.to_string()),
+            ("key", key.to_string()),
+            ("dryRun", dry_run.to_string()),
+        ];
+        if !columns.is_empty() {
+            query.push(("columns", columns.join(",")));
+        }
+        let body = (csv, "text/csv");
+        Self::required(
+            self.request(Method::POST, "/import_csv", &query, Some(body))
+                .await?,
+        )
+    }
+
+    /// Retrieves the progress of an import.
+    pub async fn import_progress(&self, id: u64) -> Result<ImportProgress, ClientError> {
+        self.get(&format!("/imports/{}", id), &[]).await
+    }
+
+    /// Lists the tables.
+    pub async fn list_tables(&self) -> Result<Vec<String>, ClientError> {
+        self.get("/tables", &[]).await
+    }
+
+    /// Creates a table, or changes the value type and key collation of an existing one.
+    pub async fn create_table(&self, schema: &TableSchema) -> Result<(), ClientError> {
+        self.ignore(Method::POST, "/tables", schema).await
+    }
+
+    /// Deletes a table with all its keys.
+    pub async fn delete_table(&self, table: &str) -> Result<(), ClientError> {
+        self.ignore(Method::DELETE, "/delete_table", &json!({"table": table}))
+            .await
+    }
+
+    /// Reports what [`Client::delete_table`] would delete, without deleting anything.
+    pub async fn preview_delete_table(&self, table: &str) -> Result<DryRunReport, ClientError> {
+        let body = Self::json(&json!({"table": table}))?;
+        let query = [("dryRun", "true".to_string())];
+        Self::required(
+            self.request(Method::DELETE, "/delete_table", &query, Some(body))
+                .await?,
+        )
+    }
+
+    /// Copies the schema, and optionally the keys, of a table to a new table.
+    ///
+    /// # Returns
+    ///
+    /// * `u64` - The number of copied keys.
+    pub async fn clone_table(
+        &self,
+        source: &str,
+        target: &str,
+        with_data: bool,
+        allow_cross_region: bool,
+    ) -> Result<u64, ClientError> {
+        let body = json!({
+            "source": source,
+            "target": target,
+            "with_data": with_data,
+            "allow_cross_region": allow_cross_region,
+        });
+        self.call(Method::POST, "/clone_table", &body).await
+    }
+
+    /// Lists the tables copied into new projects.
+    pub async fn templates(&self) -> Result<Vec<String>, ClientError> {
+        self.get("/templates", &[]).await
+    }
+
+    /// Marks a table as a template of new projects.
+    pub async fn add_template(&self, table: &str) -> Result<(), ClientError> {
+        self.ignore(Method::PUT, "/templates", &json!({"table": table}))
+            .await
+    }
+
+    /// Stops copying a table into new projects.
+    pub async fn remove_template(&self, table: &str) -> Result<(), ClientError> {
+        self.ignore(Method::DELETE, "/templates", &json!({"table": table}))
+            .await
+    }
+
+    /// Acquires or renews the lock of a key.
+    ///
+    /// # Arguments
+    ///
+    /// * `lease_secs` - The lifetime of the lock, the configured default if `None`.
+    pub async fn lock(
+        &self,
+        table: &str,
+        key: &str,
+        owner: &str,
+        lease_secs: Option<u64>,
+    ) -> Result<Lock, ClientError> {
+        let body = json!({"table": table, "key": key, "owner": owner, "lease_secs": lease_secs});
+        self.call(Method::POST, "/lock", &body).await
+    }
+
+    /// Releases the lock of a key held by an owner.
+    pub async fn unlock(&self, table: &str, key: &str, owner: &str) -> Result<(), ClientError> {
+        let body = json!({"table": table, "key": key, "owner": owner});
+        self.ignore(Method::POST, "/unlock", &body).await
+    }
+
+    /// Retrieves the lock of a key.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<Lock>` - The lock, or `None` if the key is not locked.
+    pub async fn get_lock(&self, table: &str, key: &str) -> Result<Option<Lock>, ClientError> {
+        let query = [("table", table.to_string()), ("key", key.to_string())];
+        Self::found(self.get("/lock", &query).await)
+    }
+
+    /// Lists the event logs.
+    pub async fn event_logs(&self) -> Result<Vec<EventLog>, ClientError> {
+        self.get("/events", &[]).await
+    }
+
+    /// Creates an event log, or changes its retention.
+    pub async fn create_event_log(&self, log: &EventLog) -> Result<(), ClientError> {
+        self.ignore(Method::POST, "/events", log).await
+    }
+
+    /// Deletes an event log with its events and consumer offsets.
+    pub async fn delete_event_log(&self, log: &str) -> Result<(), ClientError> {
+        self.ignore(Method::DELETE, "/events", &json!({"log": log}))
+            .await
+    }
+
+    /// Appends events to an event log.
+    pub async fn append_events(
+        &self,
+        log: &str,
+        values: &[&str],
+    ) -> Result<AppendedEvents, ClientError> {
+        let body = json!({"log": log, "values": values});
+        self.call(Method::POST, "/events/append", &body).await
+    }
+
+    /// Reads the events of an event log from an offset, or from a unix timestamp in
+    /// milliseconds.
+    pub async fn read_events(
+        &self,
+        log: &str,
+        offset: i64,
+        since: Option<i64>,
+        limit: Option<i64>,
+    ) -> Result<Vec<Event>, ClientError> {
+        let mut query = vec![("log", log.to_string()), ("offset", offset.to_string())];
+        query.extend(since.map(|since| ("since", since.to_string())));
+        query.extend(limit.map(|limit| ("limit", limit.to_string())));
+        self.get("/events/read", &query).await
+    }
+
+    /// Retrieves the offsets of the consumer groups of an event log.
+    pub async fn consumer_offsets(&self, log: &str) -> Result<Vec<ConsumerOffset>, ClientError> {
+        self.get("/events/consumers", &[("log", log.to_string())])
+            .await
+    }
+
+    /// Commits the offset of the next event a consumer group reads.
+    pub async fn commit_consumer_offset(
+        &self,
+        log: &str,
+        group: &str,
+        offset: i64,
+    ) -> Result<(), ClientError> {
+        let body = json!({"log": log, "group": group, "offset": offset});
+        self.ignore(Method::PUT, "/events/consumers", &body).await
+    }
+
+    /// Lists the aggregate views.
+    pub async fn views(&self) -> Result<Vec<AggregateView>, ClientError> {
+        self.get("/views", &[]).await
+    }
+
+    /// Creates an aggregate view over a table.
+    pub async fn create_view(&self, view: &AggregateView) -> Result<(), ClientError> {
+        self.ignore(Method::POST, "/views", view).await
+    }
+
+    /// Deletes an aggregate view.
+    pub async fn delete_view(&self, name: &str) -> Result<(), ClientError> {
+        self.ignore(Method::DELETE, "/views", &json!({"name": name}))
+            .await
+    }
+
+    /// Retrieves the aggregates of an aggregate view, optionally of the prefixes starting with
+    /// a string.
+    pub async fn view_groups(
+        &self,
+        name: &str,
+        prefix: Option<&str>,
+    ) -> Result<Vec<ViewGroup>, ClientError> {
+        let query: Vec<_> = prefix
+            .map(|prefix| ("prefix", prefix.to_string()))
+            .into_iter()
+            .collect();
+        self.get(&format!("/views/{}", name), &query).await
+    }
+
+    /// Lists the time series.
+    pub async fn time_series(&self) -> Result<Vec<TimeSeries>, ClientError> {
+        self.get("/series", &[]).await
+    }
+
+    /// Creates a time series.
+    pub async fn create_time_series(&self, series: &TimeSeries) -> Result<(), ClientError> {
+        self.ignore(Method::POST, "/series", series).await
+    }
+
+    /// Deletes a time series with its samples.
+    pub async fn delete_time_series(&self, series: &str) -> Result<(), ClientError> {
+        self.ignore(Method::DELETE, "/series", &json!({"series": series}))
+            .await
+    }
+
+    /// Records samples in a time series, returning their number.
+    pub async fn append_points(
+        &self,
+        series: &str,
+        points: &[SeriesPoint],
+    ) -> Result<usize, ClientError> {
+        let body = json!({"series": series, "points": points});
+        self.call(Method::POST, "/series/append", &body).await
+    }
+
+    /// Reads the raw samples of a key of a time series between two unix timestamps in
+    /// milliseconds.
+    pub async fn read_points(
+        &self,
+        series: &str,
+        key: &str,
+        from: i64,
+        to: Option<i64>,
+        limit: Option<i64>,
+    ) -> Result<Vec<SeriesPoint>, ClientError> {
+        let mut query = vec![
+            ("series", series.to_string()),
+            ("key", key.to_string()),
+            ("from", from.to_string()),
+        ];
+        query.extend(to.map(|to| ("to", to.to_string())));
+        query.extend(limit.map(|limit| ("limit", limit.to_string())));
+        self.get("/series/points", &query).await
+    }
+
+    /// Aggregates the samples of a key of a time series into buckets of a width in seconds.
+    pub async fn aggregate_points(
+        &self,
+        series: &str,
+        key: &str,
+        from: i64,
+        to: Option<i64>,
+        bucket_secs: i64,
+    ) -> Result<Vec<SeriesBucket>, ClientError> {
+        let mut query = vec![
+            ("series", series.to_string()),
+            ("key", key.to_string()),
+            ("from", from.to_string()),
+            ("bucket_secs", bucket_secs.to_string()),
+        ];
+        query.extend(to.map(|to| ("to", to.to_string())));
+        self.get("/series/aggregate", &query).await
+    }
+
+    /// Pushes the changes made offline, resolving conflicts with the given strategy.
+    pub async fn sync_push(
+        &self,
+        changes: &[ClientChange],
+        resolution: SyncResolution,
+    ) -> Result<Vec<SyncOutcome>, ClientError> {
+        let body = json!({"changes": changes, "resolution": resolution});
+        self.call(Method::POST, "/sync/push", &body).await
+    }
+
+    /// Pulls the changes made after a checkpoint, of one table or of all tables.
+    pub async fn sync_pull(
+        &self,
+        since: i64,
+        table: Option<&str>,
+        limit: i64,
+    ) -> Result<SyncChanges, ClientError> {
+        let mut query = vec![("since", since.to_string()), ("limit", limit.to_string())];
+        query.extend(table.map(|table| ("table", table.to_string())));
+        self.get("/sync/pull", &query).await
+    }
+
+    /// Lists the snapshots.
+    pub async fn snapshots(&self) -> Result<Vec<Snapshot>, ClientError> {
+        self.get("/snapshots", &[]).await
+    }
+
+    /// Takes a snapshot of the given tables, or of all tables if none are given.
+    pub async fn create_snapshot(&self, tables: &[&str]) -> Result<Snapshot, ClientError> {
+        self.call(Method::POST, "/snapshots", &json!({"tables": tables}))
+            .await
+    }
+
+    /// Deletes a snapshot.
+    pub async fn delete_snapshot(&self, id: i64) -> Result<(), ClientError> {
+        self.ignore(Method::DELETE, "/snapshots", &json!({"id": id}))
+            .await
+    }
+
+    /// Compares a snapshot against another.
+    pub async fn snapshot_diff(&self, id: i64, against: i64) -> Result<SnapshotDiff, ClientError> {
+        let path = format!("/snapshots/{}/diff", id);
+        self.get(&path, &[("against", against.to_string())]).await
+    }
+
+    /// Retrieves the recent changes, of a table or key if given, most recent first.
+    pub async fn activity(
+        &self,
+        table: Option<&str>,
+        key: Option<&str>,
+        offset: i64,
+        limit: i64,
+    ) -> Result<Vec<Activity>, ClientError> {
+        let mut query = vec![("offset", offset.to_string()), ("limit", limit.to_string())];
+        query.extend(table.map(|table| ("table", table.to_string())));
+        query.extend(key.map(|key| ("key", key.to_string())));
+        self.get("/activity", &query).await
+    }
+
+    /// Retrieves the comments on a key.
+    pub async fn comments(&self, table: &str, key: &str) -> Result<Vec<Comment>, ClientError> {
+        let query = [("table", table.to_string()), ("key", key.to_string())];
+        self.get("/comments", &query).await
+    }
+
+    /// Comments on a key, or replies to a comment.
+    pub async fn post_comment(
+        &self,
+        table: &str,
+        key: &str,
+        parent_id: Option<i64>,
+        author: &str,
+        body: &str,
+    ) -> Result<Comment, ClientError> {
+        let body = json!({
+            "table": table,
+            "key": key,
+            "parent_id": parent_id,
+            "author": author,
+            "body": body,
+        });
+        self.call(Method::POST, "/comments", &body).await
+    }
+
+    /// Replaces the text of a comment of the given author.
+    pub async fn edit_comment(
+        &self,
+        id: i64,
+        author: &str,
+        body: &str,
+    ) -> Result<Comment, ClientError> {
+        let body = json!({"id": id, "author": author, "body": body});
+        self.call(Method::PUT, "/comments", &body).await
+    }
+
+    /// Deletes a comment of the given author with its replies.
+    ///
+    /// # Returns
+    ///
+    /// * `usize` - The number of deleted comments.
+    pub async fn delete_comment(&self, id: i64, author: &str) -> Result<usize, ClientError> {
+        let body = json!({"id": id, "author": author});
+        self.call(Method::DELETE, "/comments", &body).await
+    }
+
+    /// Lists the projects.
+    pub async fn list_projects(&self) -> Result<Vec<Project>, ClientError> {
+        self.get("/projects", &[]).await
+    }
+
+    /// Creates a project, copying the template tables into it if asked to.
+    ///
+    /// # Returns
+    ///
+    /// * `Vec<String>` - The tables of the new project.
+    pub async fn create_project(
+        &self,
+        name: &str,
+        templates: bool,
+    ) -> Result<Vec<String>, ClientError> {
+        let body = json!({"name": name, "templates": templates});
+        self.call(Method::POST, "/projects", &body).await
+    }
+
+    /// Deletes a project with its tables.
+    ///
+    /// # Returns
+    ///
+    /// * `Vec<String>` - The deleted tables.
+    pub async fn delete_project(&self, name: &str) -> Result<Vec<String>, ClientError> {
+        self.call(Method::DELETE, "/projects", &json!({"name": name}))
+            .await
+    }
+
+    /// Lists the tables of a project.
+    pub async fn project_tables(&self, name: &str) -> Result<Vec<String>, ClientError> {
+        self.get("/projects/tables", &[("name", name.to_string())])
+            .await
+    }
+
+    /// Adds a table to a project.
+    pub async fn add_project_table(&self, project: &str, table: &str) -> Result<(), ClientError> {
+        let body = json!({"project": project, "table": table});
+        self.ignore(Method::POST, "/projects/tables", &body).await
+    }
+
+    /// Removes a table from a project.
+    pub async fn remove_project_table(
+        &self,
+        project: &str,
+        table: &str,
+    ) -> Result<(), ClientError> {
+        let body = json!({"project": project, "table": table});
+        self.ignore(Method::DELETE, "/projects/tables", &body).await
+    }
+
+    /// Registers a webhook notified of the changes of a table.
+    ///
+    /// # Returns
+    ///
+    /// * `i64` - The id of the webhook.
+    pub async fn register_webhook(
+        &self,
+        table: &str,
+        url: &str,
+        secret: &str,
+    ) -> Result<i64, ClientError> {
+        let body = json!({"table": table, "url": url, "secret": secret});
+        let id: Value = self.call(Method::POST, "/webhooks", &body).await?;
+        id["id"]
+            .as_i64()
+            .ok_or_else(|| ClientError::MissingData("/webhooks".to_string()))
+    }
+
+    /// Deletes a webhook.
+    pub async fn delete_webhook(&self, id: i64) -> Result<(), ClientError> {
+        self.ignore(Method::DELETE, "/webhooks", &json!({"id": id}))
+            .await
+    }
+
+    /// Answers the startup probe.
+    pub async fn startup_probe(&self) -> Result<ProbeResult, ClientError> {
+        self.get("/startupz", &[]).await
+    }
+
+    /// Answers the liveness probe.
+    pub async fn liveness_probe(&self) -> Result<ProbeResult, ClientError> {
+        self.get("/livez", &[]).await
+    }
+
+    /// Answers the readiness probe.
+    pub async fn readiness_probe(&self) -> Result<ProbeResult, ClientError> {
+        self.get("/readyz", &[]).await
+    }
+
+    /// Retrieves the log level of the server.
+    pub async fn log_level(&self) -> Result<String, ClientError> {
+        let level: Value = self.get("/admin/log_level", &[]).await?;
+        level["level"]
+            .as_str()
+            .map(str::to_string)
+            .ok_or_else(|| ClientError::MissingData("/admin/log_level".to_string()))
+    }
+
+    /// Changes the log level of the server until it restarts.
+    pub async fn set_log_level(&self, level: &str) -> Result<(), ClientError> {
+        self.ignore(Method::PUT, "/admin/log_level", &json!({"level": level}))
+            .await
+    }
+
+    /// Retrieves the configuration of the server, with its secrets redacted.
+    pub async fn config(&self) -> Result<Config, ClientError> {
+        self.get("/admin/config", &[]).await
+    }
+
+    /// Describes all tables.
+    pub async fn schema(&self) -> Result<Vec<TableInfo>, ClientError> {
+        self.get("/admin/schema", &[]).await
+    }
+
+    /// Retrieves the most, or least recently, accessed keys of a table.
+    pub async fn hot_keys(
+        &self,
+        table: &str,
+        stale: bool,
+        limit: i64,
+    ) -> Result<Vec<KeyStat>, ClientError> {
+        let query = [
+            ("table", table.to_string()),
+            ("stale", stale.to_string()),
+            ("limit", limit.to_string()),
+        ];
+        self.get("/stats/hot_keys", &query).await
+    }
+
+    /// Retrieves an overview of the health of the server.
+    pub async fn stats(&self) -> Result<AdminStats, ClientError> {
+        self.get("/admin/stats", &[]).await
+    }
+
+    /// Downloads the recorded requests and responses, oldest first.
+    pub async fn captures(&self) -> Result<Vec<Capture>, ClientError> {
+        Self::ndjson(self.send(Method::GET, "/admin/captures", &[], None).await?).await
+    }
+
+    /// Starts locating the data of a person, e.g. by user id and email address, to export or
+    /// anonymize it in the background.
+    pub async fn start_privacy_request(
+        &self,
+        identities: &[&str],
+        action: PrivacyAction,
+    ) -> Result<PrivacyReport, ClientError> {
+        let body = json!({"identities": identities, "action": action});
+        self.call(Method::POST, "/admin/privacy", &body).await
+    }
+
+    /// Retrieves the report of what a privacy request located and touched.
+    pub async fn privacy_report(&self, id: u64) -> Result<PrivacyReport, ClientError> {
+        self.get(&format!("/admin/privacy/{}", id), &[]).await
+    }
+
+    /// Downloads the archive of the data of a person collected by a completed export.
+    pub async fn privacy_export(&self, id: u64) -> Result<SubjectArchive, ClientError> {
+        let path = format!("/admin/privacy/{}/export", id);
+        let response = Self::checked(self.send(Method::GET, &path, &[], None).await?).await?;
+        Ok(serde_json::from_slice(&response.bytes().await?)?)
+    }
+
+    /// Discards the recorded requests and responses.
+    ///
+    /// # Returns
+    ///
+    /// * `usize` - The number of discarded exchanges.
+    pub async fn clear_captures(&self) -> Result<usize, ClientError> {
+        self.call(Method::DELETE, "/admin/captures", &json!({}))
+            .await
+    }
+
+    /// Retrieves the state of the connections of the pools of all shards and replicas.
+    pub async fn pool(&self) -> Result<Vec<PoolState>, ClientError> {
+        self.get("/admin/pool", &[]).await
+    }
+
+    /// Closes the idle connections of all pools, so fresh ones are opened on demand.
+    pub async fn recycle_pool(&self) -> Result<RecycledPools, ClientError> {
+        self.call(Method::POST, "/admin/pool/recycle", &json!({}))
+            .await
+    }
+
+    /// Retrieves the state of the runtime of the server.
+    pub async fn runtime(&self) -> Result<RuntimeStats, ClientError> {
+        self.get("/admin/runtime", &[]).await
+    }
+
+    /// Retrieves the most recently sent alerts of the answering instance, newest first.
+    pub async fn alerts(&self) -> Result<Vec<Alert>, ClientError> {
+        self.get("/admin/alerts", &[]).await
+    }
+
+    /// Runs a read-only statement against the primary through the admin SQL console.
+    ///
+    /// # Arguments
+    ///
+    /// * `sql` - A single `SELECT` or `EXPLAIN` statement.
+    /// * `max_rows` - The largest number of rows returned, the cap of the server if unset.
+    pub async fn run_sql(
+        &self,
+        sql: &str,
+        max_rows: Option<usize>,
+    ) -> Result<ConsoleResult, ClientError> {
+        let body = json!({"sql": sql, "max_rows": max_rows});
+        self.call(Method::POST, "/admin/sql", &body).await
+    }
+
+    /// Archives the keys not accessed for the configured number of days now.
+    pub async fn run_tiering(&self) -> Result<TieringReport, ClientError> {
+        self.call(Method::POST, "/admin/tiering", &json!({})).await
+    }
+
+    /// Retrieves the instances of the cluster and the one running the background jobs.
+    pub async fn cluster(&self) -> Result<ClusterState, ClientError> {
+        self.get("/admin/cluster", &[]).await
+    }
+
+    /// Retrieves the maintenance state of the server.
+    pub async fn maintenance(&self) -> Result<MaintenanceState, ClientError> {
+        self.get("/admin/maintenance", &[]).await
+    }
+
+    /// Changes the maintenance state of the server.
+    pub async fn set_maintenance(
+        &self,
+        state: &MaintenanceState,
+    ) -> Result<MaintenanceState, ClientError> {
+        self.call(Method::PUT, "/admin/maintenance", state).await
+    }
+
+    /// Retrieves the slowest recent queries, of a table if given.
+    pub async fn slow_queries(&self, table: Option<&str>) -> Result<Vec<SlowQuery>, ClientError> {
+        let query: Vec<_> = table
+            .map(|table| ("table", table.to_string()))
+            .into_iter()
+            .collect();
+        self.get("/admin/slow_queries", &query).await
+    }
+
+    /// Retrieves the indexes recommended by the index advisor.
+    pub async fn index_advisor(&self) -> Result<Vec<IndexRecommendation>, ClientError> {
+        self.get("/admin/index_advisor", &[]).await
+    }
+
+    /// Re-encrypts all values with the current encryption key.
+    pub async fn reencrypt(&self) -> Result<(), ClientError> {
+        self.ignore(Method::POST, "/admin/reencrypt", &json!({}))
+            .await
+    }
+
+    /// Lists the retention policies of the tables.
+    pub async fn table_policies(&self) -> Result<Vec<TablePolicy>, ClientError> {
+        self.get("/admin/table_policies", &[]).await
+    }
+
+    /// Sets the retention policy of a table.
+    pub async fn set_table_policy(&self, policy: &TablePolicy) -> Result<(), ClientError> {
+        self.ignore(Method::PUT, "/admin/table_policies", policy)
+            .await
+    }
+
+    /// Removes the retention policy of a table.
+    pub async fn delete_table_policy(&self, table: &str) -> Result<(), ClientError> {
+        let body = json!({"table": table});
+        self.ignore(Method::DELETE, "/admin/table_policies", &body)
+            .await
+    }
+
+    /// Lists the feature flags.
+    pub async fn feature_flags(&self) -> Result<Vec<FeatureFlag>, ClientError> {
+        self.get("/admin/flags", &[]).await
+    }
+
+    /// Creates or replaces a feature flag.
+    pub async fn set_feature_flag(&self, flag: &FeatureFlag) -> Result<(), ClientError> {
+        self.ignore(Method::PUT, "/admin/flags", flag).await
+    }
+
+    /// Removes a feature flag, turning it off.
+    pub async fn delete_feature_flag(&self, name: &str) -> Result<(), ClientError> {
+        let body = json!({"name": name});
+        self.ignore(Method::DELETE, "/admin/flags", &body).await
+    }
+
+    /// Returns whether a feature flag is on, for the client id the requests are signed with if
+    /// any, unknown flags being off.
+    pub async fn feature_enabled(&self, name: &str) -> Result<bool, ClientError> {
+        let state: FlagState = self.get("/flags", &[("name", name.to_string())]).await?;
+        Ok(state.enabled)
+    }
+
+    /// Lists the legal holds.
+    pub async fn legal_holds(&self) -> Result<Vec<LegalHold>, ClientError> {
+        self.get("/admin/holds", &[]).await
+    }
+
+    /// Places a legal hold on a table, blocking all writes to it and its deletion.
+    pub async fn place_legal_hold(&self, table: &str, reason: &str) -> Result<(), ClientError> {
+        let body = json!({"table": table, "reason": reason});
+        self.ignore(Method::POST, "/admin/holds", &body).await
+    }
+
+    /// Lifts the legal hold of a table, confirmed with the current one-time password of the
+    /// admin.
+    pub async fn lift_legal_hold(&self, table: &str, code: &str) -> Result<(), ClientError> {
+        let body = json!({"table": table, "code": code});
+        self.ignore(Method::DELETE, "/admin/holds", &body).await
+    }
+
+    /// Lists the integrations and the outcomes of their last polls.
+    pub async fn integrations(&self) -> Result<Vec<Integration>, ClientError> {
+        self.get("/admin/integrations", &[]).await
+    }
+
+    /// Registers or replaces an integration polling an external HTTP source into a table.
+    pub async fn put_integration(&self, integration: &Integration) -> Result<(), ClientError> {
+        self.ignore(Method::PUT, "/admin/integrations", integration)
+            .await
+    }
+
+    /// Removes an integration.
+    pub async fn delete_integration(&self, name: &str) -> Result<(), ClientError> {
+        let path = format!("/admin/integrations/{}", name);
+        self.ignore(Method::DELETE, &path, &json!({})).await
+    }
+
+    /// Makes an integration due, so it is polled at the next tick of the scheduler.
+    pub async fn run_integration(&self, name: &str) -> Result<(), ClientError> {
+        let path = format!("/admin/integrations/{}/run", name);
+        self.ignore(Method::POST, &path, &json!({})).await
+    }
+
+    /// Lists the JSON Schemas of the tables.
+    pub async fn json_schemas(&self) -> Result<Vec<JsonSchema>, ClientError> {
+        self.get("/admin/json_schemas", &[]).await
+    }
+
+    /// Attaches a JSON Schema to a table.
+    pub async fn set_json_schema(&self, schema: &JsonSchema) -> Result<(), ClientError> {
+        self.ignore(Method::PUT, "/admin/json_schemas", schema)
+            .await
+    }
+
+    /// Removes the JSON Schema of a table.
+    pub async fn delete_json_schema(&self, table: &str) -> Result<(), ClientError> {
+        let body = json!({"table": table});
+        self.ignore(Method::DELETE, "/admin/json_schemas", &body)
+            .await
+    }
+
+    /// Lists the regions of the server and the tables tagged with one.
+    pub async fn regions(&self) -> Result<Regions, ClientError> {
+        self.get("/admin/regions", &[]).await
+    }
+
+    /// Tags a table with a region, or untags it if `None`.
+    pub async fn set_table_region(
+        &self,
+        table: &str,
+        region: Option<&str>,
+    ) -> Result<(), ClientError> {
+        let body = json!({"table": table, "region": region});
+        self.ignore(Method::PUT, "/admin/regions", &body).await
+    }
+
+    /// Lists the transforms.
+    pub async fn transforms(&self) -> Result<Vec<Transform>, ClientError> {
+        self.get("/admin/transforms", &[]).await
+    }
+
+    /// Adds a transform.
+    ///
+    /// # Returns
+    ///
+    /// * `i64` - The id of the transform.
+    pub async fn add_transform(&self, transform: &NewTransform) -> Result<i64, ClientError> {
+        let id: Value = self
+            .call(Method::POST, "/admin/transforms", transform)
+            .await?;
+        id["id"]
+            .as_i64()
+            .ok_or_else(|| ClientError::MissingData("/admin/transforms".to_string()))
+    }
+
+    /// Deletes a transform.
+    pub async fn delete_transform(&self, id: i64) -> Result<(), ClientError> {
+        self.ignore(Method::DELETE, "/admin/transforms", &json!({"id": id}))
+            .await
+    }
+
+    /// Mints a URL granting read access to a single key until it expires.
+    pub async fn sign_url(
+        &self,
+        table: &str,
+        key: &str,
+        ttl_secs: Option<u64>,
+    ) -> Result<SignedUrl, ClientError> {
+        let body = json!({"table": table, "key": key, "ttl_secs": ttl_secs});
+        self.call(Method::POST, "/admin/sign_url", &body).await
+    }
+
+    /// Sends a `GET` request and returns the data of its response.
+    async fn get<T: DeserializeOwned>(
+        &self,
+        path: &str,
+        query: &[(&str, String)],
+    ) -> Result<T, ClientError> {
+        Self::required(self.request(Method::GET, path, query, None).await?)
+    }
+
+    /// Sends a request with a JSON body and returns the data of its response.
+    async fn call<T: DeserializeOwned>(
+        &self,
+        method: Method,
+        path: &str,
+        body: &impl Serialize,
+    ) -> Result<T, ClientError> {
+        Self::required(
+            self.request(method, path, &[], Some(Self::json(body)?))
+                .await?,
+        )
+    }
+
+    /// Sends a request with a JSON body and ignores the data of its response.
+    async fn ignore(
+        &self,
+        method: Method,
+        path: &str,
+        body: &impl Serialize,
+    ) -> Result<(), ClientError> {
+        self.request::<IgnoredAny>(method, path, &[], Some(Self::json(body)?))
+            .await
+            .map(|_| ())
+    }
+
+    /// Sends a request and decodes the data of its response.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the request fails, the server answers with an
+    /// error status or the response cannot be decoded.
+    async fn request<T: DeserializeOwned>(
+        &self,
+        method: Method,
+        path: &str,
+        query: &[(&str, String)],
+        body: Option<Body>,
+    ) -> Result<Option<T>, ClientError> {
+        let response = Self::checked(self.send(method, path, query, body).await?).await?;
+        let response: ApiResponse<T> = serde_json::from_slice(&response.bytes().await?)?;
+        Ok(response.data)
+    }
+
+    /// Sends a request, retrying it while it fails transiently and the budget lasts.
+    ///
+    /// # Errors
+    ///
+    /// This function will return the error of the last attempt if no response was received.
+    async fn send(
+        &self,
+        method: Method,
+        path: &str,
+        query: &[(&str, String)],
+        body: Option<Body>,
+    ) -> Result<Response, ClientError> {
+        let path = if query.is_empty() {
+            path.to_string()
+        } else {
+            format!("{}?{}", path, serde_urlencoded::to_string(query)?)
+        };
+        let mut attempt = 1;
+        loop {
+            let result = self.attempt(&method, &path, body.as_ref()).await;
+            let transient = match &result {
+                Ok(response) => matches!(
+                    response.status(),
+                    StatusCode::SERVICE_UNAVAILABLE | StatusCode::TOO_MANY_REQUESTS
+                ),
+                Err(e) => platform::unreachable(e) || (method.is_safe() && e.is_timeout()),
+            };
+            if !transient || attempt >= self.retry.max_attempts {
+                return Ok(result?);
+            }
+            let mut delay = result
+                .ok()
+                .and_then(|response| {
+                    response
+                        .headers()
+                        .get(RETRY_AFTER)?
+                        .to_str()
+                        .ok()?
+                        .parse()
+                        .ok()
+                })
+                .map(Duration::from_secs)
+                .unwrap_or_else(|| self.delay(attempt));
+            if self.signing.is_some() && !method.is_safe() {
+                // A signature is accepted once, so the retry must be signed in a later second.
+                delay = delay.max(Duration::from_secs(1));
+            }
+            log::warn!(
+                "{} {} failed transiently, retrying in {:?} (attempt {}/{})",
+                method,
+                path,
+                delay,
+                attempt,
+                self.retry.max_attempts
+            );
+            platform::sleep(delay).await;
+            attempt += 1;
+        }
+    }
+
+    /// Sends a request once, with the headers of the client.
+    async fn attempt(
+        &self,
+        method: &Method,
+        path: &str,
+        body: Option<&Body>,
+    ) -> Result<Response, reqwest::Error> {
+        let mut request = self
+            .http
+            .request(method.clone(), format!("{}{}", self.base_url, path));
+        if let Some(token) = &self.token {
+            request = request.bearer_auth(token);
+        }
+        if let Some(owner) = &self.lock_owner {
+            request = request.header(LOCK_OWNER_HEADER, owner);
+        }
+        if let Some(language) = &self.language {
+            request = request.header(ACCEPT_LANGUAGE, language);
+        }
+        if let Some(token) = self.consistency_token() {
+            request = request.header(CONSISTENCY_TOKEN_HEADER, token.to_string());
+        }
+        if let Some((client, secret)) = self.signing.as_ref().filter(|_| !method.is_safe()) {
+            let timestamp = platform::unix_secs();
+            let signature = SignedRequest {
+                method: method.as_str(),
+                path,
+                timestamp,
+                body: body.map(|(bytes, _)| bytes.as_slice()).unwrap_or_default(),
+            }
+            .sign(secret);
+            request = request
+                .header(CLIENT_HEADER, client)
+                .header(TIMESTAMP_HEADER, timestamp.to_string())
+                .header(REQUEST_SIGNATURE_HEADER, signature);
+        }
+        if let Some((bytes, content_type)) = body {
+            request = request
+                .header(CONTENT_TYPE, *content_type)
+                .body(bytes.clone());
+        }
+        let response = request.send().await?;
+        if let Some(token) = response
+            .headers()
+            .get(CONSISTENCY_TOKEN_HEADER)
+            .and_then(|v| v.to_str().ok()?.parse::<i64>().ok())
+        {
+            self.consistency.fetch_max(token, Ordering::Relaxed);
+        }
+        Ok(response)
+    }
+
+    /// Returns the jittered delay before the attempt following the given one.
+    fn delay(&self, attempt: u32) -> Duration {
+        let ceiling = self
+            .retry
+            .base_delay_ms
+            .saturating_mul(1 << attempt.saturating_sub(1).min(16))
+            .min(self.retry.max_delay_ms);
+        Duration::from_millis(platform::jitter() % (ceiling + 1))
+    }
+
+    /// Passes a response with a success status, turning any other into an error.
+    async fn checked(response: Response) -> Result<Response, ClientError> {
+        let status = response.status();
+        if status.is_success() {
+            return Ok(response);
+        }
+        let bytes = response.bytes().await?;
+        let (message, code, data) = match serde_json::from_slice::<ApiResponse<Value>>(&bytes) {
+            Ok(response) => (response.message, response.code, response.data),
+            Err(_) => (String::from_utf8_lossy(&bytes).into_owned(), None, None),
+        };
+        Err(ClientError::Api {
+            status: status.as_u16(),
+            message,
+            code,
+            data,
+        })
+    }
+
+    /// Decodes the rows of a newline-delimited JSON response.
+    async fn ndjson<T: DeserializeOwned>(response: Response) -> Result<Vec<T>, ClientError> {
+        let bytes = Self::checked(response).await?.bytes().await?;
+        bytes
+            .split(|byte| *byte == b'\n')
+            .filter(|line| !line.is_empty())
+            .map(|line| serde_json::from_slice(line).map_err(ClientError::from))
+            .collect()
+    }
+
+    /// Encodes a JSON body.
+    fn json(body: &impl Serialize) -> Result<Body, ClientError> {
+        Ok((serde_json::to_vec(body)?, "application/json"))
+    }
+
+    /// Unwraps the data of a response that must carry some.
+    fn required<T>(data: Option<T>) -> Result<T, ClientError> {
+        data.ok_or_else(|| ClientError::MissingData("the response has no data".to_string()))
+    }
+
+    /// Turns a `404` into `None`, for routes answering a missing key with it.
+    fn found<T>(result: Result<T, ClientError>) -> Result<Option<T>, ClientError> {
+        match result {
+            Ok(data) => Ok(Some(data)),
+            Err(e) if e.status() == Some(404) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+}
@@ -0,0 +1,72 @@
+use thiserror::Error;
+use xcloud_core::server::LEGAL_HOLD_CODE;
+
+/// Custom error type for the client.
+#[derive(Error, Debug)]
+pub enum ClientError {
+    #[error("HTTP error: {0}")]
+    Http(#[from] reqwest::Error),
+
+    #[error("JSON error: {0}")]
+    Json(#[from] serde_json::Error),
+
+    #[error("Query error: {0}")]
+    Query(#[from] serde_urlencoded::ser::Error),
+
+    /// The server answered with an error status.
+    #[error("API error {status}: {message}")]
+    Api {
+        status: u16,
+        message: String,
+        /// The stable code of the error, if the server has one for it.
+        code: Option<String>,
+        /// The data of the error response, e.g. the violations of a rejected value.
+        data: Option<serde_json::Value>,
+    },
+
+    /// The server answered with success but without the expected data.
+    #[error("Response without data: {0}")]
+    MissingData(String),
+}
+
+/// Implementation of the `ClientError` enum.
+impl ClientError {
+    /// Returns the status of the response if the server answered with an error.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<u16>` - The status code, or `None` if the request failed otherwise.
+    pub fn status(&self) -> Option<u16> {
+        match self {
+            ClientError::Api { status, .. } => Some(*status),
+            _ => None,
+        }
+    }
+
+    /// Returns the stable code of the error if the server answered with one.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<&str>` - The code, e.g. `table_not_found`, unlike the message independent of
+    ///   the language of the response.
+    pub fn code(&self) -> Option<&str> {
+        match self {
+            ClientError::Api { code, .. } => code.as_deref(),
+            _ => None,
+        }
+    }
+
+    /// Returns whether the server rejected a write because the table is under a legal hold.
+    ///
+    /// # Returns
+    ///
+    /// * `bool` - Whether the error is a legal hold rejection.
+    pub fn is_legal_hold(&self) -> bool {
+        match self {
+            ClientError::Api {
+                data: Some(data), ..
+            } => data["code"] == LEGAL_HOLD_CODE,
+            _ => false,
+        }
+    }
+}
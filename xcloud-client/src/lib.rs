@@ -0,0 +1,22 @@
+//! A typed async client of the xCLOUD API.
+//!
+//! The client sends the DTOs of `xcloud-core` and decodes the data of the responses into them,
+//! so it stays in step with the routes of the server. The replication and signed URL routes
+//! are left out, as they are called by peers and by holders of a signed URL respectively.
+//!
+//! On `wasm32` the client sends its requests through the `fetch` API of the browser, so web
+//! apps can share its types and errors with the server. Building it for `wasm32` still needs
+//! the DTOs of `xcloud-core` to be split from its server modules, which depend on SQLite and
+//! actix.
+
+pub mod client;
+pub mod errors;
+pub mod types;
+
+mod platform;
+
+#[cfg(test)]
+mod tests;
+
+pub use client::Client;
+pub use errors::ClientError;
@@ -0,0 +1,62 @@
+//! The parts of the client depending on the target, so the client also runs in browsers.
+//!
+//! On `wasm32` requests go through the `fetch` API of the browser, and timers and clocks
+//! through its JavaScript APIs, as neither threads nor the system clock are available there.
+
+use std::time::Duration;
+
+/// Waits for the given delay.
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) async fn sleep(delay: Duration) {
+    tokio::time::sleep(delay).await;
+}
+
+/// Waits for the given delay.
+#[cfg(target_arch = "wasm32")]
+pub(crate) async fn sleep(delay: Duration) {
+    gloo_timers::future::sleep(delay).await;
+}
+
+/// Returns the current unix timestamp in seconds.
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn unix_secs() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or_default()
+}
+
+/// Returns the current unix timestamp in seconds.
+#[cfg(target_arch = "wasm32")]
+pub(crate) fn unix_secs() -> u64 {
+    (js_sys::Date::now() / 1000.0) as u64
+}
+
+/// Returns a random number spreading the retries of many clients.
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn jitter() -> u64 {
+    use std::collections::hash_map::RandomState;
+    use std::hash::{BuildHasher, Hasher};
+
+    RandomState::new().build_hasher().finish()
+}
+
+/// Returns a random number spreading the retries of many clients.
+#[cfg(target_arch = "wasm32")]
+pub(crate) fn jitter() -> u64 {
+    (js_sys::Math::random() * u64::MAX as f64) as u64
+}
+
+/// Returns whether a request failed before reaching the server.
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn unreachable(e: &reqwest::Error) -> bool {
+    e.is_connect()
+}
+
+/// Returns whether a request failed before reaching the server.
+///
+/// The `fetch` API does not tell connection failures apart, so any failed request counts.
+#[cfg(target_arch = "wasm32")]
+pub(crate) fn unreachable(e: &reqwest::Error) -> bool {
+    e.is_request()
+}
@@ -0,0 +1,93 @@
+use actix_web::{App, HttpServer};
+use serde_json::json;
+use xcloud_core::config::{Config, RetryConfig, StorageBackend};
+use xcloud_core::logging::Logging;
+use xcloud_core::schema::TableSchema;
+use xcloud_core::{Database, Server};
+
+use crate::types::Patch;
+use crate::Client;
+
+/// Starts a server backed by an in-memory database on a free port.
+///
+/// # Returns
+///
+/// * `Client` - A client of the started server.
+async fn start() -> Client {
+    let mut config = Config::default();
+    config.database.backend = StorageBackend::Memory;
+    let db = Database::new(&config.database)
+        .await
+        .expect("Failed to open test database");
+    let logging = Logging::init(&config.logging);
+    let configure = Server::new(db, config, logging).configure();
+    let server = HttpServer::new(move || App::new().configure(configure.clone()))
+        .workers(1)
+        .bind(("127.0.0.1", 0))
+        .expect("Failed to bind test server");
+    let addr = server.addrs()[0];
+    actix_web::rt::spawn(server.run());
+    Client::new(&format!("http://{}", addr))
+}
+
+#[actix_web::test]
+async fn data_round_trip() {
+    let client = start().await;
+
+    client.set_data("items", "a", "1").await.unwrap();
+    assert_eq!(
+        client.get_data("items", "a").await.unwrap(),
+        Some(json!("1"))
+    );
+    assert_eq!(client.get_data("items", "b").await.unwrap(), None);
+
+    let key = client.append_data("items", "2").await.unwrap();
+    let many = client.get_many("items", &["a", &key, "c"]).await.unwrap();
+    assert_eq!(many.found.len(), 2);
+    assert_eq!(many.missing.len(), 1);
+
+    assert_eq!(client.increment("counters", "hits", 3).await.unwrap(), 3);
+    assert_eq!(client.list_push("queue", "q", "x", false).await.unwrap(), 1);
+    assert_eq!(
+        client.list_pop("queue", "q", true).await.unwrap(),
+        Some("x".to_string())
+    );
+    assert_eq!(client.list_pop("queue", "q", true).await.unwrap(), None);
+
+    let rows = client.stream_table("items").await.unwrap();
+    assert_eq!(rows.len(), 2);
+
+    client.delete_data("items", "a").await.unwrap();
+    assert_eq!(client.get_data("items", "a").await.unwrap(), None);
+    assert!(client
+        .list_tables()
+        .await
+        .unwrap()
+        .contains(&"items".to_string()));
+}
+
+#[actix_web::test]
+async fn typed_tables_and_errors() {
+    let client = start().await.with_retry(RetryConfig {
+        max_attempts: 1,
+        ..RetryConfig::default()
+    });
+
+    let schema: TableSchema =
+        serde_json::from_value(json!({"table": "docs", "value_type": "json"})).unwrap();
+    client.create_table(&schema).await.unwrap();
+    client.set_data("docs", "d", r#"{"a":1}"#).await.unwrap();
+    let patched = client
+        .patch_data("docs", "d", Patch::Merge(json!({"b": 2})))
+        .await
+        .unwrap();
+    assert_eq!(patched, Some(json!({"a": 1, "b": 2})));
+
+    let e = client.set_data("xcloud_locks", "k", "v").await.unwrap_err();
+    assert_eq!(e.status(), Some(400));
+    let e = client
+        .compare_and_set("docs", "d", Some("stale"), "{}", false)
+        .await
+        .unwrap_err();
+    assert_eq!(e.status(), Some(409));
+}
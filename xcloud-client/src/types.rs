@@ -0,0 +1,108 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use xcloud_core::concurrency::LaneStats;
+use xcloud_core::db::{PoolState, PoolStats, RemoteChange, TableRegion};
+use xcloud_core::patch::DeltaOp;
+use xcloud_core::stats::RequestCounts;
+
+/// A struct representing a key of a table.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct TableKey {
+    pub table: String,
+    pub key: String,
+}
+
+/// A struct representing a value found by `/get_many`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct FoundValue {
+    pub table: String,
+    pub key: String,
+    pub value: Value,
+}
+
+/// A struct representing the outcome of `/get_many`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct GetManyResult {
+    pub found: Vec<FoundValue>,
+    pub missing: Vec<TableKey>,
+}
+
+/// A struct representing a row of an exported or streamed table.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct StreamedRow {
+    pub key: String,
+    pub value: Value,
+}
+
+/// An enum representing the patch applied by `/patch_data`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Patch {
+    /// An RFC 6902 JSON Patch, as the array of its operations.
+    Json(Value),
+    /// An RFC 7386 JSON Merge Patch.
+    Merge(Value),
+    /// A text delta against the current value.
+    Delta(Vec<DeltaOp>),
+}
+
+/// A struct representing the events appended by `/events/append`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct AppendedEvents {
+    pub first_offset: i64,
+    pub count: usize,
+}
+
+/// A struct representing the changes pulled by `/sync/pull`.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SyncChanges {
+    pub changes: Vec<RemoteChange>,
+    /// The version to pull from next time.
+    pub checkpoint: i64,
+}
+
+/// A struct representing an overview of the health of the server.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct AdminStats {
+    pub uptime_secs: u64,
+    pub tables: usize,
+    pub keys: i64,
+    pub bytes: i64,
+    pub requests: RequestCounts,
+    pub pools: Vec<PoolStats>,
+    pub lanes: Vec<LaneStats>,
+}
+
+/// A struct representing the outcome of `/admin/pool/recycle`.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct RecycledPools {
+    /// The number of closed idle connections.
+    pub closed: usize,
+    pub pools: Vec<PoolState>,
+}
+
+/// A struct representing the regions of the server and the tables tagged with one.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Regions {
+    pub regions: Vec<String>,
+    pub tables: Vec<TableRegion>,
+}
+
+/// A struct representing a rule deriving the value of a key from a field of another key.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct NewTransform {
+    pub source_table: String,
+    pub source_key: String,
+    /// The RFC 6901 JSON Pointer of the field of the source value, empty for the whole value.
+    pub pointer: String,
+    pub target_table: String,
+    pub target_key: String,
+    pub allow_cross_region: bool,
+}
+
+/// A struct representing a URL granting read access to a single key.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct SignedUrl {
+    pub url: String,
+    /// The unix timestamp in seconds after which the URL is rejected.
+    pub expires: u64,
+}
@@ -0,0 +1,81 @@
+"use strict";
+
+const state = { table: null, key: null };
+
+async function api(path, options = {}) {
+  const response = await fetch(path, options);
+  const body = await response.json();
+  if (body.status !== "success") {
+    throw new Error(body.message);
+  }
+  return body.data;
+}
+
+function renderList(element, items, onSelect) {
+  element.replaceChildren(
+    ...items.map((item) => {
+      const li = document.createElement("li");
+      li.textContent = item;
+      li.addEventListener("click", () => {
+        element.querySelectorAll("li").forEach((el) => el.classList.remove("active"));
+        li.classList.add("active");
+        onSelect(item);
+      });
+      return li;
+    }),
+  );
+}
+
+function setStatus(message) {
+  document.getElementById("status").textContent = message;
+}
+
+async function loadTables() {
+  const tables = await api("/tables");
+  renderList(document.getElementById("tables"), tables, loadKeys);
+}
+
+async function loadKeys(table) {
+  state.table = table;
+  document.getElementById("current-table").textContent = table;
+  const keys = await api(`/keys?table=${encodeURIComponent(table)}`);
+  renderList(document.getElementById("keys"), keys, loadValue);
+}
+
+async function loadValue(key) {
+  state.key = key;
+  document.getElementById("current-key").textContent = key;
+  const params = new URLSearchParams({ table: state.table, key });
+  const value = await api(`/get_data?${params}`);
+  const textarea = document.getElementById("value");
+  textarea.value = typeof value === "string" ? value : JSON.stringify(value, null, 2);
+  textarea.disabled = false;
+  document.getElementById("save").disabled = false;
+  setStatus("");
+}
+
+async function saveValue() {
+  try {
+    await api("/update_data", {
+      method: "PUT",
+      headers: { "Content-Type": "application/json" },
+      body: JSON.stringify({
+        table: state.table,
+        key: state.key,
+        value: document.getElementById("value").value,
+      }),
+    });
+    setStatus("Saved.");
+  } catch (error) {
+    setStatus(error.message);
+  }
+}
+
+async function loadLogLevel() {
+  const { level } = await api("/admin/log_level");
+  document.getElementById("log-level").textContent = `Log level: ${level}`;
+}
+
+document.getElementById("save").addEventListener("click", saveValue);
+loadTables().catch((error) => setStatus(error.message));
+loadLogLevel().catch(() => {});
@@ -0,0 +1,208 @@
+# German translations of the error messages, keyed by the codes defined in en.ftl.
+
+a_key_cannot_be_derived_from_itself = Ein Schlüssel kann nicht von sich selbst abgeleitet werden
+a_key_filter_requires_a_table = Ein Schlüsselfilter erfordert eine Tabelle
+asset_not_found = Datei nicht gefunden
+at_least_one_event_must_be_appended = Es muss mindestens ein Ereignis angehängt werden
+at_least_one_identity_must_be_given = Es muss mindestens eine Identität angegeben werden
+at_least_one_sample_must_be_recorded = Es muss mindestens ein Messwert erfasst werden
+author_and_body_must_not_be_empty = Autor und Text dürfen nicht leer sein
+body_must_not_be_empty = Der Text darf nicht leer sein
+bucket_width_must_be_positive = Die Intervallbreite muss positiv sein
+comment_not_found = Kommentar nicht gefunden
+content_type_must_be_application_json = Der Inhaltstyp muss application/json sein
+copying_the_table_would_cross_regions = Das Kopieren der Tabelle würde Regionen überschreiten
+current_value_changed_while_merging = Der aktuelle Wert hat sich während des Zusammenführens geändert
+current_value_does_not_match_the_expected_value = Der aktuelle Wert entspricht nicht dem erwarteten Wert
+current_value_is_not_an_integer = Der aktuelle Wert ist keine ganze Zahl
+data_not_found = Daten nicht gefunden
+deriving_the_key_would_cross_regions = Das Ableiten des Schlüssels würde Regionen überschreiten
+event_log_already_exists = Das Ereignisprotokoll existiert bereits
+event_log_name_must_not_be_empty = Der Name des Ereignisprotokolls darf nicht leer sein
+event_log_not_found = Ereignisprotokoll nicht gefunden
+exactly_one_of_a_non_empty_prefix_or_a_list_of_keys_is_required = Genau eines von einem nicht leeren Präfix oder einer Schlüsselliste ist erforderlich
+exactly_one_of_a_patch_a_merge_patch_or_a_delta_is_required = Genau eines von einem Patch, einem Merge-Patch oder einem Delta ist erforderlich
+failed_to_acquire_lock = Die Sperre konnte nicht erworben werden
+failed_to_acquire_schema_lock = Die Schemasperre konnte nicht erworben werden
+failed_to_add_table_to_project = Die Tabelle konnte dem Projekt nicht hinzugefügt werden
+failed_to_add_template = Die Vorlage konnte nicht hinzugefügt werden
+failed_to_add_transform = Die Transformation konnte nicht hinzugefügt werden
+failed_to_aggregate_samples = Die Messwerte konnten nicht aggregiert werden
+failed_to_append_data = Die Daten konnten nicht angehängt werden
+failed_to_append_events = Die Ereignisse konnten nicht angehängt werden
+failed_to_apply_replicated_writes = Die replizierten Schreibvorgänge konnten nicht angewendet werden
+failed_to_archive_cold_keys = Die kalten Schlüssel konnten nicht archiviert werden
+failed_to_attach_json_schema = Das JSON-Schema konnte nicht angehängt werden
+failed_to_change_maintenance_mode = Der Wartungsmodus konnte nicht geändert werden
+failed_to_check_legal_hold = Die rechtliche Sperre konnte nicht geprüft werden
+failed_to_check_lock = Die Sperre konnte nicht geprüft werden
+failed_to_check_schema_lock = Die Schemasperre konnte nicht geprüft werden
+failed_to_clone_table = Die Tabelle konnte nicht geklont werden
+failed_to_collect_metrics = Die Metriken konnten nicht erfasst werden
+failed_to_commit_consumer_offset = Der Verbraucher-Offset konnte nicht gespeichert werden
+failed_to_compact_history = Der Verlauf konnte nicht verdichtet werden
+failed_to_compare_and_set_data = Die Daten konnten nicht verglichen und gesetzt werden
+failed_to_compare_snapshots = Die Snapshots konnten nicht verglichen werden
+failed_to_complete_dry_run = Der Probelauf konnte nicht abgeschlossen werden
+failed_to_create_event_log = Das Ereignisprotokoll konnte nicht erstellt werden
+failed_to_create_project = Das Projekt konnte nicht erstellt werden
+failed_to_create_snapshot = Der Snapshot konnte nicht erstellt werden
+failed_to_create_table = Die Tabelle konnte nicht erstellt werden
+failed_to_create_time_series = Die Zeitreihe konnte nicht erstellt werden
+failed_to_create_view = Die Ansicht konnte nicht erstellt werden
+failed_to_delete_comment = Der Kommentar konnte nicht gelöscht werden
+failed_to_delete_data = Die Daten konnten nicht gelöscht werden
+failed_to_delete_event_log = Das Ereignisprotokoll konnte nicht gelöscht werden
+failed_to_delete_project = Das Projekt konnte nicht gelöscht werden
+failed_to_delete_snapshot = Der Snapshot konnte nicht gelöscht werden
+failed_to_delete_table = Die Tabelle konnte nicht gelöscht werden
+failed_to_delete_time_series = Die Zeitreihe konnte nicht gelöscht werden
+failed_to_delete_view = Die Ansicht konnte nicht gelöscht werden
+failed_to_delete_webhook = Der Webhook konnte nicht gelöscht werden
+failed_to_detach_json_schema = Das JSON-Schema konnte nicht entfernt werden
+failed_to_edit_comment = Der Kommentar konnte nicht bearbeitet werden
+failed_to_export_table = Die Tabelle konnte nicht exportiert werden
+failed_to_import_table = Die Tabelle konnte nicht importiert werden
+failed_to_increment_data = Die Daten konnten nicht erhöht werden
+failed_to_lift_legal_hold = Die rechtliche Sperre konnte nicht aufgehoben werden
+failed_to_list_comments = Die Kommentare konnten nicht aufgelistet werden
+failed_to_list_event_logs = Die Ereignisprotokolle konnten nicht aufgelistet werden
+failed_to_list_keys = Die Schlüssel konnten nicht aufgelistet werden
+failed_to_list_project_tables = Die Projekttabellen konnten nicht aufgelistet werden
+failed_to_list_projects = Die Projekte konnten nicht aufgelistet werden
+failed_to_list_snapshots = Die Snapshots konnten nicht aufgelistet werden
+failed_to_list_tables = Die Tabellen konnten nicht aufgelistet werden
+failed_to_list_templates = Die Vorlagen konnten nicht aufgelistet werden
+failed_to_list_tree = Der Baum konnte nicht aufgelistet werden
+failed_to_merge_data = Die Daten konnten nicht zusammengeführt werden
+failed_to_patch_data = Die Daten konnten nicht gepatcht werden
+failed_to_place_legal_hold = Die rechtliche Sperre konnte nicht gesetzt werden
+failed_to_pop_value = Der Wert konnte nicht entnommen werden
+failed_to_post_comment = Der Kommentar konnte nicht veröffentlicht werden
+failed_to_pull_changes = Die Änderungen konnten nicht abgerufen werden
+failed_to_push_changes = Die Änderungen konnten nicht übertragen werden
+failed_to_push_value = Der Wert konnte nicht hinzugefügt werden
+failed_to_read_events = Die Ereignisse konnten nicht gelesen werden
+failed_to_read_import = Der Import konnte nicht gelesen werden: { $reason }
+failed_to_read_samples = Die Messwerte konnten nicht gelesen werden
+failed_to_recommend_indexes = Es konnten keine Indizes empfohlen werden
+failed_to_record_samples = Die Messwerte konnten nicht erfasst werden
+failed_to_register_integration = Die Integration konnte nicht registriert werden
+failed_to_register_webhook = Der Webhook konnte nicht registriert werden
+failed_to_release_lock = Die Sperre konnte nicht freigegeben werden
+failed_to_remove_feature_flag = Das Feature-Flag konnte nicht entfernt werden
+failed_to_remove_integration = Die Integration konnte nicht entfernt werden
+failed_to_remove_table_from_project = Die Tabelle konnte nicht aus dem Projekt entfernt werden
+failed_to_remove_table_policy = Die Tabellenrichtlinie konnte nicht entfernt werden
+failed_to_remove_template = Die Vorlage konnte nicht entfernt werden
+failed_to_remove_transform = Die Transformation konnte nicht entfernt werden
+failed_to_retrieve_activity = Die Aktivität konnte nicht abgerufen werden
+failed_to_retrieve_cluster_members = Die Clustermitglieder konnten nicht abgerufen werden
+failed_to_retrieve_consumer_offsets = Die Verbraucher-Offsets konnten nicht abgerufen werden
+failed_to_retrieve_data = Die Daten konnten nicht abgerufen werden
+failed_to_retrieve_history = Der Verlauf konnte nicht abgerufen werden
+failed_to_retrieve_integrations = Die Integrationen konnten nicht abgerufen werden
+failed_to_retrieve_json_schemas = Die JSON-Schemas konnten nicht abgerufen werden
+failed_to_retrieve_key_statistics = Die Schlüsselstatistiken konnten nicht abgerufen werden
+failed_to_retrieve_legal_holds = Die rechtlichen Sperren konnten nicht abgerufen werden
+failed_to_retrieve_list = Die Liste konnte nicht abgerufen werden
+failed_to_retrieve_lock = Die Sperre konnte nicht abgerufen werden
+failed_to_retrieve_schema = Das Schema konnte nicht abgerufen werden
+failed_to_retrieve_statistics = Die Statistiken konnten nicht abgerufen werden
+failed_to_retrieve_table_policies = Die Tabellenrichtlinien konnten nicht abgerufen werden
+failed_to_retrieve_time_series = Die Zeitreihen konnten nicht abgerufen werden
+failed_to_retrieve_transforms = Die Transformationen konnten nicht abgerufen werden
+failed_to_retrieve_view = Die Ansicht konnte nicht abgerufen werden
+failed_to_retrieve_views = Die Ansichten konnten nicht abgerufen werden
+failed_to_run_statement = Die Anweisung konnte nicht ausgeführt werden
+failed_to_schedule_integration = Die Integration konnte nicht eingeplant werden
+failed_to_search_data = Die Daten konnten nicht durchsucht werden
+failed_to_set_data = Die Daten konnten nicht gesetzt werden
+failed_to_set_feature_flag = Das Feature-Flag konnte nicht gesetzt werden
+failed_to_set_table_policy = Die Tabellenrichtlinie konnte nicht gesetzt werden
+failed_to_set_table_region = Die Tabellenregion konnte nicht gesetzt werden
+failed_to_stream_table = Die Tabelle konnte nicht gestreamt werden
+failed_to_update_data = Die Daten konnten nicht aktualisiert werden
+feature_flag_not_found = Feature-Flag nicht gefunden
+full_text_search_is_unavailable_while_values_are_compressed_deduplicated_or_archived = Die Volltextsuche ist nicht verfügbar, solange Werte komprimiert, dedupliziert oder archiviert werden
+full_text_search_is_unavailable_while_values_are_encrypted = Die Volltextsuche ist nicht verfügbar, solange Werte verschlüsselt sind
+group_must_not_be_empty_and_offset_must_not_be_negative = Die Gruppe darf nicht leer und der Offset nicht negativ sein
+import_not_found = Import nicht gefunden
+integration_not_found = Integration nicht gefunden
+interval_must_be_positive = Das Intervall muss positiv sein
+invalid_batch = Ungültiger Stapel: { $reason }
+invalid_delimiter = Ungültiges Trennzeichen: { $delimiter }
+invalid_fields = Ungültige Felder: { $reason }
+invalid_json_schema = Ungültiges JSON-Schema: { $reason }
+invalid_log_level = Ungültige Protokollstufe: { $reason }
+invalid_or_expired_signature = Ungültige oder abgelaufene Signatur
+invalid_request_payload = Ungültiger Anfrageinhalt
+invalid_request_signature = Ungültige Anfragesignatur
+invalid_second_factor_code = Ungültiger Code des zweiten Faktors
+invalid_signature = Ungültige Signatur
+json_pointers_must_be_empty_or_start_with = JSON-Pointer müssen leer sein oder mit '/' beginnen
+key_is_locked = Der Schlüssel ist von { $owner } gesperrt
+key_is_not_locked = Der Schlüssel ist nicht gesperrt
+keys_of_the_table_collide_without_case_sensitivity = Schlüssel der Tabelle kollidieren ohne Unterscheidung der Groß- und Kleinschreibung
+legal_hold = Die Tabelle steht unter rechtlicher Sperre: { $reason }
+lifting_legal_holds_requires_a_second_factor_which_is_not_configured = Das Aufheben rechtlicher Sperren erfordert einen zweiten Faktor, der nicht konfiguriert ist
+list_is_empty = Die Liste ist leer
+lock_is_not_held_by_this_owner = Die Sperre wird nicht von diesem Besitzer gehalten
+name_and_table_must_not_be_empty = Name und Tabelle dürfen nicht leer sein
+no_compaction_age_is_configured_for_the_table = Für die Tabelle ist kein Verdichtungsalter konfiguriert
+not_alive = Nicht aktiv
+not_ready = Nicht bereit
+not_started = Nicht gestartet
+parent_comment_not_found_on_this_key = Übergeordneter Kommentar zu diesem Schlüssel nicht gefunden
+patch_cannot_be_applied = Der Patch kann nicht angewendet werden: { $reason }
+pointer_must_be_empty_or_start_with = Der Pointer muss leer sein oder mit '/' beginnen
+privacy_request_is_not_a_completed_export = Die Datenschutzanfrage ist kein abgeschlossener Export
+privacy_request_not_found = Datenschutzanfrage nicht gefunden
+project_already_exists = Das Projekt existiert bereits
+project_name_must_not_be_empty = Der Projektname darf nicht leer sein
+project_not_found = Projekt nicht gefunden
+region_is_not_configured = Die Region ist nicht konfiguriert
+replication_is_not_configured = Die Replikation ist nicht konfiguriert
+request_body_is_too_large = Der Anfrageinhalt ist zu groß
+request_payload_is_too_large = Die Nutzdaten der Anfrage sind zu groß
+request_signature_is_missing = Die Signatur der Anfrage fehlt
+request_timestamp_is_outside_the_allowed_window = Der Zeitstempel der Anfrage liegt außerhalb des zulässigen Zeitfensters
+request_was_already_received = Die Anfrage wurde bereits empfangen
+retention_limits_must_not_be_negative = Aufbewahrungsgrenzen dürfen nicht negativ sein
+sample_values_must_be_finite = Messwerte müssen endlich sein
+signed_urls_are_not_configured = Signierte URLs sind nicht konfiguriert
+snapshot_not_found = Snapshot nicht gefunden
+source_table_does_not_exist_or_target_table_already_exists = Die Quelltabelle existiert nicht oder die Zieltabelle existiert bereits
+table_already_exists_its_region_cannot_change = Die Tabelle existiert bereits, ihre Region kann nicht geändert werden
+table_does_not_belong_to_the_project = Die Tabelle gehört nicht zum Projekt
+table_does_not_hold_integer_values = Die Tabelle enthält keine ganzzahligen Werte
+table_has_no_json_schema = Die Tabelle hat kein JSON-Schema
+table_has_no_policy = Die Tabelle hat keine Richtlinie
+table_is_already_under_legal_hold = Die Tabelle steht bereits unter rechtlicher Sperre
+table_is_locked = Die Tabelle ist durch einen { $operation }-Vorgang gesperrt
+table_is_not_a_template = Die Tabelle ist keine Vorlage
+table_is_not_under_legal_hold = Die Tabelle steht nicht unter rechtlicher Sperre
+table_name_must_not_be_empty = Der Tabellenname darf nicht leer sein
+table_not_found = Tabelle nicht gefunden
+target_key_is_already_derived = Der Zielschlüssel ist bereits abgeleitet
+target_table_name_must_not_be_empty = Der Name der Zieltabelle darf nicht leer sein
+the_database_is_temporarily_unavailable = Die Datenbank ist vorübergehend nicht verfügbar
+the_endpoint_is_not_available = Der Endpunkt ist nicht verfügbar
+the_key_column_is_required = Die Schlüsselspalte ist erforderlich
+the_server_is_in_maintenance_mode = Der Server befindet sich im Wartungsmodus
+the_sql_console_is_disabled = Die SQL-Konsole ist deaktiviert
+time_series_already_exists = Die Zeitreihe existiert bereits
+time_series_name_must_not_be_empty = Der Name der Zeitreihe darf nicht leer sein
+time_series_not_found = Zeitreihe nicht gefunden
+too_many_concurrent_requests = Zu viele gleichzeitige Anfragen
+transform_not_found = Transformation nicht gefunden
+unknown_client = Unbekannter Client
+url_must_use_http_or_https = Die URL muss http oder https verwenden
+value_does_not_match_the_table_schema = Der Wert entspricht nicht dem Tabellenschema: { $violations }
+value_is_not_a_boolean = Der Wert des Schlüssels { $key } ist kein Wahrheitswert
+value_is_not_a_number = Der Wert des Schlüssels { $key } ist keine Zahl
+value_is_not_an_integer = Der Wert des Schlüssels { $key } ist keine ganze Zahl
+values_cannot_be_merged = Die Werte können nicht zusammengeführt werden: { $reason }
+view_already_exists = Die Ansicht existiert bereits
+view_and_table_names_must_not_be_empty = Ansichts- und Tabellennamen dürfen nicht leer sein
+view_not_found = Ansicht nicht gefunden
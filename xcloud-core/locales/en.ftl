@@ -0,0 +1,211 @@
+# The messages of the errors returned by the server, keyed by their error code.
+#
+# The codes are stable and returned with every error response, whose message is built from
+# this file or its translations. Variables stand for the details the server fills in.
+
+a_key_cannot_be_derived_from_itself = A key cannot be derived from itself
+a_key_filter_requires_a_table = A key filter requires a table
+asset_not_found = Asset not found
+at_least_one_event_must_be_appended = At least one event must be appended
+at_least_one_identity_must_be_given = At least one identity must be given
+at_least_one_sample_must_be_recorded = At least one sample must be recorded
+author_and_body_must_not_be_empty = Author and body must not be empty
+body_must_not_be_empty = Body must not be empty
+bucket_width_must_be_positive = Bucket width must be positive
+comment_not_found = Comment not found
+content_type_must_be_application_json = Content type must be application/json
+copying_the_table_would_cross_regions = Copying the table would cross regions
+current_value_changed_while_merging = Current value changed while merging
+current_value_does_not_match_the_expected_value = Current value does not match the expected value
+current_value_is_not_an_integer = Current value is not an integer
+data_not_found = Data not found
+deriving_the_key_would_cross_regions = Deriving the key would cross regions
+event_log_already_exists = Event log already exists
+event_log_name_must_not_be_empty = Event log name must not be empty
+event_log_not_found = Event log not found
+exactly_one_of_a_non_empty_prefix_or_a_list_of_keys_is_required = Exactly one of a non-empty prefix or a list of keys is required
+exactly_one_of_a_patch_a_merge_patch_or_a_delta_is_required = Exactly one of a patch, a merge patch or a delta is required
+failed_to_acquire_lock = Failed to acquire lock
+failed_to_acquire_schema_lock = Failed to acquire schema lock
+failed_to_add_table_to_project = Failed to add table to project
+failed_to_add_template = Failed to add template
+failed_to_add_transform = Failed to add transform
+failed_to_aggregate_samples = Failed to aggregate samples
+failed_to_append_data = Failed to append data
+failed_to_append_events = Failed to append events
+failed_to_apply_replicated_writes = Failed to apply replicated writes
+failed_to_archive_cold_keys = Failed to archive cold keys
+failed_to_attach_json_schema = Failed to attach JSON Schema
+failed_to_change_maintenance_mode = Failed to change maintenance mode
+failed_to_check_legal_hold = Failed to check legal hold
+failed_to_check_lock = Failed to check lock
+failed_to_check_schema_lock = Failed to check schema lock
+failed_to_clone_table = Failed to clone table
+failed_to_collect_metrics = Failed to collect metrics
+failed_to_commit_consumer_offset = Failed to commit consumer offset
+failed_to_compact_history = Failed to compact history
+failed_to_compare_and_set_data = Failed to compare and set data
+failed_to_compare_snapshots = Failed to compare snapshots
+failed_to_complete_dry_run = Failed to complete dry run
+failed_to_create_event_log = Failed to create event log
+failed_to_create_project = Failed to create project
+failed_to_create_snapshot = Failed to create snapshot
+failed_to_create_table = Failed to create table
+failed_to_create_time_series = Failed to create time series
+failed_to_create_view = Failed to create view
+failed_to_delete_comment = Failed to delete comment
+failed_to_delete_data = Failed to delete data
+failed_to_delete_event_log = Failed to delete event log
+failed_to_delete_project = Failed to delete project
+failed_to_delete_snapshot = Failed to delete snapshot
+failed_to_delete_table = Failed to delete table
+failed_to_delete_time_series = Failed to delete time series
+failed_to_delete_view = Failed to delete view
+failed_to_delete_webhook = Failed to delete webhook
+failed_to_detach_json_schema = Failed to detach JSON Schema
+failed_to_edit_comment = Failed to edit comment
+failed_to_export_table = Failed to export table
+failed_to_import_table = Failed to import table
+failed_to_increment_data = Failed to increment data
+failed_to_lift_legal_hold = Failed to lift legal hold
+failed_to_list_comments = Failed to list comments
+failed_to_list_event_logs = Failed to list event logs
+failed_to_list_keys = Failed to list keys
+failed_to_list_project_tables = Failed to list project tables
+failed_to_list_projects = Failed to list projects
+failed_to_list_snapshots = Failed to list snapshots
+failed_to_list_tables = Failed to list tables
+failed_to_list_templates = Failed to list templates
+failed_to_list_tree = Failed to list tree
+failed_to_merge_data = Failed to merge data
+failed_to_patch_data = Failed to patch data
+failed_to_place_legal_hold = Failed to place legal hold
+failed_to_pop_value = Failed to pop value
+failed_to_post_comment = Failed to post comment
+failed_to_pull_changes = Failed to pull changes
+failed_to_push_changes = Failed to push changes
+failed_to_push_value = Failed to push value
+failed_to_read_events = Failed to read events
+failed_to_read_import = Failed to read import: { $reason }
+failed_to_read_samples = Failed to read samples
+failed_to_recommend_indexes = Failed to recommend indexes
+failed_to_record_samples = Failed to record samples
+failed_to_register_integration = Failed to register integration
+failed_to_register_webhook = Failed to register webhook
+failed_to_release_lock = Failed to release lock
+failed_to_remove_feature_flag = Failed to remove feature flag
+failed_to_remove_integration = Failed to remove integration
+failed_to_remove_table_from_project = Failed to remove table from project
+failed_to_remove_table_policy = Failed to remove table policy
+failed_to_remove_template = Failed to remove template
+failed_to_remove_transform = Failed to remove transform
+failed_to_retrieve_activity = Failed to retrieve activity
+failed_to_retrieve_cluster_members = Failed to retrieve cluster members
+failed_to_retrieve_consumer_offsets = Failed to retrieve consumer offsets
+failed_to_retrieve_data = Failed to retrieve data
+failed_to_retrieve_history = Failed to retrieve history
+failed_to_retrieve_integrations = Failed to retrieve integrations
+failed_to_retrieve_json_schemas = Failed to retrieve JSON Schemas
+failed_to_retrieve_key_statistics = Failed to retrieve key statistics
+failed_to_retrieve_legal_holds = Failed to retrieve legal holds
+failed_to_retrieve_list = Failed to retrieve list
+failed_to_retrieve_lock = Failed to retrieve lock
+failed_to_retrieve_schema = Failed to retrieve schema
+failed_to_retrieve_statistics = Failed to retrieve statistics
+failed_to_retrieve_table_policies = Failed to retrieve table policies
+failed_to_retrieve_time_series = Failed to retrieve time series
+failed_to_retrieve_transforms = Failed to retrieve transforms
+failed_to_retrieve_view = Failed to retrieve view
+failed_to_retrieve_views = Failed to retrieve views
+failed_to_run_statement = Failed to run statement
+failed_to_schedule_integration = Failed to schedule integration
+failed_to_search_data = Failed to search data
+failed_to_set_data = Failed to set data
+failed_to_set_feature_flag = Failed to set feature flag
+failed_to_set_table_policy = Failed to set table policy
+failed_to_set_table_region = Failed to set table region
+failed_to_stream_table = Failed to stream table
+failed_to_update_data = Failed to update data
+feature_flag_not_found = Feature flag not found
+full_text_search_is_unavailable_while_values_are_compressed_deduplicated_or_archived = Full-text search is unavailable while values are compressed, deduplicated or archived
+full_text_search_is_unavailable_while_values_are_encrypted = Full-text search is unavailable while values are encrypted
+group_must_not_be_empty_and_offset_must_not_be_negative = Group must not be empty and offset must not be negative
+import_not_found = Import not found
+integration_not_found = Integration not found
+interval_must_be_positive = Interval must be positive
+invalid_batch = Invalid batch: { $reason }
+invalid_delimiter = Invalid delimiter: { $delimiter }
+invalid_fields = Invalid fields: { $reason }
+invalid_json_schema = Invalid JSON Schema: { $reason }
+invalid_log_level = Invalid log level: { $reason }
+invalid_or_expired_signature = Invalid or expired signature
+invalid_request_payload = Invalid request payload
+invalid_request_signature = Invalid request signature
+invalid_second_factor_code = Invalid second-factor code
+invalid_signature = Invalid signature
+json_pointers_must_be_empty_or_start_with = JSON Pointers must be empty or start with '/'
+key_is_locked = Key is locked by { $owner }
+key_is_not_locked = Key is not locked
+keys_of_the_table_collide_without_case_sensitivity = Keys of the table collide without case sensitivity
+legal_hold = Table is under legal hold: { $reason }
+lifting_legal_holds_requires_a_second_factor_which_is_not_configured = Lifting legal holds requires a second factor, which is not configured
+list_is_empty = List is empty
+lock_is_not_held_by_this_owner = Lock is not held by this owner
+name_and_table_must_not_be_empty = Name and table must not be empty
+no_compaction_age_is_configured_for_the_table = No compaction age is configured for the table
+not_alive = Not alive
+not_ready = Not ready
+not_started = Not started
+parent_comment_not_found_on_this_key = Parent comment not found on this key
+patch_cannot_be_applied = Patch cannot be applied: { $reason }
+pointer_must_be_empty_or_start_with = Pointer must be empty or start with '/'
+privacy_request_is_not_a_completed_export = Privacy request is not a completed export
+privacy_request_not_found = Privacy request not found
+project_already_exists = Project already exists
+project_name_must_not_be_empty = Project name must not be empty
+project_not_found = Project not found
+region_is_not_configured = Region is not configured
+replication_is_not_configured = Replication is not configured
+request_body_is_too_large = Request body is too large
+request_payload_is_too_large = Request payload is too large
+request_signature_is_missing = Request signature is missing
+request_timestamp_is_outside_the_allowed_window = Request timestamp is outside the allowed window
+request_was_already_received = Request was already received
+retention_limits_must_not_be_negative = Retention limits must not be negative
+sample_values_must_be_finite = Sample values must be finite
+signed_urls_are_not_configured = Signed URLs are not configured
+snapshot_not_found = Snapshot not found
+source_table_does_not_exist_or_target_table_already_exists = Source table does not exist or target table already exists
+table_already_exists_its_region_cannot_change = Table already exists, its region cannot change
+table_does_not_belong_to_the_project = Table does not belong to the project
+table_does_not_hold_integer_values = Table does not hold integer values
+table_has_no_json_schema = Table has no JSON Schema
+table_has_no_policy = Table has no policy
+table_is_already_under_legal_hold = Table is already under legal hold
+table_is_locked = Table is locked by a { $operation } operation
+table_is_not_a_template = Table is not a template
+table_is_not_under_legal_hold = Table is not under legal hold
+table_name_must_not_be_empty = Table name must not be empty
+table_not_found = Table not found
+target_key_is_already_derived = Target key is already derived
+target_table_name_must_not_be_empty = Target table name must not be empty
+the_database_is_temporarily_unavailable = The database is temporarily unavailable
+the_endpoint_is_not_available = The endpoint is not available
+the_key_column_is_required = The key column is required
+the_server_is_in_maintenance_mode = The server is in maintenance mode
+the_sql_console_is_disabled = The SQL console is disabled
+time_series_already_exists = Time series already exists
+time_series_name_must_not_be_empty = Time series name must not be empty
+time_series_not_found = Time series not found
+too_many_concurrent_requests = Too many concurrent requests
+transform_not_found = Transform not found
+unknown_client = Unknown client
+url_must_use_http_or_https = URL must use http or https
+value_does_not_match_the_table_schema = Value does not match the table schema: { $violations }
+value_is_not_a_boolean = Value of key { $key } is not a boolean
+value_is_not_a_number = Value of key { $key } is not a number
+value_is_not_an_integer = Value of key { $key } is not an integer
+values_cannot_be_merged = Values cannot be merged: { $reason }
+view_already_exists = View already exists
+view_and_table_names_must_not_be_empty = View and table names must not be empty
+view_not_found = View not found
@@ -0,0 +1,208 @@
+# French translations of the error messages, keyed by the codes defined in en.ftl.
+
+a_key_cannot_be_derived_from_itself = Une clé ne peut pas être dérivée d'elle-même
+a_key_filter_requires_a_table = Un filtre de clés nécessite une table
+asset_not_found = Fichier introuvable
+at_least_one_event_must_be_appended = Au moins un événement doit être ajouté
+at_least_one_identity_must_be_given = Au moins une identité doit être indiquée
+at_least_one_sample_must_be_recorded = Au moins une mesure doit être enregistrée
+author_and_body_must_not_be_empty = L'auteur et le texte ne doivent pas être vides
+body_must_not_be_empty = Le texte ne doit pas être vide
+bucket_width_must_be_positive = La largeur d'intervalle doit être positive
+comment_not_found = Commentaire introuvable
+content_type_must_be_application_json = Le type de contenu doit être application/json
+copying_the_table_would_cross_regions = La copie de la table franchirait des régions
+current_value_changed_while_merging = La valeur actuelle a changé pendant la fusion
+current_value_does_not_match_the_expected_value = La valeur actuelle ne correspond pas à la valeur attendue
+current_value_is_not_an_integer = La valeur actuelle n'est pas un entier
+data_not_found = Données introuvables
+deriving_the_key_would_cross_regions = La dérivation de la clé franchirait des régions
+event_log_already_exists = Le journal d'événements existe déjà
+event_log_name_must_not_be_empty = Le nom du journal d'événements ne doit pas être vide
+event_log_not_found = Journal d'événements introuvable
+exactly_one_of_a_non_empty_prefix_or_a_list_of_keys_is_required = Exactement un préfixe non vide ou une liste de clés est requis
+exactly_one_of_a_patch_a_merge_patch_or_a_delta_is_required = Exactement un patch, un patch de fusion ou un delta est requis
+failed_to_acquire_lock = Impossible d'acquérir le verrou
+failed_to_acquire_schema_lock = Impossible d'acquérir le verrou du schéma
+failed_to_add_table_to_project = Impossible d'ajouter la table au projet
+failed_to_add_template = Impossible d'ajouter le modèle
+failed_to_add_transform = Impossible d'ajouter la transformation
+failed_to_aggregate_samples = Impossible d'agréger les mesures
+failed_to_append_data = Impossible d'ajouter les données
+failed_to_append_events = Impossible d'ajouter les événements
+failed_to_apply_replicated_writes = Impossible d'appliquer les écritures répliquées
+failed_to_archive_cold_keys = Impossible d'archiver les clés froides
+failed_to_attach_json_schema = Impossible d'attacher le schéma JSON
+failed_to_change_maintenance_mode = Impossible de changer le mode maintenance
+failed_to_check_legal_hold = Impossible de vérifier la conservation légale
+failed_to_check_lock = Impossible de vérifier le verrou
+failed_to_check_schema_lock = Impossible de vérifier le verrou du schéma
+failed_to_clone_table = Impossible de cloner la table
+failed_to_collect_metrics = Impossible de collecter les métriques
+failed_to_commit_consumer_offset = Impossible d'enregistrer la position du consommateur
+failed_to_compact_history = Impossible de compacter l'historique
+failed_to_compare_and_set_data = Impossible de comparer et définir les données
+failed_to_compare_snapshots = Impossible de comparer les instantanés
+failed_to_complete_dry_run = Impossible de terminer la simulation
+failed_to_create_event_log = Impossible de créer le journal d'événements
+failed_to_create_project = Impossible de créer le projet
+failed_to_create_snapshot = Impossible de créer l'instantané
+failed_to_create_table = Impossible de créer la table
+failed_to_create_time_series = Impossible de créer la série temporelle
+failed_to_create_view = Impossible de créer la vue
+failed_to_delete_comment = Impossible de supprimer le commentaire
+failed_to_delete_data = Impossible de supprimer les données
+failed_to_delete_event_log = Impossible de supprimer le journal d'événements
+failed_to_delete_project = Impossible de supprimer le projet
+failed_to_delete_snapshot = Impossible de supprimer l'instantané
+failed_to_delete_table = Impossible de supprimer la table
+failed_to_delete_time_series = Impossible de supprimer la série temporelle
+failed_to_delete_view = Impossible de supprimer la vue
+failed_to_delete_webhook = Impossible de supprimer le webhook
+failed_to_detach_json_schema = Impossible de détacher le schéma JSON
+failed_to_edit_comment = Impossible de modifier le commentaire
+failed_to_export_table = Impossible d'exporter la table
+failed_to_import_table = Impossible d'importer la table
+failed_to_increment_data = Impossible d'incrémenter les données
+failed_to_lift_legal_hold = Impossible de lever la conservation légale
+failed_to_list_comments = Impossible de lister les commentaires
+failed_to_list_event_logs = Impossible de lister les journaux d'événements
+failed_to_list_keys = Impossible de lister les clés
+failed_to_list_project_tables = Impossible de lister les tables du projet
+failed_to_list_projects = Impossible de lister les projets
+failed_to_list_snapshots = Impossible de lister les instantanés
+failed_to_list_tables = Impossible de lister les tables
+failed_to_list_templates = Impossible de lister les modèles
+failed_to_list_tree = Impossible de lister l'arborescence
+failed_to_merge_data = Impossible de fusionner les données
+failed_to_patch_data = Impossible de modifier les données
+failed_to_place_legal_hold = Impossible de placer la conservation légale
+failed_to_pop_value = Impossible de retirer la valeur
+failed_to_post_comment = Impossible de publier le commentaire
+failed_to_pull_changes = Impossible de récupérer les modifications
+failed_to_push_changes = Impossible d'envoyer les modifications
+failed_to_push_value = Impossible d'ajouter la valeur
+failed_to_read_events = Impossible de lire les événements
+failed_to_read_import = Impossible de lire l'import : { $reason }
+failed_to_read_samples = Impossible de lire les mesures
+failed_to_recommend_indexes = Impossible de recommander des index
+failed_to_record_samples = Impossible d'enregistrer les mesures
+failed_to_register_integration = Impossible d'enregistrer l'intégration
+failed_to_register_webhook = Impossible d'enregistrer le webhook
+failed_to_release_lock = Impossible de libérer le verrou
+failed_to_remove_feature_flag = Impossible de supprimer l'indicateur de fonctionnalité
+failed_to_remove_integration = Impossible de supprimer l'intégration
+failed_to_remove_table_from_project = Impossible de retirer la table du projet
+failed_to_remove_table_policy = Impossible de supprimer la politique de la table
+failed_to_remove_template = Impossible de supprimer le modèle
+failed_to_remove_transform = Impossible de supprimer la transformation
+failed_to_retrieve_activity = Impossible de récupérer l'activité
+failed_to_retrieve_cluster_members = Impossible de récupérer les membres du cluster
+failed_to_retrieve_consumer_offsets = Impossible de récupérer les positions des consommateurs
+failed_to_retrieve_data = Impossible de récupérer les données
+failed_to_retrieve_history = Impossible de récupérer l'historique
+failed_to_retrieve_integrations = Impossible de récupérer les intégrations
+failed_to_retrieve_json_schemas = Impossible de récupérer les schémas JSON
+failed_to_retrieve_key_statistics = Impossible de récupérer les statistiques des clés
+failed_to_retrieve_legal_holds = Impossible de récupérer les conservations légales
+failed_to_retrieve_list = Impossible de récupérer la liste
+failed_to_retrieve_lock = Impossible de récupérer le verrou
+failed_to_retrieve_schema = Impossible de récupérer le schéma
+failed_to_retrieve_statistics = Impossible de récupérer les statistiques
+failed_to_retrieve_table_policies = Impossible de récupérer les politiques des tables
+failed_to_retrieve_time_series = Impossible de récupérer les séries temporelles
+failed_to_retrieve_transforms = Impossible de récupérer les transformations
+failed_to_retrieve_view = Impossible de récupérer la vue
+failed_to_retrieve_views = Impossible de récupérer les vues
+failed_to_run_statement = Impossible d'exécuter l'instruction
+failed_to_schedule_integration = Impossible de planifier l'intégration
+failed_to_search_data = Impossible de rechercher dans les données
+failed_to_set_data = Impossible de définir les données
+failed_to_set_feature_flag = Impossible de définir l'indicateur de fonctionnalité
+failed_to_set_table_policy = Impossible de définir la politique de la table
+failed_to_set_table_region = Impossible de définir la région de la table
+failed_to_stream_table = Impossible de diffuser la table
+failed_to_update_data = Impossible de mettre à jour les données
+feature_flag_not_found = Indicateur de fonctionnalité introuvable
+full_text_search_is_unavailable_while_values_are_compressed_deduplicated_or_archived = La recherche plein texte est indisponible tant que les valeurs sont compressées, dédupliquées ou archivées
+full_text_search_is_unavailable_while_values_are_encrypted = La recherche plein texte est indisponible tant que les valeurs sont chiffrées
+group_must_not_be_empty_and_offset_must_not_be_negative = Le groupe ne doit pas être vide et la position ne doit pas être négative
+import_not_found = Import introuvable
+integration_not_found = Intégration introuvable
+interval_must_be_positive = L'intervalle doit être positif
+invalid_batch = Lot invalide : { $reason }
+invalid_delimiter = Délimiteur invalide : { $delimiter }
+invalid_fields = Champs invalides : { $reason }
+invalid_json_schema = Schéma JSON invalide : { $reason }
+invalid_log_level = Niveau de journalisation invalide : { $reason }
+invalid_or_expired_signature = Signature invalide ou expirée
+invalid_request_payload = Contenu de la requête invalide
+invalid_request_signature = Signature de requête invalide
+invalid_second_factor_code = Code de second facteur invalide
+invalid_signature = Signature invalide
+json_pointers_must_be_empty_or_start_with = Les pointeurs JSON doivent être vides ou commencer par '/'
+key_is_locked = La clé est verrouillée par { $owner }
+key_is_not_locked = La clé n'est pas verrouillée
+keys_of_the_table_collide_without_case_sensitivity = Des clés de la table entrent en collision sans distinction de casse
+legal_hold = La table est sous conservation légale : { $reason }
+lifting_legal_holds_requires_a_second_factor_which_is_not_configured = La levée des conservations légales nécessite un second facteur, qui n'est pas configuré
+list_is_empty = La liste est vide
+lock_is_not_held_by_this_owner = Le verrou n'est pas détenu par ce propriétaire
+name_and_table_must_not_be_empty = Le nom et la table ne doivent pas être vides
+no_compaction_age_is_configured_for_the_table = Aucun âge de compactage n'est configuré pour la table
+not_alive = Inactif
+not_ready = Pas prêt
+not_started = Non démarré
+parent_comment_not_found_on_this_key = Commentaire parent introuvable sur cette clé
+patch_cannot_be_applied = Le patch ne peut pas être appliqué : { $reason }
+pointer_must_be_empty_or_start_with = Le pointeur doit être vide ou commencer par '/'
+privacy_request_is_not_a_completed_export = La demande de confidentialité n'est pas un export terminé
+privacy_request_not_found = Demande de confidentialité introuvable
+project_already_exists = Le projet existe déjà
+project_name_must_not_be_empty = Le nom du projet ne doit pas être vide
+project_not_found = Projet introuvable
+region_is_not_configured = La région n'est pas configurée
+replication_is_not_configured = La réplication n'est pas configurée
+request_body_is_too_large = Le corps de la requête est trop volumineux
+request_payload_is_too_large = Le contenu de la requête est trop volumineux
+request_signature_is_missing = La signature de la requête est manquante
+request_timestamp_is_outside_the_allowed_window = L'horodatage de la requête est en dehors de la fenêtre autorisée
+request_was_already_received = La requête a déjà été reçue
+retention_limits_must_not_be_negative = Les limites de conservation ne doivent pas être négatives
+sample_values_must_be_finite = Les mesures doivent être finies
+signed_urls_are_not_configured = Les URL signées ne sont pas configurées
+snapshot_not_found = Instantané introuvable
+source_table_does_not_exist_or_target_table_already_exists = La table source n'existe pas ou la table cible existe déjà
+table_already_exists_its_region_cannot_change = La table existe déjà, sa région ne peut pas changer
+table_does_not_belong_to_the_project = La table n'appartient pas au projet
+table_does_not_hold_integer_values = La table ne contient pas de valeurs entières
+table_has_no_json_schema = La table n'a pas de schéma JSON
+table_has_no_policy = La table n'a pas de politique
+table_is_already_under_legal_hold = La table est déjà sous conservation légale
+table_is_locked = La table est verrouillée par une opération { $operation }
+table_is_not_a_template = La table n'est pas un modèle
+table_is_not_under_legal_hold = La table n'est pas sous conservation légale
+table_name_must_not_be_empty = Le nom de la table ne doit pas être vide
+table_not_found = Table introuvable
+target_key_is_already_derived = La clé cible est déjà dérivée
+target_table_name_must_not_be_empty = Le nom de la table cible ne doit pas être vide
+the_database_is_temporarily_unavailable = La base de données est temporairement indisponible
+the_endpoint_is_not_available = Le point de terminaison n'est pas disponible
+the_key_column_is_required = La colonne de clé est requise
+the_server_is_in_maintenance_mode = Le serveur est en mode maintenance
+the_sql_console_is_disabled = La console SQL est désactivée
+time_series_already_exists = La série temporelle existe déjà
+time_series_name_must_not_be_empty = Le nom de la série temporelle ne doit pas être vide
+time_series_not_found = Série temporelle introuvable
+too_many_concurrent_requests = Trop de requêtes simultanées
+transform_not_found = Transformation introuvable
+unknown_client = Client inconnu
+url_must_use_http_or_https = L'URL doit utiliser http ou https
+value_does_not_match_the_table_schema = La valeur ne correspond pas au schéma de la table : { $violations }
+value_is_not_a_boolean = La valeur de la clé { $key } n'est pas un booléen
+value_is_not_a_number = La valeur de la clé { $key } n'est pas un nombre
+value_is_not_an_integer = La valeur de la clé { $key } n'est pas un entier
+values_cannot_be_merged = Les valeurs ne peuvent pas être fusionnées : { $reason }
+view_already_exists = La vue existe déjà
+view_and_table_names_must_not_be_empty = Les noms de la vue et de la table ne doivent pas être vides
+view_not_found = Vue introuvable
@@ -0,0 +1,208 @@
+# Dutch translations of the error messages, keyed by the codes defined in en.ftl.
+
+a_key_cannot_be_derived_from_itself = Een sleutel kan niet van zichzelf worden afgeleid
+a_key_filter_requires_a_table = Een sleutelfilter vereist een tabel
+asset_not_found = Bestand niet gevonden
+at_least_one_event_must_be_appended = Er moet ten minste één gebeurtenis worden toegevoegd
+at_least_one_identity_must_be_given = Er moet ten minste één identiteit worden opgegeven
+at_least_one_sample_must_be_recorded = Er moet ten minste één meetwaarde worden vastgelegd
+author_and_body_must_not_be_empty = Auteur en tekst mogen niet leeg zijn
+body_must_not_be_empty = De tekst mag niet leeg zijn
+bucket_width_must_be_positive = De intervalbreedte moet positief zijn
+comment_not_found = Opmerking niet gevonden
+content_type_must_be_application_json = Het inhoudstype moet application/json zijn
+copying_the_table_would_cross_regions = Het kopiëren van de tabel zou regio's overschrijden
+current_value_changed_while_merging = De huidige waarde is tijdens het samenvoegen gewijzigd
+current_value_does_not_match_the_expected_value = De huidige waarde komt niet overeen met de verwachte waarde
+current_value_is_not_an_integer = De huidige waarde is geen geheel getal
+data_not_found = Gegevens niet gevonden
+deriving_the_key_would_cross_regions = Het afleiden van de sleutel zou regio's overschrijden
+event_log_already_exists = Het gebeurtenislogboek bestaat al
+event_log_name_must_not_be_empty = De naam van het gebeurtenislogboek mag niet leeg zijn
+event_log_not_found = Gebeurtenislogboek niet gevonden
+exactly_one_of_a_non_empty_prefix_or_a_list_of_keys_is_required = Precies één niet-leeg voorvoegsel of een lijst met sleutels is vereist
+exactly_one_of_a_patch_a_merge_patch_or_a_delta_is_required = Precies één patch, merge-patch of delta is vereist
+failed_to_acquire_lock = Kan de vergrendeling niet verkrijgen
+failed_to_acquire_schema_lock = Kan de schemavergrendeling niet verkrijgen
+failed_to_add_table_to_project = Kan de tabel niet aan het project toevoegen
+failed_to_add_template = Kan de sjabloon niet toevoegen
+failed_to_add_transform = Kan de transformatie niet toevoegen
+failed_to_aggregate_samples = Kan de meetwaarden niet aggregeren
+failed_to_append_data = Kan de gegevens niet toevoegen
+failed_to_append_events = Kan de gebeurtenissen niet toevoegen
+failed_to_apply_replicated_writes = Kan de gerepliceerde schrijfbewerkingen niet toepassen
+failed_to_archive_cold_keys = Kan de koude sleutels niet archiveren
+failed_to_attach_json_schema = Kan het JSON-schema niet koppelen
+failed_to_change_maintenance_mode = Kan de onderhoudsmodus niet wijzigen
+failed_to_check_legal_hold = Kan de juridische bewaarplicht niet controleren
+failed_to_check_lock = Kan de vergrendeling niet controleren
+failed_to_check_schema_lock = Kan de schemavergrendeling niet controleren
+failed_to_clone_table = Kan de tabel niet klonen
+failed_to_collect_metrics = Kan de metrieken niet verzamelen
+failed_to_commit_consumer_offset = Kan de consumentpositie niet vastleggen
+failed_to_compact_history = Kan de geschiedenis niet comprimeren
+failed_to_compare_and_set_data = Kan de gegevens niet vergelijken en instellen
+failed_to_compare_snapshots = Kan de momentopnamen niet vergelijken
+failed_to_complete_dry_run = Kan de proefrun niet voltooien
+failed_to_create_event_log = Kan het gebeurtenislogboek niet aanmaken
+failed_to_create_project = Kan het project niet aanmaken
+failed_to_create_snapshot = Kan de momentopname niet aanmaken
+failed_to_create_table = Kan de tabel niet aanmaken
+failed_to_create_time_series = Kan de tijdreeks niet aanmaken
+failed_to_create_view = Kan de weergave niet aanmaken
+failed_to_delete_comment = Kan de opmerking niet verwijderen
+failed_to_delete_data = Kan de gegevens niet verwijderen
+failed_to_delete_event_log = Kan het gebeurtenislogboek niet verwijderen
+failed_to_delete_project = Kan het project niet verwijderen
+failed_to_delete_snapshot = Kan de momentopname niet verwijderen
+failed_to_delete_table = Kan de tabel niet verwijderen
+failed_to_delete_time_series = Kan de tijdreeks niet verwijderen
+failed_to_delete_view = Kan de weergave niet verwijderen
+failed_to_delete_webhook = Kan de webhook niet verwijderen
+failed_to_detach_json_schema = Kan het JSON-schema niet ontkoppelen
+failed_to_edit_comment = Kan de opmerking niet bewerken
+failed_to_export_table = Kan de tabel niet exporteren
+failed_to_import_table = Kan de tabel niet importeren
+failed_to_increment_data = Kan de gegevens niet verhogen
+failed_to_lift_legal_hold = Kan de juridische bewaarplicht niet opheffen
+failed_to_list_comments = Kan de opmerkingen niet weergeven
+failed_to_list_event_logs = Kan de gebeurtenislogboeken niet weergeven
+failed_to_list_keys = Kan de sleutels niet weergeven
+failed_to_list_project_tables = Kan de projecttabellen niet weergeven
+failed_to_list_projects = Kan de projecten niet weergeven
+failed_to_list_snapshots = Kan de momentopnamen niet weergeven
+failed_to_list_tables = Kan de tabellen niet weergeven
+failed_to_list_templates = Kan de sjablonen niet weergeven
+failed_to_list_tree = Kan de boomstructuur niet weergeven
+failed_to_merge_data = Kan de gegevens niet samenvoegen
+failed_to_patch_data = Kan de gegevens niet patchen
+failed_to_place_legal_hold = Kan de juridische bewaarplicht niet instellen
+failed_to_pop_value = Kan de waarde niet ophalen
+failed_to_post_comment = Kan de opmerking niet plaatsen
+failed_to_pull_changes = Kan de wijzigingen niet ophalen
+failed_to_push_changes = Kan de wijzigingen niet verzenden
+failed_to_push_value = Kan de waarde niet toevoegen
+failed_to_read_events = Kan de gebeurtenissen niet lezen
+failed_to_read_import = Kan de import niet lezen: { $reason }
+failed_to_read_samples = Kan de meetwaarden niet lezen
+failed_to_recommend_indexes = Kan geen indexen aanbevelen
+failed_to_record_samples = Kan de meetwaarden niet vastleggen
+failed_to_register_integration = Kan de integratie niet registreren
+failed_to_register_webhook = Kan de webhook niet registreren
+failed_to_release_lock = Kan de vergrendeling niet vrijgeven
+failed_to_remove_feature_flag = Kan de functievlag niet verwijderen
+failed_to_remove_integration = Kan de integratie niet verwijderen
+failed_to_remove_table_from_project = Kan de tabel niet uit het project verwijderen
+failed_to_remove_table_policy = Kan het tabelbeleid niet verwijderen
+failed_to_remove_template = Kan de sjabloon niet verwijderen
+failed_to_remove_transform = Kan de transformatie niet verwijderen
+failed_to_retrieve_activity = Kan de activiteit niet ophalen
+failed_to_retrieve_cluster_members = Kan de clusterleden niet ophalen
+failed_to_retrieve_consumer_offsets = Kan de consumentposities niet ophalen
+failed_to_retrieve_data = Kan de gegevens niet ophalen
+failed_to_retrieve_history = Kan de geschiedenis niet ophalen
+failed_to_retrieve_integrations = Kan de integraties niet ophalen
+failed_to_retrieve_json_schemas = Kan de JSON-schema's niet ophalen
+failed_to_retrieve_key_statistics = Kan de sleutelstatistieken niet ophalen
+failed_to_retrieve_legal_holds = Kan de juridische bewaarplichten niet ophalen
+failed_to_retrieve_list = Kan de lijst niet ophalen
+failed_to_retrieve_lock = Kan de vergrendeling niet ophalen
+failed_to_retrieve_schema = Kan het schema niet ophalen
+failed_to_retrieve_statistics = Kan de statistieken niet ophalen
+failed_to_retrieve_table_policies = Kan het tabelbeleid niet ophalen
+failed_to_retrieve_time_series = Kan de tijdreeksen niet ophalen
+failed_to_retrieve_transforms = Kan de transformaties niet ophalen
+failed_to_retrieve_view = Kan de weergave niet ophalen
+failed_to_retrieve_views = Kan de weergaven niet ophalen
+failed_to_run_statement = Kan de instructie niet uitvoeren
+failed_to_schedule_integration = Kan de integratie niet inplannen
+failed_to_search_data = Kan de gegevens niet doorzoeken
+failed_to_set_data = Kan de gegevens niet instellen
+failed_to_set_feature_flag = Kan de functievlag niet instellen
+failed_to_set_table_policy = Kan het tabelbeleid niet instellen
+failed_to_set_table_region = Kan de tabelregio niet instellen
+failed_to_stream_table = Kan de tabel niet streamen
+failed_to_update_data = Kan de gegevens niet bijwerken
+feature_flag_not_found = Functievlag niet gevonden
+full_text_search_is_unavailable_while_values_are_compressed_deduplicated_or_archived = Zoeken in volledige tekst is niet beschikbaar zolang waarden gecomprimeerd, ontdubbeld of gearchiveerd worden
+full_text_search_is_unavailable_while_values_are_encrypted = Zoeken in volledige tekst is niet beschikbaar zolang waarden versleuteld zijn
+group_must_not_be_empty_and_offset_must_not_be_negative = De groep mag niet leeg zijn en de positie mag niet negatief zijn
+import_not_found = Import niet gevonden
+integration_not_found = Integratie niet gevonden
+interval_must_be_positive = Het interval moet positief zijn
+invalid_batch = Ongeldige batch: { $reason }
+invalid_delimiter = Ongeldig scheidingsteken: { $delimiter }
+invalid_fields = Ongeldige velden: { $reason }
+invalid_json_schema = Ongeldig JSON-schema: { $reason }
+invalid_log_level = Ongeldig logniveau: { $reason }
+invalid_or_expired_signature = Ongeldige of verlopen handtekening
+invalid_request_payload = Ongeldige inhoud van het verzoek
+invalid_request_signature = Ongeldige handtekening van het verzoek
+invalid_second_factor_code = Ongeldige code voor de tweede factor
+invalid_signature = Ongeldige handtekening
+json_pointers_must_be_empty_or_start_with = JSON-pointers moeten leeg zijn of met '/' beginnen
+key_is_locked = De sleutel is vergrendeld door { $owner }
+key_is_not_locked = De sleutel is niet vergrendeld
+keys_of_the_table_collide_without_case_sensitivity = Sleutels van de tabel botsen zonder onderscheid tussen hoofd- en kleine letters
+legal_hold = De tabel valt onder een juridische bewaarplicht: { $reason }
+lifting_legal_holds_requires_a_second_factor_which_is_not_configured = Het opheffen van juridische bewaarplichten vereist een tweede factor, die niet is geconfigureerd
+list_is_empty = De lijst is leeg
+lock_is_not_held_by_this_owner = De vergrendeling is niet in handen van deze eigenaar
+name_and_table_must_not_be_empty = Naam en tabel mogen niet leeg zijn
+no_compaction_age_is_configured_for_the_table = Er is geen compactieleeftijd geconfigureerd voor de tabel
+not_alive = Niet actief
+not_ready = Niet gereed
+not_started = Niet gestart
+parent_comment_not_found_on_this_key = Bovenliggende opmerking niet gevonden bij deze sleutel
+patch_cannot_be_applied = De patch kan niet worden toegepast: { $reason }
+pointer_must_be_empty_or_start_with = De pointer moet leeg zijn of met '/' beginnen
+privacy_request_is_not_a_completed_export = Het privacyverzoek is geen voltooide export
+privacy_request_not_found = Privacyverzoek niet gevonden
+project_already_exists = Het project bestaat al
+project_name_must_not_be_empty = De projectnaam mag niet leeg zijn
+project_not_found = Project niet gevonden
+region_is_not_configured = De regio is niet geconfigureerd
+replication_is_not_configured = Replicatie is niet geconfigureerd
+request_body_is_too_large = De inhoud van het verzoek is te groot
+request_payload_is_too_large = De gegevens van het verzoek zijn te groot
+request_signature_is_missing = De handtekening van het verzoek ontbreekt
+request_timestamp_is_outside_the_allowed_window = Het tijdstempel van het verzoek valt buiten het toegestane venster
+request_was_already_received = Het verzoek is al ontvangen
+retention_limits_must_not_be_negative = Bewaarlimieten mogen niet negatief zijn
+sample_values_must_be_finite = Meetwaarden moeten eindig zijn
+signed_urls_are_not_configured = Ondertekende URL's zijn niet geconfigureerd
+snapshot_not_found = Momentopname niet gevonden
+source_table_does_not_exist_or_target_table_already_exists = De brontabel bestaat niet of de doeltabel bestaat al
+table_already_exists_its_region_cannot_change = De tabel bestaat al, de regio kan niet worden gewijzigd
+table_does_not_belong_to_the_project = De tabel hoort niet bij het project
+table_does_not_hold_integer_values = De tabel bevat geen gehele getallen
+table_has_no_json_schema = De tabel heeft geen JSON-schema
+table_has_no_policy = De tabel heeft geen beleid
+table_is_already_under_legal_hold = De tabel valt al onder een juridische bewaarplicht
+table_is_locked = De tabel is vergrendeld door een { $operation }-bewerking
+table_is_not_a_template = De tabel is geen sjabloon
+table_is_not_under_legal_hold = De tabel valt niet onder een juridische bewaarplicht
+table_name_must_not_be_empty = De tabelnaam mag niet leeg zijn
+table_not_found = Tabel niet gevonden
+target_key_is_already_derived = De doelsleutel is al afgeleid
+target_table_name_must_not_be_empty = De naam van de doeltabel mag niet leeg zijn
+the_database_is_temporarily_unavailable = De database is tijdelijk niet beschikbaar
+the_endpoint_is_not_available = Het eindpunt is niet beschikbaar
+the_key_column_is_required = De sleutelkolom is vereist
+the_server_is_in_maintenance_mode = De server is in onderhoudsmodus
+the_sql_console_is_disabled = De SQL-console is uitgeschakeld
+time_series_already_exists = De tijdreeks bestaat al
+time_series_name_must_not_be_empty = De naam van de tijdreeks mag niet leeg zijn
+time_series_not_found = Tijdreeks niet gevonden
+too_many_concurrent_requests = Te veel gelijktijdige verzoeken
+transform_not_found = Transformatie niet gevonden
+unknown_client = Onbekende client
+url_must_use_http_or_https = De URL moet http of https gebruiken
+value_does_not_match_the_table_schema = De waarde komt niet overeen met het tabelschema: { $violations }
+value_is_not_a_boolean = De waarde van sleutel { $key } is geen booleaanse waarde
+value_is_not_a_number = De waarde van sleutel { $key } is geen getal
+value_is_not_an_integer = De waarde van sleutel { $key } is geen geheel getal
+values_cannot_be_merged = De waarden kunnen niet worden samengevoegd: { $reason }
+view_already_exists = De weergave bestaat al
+view_and_table_names_must_not_be_empty = Namen van weergave en tabel mogen niet leeg zijn
+view_not_found = Weergave niet gevonden
@@ -0,0 +1,143 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::IndexAdvisorConfig;
+use crate::db::Database;
+use crate::ident::Ident;
+use crate::utils::Utils;
+
+/// A struct representing a recurring slow query scanning a whole table to filter it.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct IndexRecommendation {
+    pub table: String,
+    pub operation: String,
+    /// The statement with placeholders instead of values.
+    pub sql: String,
+    /// The number of times the query was recorded as slow.
+    pub occurrences: usize,
+    /// The total time in milliseconds the recorded queries took.
+    pub total_ms: i64,
+    /// The query plan of the statement.
+    pub plan: Vec<String>,
+    /// The statement creating an index serving the filter, if one can.
+    pub index: Option<String>,
+}
+
+/// Implementation of the `IndexRecommendation` struct.
+impl IndexRecommendation {
+    /// Checks whether a query plan scans the given table instead of searching it.
+    ///
+    /// # Arguments
+    ///
+    /// * `table` - The sanitized name of the table.
+    /// * `plan` - The details of the steps of the query plan.
+    ///
+    /// # Returns
+    ///
+    /// * `bool` - Whether a step reads every row of the table.
+    pub fn scans(table: &str, plan: &[String]) -> bool {
+        plan.iter().any(|step| {
+            step.strip_prefix("SCAN ")
+                .map(|rest| rest.strip_prefix("TABLE ").unwrap_or(rest))
+                .and_then(|rest| rest.split_whitespace().next())
+                .is_some_and(|scanned| scanned.trim_matches('"') == table)
+        })
+    }
+
+    /// Returns the columns compared in the WHERE clause of a statement.
+    ///
+    /// # Arguments
+    ///
+    /// * `sql` - The statement, with normalized whitespace.
+    ///
+    /// # Returns
+    ///
+    /// * `Vec<&str>` - The `key` and `value` columns the statement filters on.
+    pub fn filtered_columns(sql: &str) -> Vec<&str> {
+        let Some((_, clause)) = sql.split_once(" WHERE ") else {
+            return Vec::new();
+        };
+        let clause = [" ORDER BY ", " GROUP BY ", " LIMIT "]
+            .iter()
+            .filter_map(|end| clause.find(end))
+            .min()
+            .map_or(clause, |end| &clause[..end]);
+        ["key", "value"]
+            .into_iter()
+            .filter(|column| {
+                clause
+                    .split(|c: char| !c.is_alphanumeric() && c != '_')
+                    .any(|word| word == *column)
+            })
+            .collect()
+    }
+
+    /// Returns the statement creating the index on the values of a table, the same index
+    /// tables are created with when values are indexed.
+    ///
+    /// # Arguments
+    ///
+    /// * `table` - The name of the table.
+    ///
+    /// # Returns
+    ///
+    /// * `String` - The statement.
+    pub fn value_index(table: &str) -> String {
+        format!(
+            "CREATE INDEX IF NOT EXISTS {} ON {} (value)",
+            Ident::derived("xcloud_index_", &format!("{}_value", table)),
+            Ident::quote(&Utils::sanitize(table))
+        )
+    }
+}
+
+/// A struct periodically creating the indexes recommended by the index advisor.
+pub struct IndexAdvisor {
+    db: Arc<tokio::sync::Mutex<Database>>,
+    config: IndexAdvisorConfig,
+}
+
+/// Implementation of the `IndexAdvisor` struct.
+impl IndexAdvisor {
+    /// Creates a new instance of the IndexAdvisor.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - A reference to the database wrapped in an Arc and Mutex for thread safety.
+    /// * `config` - The index advisor configuration holding the interval and the threshold.
+    ///
+    /// # Returns
+    ///
+    /// * `IndexAdvisor` - A new instance of the IndexAdvisor.
+    pub fn new(db: Arc<tokio::sync::Mutex<Database>>, config: IndexAdvisorConfig) -> Self {
+        IndexAdvisor { db, config }
+    }
+
+    /// Creates the recommended indexes in the background at the configured interval, if
+    /// enabled.
+    pub fn spawn(self) {
+        if !self.config.auto_create {
+            return;
+        }
+        tokio::spawn(async move {
+            let mut interval =
+                tokio::time::interval(Duration::from_secs(self.config.interval_secs.max(1)));
+            loop {
+                interval.tick().await;
+                let db = self.db.lock().await;
+                match db
+                    .create_recommended_indexes(self.config.min_occurrences)
+                    .await
+                {
+                    Ok(created) if !created.is_empty() => {
+                        log::info!("Created recommended indexes on {}", created.join(", "))
+                    }
+                    Ok(_) => {}
+                    Err(e) => log::error!("Failed to create recommended indexes: {}", e),
+                }
+            }
+        });
+    }
+}
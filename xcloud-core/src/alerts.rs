@@ -0,0 +1,394 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use async_trait::async_trait;
+use lettre::message::Mailbox;
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::breaker::CircuitBreaker;
+use crate::cluster::Cluster;
+use crate::config::{AlertChannel, AlertConfig};
+use crate::db::Database;
+use crate::jobs::{Job, JobQueue};
+use crate::stats::{RequestCounts, RequestStats};
+
+/// The number of most recently sent alerts kept for `/admin/alerts`.
+const RECENT_CAPACITY: usize = 100;
+
+/// An enum representing the condition an alert reports, alerts of the same kind being
+/// deduplicated.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum AlertKind {
+    /// Too many requests answered with a `5xx` status.
+    ErrorRate,
+    /// The database circuit breaker opened.
+    CircuitBreaker,
+    /// The tables near their storage quota.
+    StorageQuota,
+}
+
+/// A struct representing a threshold crossed by the server.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Alert {
+    pub kind: AlertKind,
+    pub message: String,
+    /// The observed value, e.g. the error rate or the used share of the quota.
+    pub value: f64,
+    /// The threshold the value crossed.
+    pub threshold: f64,
+    /// The unix timestamp in seconds at which the threshold was found crossed.
+    pub timestamp: u64,
+}
+
+/// Implementation of the `Alert` struct.
+impl Alert {
+    /// Creates a new alert raised now.
+    fn new(kind: AlertKind, message: String, value: f64, threshold: f64) -> Self {
+        Alert {
+            kind,
+            message,
+            value,
+            threshold,
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or_default(),
+        }
+    }
+}
+
+/// A struct representing what the previous check observed.
+struct AlerterState {
+    /// The request counters at the previous check.
+    counts: RequestCounts,
+    /// The number of times the circuit breaker opened at the previous check.
+    trips: u64,
+    /// The instant each kind of alert was last sent.
+    sent: HashMap<AlertKind, Instant>,
+    /// The most recently sent alerts, oldest first.
+    recent: VecDeque<Alert>,
+}
+
+/// A struct checking the internal metrics against the configured thresholds and sending
+/// alerts to the configured channels.
+///
+/// The error rate and the circuit breaker trips are measured per instance since the previous
+/// check, so every instance of a cluster alerts about itself. The storage quota is shared and
+/// only checked by the leader. An alert is not sent again during the cooldown, however many
+/// checks find its condition. Deliveries are retried with the webhook job queue.
+pub struct Alerter {
+    config: AlertConfig,
+    stats: Arc<RequestStats>,
+    breaker: Arc<CircuitBreaker>,
+    queue: JobQueue,
+    client: reqwest::Client,
+    state: Mutex<AlerterState>,
+}
+
+/// Implementation of the `Alerter` struct.
+impl Alerter {
+    /// Creates a new instance of the Alerter, measuring from now.
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - The alert configuration holding the thresholds and the channels.
+    /// * `stats` - The request counters of the server.
+    /// * `breaker` - The circuit breaker of the database.
+    /// * `queue` - The queue delivering the alerts with retries.
+    ///
+    /// # Returns
+    ///
+    /// * `Alerter` - A new instance of the Alerter.
+    pub fn new(
+        config: AlertConfig,
+        stats: Arc<RequestStats>,
+        breaker: Arc<CircuitBreaker>,
+        queue: JobQueue,
+    ) -> Self {
+        let state = AlerterState {
+            counts: stats.counts(),
+            trips: breaker.trips(),
+            sent: HashMap::new(),
+            recent: VecDeque::new(),
+        };
+        Alerter {
+            config,
+            stats,
+            breaker,
+            queue,
+            client: reqwest::Client::new(),
+            state: Mutex::new(state),
+        }
+    }
+
+    /// Checks the thresholds in the background at the configured interval, if any channel is
+    /// configured.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - A reference to the database wrapped in an Arc and Mutex for thread safety.
+    /// * `cluster` - The cluster electing the instance checking the shared quota.
+    pub fn spawn(self: &Arc<Self>, db: Arc<tokio::sync::Mutex<Database>>, cluster: Arc<Cluster>) {
+        if self.config.channels.is_empty() {
+            return;
+        }
+        let alerter = self.clone();
+        tokio::spawn(async move {
+            let mut interval =
+                tokio::time::interval(Duration::from_secs(alerter.config.interval_secs.max(1)));
+            interval.tick().await;
+            loop {
+                interval.tick().await;
+                let alerts = if cluster.is_leader() {
+                    alerter.evaluate(Some(&*db.lock().await)).await
+                } else {
+                    alerter.evaluate(None).await
+                };
+                match alerts {
+                    Ok(alerts) => {
+                        alerter.dispatch(alerts);
+                    }
+                    Err(e) => log::error!("Failed to check alert thresholds: {}", e),
+                }
+            }
+        });
+    }
+
+    /// Measures the metrics since the previous check and returns the alerts of the crossed
+    /// thresholds.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - The database whose tables are checked against the storage quota, or `None` to
+    ///   skip the quota.
+    ///
+    /// # Returns
+    ///
+    /// * `Vec<Alert>` - The alerts of the crossed thresholds, sent or not.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the tables cannot be described.
+    pub async fn evaluate(&self, db: Option<&Database>) -> Result<Vec<Alert>, sqlx::Error> {
+        let mut alerts = Vec::new();
+        let counts = self.stats.counts();
+        let trips = self.breaker.trips();
+        let (previous, previous_trips) = {
+            let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+            let previous = std::mem::replace(&mut state.counts, counts.clone());
+            (previous, std::mem::replace(&mut state.trips, trips))
+        };
+
+        let served = Self::total(&counts).saturating_sub(Self::total(&previous));
+        let failed = counts.server_error.saturating_sub(previous.server_error);
+        if served > 0 && served >= self.config.min_requests {
+            let rate = failed as f64 / served as f64;
+            if rate > self.config.error_rate {
+                alerts.push(Alert::new(
+                    AlertKind::ErrorRate,
+                    format!(
+                        "{:.1}% of {} requests failed with a server error, above {:.1}%",
+                        rate * 100.0,
+                        served,
+                        self.config.error_rate * 100.0
+                    ),
+                    rate,
+                    self.config.error_rate,
+                ));
+            }
+        }
+
+        let opened = trips.saturating_sub(previous_trips);
+        if opened > 0 {
+            alerts.push(Alert::new(
+                AlertKind::CircuitBreaker,
+                format!(
+                    "The database circuit breaker opened {} time(s), requests were rejected",
+                    opened
+                ),
+                opened as f64,
+                0.0,
+            ));
+        }
+
+        if let (Some(db), Some(quota)) = (db, self.config.storage_quota_bytes) {
+            let used: i64 = db
+                .describe_tables()
+                .await?
+                .iter()
+                .map(|table| table.approx_bytes)
+                .sum();
+            let share = used.max(0) as f64 / quota.max(1) as f64;
+            if share >= self.config.quota_warn_ratio {
+                alerts.push(Alert::new(
+                    AlertKind::StorageQuota,
+                    format!(
+                        "The tables use {} of the {} bytes of the storage quota ({:.0}%)",
+                        used,
+                        quota,
+                        share * 100.0
+                    ),
+                    share,
+                    self.config.quota_warn_ratio,
+                ));
+            }
+        }
+        Ok(alerts)
+    }
+
+    /// Sends the alerts whose kind was not sent during the cooldown to every channel.
+    ///
+    /// # Arguments
+    ///
+    /// * `alerts` - The alerts of the crossed thresholds.
+    ///
+    /// # Returns
+    ///
+    /// * `Vec<Alert>` - The alerts sent, the others being suppressed.
+    pub fn dispatch(&self, alerts: Vec<Alert>) -> Vec<Alert> {
+        let cooldown = Duration::from_secs(self.config.cooldown_secs);
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        let mut sent = Vec::new();
+        for alert in alerts {
+            if state
+                .sent
+                .get(&alert.kind)
+                .is_some_and(|at| at.elapsed() < cooldown)
+            {
+                log::debug!("Suppressed alert during cooldown: {}", alert.message);
+                continue;
+            }
+            log::warn!("Alert: {}", alert.message);
+            state.sent.insert(alert.kind, Instant::now());
+            if state.recent.len() >= RECENT_CAPACITY {
+                state.recent.pop_front();
+            }
+            state.recent.push_back(alert.clone());
+            for channel in &self.config.channels {
+                self.queue.enqueue(Arc::new(AlertDelivery {
+                    client: self.client.clone(),
+                    channel: channel.clone(),
+                    alert: alert.clone(),
+                }));
+            }
+            sent.push(alert);
+        }
+        sent
+    }
+
+    /// Returns the most recently sent alerts, newest first.
+    pub fn recent(&self) -> Vec<Alert> {
+        let state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        state.recent.iter().rev().cloned().collect()
+    }
+
+    /// Returns the number of requests counted.
+    fn total(counts: &RequestCounts) -> u64 {
+        counts.informational
+            + counts.success
+            + counts.redirection
+            + counts.client_error
+            + counts.server_error
+    }
+}
+
+/// A job delivering a single alert to a single channel.
+struct AlertDelivery {
+    client: reqwest::Client,
+    channel: AlertChannel,
+    alert: Alert,
+}
+
+/// Implementation of the `AlertDelivery` struct.
+impl AlertDelivery {
+    /// Posts a JSON body to a URL.
+    async fn post(&self, url: &str, body: &serde_json::Value) -> Result<(), String> {
+        let response = self
+            .client
+            .post(url)
+            .json(body)
+            .send()
+            .await
+            .map_err(|e| e.to_string())?;
+        if response.status().is_success() {
+            Ok(())
+        } else {
+            Err(format!("unexpected status {}", response.status()))
+        }
+    }
+
+    /// Emails the alert through an SMTP relay.
+    async fn email(
+        &self,
+        host: &str,
+        port: u16,
+        credentials: Option<Credentials>,
+        from: &str,
+        to: &[String],
+    ) -> Result<(), String> {
+        let parse = |address: &str| {
+            address
+                .parse::<Mailbox>()
+                .map_err(|e| format!("invalid address {}: {}", address, e))
+        };
+        let mut builder = Message::builder()
+            .from(parse(from)?)
+            .subject(format!("[xCLOUD] {}", self.alert.message));
+        for address in to {
+            builder = builder.to(parse(address)?);
+        }
+        let message = builder
+            .body(format!(
+                "{}\n\nValue: {}\nThreshold: {}\nTimestamp: {}\n",
+                self.alert.message, self.alert.value, self.alert.threshold, self.alert.timestamp
+            ))
+            .map_err(|e| e.to_string())?;
+        let mut transport = AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(host)
+            .map_err(|e| e.to_string())?
+            .port(port);
+        if let Some(credentials) = credentials {
+            transport = transport.credentials(credentials);
+        }
+        transport
+            .build()
+            .send(message)
+            .await
+            .map(|_| ())
+            .map_err(|e| e.to_string())
+    }
+}
+
+#[async_trait]
+impl Job for AlertDelivery {
+    fn name(&self) -> String {
+        format!("alert to {}", self.channel.describe())
+    }
+
+    async fn run(&self) -> Result<(), String> {
+        match &self.channel {
+            AlertChannel::Webhook { url } => self.post(url, &json!(self.alert)).await,
+            AlertChannel::Slack { url } => {
+                let text = format!(":rotating_light: *xCLOUD* {}", self.alert.message);
+                self.post(url, &json!({ "text": text })).await
+            }
+            AlertChannel::Smtp {
+                host,
+                port,
+                username,
+                password,
+                from,
+                to,
+            } => {
+                let credentials = username
+                    .as_ref()
+                    .map(|username| Credentials::new(username.clone(), password.clone()));
+                self.email(host, *port, credentials, from, to).await
+            }
+        }
+    }
+}
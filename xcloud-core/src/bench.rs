@@ -0,0 +1,367 @@
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+/// The usage of `xcloud bench`, printed when its options cannot be parsed.
+pub const USAGE: &str = "Usage: xcloud bench [--target URL] [--table NAME] [--requests N] \
+[--concurrency N] [--value-size BYTES] [--keys N] [--read-ratio 0..1] [--distribution uniform|zipf]";
+
+/// An enum representing how the keys of a workload are picked.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum KeyDistribution {
+    /// Every key is equally likely.
+    Uniform,
+    /// The n-th key is picked with a probability proportional to 1/n, so a few keys are hot.
+    Zipf,
+}
+
+/// A struct representing the workload driven by `xcloud bench`.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct BenchOptions {
+    /// The base URL of the instance under test.
+    pub target: String,
+    pub table: String,
+    /// The number of measured requests.
+    pub requests: u64,
+    /// The number of requests in flight at the same time.
+    pub concurrency: usize,
+    /// The size in bytes of the written values.
+    pub value_size: usize,
+    /// The number of distinct keys, all written once before the measured requests.
+    pub keys: u64,
+    /// The share of the measured requests that are reads, the others being writes.
+    pub read_ratio: f64,
+    pub distribution: KeyDistribution,
+}
+
+impl Default for BenchOptions {
+    fn default() -> Self {
+        BenchOptions {
+            target: "http://127.0.0.1:8080".to_string(),
+            table: "xbench".to_string(),
+            requests: 10_000,
+            concurrency: 16,
+            value_size: 128,
+            keys: 1000,
+            read_ratio: 0.9,
+            distribution: KeyDistribution::Uniform,
+        }
+    }
+}
+
+/// Implementation of the `BenchOptions` struct.
+impl BenchOptions {
+    /// Parses the options from the command line arguments following `bench`.
+    ///
+    /// # Arguments
+    ///
+    /// * `args` - The arguments, as `--name value` pairs.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<BenchOptions, String>` - The options, defaulting the missing ones, or the
+    ///   reason the arguments are invalid.
+    pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Self, String> {
+        let mut options = BenchOptions::default();
+        let mut args = args.into_iter();
+        while let Some(name) = args.next() {
+            let value = args
+                .next()
+                .ok_or_else(|| format!("missing value of {}", name))?;
+            let invalid = format!("invalid value of {}: {}", name, value);
+            match name.as_str() {
+                "--target" => options.target = value.trim_end_matches('/').to_string(),
+                "--table" => options.table = value,
+                "--requests" => options.requests = value.parse().map_err(|_| invalid)?,
+                "--concurrency" => options.concurrency = value.parse().map_err(|_| invalid)?,
+                "--value-size" => options.value_size = value.parse().map_err(|_| invalid)?,
+                "--keys" => options.keys = value.parse().map_err(|_| invalid)?,
+                "--read-ratio" => {
+                    options.read_ratio = value
+                        .parse()
+                        .ok()
+                        .filter(|ratio| (0.0..=1.0).contains(ratio))
+                        .ok_or(invalid)?
+                }
+                "--distribution" => {
+                    options.distribution =
+                        serde_json::from_value(json!(value)).map_err(|_| invalid)?
+                }
+                _ => return Err(format!("unknown option {}", name)),
+            }
+        }
+        if options.concurrency == 0 || options.keys == 0 {
+            return Err("--concurrency and --keys must be positive".to_string());
+        }
+        Ok(options)
+    }
+}
+
+/// A struct representing the latencies of one kind of request.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct LatencySummary {
+    pub count: usize,
+    /// The number of requests that failed or were answered with a server error.
+    pub errors: usize,
+    pub p50_ms: f64,
+    pub p90_ms: f64,
+    pub p99_ms: f64,
+    pub max_ms: f64,
+}
+
+/// Implementation of the `LatencySummary` struct.
+impl LatencySummary {
+    /// Summarizes the given latencies.
+    ///
+    /// # Arguments
+    ///
+    /// * `latencies` - The latencies of the requests, sorted in place.
+    /// * `errors` - The number of failed requests.
+    ///
+    /// # Returns
+    ///
+    /// * `LatencySummary` - The nearest-rank percentiles of the latencies in milliseconds.
+    pub fn new(latencies: &mut [Duration], errors: usize) -> Self {
+        latencies.sort_unstable();
+        let percentile = |p: f64| {
+            let rank = ((p * latencies.len() as f64).ceil() as usize).clamp(1, latencies.len());
+            latencies[rank - 1].as_secs_f64() * 1000.0
+        };
+        if latencies.is_empty() {
+            return LatencySummary {
+                errors,
+                ..LatencySummary::default()
+            };
+        }
+        LatencySummary {
+            count: latencies.len(),
+            errors,
+            p50_ms: percentile(0.5),
+            p90_ms: percentile(0.9),
+            p99_ms: percentile(0.99),
+            max_ms: percentile(1.0),
+        }
+    }
+}
+
+/// A struct representing the outcome of `xcloud bench`.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct BenchReport {
+    pub options: BenchOptions,
+    pub elapsed_secs: f64,
+    /// The number of measured requests completed per second.
+    pub throughput: f64,
+    pub reads: LatencySummary,
+    pub writes: LatencySummary,
+}
+
+/// Implementation of the `BenchReport` struct.
+impl BenchReport {
+    /// Renders the report.
+    ///
+    /// # Returns
+    ///
+    /// * `String` - The report as pretty-printed JSON.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap_or_default()
+    }
+}
+
+/// A struct representing a small, fast pseudo-random number generator, xorshift64*.
+struct Rng(u64);
+
+/// Implementation of the `Rng` struct.
+impl Rng {
+    /// Creates a generator from a seed.
+    fn new(seed: u64) -> Self {
+        Rng(seed | 1)
+    }
+
+    /// Returns a number uniformly distributed in `[0, 1)`.
+    fn next_f64(&mut self) -> f64 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        (self.0.wrapping_mul(0x2545_f491_4f6c_dd1d) >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+/// A struct picking the keys of a workload.
+struct KeyPicker {
+    keys: u64,
+    /// The cumulative probabilities of the keys, for the Zipf distribution.
+    cumulative: Vec<f64>,
+}
+
+/// Implementation of the `KeyPicker` struct.
+impl KeyPicker {
+    /// Creates the picker of the given distribution over the keys.
+    fn new(distribution: KeyDistribution, keys: u64) -> Self {
+        let cumulative = match distribution {
+            KeyDistribution::Uniform => Vec::new(),
+            KeyDistribution::Zipf => {
+                let mut total = 0.0;
+                let mut cumulative: Vec<f64> = (1..=keys)
+                    .map(|n| {
+                        total += 1.0 / n as f64;
+                        total
+                    })
+                    .collect();
+                cumulative.iter_mut().for_each(|weight| *weight /= total);
+                cumulative
+            }
+        };
+        KeyPicker { keys, cumulative }
+    }
+
+    /// Picks a key.
+    fn pick(&self, rng: &mut Rng) -> String {
+        let sample = rng.next_f64();
+        let index = if self.cumulative.is_empty() {
+            (sample * self.keys as f64) as u64
+        } else {
+            self.cumulative.partition_point(|weight| *weight < sample) as u64
+        };
+        format!("key-{:08}", index.min(self.keys - 1))
+    }
+}
+
+/// A struct driving a read and write workload against an instance over HTTP.
+pub struct Bench {
+    options: BenchOptions,
+    client: reqwest::Client,
+}
+
+/// Implementation of the `Bench` struct.
+impl Bench {
+    /// Creates a new instance of the Bench.
+    ///
+    /// # Arguments
+    ///
+    /// * `options` - The workload to drive.
+    ///
+    /// # Returns
+    ///
+    /// * `Bench` - A new instance of the Bench.
+    pub fn new(options: BenchOptions) -> Self {
+        Bench {
+            client: reqwest::Client::builder()
+                .pool_max_idle_per_host(options.concurrency)
+                .build()
+                .unwrap_or_default(),
+            options,
+        }
+    }
+
+    /// Writes every key once, then runs the measured requests.
+    ///
+    /// # Returns
+    ///
+    /// * `BenchReport` - The throughput and the latency percentiles of the reads and writes.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the keys cannot be written beforehand.
+    pub async fn run(self) -> Result<BenchReport, String> {
+        let bench = Arc::new(self);
+        let value = "x".repeat(bench.options.value_size);
+        for index in 0..bench.options.keys {
+            bench
+                .write(&format!("key-{:08}", index), &value)
+                .await
+                .map_err(|e| format!("Failed to write the keys: {}", e))?;
+        }
+
+        let picker = Arc::new(KeyPicker::new(
+            bench.options.distribution,
+            bench.options.keys,
+        ));
+        let seed = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_nanos() as u64);
+        let concurrency = bench.options.concurrency as u64;
+        let started = Instant::now();
+        let workers: Vec<_> = (0..concurrency)
+            .map(|worker| {
+                let (bench, picker, value) = (bench.clone(), picker.clone(), value.clone());
+                let requests = bench.options.requests / concurrency
+                    + u64::from(worker < bench.options.requests % concurrency);
+                tokio::spawn(async move {
+                    let mut rng = Rng::new(seed.wrapping_add(worker.wrapping_mul(0x9e37_79b9)));
+                    let (mut reads, mut writes) = (Vec::new(), Vec::new());
+                    let (mut read_errors, mut write_errors) = (0, 0);
+                    for _ in 0..requests {
+                        let key = picker.pick(&mut rng);
+                        let read = rng.next_f64() < bench.options.read_ratio;
+                        let request_started = Instant::now();
+                        let outcome = if read {
+                            bench.read(&key).await
+                        } else {
+                            bench.write(&key, &value).await
+                        };
+                        let (latencies, errors) = if read {
+                            (&mut reads, &mut read_errors)
+                        } else {
+                            (&mut writes, &mut write_errors)
+                        };
+                        match outcome {
+                            Ok(()) => latencies.push(request_started.elapsed()),
+                            Err(_) => *errors += 1,
+                        }
+                    }
+                    (reads, read_errors, writes, write_errors)
+                })
+            })
+            .collect();
+
+        let (mut reads, mut writes) = (Vec::new(), Vec::new());
+        let (mut read_errors, mut write_errors) = (0, 0);
+        for worker in workers {
+            let outcome = worker.await.map_err(|e| e.to_string())?;
+            reads.extend(outcome.0);
+            read_errors += outcome.1;
+            writes.extend(outcome.2);
+            write_errors += outcome.3;
+        }
+        let elapsed = started.elapsed().as_secs_f64();
+        Ok(BenchReport {
+            throughput: (reads.len() + writes.len()) as f64 / elapsed.max(f64::EPSILON),
+            elapsed_secs: elapsed,
+            reads: LatencySummary::new(&mut reads, read_errors),
+            writes: LatencySummary::new(&mut writes, write_errors),
+            options: Arc::try_unwrap(bench)
+                .map(|bench| bench.options)
+                .unwrap_or_default(),
+        })
+    }
+
+    /// Reads a key, treating a missing key as a successful read.
+    async fn read(&self, key: &str) -> Result<(), String> {
+        let response = self
+            .client
+            .get(format!("{}/get_data", self.options.target))
+            .query(&[("table", self.options.table.as_str()), ("key", key)])
+            .send()
+            .await
+            .map_err(|e| e.to_string())?;
+        match response.status() {
+            status if status.is_success() || status == reqwest::StatusCode::NOT_FOUND => Ok(()),
+            status => Err(status.to_string()),
+        }
+    }
+
+    /// Writes a key.
+    async fn write(&self, key: &str, value: &str) -> Result<(), String> {
+        self.client
+            .post(format!("{}/set_data", self.options.target))
+            .json(&json!({"table": self.options.table, "key": key, "value": value}))
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map(|_| ())
+            .map_err(|e| e.to_string())
+    }
+}
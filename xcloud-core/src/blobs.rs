@@ -0,0 +1,153 @@
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::PathBuf;
+use std::time::SystemTime;
+
+/// A trait for stores holding immutable binary objects outside the database, named by the hex
+/// encoded SHA-256 of their content.
+///
+/// Objects are written once and never modified, so a name always refers to the same bytes.
+pub trait BlobStore: Send + Sync {
+    /// Stores an object unless an object of the same name is stored already.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the object.
+    /// * `bytes` - The content of the object.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the object cannot be stored durably.
+    fn put(&self, name: &str, bytes: &[u8]) -> io::Result<()>;
+
+    /// Reads an object.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the object.
+    ///
+    /// # Returns
+    ///
+    /// * `Vec<u8>` - The content of the object.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the object is missing or cannot be read.
+    fn get(&self, name: &str) -> io::Result<Vec<u8>>;
+
+    /// Lists the stored objects.
+    ///
+    /// # Returns
+    ///
+    /// * `Vec<(String, SystemTime)>` - The name of every object and when it was written.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the objects cannot be listed.
+    fn list(&self) -> io::Result<Vec<(String, SystemTime)>>;
+
+    /// Removes an object.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the object.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the object cannot be removed.
+    fn delete(&self, name: &str) -> io::Result<()>;
+}
+
+/// A struct storing objects as files in a local directory.
+///
+/// Objects are spread over two levels of directories named by the first four characters of
+/// their name, e.g. `ab/cd/abcd…`, so no directory grows too large. Every object is written to
+/// a partial file first, synced to disk and renamed, so an object is either complete or absent.
+pub struct FsBlobStore {
+    root: PathBuf,
+}
+
+/// Implementation of the `FsBlobStore` struct.
+impl FsBlobStore {
+    /// Creates a store in the given directory, which is created once an object is stored.
+    ///
+    /// # Arguments
+    ///
+    /// * `root` - The directory holding the objects.
+    ///
+    /// # Returns
+    ///
+    /// * `FsBlobStore` - A new instance of the FsBlobStore.
+    pub fn new(root: PathBuf) -> Self {
+        FsBlobStore { root }
+    }
+
+    /// Returns the path of the file of an object.
+    fn path(&self, name: &str) -> io::Result<PathBuf> {
+        if name.len() < 4 || !name.bytes().all(|byte| byte.is_ascii_alphanumeric()) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("invalid object name {}", name),
+            ));
+        }
+        Ok(self.root.join(&name[..2]).join(&name[2..4]).join(name))
+    }
+}
+
+/// Implementation of the `BlobStore` trait for the `FsBlobStore` struct.
+impl BlobStore for FsBlobStore {
+    fn put(&self, name: &str, bytes: &[u8]) -> io::Result<()> {
+        let path = self.path(name)?;
+        if path.exists() {
+            return Ok(());
+        }
+        let dir = path.parent().unwrap_or(&self.root);
+        fs::create_dir_all(dir)?;
+        let partial = path.with_extension("partial");
+        let mut file = File::create(&partial)?;
+        file.write_all(bytes)?;
+        file.sync_all()?;
+        fs::rename(&partial, &path)?;
+        #[cfg(unix)]
+        File::open(dir)?.sync_all()?;
+        Ok(())
+    }
+
+    fn get(&self, name: &str) -> io::Result<Vec<u8>> {
+        fs::read(self.path(name)?)
+    }
+
+    fn list(&self) -> io::Result<Vec<(String, SystemTime)>> {
+        let mut objects = Vec::new();
+        if !self.root.exists() {
+            return Ok(objects);
+        }
+        for first in fs::read_dir(&self.root)? {
+            let first = first?;
+            if !first.file_type()?.is_dir() {
+                continue;
+            }
+            for second in fs::read_dir(first.path())? {
+                let second = second?;
+                if !second.file_type()?.is_dir() {
+                    continue;
+                }
+                for entry in fs::read_dir(second.path())? {
+                    let entry = entry?;
+                    let Some(name) = entry.file_name().to_str().map(str::to_string) else {
+                        continue;
+                    };
+                    if name.contains('.') {
+                        continue;
+                    }
+                    objects.push((name, entry.metadata()?.modified()?));
+                }
+            }
+        }
+        Ok(objects)
+    }
+
+    fn delete(&self, name: &str) -> io::Result<()> {
+        fs::remove_file(self.path(name)?)
+    }
+}
@@ -0,0 +1,121 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::{Duration, Instant};
+
+use crate::config::CircuitBreakerConfig;
+use crate::db::Database;
+
+/// A struct representing the failures observed by the circuit breaker.
+#[derive(Default)]
+struct BreakerState {
+    /// The number of consecutive failed database operations.
+    failures: u32,
+    /// The instant until which requests are rejected, if the circuit is open.
+    open_until: Option<Instant>,
+}
+
+/// A struct rejecting requests while the database is unavailable.
+///
+/// The circuit opens once the configured number of consecutive database operations failed with
+/// transient errors. While open, requests are rejected without touching the database. Once the
+/// open period elapses, requests are let through again and the first failure reopens the circuit,
+/// while the first success closes it. A background probe closes it early when the database
+/// recovers.
+pub struct CircuitBreaker {
+    config: CircuitBreakerConfig,
+    state: Mutex<BreakerState>,
+    /// The number of times the circuit opened since the server started.
+    trips: AtomicU64,
+}
+
+/// Implementation of the `CircuitBreaker` struct.
+impl CircuitBreaker {
+    /// Creates a new, closed circuit breaker.
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - The circuit breaker configuration holding the threshold and the periods.
+    ///
+    /// # Returns
+    ///
+    /// * `CircuitBreaker` - A new instance of the CircuitBreaker.
+    pub fn new(config: CircuitBreakerConfig) -> Self {
+        CircuitBreaker {
+            config,
+            state: Mutex::new(BreakerState::default()),
+            trips: AtomicU64::new(0),
+        }
+    }
+
+    /// Returns whether requests are currently rejected.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<u64>` - The seconds until requests are let through again, or `None` if the
+    ///   circuit is closed.
+    pub fn open_for(&self) -> Option<u64> {
+        let state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        let remaining = state.open_until?.checked_duration_since(Instant::now())?;
+        Some(remaining.as_secs() + 1)
+    }
+
+    /// Returns the number of times the circuit opened since the server started.
+    pub fn trips(&self) -> u64 {
+        self.trips.load(Ordering::Relaxed)
+    }
+
+    /// Records a database operation that failed with a transient error.
+    pub fn record_failure(&self) {
+        if self.config.failure_threshold == 0 {
+            return;
+        }
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        state.failures = state.failures.saturating_add(1);
+        if state.failures >= self.config.failure_threshold {
+            if state.open_until.is_none_or(|until| until <= Instant::now()) {
+                self.trips.fetch_add(1, Ordering::Relaxed);
+                log::warn!(
+                    "Opening the circuit breaker after {} consecutive database failures",
+                    state.failures
+                );
+            }
+            state.open_until = Some(Instant::now() + Duration::from_secs(self.config.open_secs));
+        }
+    }
+
+    /// Records a successful database operation, closing the circuit.
+    pub fn record_success(&self) {
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        if state.open_until.take().is_some() {
+            log::info!("Closing the circuit breaker, the database recovered");
+        }
+        state.failures = 0;
+    }
+
+    /// Starts probing the database in the background while the circuit is open.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - The database to probe.
+    pub fn spawn_probe(self: &Arc<Self>, db: Arc<tokio::sync::Mutex<Database>>) {
+        if self.config.failure_threshold == 0 {
+            return;
+        }
+        let breaker = self.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(Duration::from_secs(
+                breaker.config.probe_interval_secs.max(1),
+            ));
+            loop {
+                interval.tick().await;
+                if breaker.open_for().is_none() {
+                    continue;
+                }
+                match db.lock().await.ping().await {
+                    Ok(()) => breaker.record_success(),
+                    Err(e) => log::debug!("Database still unavailable: {}", e),
+                }
+            }
+        });
+    }
+}
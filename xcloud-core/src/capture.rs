@@ -0,0 +1,203 @@
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::{Mutex, PoisonError};
+
+use actix_web::http::header::HeaderMap;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::config::{CaptureConfig, REDACTED};
+
+/// The headers never recorded in clear, as they carry credentials.
+const SENSITIVE_HEADERS: [&str; 4] = [
+    "authorization",
+    "cookie",
+    "set-cookie",
+    "x-xcloud-request-signature",
+];
+
+/// The body recorded in place of a streamed response body, which is never buffered.
+pub const STREAMED_BODY: &str = "<streamed>";
+
+/// A struct representing a recorded request with its response.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Capture {
+    /// The unix timestamp in milliseconds at which the request was received.
+    pub timestamp: i64,
+    pub method: String,
+    /// The path and the query string, if any.
+    pub path: String,
+    pub request_headers: BTreeMap<String, String>,
+    pub request_body: String,
+    pub status: u16,
+    pub response_body: String,
+    pub duration_ms: i64,
+}
+
+/// A struct keeping the most recent recorded exchanges in a ring buffer.
+#[derive(Default)]
+pub struct TrafficCapture {
+    entries: Mutex<VecDeque<Capture>>,
+}
+
+/// Implementation of the `TrafficCapture` struct.
+impl TrafficCapture {
+    /// Returns whether requests to a path are recorded.
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - The capture configuration.
+    /// * `path` - The path of the request.
+    ///
+    /// # Returns
+    ///
+    /// * `bool` - Whether the request is recorded.
+    pub fn captures(config: &CaptureConfig, path: &str) -> bool {
+        config.enabled
+            && !path.starts_with("/admin/captures")
+            && (config.routes.is_empty()
+                || config.routes.iter().any(|route| path.starts_with(route)))
+    }
+
+    /// Records an exchange, dropping the oldest once at capacity.
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - The capture configuration holding the capacity.
+    /// * `capture` - The sanitized exchange.
+    pub fn record(&self, config: &CaptureConfig, capture: Capture) {
+        let mut entries = self.entries.lock().unwrap_or_else(PoisonError::into_inner);
+        while entries.len() >= config.capacity.max(1) {
+            entries.pop_front();
+        }
+        entries.push_back(capture);
+    }
+
+    /// Returns the recorded exchanges, oldest first.
+    ///
+    /// # Returns
+    ///
+    /// * `Vec<Capture>` - The recorded exchanges.
+    pub fn entries(&self) -> Vec<Capture> {
+        self.entries
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .iter()
+            .cloned()
+            .collect()
+    }
+
+    /// Discards all recorded exchanges.
+    ///
+    /// # Returns
+    ///
+    /// * `usize` - The number of discarded exchanges.
+    pub fn clear(&self) -> usize {
+        let mut entries = self.entries.lock().unwrap_or_else(PoisonError::into_inner);
+        let cleared = entries.len();
+        entries.clear();
+        cleared
+    }
+
+    /// Copies the headers of a request, redacting those carrying credentials.
+    ///
+    /// # Arguments
+    ///
+    /// * `headers` - The headers of the request.
+    ///
+    /// # Returns
+    ///
+    /// * `BTreeMap<String, String>` - The sanitized headers by lowercase name.
+    pub fn sanitize_headers(headers: &HeaderMap) -> BTreeMap<String, String> {
+        headers
+            .iter()
+            .map(|(name, value)| {
+                let name = name.as_str().to_ascii_lowercase();
+                let value = if SENSITIVE_HEADERS.contains(&name.as_str()) {
+                    REDACTED.to_string()
+                } else {
+                    String::from_utf8_lossy(value.as_bytes()).into_owned()
+                };
+                (name, value)
+            })
+            .collect()
+    }
+
+    /// Redacts the values of the configured fields of the query string of a path.
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - The capture configuration holding the redacted fields.
+    /// * `path` - The path and the query string, if any.
+    ///
+    /// # Returns
+    ///
+    /// * `String` - The sanitized path.
+    pub fn sanitize_path(config: &CaptureConfig, path: &str) -> String {
+        let Some((path, query)) = path.split_once('?') else {
+            return path.to_string();
+        };
+        let query = query
+            .split('&')
+            .map(|pair| match pair.split_once('=') {
+                Some((name, _)) if Self::redacts(config, name) => format!("{}={}", name, REDACTED),
+                _ => pair.to_string(),
+            })
+            .collect::<Vec<_>>()
+            .join("&");
+        format!("{}?{}", path, query)
+    }
+
+    /// Redacts the values of the configured fields of a JSON body and cuts the body off at the
+    /// configured size.
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - The capture configuration holding the redacted fields and the size.
+    /// * `body` - The body of the request or response.
+    ///
+    /// # Returns
+    ///
+    /// * `String` - The sanitized body.
+    pub fn sanitize_body(config: &CaptureConfig, body: &[u8]) -> String {
+        let body = match serde_json::from_slice::<Value>(body) {
+            Ok(mut json) => {
+                Self::redact(config, &mut json);
+                json.to_string()
+            }
+            Err(_) => String::from_utf8_lossy(body).into_owned(),
+        };
+        if body.len() <= config.max_body_bytes {
+            return body;
+        }
+        let mut end = config.max_body_bytes;
+        while !body.is_char_boundary(end) {
+            end -= 1;
+        }
+        body[..end].to_string()
+    }
+
+    /// Redacts the values of the configured fields of a JSON value, at any depth.
+    fn redact(config: &CaptureConfig, value: &mut Value) {
+        match value {
+            Value::Object(fields) => {
+                for (name, field) in fields {
+                    if Self::redacts(config, name) {
+                        *field = Value::String(REDACTED.to_string());
+                    } else {
+                        Self::redact(config, field);
+                    }
+                }
+            }
+            Value::Array(items) => items.iter_mut().for_each(|item| Self::redact(config, item)),
+            _ => {}
+        }
+    }
+
+    /// Returns whether the value of a field is redacted.
+    fn redacts(config: &CaptureConfig, name: &str) -> bool {
+        config
+            .redact_fields
+            .iter()
+            .any(|field| field.eq_ignore_ascii_case(name))
+    }
+}
@@ -0,0 +1,146 @@
+use std::net::ToSocketAddrs;
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::Config;
+use crate::crypto::KeyRing;
+use crate::db::Database;
+use crate::errors::AppError;
+
+/// An enum representing the outcome of a single check.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum CheckStatus {
+    Passed,
+    Failed,
+    /// The check was not run because a check it depends on failed.
+    Skipped,
+}
+
+/// A struct representing the outcome of a single check.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Check {
+    pub name: String,
+    pub status: CheckStatus,
+    /// What was verified, or why the check failed or was skipped.
+    pub detail: String,
+}
+
+/// A struct representing the report of `xcloud check`, verifying that the server can start.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct CheckReport {
+    /// Whether every check passed.
+    pub passed: bool,
+    pub checks: Vec<Check>,
+}
+
+/// Implementation of the `CheckReport` struct.
+impl CheckReport {
+    /// Runs the checks against the given configuration.
+    ///
+    /// The checks validate the configuration and encryption keys, resolve the bind address and
+    /// connect to every shard and replica of the database, creating or upgrading the internal
+    /// tables the way the server does at startup.
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - The loaded configuration, or the reason it cannot be loaded.
+    ///
+    /// # Returns
+    ///
+    /// * `CheckReport` - The outcome of every check.
+    pub async fn run(config: Result<Config, AppError>) -> Self {
+        let mut report = CheckReport {
+            passed: true,
+            checks: Vec::new(),
+        };
+        let config = match config {
+            Ok(config) => {
+                report.record("config", Ok(format!("{:?}", Config::path())));
+                config
+            }
+            Err(e) => {
+                report.record("config", Err(e.to_string()));
+                for name in ["bind_address", "encryption", "database"] {
+                    report.skip(name, "the configuration cannot be loaded");
+                }
+                return report;
+            }
+        };
+        report.record(
+            "bind_address",
+            config
+                .bind_address
+                .to_socket_addrs()
+                .map(|_| config.bind_address.clone())
+                .map_err(|e| e.to_string()),
+        );
+        report.record(
+            "encryption",
+            KeyRing::from_config(&config.encryption)
+                .map(|keyring| match keyring {
+                    Some(_) => format!("{} key(s)", config.encryption.keys.len()),
+                    None => "disabled".to_string(),
+                })
+                .map_err(|e| e.to_string()),
+        );
+        report.record(
+            "database",
+            Database::new(&config.database)
+                .await
+                .map(|db| {
+                    format!(
+                        "{} shard(s), {} replica(s), internal tables up to date",
+                        db.pool_stats().len(),
+                        config.database.replicas.len()
+                    )
+                })
+                .map_err(|e| e.to_string()),
+        );
+        report
+    }
+
+    /// Renders the report for deployment pipelines.
+    ///
+    /// # Returns
+    ///
+    /// * `String` - The report as pretty-printed JSON.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap_or_default()
+    }
+
+    /// Records the outcome of a check.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the check.
+    /// * `outcome` - What was verified, or why the check failed.
+    fn record(&mut self, name: &str, outcome: Result<String, String>) {
+        let (status, detail) = match outcome {
+            Ok(detail) => (CheckStatus::Passed, detail),
+            Err(reason) => {
+                self.passed = false;
+                (CheckStatus::Failed, reason)
+            }
+        };
+        self.checks.push(Check {
+            name: name.to_string(),
+            status,
+            detail,
+        });
+    }
+
+    /// Records a check that was not run.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the check.
+    /// * `reason` - Why the check was not run.
+    fn skip(&mut self, name: &str, reason: &str) {
+        self.checks.push(Check {
+            name: name.to_string(),
+            status: CheckStatus::Skipped,
+            detail: reason.to_string(),
+        });
+    }
+}
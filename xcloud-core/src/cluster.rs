@@ -0,0 +1,183 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::ClusterConfig;
+use crate::db::{ClusterMember, Database};
+
+/// A struct representing the membership of a cluster as seen by an instance.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ClusterState {
+    /// Whether the instances compete for leadership.
+    pub enabled: bool,
+    /// The id of the instance answering.
+    pub instance_id: String,
+    /// The id of the instance running the background jobs, if any holds the leadership.
+    pub leader: Option<String>,
+    /// The instances that reported themselves within a lease.
+    pub members: Vec<ClusterMember>,
+}
+
+/// A struct electing, through a lease in the shared database, the single instance of a cluster
+/// that runs the background jobs, e.g. the expiry sweeps and the scheduled snapshots.
+///
+/// Every instance serves traffic. Each one reports itself at the configured interval and takes
+/// the leadership when the lease of the current leader expired, so a crashed leader is replaced
+/// within a lease. An instance that cannot reach the database steps down until it can.
+pub struct Cluster {
+    config: ClusterConfig,
+    instance_id: String,
+    address: String,
+    leader: AtomicBool,
+}
+
+/// Implementation of the `Cluster` struct.
+impl Cluster {
+    /// Creates a new instance of the Cluster.
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - The cluster configuration holding the instance id and the lease.
+    /// * `bind_address` - The address the server binds to, advertised unless configured.
+    ///
+    /// # Returns
+    ///
+    /// * `Cluster` - A new instance of the Cluster.
+    pub fn new(config: ClusterConfig, bind_address: &str) -> Self {
+        let instance_id = if config.instance_id.is_empty() {
+            uuid::Uuid::now_v7().to_string()
+        } else {
+            config.instance_id.clone()
+        };
+        let address = config
+            .advertise_address
+            .clone()
+            .unwrap_or_else(|| bind_address.to_string());
+        Cluster {
+            leader: AtomicBool::new(!config.enabled),
+            config,
+            instance_id,
+            address,
+        }
+    }
+
+    /// Returns the id of the instance.
+    pub fn instance_id(&self) -> &str {
+        &self.instance_id
+    }
+
+    /// Returns whether the instance runs the background jobs, always the case when clustering
+    /// is disabled.
+    pub fn is_leader(&self) -> bool {
+        !self.config.enabled || self.leader.load(Ordering::Relaxed)
+    }
+
+    /// Returns the number of seconds the leadership lasts without being renewed.
+    fn lease_secs(&self) -> i64 {
+        self.config.lease_secs.max(1) as i64
+    }
+
+    /// Reports the instance and renews or takes the leadership once.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - The database shared by the instances.
+    ///
+    /// # Returns
+    ///
+    /// * `bool` - Whether the instance is the leader.
+    pub async fn heartbeat(&self, db: &Database) -> bool {
+        let leader = match db
+            .cluster_heartbeat(&self.instance_id, &self.address, self.lease_secs())
+            .await
+        {
+            Ok(leader) => leader,
+            Err(e) => {
+                log::warn!("Failed to renew the cluster membership: {}", e);
+                false
+            }
+        };
+        if self.leader.swap(leader, Ordering::Relaxed) != leader {
+            if leader {
+                log::info!("Instance {} became the cluster leader", self.instance_id);
+            } else {
+                log::info!(
+                    "Instance {} stepped down as cluster leader",
+                    self.instance_id
+                );
+            }
+        }
+        leader
+    }
+
+    /// Reports the instance in the background at the configured interval, if clustering is
+    /// enabled.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - The database shared by the instances.
+    pub fn spawn(self: &Arc<Self>, db: Arc<tokio::sync::Mutex<Database>>) {
+        if !self.config.enabled {
+            return;
+        }
+        let cluster = self.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(Duration::from_secs(
+                cluster.config.heartbeat_interval_secs.max(1),
+            ));
+            loop {
+                interval.tick().await;
+                cluster.heartbeat(&*db.lock().await).await;
+            }
+        });
+    }
+
+    /// Gives up the leadership and the membership, so another instance takes over at its next
+    /// heartbeat.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - The database shared by the instances.
+    pub async fn leave(&self, db: &Database) {
+        if !self.config.enabled {
+            return;
+        }
+        self.leader.store(false, Ordering::Relaxed);
+        if let Err(e) = db.leave_cluster(&self.instance_id).await {
+            log::warn!("Failed to leave the cluster: {}", e);
+        }
+    }
+
+    /// Returns the membership of the cluster.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - The database shared by the instances.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the members cannot be retrieved.
+    pub async fn state(&self, db: &Database) -> Result<ClusterState, sqlx::Error> {
+        let members = if self.config.enabled {
+            db.cluster_members(self.lease_secs()).await?
+        } else {
+            Vec::new()
+        };
+        let leader = if self.config.enabled {
+            members
+                .iter()
+                .find(|member| member.leader)
+                .map(|member| member.instance_id.clone())
+        } else {
+            Some(self.instance_id.clone())
+        };
+        Ok(ClusterState {
+            enabled: self.config.enabled,
+            instance_id: self.instance_id.clone(),
+            leader,
+            members,
+        })
+    }
+}
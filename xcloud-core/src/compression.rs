@@ -0,0 +1,92 @@
+use std::io;
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+
+use crate::config::CompressionConfig;
+
+/// The codec compressed values are stored with, recorded in the `codec` column of their row.
+pub const CODEC: &str = "zstd";
+
+/// A struct compressing values above a size threshold before they are stored.
+///
+/// Compressed values are stored as the base64 encoded zstd frame, with the `codec` column of
+/// their row set to [`CODEC`]. Values below the threshold, and values that do not shrink, are
+/// stored as is without a codec.
+pub struct Compressor {
+    threshold: usize,
+    level: i32,
+}
+
+/// Implementation of the `Compressor` struct.
+impl Compressor {
+    /// Creates a compressor from the compression configuration.
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - The compression configuration holding the threshold and level.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<Compressor>` - The compressor, or `None` if compression is disabled.
+    pub fn from_config(config: &CompressionConfig) -> Option<Self> {
+        config.enabled.then_some(Compressor {
+            threshold: config.threshold_bytes,
+            level: config.level,
+        })
+    }
+
+    /// Compresses the given value if it is at least as large as the threshold.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The value to compress.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<String>` - The value compressed with [`CODEC`], or `None` if compression does
+    ///   not pay off.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the value cannot be compressed.
+    pub fn compress(&self, value: &str) -> io::Result<Option<String>> {
+        if value.len() < self.threshold {
+            return Ok(None);
+        }
+        let compressed = STANDARD.encode(zstd::encode_all(value.as_bytes(), self.level)?);
+        Ok((compressed.len() < value.len()).then_some(compressed))
+    }
+
+    /// Decompresses a stored value with the codec recorded for it.
+    ///
+    /// # Arguments
+    ///
+    /// * `codec` - The codec the value is compressed with, or `None` if it is stored as is.
+    /// * `stored` - The stored value.
+    ///
+    /// # Returns
+    ///
+    /// * `String` - The original value.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the codec is unknown or the value is no valid
+    /// compressed value.
+    pub fn decompress(codec: Option<&str>, stored: String) -> io::Result<String> {
+        match codec {
+            None => Ok(stored),
+            Some(CODEC) => {
+                let bytes = STANDARD
+                    .decode(stored)
+                    .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+                String::from_utf8(zstd::decode_all(bytes.as_slice())?)
+                    .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+            }
+            Some(codec) => Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("unknown codec {}", codec),
+            )),
+        }
+    }
+}
@@ -0,0 +1,313 @@
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+use tokio::sync::{Notify, OwnedSemaphorePermit, Semaphore};
+
+use crate::config::ConcurrencyConfig;
+
+/// The header choosing the traffic class of a request, `interactive` or `bulk`.
+pub const PRIORITY_HEADER: &str = "X-xCLOUD-Priority";
+
+/// An enum representing the class of traffic a request belongs to.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum TrafficClass {
+    /// Latency-sensitive requests, such as single gets and sets, never held back.
+    Interactive,
+    /// Long-running requests, such as exports and imports, held back for interactive ones.
+    Bulk,
+}
+
+/// A struct representing the queue depth and latency of a traffic class.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct LaneStats {
+    pub class: TrafficClass,
+    /// The number of requests waiting to be admitted.
+    pub queued: usize,
+    /// The number of requests being served.
+    pub in_flight: usize,
+    /// The number of requests served since the server started.
+    pub served: u64,
+    /// The moving average of the latency of the served requests in milliseconds.
+    pub latency_ms: f64,
+}
+
+/// A struct counting the requests of a traffic class.
+#[derive(Default)]
+struct Lane {
+    queued: AtomicUsize,
+    in_flight: AtomicUsize,
+    served: AtomicU64,
+    /// The moving average of the latency in microseconds, `0` until a request is served.
+    latency_us: AtomicU64,
+}
+
+/// Implementation of the `Lane` struct.
+impl Lane {
+    /// Folds the latency of a served request into the moving average.
+    fn record(&self, latency: Duration) {
+        let sample = latency.as_micros().min(u64::MAX as u128) as u64;
+        let _ = self
+            .latency_us
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |average| {
+                Some(match average {
+                    0 => sample.max(1),
+                    _ => (average - average / 8 + sample / 8).max(1),
+                })
+            });
+        self.served.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Returns the moving average of the latency.
+    fn latency(&self) -> Duration {
+        Duration::from_micros(self.latency_us.load(Ordering::Relaxed))
+    }
+}
+
+/// A struct representing an admitted request, counted as in flight in its lane until dropped.
+pub struct LaneTicket {
+    limits: Arc<ConcurrencyLimits>,
+    class: TrafficClass,
+    started: Instant,
+    _permit: Option<OwnedSemaphorePermit>,
+}
+
+/// Implementation of the `LaneTicket` struct.
+impl LaneTicket {
+    /// Returns the traffic class the request was admitted in.
+    ///
+    /// # Returns
+    ///
+    /// * `TrafficClass` - The class of the request.
+    pub fn class(&self) -> TrafficClass {
+        self.class
+    }
+}
+
+/// Implementation of the `Drop` trait for the `LaneTicket` struct.
+impl Drop for LaneTicket {
+    fn drop(&mut self) {
+        let lane = self.limits.lane(self.class);
+        lane.in_flight.fetch_sub(1, Ordering::Relaxed);
+        lane.record(self.started.elapsed());
+        if self.class == TrafficClass::Interactive {
+            self.limits.interactive_done.notify_waiters();
+        }
+    }
+}
+
+/// A struct representing a group of routes sharing a concurrency limit.
+struct RouteGroup {
+    name: String,
+    routes: Vec<String>,
+    permits: Arc<Semaphore>,
+}
+
+/// A struct limiting the number of requests served concurrently per group of routes, so
+/// expensive operations cannot starve latency-sensitive ones.
+///
+/// The groups are read from the configuration at startup. A request waits up to the configured
+/// queue timeout for a free slot of its group and is rejected once it elapses. Requests to
+/// routes outside every group are not limited.
+///
+/// Independently of the groups, every request is admitted in the lane of its traffic class.
+/// Interactive requests are admitted at once. Bulk requests are limited in number and, while
+/// interactive requests are in flight and their average latency exceeds the target, held back
+/// until the interactive lane catches up, so exports and imports cannot crowd the database.
+pub struct ConcurrencyLimits {
+    groups: Vec<RouteGroup>,
+    queue_timeout: Duration,
+    bulk_routes: Vec<String>,
+    bulk_permits: Arc<Semaphore>,
+    bulk_queue_timeout: Duration,
+    interactive_target: Duration,
+    interactive: Lane,
+    bulk: Lane,
+    /// Wakes the held back bulk requests whenever an interactive request is served.
+    interactive_done: Notify,
+}
+
+/// Implementation of the `ConcurrencyLimits` struct.
+impl ConcurrencyLimits {
+    /// Creates the limits of the configured groups.
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - The concurrency configuration holding the groups and the queue timeout.
+    ///
+    /// # Returns
+    ///
+    /// * `ConcurrencyLimits` - A new instance of the ConcurrencyLimits.
+    pub fn new(config: &ConcurrencyConfig) -> Self {
+        let mut groups: Vec<RouteGroup> = config
+            .groups
+            .iter()
+            .map(|(name, group)| RouteGroup {
+                name: name.clone(),
+                routes: group.routes.clone(),
+                permits: Arc::new(Semaphore::new(group.max_concurrent.max(1))),
+            })
+            .collect();
+        groups.sort_by(|a, b| a.name.cmp(&b.name));
+        ConcurrencyLimits {
+            groups,
+            queue_timeout: Duration::from_millis(config.queue_timeout_ms),
+            bulk_routes: config.lanes.bulk_routes.clone(),
+            bulk_permits: Arc::new(Semaphore::new(config.lanes.max_bulk_concurrent.max(1))),
+            bulk_queue_timeout: Duration::from_millis(config.lanes.queue_timeout_ms),
+            interactive_target: Duration::from_millis(config.lanes.interactive_target_ms),
+            interactive: Lane::default(),
+            bulk: Lane::default(),
+            interactive_done: Notify::new(),
+        }
+    }
+
+    /// Waits for a free slot of the group of the given path.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path of the request.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Option<OwnedSemaphorePermit>, String>` - The slot, held until it is dropped,
+    ///   `None` if the path is not limited, or the name of the group that stayed full.
+    pub async fn acquire(&self, path: &str) -> Result<Option<OwnedSemaphorePermit>, String> {
+        let Some(group) = self
+            .groups
+            .iter()
+            .find(|group| Self::matches(&group.routes, path))
+        else {
+            return Ok(None);
+        };
+        match tokio::time::timeout(self.queue_timeout, group.permits.clone().acquire_owned()).await
+        {
+            Ok(Ok(permit)) => Ok(Some(permit)),
+            _ => Err(group.name.clone()),
+        }
+    }
+
+    /// Classifies a request into a traffic class.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path of the request.
+    /// * `priority` - The value of the priority header, if any, overriding the route.
+    ///
+    /// # Returns
+    ///
+    /// * `TrafficClass` - The class of the request, bulk if its route is configured as bulk.
+    pub fn classify(&self, path: &str, priority: Option<&str>) -> TrafficClass {
+        match priority.map(|priority| priority.trim().to_ascii_lowercase()) {
+            Some(priority) if priority == "bulk" => TrafficClass::Bulk,
+            Some(priority) if priority == "interactive" => TrafficClass::Interactive,
+            _ if Self::matches(&self.bulk_routes, path) => TrafficClass::Bulk,
+            _ => TrafficClass::Interactive,
+        }
+    }
+
+    /// Admits a request in the lane of its traffic class, waiting while it is a bulk request
+    /// and the bulk lane is full or the interactive lane is behind its latency target.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path of the request.
+    /// * `priority` - The value of the priority header, if any.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<LaneTicket, TrafficClass>` - The admission, held until the request is served,
+    ///   or the class of the request if it was not admitted within the queue timeout.
+    pub async fn admit(
+        self: &Arc<Self>,
+        path: &str,
+        priority: Option<&str>,
+    ) -> Result<LaneTicket, TrafficClass> {
+        let class = self.classify(path, priority);
+        let lane = self.lane(class);
+        let mut permit = None;
+        if class == TrafficClass::Bulk {
+            lane.queued.fetch_add(1, Ordering::Relaxed);
+            let admitted =
+                tokio::time::timeout(self.bulk_queue_timeout, self.wait_for_bulk_slot()).await;
+            lane.queued.fetch_sub(1, Ordering::Relaxed);
+            permit = Some(admitted.ok().flatten().ok_or(class)?);
+        }
+        lane.in_flight.fetch_add(1, Ordering::Relaxed);
+        Ok(LaneTicket {
+            limits: self.clone(),
+            class,
+            started: Instant::now(),
+            _permit: permit,
+        })
+    }
+
+    /// Returns the queue depth and latency of every traffic class.
+    ///
+    /// # Returns
+    ///
+    /// * `Vec<LaneStats>` - The interactive lane followed by the bulk lane.
+    pub fn lane_stats(&self) -> Vec<LaneStats> {
+        [TrafficClass::Interactive, TrafficClass::Bulk]
+            .into_iter()
+            .map(|class| {
+                let lane = self.lane(class);
+                LaneStats {
+                    class,
+                    queued: lane.queued.load(Ordering::Relaxed),
+                    in_flight: lane.in_flight.load(Ordering::Relaxed),
+                    served: lane.served.load(Ordering::Relaxed),
+                    latency_ms: lane.latency().as_secs_f64() * 1000.0,
+                }
+            })
+            .collect()
+    }
+
+    /// Waits up to the bulk queue timeout until the interactive lane is idle or within its
+    /// latency target, so bulk work running past its request, such as the batches of an import,
+    /// holds the database only while interactive requests keep up.
+    pub async fn yield_to_interactive(&self) {
+        let _ = tokio::time::timeout(self.bulk_queue_timeout, self.wait_for_interactive()).await;
+    }
+
+    /// Waits for a free slot of the bulk lane, then until the interactive lane is idle or
+    /// within its latency target.
+    async fn wait_for_bulk_slot(&self) -> Option<OwnedSemaphorePermit> {
+        let permit = self.bulk_permits.clone().acquire_owned().await.ok()?;
+        self.wait_for_interactive().await;
+        Some(permit)
+    }
+
+    /// Waits until the interactive lane is idle or within its latency target.
+    async fn wait_for_interactive(&self) {
+        loop {
+            let served = self.interactive_done.notified();
+            if self.interactive.in_flight.load(Ordering::Relaxed) == 0
+                || self.interactive.latency() <= self.interactive_target
+            {
+                return;
+            }
+            served.await;
+        }
+    }
+
+    /// Returns the lane of a traffic class.
+    fn lane(&self, class: TrafficClass) -> &Lane {
+        match class {
+            TrafficClass::Interactive => &self.interactive,
+            TrafficClass::Bulk => &self.bulk,
+        }
+    }
+
+    /// Checks whether a path is one of the routes or below one of them.
+    fn matches(routes: &[String], path: &str) -> bool {
+        routes.iter().any(|route| {
+            path == route
+                || path
+                    .strip_prefix(route.as_str())
+                    .is_some_and(|rest| rest.starts_with('/'))
+        })
+    }
+}
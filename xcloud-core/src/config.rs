@@ -0,0 +1,1342 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::errors::AppError;
+use crate::utils::Utils;
+
+/// The placeholder replacing secrets in exposed configuration.
+pub const REDACTED: &str = "<redacted>";
+
+/// A struct representing the configuration of the application.
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct Config {
+    pub bind_address: String,
+    pub activity: ActivityConfig,
+    pub admin: AdminConfig,
+    pub alerts: AlertConfig,
+    pub capture: CaptureConfig,
+    pub circuit_breaker: CircuitBreakerConfig,
+    pub cluster: ClusterConfig,
+    pub compression: CompressionConfig,
+    pub concurrency: ConcurrencyConfig,
+    pub cors: CorsConfig,
+    pub database: DatabaseConfig,
+    pub dedup: DedupConfig,
+    pub encryption: EncryptionConfig,
+    pub health: HealthConfig,
+    pub history: HistoryConfig,
+    pub ids: IdConfig,
+    pub index_advisor: IndexAdvisorConfig,
+    pub integrations: IntegrationConfig,
+    pub key_stats: KeyStatsConfig,
+    pub limits: LimitsConfig,
+    pub locks: LocksConfig,
+    pub logging: LoggingConfig,
+    pub metrics: MetricsConfig,
+    pub mqtt: MqttConfig,
+    pub privacy: PrivacyConfig,
+    pub replication: ReplicationConfig,
+    pub request_signing: RequestSigningConfig,
+    pub retention: RetentionConfig,
+    pub retry: RetryConfig,
+    pub runtime: RuntimeConfig,
+    pub seeds: SeedConfig,
+    pub signed_urls: SignedUrlConfig,
+    pub slow_queries: SlowQueryConfig,
+    pub snapshots: SnapshotConfig,
+    pub sql_console: SqlConsoleConfig,
+    pub tiering: TieringConfig,
+    pub write_behind: WriteBehindConfig,
+}
+
+/// A struct representing the feed of recent writes.
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct ActivityConfig {
+    /// Whether writes are recorded in the `xcloud_activity` table.
+    pub enabled: bool,
+    /// The number of most recent writes kept.
+    pub max_entries: i64,
+}
+
+/// A struct representing the admin features of the server.
+#[derive(Serialize, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct AdminConfig {
+    /// Whether the embedded admin dashboard is served at `/admin`.
+    pub dashboard: bool,
+    /// The base32-encoded secret of the time-based one-time passwords confirming the lifting of
+    /// legal holds, which cannot be lifted if empty.
+    pub hold_totp_secret: String,
+}
+
+/// A struct representing the notification of operators when the server fails or nears its
+/// limits.
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct AlertConfig {
+    /// The interval in seconds at which the thresholds are checked.
+    pub interval_secs: u64,
+    /// The number of seconds during which an alert is not sent again, even if its condition
+    /// persists or reoccurs.
+    pub cooldown_secs: u64,
+    /// The share of the requests served since the previous check answered with a `5xx` status
+    /// above which an alert is sent.
+    pub error_rate: f64,
+    /// The number of requests served since the previous check below which the error rate is
+    /// not considered.
+    pub min_requests: u64,
+    /// The approximate size in bytes the keys and values of all tables are allowed to take,
+    /// unlimited if unset.
+    pub storage_quota_bytes: Option<u64>,
+    /// The share of the storage quota above which an alert is sent.
+    pub quota_warn_ratio: f64,
+    /// The channels the alerts are sent to, alerting is disabled if empty.
+    pub channels: Vec<AlertChannel>,
+}
+
+/// An enum representing a channel alerts are sent to.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum AlertChannel {
+    /// A URL receiving the alerts as JSON with `POST`.
+    Webhook { url: String },
+    /// A Slack-compatible incoming webhook receiving the alerts as `text` messages.
+    Slack { url: String },
+    /// An SMTP relay emailing the alerts, connected to with STARTTLS.
+    Smtp {
+        host: String,
+        #[serde(default = "AlertChannel::default_smtp_port")]
+        port: u16,
+        #[serde(default)]
+        username: Option<String>,
+        #[serde(default)]
+        password: String,
+        from: String,
+        to: Vec<String>,
+    },
+}
+
+/// A struct representing the recording of requests and responses for reproducing issues.
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct CaptureConfig {
+    /// Whether requests and their responses are recorded, for debugging only.
+    pub enabled: bool,
+    /// The path prefixes of the recorded routes, all routes if empty.
+    pub routes: Vec<String>,
+    /// The number of most recent exchanges kept in memory.
+    pub capacity: usize,
+    /// The number of bytes of a body kept, the rest being cut off.
+    pub max_body_bytes: usize,
+    /// The fields of JSON bodies whose values are redacted, at any depth.
+    pub redact_fields: Vec<String>,
+}
+
+/// A struct representing the rejection of requests during database outages.
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct CircuitBreakerConfig {
+    /// The number of consecutive transient database failures opening the circuit, disabled if zero.
+    pub failure_threshold: u32,
+    /// The number of seconds requests are rejected once the circuit opened.
+    pub open_secs: u64,
+    /// The interval in seconds at which the database is probed while the circuit is open.
+    pub probe_interval_secs: u64,
+}
+
+/// A struct representing the election of the instance running the background jobs among the
+/// instances sharing a database.
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct ClusterConfig {
+    /// Whether the instance competes for leadership, otherwise it always runs the background
+    /// jobs as the only instance.
+    pub enabled: bool,
+    /// The unique id of the instance, generated at startup if empty.
+    pub instance_id: String,
+    /// The address other instances and operators reach the instance at, the bind address if
+    /// unset.
+    pub advertise_address: Option<String>,
+    /// The number of seconds the leadership lasts without being renewed.
+    pub lease_secs: u64,
+    /// The interval in seconds at which the instance reports itself and renews its leadership.
+    pub heartbeat_interval_secs: u64,
+}
+
+/// A struct representing the compression of large stored values.
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct CompressionConfig {
+    /// Whether values are compressed before they are stored.
+    pub enabled: bool,
+    /// The size in bytes from which a value is compressed.
+    pub threshold_bytes: usize,
+    /// The zstd compression level.
+    pub level: i32,
+}
+
+/// A struct representing the limits of concurrently served requests, read at startup.
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct ConcurrencyConfig {
+    /// The groups of routes sharing a limit, keyed by name.
+    pub groups: HashMap<String, RouteGroupConfig>,
+    /// The number of milliseconds a request waits for a free slot before it is rejected.
+    pub queue_timeout_ms: u64,
+    /// The lanes separating interactive from bulk traffic.
+    pub lanes: LanesConfig,
+}
+
+/// A struct representing the lanes scheduling interactive and bulk traffic.
+///
+/// A request chooses its class with the `X-xCLOUD-Priority` header, `interactive` or `bulk`,
+/// and otherwise falls in the bulk lane if its route is a bulk route.
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct LanesConfig {
+    /// The paths of the bulk routes, each also covering the paths below it.
+    pub bulk_routes: Vec<String>,
+    /// The maximum number of bulk requests served at the same time.
+    pub max_bulk_concurrent: usize,
+    /// The average latency in milliseconds of the interactive requests above which bulk
+    /// requests are held back while interactive ones are in flight.
+    pub interactive_target_ms: u64,
+    /// The number of milliseconds a bulk request waits to be admitted before it is rejected.
+    pub queue_timeout_ms: u64,
+}
+
+/// A struct representing a group of routes sharing a concurrency limit.
+#[derive(Serialize, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct RouteGroupConfig {
+    /// The paths of the routes, each also covering the paths below it.
+    pub routes: Vec<String>,
+    /// The maximum number of requests to the routes served at the same time.
+    pub max_concurrent: usize,
+}
+
+/// A struct representing the CORS policy of the server.
+#[derive(Serialize, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct CorsConfig {
+    /// The origins allowed to call the API, no origin is allowed if empty.
+    pub allowed_origins: Vec<String>,
+    /// The policy of the read routes, replacing the allowed origins if set.
+    pub read: Option<CorsPolicy>,
+    /// The policy of the write routes, replacing the allowed origins if set.
+    pub write: Option<CorsPolicy>,
+    /// The policy of the admin routes, which cannot be called from other origins if unset.
+    pub admin: Option<CorsPolicy>,
+}
+
+/// A struct representing the CORS policy of a scope of routes.
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct CorsPolicy {
+    /// Whether the routes may be called from other origins at all.
+    pub enabled: bool,
+    /// The origins allowed to call the routes, no origin is allowed if empty.
+    pub allowed_origins: Vec<String>,
+}
+
+/// An enum representing a scope of routes sharing a CORS policy.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum CorsScope {
+    /// The routes reading data.
+    Read,
+    /// The routes changing data.
+    Write,
+    /// The admin routes.
+    Admin,
+}
+
+/// A struct representing the database connections of the server.
+#[derive(Serialize, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct DatabaseConfig {
+    /// The backend storing the key-value pairs of the tables.
+    pub backend: StorageBackend,
+    /// The URL of the primary database, defaults to a SQLite file in the data directory.
+    pub url: Option<String>,
+    /// The URLs of additional shards, tables are spread over the primary and these by consistent
+    /// hashing of their names. Changing a URL moves the tables assigned to it.
+    pub shards: Vec<String>,
+    /// The URLs of the regional databases by region name. Tables tagged with a region are
+    /// stored in its database instead of the shards.
+    pub regions: HashMap<String, String>,
+    /// The URLs of the read replicas of the primary.
+    pub replicas: Vec<String>,
+    /// The strategy used to pick a read replica.
+    pub replica_selection: ReplicaSelection,
+    /// The collation of the keys of newly created tables. Existing tables keep theirs.
+    pub key_collation: KeyCollation,
+    /// Whether newly created tables get an index on their values, speeding up searches and
+    /// filters on values at the cost of slower writes.
+    pub index_values: bool,
+}
+
+/// An enum representing the collation used to compare the keys of a table.
+///
+/// SQLite has no storage engines or character sets, all text is stored as UTF-8.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum KeyCollation {
+    /// Keys are compared byte by byte, so keys differing only by case are distinct.
+    #[default]
+    Binary,
+    /// Keys differing only by the case of ASCII letters are the same key.
+    Nocase,
+    /// Keys differing only by trailing spaces are the same key.
+    Rtrim,
+}
+
+/// Implementation of the `KeyCollation` enum.
+impl KeyCollation {
+    /// Returns the name of the collation in SQL.
+    ///
+    /// # Returns
+    ///
+    /// * `&str` - The name of the collation.
+    pub fn as_sql(&self) -> &'static str {
+        match self {
+            KeyCollation::Binary => "BINARY",
+            KeyCollation::Nocase => "NOCASE",
+            KeyCollation::Rtrim => "RTRIM",
+        }
+    }
+}
+
+/// An enum representing the strategy used to pick a read replica.
+#[derive(Serialize, Deserialize, Clone, Copy, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ReplicaSelection {
+    #[default]
+    RoundRobin,
+    LeastLoaded,
+}
+
+/// An enum representing the backend storing the key-value pairs of the tables.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum StorageBackend {
+    /// The tables are stored in SQLite at the configured URLs.
+    #[default]
+    Sqlite,
+    /// The tables are kept in memory and lost when the server stops, for demos and tests.
+    /// Internal tables are kept in an in-memory SQLite database, shards and replicas are unused.
+    Memory,
+}
+
+/// A struct representing the deduplication of identical stored values.
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct DedupConfig {
+    /// Whether identical values are stored once in the `xcloud_values` table.
+    pub enabled: bool,
+    /// The size in bytes from which a value is deduplicated.
+    pub min_bytes: usize,
+}
+
+/// A struct representing the encryption at rest of stored values.
+#[derive(Serialize, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct EncryptionConfig {
+    /// Whether values are encrypted before they are stored.
+    pub enabled: bool,
+    /// The base64 encoded 256-bit key encryption keys, keyed by version.
+    pub keys: HashMap<String, String>,
+    /// The version of the key used to encrypt new values.
+    pub active_key: u32,
+}
+
+/// A struct representing the behavior of the startup, liveness and readiness probes.
+#[derive(Serialize, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct HealthConfig {
+    /// Whether the liveness probe fails while the database is unreachable, so the orchestrator
+    /// restarts the server.
+    pub liveness_checks_database: bool,
+    /// Whether the readiness probe fails while the server is read-only for maintenance.
+    pub unready_when_read_only: bool,
+}
+
+/// A struct representing the versioned history kept of stored values.
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct HistoryConfig {
+    /// The number of versions kept per key, history is disabled if zero.
+    pub depth: usize,
+    /// Per table overrides of the depth, keyed by table name.
+    pub tables: HashMap<String, usize>,
+    /// The age in seconds after which versions are pruned, versions are kept if unset.
+    pub retention_secs: Option<u64>,
+    /// The number of seconds between two sweeps of expired versions.
+    pub sweep_interval_secs: u64,
+    /// Whether versions older than the newest are stored as deltas against the next version.
+    pub deltas: bool,
+    /// The age in seconds beyond which the versions of a key are collapsed into those current
+    /// at the snapshots and at the start of the window, versions are not compacted if unset.
+    pub compact_after_secs: Option<u64>,
+    /// Per table overrides of the compaction age, keyed by table name.
+    pub compact_tables: HashMap<String, u64>,
+}
+
+/// A struct representing the recommendation of indexes for recurring slow queries.
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct IndexAdvisorConfig {
+    /// Whether the recommended indexes are created automatically.
+    pub auto_create: bool,
+    /// The number of seconds between two automatic creations.
+    pub interval_secs: u64,
+    /// The number of times a query must have been slow to get a recommendation.
+    pub min_occurrences: usize,
+}
+
+/// A struct representing the polling of the external HTTP sources of the integrations.
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct IntegrationConfig {
+    /// The interval in seconds at which due integrations are looked up.
+    pub tick_secs: u64,
+    /// The number of seconds after which a request to a source is abandoned.
+    pub timeout_secs: u64,
+}
+
+/// A struct representing the tracking of reads and writes per key.
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct KeyStatsConfig {
+    /// Whether the reads and writes of every key are counted in the `xcloud_key_stats` table.
+    pub enabled: bool,
+    /// The number of seconds the counters are buffered in memory before they are written.
+    pub flush_interval_secs: u64,
+}
+
+/// A struct representing the request size limits of the server.
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct LimitsConfig {
+    /// The maximum size in bytes of any request body.
+    pub max_body_size: usize,
+    /// The default maximum size in bytes of a JSON payload.
+    pub json_payload: usize,
+    /// Per route overrides of the JSON payload limit, keyed by path.
+    pub routes: HashMap<String, usize>,
+}
+
+/// A struct representing the leases editors take on keys.
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct LocksConfig {
+    /// Whether writes to a key locked by another owner are rejected.
+    pub enforce: bool,
+    /// The lease duration in seconds used if a request does not specify one.
+    pub default_lease_secs: u64,
+    /// The longest lease duration in seconds a request may ask for.
+    pub max_lease_secs: u64,
+}
+
+/// An enum representing the format of the keys generated for appended values.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum IdFormat {
+    /// A time-ordered UUID as defined by RFC 9562.
+    #[default]
+    UuidV7,
+    /// A time-ordered identifier in Crockford's base32.
+    Ulid,
+    /// A 64-bit integer of the milliseconds, node id and sequence, zero-padded to 20 digits.
+    Snowflake,
+}
+
+/// A struct representing the generation of the keys of appended values.
+#[derive(Serialize, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct IdConfig {
+    pub format: IdFormat,
+    /// The id of this instance within snowflake ids, from 0 to 1023, unique per instance
+    /// appending to the same tables.
+    pub node_id: u16,
+}
+
+/// An enum representing the output format of the logs.
+#[derive(Serialize, Deserialize, Clone, Copy, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum LogFormat {
+    #[default]
+    Pretty,
+    Json,
+}
+
+/// A struct representing the logging configuration.
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct LoggingConfig {
+    /// The initial log filter, overridden by `RUST_LOG` if set.
+    pub level: String,
+    /// The output format of the logs.
+    pub format: LogFormat,
+}
+
+/// A struct representing the exporters pushing the metrics of the server.
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct MetricsConfig {
+    /// The base URL of the Prometheus Pushgateway receiving the metrics, disabled if unset.
+    pub pushgateway: Option<String>,
+    /// The job the metrics are grouped under in the Pushgateway.
+    pub job: String,
+    /// The `host:port` of the StatsD daemon receiving the metrics over UDP, disabled if unset.
+    pub statsd: Option<String>,
+    /// The prefix of the StatsD metric names.
+    pub statsd_prefix: String,
+    /// The number of seconds between two pushes.
+    pub push_interval_secs: u64,
+}
+
+/// A struct representing the bridge between an MQTT broker and the tables.
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct MqttConfig {
+    /// The `host:port` of the broker, the bridge is disabled if unset.
+    pub broker: Option<String>,
+    /// The id the bridge connects to the broker with.
+    pub client_id: String,
+    pub username: Option<String>,
+    pub password: String,
+    /// The number of seconds between two pings of an idle connection.
+    pub keep_alive_secs: u64,
+    /// The quality of service of the subscriptions and published events, from 0 to 2.
+    pub qos: u8,
+    /// The rules writing the messages of matching topics into tables, the first match wins.
+    pub routes: Vec<MqttRoute>,
+    /// The topic under which changes are published as `<topic>/<table>/<key>`, disabled if
+    /// unset. Messages under it are never written back.
+    pub publish_topic: Option<String>,
+}
+
+/// A struct representing a rule writing the messages of matching topics into a table.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct MqttRoute {
+    /// The topic filter, where `+` matches one level and a trailing `#` any number of levels.
+    pub filter: String,
+    /// The table the messages are written into.
+    pub table: String,
+    /// The key template, where `{topic}` is replaced by the topic and `{0}`, `{1}`, ... by its
+    /// levels.
+    #[serde(default = "MqttRoute::default_key")]
+    pub key: String,
+}
+
+/// A struct representing the locating of the data of a person for privacy requests.
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct PrivacyConfig {
+    /// The top-level fields of JSON values naming the person a row belongs to.
+    pub owner_fields: Vec<String>,
+}
+
+/// A struct representing the replication of writes to a peer instance.
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct ReplicationConfig {
+    /// The base URL of the peer receiving the writes, replication is disabled if unset.
+    pub peer: Option<String>,
+    /// The secret signing the batches, shared by both instances.
+    pub secret: String,
+    /// The maximum number of changes sent in one batch.
+    pub batch_size: i64,
+    /// The number of seconds between two polls of the outbox.
+    pub interval_secs: u64,
+    /// How a receiving instance resolves writes to keys it already holds.
+    pub conflict: ConflictPolicy,
+}
+
+/// An enum representing how replicated writes to existing keys are resolved.
+#[derive(Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum ConflictPolicy {
+    /// Replicated writes always overwrite local values.
+    #[default]
+    SourceWins,
+    /// Replicated writes only create missing keys and never overwrite or delete local values.
+    TargetWins,
+}
+
+/// A struct representing the HMAC signatures mutating requests must carry.
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct RequestSigningConfig {
+    /// Whether requests other than `GET`, `HEAD` and `OPTIONS` must be signed.
+    pub enabled: bool,
+    /// The secrets of the clients signing requests, keyed by client id.
+    pub clients: HashMap<String, String>,
+    /// The number of seconds the timestamp of a signed request may differ from the clock.
+    pub max_skew_secs: u64,
+}
+
+/// A struct representing the enforcement of the per-table retention policies.
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct RetentionConfig {
+    /// The interval in seconds at which the retention policies are applied and the time series
+    /// downsampled.
+    pub sweep_interval_secs: u64,
+}
+
+/// A struct representing the retrying of database operations failing with transient errors.
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct RetryConfig {
+    /// The number of attempts of an operation, operations are not retried if at most one.
+    pub max_attempts: u32,
+    /// The delay in milliseconds before the first retry, doubled for every following retry.
+    pub base_delay_ms: u64,
+    /// The maximum delay in milliseconds between two attempts.
+    pub max_delay_ms: u64,
+}
+
+/// A struct representing the thread pools of the server, applied at startup.
+#[derive(Serialize, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct RuntimeConfig {
+    /// The number of HTTP workers, one per available core if unset.
+    pub http_workers: Option<usize>,
+    /// The maximum number of threads of the blocking pool of every HTTP worker, 512 divided by
+    /// the number of available cores if unset.
+    pub max_blocking_threads: Option<usize>,
+}
+
+/// A struct representing the fixtures seeding the database.
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct SeedConfig {
+    /// Whether the fixtures are applied when the server starts.
+    pub on_startup: bool,
+    /// The directory of the JSON and TOML fixture files, the `seeds` directory next to the
+    /// configuration file if unset.
+    pub dir: Option<String>,
+}
+
+/// A struct representing the signing of URLs granting temporary public reads.
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct SignedUrlConfig {
+    /// The secret signing the URLs, signed URLs are disabled if empty.
+    pub secret: String,
+    /// The lifetime in seconds of URLs minted without an explicit one.
+    pub default_ttl_secs: u64,
+    /// The longest lifetime in seconds a URL may be minted with.
+    pub max_ttl_secs: u64,
+}
+
+/// A struct representing the recording of slow database queries.
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct SlowQueryConfig {
+    /// The duration in milliseconds from which a query is considered slow.
+    pub threshold_ms: u64,
+    /// The number of slow queries kept in memory.
+    pub capacity: usize,
+    /// Whether slow queries are also written to the `xcloud_slow_queries` table.
+    pub persist: bool,
+}
+
+/// A struct representing the scheduled snapshots of the tables.
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct SnapshotConfig {
+    /// The interval in seconds at which snapshots are captured, disabled if zero.
+    pub interval_secs: u64,
+    /// The tables captured by scheduled snapshots, all tables if empty.
+    pub tables: Vec<String>,
+    /// The number of most recent scheduled snapshots kept.
+    pub keep: usize,
+}
+
+/// A struct representing the admin console running read-only SQL against the primary.
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct SqlConsoleConfig {
+    /// Whether `/admin/sql` accepts statements.
+    pub enabled: bool,
+    /// The longest time in milliseconds a statement may run, requests may only lower it.
+    pub timeout_ms: u64,
+    /// The largest number of rows returned, requests may only lower it.
+    pub max_rows: usize,
+}
+
+/// A struct representing the archiving of cold keys to a slower storage tier.
+///
+/// The last access of a key is taken from the key statistics, which must be enabled for keys to
+/// be found cold. Archived values are only readable while tiering is enabled, so reading an
+/// archived key fails once it is disabled until it is enabled again.
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct TieringConfig {
+    /// Whether keys not accessed for `cold_after_days` are archived in the background.
+    pub enabled: bool,
+    /// The directory holding the archives in two levels of subdirectories, e.g. a mounted
+    /// object storage bucket shared by every instance, defaults to the `archive` directory next
+    /// to the configuration file.
+    pub dir: Option<String>,
+    /// The number of days without a read or a write after which a key is archived.
+    pub cold_after_days: u64,
+    /// The interval in seconds at which cold keys are looked for.
+    pub interval_secs: u64,
+    /// The largest number of keys archived per run.
+    pub batch_size: usize,
+}
+
+/// A struct representing the coalescing of rapid writes to the same keys in memory.
+///
+/// Writes to the configured tables are acknowledged once buffered, and only the latest value of
+/// every key is written when flushed, so the intermediate values are never recorded in the
+/// history, the activity feed or the outbox. Buffered writes are lost if the process crashes
+/// before they are flushed. Reading a key flushes it, while listing, scanning and exporting a
+/// table only see the flushed writes.
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct WriteBehindConfig {
+    /// The tables written behind, with the longest time in milliseconds a write of each is
+    /// buffered before it is flushed.
+    pub tables: HashMap<String, u64>,
+    /// The number of buffered writes at which all buffered writes are flushed.
+    pub max_pending: usize,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            bind_address: "0.0.0.0:8080".to_string(),
+            activity: ActivityConfig::default(),
+            admin: AdminConfig::default(),
+            alerts: AlertConfig::default(),
+            capture: CaptureConfig::default(),
+            circuit_breaker: CircuitBreakerConfig::default(),
+            cluster: ClusterConfig::default(),
+            compression: CompressionConfig::default(),
+            concurrency: ConcurrencyConfig::default(),
+            cors: CorsConfig::default(),
+            database: DatabaseConfig::default(),
+            dedup: DedupConfig::default(),
+            encryption: EncryptionConfig::default(),
+            health: HealthConfig::default(),
+            history: HistoryConfig::default(),
+            ids: IdConfig::default(),
+            index_advisor: IndexAdvisorConfig::default(),
+            integrations: IntegrationConfig::default(),
+            key_stats: KeyStatsConfig::default(),
+            limits: LimitsConfig::default(),
+            locks: LocksConfig::default(),
+            logging: LoggingConfig::default(),
+            metrics: MetricsConfig::default(),
+            mqtt: MqttConfig::default(),
+            privacy: PrivacyConfig::default(),
+            replication: ReplicationConfig::default(),
+            request_signing: RequestSigningConfig::default(),
+            retention: RetentionConfig::default(),
+            retry: RetryConfig::default(),
+            runtime: RuntimeConfig::default(),
+            seeds: SeedConfig::default(),
+            signed_urls: SignedUrlConfig::default(),
+            slow_queries: SlowQueryConfig::default(),
+            snapshots: SnapshotConfig::default(),
+            sql_console: SqlConsoleConfig::default(),
+            tiering: TieringConfig::default(),
+            write_behind: WriteBehindConfig::default(),
+        }
+    }
+}
+
+impl Default for ActivityConfig {
+    fn default() -> Self {
+        ActivityConfig {
+            enabled: false,
+            max_entries: 100_000,
+        }
+    }
+}
+
+impl Default for AlertConfig {
+    fn default() -> Self {
+        AlertConfig {
+            interval_secs: 60,
+            cooldown_secs: 900,
+            error_rate: 0.05,
+            min_requests: 20,
+            storage_quota_bytes: None,
+            quota_warn_ratio: 0.9,
+            channels: Vec::new(),
+        }
+    }
+}
+
+impl Default for CaptureConfig {
+    fn default() -> Self {
+        CaptureConfig {
+            enabled: false,
+            routes: Vec::new(),
+            capacity: 1000,
+            max_body_bytes: 64 * 1024,
+            redact_fields: vec![
+                "password".to_string(),
+                "secret".to_string(),
+                "signature".to_string(),
+                "token".to_string(),
+            ],
+        }
+    }
+}
+
+impl Default for CircuitBreakerConfig {
+    fn default() -> Self {
+        CircuitBreakerConfig {
+            failure_threshold: 5,
+            open_secs: 30,
+            probe_interval_secs: 5,
+        }
+    }
+}
+
+impl Default for ClusterConfig {
+    fn default() -> Self {
+        ClusterConfig {
+            enabled: false,
+            instance_id: String::new(),
+            advertise_address: None,
+            lease_secs: 15,
+            heartbeat_interval_secs: 5,
+        }
+    }
+}
+
+impl Default for CompressionConfig {
+    fn default() -> Self {
+        CompressionConfig {
+            enabled: false,
+            threshold_bytes: 4096,
+            level: 3,
+        }
+    }
+}
+
+impl Default for ConcurrencyConfig {
+    fn default() -> Self {
+        ConcurrencyConfig {
+            groups: HashMap::new(),
+            queue_timeout_ms: 1000,
+            lanes: LanesConfig::default(),
+        }
+    }
+}
+
+impl Default for LanesConfig {
+    fn default() -> Self {
+        LanesConfig {
+            bulk_routes: vec![
+                "/consistent_export".to_string(),
+                "/import".to_string(),
+                "/stream_table".to_string(),
+            ],
+            max_bulk_concurrent: 4,
+            interactive_target_ms: 100,
+            queue_timeout_ms: 30_000,
+        }
+    }
+}
+
+impl Default for CorsPolicy {
+    fn default() -> Self {
+        CorsPolicy {
+            enabled: true,
+            allowed_origins: Vec::new(),
+        }
+    }
+}
+
+impl Default for DedupConfig {
+    fn default() -> Self {
+        DedupConfig {
+            enabled: false,
+            min_bytes: 1024,
+        }
+    }
+}
+
+impl Default for HistoryConfig {
+    fn default() -> Self {
+        HistoryConfig {
+            depth: 0,
+            tables: HashMap::new(),
+            retention_secs: None,
+            sweep_interval_secs: 3600,
+            deltas: false,
+            compact_after_secs: None,
+            compact_tables: HashMap::new(),
+        }
+    }
+}
+
+impl Default for IndexAdvisorConfig {
+    fn default() -> Self {
+        IndexAdvisorConfig {
+            auto_create: false,
+            interval_secs: 3600,
+            min_occurrences: 3,
+        }
+    }
+}
+
+impl Default for IntegrationConfig {
+    fn default() -> Self {
+        IntegrationConfig {
+            tick_secs: 5,
+            timeout_secs: 30,
+        }
+    }
+}
+
+impl Default for KeyStatsConfig {
+    fn default() -> Self {
+        KeyStatsConfig {
+            enabled: false,
+            flush_interval_secs: 10,
+        }
+    }
+}
+
+impl Default for LimitsConfig {
+    fn default() -> Self {
+        LimitsConfig {
+            max_body_size: 16 * 1024 * 1024,
+            json_payload: 4 * 1024 * 1024,
+            routes: HashMap::new(),
+        }
+    }
+}
+
+impl Default for LocksConfig {
+    fn default() -> Self {
+        LocksConfig {
+            enforce: false,
+            default_lease_secs: 300,
+            max_lease_secs: 3600,
+        }
+    }
+}
+
+impl Default for LoggingConfig {
+    fn default() -> Self {
+        LoggingConfig {
+            level: "debug".to_string(),
+            format: LogFormat::default(),
+        }
+    }
+}
+
+impl Default for MetricsConfig {
+    fn default() -> Self {
+        MetricsConfig {
+            pushgateway: None,
+            job: "xcloud".to_string(),
+            statsd: None,
+            statsd_prefix: "xcloud".to_string(),
+            push_interval_secs: 15,
+        }
+    }
+}
+
+impl Default for MqttConfig {
+    fn default() -> Self {
+        MqttConfig {
+            broker: None,
+            client_id: "xcloud".to_string(),
+            username: None,
+            password: String::new(),
+            keep_alive_secs: 30,
+            qos: 1,
+            routes: Vec::new(),
+            publish_topic: None,
+        }
+    }
+}
+
+impl Default for PrivacyConfig {
+    fn default() -> Self {
+        PrivacyConfig {
+            owner_fields: vec![
+                "owner".to_string(),
+                "user_id".to_string(),
+                "email".to_string(),
+            ],
+        }
+    }
+}
+
+impl Default for ReplicationConfig {
+    fn default() -> Self {
+        ReplicationConfig {
+            peer: None,
+            secret: String::new(),
+            batch_size: 100,
+            interval_secs: 5,
+            conflict: ConflictPolicy::default(),
+        }
+    }
+}
+
+impl Default for RequestSigningConfig {
+    fn default() -> Self {
+        RequestSigningConfig {
+            enabled: false,
+            clients: HashMap::new(),
+            max_skew_secs: 300,
+        }
+    }
+}
+
+impl Default for RetentionConfig {
+    fn default() -> Self {
+        RetentionConfig {
+            sweep_interval_secs: 300,
+        }
+    }
+}
+
+impl Default for RetryConfig {
+    fn default() -> Self {
+        RetryConfig {
+            max_attempts: 4,
+            base_delay_ms: 50,
+            max_delay_ms: 1000,
+        }
+    }
+}
+
+impl Default for SeedConfig {
+    fn default() -> Self {
+        SeedConfig {
+            on_startup: true,
+            dir: None,
+        }
+    }
+}
+
+impl Default for SignedUrlConfig {
+    fn default() -> Self {
+        SignedUrlConfig {
+            secret: String::new(),
+            default_ttl_secs: 3600,
+            max_ttl_secs: 86400,
+        }
+    }
+}
+
+impl Default for SlowQueryConfig {
+    fn default() -> Self {
+        SlowQueryConfig {
+            threshold_ms: 200,
+            capacity: 100,
+            persist: false,
+        }
+    }
+}
+
+impl Default for SnapshotConfig {
+    fn default() -> Self {
+        SnapshotConfig {
+            interval_secs: 0,
+            tables: Vec::new(),
+            keep: 24,
+        }
+    }
+}
+
+impl Default for SqlConsoleConfig {
+    fn default() -> Self {
+        SqlConsoleConfig {
+            enabled: false,
+            timeout_ms: 5_000,
+            max_rows: 1_000,
+        }
+    }
+}
+
+impl Default for TieringConfig {
+    fn default() -> Self {
+        TieringConfig {
+            enabled: false,
+            dir: None,
+            cold_after_days: 90,
+            interval_secs: 3_600,
+            batch_size: 1_000,
+        }
+    }
+}
+
+impl Default for WriteBehindConfig {
+    fn default() -> Self {
+        WriteBehindConfig {
+            tables: HashMap::new(),
+            max_pending: 10_000,
+        }
+    }
+}
+
+/// Implementation of the `AlertChannel` enum.
+impl AlertChannel {
+    /// Returns the default port of SMTP relays, the submission port.
+    fn default_smtp_port() -> u16 {
+        587
+    }
+
+    /// Returns a description of the channel naming no secret, used for logging.
+    pub fn describe(&self) -> String {
+        match self {
+            AlertChannel::Webhook { url } => format!("webhook {}", Utils::redact_url(url)),
+            AlertChannel::Slack { .. } => "Slack webhook".to_string(),
+            AlertChannel::Smtp { host, .. } => format!("SMTP relay {}", host),
+        }
+    }
+}
+
+/// Implementation of the `Config` struct.
+impl Config {
+    /// Loads the configuration from the `config.toml` file in the data directory.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Config, AppError>` - The loaded configuration, or the defaults if no file exists.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the file cannot be read or parsed.
+    pub fn load() -> Result<Self, AppError> {
+        let path = Self::path();
+        if !path.exists() {
+            log::info!("No config found at {:?}, using defaults", path);
+            return Ok(Config::default());
+        }
+        log::info!("Config path: {:?}", path);
+        Ok(toml::from_str(&std::fs::read_to_string(path)?)?)
+    }
+
+    /// Returns the path of the configuration file.
+    ///
+    /// # Returns
+    ///
+    /// * `PathBuf` - The path of the `config.toml` file in the data directory.
+    pub fn path() -> std::path::PathBuf {
+        Utils::get_path(&["xcloud", "config.toml"])
+    }
+
+    /// Returns a copy of the configuration with all secrets redacted.
+    ///
+    /// # Returns
+    ///
+    /// * `Config` - The configuration safe to expose to operators.
+    pub fn redacted(&self) -> Self {
+        let mut config = self.clone();
+        for key in config.encryption.keys.values_mut() {
+            *key = REDACTED.to_string();
+        }
+        config.database.url = config.database.url.as_deref().map(Utils::redact_url);
+        if !config.replication.secret.is_empty() {
+            config.replication.secret = REDACTED.to_string();
+        }
+        if !config.admin.hold_totp_secret.is_empty() {
+            config.admin.hold_totp_secret = REDACTED.to_string();
+        }
+        if !config.mqtt.password.is_empty() {
+            config.mqtt.password = REDACTED.to_string();
+        }
+        if config.mqtt.username.is_some() {
+            config.mqtt.username = Some(REDACTED.to_string());
+        }
+        config.metrics.pushgateway = config.metrics.pushgateway.as_deref().map(Utils::redact_url);
+        for channel in &mut config.alerts.channels {
+            match channel {
+                AlertChannel::Webhook { url } => *url = Utils::redact_url(url),
+                AlertChannel::Slack { url } => *url = REDACTED.to_string(),
+                AlertChannel::Smtp { password, .. } if !password.is_empty() => {
+                    *password = REDACTED.to_string()
+                }
+                AlertChannel::Smtp { .. } => {}
+            }
+        }
+        if !config.signed_urls.secret.is_empty() {
+            config.signed_urls.secret = REDACTED.to_string();
+        }
+        for secret in config.request_signing.clients.values_mut() {
+            *secret = REDACTED.to_string();
+        }
+        config.replication.peer = config.replication.peer.as_deref().map(Utils::redact_url);
+        config.database.shards = config
+            .database
+            .shards
+            .iter()
+            .map(|url| Utils::redact_url(url))
+            .collect();
+        config.database.replicas = config
+            .database
+            .replicas
+            .iter()
+            .map(|url| Utils::redact_url(url))
+            .collect();
+        for url in config.database.regions.values_mut() {
+            *url = Utils::redact_url(url);
+        }
+        config
+    }
+}
+
+/// Implementation of the `CorsConfig` struct.
+impl CorsConfig {
+    /// Checks whether the given origin may call the routes of a scope.
+    ///
+    /// # Arguments
+    ///
+    /// * `scope` - The scope of the called route.
+    /// * `origin` - The value of the `Origin` header.
+    ///
+    /// # Returns
+    ///
+    /// * `bool` - Whether the origin is allowed.
+    pub fn allows(&self, scope: CorsScope, origin: &[u8]) -> bool {
+        let policy = match scope {
+            CorsScope::Read => &self.read,
+            CorsScope::Write => &self.write,
+            CorsScope::Admin => &self.admin,
+        };
+        let allowed_origins = match policy {
+            Some(policy) if !policy.enabled => return false,
+            Some(policy) => &policy.allowed_origins,
+            None if scope == CorsScope::Admin => return false,
+            None => &self.allowed_origins,
+        };
+        allowed_origins
+            .iter()
+            .any(|allowed| allowed.as_bytes() == origin)
+    }
+}
+
+/// Implementation of the `CorsScope` enum.
+impl CorsScope {
+    /// Determines the scope of a data route from the method it is called with.
+    ///
+    /// # Arguments
+    ///
+    /// * `method` - The method of the request, or the requested method of a preflight request.
+    ///
+    /// # Returns
+    ///
+    /// * `CorsScope` - `Read` for `GET` and `HEAD`, `Write` otherwise.
+    pub fn of(method: &str) -> Self {
+        if matches!(method, "GET" | "HEAD") {
+            CorsScope::Read
+        } else {
+            CorsScope::Write
+        }
+    }
+}
+
+/// Implementation of the `HistoryConfig` struct.
+impl HistoryConfig {
+    /// Returns the number of versions kept per key of the given table.
+    ///
+    /// # Arguments
+    ///
+    /// * `table` - The name of the table.
+    ///
+    /// # Returns
+    ///
+    /// * `usize` - The table specific depth, or the default depth.
+    pub fn depth(&self, table: &str) -> usize {
+        self.tables.get(table).copied().unwrap_or(self.depth)
+    }
+
+    /// Returns the age beyond which the versions of the given table are compacted.
+    ///
+    /// # Arguments
+    ///
+    /// * `table` - The name of the table.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<u64>` - The table specific age, the default age, or `None` if the versions
+    ///   are not compacted.
+    pub fn compact_after(&self, table: &str) -> Option<u64> {
+        self.compact_tables
+            .get(table)
+            .copied()
+            .or(self.compact_after_secs)
+    }
+
+    /// Returns whether the versions of any table are compacted.
+    pub fn compacts(&self) -> bool {
+        self.compact_after_secs.is_some() || !self.compact_tables.is_empty()
+    }
+}
+
+/// Implementation of the `LimitsConfig` struct.
+impl LimitsConfig {
+    /// Returns the JSON payload limit for the given route.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path of the route.
+    ///
+    /// # Returns
+    ///
+    /// * `usize` - The route specific limit, or the default JSON payload limit.
+    pub fn json_limit(&self, path: &str) -> usize {
+        self.routes
+            .get(path)
+            .copied()
+            .unwrap_or(self.json_payload)
+            .min(self.max_body_size)
+    }
+}
+
+/// Implementation of the `MqttRoute` struct.
+impl MqttRoute {
+    /// Returns the default key template, the whole topic.
+    fn default_key() -> String {
+        "{topic}".to_string()
+    }
+
+    /// Derives the key of a message from its topic, if the topic matches the filter.
+    ///
+    /// # Arguments
+    ///
+    /// * `topic` - The topic of the message.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<String>` - The key, or `None` if the topic does not match or the key is empty.
+    pub fn key_for(&self, topic: &str) -> Option<String> {
+        let levels: Vec<&str> = topic.split('/').collect();
+        let filter: Vec<&str> = self.filter.split('/').collect();
+        for (i, part) in filter.iter().enumerate() {
+            match *part {
+                "#" if i == filter.len() - 1 => break,
+                "+" if i < levels.len() => {}
+                part if levels.get(i) == Some(&part) => {}
+                _ => return None,
+            }
+        }
+        if filter.last() != Some(&"#") && filter.len() != levels.len() {
+            return None;
+        }
+        let mut key = self.key.replace("{topic}", topic);
+        for (i, level) in levels.iter().enumerate() {
+            key = key.replace(&format!("{{{}}}", i), level);
+        }
+        (!key.is_empty()).then_some(key)
+    }
+}
@@ -0,0 +1,125 @@
+use std::future::Future;
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// The header carrying the consistency token, returned by writes and accepted by reads.
+pub const CONSISTENCY_TOKEN_HEADER: &str = "X-xCLOUD-Consistency-Token";
+
+/// The interval in milliseconds at which the positions of the read replicas are refreshed.
+const POLL_INTERVAL_MS: u64 = 200;
+
+tokio::task_local! {
+    /// The position a read replica must have reached to serve the reads of the current request.
+    static MIN_POSITION: i64;
+}
+
+/// A struct tracking the position of the primary and of the read replicas in the sequence of
+/// writes, so reads carrying a consistency token only go to replicas that applied the write.
+///
+/// The position is the highest version of the sync log, which every write to a key advances
+/// and which the replicas mirror with the rest of the primary. A token is the position of the
+/// primary after a write. Replicas are polled in the background, and a read whose token no
+/// replica reached goes to the primary.
+pub struct Consistency {
+    /// The highest position written through this instance.
+    written: AtomicI64,
+    /// The read replicas and the positions they were last seen at.
+    replicas: Vec<(Arc<sqlx::SqlitePool>, AtomicI64)>,
+}
+
+/// Implementation of the `Consistency` struct.
+impl Consistency {
+    /// Creates a new instance of the Consistency, assuming the replicas applied nothing yet.
+    ///
+    /// # Arguments
+    ///
+    /// * `replicas` - The pools of the read replicas.
+    ///
+    /// # Returns
+    ///
+    /// * `Consistency` - A new instance of the Consistency.
+    pub fn new(replicas: &[Arc<sqlx::SqlitePool>]) -> Self {
+        Consistency {
+            written: AtomicI64::new(0),
+            replicas: replicas
+                .iter()
+                .map(|pool| (pool.clone(), AtomicI64::new(0)))
+                .collect(),
+        }
+    }
+
+    /// Records a write that advanced the primary to the given position.
+    pub fn record_write(&self, position: i64) {
+        self.written.fetch_max(position, Ordering::Relaxed);
+    }
+
+    /// Returns the token of the writes made through this instance so far.
+    pub fn token(&self) -> i64 {
+        self.written.load(Ordering::Relaxed)
+    }
+
+    /// Returns whether a read replica may serve the reads of the current request, i.e. the
+    /// request carries no token or the replica reached it.
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - The index of the replica.
+    pub fn replica_current(&self, index: usize) -> bool {
+        let Some((_, position)) = self.replicas.get(index) else {
+            return false;
+        };
+        MIN_POSITION
+            .try_with(|min| position.load(Ordering::Relaxed) >= *min)
+            .unwrap_or(true)
+    }
+
+    /// Runs a future with the reads it makes routed to replicas that reached the token.
+    ///
+    /// # Arguments
+    ///
+    /// * `token` - The token of the writes the reads must see, if any.
+    /// * `future` - The future making the reads.
+    ///
+    /// # Returns
+    ///
+    /// * `F::Output` - The output of the future.
+    pub async fn scope<F: Future>(token: Option<i64>, future: F) -> F::Output {
+        match token {
+            Some(token) => MIN_POSITION.scope(token, future).await,
+            None => future.await,
+        }
+    }
+
+    /// Reads the position every replica reached once.
+    pub async fn refresh(&self) {
+        for (index, (pool, position)) in self.replicas.iter().enumerate() {
+            let reached: Result<i64, sqlx::Error> = sqlx::query_scalar(
+                "SELECT COALESCE(
+                    (SELECT seq FROM sqlite_sequence WHERE name = 'xcloud_sync_log'), 0
+                 )",
+            )
+            .fetch_one(&**pool)
+            .await;
+            match reached {
+                Ok(reached) => position.store(reached, Ordering::Relaxed),
+                Err(e) => log::debug!("Failed to read the position of replica {}: {}", index, e),
+            }
+        }
+    }
+
+    /// Refreshes the positions of the replicas in the background, if any is configured.
+    pub fn spawn(self: &Arc<Self>) {
+        if self.replicas.is_empty() {
+            return;
+        }
+        let consistency = self.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(Duration::from_millis(POLL_INTERVAL_MS));
+            loop {
+                interval.tick().await;
+                consistency.refresh().await;
+            }
+        });
+    }
+}
@@ -0,0 +1,272 @@
+use std::time::{Duration, Instant};
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use futures::StreamExt;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use sqlx::sqlite::{SqliteConnection, SqliteRow};
+use sqlx::{Column, Connection, Executor, Row, Statement, TypeInfo, ValueRef};
+
+/// The number of virtual machine instructions between two checks of the deadline.
+const PROGRESS_OPS: i32 = 1_000;
+
+/// The keywords a console statement may start with.
+const LEADING_KEYWORDS: [&str; 3] = ["SELECT", "WITH", "EXPLAIN"];
+
+/// The keywords of the statements writing to or reconfiguring the database, refused anywhere
+/// in a console statement. `REPLACE` is left out as it also names a string function, the
+/// statements it starts being refused by their leading keyword or their `INTO`.
+const FORBIDDEN_KEYWORDS: [&str; 17] = [
+    "INSERT",
+    "UPDATE",
+    "DELETE",
+    "INTO",
+    "CREATE",
+    "DROP",
+    "ALTER",
+    "ATTACH",
+    "DETACH",
+    "PRAGMA",
+    "VACUUM",
+    "REINDEX",
+    "ANALYZE",
+    "BEGIN",
+    "COMMIT",
+    "ROLLBACK",
+    "SAVEPOINT",
+];
+
+/// A struct representing the result of a console statement.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct ConsoleResult {
+    /// The names of the columns, in the order of the statement.
+    pub columns: Vec<String>,
+    /// The rows as objects keyed by column, blobs being encoded in base64.
+    pub rows: Vec<Map<String, Value>>,
+    /// Whether rows beyond the row cap were left out.
+    pub truncated: bool,
+    /// The time the statement took in milliseconds.
+    pub elapsed_ms: u64,
+}
+
+/// A struct checking the statements of the admin SQL console.
+///
+/// Statements are tokenized, skipping comments, quoted strings and quoted identifiers, and
+/// accepted only if they are a single `SELECT`, `WITH` or `EXPLAIN` statement naming no
+/// writing keyword. The check is the first of two safeguards, the statements also being run
+/// on a connection opened read-only.
+pub struct SqlConsole;
+
+/// Implementation of the `SqlConsole` struct.
+impl SqlConsole {
+    /// Checks that a statement only reads.
+    ///
+    /// # Arguments
+    ///
+    /// * `sql` - The statement as submitted.
+    ///
+    /// # Returns
+    ///
+    /// * `String` - The statement without its trailing semicolon.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the statement is empty, holds several statements,
+    /// does not start with a read-only keyword, names a writing keyword or leaves a string or
+    /// comment open.
+    pub fn validate(sql: &str) -> Result<String, sqlx::Error> {
+        let refuse = |reason: String| sqlx::Error::InvalidArgument(reason);
+        let (words, end) = Self::tokenize(sql).map_err(|e| refuse(e.to_string()))?;
+        let Some(first) = words.first() else {
+            return Err(refuse("the statement is empty".to_string()));
+        };
+        if !LEADING_KEYWORDS.contains(&first.as_str()) {
+            return Err(refuse(format!(
+                "only SELECT and EXPLAIN statements are allowed, not {}",
+                first
+            )));
+        }
+        if let Some(word) = words
+            .iter()
+            .find(|word| FORBIDDEN_KEYWORDS.contains(&word.as_str()))
+        {
+            return Err(refuse(format!("the statement may not use {}", word)));
+        }
+        Ok(sql[..end].trim().to_string())
+    }
+
+    /// Runs a statement once checked, stopping it at the deadline and at the row cap.
+    ///
+    /// # Arguments
+    ///
+    /// * `conn` - A connection opened read-only, closed once the statement ran.
+    /// * `sql` - The statement as submitted.
+    /// * `max_rows` - The largest number of rows returned.
+    /// * `timeout` - The longest time the statement may run.
+    ///
+    /// # Returns
+    ///
+    /// * `ConsoleResult` - The columns and the rows returned by the statement.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an `InvalidArgument` error if the statement is refused, fails
+    /// or runs past the deadline, and any other error if the connection fails.
+    pub async fn run(
+        mut conn: SqliteConnection,
+        sql: &str,
+        max_rows: usize,
+        timeout: Duration,
+    ) -> Result<ConsoleResult, sqlx::Error> {
+        let sql = Self::validate(sql)?;
+        let started = Instant::now();
+        let deadline = started + timeout;
+        conn.lock_handle()
+            .await?
+            .set_progress_handler(PROGRESS_OPS, move || Instant::now() < deadline);
+        let result = Self::collect(&mut conn, &sql, max_rows).await;
+        if let Err(e) = conn.close().await {
+            log::debug!("Failed to close the console connection: {}", e);
+        }
+        let (columns, rows, truncated) = match result {
+            Ok(result) => result,
+            Err(sqlx::Error::Database(_)) if Instant::now() >= deadline => {
+                return Err(sqlx::Error::InvalidArgument(format!(
+                    "the statement ran longer than {} ms",
+                    timeout.as_millis()
+                )));
+            }
+            Err(sqlx::Error::Database(e)) => {
+                return Err(sqlx::Error::InvalidArgument(e.message().to_string()))
+            }
+            Err(e) => return Err(e),
+        };
+        Ok(ConsoleResult {
+            columns,
+            rows,
+            truncated,
+            elapsed_ms: started.elapsed().as_millis() as u64,
+        })
+    }
+
+    /// Fetches the rows of a statement up to the row cap.
+    async fn collect(
+        conn: &mut SqliteConnection,
+        sql: &str,
+        max_rows: usize,
+    ) -> Result<(Vec<String>, Vec<Map<String, Value>>, bool), sqlx::Error> {
+        let columns = (&mut *conn)
+            .prepare(sql)
+            .await?
+            .columns()
+            .iter()
+            .map(|column| column.name().to_string())
+            .collect();
+        let mut rows = Vec::new();
+        let mut stream = sqlx::query(sql).fetch(conn);
+        while let Some(row) = stream.next().await {
+            let row = row?;
+            if rows.len() >= max_rows {
+                return Ok((columns, rows, true));
+            }
+            rows.push(Self::row_to_json(&row));
+        }
+        Ok((columns, rows, false))
+    }
+
+    /// Splits a statement into its uppercased bare words.
+    ///
+    /// # Returns
+    ///
+    /// * `(Vec<String>, usize)` - The words, and the offset of the terminating semicolon or the
+    ///   length of the statement.
+    fn tokenize(sql: &str) -> Result<(Vec<String>, usize), &'static str> {
+        let bytes = sql.as_bytes();
+        let mut words = Vec::new();
+        let mut end = None;
+        let mut i = 0;
+        while i < bytes.len() {
+            let byte = bytes[i];
+            let next = match byte {
+                b'-' if bytes.get(i + 1) == Some(&b'-') => {
+                    Some(sql[i..].find('\n').map_or(bytes.len(), |at| i + at + 1))
+                }
+                b'/' if bytes.get(i + 1) == Some(&b'*') => Some(
+                    Self::skip(sql, i + 2, "*/").ok_or("the statement leaves a comment open")?,
+                ),
+                b'\'' | b'"' | b'`' => Some(
+                    Self::skip(sql, i + 1, &(byte as char).to_string())
+                        .ok_or("the statement leaves a quote open")?,
+                ),
+                b'[' => {
+                    Some(Self::skip(sql, i + 1, "]").ok_or("the statement leaves a bracket open")?)
+                }
+                b';' => {
+                    end.get_or_insert(i);
+                    Some(i + 1)
+                }
+                _ => None,
+            };
+            if let Some(next) = next {
+                i = next;
+                continue;
+            }
+            if end.is_some() && !byte.is_ascii_whitespace() {
+                return Err("only a single statement is allowed");
+            }
+            if byte.is_ascii_alphabetic() || byte == b'_' {
+                let start = i;
+                while i < bytes.len() && (bytes[i].is_ascii_alphanumeric() || bytes[i] == b'_') {
+                    i += 1;
+                }
+                words.push(sql[start..i].to_ascii_uppercase());
+                continue;
+            }
+            i += 1;
+        }
+        Ok((words, end.unwrap_or(bytes.len())))
+    }
+
+    /// Returns the offset following the first occurrence of a delimiter at or after an offset.
+    fn skip(sql: &str, from: usize, close: &str) -> Option<usize> {
+        sql[from..].find(close).map(|at| from + at + close.len())
+    }
+
+    /// Converts a row into a JSON object keyed by column.
+    ///
+    /// # Arguments
+    ///
+    /// * `row` - The row returned by the statement.
+    ///
+    /// # Returns
+    ///
+    /// * `Map<String, Value>` - The values of the row, blobs being encoded in base64.
+    fn row_to_json(row: &SqliteRow) -> Map<String, Value> {
+        let mut object = Map::new();
+        for column in row.columns() {
+            let index = column.ordinal();
+            let kind = match row.try_get_raw(index) {
+                Ok(raw) if !raw.is_null() => raw.type_info().name().to_string(),
+                _ => "NULL".to_string(),
+            };
+            let value = match kind.as_str() {
+                "NULL" => Value::Null,
+                "INTEGER" => row
+                    .try_get::<i64, _>(index)
+                    .map_or(Value::Null, Value::from),
+                "REAL" => row
+                    .try_get::<f64, _>(index)
+                    .map_or(Value::Null, Value::from),
+                "BLOB" => row
+                    .try_get::<Vec<u8>, _>(index)
+                    .map_or(Value::Null, |blob| Value::from(STANDARD.encode(blob))),
+                _ => row
+                    .try_get::<String, _>(index)
+                    .map_or(Value::Null, Value::from),
+            };
+            object.insert(column.name().to_string(), value);
+        }
+        object
+    }
+}
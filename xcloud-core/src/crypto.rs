@@ -0,0 +1,230 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use async_trait::async_trait;
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use thiserror::Error;
+use tokio::sync::Mutex;
+
+use crate::config::EncryptionConfig;
+use crate::db::Database;
+use crate::jobs::Job;
+
+/// The prefix marking a stored value as encrypted.
+const PREFIX: &str = "enc:v1:";
+
+/// The size in bytes of an AES-GCM nonce.
+const NONCE_SIZE: usize = 12;
+
+/// Custom error type for encryption and decryption of values.
+#[derive(Error, Debug)]
+pub enum CryptoError {
+    #[error("Unknown key version {0}")]
+    UnknownKey(u32),
+
+    #[error("Invalid key: {0}")]
+    InvalidKey(String),
+
+    #[error("Malformed encrypted value")]
+    Malformed,
+
+    #[error("Encryption failed")]
+    Encrypt,
+
+    #[error("Decryption failed")]
+    Decrypt,
+}
+
+/// A struct holding the versioned key encryption keys used for envelope encryption.
+///
+/// Every value is encrypted with a freshly generated data key, which is in turn
+/// encrypted with the active key encryption key. Stored values have the format
+/// `enc:v1:<key version>:<wrapped data key>:<ciphertext>`.
+pub struct KeyRing {
+    keys: HashMap<u32, Aes256Gcm>,
+    active: u32,
+}
+
+/// Implementation of the `KeyRing` struct.
+impl KeyRing {
+    /// Creates a key ring from the encryption configuration.
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - The encryption configuration holding the base64 encoded 256-bit keys.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<KeyRing>` - The key ring, or `None` if encryption is disabled.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if a key is invalid or the active key is missing.
+    pub fn from_config(config: &EncryptionConfig) -> Result<Option<Self>, CryptoError> {
+        if !config.enabled {
+            return Ok(None);
+        }
+        let mut keys = HashMap::new();
+        for (version, encoded) in &config.keys {
+            let version: u32 = version
+                .parse()
+                .map_err(|_| CryptoError::InvalidKey(format!("'{}' is not a version", version)))?;
+            let bytes = STANDARD
+                .decode(encoded)
+                .map_err(|e| CryptoError::InvalidKey(e.to_string()))?;
+            if bytes.len() != 32 {
+                return Err(CryptoError::InvalidKey(format!(
+                    "key {} must be 32 bytes",
+                    version
+                )));
+            }
+            keys.insert(
+                version,
+                Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&bytes)),
+            );
+        }
+        if !keys.contains_key(&config.active_key) {
+            return Err(CryptoError::UnknownKey(config.active_key));
+        }
+        Ok(Some(KeyRing {
+            keys,
+            active: config.active_key,
+        }))
+    }
+
+    /// Encrypts the given value with a new data key wrapped by the active key.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The plaintext value.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the value cannot be encrypted.
+    pub fn encrypt(&self, value: &str) -> Result<String, CryptoError> {
+        let kek = self
+            .keys
+            .get(&self.active)
+            .ok_or(CryptoError::UnknownKey(self.active))?;
+        let data_key = Aes256Gcm::generate_key(OsRng);
+        let dek = Aes256Gcm::new(&data_key);
+        Ok(format!(
+            "{}{}:{}:{}",
+            PREFIX,
+            self.active,
+            Self::seal(kek, &data_key)?,
+            Self::seal(&dek, value.as_bytes())?
+        ))
+    }
+
+    /// Decrypts the given stored value, returning plaintext values unchanged.
+    ///
+    /// # Arguments
+    ///
+    /// * `stored` - The stored value.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the value cannot be decrypted.
+    pub fn decrypt(&self, stored: &str) -> Result<String, CryptoError> {
+        let Some(rest) = stored.strip_prefix(PREFIX) else {
+            return Ok(stored.to_string());
+        };
+        let mut parts = rest.splitn(3, ':');
+        let (Some(version), Some(wrapped), Some(sealed)) =
+            (parts.next(), parts.next(), parts.next())
+        else {
+            return Err(CryptoError::Malformed);
+        };
+        let version: u32 = version.parse().map_err(|_| CryptoError::Malformed)?;
+        let kek = self
+            .keys
+            .get(&version)
+            .ok_or(CryptoError::UnknownKey(version))?;
+        let data_key = Self::open(kek, wrapped)?;
+        if data_key.len() != 32 {
+            return Err(CryptoError::Malformed);
+        }
+        let dek = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&data_key));
+        String::from_utf8(Self::open(&dek, sealed)?).map_err(|_| CryptoError::Malformed)
+    }
+
+    /// Checks whether the stored value is not yet encrypted with the active key.
+    ///
+    /// # Arguments
+    ///
+    /// * `stored` - The stored value.
+    ///
+    /// # Returns
+    ///
+    /// * `bool` - Whether the value should be re-encrypted.
+    pub fn needs_rotation(&self, stored: &str) -> bool {
+        stored
+            .strip_prefix(PREFIX)
+            .and_then(|rest| rest.split(':').next())
+            .and_then(|version| version.parse::<u32>().ok())
+            != Some(self.active)
+    }
+
+    /// Encrypts the plaintext with the cipher, prefixing the random nonce.
+    fn seal(cipher: &Aes256Gcm, plaintext: &[u8]) -> Result<String, CryptoError> {
+        let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+        let mut sealed = nonce.to_vec();
+        sealed.extend(
+            cipher
+                .encrypt(&nonce, plaintext)
+                .map_err(|_| CryptoError::Encrypt)?,
+        );
+        Ok(STANDARD.encode(sealed))
+    }
+
+    /// Decrypts a nonce prefixed ciphertext produced by [`KeyRing::seal`].
+    fn open(cipher: &Aes256Gcm, sealed: &str) -> Result<Vec<u8>, CryptoError> {
+        let bytes = STANDARD
+            .decode(sealed)
+            .map_err(|_| CryptoError::Malformed)?;
+        if bytes.len() < NONCE_SIZE {
+            return Err(CryptoError::Malformed);
+        }
+        let (nonce, ciphertext) = bytes.split_at(NONCE_SIZE);
+        cipher
+            .decrypt(Nonce::from_slice(nonce), ciphertext)
+            .map_err(|_| CryptoError::Decrypt)
+    }
+}
+
+/// A job re-encrypting all stored values with the active key.
+pub struct ReencryptJob {
+    pub db: Arc<Mutex<Database>>,
+}
+
+#[async_trait]
+impl Job for ReencryptJob {
+    fn name(&self) -> String {
+        "re-encryption".to_string()
+    }
+
+    async fn run(&self) -> Result<(), String> {
+        let tables = self
+            .db
+            .lock()
+            .await
+            .list_tables()
+            .await
+            .map_err(|e| e.to_string())?;
+        for table in tables {
+            let count = self
+                .db
+                .lock()
+                .await
+                .reencrypt_table(&table)
+                .await
+                .map_err(|e| e.to_string())?;
+            tracing::info!(table = %table, count, "Re-encrypted values");
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,65 @@
+use actix_web::{web, HttpResponse, Responder};
+use rust_embed::RustEmbed;
+
+use crate::response::ApiResponse;
+
+/// The static assets of the admin dashboard, embedded into the binary.
+#[derive(RustEmbed)]
+#[folder = "admin/"]
+struct Assets;
+
+/// A struct serving the embedded admin dashboard.
+pub struct Dashboard;
+
+/// Implementation of the `Dashboard` struct.
+impl Dashboard {
+    /// Registers the routes of the admin dashboard.
+    ///
+    /// # Arguments
+    ///
+    /// * `cfg` - The service configuration to register the routes on.
+    pub fn configure(cfg: &mut web::ServiceConfig) {
+        cfg.route("/admin", web::get().to(Self::index))
+            .route("/admin/assets/{file}", web::get().to(Self::asset));
+    }
+
+    /// Serves the entry page of the admin dashboard.
+    ///
+    /// # Returns
+    ///
+    /// * `HttpResponse` - The HTTP response containing the page.
+    async fn index() -> impl Responder {
+        Self::serve("index.html")
+    }
+
+    /// Serves a static asset of the admin dashboard.
+    ///
+    /// # Arguments
+    ///
+    /// * `file` - The name of the asset.
+    ///
+    /// # Returns
+    ///
+    /// * `HttpResponse` - The HTTP response containing the asset or an error message.
+    async fn asset(file: web::Path<String>) -> impl Responder {
+        Self::serve(&file)
+    }
+
+    /// Builds the response for an embedded file.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path of the file inside the embedded folder.
+    ///
+    /// # Returns
+    ///
+    /// * `HttpResponse` - The HTTP response containing the file or an error message.
+    fn serve(path: &str) -> HttpResponse {
+        match Assets::get(path) {
+            Some(file) => HttpResponse::Ok()
+                .content_type(file.metadata.mimetype())
+                .body(file.data.into_owned()),
+            None => HttpResponse::NotFound().json(ApiResponse::error("asset_not_found")),
+        }
+    }
+}
@@ -0,0 +1,8217 @@
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::PoisonError;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+use futures::StreamExt;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tokio::sync::mpsc;
+
+use crate::advisor::IndexRecommendation;
+use crate::breaker::CircuitBreaker;
+use crate::compression::{self, Compressor};
+use crate::config::{
+    ActivityConfig, CompressionConfig, DatabaseConfig, DedupConfig, HistoryConfig, KeyCollation,
+    KeyStatsConfig, ReplicaSelection, RetryConfig, SlowQueryConfig, StorageBackend, TieringConfig,
+    WriteBehindConfig,
+};
+use crate::consistency::Consistency;
+use crate::crypto::KeyRing;
+use crate::dedup::Deduplicator;
+use crate::flags::FeatureFlag;
+use crate::ident::{Ident, TableRegistry};
+use crate::keystats::{KeyStat, KeyStats};
+use crate::patch::ValuePatch;
+use crate::plugin::Plugins;
+use crate::retry::RetryPolicy;
+use crate::schema::{JsonSchema, SchemaViolation, TableSchema, ValueType, ValueValidator};
+use crate::sharding::ShardRing;
+use crate::slowlog::{SlowQuery, SlowQueryLog};
+use crate::storage::{MemoryStorage, Storage};
+use crate::tiering::{self, ColdStorage};
+use crate::utils::Utils;
+use crate::webhooks::Webhook;
+use crate::writebehind::WriteBuffer;
+
+/// A struct representing a single full-text search hit.
+#[derive(Serialize, Deserialize, sqlx::FromRow)]
+pub struct SearchHit {
+    pub key: String,
+    pub value: String,
+    pub score: f64,
+}
+
+/// The receiver yielding the key-value pairs of a streamed table, ending after the last pair or
+/// an error.
+pub type RowStream = mpsc::Receiver<Result<(String, String), sqlx::Error>>;
+
+/// The number of keys sampled by a dry run.
+pub const DRY_RUN_SAMPLE: usize = 10;
+
+/// A struct reporting what a destructive operation would affect.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct DryRunReport {
+    /// The number of keys that would be affected.
+    pub count: u64,
+    /// The first affected keys, at most [`DRY_RUN_SAMPLE`].
+    pub sample: Vec<String>,
+}
+
+/// A struct representing a child of a path in the `/`-separated hierarchy of the keys.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, sqlx::FromRow)]
+pub struct TreeNode {
+    /// The segment naming the child below the path.
+    pub name: String,
+    /// Whether a value is stored at the child itself.
+    pub leaf: bool,
+    /// The number of keys below the child.
+    pub descendants: i64,
+}
+
+/// A struct representing a column of a stored table.
+#[derive(Serialize, Deserialize, Clone, Debug, sqlx::FromRow)]
+pub struct ColumnInfo {
+    pub name: String,
+    /// The declared SQL type of the column.
+    #[sqlx(rename = "type")]
+    #[serde(rename = "type")]
+    pub column_type: String,
+}
+
+/// A struct describing a stored table.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct TableInfo {
+    pub table: String,
+    /// The declared type of the values of the table.
+    pub value_type: ValueType,
+    /// The collation the keys of the table are compared with.
+    pub key_collation: KeyCollation,
+    pub columns: Vec<ColumnInfo>,
+    /// The number of rows.
+    pub rows: i64,
+    /// The approximate size in bytes of the stored keys and values.
+    pub approx_bytes: i64,
+    /// The index of the shard holding the table, `0` being the primary.
+    pub shard: usize,
+}
+
+/// A struct representing the utilization of the connection pool of a shard.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct PoolStats {
+    /// The index of the shard, `0` being the primary.
+    pub shard: usize,
+    /// The number of open connections.
+    pub connections: u32,
+    /// The number of open connections not in use.
+    pub idle: usize,
+    pub max_connections: u32,
+}
+
+/// An enum representing what a connection pool is used for.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum PoolRole {
+    /// The pool of a shard or region, written to and read from.
+    Shard,
+    /// The pool of a read replica.
+    Replica,
+}
+
+/// A struct representing the state of the connections of a connection pool.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct PoolState {
+    pub role: PoolRole,
+    /// The index of the shard or replica, shard `0` being the primary.
+    pub index: usize,
+    /// The number of open connections.
+    pub connections: u32,
+    /// The number of open connections not in use.
+    pub idle: usize,
+    /// The number of open connections in use.
+    pub in_use: usize,
+    pub max_connections: u32,
+    /// The seconds since the idle connections were last closed, or since the pool was opened,
+    /// which bounds the age of the connections that were idle then.
+    pub since_recycle_secs: u64,
+}
+
+/// A struct representing a write recorded in the outbox for replication.
+#[derive(Serialize, Deserialize, Clone, Debug, sqlx::FromRow)]
+pub struct OutboxEntry {
+    pub id: i64,
+    pub table: String,
+    pub key: Option<String>,
+    /// The kind of write, one of `set`, `update`, `delete` or `delete_table`.
+    pub op: String,
+    pub value: Option<String>,
+    pub timestamp: i64,
+}
+
+/// A struct representing the retention policy of a table.
+#[derive(Serialize, Deserialize, Clone, Debug, sqlx::FromRow)]
+pub struct TablePolicy {
+    pub table: String,
+    /// The age in seconds of the last write after which a row is pruned, unlimited if `None`.
+    pub max_age_secs: Option<i64>,
+    /// The number of most recently written rows kept, unlimited if `None`.
+    pub max_rows: Option<i64>,
+}
+
+/// A struct representing a legal hold keeping a table immutable.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, sqlx::FromRow)]
+pub struct LegalHold {
+    pub table: String,
+    pub reason: String,
+    /// The unix timestamp in seconds at which the hold was placed.
+    pub placed_at: i64,
+}
+
+/// A struct representing an instance sharing the database with others.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, sqlx::FromRow)]
+pub struct ClusterMember {
+    pub instance_id: String,
+    pub address: String,
+    /// The unix timestamp in seconds at which the instance started.
+    pub started_at: i64,
+    /// The unix timestamp in seconds at which the instance last reported itself.
+    pub heartbeat_at: i64,
+    /// Whether the instance runs the background jobs.
+    pub leader: bool,
+}
+
+/// A struct representing an external HTTP source polled into a table, and the outcome of its
+/// last poll.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, sqlx::FromRow)]
+pub struct Integration {
+    pub name: String,
+    /// The URL fetched with `GET`, answering with JSON.
+    pub url: String,
+    /// The number of seconds between two polls.
+    pub interval_secs: i64,
+    /// The value of the `Authorization` header sent with the requests, if any.
+    #[serde(default)]
+    pub authorization: Option<String>,
+    /// The table the items are written into.
+    pub table: String,
+    /// The RFC 6901 JSON Pointer of the items in the response, empty for the whole response.
+    /// An array holds many items, any other value is a single item.
+    #[serde(default)]
+    pub items_pointer: String,
+    /// The JSON Pointer of the key within an item, a string or a number.
+    pub key_pointer: String,
+    /// The JSON Pointer of the value within an item, empty for the whole item.
+    #[serde(default)]
+    pub value_pointer: String,
+    /// The unix timestamp in seconds of the last poll, `None` if never polled.
+    #[serde(default)]
+    pub last_run_at: Option<i64>,
+    /// The unix timestamp in seconds from which the next poll is due.
+    #[serde(default)]
+    pub next_run_at: i64,
+    /// The number of rows written by the last poll.
+    #[serde(default)]
+    pub last_rows: i64,
+    /// The error of the last poll, or the items it skipped, `None` if it fully succeeded.
+    #[serde(default)]
+    pub last_error: Option<String>,
+}
+
+/// A struct representing a project grouping tables.
+#[derive(Serialize, Deserialize, Clone, Debug, sqlx::FromRow)]
+pub struct Project {
+    pub name: String,
+    /// The unix timestamp in seconds at which the project was created.
+    pub created_at: i64,
+}
+
+/// A struct representing a rule deriving the value of a key from a field of another key.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, sqlx::FromRow)]
+pub struct Transform {
+    pub id: i64,
+    pub source_table: String,
+    pub source_key: String,
+    /// The RFC 6901 JSON Pointer of the field of the source value, empty for the whole value.
+    pub pointer: String,
+    pub target_table: String,
+    pub target_key: String,
+}
+
+/// A struct representing an aggregate over the keys of a table grouped by their prefix, kept up
+/// to date on every write.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, sqlx::FromRow)]
+pub struct AggregateView {
+    pub name: String,
+    pub table: String,
+    /// The delimiter ending the prefix grouping a key, which is its own group if it lacks it.
+    /// An empty delimiter groups all keys of the table together.
+    #[serde(default = "AggregateView::default_delimiter")]
+    pub delimiter: String,
+    /// The RFC 6901 JSON Pointer of the summed field, empty for the whole value.
+    #[serde(default)]
+    pub pointer: String,
+}
+
+/// Implementation of the `AggregateView` struct.
+impl AggregateView {
+    /// Returns the default delimiter of the prefixes, a colon.
+    fn default_delimiter() -> String {
+        ":".to_string()
+    }
+
+    /// Returns the prefix grouping a key.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key.
+    ///
+    /// # Returns
+    ///
+    /// * `&str` - The part of the key before the delimiter, or the whole key if it lacks it.
+    pub fn prefix<'a>(&self, key: &'a str) -> &'a str {
+        key.split_once(self.delimiter.as_str())
+            .map_or(key, |(prefix, _)| prefix)
+    }
+
+    /// Returns the amount a value adds to the sum of its group.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The value.
+    ///
+    /// # Returns
+    ///
+    /// * `f64` - The finite number at the pointer, or the one a string there holds, zero
+    ///   otherwise.
+    pub fn amount(&self, value: &str) -> f64 {
+        let Ok(document) = serde_json::from_str::<serde_json::Value>(value) else {
+            return 0.0;
+        };
+        let amount = match document.pointer(&self.pointer) {
+            Some(serde_json::Value::Number(number)) => number.as_f64(),
+            Some(serde_json::Value::String(number)) => number.trim().parse().ok(),
+            _ => None,
+        };
+        amount.filter(|amount| amount.is_finite()).unwrap_or(0.0)
+    }
+}
+
+/// A struct representing the aggregate of the keys of a table sharing a prefix.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, sqlx::FromRow)]
+pub struct ViewGroup {
+    pub prefix: String,
+    pub count: i64,
+    pub sum: f64,
+    /// The unix timestamp in seconds at which a key of the group was last written.
+    pub updated_at: i64,
+}
+
+/// A struct representing an append-only event log.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, sqlx::FromRow)]
+pub struct EventLog {
+    pub name: String,
+    /// The age in seconds after which events are pruned, unlimited if `None`.
+    pub max_age_secs: Option<i64>,
+    /// The number of most recent events kept, unlimited if `None`.
+    pub max_events: Option<i64>,
+}
+
+/// A struct representing an event of an event log.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, sqlx::FromRow)]
+pub struct Event {
+    pub offset: i64,
+    /// The unix timestamp in milliseconds at which the event was appended.
+    pub timestamp: i64,
+    pub value: String,
+}
+
+/// A struct representing a time series of numeric samples of many keys, e.g. sensors.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, sqlx::FromRow)]
+pub struct TimeSeries {
+    pub name: String,
+    /// The width in seconds of the buckets older samples are downsampled into.
+    #[serde(default = "TimeSeries::default_bucket_secs")]
+    pub bucket_secs: i64,
+    /// The age in seconds after which samples are downsampled, never if `None`.
+    pub downsample_after_secs: Option<i64>,
+}
+
+/// Implementation of the `TimeSeries` struct.
+impl TimeSeries {
+    /// Returns the default width of the downsampling buckets, one minute.
+    fn default_bucket_secs() -> i64 {
+        60
+    }
+}
+
+/// A struct representing a sample of a key of a time series.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, sqlx::FromRow)]
+pub struct SeriesPoint {
+    pub key: String,
+    /// The unix timestamp in milliseconds of the sample.
+    pub timestamp: i64,
+    pub value: f64,
+}
+
+/// A struct representing the aggregate of the samples of a key within a bucket of time.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, sqlx::FromRow)]
+pub struct SeriesBucket {
+    /// The unix timestamp in milliseconds at which the bucket starts.
+    pub start: i64,
+    pub count: i64,
+    pub avg: f64,
+    pub min: f64,
+    pub max: f64,
+}
+
+/// A struct representing the progress of a consumer group through an event log.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, sqlx::FromRow)]
+pub struct ConsumerOffset {
+    pub group: String,
+    /// The offset of the next event the group reads.
+    pub offset: i64,
+    /// The number of events appended that the group has not read yet.
+    pub lag: i64,
+}
+
+/// An enum representing how a pushed change to a key changed remotely since its base version
+/// is resolved.
+#[derive(Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum SyncResolution {
+    /// The remote value is kept and the change is rejected.
+    #[default]
+    ServerWins,
+    /// The change overwrites the remote value.
+    ClientWins,
+    /// The fields the change modified from its base are merged into a remote JSON value, the
+    /// remote value winning otherwise.
+    Merge,
+}
+
+/// An enum representing the outcome of a pushed change.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum SyncStatus {
+    Applied,
+    Merged,
+    Rejected,
+}
+
+/// A struct representing a change a client made to a key while offline.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ClientChange {
+    pub table: String,
+    pub key: String,
+    /// The version of the key the change was made from, `None` if the client created the key.
+    pub base_version: Option<i64>,
+    /// The value the change was made from, needed to merge the change.
+    #[serde(default)]
+    pub base: Option<String>,
+    /// The new value, or `None` if the client deleted the key.
+    pub value: Option<String>,
+}
+
+/// A struct representing the outcome of a pushed change.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct SyncOutcome {
+    pub table: String,
+    pub key: String,
+    pub status: SyncStatus,
+    /// The version of the key after the push, `None` if it was never written.
+    pub version: Option<i64>,
+    /// The value of the key after the push, `None` if it is absent.
+    pub value: Option<String>,
+}
+
+/// A struct representing the latest change to a key, as pulled by clients.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, sqlx::FromRow)]
+pub struct RemoteChange {
+    #[sqlx(rename = "table_name")]
+    pub table: String,
+    pub key: String,
+    pub version: i64,
+    /// The current value of the key, `None` if it was deleted.
+    #[sqlx(skip)]
+    pub value: Option<String>,
+    #[serde(skip)]
+    pub deleted: bool,
+}
+
+/// A struct representing the region a table is stored in.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, sqlx::FromRow)]
+pub struct TableRegion {
+    pub table: String,
+    pub region: String,
+}
+
+/// A struct representing a comment on a key.
+#[derive(Serialize, Deserialize, Clone, Debug, sqlx::FromRow)]
+pub struct Comment {
+    pub id: i64,
+    pub table: String,
+    pub key: String,
+    /// The id of the comment replied to, or `None` if the comment starts a thread.
+    pub parent_id: Option<i64>,
+    pub author: String,
+    pub body: String,
+    /// The unix timestamp in seconds at which the comment was posted.
+    pub created_at: i64,
+    /// The unix timestamp in seconds at which the comment was last edited.
+    pub updated_at: i64,
+}
+
+/// A struct representing a run of consecutive writes of the same kind to a table.
+#[derive(Serialize, Deserialize, Clone, Debug, sqlx::FromRow)]
+pub struct Activity {
+    pub table: String,
+    /// The kind of write, one of `set`, `update`, `delete` or `delete_table`.
+    pub op: String,
+    /// The written key if all writes were to the same key.
+    pub key: Option<String>,
+    /// The number of distinct keys written.
+    pub keys: i64,
+    /// The number of writes.
+    pub writes: i64,
+    /// The unix timestamp in seconds of the first write.
+    pub first_at: i64,
+    /// The unix timestamp in seconds of the last write.
+    pub last_at: i64,
+    /// A human readable description of the writes.
+    #[sqlx(skip)]
+    pub summary: String,
+}
+
+/// Implementation of the `Activity` struct.
+impl Activity {
+    /// Describes the writes, e.g. `updated 14 keys in table walls`.
+    fn describe(&self) -> String {
+        let verb = match self.op.as_str() {
+            "delete_table" => return format!("deleted table {}", self.table),
+            "update" => "updated",
+            "delete" => "deleted",
+            op => op,
+        };
+        match &self.key {
+            Some(key) => format!("{} key {} in table {}", verb, key, self.table),
+            None => format!("{} {} keys in table {}", verb, self.keys, self.table),
+        }
+    }
+}
+
+/// A struct representing a lease on a key held by an editor.
+#[derive(Serialize, Deserialize, Clone, Debug, sqlx::FromRow)]
+pub struct Lock {
+    pub table: String,
+    pub key: String,
+    pub owner: String,
+    /// The unix timestamp in seconds at which the lease expires.
+    pub expires_at: i64,
+}
+
+/// A struct representing an advisory lock on a table held during a schema operation.
+#[derive(Serialize, Deserialize, Clone, Debug, sqlx::FromRow)]
+pub struct SchemaLock {
+    pub table: String,
+    /// The schema operation holding the lock.
+    pub operation: String,
+    /// The unix timestamp in seconds at which the lock expires if never released.
+    pub expires_at: i64,
+}
+
+/// A struct representing a previous version of a value.
+#[derive(Serialize, Deserialize, Clone, Debug, sqlx::FromRow)]
+pub struct HistoryEntry {
+    /// The value, or `None` if the key was deleted.
+    pub value: Option<String>,
+    /// The unix timestamp in seconds at which the value was written.
+    pub changed_at: i64,
+}
+
+/// A struct representing a captured view of the key-value pairs of a set of tables.
+#[derive(Serialize, Deserialize, Clone, Debug, sqlx::FromRow)]
+pub struct Snapshot {
+    pub id: i64,
+    /// The unix timestamp in seconds at which the snapshot was captured.
+    pub created_at: i64,
+    /// Whether the snapshot was captured by the scheduler rather than on request.
+    pub scheduled: bool,
+    /// The number of captured key-value pairs.
+    pub keys: i64,
+}
+
+/// A struct representing a key of a table captured by a snapshot.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, sqlx::FromRow)]
+pub struct SnapshotKey {
+    pub table: String,
+    pub key: String,
+}
+
+/// A struct representing the differences between two snapshots.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct SnapshotDiff {
+    /// The keys captured by the snapshot but not by the one it is compared against.
+    pub added: Vec<SnapshotKey>,
+    /// The keys captured by both snapshots with different values.
+    pub changed: Vec<SnapshotKey>,
+    /// The keys captured by the snapshot it is compared against only.
+    pub removed: Vec<SnapshotKey>,
+}
+
+/// The columns of the user tables describing how their values are stored, with their
+/// definitions, which are added to the tables created before they existed.
+const STORED_COLUMNS: [(&str, &str); 3] = [
+    ("deduplicated", "INTEGER NOT NULL DEFAULT 0"),
+    ("codec", "TEXT"),
+    ("tier", "TEXT"),
+];
+
+/// A struct representing a value of a user table as it is written to its row.
+struct Sealed {
+    /// The sealed value, or the hash of the deduplicated value the row refers to.
+    value: String,
+    /// Whether the row refers to a deduplicated value instead of holding it.
+    deduplicated: bool,
+    /// The codec the sealed value is compressed with, if any.
+    codec: Option<&'static str>,
+}
+
+/// A struct that represents a database.
+pub struct Database {
+    pool: std::sync::Arc<sqlx::SqlitePool>,
+    shards: Vec<std::sync::Arc<sqlx::SqlitePool>>,
+    ring: ShardRing,
+    /// The index in `shards` of the database of every configured region.
+    regions: BTreeMap<String, usize>,
+    /// The region of every tagged table, mirroring `xcloud_table_regions`.
+    table_regions: std::sync::RwLock<BTreeMap<String, String>>,
+    replicas: Vec<std::sync::Arc<sqlx::SqlitePool>>,
+    replica_selection: ReplicaSelection,
+    next_replica: AtomicUsize,
+    /// The positions of the primary and the replicas, routing reads carrying a token.
+    consistency: std::sync::Arc<Consistency>,
+    key_collation: KeyCollation,
+    index_values: bool,
+    keyring: Option<std::sync::Arc<KeyRing>>,
+    compressor: Option<Compressor>,
+    dedup: Option<Deduplicator>,
+    outbox: bool,
+    activity: ActivityConfig,
+    history: HistoryConfig,
+    key_stats: Option<KeyStats>,
+    /// The archive of the values of cold keys, if tiering is enabled.
+    cold: Option<ColdStorage>,
+    slow_queries: Option<SlowQueryLog>,
+    retry: RetryPolicy,
+    breaker: Option<std::sync::Arc<CircuitBreaker>>,
+    /// The backend holding the key-value pairs instead of SQLite, if any.
+    storage: Option<Box<dyn Storage>>,
+    write_behind: Option<WriteBuffer>,
+    /// When the connections of every shard, then of every replica, were last recycled.
+    recycled: std::sync::Mutex<Vec<Instant>>,
+    plugins: Plugins,
+    /// The user tables created on the shards.
+    tables: TableRegistry,
+}
+
+impl Database {
+    /// Creates a new [`Database`].
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - The database configuration holding the primary and replica URLs.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the database cannot be created.
+    pub async fn new(config: &DatabaseConfig) -> Result<Self, sqlx::Error> {
+        if config.backend == StorageBackend::Memory {
+            return Self::in_memory().await;
+        }
+        let url = match &config.url {
+            Some(url) => url.clone(),
+            None => {
+                let db_path = Utils::get_path(&["xcloud", "data", "xcloud.db"]);
+                log::info!("Database path: {:?}", db_path);
+                Utils::ensure_path_exists(db_path.clone())?;
+                format!("sqlite://{}", db_path.display())
+            }
+        };
+        let pool = std::sync::Arc::new(sqlx::SqlitePool::connect(&url).await?);
+        let mut shards = vec![pool.clone()];
+        for shard in &config.shards {
+            shards.push(std::sync::Arc::new(sqlx::SqlitePool::connect(shard).await?));
+        }
+        log::info!("Connected to {} shard(s)", shards.len());
+        let mut regions = BTreeMap::new();
+        for (region, url) in config.regions.iter().collect::<BTreeMap<_, _>>() {
+            regions.insert(region.clone(), shards.len());
+            shards.push(std::sync::Arc::new(sqlx::SqlitePool::connect(url).await?));
+        }
+        log::info!("Connected to {} region(s)", regions.len());
+        let mut replicas = Vec::with_capacity(config.replicas.len());
+        for replica in &config.replicas {
+            replicas.push(std::sync::Arc::new(
+                sqlx::SqlitePool::connect(replica).await?,
+            ));
+        }
+        log::info!("Connected to {} read replica(s)", replicas.len());
+        let ring = ShardRing::new(
+            &std::iter::once(&url)
+                .chain(&config.shards)
+                .collect::<Vec<_>>(),
+        );
+        let recycled = vec![Instant::now(); shards.len() + replicas.len()];
+        let consistency = std::sync::Arc::new(Consistency::new(&replicas));
+        let db = Self {
+            pool,
+            shards,
+            ring,
+            regions,
+            table_regions: std::sync::RwLock::default(),
+            replicas,
+            replica_selection: config.replica_selection,
+            next_replica: AtomicUsize::new(0),
+            consistency,
+            key_collation: config.key_collation,
+            index_values: config.index_values,
+            keyring: None,
+            compressor: None,
+            dedup: None,
+            outbox: false,
+            activity: ActivityConfig::default(),
+            history: HistoryConfig::default(),
+            key_stats: None,
+            cold: None,
+            slow_queries: None,
+            retry: RetryPolicy::new(RetryConfig::default()),
+            breaker: None,
+            storage: None,
+            write_behind: None,
+            recycled: std::sync::Mutex::new(recycled),
+            plugins: Plugins::default(),
+            tables: TableRegistry::default(),
+        };
+        db.init().await?;
+        Ok(db)
+    }
+
+    /// Creates a new [`Database`] keeping all data in memory.
+    ///
+    /// The key-value pairs are held by a [`MemoryStorage`], the internal tables by an in-memory
+    /// SQLite database on a single connection, so nothing is written to disk.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the database cannot be created.
+    pub async fn in_memory() -> Result<Self, sqlx::Error> {
+        let pool = std::sync::Arc::new(
+            sqlx::sqlite::SqlitePoolOptions::new()
+                .max_connections(1)
+                .idle_timeout(None)
+                .max_lifetime(None)
+                .connect("sqlite::memory:")
+                .await?,
+        );
+        log::info!("Keeping all data in memory");
+        let db = Self {
+            shards: vec![pool.clone()],
+            pool,
+            ring: ShardRing::new(&["sqlite::memory:"]),
+            regions: BTreeMap::new(),
+            table_regions: std::sync::RwLock::default(),
+            replicas: Vec::new(),
+            replica_selection: ReplicaSelection::default(),
+            next_replica: AtomicUsize::new(0),
+            consistency: std::sync::Arc::new(Consistency::new(&[])),
+            key_collation: KeyCollation::default(),
+            index_values: false,
+            keyring: None,
+            compressor: None,
+            dedup: None,
+            outbox: false,
+            activity: ActivityConfig::default(),
+            history: HistoryConfig::default(),
+            key_stats: None,
+            cold: None,
+            slow_queries: None,
+            retry: RetryPolicy::new(RetryConfig::default()),
+            breaker: None,
+            storage: Some(Box::new(MemoryStorage::default())),
+            write_behind: None,
+            recycled: std::sync::Mutex::new(vec![Instant::now()]),
+            plugins: Plugins::default(),
+            tables: TableRegistry::default(),
+        };
+        db.init().await?;
+        Ok(db)
+    }
+
+    /// Initializes the internal tables.
+    async fn init(&self) -> Result<(), sqlx::Error> {
+        self.init_webhooks().await?;
+        self.init_schemas().await?;
+        self.init_lists().await?;
+        self.init_values().await?;
+        self.migrate_tables().await?;
+        if self.storage.is_none() {
+            self.tables.register(self.list_tables().await?);
+        }
+        self.init_settings().await?;
+        self.init_outbox().await?;
+        self.init_locks().await?;
+        self.init_schema_locks().await?;
+        self.init_history().await?;
+        self.init_slow_queries().await?;
+        self.init_table_policies().await?;
+        self.init_feature_flags().await?;
+        self.init_legal_holds().await?;
+        self.init_integrations().await?;
+        self.init_projects().await?;
+        self.init_comments().await?;
+        self.init_activity().await?;
+        self.init_snapshots().await?;
+        self.init_key_stats().await?;
+        self.init_transforms().await?;
+        self.init_json_schemas().await?;
+        self.init_table_regions().await?;
+        self.init_event_logs().await?;
+        self.init_time_series().await?;
+        self.init_views().await?;
+        self.init_cluster().await?;
+        self.init_sync_log().await?;
+        Ok(())
+    }
+
+    /// Enables encryption at rest of the values using the given key ring.
+    ///
+    /// # Arguments
+    ///
+    /// * `keyring` - The key ring, or `None` to store values in plaintext.
+    ///
+    /// # Returns
+    ///
+    /// * `Database` - The database with encryption configured.
+    pub fn with_keyring(mut self, keyring: Option<KeyRing>) -> Self {
+        self.keyring = keyring.map(std::sync::Arc::new);
+        self
+    }
+
+    /// Enables compression of values above the configured size threshold.
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - The compression configuration holding the threshold and level.
+    ///
+    /// # Returns
+    ///
+    /// * `Database` - The database with compression configured.
+    pub fn with_compression(mut self, config: CompressionConfig) -> Self {
+        self.compressor = Compressor::from_config(&config);
+        self
+    }
+
+    /// Enables deduplication of identical values above the configured size threshold.
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - The deduplication configuration holding the threshold.
+    ///
+    /// # Returns
+    ///
+    /// * `Database` - The database with deduplication configured.
+    pub fn with_dedup(mut self, config: DedupConfig) -> Self {
+        self.dedup = Deduplicator::from_config(&config);
+        self
+    }
+
+    /// Enables recording of all writes in the outbox, from which they are replicated.
+    ///
+    /// # Arguments
+    ///
+    /// * `enabled` - Whether writes should be recorded.
+    ///
+    /// # Returns
+    ///
+    /// * `Database` - The database with the outbox configured.
+    pub fn with_outbox(mut self, enabled: bool) -> Self {
+        self.outbox = enabled;
+        self
+    }
+
+    /// Enables recording of all writes in the activity feed.
+    ///
+    /// # Arguments
+    ///
+    /// * `activity` - The activity configuration holding the number of writes kept.
+    ///
+    /// # Returns
+    ///
+    /// * `Database` - The database with the activity feed configured.
+    pub fn with_activity(mut self, activity: ActivityConfig) -> Self {
+        self.activity = activity;
+        self
+    }
+
+    /// Enables keeping previous versions of values.
+    ///
+    /// # Arguments
+    ///
+    /// * `history` - The history configuration holding the depth per table.
+    ///
+    /// # Returns
+    ///
+    /// * `Database` - The database with the history configured.
+    pub fn with_history(mut self, history: HistoryConfig) -> Self {
+        self.history = history;
+        self
+    }
+
+    /// Enables counting the reads and writes of every key.
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - The key statistics configuration.
+    ///
+    /// # Returns
+    ///
+    /// * `Database` - The database with the key statistics configured.
+    pub fn with_key_stats(mut self, config: KeyStatsConfig) -> Self {
+        self.key_stats = config.enabled.then(KeyStats::default);
+        self
+    }
+
+    /// Enables reading and archiving the values of cold keys in the archive directory if
+    /// tiering is enabled.
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - The tiering configuration naming the archive directory.
+    ///
+    /// # Returns
+    ///
+    /// * `Database` - The database with the archive configured.
+    pub fn with_tiering(mut self, config: TieringConfig) -> Self {
+        self.cold = config.enabled.then(|| ColdStorage::new(&config));
+        self
+    }
+
+    /// Enables buffering of the writes to the tables written behind.
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - The write-behind configuration naming the tables.
+    ///
+    /// # Returns
+    ///
+    /// * `Database` - The database with write-behind configured.
+    pub fn with_write_behind(mut self, config: WriteBehindConfig) -> Self {
+        self.write_behind = WriteBuffer::new(&config);
+        self
+    }
+
+    /// Enables recording of queries exceeding the slow query threshold.
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - The slow query configuration holding the threshold and capacity.
+    ///
+    /// # Returns
+    ///
+    /// * `Database` - The database with the slow query log configured.
+    pub fn with_slow_query_log(mut self, config: SlowQueryConfig) -> Self {
+        self.slow_queries = Some(SlowQueryLog::new(config));
+        self
+    }
+
+    /// Configures the retrying of queries failing with transient errors.
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - The retry configuration holding the budget and the delays.
+    ///
+    /// # Returns
+    ///
+    /// * `Database` - The database with the retry policy configured.
+    pub fn with_retry(mut self, config: RetryConfig) -> Self {
+        self.retry = RetryPolicy::new(config);
+        self
+    }
+
+    /// Reports the outcome of queries to a circuit breaker.
+    ///
+    /// # Arguments
+    ///
+    /// * `breaker` - The circuit breaker counting the transient failures.
+    ///
+    /// # Returns
+    ///
+    /// * `Database` - The database with the circuit breaker configured.
+    pub fn with_circuit_breaker(mut self, breaker: std::sync::Arc<CircuitBreaker>) -> Self {
+        self.breaker = Some(breaker);
+        self
+    }
+
+    /// Runs the hooks of the plugins on the reads, writes and deletes of keys.
+    ///
+    /// # Arguments
+    ///
+    /// * `plugins` - The plugins, which may still be registered afterwards.
+    ///
+    /// # Returns
+    ///
+    /// * `Database` - The database with the plugins configured.
+    pub fn with_plugins(mut self, plugins: Plugins) -> Self {
+        self.plugins = plugins;
+        self
+    }
+
+    /// Checks that all shards are reachable.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if a shard cannot be queried.
+    pub async fn ping(&self) -> Result<(), sqlx::Error> {
+        for shard in &self.shards {
+            sqlx::query("SELECT 1").execute(&**shard).await?;
+        }
+        Ok(())
+    }
+
+    /// Returns whether values are encrypted at rest.
+    ///
+    /// # Returns
+    ///
+    /// * `bool` - Whether a key ring is configured.
+    pub fn encrypts_values(&self) -> bool {
+        self.keyring.is_some()
+    }
+
+    /// Returns whether values are stored as they are written, apart from their encryption.
+    ///
+    /// # Returns
+    ///
+    /// * `bool` - Whether neither compression, deduplication nor tiering is enabled.
+    pub fn stores_plain_values(&self) -> bool {
+        self.compressor.is_none() && self.dedup.is_none() && self.cold.is_none()
+    }
+
+    /// Returns the tracker of the positions of the primary and the replicas.
+    ///
+    /// # Returns
+    ///
+    /// * `Arc<Consistency>` - The tracker shared with the requests and the replica poller.
+    pub fn consistency(&self) -> std::sync::Arc<Consistency> {
+        self.consistency.clone()
+    }
+
+    /// Encrypts the value for storage if encryption at rest is enabled.
+    fn seal_value(&self, value: &str) -> Result<String, sqlx::Error> {
+        match &self.keyring {
+            Some(keyring) => keyring
+                .encrypt(value)
+                .map_err(|e| sqlx::Error::Encode(Box::new(e))),
+            None => Ok(value.to_string()),
+        }
+    }
+
+    /// Compresses and encrypts a value of a user table for storage if compression or
+    /// encryption at rest is enabled.
+    ///
+    /// # Returns
+    ///
+    /// * `(String, Option<&str>)` - The sealed value and the codec it is compressed with, if any.
+    fn seal_stored(&self, value: &str) -> Result<(String, Option<&'static str>), sqlx::Error> {
+        let compressed = match &self.compressor {
+            Some(compressor) => compressor
+                .compress(value)
+                .map_err(|e| sqlx::Error::Encode(Box::new(e)))?,
+            None => None,
+        };
+        let codec = compressed.is_some().then_some(compression::CODEC);
+        Ok((
+            self.seal_value(compressed.as_deref().unwrap_or(value))?,
+            codec,
+        ))
+    }
+
+    /// Decrypts a stored value.
+    fn open_value(&self, stored: String) -> Result<String, sqlx::Error> {
+        match &self.keyring {
+            Some(keyring) => keyring
+                .decrypt(&stored)
+                .map_err(|e| sqlx::Error::Decode(Box::new(e))),
+            None => Ok(stored),
+        }
+    }
+
+    /// Opens a stored value of a user table read along with the codec and the tier recorded
+    /// for it.
+    fn open_stored(
+        &self,
+        stored: String,
+        codec: Option<&str>,
+        tier: Option<&str>,
+    ) -> Result<String, sqlx::Error> {
+        Self::decode_value(
+            self.keyring.as_deref(),
+            self.cold.as_ref(),
+            stored,
+            codec,
+            tier,
+        )
+    }
+
+    /// Runs a query, retrying it on transient errors, reporting its outcome to the circuit
+    /// breaker and recording it in the slow query log if it exceeds the threshold.
+    ///
+    /// # Arguments
+    ///
+    /// * `table` - The table the query runs against.
+    /// * `operation` - The database operation running the query.
+    /// * `sql` - The statement with placeholders instead of values.
+    /// * `query` - The query to run, called once per attempt.
+    ///
+    /// # Errors
+    ///
+    /// This function will return the error of the last attempt.
+    async fn timed<T, Fut>(
+        &self,
+        table: &str,
+        operation: &str,
+        sql: &str,
+        query: impl FnMut() -> Fut,
+    ) -> Result<T, sqlx::Error>
+    where
+        Fut: Future<Output = Result<T, sqlx::Error>>,
+    {
+        let started = Instant::now();
+        let result = self.retry.run(query).await;
+        if let Some(breaker) = &self.breaker {
+            match &result {
+                Err(e) if RetryPolicy::is_transient(e) => breaker.record_failure(),
+                Err(_) => {}
+                Ok(_) => breaker.record_success(),
+            }
+        }
+        if let Some(slow_queries) = &self.slow_queries {
+            if let Some(slow) = slow_queries.record(table, operation, sql, started.elapsed()) {
+                if slow_queries.persists() {
+                    if let Err(e) = self.persist_slow_query(&slow).await {
+                        log::warn!("Failed to persist slow query: {}", e);
+                    }
+                }
+            }
+        }
+        result
+    }
+
+    /// Runs a read on the read pool of a table, falling back to the shard of the table if a
+    /// read replica fails.
+    ///
+    /// # Arguments
+    ///
+    /// * `table` - The table the query runs against.
+    /// * `operation` - The database operation running the query.
+    /// * `sql` - The statement with placeholders instead of values.
+    /// * `query` - The query to run against the given pool, called once per attempt.
+    ///
+    /// # Errors
+    ///
+    /// This function will return the error of the last attempt.
+    async fn timed_read<'a, T, Fut>(
+        &'a self,
+        table: &str,
+        operation: &str,
+        sql: &str,
+        query: impl Fn(&'a sqlx::SqlitePool) -> Fut,
+    ) -> Result<T, sqlx::Error>
+    where
+        Fut: Future<Output = Result<T, sqlx::Error>>,
+    {
+        let read_pool = self.read_pool(table);
+        match self.timed(table, operation, sql, || query(read_pool)).await {
+            Err(e) if !std::ptr::eq(read_pool, self.shard(table)) => {
+                log::warn!("Read replica failed, falling back to primary: {}", e);
+                let shard = self.shard(table);
+                self.timed(table, operation, sql, || query(shard)).await
+            }
+            result => result,
+        }
+    }
+
+    /// Checks whether an error reports a table that does not exist, which reads treat as an
+    /// empty table instead of creating it.
+    ///
+    /// # Arguments
+    ///
+    /// * `e` - The error to check.
+    ///
+    /// # Returns
+    ///
+    /// * `bool` - Whether the queried table does not exist.
+    fn is_missing_table(e: &sqlx::Error) -> bool {
+        e.as_database_error()
+            .is_some_and(|e| e.message().starts_with("no such table"))
+    }
+
+    /// Resolves the name of an existing user table, looking it up on its shard when it was
+    /// created by another instance sharing the database.
+    ///
+    /// # Arguments
+    ///
+    /// * `table` - The name of the table as requested.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<String>` - The sanitized name of the table, or `None` if it was never created.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the name is reserved for the internal tables or
+    /// the shard cannot be queried.
+    async fn known_table(&self, table: &str) -> Result<Option<String>, sqlx::Error> {
+        let name = Ident::table_name(table)?;
+        if self.tables.contains(&name) {
+            return Ok(Some(name));
+        }
+        let exists: Option<(i64,)> =
+            sqlx::query_as("SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = ?1")
+                .bind(&name)
+                .fetch_optional(self.shard(table))
+                .await?;
+        if exists.is_none() {
+            return Ok(None);
+        }
+        self.tables.register([name.clone()]);
+        Ok(Some(name))
+    }
+
+    /// Resolves the name of an existing user table.
+    ///
+    /// # Arguments
+    ///
+    /// * `table` - The name of the table as requested.
+    ///
+    /// # Returns
+    ///
+    /// * `String` - The sanitized name of the table.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an `InvalidArgument` error if the table was never created.
+    async fn table_name(&self, table: &str) -> Result<String, sqlx::Error> {
+        self.known_table(table)
+            .await?
+            .ok_or_else(|| sqlx::Error::InvalidArgument(format!("unknown table: {}", table)))
+    }
+
+    /// Resolves and quotes the name of an existing user table.
+    ///
+    /// # Arguments
+    ///
+    /// * `table` - The name of the table as requested.
+    ///
+    /// # Returns
+    ///
+    /// * `String` - The quoted name of the table.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an `InvalidArgument` error if the table was never created.
+    async fn table(&self, table: &str) -> Result<String, sqlx::Error> {
+        Ok(Ident::quote(&self.table_name(table).await?))
+    }
+
+    /// Returns the backend holding the key-value pairs of a user table instead of SQLite, if any.
+    ///
+    /// # Arguments
+    ///
+    /// * `table` - The name of the table.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an `InvalidArgument` error if the name is reserved for the
+    /// internal tables, as SQLite does.
+    fn backend(&self, table: &str) -> Result<Option<&dyn Storage>, sqlx::Error> {
+        let Some(storage) = &self.storage else {
+            return Ok(None);
+        };
+        Ident::table_name(table)?;
+        Ok(Some(storage.as_ref()))
+    }
+
+    /// Selects the shard holding the given table.
+    ///
+    /// # Arguments
+    ///
+    /// * `table` - The name of the table.
+    ///
+    /// # Returns
+    ///
+    /// * `&SqlitePool` - The pool of the shard, the primary if no shards are configured.
+    fn shard(&self, table: &str) -> &sqlx::SqlitePool {
+        self.shards
+            .get(self.shard_index(table))
+            .map_or(&*self.pool, |pool| &**pool)
+    }
+
+    /// Selects the index of the shard holding the given table.
+    ///
+    /// # Arguments
+    ///
+    /// * `table` - The name of the table.
+    ///
+    /// # Returns
+    ///
+    /// * `usize` - The database of the region of the table if it is tagged, otherwise the shard
+    ///   the table is assigned to by consistent hashing.
+    fn shard_index(&self, table: &str) -> usize {
+        let table = Utils::sanitize(table);
+        self.table_regions
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(&table)
+            .and_then(|region| self.regions.get(region))
+            .copied()
+            .unwrap_or_else(|| self.ring.shard_for(&table))
+    }
+
+    /// Selects the pool used for reads of a table.
+    ///
+    /// Replicas mirror the primary, so they are only used for tables stored on the primary shard.
+    /// Replicas that did not reach the consistency token of the current request are skipped.
+    ///
+    /// # Arguments
+    ///
+    /// * `table` - The name of the table.
+    ///
+    /// # Returns
+    ///
+    /// * `&SqlitePool` - The selected read replica or the pool of the shard.
+    fn read_pool(&self, table: &str) -> &sqlx::SqlitePool {
+        let shard = self.shard(table);
+        if !std::ptr::eq(shard, &*self.pool) {
+            return shard;
+        }
+        let replica = match self.replica_selection {
+            ReplicaSelection::RoundRobin => {
+                let start = self.next_replica.fetch_add(1, Ordering::Relaxed);
+                (0..self.replicas.len())
+                    .map(|offset| (start + offset) % self.replicas.len())
+                    .find(|index| self.consistency.replica_current(*index))
+                    .and_then(|index| self.replicas.get(index))
+            }
+            ReplicaSelection::LeastLoaded => self
+                .replicas
+                .iter()
+                .enumerate()
+                .filter(|(index, _)| self.consistency.replica_current(*index))
+                .min_by_key(|(_, pool)| (pool.size() as usize).saturating_sub(pool.num_idle()))
+                .map(|(_, pool)| pool),
+        };
+        replica.map_or(&*self.pool, |pool| &**pool)
+    }
+
+    /// Initializes the table with the given name.
+    ///
+    /// # Arguments
+    ///
+    /// * `table` - The name of the table to initialize.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the table cannot be initialized.
+    pub async fn init_table(&self, table: &str) -> Result<(), sqlx::Error> {
+        self.init_table_with(table, self.key_collation).await
+    }
+
+    /// Initializes the table with the given name, comparing its keys with the given collation
+    /// if the table does not exist yet.
+    async fn init_table_with(
+        &self,
+        table: &str,
+        collation: KeyCollation,
+    ) -> Result<(), sqlx::Error> {
+        let name = Ident::table_name(table)?;
+        sqlx::query(&Self::create_table_sql(&Ident::quote(&name), collation))
+            .execute(self.shard(table))
+            .await?;
+        self.tables.register([name]);
+        if self.index_values {
+            sqlx::query(&IndexRecommendation::value_index(table))
+                .execute(self.shard(table))
+                .await?;
+        }
+        if self.dedup.is_some() {
+            for trigger in Deduplicator::triggers(&Utils::sanitize(table)) {
+                sqlx::query(&trigger).execute(self.shard(table)).await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the statement creating a user table if it does not exist.
+    ///
+    /// # Arguments
+    ///
+    /// * `quoted` - The quoted name of the table.
+    /// * `collation` - The collation the keys of the table are compared with.
+    fn create_table_sql(quoted: &str, collation: KeyCollation) -> String {
+        format!(
+            "CREATE TABLE IF NOT EXISTS {} (
+                key TEXT PRIMARY KEY COLLATE {},
+                value TEXT NOT NULL{}
+            )",
+            quoted,
+            collation.as_sql(),
+            STORED_COLUMNS
+                .iter()
+                .map(|(column, definition)| format!(",\n                {} {}", column, definition))
+                .collect::<String>()
+        )
+    }
+
+    /// Returns the columns of a user table, separated by commas.
+    fn table_columns() -> String {
+        ["key", "value"]
+            .into_iter()
+            .chain(STORED_COLUMNS.iter().map(|(column, _)| *column))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    /// Adds the columns describing how values are stored to the user tables created before
+    /// they existed.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if a table cannot be altered.
+    async fn migrate_tables(&self) -> Result<(), sqlx::Error> {
+        for shard in &self.shards {
+            let tables: Vec<String> = sqlx::query_scalar(
+                "SELECT name FROM sqlite_master
+                 WHERE type = 'table'
+                   AND name NOT LIKE 'sqlite\\_%' ESCAPE '\\'
+                   AND name NOT LIKE 'xcloud\\_%' ESCAPE '\\'",
+            )
+            .fetch_all(&**shard)
+            .await?;
+            for table in tables {
+                let columns: Vec<String> =
+                    sqlx::query_scalar("SELECT name FROM pragma_table_info(?1)")
+                        .bind(&table)
+                        .fetch_all(&**shard)
+                        .await?;
+                for (column, definition) in STORED_COLUMNS {
+                    if columns.iter().any(|name| name == column) {
+                        continue;
+                    }
+                    sqlx::query(&format!(
+                        "ALTER TABLE {} ADD COLUMN {} {}",
+                        Ident::quote(&table),
+                        column,
+                        definition
+                    ))
+                    .execute(&**shard)
+                    .await?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the statement writing a sealed value of a user table, binding the key to `?1`,
+    /// the value to `?2`, whether it is deduplicated to `?3` and its codec to `?4`.
+    ///
+    /// # Arguments
+    ///
+    /// * `table` - The name of the table.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the table was never created.
+    async fn upsert(&self, table: &str) -> Result<String, sqlx::Error> {
+        Ok(format!(
+            "INSERT INTO {} (key, value, deduplicated, codec) VALUES (?1, ?2, ?3, ?4)
+             ON CONFLICT(key) DO UPDATE
+             SET value = excluded.value,
+                 deduplicated = excluded.deduplicated,
+                 codec = excluded.codec,
+                 tier = NULL",
+            self.table(table).await?
+        ))
+    }
+
+    /// Returns the columns selecting the stored value of a row of a user table as `value`, the
+    /// codec it is compressed with as `codec` and the tier holding it as `tier`, resolving
+    /// deduplicated values.
+    ///
+    /// # Arguments
+    ///
+    /// * `row` - The prefix qualifying the columns of the row, e.g. `t.`, or an empty string.
+    fn stored_columns(row: &str) -> String {
+        format!(
+            "{} AS value, {} AS codec, {}tier AS tier",
+            Deduplicator::resolve(row),
+            Deduplicator::resolve_codec(row),
+            row
+        )
+    }
+
+    /// Seals a value of a user table and stores it once in the value store of the given shard
+    /// if it is deduplicated.
+    ///
+    /// # Arguments
+    ///
+    /// * `executor` - The shard, or a transaction on it, holding the table the value is written to.
+    /// * `value` - The value to seal.
+    ///
+    /// # Returns
+    ///
+    /// * `Sealed` - The hash of the stored value, or the sealed value to store as is.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the value cannot be sealed or stored.
+    async fn store_value<'e>(
+        &self,
+        executor: impl sqlx::SqliteExecutor<'e>,
+        value: &str,
+    ) -> Result<Sealed, sqlx::Error> {
+        let (sealed, codec) = self.seal_stored(value)?;
+        let Some(hash) = self.dedup.as_ref().and_then(|dedup| dedup.reference(value)) else {
+            return Ok(Sealed {
+                value: sealed,
+                deduplicated: false,
+                codec,
+            });
+        };
+        sqlx::query(
+            "INSERT INTO xcloud_values (hash, stored, refs, codec) VALUES (?1, ?2, 0, ?3)
+             ON CONFLICT(hash) DO NOTHING",
+        )
+        .bind(&hash)
+        .bind(sealed)
+        .bind(codec)
+        .execute(executor)
+        .await?;
+        Ok(Sealed {
+            value: hash,
+            deduplicated: true,
+            codec: None,
+        })
+    }
+
+    /// Sets the data of this [`Database`].
+    ///
+    /// # Arguments
+    ///
+    /// * `table` - The table to set the data in.
+    /// * `key` - The key of the data to set.
+    /// * `value` - The value of the data to set.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the data cannot be set.
+    #[tracing::instrument(level = "debug", skip(self, value), err)]
+    pub async fn set_data(&self, table: &str, key: &str, value: &str) -> Result<(), sqlx::Error> {
+        let written = self.plugins.before_write(table, key, value).await?;
+        let value = &*written;
+        if let Some(buffer) = self.write_behind.as_ref().filter(|b| b.buffers(table)) {
+            if buffer.buffer(table, key, value) {
+                self.flush_writes(true).await?;
+            }
+            return Ok(());
+        }
+        self.write_value(table, key, value).await?;
+        self.record_change(table, Some(key), "set", Some(value))
+            .await
+    }
+
+    /// Writes the buffered value of a key, if any, so an operation on the key sees it.
+    ///
+    /// # Arguments
+    ///
+    /// * `table` - The table holding the key.
+    /// * `key` - The key.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the value cannot be written.
+    async fn flush_key(&self, table: &str, key: &str) -> Result<(), sqlx::Error> {
+        let Some(value) = self.write_behind.as_ref().and_then(|b| b.take(table, key)) else {
+            return Ok(());
+        };
+        self.write_value(table, key, &value).await?;
+        self.record_change(table, Some(key), "set", Some(&value))
+            .await
+    }
+
+    /// Writes the buffered writes of the tables whose oldest buffered write is due.
+    ///
+    /// # Arguments
+    ///
+    /// * `all` - Whether to write the buffered writes of every table, due or not.
+    ///
+    /// # Returns
+    ///
+    /// * `usize` - The number of written keys.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if a value cannot be written, in which case the
+    /// taken writes not written yet are lost.
+    pub async fn flush_writes(&self, all: bool) -> Result<usize, sqlx::Error> {
+        let Some(buffer) = &self.write_behind else {
+            return Ok(0);
+        };
+        let mut flushed = 0;
+        for (table, pairs) in buffer.take_due(all) {
+            for (key, value) in &pairs {
+                self.write_value(&table, key, value).await?;
+                self.record_change(&table, Some(key), "set", Some(value))
+                    .await?;
+            }
+            flushed += pairs.len();
+        }
+        Ok(flushed)
+    }
+
+    /// Writes the value of a key without recording the change.
+    async fn write_value(&self, table: &str, key: &str, value: &str) -> Result<(), sqlx::Error> {
+        if let Some(storage) = self.backend(table)? {
+            return storage.set_data(table, key, value).await;
+        }
+        self.init_table(table).await?;
+        let sql = self.upsert(table).await?;
+        let sealed = self.store_value(self.shard(table), value).await?;
+        let query = || {
+            sqlx::query(&sql)
+                .bind(key)
+                .bind(&sealed.value)
+                .bind(sealed.deduplicated)
+                .bind(sealed.codec)
+                .execute(self.shard(table))
+        };
+        self.timed(table, "set_data", &sql, query).await?;
+        Ok(())
+    }
+
+    /// Sets a batch of key-value pairs of this [`Database`] inside a single transaction.
+    ///
+    /// # Arguments
+    ///
+    /// * `table` - The table to set the data in.
+    /// * `pairs` - The key-value pairs to set.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the data cannot be set, in which case none of the
+    /// pairs are written.
+    #[tracing::instrument(level = "debug", skip(self, pairs), fields(count = pairs.len()), err)]
+    pub async fn set_many(
+        &self,
+        table: &str,
+        pairs: &[(String, String)],
+    ) -> Result<(), sqlx::Error> {
+        for (key, _) in pairs {
+            self.flush_key(table, key).await?;
+        }
+        if self.storage.is_some() {
+            for (key, value) in pairs {
+                self.set_data(table, key, value).await?;
+            }
+            return Ok(());
+        }
+        let mut written = Vec::with_capacity(pairs.len());
+        for (key, value) in pairs {
+            let value = self.plugins.before_write(table, key, value).await?;
+            written.push((key.clone(), value.into_owned()));
+        }
+        let pairs = &written;
+        self.init_table(table).await?;
+        let sql = self.upsert(table).await?;
+        let mut tx = self.shard(table).begin().await?;
+        for (key, value) in pairs {
+            let sealed = self.store_value(&mut *tx, value).await?;
+            sqlx::query(&sql)
+                .bind(key)
+                .bind(sealed.value)
+                .bind(sealed.deduplicated)
+                .bind(sealed.codec)
+                .execute(&mut *tx)
+                .await?;
+        }
+        tx.commit().await?;
+        for (key, value) in pairs {
+            self.record_change(table, Some(key), "set", Some(value))
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// Updates the data of this [`Database`].
+    ///
+    /// # Arguments
+    ///
+    /// * `table` - The table to update the data in.
+    /// * `key` - The key of the data to update.
+    /// * `value` - The new value of the data.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the data cannot be updated.
+    #[tracing::instrument(level = "debug", skip(self, value), err)]
+    pub async fn update_data(
+        &self,
+        table: &str,
+        key: &str,
+        value: &str,
+    ) -> Result<(), sqlx::Error> {
+        let written = self.plugins.before_write(table, key, value).await?;
+        let value = &*written;
+        self.flush_key(table, key).await?;
+        if let Some(storage) = self.backend(table)? {
+            storage.update_data(table, key, value).await?;
+            return self
+                .record_change(table, Some(key), "update", Some(value))
+                .await;
+        }
+        self.init_table(table).await?;
+        let sql = format!(
+            "UPDATE {} SET value = ?1, deduplicated = ?3, codec = ?4, tier = NULL WHERE key = ?2",
+            self.table(table).await?
+        );
+        let sealed = self.store_value(self.shard(table), value).await?;
+        let query = || {
+            sqlx::query(&sql)
+                .bind(&sealed.value)
+                .bind(key)
+                .bind(sealed.deduplicated)
+                .bind(sealed.codec)
+                .execute(self.shard(table))
+        };
+        let updated = self.timed(table, "update_data", &sql, query).await?;
+        if sealed.deduplicated && updated.rows_affected() == 0 {
+            sqlx::query("DELETE FROM xcloud_values WHERE hash = ?1 AND refs <= 0")
+                .bind(&sealed.value)
+                .execute(self.shard(table))
+                .await?;
+        }
+        self.record_change(table, Some(key), "update", Some(value))
+            .await
+    }
+
+    /// Gets the data of this [`Database`].
+    ///
+    /// # Arguments
+    ///
+    /// * `table` - The table to get the data from.
+    /// * `key` - The key of the data to get.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the data cannot be retrieved.
+    #[tracing::instrument(level = "debug", skip(self), err)]
+    pub async fn get_data(&self, table: &str, key: &str) -> Result<Option<String>, sqlx::Error> {
+        match self.read_data(table, key).await? {
+            Some(value) => Ok(Some(self.plugins.after_read(table, key, value).await)),
+            None => Ok(None),
+        }
+    }
+
+    /// Reads the value of a key without running the read hooks of the plugins.
+    async fn read_data(&self, table: &str, key: &str) -> Result<Option<String>, sqlx::Error> {
+        self.flush_key(table, key).await?;
+        if let Some(storage) = self.backend(table)? {
+            return storage.get_data(table, key).await;
+        }
+        let Some(name) = self.known_table(table).await? else {
+            return Ok(None);
+        };
+        let sql = format!(
+            "SELECT {} FROM {} WHERE key = ?1",
+            Self::stored_columns(""),
+            Ident::quote(&name)
+        );
+        let query = |pool| sqlx::query_as(&sql).bind(key).fetch_optional(pool);
+        let stored: Option<(String, Option<String>, Option<String>)> =
+            match self.timed_read(table, "get_data", &sql, query).await {
+                Err(e) if Self::is_missing_table(&e) => return Ok(None),
+                result => result?,
+            };
+        match stored {
+            Some((stored, codec, Some(tier))) if self.cold.is_some() && tier == tiering::TIER => {
+                self.rehydrate(table, key, stored, codec.as_deref())
+                    .await
+                    .map(Some)
+            }
+            stored => stored
+                .map(|(stored, codec, tier)| {
+                    self.open_stored(stored, codec.as_deref(), tier.as_deref())
+                })
+                .transpose(),
+        }
+    }
+
+    /// Reads an archived value and stores it in its table again, the archive being removed by
+    /// the tiering job once no table refers to it.
+    ///
+    /// # Arguments
+    ///
+    /// * `table` - The table holding the key.
+    /// * `key` - The archived key.
+    /// * `hash` - The hash of the archive held in place of the value.
+    /// * `codec` - The codec the archived value is compressed with, if any.
+    ///
+    /// # Returns
+    ///
+    /// * `String` - The value, returned even if it cannot be stored again.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the archive cannot be read.
+    async fn rehydrate(
+        &self,
+        table: &str,
+        key: &str,
+        hash: String,
+        codec: Option<&str>,
+    ) -> Result<String, sqlx::Error> {
+        let value = self.open_stored(hash.clone(), codec, Some(tiering::TIER))?;
+        let restore = async {
+            let stored = self.store_value(self.shard(table), &value).await?;
+            sqlx::query(&format!(
+                "UPDATE {} SET value = ?1, deduplicated = ?4, codec = ?5, tier = NULL
+                 WHERE key = ?2 AND value = ?3 AND tier = ?6",
+                self.table(table).await?
+            ))
+            .bind(stored.value)
+            .bind(key)
+            .bind(&hash)
+            .bind(stored.deduplicated)
+            .bind(stored.codec)
+            .bind(tiering::TIER)
+            .execute(self.shard(table))
+            .await
+        };
+        if let Err(e) = restore.await {
+            log::warn!("Failed to restore archived key {} of {}: {}", key, table, e);
+        }
+        Ok(value)
+    }
+
+    /// Gets the values of many keys of a table in as few queries as possible.
+    ///
+    /// # Arguments
+    ///
+    /// * `table` - The table to get the data from.
+    /// * `keys` - The keys of the data to get.
+    ///
+    /// # Returns
+    ///
+    /// * `Vec<(String, String)>` - The found key-value pairs, missing keys are omitted.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the data cannot be retrieved.
+    #[tracing::instrument(level = "debug", skip(self, keys), fields(keys = keys.len()), err)]
+    pub async fn get_many(
+        &self,
+        table: &str,
+        keys: &[String],
+    ) -> Result<Vec<(String, String)>, sqlx::Error> {
+        let mut found = Vec::with_capacity(keys.len());
+        for (key, value) in self.read_many(table, keys).await? {
+            let value = self.plugins.after_read(table, &key, value).await;
+            found.push((key, value));
+        }
+        Ok(found)
+    }
+
+    /// Reads the values of many keys without running the read hooks of the plugins.
+    async fn read_many(
+        &self,
+        table: &str,
+        keys: &[String],
+    ) -> Result<Vec<(String, String)>, sqlx::Error> {
+        for key in keys {
+            self.flush_key(table, key).await?;
+        }
+        if let Some(storage) = self.backend(table)? {
+            return storage.get_many(table, keys).await;
+        }
+        let Some(name) = self.known_table(table).await? else {
+            return Ok(Vec::new());
+        };
+        let mut found = Vec::with_capacity(keys.len());
+        for chunk in keys.chunks(500) {
+            let placeholders = (1..=chunk.len())
+                .map(|i| format!("?{}", i))
+                .collect::<Vec<_>>()
+                .join(", ");
+            let sql = format!(
+                "SELECT key, {} FROM {} WHERE key IN ({})",
+                Self::stored_columns(""),
+                Ident::quote(&name),
+                placeholders
+            );
+            let rows = |pool| {
+                chunk
+                    .iter()
+                    .fold(
+                        sqlx::query_as::<_, (String, String, Option<String>, Option<String>)>(&sql),
+                        |query, key| query.bind(key),
+                    )
+                    .fetch_all(pool)
+            };
+            let rows = match self.timed_read(table, "get_many", &sql, rows).await {
+                Err(e) if Self::is_missing_table(&e) => return Ok(Vec::new()),
+                rows => rows?,
+            };
+            for (key, stored, codec, tier) in rows {
+                found.push((
+                    key,
+                    self.open_stored(stored, codec.as_deref(), tier.as_deref())?,
+                ));
+            }
+        }
+        Ok(found)
+    }
+
+    /// Streams all key-value pairs of a table in key order without buffering the table in memory.
+    ///
+    /// The rows are read by a database cursor in a background task and handed over through a
+    /// bounded channel, so a slow consumer pauses the cursor instead of accumulating rows.
+    ///
+    /// # Arguments
+    ///
+    /// * `table` - The table to stream.
+    ///
+    /// # Returns
+    ///
+    /// * `RowStream` - The receiver yielding the key-value pairs.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the table cannot be initialized.
+    #[tracing::instrument(level = "debug", skip(self), err)]
+    pub async fn stream_table(&self, table: &str) -> Result<RowStream, sqlx::Error> {
+        if self.storage.is_some() {
+            return Ok(self.export_stored(table).await?.1);
+        }
+        self.init_table(table).await?;
+        let pool = self.shard(table).clone();
+        let keyring = self.keyring.clone();
+        let cold = self.cold.clone();
+        let sql = format!(
+            "SELECT key, {} FROM {} ORDER BY key",
+            Self::stored_columns(""),
+            self.table(table).await?
+        );
+        let (tx, rx) = mpsc::channel(64);
+        tokio::spawn(async move {
+            let mut rows =
+                sqlx::query_as::<_, (String, String, Option<String>, Option<String>)>(&sql)
+                    .fetch(&pool);
+            while let Some(row) = rows.next().await {
+                let row =
+                    row.and_then(|row| Self::decode_row(keyring.as_deref(), cold.as_ref(), row));
+                let failed = row.is_err();
+                if tx.send(row).await.is_err() || failed {
+                    break;
+                }
+            }
+        });
+        Ok(rx)
+    }
+
+    /// Streams all key-value pairs of a table as they were at a single point in time.
+    ///
+    /// Unlike [`Database::stream_table`], the rows are counted and read within one read
+    /// transaction, which SQLite isolates from concurrent writes until it ends, so the count
+    /// and the rows describe the same state of the table. Writes committed by other
+    /// connections during the export are not included. Without write-ahead logging, SQLite
+    /// makes those writes wait until the export completes.
+    ///
+    /// # Arguments
+    ///
+    /// * `table` - The table to export.
+    ///
+    /// # Returns
+    ///
+    /// * `(i64, RowStream)` - The number of exported rows and the receiver yielding the
+    ///   key-value pairs in key order, ending after the last pair or an error.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the read transaction cannot be started.
+    #[tracing::instrument(level = "debug", skip(self), err)]
+    pub async fn consistent_export(&self, table: &str) -> Result<(i64, RowStream), sqlx::Error> {
+        if self.storage.is_some() {
+            return self.export_stored(table).await;
+        }
+        self.init_table(table).await?;
+        let mut transaction = self.shard(table).begin().await?;
+        let count: i64 = sqlx::query_scalar(&format!(
+            "SELECT COUNT(*) FROM {}",
+            self.table(table).await?
+        ))
+        .fetch_one(&mut *transaction)
+        .await?;
+        let keyring = self.keyring.clone();
+        let cold = self.cold.clone();
+        let sql = format!(
+            "SELECT key, {} FROM {} ORDER BY key",
+            Self::stored_columns(""),
+            self.table(table).await?
+        );
+        let (tx, rx) = mpsc::channel(64);
+        tokio::spawn(async move {
+            {
+                let mut rows =
+                    sqlx::query_as::<_, (String, String, Option<String>, Option<String>)>(&sql)
+                        .fetch(&mut *transaction);
+                while let Some(row) = rows.next().await {
+                    let row = row
+                        .and_then(|row| Self::decode_row(keyring.as_deref(), cold.as_ref(), row));
+                    let failed = row.is_err();
+                    if tx.send(row).await.is_err() || failed {
+                        break;
+                    }
+                }
+            }
+            let _ = transaction.rollback().await;
+        });
+        Ok((count, rx))
+    }
+
+    /// Reads all key-value pairs of a table held by the storage backend at once, which has no
+    /// cursor to stream them from.
+    ///
+    /// # Arguments
+    ///
+    /// * `table` - The table to read.
+    ///
+    /// # Returns
+    ///
+    /// * `(i64, RowStream)` - The number of pairs and the receiver yielding them in key order.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the pairs cannot be read.
+    async fn export_stored(&self, table: &str) -> Result<(i64, RowStream), sqlx::Error> {
+        let keys = self.list_keys(table, 0, None).await?;
+        let rows = self.get_many(table, &keys).await?;
+        let (tx, rx) = mpsc::channel(rows.len().max(1));
+        for row in &rows {
+            let _ = tx.try_send(Ok(row.clone()));
+        }
+        Ok((rows.len() as i64, rx))
+    }
+
+    /// Decodes a stored key-value pair read outside of [`Database::get_data`].
+    ///
+    /// # Arguments
+    ///
+    /// * `keyring` - The key ring encrypting the values, if encryption is enabled.
+    /// * `cold` - The archive of the values of cold keys, if tiering is enabled.
+    /// * `row` - The key, the stored value, the codec it is compressed with and the tier
+    ///   holding it.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the value cannot be read back, decrypted or
+    /// decompressed.
+    fn decode_row(
+        keyring: Option<&KeyRing>,
+        cold: Option<&ColdStorage>,
+        (key, stored, codec, tier): (String, String, Option<String>, Option<String>),
+    ) -> Result<(String, String), sqlx::Error> {
+        let value = Self::decode_value(keyring, cold, stored, codec.as_deref(), tier.as_deref())?;
+        Ok((key, value))
+    }
+
+    /// Reads a stored value of a user table back from the tier holding it, then decrypts and
+    /// decompresses it.
+    ///
+    /// # Arguments
+    ///
+    /// * `keyring` - The key ring encrypting the values, if encryption is enabled.
+    /// * `cold` - The archive of the values of cold keys, if tiering is enabled.
+    /// * `stored` - The value held by the row.
+    /// * `codec` - The codec the value is compressed with, if any.
+    /// * `tier` - The tier holding the value, or `None` if the row holds it.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the value cannot be read back, decrypted or
+    /// decompressed.
+    fn decode_value(
+        keyring: Option<&KeyRing>,
+        cold: Option<&ColdStorage>,
+        stored: String,
+        codec: Option<&str>,
+        tier: Option<&str>,
+    ) -> Result<String, sqlx::Error> {
+        let stored =
+            ColdStorage::open(cold, tier, stored).map_err(|e| sqlx::Error::Decode(Box::new(e)))?;
+        let value = match keyring {
+            Some(keyring) => keyring
+                .decrypt(&stored)
+                .map_err(|e| sqlx::Error::Decode(Box::new(e)))?,
+            None => stored,
+        };
+        Compressor::decompress(codec, value).map_err(|e| sqlx::Error::Decode(Box::new(e)))
+    }
+
+    /// Atomically increments the integer value of a key, creating it at zero if missing.
+    ///
+    /// # Arguments
+    ///
+    /// * `table` - The table holding the key.
+    /// * `key` - The key of the value to increment.
+    /// * `delta` - The amount to add, may be negative.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<i64>` - The new value, or `None` if the current value is not an integer.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an `InvalidArgument` error if the new value would overflow a
+    /// 64-bit integer, in which case the value is left unchanged, and any other error if the
+    /// value cannot be incremented.
+    #[tracing::instrument(level = "debug", skip(self), err)]
+    pub async fn increment(
+        &self,
+        table: &str,
+        key: &str,
+        delta: i64,
+    ) -> Result<Option<i64>, sqlx::Error> {
+        self.flush_key(table, key).await?;
+        if let Some(storage) = self.backend(table)? {
+            let value = storage.increment(table, key, delta).await?;
+            if let Some(value) = value {
+                self.record_change(table, Some(key), "set", Some(&value.to_string()))
+                    .await?;
+            }
+            return Ok(value);
+        }
+        self.init_table(table).await?;
+        if self.keyring.is_some() {
+            return self.increment_sealed(table, key, delta).await;
+        }
+        let value: Option<String> = sqlx::query_scalar(&format!(
+            "INSERT INTO {0} (key, value) VALUES (?1, ?2)
+             ON CONFLICT(key) DO UPDATE SET value = CAST(value AS INTEGER) + ?2
+             WHERE NOT deduplicated
+               AND codec IS NULL
+               AND tier IS NULL
+               AND CAST(CAST(value AS INTEGER) AS TEXT) = value
+               AND (?2 <= 0 OR CAST(value AS INTEGER) <= 9223372036854775807 - ?2)
+               AND (?2 >= 0 OR CAST(value AS INTEGER) >= (-9223372036854775807 - 1) - ?2)
+             RETURNING value",
+            self.table(table).await?
+        ))
+        .bind(key)
+        .bind(delta)
+        .fetch_optional(self.shard(table))
+        .await?;
+        let Some(value) = value else {
+            // Integers stored otherwise than as is, e.g. deduplicated or archived, are
+            // incremented once they are read back, which also rejects an overflow.
+            return match self.read_data(table, key).await? {
+                Some(current) if current.parse::<i64>().is_ok() => {
+                    self.increment_sealed(table, key, delta).await
+                }
+                _ => Ok(None),
+            };
+        };
+        self.record_change(table, Some(key), "set", Some(&value))
+            .await?;
+        Ok(value.parse().ok())
+    }
+
+    /// Returns the error of an increment overflowing a 64-bit integer.
+    ///
+    /// # Arguments
+    ///
+    /// * `delta` - The amount that was to be added.
+    pub(crate) fn overflow(delta: i64) -> sqlx::Error {
+        sqlx::Error::InvalidArgument(format!(
+            "incrementing by {} overflows a 64-bit integer",
+            delta
+        ))
+    }
+
+    /// Increments a sealed integer value inside a transaction.
+    async fn increment_sealed(
+        &self,
+        table: &str,
+        key: &str,
+        delta: i64,
+    ) -> Result<Option<i64>, sqlx::Error> {
+        let mut tx = self.shard(table).begin().await?;
+        let stored: Option<(String, Option<String>, Option<String>)> = sqlx::query_as(&format!(
+            "SELECT {} FROM {} WHERE key = ?1",
+            Self::stored_columns(""),
+            self.table(table).await?
+        ))
+        .bind(key)
+        .fetch_optional(&mut *tx)
+        .await?;
+        let current = match stored {
+            Some((stored, codec, tier)) => {
+                match self
+                    .open_stored(stored, codec.as_deref(), tier.as_deref())?
+                    .parse::<i64>()
+                {
+                    Ok(current) => current,
+                    Err(_) => return Ok(None),
+                }
+            }
+            None => 0,
+        };
+        let value = current
+            .checked_add(delta)
+            .ok_or_else(|| Self::overflow(delta))?;
+        let sealed = self.store_value(&mut *tx, &value.to_string()).await?;
+        sqlx::query(&self.upsert(table).await?)
+            .bind(key)
+            .bind(sealed.value)
+            .bind(sealed.deduplicated)
+            .bind(sealed.codec)
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+        self.record_change(table, Some(key), "set", Some(&value.to_string()))
+            .await?;
+        Ok(Some(value))
+    }
+
+    /// Sets the value of a key only if its current value is the expected one.
+    ///
+    /// # Arguments
+    ///
+    /// * `table` - The table holding the key.
+    /// * `key` - The key to set.
+    /// * `expected` - The expected current value, or `None` if the key is expected to be absent.
+    /// * `value` - The new value.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(), Option<String>>` - Nothing if the value was set, or the current value if it
+    ///   did not match.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the value cannot be compared or set.
+    #[tracing::instrument(level = "debug", skip(self, expected, value), err)]
+    pub async fn compare_and_set(
+        &self,
+        table: &str,
+        key: &str,
+        expected: Option<&str>,
+        value: &str,
+    ) -> Result<Result<(), Option<String>>, sqlx::Error> {
+        self.flush_key(table, key).await?;
+        let outcome = if let Some(storage) = self.backend(table)? {
+            storage.compare_and_set(table, key, expected, value).await?
+        } else if self.keyring.is_some() || self.compressor.is_some() || self.dedup.is_some() {
+            self.compare_and_set_sealed(table, key, expected, value)
+                .await?
+        } else {
+            self.init_table(table).await?;
+            let sql = match expected {
+                Some(_) => format!(
+                    "UPDATE {} SET value = ?1
+                     WHERE key = ?2 AND value = ?3
+                       AND NOT deduplicated AND codec IS NULL AND tier IS NULL",
+                    self.table(table).await?
+                ),
+                None => format!(
+                    "INSERT INTO {} (key, value) VALUES (?2, ?1) ON CONFLICT(key) DO NOTHING",
+                    self.table(table).await?
+                ),
+            };
+            let mut query = sqlx::query(&sql).bind(value).bind(key);
+            if let Some(expected) = expected {
+                query = query.bind(expected);
+            }
+            if query.execute(self.shard(table)).await?.rows_affected() > 0 {
+                Ok(())
+            } else {
+                // Values stored otherwise than as is, e.g. while compression was enabled,
+                // only match once they are read back.
+                match self.get_data(table, key).await? {
+                    current if expected.is_some() && current.as_deref() == expected => {
+                        self.compare_and_set_sealed(table, key, expected, value)
+                            .await?
+                    }
+                    current => Err(current),
+                }
+            }
+        };
+        if outcome.is_ok() {
+            self.record_change(table, Some(key), "set", Some(value))
+                .await?;
+        }
+        Ok(outcome)
+    }
+
+    /// Compares and sets an encrypted value inside a transaction.
+    async fn compare_and_set_sealed(
+        &self,
+        table: &str,
+        key: &str,
+        expected: Option<&str>,
+        value: &str,
+    ) -> Result<Result<(), Option<String>>, sqlx::Error> {
+        self.init_table(table).await?;
+        let mut tx = self.shard(table).begin().await?;
+        let stored: Option<(String, Option<String>, Option<String>)> = sqlx::query_as(&format!(
+            "SELECT {} FROM {} WHERE key = ?1",
+            Self::stored_columns(""),
+            self.table(table).await?
+        ))
+        .bind(key)
+        .fetch_optional(&mut *tx)
+        .await?;
+        let current = stored
+            .map(|(stored, codec, tier)| {
+                self.open_stored(stored, codec.as_deref(), tier.as_deref())
+            })
+            .transpose()?;
+        if current.as_deref() != expected {
+            return Ok(Err(current));
+        }
+        let sealed = self.store_value(&mut *tx, value).await?;
+        sqlx::query(&self.upsert(table).await?)
+            .bind(key)
+            .bind(sealed.value)
+            .bind(sealed.deduplicated)
+            .bind(sealed.codec)
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+        Ok(Ok(()))
+    }
+
+    /// Applies a patch to the JSON value of a key.
+    ///
+    /// The patch is applied to the current value and written only if the value did not change
+    /// in the meantime, retrying a few times otherwise, so concurrent patches of different
+    /// fields of the same value do not overwrite each other.
+    ///
+    /// # Arguments
+    ///
+    /// * `table` - The table holding the key.
+    /// * `key` - The key to patch.
+    /// * `patch` - The patch to apply.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<Result<String, String>>` - The patched value, the reason the patch cannot be
+    ///   applied or does not match the table schema, or `None` if the key does not exist.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the value cannot be read or written.
+    #[tracing::instrument(level = "debug", skip(self, patch), err)]
+    pub async fn patch_data(
+        &self,
+        table: &str,
+        key: &str,
+        patch: &ValuePatch,
+    ) -> Result<Option<Result<String, String>>, sqlx::Error> {
+        let validator = self.value_validator(table).await?;
+        for _ in 0..3 {
+            let Some(current) = self.get_data(table, key).await? else {
+                return Ok(None);
+            };
+            let patched = match patch.apply(&current).and_then(|patched| {
+                validator
+                    .validate(&patched)
+                    .map(|_| patched)
+                    .map_err(|violations| SchemaViolation::describe(&violations))
+            }) {
+                Ok(patched) => patched,
+                Err(reason) => return Ok(Some(Err(reason))),
+            };
+            if self
+                .compare_and_set(table, key, Some(&current), &patched)
+                .await?
+                .is_ok()
+            {
+                return Ok(Some(Ok(patched)));
+            }
+        }
+        Ok(Some(Err(
+            "value kept changing while it was patched".to_string()
+        )))
+    }
+
+    /// Deletes the data of this [`Database`].
+    ///
+    /// # Arguments
+    ///
+    /// * `table` - The table to delete the data from.
+    /// * `key` - The key of the data to delete.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the data cannot be deleted.
+    #[tracing::instrument(level = "debug", skip(self), err)]
+    pub async fn delete_data(&self, table: &str, key: &str) -> Result<(), sqlx::Error> {
+        self.flush_key(table, key).await?;
+        if let Some(storage) = self.backend(table)? {
+            storage.delete_data(table, key).await?;
+        } else if let Some(name) = self.known_table(table).await? {
+            let sql = format!("DELETE FROM {} WHERE key = ?1", Ident::quote(&name));
+            let query = || sqlx::query(&sql).bind(key).execute(self.shard(table));
+            match self.timed(table, "delete_data", &sql, query).await {
+                Err(e) if Self::is_missing_table(&e) => {}
+                result => {
+                    result?;
+                }
+            }
+        }
+        sqlx::query("DELETE FROM xcloud_lists WHERE table_name = ?1 AND key = ?2")
+            .bind(Utils::sanitize(table))
+            .bind(key)
+            .execute(self.shard(table))
+            .await?;
+        self.record_change(table, Some(key), "delete", None).await?;
+        self.plugins.deleted(table, key).await;
+        Ok(())
+    }
+
+    /// Deletes many keys of a table at once.
+    ///
+    /// # Arguments
+    ///
+    /// * `table` - The table to delete the keys from.
+    /// * `keys` - The keys to delete.
+    ///
+    /// # Returns
+    ///
+    /// * `u64` - The number of deleted keys.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the keys cannot be deleted.
+    #[tracing::instrument(level = "debug", skip(self, keys), fields(keys = keys.len()), err)]
+    pub async fn delete_many(&self, table: &str, keys: &[String]) -> Result<u64, sqlx::Error> {
+        for key in keys {
+            self.flush_key(table, key).await?;
+        }
+        let deleted = self.delete_keys(table, keys).await?;
+        for key in &deleted {
+            self.record_change(table, Some(key), "delete", None).await?;
+            self.plugins.deleted(table, key).await;
+        }
+        Ok(deleted.len() as u64)
+    }
+
+    /// Deletes all keys of a table starting with the given prefix.
+    ///
+    /// # Arguments
+    ///
+    /// * `table` - The table to delete the keys from.
+    /// * `prefix` - The prefix of the keys to delete.
+    ///
+    /// # Returns
+    ///
+    /// * `u64` - The number of deleted keys.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the keys cannot be deleted.
+    #[tracing::instrument(level = "debug", skip(self), err)]
+    pub async fn delete_prefix(&self, table: &str, prefix: &str) -> Result<u64, sqlx::Error> {
+        let deleted = if let Some(storage) = self.backend(table)? {
+            let keys: Vec<String> = storage
+                .list_keys(table, 0, None)
+                .await?
+                .into_iter()
+                .filter(|key| key.starts_with(prefix))
+                .collect();
+            self.delete_keys(table, &keys).await?
+        } else {
+            self.init_table(table).await?;
+            let mut tx = self.shard(table).begin().await?;
+            let deleted: Vec<String> = sqlx::query_scalar(&format!(
+                "DELETE FROM {} WHERE substr(key, 1, ?2) = ?1 RETURNING key",
+                self.table(table).await?
+            ))
+            .bind(prefix)
+            .bind(prefix.chars().count() as i64)
+            .fetch_all(&mut *tx)
+            .await?;
+            sqlx::query(
+                "DELETE FROM xcloud_lists WHERE table_name = ?1 AND substr(key, 1, ?3) = ?2",
+            )
+            .bind(Utils::sanitize(table))
+            .bind(prefix)
+            .bind(prefix.chars().count() as i64)
+            .execute(&mut *tx)
+            .await?;
+            tx.commit().await?;
+            deleted
+        };
+        for key in &deleted {
+            self.record_change(table, Some(key), "delete", None).await?;
+            self.plugins.deleted(table, key).await;
+        }
+        Ok(deleted.len() as u64)
+    }
+
+    /// Reports the keys a deletion would remove without deleting them.
+    ///
+    /// # Arguments
+    ///
+    /// * `table` - The table to delete from.
+    /// * `prefix` - The prefix of the keys to delete, if any.
+    /// * `keys` - The keys to delete, if any. All keys are deleted if neither is given.
+    ///
+    /// # Returns
+    ///
+    /// * `DryRunReport` - The number of keys that would be deleted and a sample of them.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the keys cannot be retrieved.
+    pub async fn preview_delete(
+        &self,
+        table: &str,
+        prefix: Option<&str>,
+        keys: Option<&[String]>,
+    ) -> Result<DryRunReport, sqlx::Error> {
+        if !self.list_tables().await?.contains(&Utils::sanitize(table)) {
+            return Ok(DryRunReport::default());
+        }
+        let report = |keys: Vec<String>| DryRunReport {
+            count: keys.len() as u64,
+            sample: keys.into_iter().take(DRY_RUN_SAMPLE).collect(),
+        };
+        if let Some(keys) = keys {
+            let mut keys = keys.to_vec();
+            keys.sort();
+            keys.dedup();
+            let found = self.get_many(table, &keys).await?;
+            return Ok(report(found.into_iter().map(|(key, _)| key).collect()));
+        }
+        let prefix = prefix.unwrap_or_default();
+        if let Some(storage) = self.backend(table)? {
+            let keys = storage.list_keys(table, 0, None).await?;
+            return Ok(report(
+                keys.into_iter()
+                    .filter(|key| key.starts_with(prefix))
+                    .collect(),
+            ));
+        }
+        let condition = format!(
+            "FROM {} WHERE substr(key, 1, ?2) = ?1",
+            self.table(table).await?
+        );
+        let count: i64 = sqlx::query_scalar(&format!("SELECT COUNT(*) {}", condition))
+            .bind(prefix)
+            .bind(prefix.chars().count() as i64)
+            .fetch_one(self.shard(table))
+            .await?;
+        let sample: Vec<String> =
+            sqlx::query_scalar(&format!("SELECT key {} ORDER BY key LIMIT ?3", condition))
+                .bind(prefix)
+                .bind(prefix.chars().count() as i64)
+                .bind(DRY_RUN_SAMPLE as i64)
+                .fetch_all(self.shard(table))
+                .await?;
+        Ok(DryRunReport {
+            count: count as u64,
+            sample,
+        })
+    }
+
+    /// Deletes the table with the given name.
+    ///
+    /// # Arguments
+    ///
+    /// * `table` - The name of the table to delete.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the table cannot be deleted.
+    #[tracing::instrument(level = "debug", skip(self), err)]
+    pub async fn delete_table(&self, table: &str) -> Result<(), sqlx::Error> {
+        Ident::table_name(table)?;
+        if let Some(buffer) = &self.write_behind {
+            buffer.take_table(table);
+        }
+        if let Some(storage) = self.backend(table)? {
+            storage.delete_table(table).await?;
+        }
+        if let Some(name) = self
+            .known_table(table)
+            .await?
+            .filter(|_| self.storage.is_none())
+        {
+            let quoted = Ident::quote(&name);
+            sqlx::query(&format!("DELETE FROM {} WHERE deduplicated", quoted))
+                .execute(self.shard(table))
+                .await?;
+            sqlx::query(&format!("DROP TABLE IF EXISTS {}", quoted))
+                .execute(self.shard(table))
+                .await?;
+            self.tables.unregister(&name);
+        }
+        sqlx::query("DELETE FROM xcloud_tables WHERE table_name = ?1")
+            .bind(Utils::sanitize(table))
+            .execute(&*self.pool)
+            .await?;
+        sqlx::query("DELETE FROM xcloud_project_tables WHERE table_name = ?1")
+            .bind(Utils::sanitize(table))
+            .execute(&*self.pool)
+            .await?;
+        sqlx::query("DELETE FROM xcloud_templates WHERE table_name = ?1")
+            .bind(Utils::sanitize(table))
+            .execute(&*self.pool)
+            .await?;
+        sqlx::query("DELETE FROM xcloud_comments WHERE table_name = ?1")
+            .bind(Utils::sanitize(table))
+            .execute(&*self.pool)
+            .await?;
+        sqlx::query("DELETE FROM xcloud_key_stats WHERE table_name = ?1")
+            .bind(Utils::sanitize(table))
+            .execute(&*self.pool)
+            .await?;
+        sqlx::query("DELETE FROM xcloud_transforms WHERE source_table = ?1 OR target_table = ?1")
+            .bind(Utils::sanitize(table))
+            .execute(&*self.pool)
+            .await?;
+        sqlx::query("DELETE FROM xcloud_json_schemas WHERE table_name = ?1")
+            .bind(Utils::sanitize(table))
+            .execute(&*self.pool)
+            .await?;
+        sqlx::query("DELETE FROM xcloud_lists WHERE table_name = ?1")
+            .bind(Utils::sanitize(table))
+            .execute(self.shard(table))
+            .await?;
+        sqlx::query(&format!(
+            "DROP TABLE IF EXISTS {}",
+            Ident::derived("xcloud_fts_", table)
+        ))
+        .execute(self.shard(table))
+        .await?;
+        self.record_change(table, None, "delete_table", None).await
+    }
+
+    /// Initializes the table holding the declared schemas of the tables.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the table cannot be initialized.
+    pub async fn init_schemas(&self) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS xcloud_tables (
+                table_name TEXT PRIMARY KEY,
+                value_type TEXT NOT NULL
+            )",
+        )
+        .execute(&*self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Creates a table with the given schema. Retyping an existing table validates its
+    /// current values against the new type first.
+    ///
+    /// # Arguments
+    ///
+    /// * `schema` - The schema of the table to create.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an `InvalidArgument` error naming the first key whose value
+    /// does not match the new type, in which case the table is left unchanged, and any other
+    /// error if the table cannot be created.
+    pub async fn create_table(&self, schema: &TableSchema) -> Result<(), sqlx::Error> {
+        if schema.value_type != ValueType::String
+            && self.get_value_type(&schema.table).await? != schema.value_type
+        {
+            let mut rows = self.stream_table(&schema.table).await?;
+            while let Some(row) = rows.recv().await {
+                let (key, value) = row?;
+                if let Err(reason) = schema.value_type.validate(&value) {
+                    return Err(sqlx::Error::InvalidArgument(format!(
+                        "the value of '{}' is no {}: {}",
+                        key,
+                        schema.value_type.as_str(),
+                        reason
+                    )));
+                }
+            }
+        }
+        match schema.case_sensitive {
+            Some(case_sensitive) => {
+                let collation = if case_sensitive {
+                    KeyCollation::Binary
+                } else {
+                    KeyCollation::Nocase
+                };
+                self.init_table_with(&schema.table, collation).await?;
+                self.set_key_collation(&schema.table, collation).await?;
+            }
+            None => self.init_table(&schema.table).await?,
+        }
+        sqlx::query(
+            "INSERT OR REPLACE INTO xcloud_tables (table_name, value_type) VALUES (?1, ?2)",
+        )
+        .bind(Utils::sanitize(&schema.table))
+        .bind(schema.value_type.as_str())
+        .execute(&*self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Gets the collation the keys of the given table are compared with.
+    ///
+    /// # Arguments
+    ///
+    /// * `table` - The table to get the key collation of.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the definition of the table cannot be retrieved.
+    pub async fn get_key_collation(&self, table: &str) -> Result<KeyCollation, sqlx::Error> {
+        if self.storage.is_some() {
+            return Ok(KeyCollation::Binary);
+        }
+        let sql: Option<String> =
+            sqlx::query_scalar("SELECT sql FROM sqlite_master WHERE type = 'table' AND name = ?1")
+                .bind(Utils::sanitize(table))
+                .fetch_optional(self.shard(table))
+                .await?;
+        let sql = sql.unwrap_or_default().to_uppercase();
+        Ok(if sql.contains("COLLATE NOCASE") {
+            KeyCollation::Nocase
+        } else if sql.contains("COLLATE RTRIM") {
+            KeyCollation::Rtrim
+        } else {
+            KeyCollation::Binary
+        })
+    }
+
+    /// Changes the collation the keys of an existing table are compared with by rebuilding
+    /// the table. The full-text index of the table is dropped and rebuilt on the next search.
+    ///
+    /// The memory backend always compares keys byte by byte.
+    ///
+    /// # Arguments
+    ///
+    /// * `table` - The table to rebuild.
+    /// * `collation` - The new collation of the keys.
+    ///
+    /// # Errors
+    ///
+    /// This function will return a unique violation if keys of the table collide under the new
+    /// collation, in which case the table is left unchanged.
+    async fn set_key_collation(
+        &self,
+        table: &str,
+        collation: KeyCollation,
+    ) -> Result<(), sqlx::Error> {
+        if self.storage.is_some() || self.get_key_collation(table).await? == collation {
+            return Ok(());
+        }
+        let table = self.table_name(table).await?;
+        let (quoted, rebuild) = (
+            Ident::quote(&table),
+            Ident::derived("xcloud_rebuild_", &table),
+        );
+        let mut tx = self.shard(&table).begin().await?;
+        for sql in [
+            Self::create_table_sql(&rebuild, collation),
+            format!(
+                "INSERT INTO {0} ({1}) SELECT {1} FROM {2}",
+                rebuild,
+                Self::table_columns(),
+                quoted
+            ),
+            format!("DROP TABLE {}", quoted),
+            format!(
+                "DROP TABLE IF EXISTS {}",
+                Ident::derived("xcloud_fts_", &table)
+            ),
+            format!("ALTER TABLE {} RENAME TO {}", rebuild, quoted),
+        ] {
+            sqlx::query(&sql).execute(&mut *tx).await?;
+        }
+        tx.commit().await?;
+        tracing::info!(table = %table, collation = collation.as_sql(), "Rebuilt table");
+        self.init_table(&table).await
+    }
+
+    /// Gets the declared value type of the given table.
+    ///
+    /// # Arguments
+    ///
+    /// * `table` - The table to get the value type of.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the value type cannot be retrieved.
+    pub async fn get_value_type(&self, table: &str) -> Result<ValueType, sqlx::Error> {
+        let value_type: Option<String> =
+            sqlx::query_scalar("SELECT value_type FROM xcloud_tables WHERE table_name = ?1")
+                .bind(Utils::sanitize(table))
+                .fetch_optional(&*self.pool)
+                .await?;
+        Ok(value_type
+            .as_deref()
+            .and_then(ValueType::parse)
+            .unwrap_or_default())
+    }
+
+    /// Initializes the table holding the regions of the tables and loads them.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the table cannot be initialized.
+    pub async fn init_table_regions(&self) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS xcloud_table_regions (
+                table_name TEXT PRIMARY KEY,
+                region TEXT NOT NULL
+            )",
+        )
+        .execute(&*self.pool)
+        .await?;
+        let rows: Vec<(String, String)> =
+            sqlx::query_as("SELECT table_name, region FROM xcloud_table_regions")
+                .fetch_all(&*self.pool)
+                .await?;
+        for (table, region) in &rows {
+            if !self.regions.contains_key(region) {
+                log::warn!(
+                    "Table {} is tagged with unconfigured region {}",
+                    table,
+                    region
+                );
+            }
+        }
+        *self
+            .table_regions
+            .write()
+            .unwrap_or_else(PoisonError::into_inner) = rows.into_iter().collect();
+        Ok(())
+    }
+
+    /// Returns the names of the configured regions.
+    ///
+    /// # Returns
+    ///
+    /// * `Vec<String>` - The regions, sorted by name.
+    pub fn regions(&self) -> Vec<String> {
+        self.regions.keys().cloned().collect()
+    }
+
+    /// Returns the region a table is tagged with.
+    ///
+    /// # Arguments
+    ///
+    /// * `table` - The name of the table.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<String>` - The region, or `None` if the table is not tagged.
+    pub fn table_region(&self, table: &str) -> Option<String> {
+        self.table_regions
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(&Utils::sanitize(table))
+            .cloned()
+    }
+
+    /// Returns the regions of all tagged tables.
+    ///
+    /// # Returns
+    ///
+    /// * `Vec<TableRegion>` - The tagged tables, sorted by name.
+    pub fn table_regions(&self) -> Vec<TableRegion> {
+        self.table_regions
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .iter()
+            .map(|(table, region)| TableRegion {
+                table: table.clone(),
+                region: region.clone(),
+            })
+            .collect()
+    }
+
+    /// Tags a table with the region its data must be stored in, or removes the tag.
+    ///
+    /// Only tables that do not exist yet can be tagged, as changing the region of an existing
+    /// table would move its data across regions. The tag outlives the table, so a table created
+    /// again under the same name stays in the region.
+    ///
+    /// # Arguments
+    ///
+    /// * `table` - The name of the table.
+    /// * `region` - The configured region, or `None` to store the table on the shards.
+    ///
+    /// # Returns
+    ///
+    /// * `bool` - Whether the tag was changed, `false` if the table already exists.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the tag cannot be changed.
+    pub async fn set_table_region(
+        &self,
+        table: &str,
+        region: Option<&str>,
+    ) -> Result<bool, sqlx::Error> {
+        let table = Utils::sanitize(table);
+        if self.list_tables().await?.contains(&table) {
+            return Ok(false);
+        }
+        match region {
+            Some(region) => {
+                sqlx::query(
+                    "INSERT OR REPLACE INTO xcloud_table_regions (table_name, region)
+                     VALUES (?1, ?2)",
+                )
+                .bind(&table)
+                .bind(region)
+                .execute(&*self.pool)
+                .await?;
+            }
+            None => {
+                sqlx::query("DELETE FROM xcloud_table_regions WHERE table_name = ?1")
+                    .bind(&table)
+                    .execute(&*self.pool)
+                    .await?;
+            }
+        }
+        let mut regions = self
+            .table_regions
+            .write()
+            .unwrap_or_else(PoisonError::into_inner);
+        match region {
+            Some(region) => regions.insert(table, region.to_string()),
+            None => regions.remove(&table),
+        };
+        Ok(true)
+    }
+
+    /// Initializes the table holding the JSON Schemas attached to the tables.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the table cannot be initialized.
+    pub async fn init_json_schemas(&self) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS xcloud_json_schemas (
+                table_name TEXT PRIMARY KEY,
+                schema TEXT NOT NULL
+            )",
+        )
+        .execute(&*self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Attaches a JSON Schema to a table, replacing any previous one. Existing values are not
+    /// revalidated.
+    ///
+    /// # Arguments
+    ///
+    /// * `schema` - The table and the JSON Schema its values must match.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the schema cannot be attached.
+    pub async fn set_json_schema(&self, schema: &JsonSchema) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            "INSERT OR REPLACE INTO xcloud_json_schemas (table_name, schema) VALUES (?1, ?2)",
+        )
+        .bind(Utils::sanitize(&schema.table))
+        .bind(schema.schema.to_string())
+        .execute(&*self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Retrieves the JSON Schemas attached to the tables.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the schemas cannot be retrieved.
+    pub async fn json_schemas(&self) -> Result<Vec<JsonSchema>, sqlx::Error> {
+        let rows: Vec<(String, String)> = sqlx::query_as(
+            "SELECT table_name, schema FROM xcloud_json_schemas ORDER BY table_name",
+        )
+        .fetch_all(&*self.pool)
+        .await?;
+        Ok(rows
+            .into_iter()
+            .filter_map(|(table, schema)| {
+                serde_json::from_str(&schema)
+                    .ok()
+                    .map(|schema| JsonSchema { table, schema })
+            })
+            .collect())
+    }
+
+    /// Detaches the JSON Schema of a table.
+    ///
+    /// # Arguments
+    ///
+    /// * `table` - The table whose schema to detach.
+    ///
+    /// # Returns
+    ///
+    /// * `bool` - Whether the table had a schema.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the schema cannot be detached.
+    pub async fn delete_json_schema(&self, table: &str) -> Result<bool, sqlx::Error> {
+        let removed = sqlx::query("DELETE FROM xcloud_json_schemas WHERE table_name = ?1")
+            .bind(Utils::sanitize(table))
+            .execute(&*self.pool)
+            .await?;
+        Ok(removed.rows_affected() > 0)
+    }
+
+    /// Builds the validator of the values of the given table from its value type and JSON Schema.
+    ///
+    /// A stored schema that no longer compiles is ignored, as it could only have been attached
+    /// by an older version.
+    ///
+    /// # Arguments
+    ///
+    /// * `table` - The table to validate the values of.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the value type or schema cannot be retrieved.
+    pub async fn value_validator(&self, table: &str) -> Result<ValueValidator, sqlx::Error> {
+        let value_type = self.get_value_type(table).await?;
+        let schema: Option<String> =
+            sqlx::query_scalar("SELECT schema FROM xcloud_json_schemas WHERE table_name = ?1")
+                .bind(Utils::sanitize(table))
+                .fetch_optional(&*self.pool)
+                .await?;
+        let schema = schema.and_then(|schema| serde_json::from_str(&schema).ok());
+        Ok(match schema {
+            Some(schema) => ValueValidator::new(value_type)
+                .with_schema(&schema)
+                .unwrap_or_else(|_| ValueValidator::new(value_type)),
+            None => ValueValidator::new(value_type),
+        })
+    }
+
+    /// Validates a value against the value type and JSON Schema of the given table.
+    ///
+    /// # Arguments
+    ///
+    /// * `table` - The table the value is written to.
+    /// * `value` - The raw value to validate.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(), Vec<SchemaViolation>>` - Every violation, if the value is invalid.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the value type or schema cannot be retrieved.
+    pub async fn validate_value(
+        &self,
+        table: &str,
+        value: &str,
+    ) -> Result<Result<(), Vec<SchemaViolation>>, sqlx::Error> {
+        Ok(self.value_validator(table).await?.validate(value))
+    }
+
+    /// Initializes the table holding the registered webhooks.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the table cannot be initialized.
+    pub async fn init_webhooks(&self) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS xcloud_webhooks (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                table_name TEXT NOT NULL,
+                url TEXT NOT NULL,
+                secret TEXT NOT NULL
+            )",
+        )
+        .execute(&*self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Registers a webhook for the given table.
+    ///
+    /// # Arguments
+    ///
+    /// * `table` - The table whose changes should be delivered.
+    /// * `url` - The URL the payloads are posted to.
+    /// * `secret` - The secret used to sign the payloads.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the webhook cannot be registered.
+    pub async fn add_webhook(
+        &self,
+        table: &str,
+        url: &str,
+        secret: &str,
+    ) -> Result<i64, sqlx::Error> {
+        Ok(
+            sqlx::query(
+                "INSERT INTO xcloud_webhooks (table_name, url, secret) VALUES (?1, ?2, ?3)",
+            )
+            .bind(Utils::sanitize(table))
+            .bind(url)
+            .bind(secret)
+            .execute(&*self.pool)
+            .await?
+            .last_insert_rowid(),
+        )
+    }
+
+    /// Gets the webhooks registered for the given table.
+    ///
+    /// # Arguments
+    ///
+    /// * `table` - The table to get the webhooks for.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the webhooks cannot be retrieved.
+    pub async fn get_webhooks(&self, table: &str) -> Result<Vec<Webhook>, sqlx::Error> {
+        sqlx::query_as::<_, Webhook>(
+            "SELECT id, table_name, url, secret FROM xcloud_webhooks WHERE table_name = ?1",
+        )
+        .bind(Utils::sanitize(table))
+        .fetch_all(&*self.pool)
+        .await
+    }
+
+    /// Deletes the webhook with the given id.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The id of the webhook to delete.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the webhook cannot be deleted.
+    pub async fn delete_webhook(&self, id: i64) -> Result<(), sqlx::Error> {
+        sqlx::query("DELETE FROM xcloud_webhooks WHERE id = ?1")
+            .bind(id)
+            .execute(&*self.pool)
+            .await?;
+        Ok(())
+    }
+
+    /// Lists the names of all user tables across all shards, excluding internal tables.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the tables cannot be listed.
+    pub async fn list_tables(&self) -> Result<Vec<String>, sqlx::Error> {
+        if let Some(storage) = &self.storage {
+            return storage.list_tables().await;
+        }
+        let mut tables = Vec::new();
+        for shard in &self.shards {
+            let names: Vec<String> = sqlx::query_scalar(
+                "SELECT name FROM sqlite_master
+                 WHERE type = 'table'
+                   AND name NOT LIKE 'sqlite\\_%' ESCAPE '\\'
+                   AND name NOT LIKE 'xcloud\\_%' ESCAPE '\\'",
+            )
+            .fetch_all(&**shard)
+            .await?;
+            tables.extend(names);
+        }
+        tables.sort();
+        tables.dedup();
+        Ok(tables)
+    }
+
+    /// Describes all user tables with their columns, row counts and approximate sizes.
+    ///
+    /// The columns describing how the values are stored are left out.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the tables cannot be described.
+    pub async fn describe_tables(&self) -> Result<Vec<TableInfo>, sqlx::Error> {
+        let mut tables = Vec::new();
+        for table in self.list_tables().await? {
+            let value_type = self.get_value_type(&table).await?;
+            let key_collation = self.get_key_collation(&table).await?;
+            let shard = self.shard_index(&table);
+            let (columns, rows, approx_bytes) = if let Some(storage) = &self.storage {
+                let keys = storage.list_keys(&table, 0, None).await?;
+                let approx_bytes = storage
+                    .get_many(&table, &keys)
+                    .await?
+                    .iter()
+                    .map(|(key, value)| (key.len() + value.len()) as i64)
+                    .sum();
+                let columns = ["key", "value"]
+                    .iter()
+                    .map(|name| ColumnInfo {
+                        name: name.to_string(),
+                        column_type: "TEXT".to_string(),
+                    })
+                    .collect();
+                (columns, keys.len() as i64, approx_bytes)
+            } else {
+                let columns: Vec<ColumnInfo> = sqlx::query_as(
+                    "SELECT name, type FROM pragma_table_info(?1)
+                     WHERE name IN ('key', 'value') ORDER BY cid",
+                )
+                .bind(&table)
+                .fetch_all(self.shard(&table))
+                .await?;
+                let (rows, approx_bytes): (i64, i64) = sqlx::query_as(&format!(
+                    "SELECT COUNT(*),
+                            COALESCE(SUM(length(CAST(key AS BLOB)) + length(CAST(value AS BLOB))), 0)
+                     FROM {}",
+                    Ident::quote(&table)
+                ))
+                .fetch_one(self.shard(&table))
+                .await?;
+                (columns, rows, approx_bytes)
+            };
+            tables.push(TableInfo {
+                table,
+                value_type,
+                key_collation,
+                columns,
+                rows,
+                approx_bytes,
+                shard,
+            });
+        }
+        Ok(tables)
+    }
+
+    /// Returns the utilization of the connection pools of all shards.
+    ///
+    /// # Returns
+    ///
+    /// * `Vec<PoolStats>` - The utilization per shard, the primary first.
+    pub fn pool_stats(&self) -> Vec<PoolStats> {
+        self.shards
+            .iter()
+            .enumerate()
+            .map(|(shard, pool)| PoolStats {
+                shard,
+                connections: pool.size(),
+                idle: pool.num_idle(),
+                max_connections: pool.options().get_max_connections(),
+            })
+            .collect()
+    }
+
+    /// Returns the state of the connections of the pools of all shards and replicas.
+    ///
+    /// # Returns
+    ///
+    /// * `Vec<PoolState>` - The state per pool, the shards first, the primary first.
+    pub fn pool_states(&self) -> Vec<PoolState> {
+        let recycled = self.recycled.lock().unwrap_or_else(PoisonError::into_inner);
+        let roles = self
+            .shards
+            .iter()
+            .enumerate()
+            .map(|(index, pool)| (PoolRole::Shard, index, pool))
+            .chain(
+                self.replicas
+                    .iter()
+                    .enumerate()
+                    .map(|(index, pool)| (PoolRole::Replica, index, pool)),
+            );
+        roles
+            .zip(recycled.iter())
+            .map(|((role, index, pool), recycled)| {
+                let connections = pool.size();
+                let idle = pool.num_idle();
+                PoolState {
+                    role,
+                    index,
+                    connections,
+                    idle,
+                    in_use: (connections as usize).saturating_sub(idle),
+                    max_connections: pool.options().get_max_connections(),
+                    since_recycle_secs: recycled.elapsed().as_secs(),
+                }
+            })
+            .collect()
+    }
+
+    /// Closes the idle connections of the pools of all shards and replicas, so the pools open
+    /// fresh ones on demand, e.g. after a failover left the open ones dead. Connections in use
+    /// are returned to their pool as usual.
+    ///
+    /// An in-memory database is kept on its single connection, which is never closed.
+    ///
+    /// # Returns
+    ///
+    /// * `usize` - The number of closed connections.
+    pub async fn recycle_pools(&self) -> usize {
+        if self.storage.is_some() {
+            return 0;
+        }
+        let mut closed = 0;
+        for (index, pool) in self.shards.iter().chain(&self.replicas).enumerate() {
+            let idle: Vec<_> = std::iter::from_fn(|| pool.try_acquire())
+                .take(pool.num_idle())
+                .collect();
+            for connection in idle {
+                if let Err(e) = connection.close().await {
+                    log::warn!("Failed to close pooled connection: {}", e);
+                }
+                closed += 1;
+            }
+            if let Some(recycled) = self
+                .recycled
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .get_mut(index)
+            {
+                *recycled = Instant::now();
+            }
+        }
+        log::info!("Closed {} idle pooled connection(s)", closed);
+        closed
+    }
+
+    /// Lists the keys of a table in ascending order.
+    ///
+    /// # Arguments
+    ///
+    /// * `table` - The table to list the keys of.
+    /// * `offset` - The number of keys to skip.
+    /// * `limit` - The maximum number of keys to return, or `None` for all.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the keys cannot be listed.
+    pub async fn list_keys(
+        &self,
+        table: &str,
+        offset: i64,
+        limit: Option<i64>,
+    ) -> Result<Vec<String>, sqlx::Error> {
+        if let Some(storage) = self.backend(table)? {
+            return storage.list_keys(table, offset, limit).await;
+        }
+        self.init_table(table).await?;
+        let sql = format!(
+            "SELECT key FROM {} ORDER BY key LIMIT ?1 OFFSET ?2",
+            self.table(table).await?
+        );
+        let query = || {
+            sqlx::query_scalar(&sql)
+                .bind(limit.unwrap_or(-1))
+                .bind(offset.max(0))
+                .fetch_all(self.read_pool(table))
+        };
+        self.timed(table, "list_keys", &sql, query).await
+    }
+
+    /// Lists the immediate children of a path, treating `/`-separated key segments as a
+    /// hierarchy like the entries of a directory.
+    ///
+    /// # Arguments
+    ///
+    /// * `table` - The table holding the keys.
+    /// * `path` - The path to list, the root if empty.
+    ///
+    /// # Returns
+    ///
+    /// * `Vec<TreeNode>` - The children ordered by name.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the keys cannot be listed.
+    pub async fn tree(&self, table: &str, path: &str) -> Result<Vec<TreeNode>, sqlx::Error> {
+        let path = path.trim_matches('/');
+        let prefix = if path.is_empty() {
+            String::new()
+        } else {
+            format!("{}/", path)
+        };
+        if let Some(storage) = self.backend(table)? {
+            let mut nodes: BTreeMap<String, TreeNode> = BTreeMap::new();
+            for key in storage.list_keys(table, 0, None).await? {
+                let Some(rest) = key.strip_prefix(&prefix).filter(|rest| !rest.is_empty()) else {
+                    continue;
+                };
+                let (name, leaf) = match rest.split_once('/') {
+                    Some((name, _)) => (name, false),
+                    None => (rest, true),
+                };
+                let node = nodes.entry(name.to_string()).or_insert_with(|| TreeNode {
+                    name: name.to_string(),
+                    leaf: false,
+                    descendants: 0,
+                });
+                node.leaf |= leaf;
+                node.descendants += i64::from(!leaf);
+            }
+            return Ok(nodes.into_values().collect());
+        }
+        self.init_table(table).await?;
+        let sql = format!(
+            "WITH children AS (
+                SELECT substr(key, ?2 + 1) AS rest FROM {}
+                WHERE key >= ?1 AND substr(key, 1, ?2) = ?1 AND length(key) > ?2
+             )
+             SELECT CASE WHEN instr(rest, '/') = 0 THEN rest
+                         ELSE substr(rest, 1, instr(rest, '/') - 1) END AS name,
+                    MAX(instr(rest, '/') = 0) AS leaf,
+                    SUM(instr(rest, '/') > 0) AS descendants
+             FROM children GROUP BY name ORDER BY name",
+            self.table(table).await?
+        );
+        let query = || {
+            sqlx::query_as(&sql)
+                .bind(&prefix)
+                .bind(prefix.chars().count() as i64)
+                .fetch_all(self.read_pool(table))
+        };
+        self.timed(table, "tree", &sql, query).await
+    }
+
+    /// Re-encrypts all values of the table, including the deduplicated values it references,
+    /// that are not encrypted with the active key.
+    ///
+    /// # Arguments
+    ///
+    /// * `table` - The table to re-encrypt.
+    ///
+    /// # Returns
+    ///
+    /// * `u64` - The number of re-encrypted values.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the values cannot be re-encrypted.
+    pub async fn reencrypt_table(&self, table: &str) -> Result<u64, sqlx::Error> {
+        let Some(keyring) = &self.keyring else {
+            return Ok(0);
+        };
+        let rows: Vec<(String, String)> = sqlx::query_as(&format!(
+            "SELECT key, value FROM {} WHERE NOT deduplicated AND tier IS NULL",
+            self.table(table).await?
+        ))
+        .fetch_all(self.shard(table))
+        .await?;
+        let mut count = 0;
+        for (key, stored) in rows {
+            if !keyring.needs_rotation(&stored) {
+                continue;
+            }
+            let value = keyring
+                .decrypt(&stored)
+                .map_err(|e| sqlx::Error::Decode(Box::new(e)))?;
+            count += sqlx::query(&format!(
+                "UPDATE {} SET value = ?1
+                 WHERE key = ?2 AND value = ?3 AND NOT deduplicated AND tier IS NULL",
+                self.table(table).await?
+            ))
+            .bind(
+                keyring
+                    .encrypt(&value)
+                    .map_err(|e| sqlx::Error::Encode(Box::new(e)))?,
+            )
+            .bind(&key)
+            .bind(&stored)
+            .execute(self.shard(table))
+            .await?
+            .rows_affected();
+        }
+        let values: Vec<(String, String)> = sqlx::query_as(&format!(
+            "SELECT hash, stored FROM xcloud_values WHERE hash IN (
+                SELECT value FROM {} WHERE deduplicated
+             )",
+            self.table(table).await?
+        ))
+        .fetch_all(self.shard(table))
+        .await?;
+        for (hash, stored) in values {
+            if !keyring.needs_rotation(&stored) {
+                continue;
+            }
+            let value = keyring
+                .decrypt(&stored)
+                .map_err(|e| sqlx::Error::Decode(Box::new(e)))?;
+            count +=
+                sqlx::query("UPDATE xcloud_values SET stored = ?1 WHERE hash = ?2 AND stored = ?3")
+                    .bind(
+                        keyring
+                            .encrypt(&value)
+                            .map_err(|e| sqlx::Error::Encode(Box::new(e)))?,
+                    )
+                    .bind(&hash)
+                    .bind(&stored)
+                    .execute(self.shard(table))
+                    .await?
+                    .rows_affected();
+        }
+        Ok(count)
+    }
+
+    /// Initializes the table holding the elements of list values on every shard.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the table cannot be initialized.
+    pub async fn init_lists(&self) -> Result<(), sqlx::Error> {
+        for shard in &self.shards {
+            sqlx::query(
+                "CREATE TABLE IF NOT EXISTS xcloud_lists (
+                    table_name TEXT NOT NULL,
+                    key TEXT NOT NULL,
+                    position INTEGER NOT NULL,
+                    value TEXT NOT NULL,
+                    PRIMARY KEY (table_name, key, position)
+                )",
+            )
+            .execute(&**shard)
+            .await?;
+        }
+        Ok(())
+    }
+
+    /// Initializes the table holding the deduplicated values on every shard.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the table cannot be initialized.
+    pub async fn init_values(&self) -> Result<(), sqlx::Error> {
+        for shard in &self.shards {
+            sqlx::query(
+                "CREATE TABLE IF NOT EXISTS xcloud_values (
+                    hash TEXT PRIMARY KEY,
+                    stored TEXT NOT NULL,
+                    refs INTEGER NOT NULL,
+                    codec TEXT
+                )",
+            )
+            .execute(&**shard)
+            .await?;
+            let has_codec: bool = sqlx::query_scalar(
+                "SELECT COUNT(*) > 0 FROM pragma_table_info('xcloud_values') WHERE name = 'codec'",
+            )
+            .fetch_one(&**shard)
+            .await?;
+            if !has_codec {
+                sqlx::query("ALTER TABLE xcloud_values ADD COLUMN codec TEXT")
+                    .execute(&**shard)
+                    .await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Pushes a value onto the list of a key.
+    ///
+    /// # Arguments
+    ///
+    /// * `table` - The table holding the list.
+    /// * `key` - The key of the list.
+    /// * `value` - The value to push.
+    /// * `front` - Whether to push to the front instead of the back of the list.
+    ///
+    /// # Returns
+    ///
+    /// * `i64` - The length of the list after the push.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the value cannot be pushed.
+    #[tracing::instrument(level = "debug", skip(self, value), err)]
+    pub async fn list_push(
+        &self,
+        table: &str,
+        key: &str,
+        value: &str,
+        front: bool,
+    ) -> Result<i64, sqlx::Error> {
+        let position = if front {
+            "COALESCE(MIN(position), 0) - 1"
+        } else {
+            "COALESCE(MAX(position), 0) + 1"
+        };
+        sqlx::query(&format!(
+            "INSERT INTO xcloud_lists (table_name, key, position, value)
+             SELECT ?1, ?2, {}, ?3 FROM xcloud_lists WHERE table_name = ?1 AND key = ?2",
+            position
+        ))
+        .bind(Utils::sanitize(table))
+        .bind(key)
+        .bind(self.seal_value(value)?)
+        .execute(self.shard(table))
+        .await?;
+        sqlx::query_scalar("SELECT COUNT(*) FROM xcloud_lists WHERE table_name = ?1 AND key = ?2")
+            .bind(Utils::sanitize(table))
+            .bind(key)
+            .fetch_one(self.shard(table))
+            .await
+    }
+
+    /// Pops a value from the list of a key.
+    ///
+    /// # Arguments
+    ///
+    /// * `table` - The table holding the list.
+    /// * `key` - The key of the list.
+    /// * `front` - Whether to pop from the front instead of the back of the list.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<String>` - The popped value, or `None` if the list is empty.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the value cannot be popped.
+    #[tracing::instrument(level = "debug", skip(self), err)]
+    pub async fn list_pop(
+        &self,
+        table: &str,
+        key: &str,
+        front: bool,
+    ) -> Result<Option<String>, sqlx::Error> {
+        let stored: Option<String> = sqlx::query_scalar(&format!(
+            "DELETE FROM xcloud_lists WHERE rowid = (
+                SELECT rowid FROM xcloud_lists WHERE table_name = ?1 AND key = ?2
+                ORDER BY position {} LIMIT 1
+             ) RETURNING value",
+            if front { "ASC" } else { "DESC" }
+        ))
+        .bind(Utils::sanitize(table))
+        .bind(key)
+        .fetch_optional(self.shard(table))
+        .await?;
+        stored.map(|stored| self.open_value(stored)).transpose()
+    }
+
+    /// Gets a range of the list of a key, ordered from front to back.
+    ///
+    /// # Arguments
+    ///
+    /// * `table` - The table holding the list.
+    /// * `key` - The key of the list.
+    /// * `offset` - The number of elements to skip from the front.
+    /// * `limit` - The maximum number of elements to return, or `None` for all.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the range cannot be retrieved.
+    #[tracing::instrument(level = "debug", skip(self), err)]
+    pub async fn list_range(
+        &self,
+        table: &str,
+        key: &str,
+        offset: i64,
+        limit: Option<i64>,
+    ) -> Result<Vec<String>, sqlx::Error> {
+        let stored: Vec<String> = sqlx::query_scalar(
+            "SELECT value FROM xcloud_lists WHERE table_name = ?1 AND key = ?2
+             ORDER BY position LIMIT ?3 OFFSET ?4",
+        )
+        .bind(Utils::sanitize(table))
+        .bind(key)
+        .bind(limit.unwrap_or(-1))
+        .bind(offset.max(0))
+        .fetch_all(self.read_pool(table))
+        .await?;
+        stored
+            .into_iter()
+            .map(|stored| self.open_value(stored))
+            .collect()
+    }
+
+    /// Initializes the tables holding the event logs, their events on every shard and the
+    /// offsets committed by their consumer groups.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the tables cannot be initialized.
+    pub async fn init_event_logs(&self) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS xcloud_event_logs (
+                name TEXT PRIMARY KEY,
+                max_age_secs INTEGER,
+                max_events INTEGER
+            )",
+        )
+        .execute(&*self.pool)
+        .await?;
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS xcloud_consumer_offsets (
+                log TEXT NOT NULL,
+                consumer_group TEXT NOT NULL,
+                \"offset\" INTEGER NOT NULL,
+                PRIMARY KEY (log, consumer_group)
+            )",
+        )
+        .execute(&*self.pool)
+        .await?;
+        for shard in &self.shards {
+            sqlx::query(
+                "CREATE TABLE IF NOT EXISTS xcloud_events (
+                    log TEXT NOT NULL,
+                    \"offset\" INTEGER NOT NULL,
+                    timestamp INTEGER NOT NULL,
+                    value TEXT NOT NULL,
+                    PRIMARY KEY (log, \"offset\")
+                )",
+            )
+            .execute(&**shard)
+            .await?;
+            sqlx::query(
+                "CREATE INDEX IF NOT EXISTS xcloud_events_timestamp
+                 ON xcloud_events (log, timestamp)",
+            )
+            .execute(&**shard)
+            .await?;
+            sqlx::query(
+                "CREATE TABLE IF NOT EXISTS xcloud_event_heads (
+                    log TEXT PRIMARY KEY,
+                    next_offset INTEGER NOT NULL
+                )",
+            )
+            .execute(&**shard)
+            .await?;
+        }
+        Ok(())
+    }
+
+    /// Creates an event log.
+    ///
+    /// # Arguments
+    ///
+    /// * `log` - The name and the retention of the log.
+    ///
+    /// # Returns
+    ///
+    /// * `bool` - Whether the log was created, `false` if it already exists.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the log cannot be created.
+    pub async fn create_event_log(&self, log: &EventLog) -> Result<bool, sqlx::Error> {
+        let created = sqlx::query(
+            "INSERT OR IGNORE INTO xcloud_event_logs (name, max_age_secs, max_events)
+             VALUES (?1, ?2, ?3)",
+        )
+        .bind(Utils::sanitize(&log.name))
+        .bind(log.max_age_secs)
+        .bind(log.max_events)
+        .execute(&*self.pool)
+        .await?
+        .rows_affected();
+        Ok(created > 0)
+    }
+
+    /// Retrieves all event logs.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the logs cannot be retrieved.
+    pub async fn event_logs(&self) -> Result<Vec<EventLog>, sqlx::Error> {
+        sqlx::query_as("SELECT name, max_age_secs, max_events FROM xcloud_event_logs ORDER BY name")
+            .fetch_all(&*self.pool)
+            .await
+    }
+
+    /// Checks whether an event log exists.
+    async fn has_event_log(&self, log: &str) -> Result<bool, sqlx::Error> {
+        let exists: Option<i64> =
+            sqlx::query_scalar("SELECT 1 FROM xcloud_event_logs WHERE name = ?1")
+                .bind(Utils::sanitize(log))
+                .fetch_optional(&*self.pool)
+                .await?;
+        Ok(exists.is_some())
+    }
+
+    /// Deletes an event log with its events and the offsets of its consumer groups.
+    ///
+    /// # Arguments
+    ///
+    /// * `log` - The name of the log.
+    ///
+    /// # Returns
+    ///
+    /// * `bool` - Whether the log existed.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the log cannot be deleted.
+    pub async fn delete_event_log(&self, log: &str) -> Result<bool, sqlx::Error> {
+        let log = Utils::sanitize(log);
+        let deleted = sqlx::query("DELETE FROM xcloud_event_logs WHERE name = ?1")
+            .bind(&log)
+            .execute(&*self.pool)
+            .await?
+            .rows_affected();
+        sqlx::query("DELETE FROM xcloud_consumer_offsets WHERE log = ?1")
+            .bind(&log)
+            .execute(&*self.pool)
+            .await?;
+        let mut tx = self.shard(&log).begin().await?;
+        for table in ["xcloud_events", "xcloud_event_heads"] {
+            sqlx::query(&format!("DELETE FROM {} WHERE log = ?1", table))
+                .bind(&log)
+                .execute(&mut *tx)
+                .await?;
+        }
+        tx.commit().await?;
+        Ok(deleted > 0)
+    }
+
+    /// Appends events to an event log.
+    ///
+    /// Offsets are assigned consecutively and never reused, even once older events are pruned.
+    ///
+    /// # Arguments
+    ///
+    /// * `log` - The name of the log.
+    /// * `values` - The values of the events, in order.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<i64>` - The offset of the first appended event, or `None` if the log does not
+    ///   exist.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the events cannot be appended.
+    pub async fn append_events(
+        &self,
+        log: &str,
+        values: &[String],
+    ) -> Result<Option<i64>, sqlx::Error> {
+        if !self.has_event_log(log).await? {
+            return Ok(None);
+        }
+        let log = Utils::sanitize(log);
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_millis() as i64);
+        let mut tx = self.shard(&log).begin().await?;
+        let first: i64 = sqlx::query_scalar(
+            "INSERT INTO xcloud_event_heads (log, next_offset) VALUES (?1, ?2)
+             ON CONFLICT(log) DO UPDATE SET next_offset = next_offset + ?2
+             RETURNING next_offset - ?2",
+        )
+        .bind(&log)
+        .bind(values.len() as i64)
+        .fetch_one(&mut *tx)
+        .await?;
+        for (offset, value) in (first..).zip(values) {
+            sqlx::query(
+                "INSERT INTO xcloud_events (log, \"offset\", timestamp, value)
+                 VALUES (?1, ?2, ?3, ?4)",
+            )
+            .bind(&log)
+            .bind(offset)
+            .bind(timestamp)
+            .bind(self.seal_value(value)?)
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
+        Ok(Some(first))
+    }
+
+    /// Reads the events of an event log in order.
+    ///
+    /// # Arguments
+    ///
+    /// * `log` - The name of the log.
+    /// * `offset` - The offset of the first event to read.
+    /// * `since` - The unix timestamp in milliseconds before which events are skipped, if any.
+    /// * `limit` - The maximum number of events to read, unlimited if `None`.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<Vec<Event>>` - The events, or `None` if the log does not exist.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the events cannot be read.
+    pub async fn read_events(
+        &self,
+        log: &str,
+        offset: i64,
+        since: Option<i64>,
+        limit: Option<i64>,
+    ) -> Result<Option<Vec<Event>>, sqlx::Error> {
+        if !self.has_event_log(log).await? {
+            return Ok(None);
+        }
+        let events: Vec<Event> = sqlx::query_as(
+            "SELECT \"offset\", timestamp, value FROM xcloud_events
+             WHERE log = ?1 AND \"offset\" >= ?2 AND timestamp >= ?3
+             ORDER BY \"offset\" LIMIT ?4",
+        )
+        .bind(Utils::sanitize(log))
+        .bind(offset)
+        .bind(since.unwrap_or(i64::MIN))
+        .bind(limit.unwrap_or(-1))
+        .fetch_all(self.read_pool(log))
+        .await?;
+        events
+            .into_iter()
+            .map(|event| {
+                Ok(Event {
+                    value: self.open_value(event.value)?,
+                    ..event
+                })
+            })
+            .collect::<Result<_, _>>()
+            .map(Some)
+    }
+
+    /// Returns the offset the next event appended to a log will get.
+    async fn event_head(&self, log: &str) -> Result<i64, sqlx::Error> {
+        let head: Option<i64> =
+            sqlx::query_scalar("SELECT next_offset FROM xcloud_event_heads WHERE log = ?1")
+                .bind(Utils::sanitize(log))
+                .fetch_optional(self.shard(log))
+                .await?;
+        Ok(head.unwrap_or_default())
+    }
+
+    /// Commits the offset of the next event a consumer group of an event log reads.
+    ///
+    /// # Arguments
+    ///
+    /// * `log` - The name of the log.
+    /// * `group` - The name of the consumer group.
+    /// * `offset` - The offset of the next event the group reads.
+    ///
+    /// # Returns
+    ///
+    /// * `bool` - Whether the log exists.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the offset cannot be committed.
+    pub async fn commit_consumer_offset(
+        &self,
+        log: &str,
+        group: &str,
+        offset: i64,
+    ) -> Result<bool, sqlx::Error> {
+        if !self.has_event_log(log).await? {
+            return Ok(false);
+        }
+        sqlx::query(
+            "INSERT OR REPLACE INTO xcloud_consumer_offsets (log, consumer_group, \"offset\")
+             VALUES (?1, ?2, ?3)",
+        )
+        .bind(Utils::sanitize(log))
+        .bind(group)
+        .bind(offset)
+        .execute(&*self.pool)
+        .await?;
+        Ok(true)
+    }
+
+    /// Retrieves the offsets committed by the consumer groups of an event log.
+    ///
+    /// # Arguments
+    ///
+    /// * `log` - The name of the log.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<Vec<ConsumerOffset>>` - The offsets and lags by group, or `None` if the log
+    ///   does not exist.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the offsets cannot be retrieved.
+    pub async fn consumer_offsets(
+        &self,
+        log: &str,
+    ) -> Result<Option<Vec<ConsumerOffset>>, sqlx::Error> {
+        if !self.has_event_log(log).await? {
+            return Ok(None);
+        }
+        let head = self.event_head(log).await?;
+        let offsets: Vec<(String, i64)> = sqlx::query_as(
+            "SELECT consumer_group, \"offset\" FROM xcloud_consumer_offsets
+             WHERE log = ?1 ORDER BY consumer_group",
+        )
+        .bind(Utils::sanitize(log))
+        .fetch_all(&*self.pool)
+        .await?;
+        Ok(Some(
+            offsets
+                .into_iter()
+                .map(|(group, offset)| ConsumerOffset {
+                    group,
+                    offset,
+                    lag: (head - offset).max(0),
+                })
+                .collect(),
+        ))
+    }
+
+    /// Prunes the events exceeding the retention of their event logs.
+    ///
+    /// # Returns
+    ///
+    /// * `u64` - The number of pruned events.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the events cannot be pruned.
+    pub async fn prune_event_logs(&self) -> Result<u64, sqlx::Error> {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_millis() as i64);
+        let mut pruned = 0;
+        for log in self.event_logs().await? {
+            if let Some(max_age_secs) = log.max_age_secs {
+                pruned +=
+                    sqlx::query("DELETE FROM xcloud_events WHERE log = ?1 AND timestamp < ?2")
+                        .bind(&log.name)
+                        .bind(now - max_age_secs.saturating_mul(1000))
+                        .execute(self.shard(&log.name))
+                        .await?
+                        .rows_affected();
+            }
+            if let Some(max_events) = log.max_events {
+                let head = self.event_head(&log.name).await?;
+                pruned +=
+                    sqlx::query("DELETE FROM xcloud_events WHERE log = ?1 AND \"offset\" < ?2")
+                        .bind(&log.name)
+                        .bind(head - max_events.max(0))
+                        .execute(self.shard(&log.name))
+                        .await?
+                        .rows_affected();
+            }
+        }
+        Ok(pruned)
+    }
+
+    /// Initializes the tables holding the time series, their raw samples and the buckets their
+    /// older samples are downsampled into.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the tables cannot be initialized.
+    pub async fn init_time_series(&self) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS xcloud_series (
+                name TEXT PRIMARY KEY,
+                bucket_secs INTEGER NOT NULL,
+                downsample_after_secs INTEGER
+            )",
+        )
+        .execute(&*self.pool)
+        .await?;
+        for shard in &self.shards {
+            sqlx::query(
+                "CREATE TABLE IF NOT EXISTS xcloud_series_points (
+                    series TEXT NOT NULL,
+                    key TEXT NOT NULL,
+                    timestamp INTEGER NOT NULL,
+                    value REAL NOT NULL,
+                    PRIMARY KEY (series, key, timestamp)
+                ) WITHOUT ROWID",
+            )
+            .execute(&**shard)
+            .await?;
+            sqlx::query(
+                "CREATE TABLE IF NOT EXISTS xcloud_series_buckets (
+                    series TEXT NOT NULL,
+                    key TEXT NOT NULL,
+                    start INTEGER NOT NULL,
+                    count INTEGER NOT NULL,
+                    sum REAL NOT NULL,
+                    min REAL NOT NULL,
+                    max REAL NOT NULL,
+                    PRIMARY KEY (series, key, start)
+                ) WITHOUT ROWID",
+            )
+            .execute(&**shard)
+            .await?;
+        }
+        Ok(())
+    }
+
+    /// Creates a time series.
+    ///
+    /// # Arguments
+    ///
+    /// * `series` - The name and the downsampling of the series.
+    ///
+    /// # Returns
+    ///
+    /// * `bool` - Whether the series was created, `false` if it already exists.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the series cannot be created.
+    pub async fn create_time_series(&self, series: &TimeSeries) -> Result<bool, sqlx::Error> {
+        let created = sqlx::query(
+            "INSERT OR IGNORE INTO xcloud_series (name, bucket_secs, downsample_after_secs)
+             VALUES (?1, ?2, ?3)",
+        )
+        .bind(Utils::sanitize(&series.name))
+        .bind(series.bucket_secs.max(1))
+        .bind(series.downsample_after_secs)
+        .execute(&*self.pool)
+        .await?
+        .rows_affected();
+        Ok(created > 0)
+    }
+
+    /// Retrieves all time series.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the series cannot be retrieved.
+    pub async fn time_series(&self) -> Result<Vec<TimeSeries>, sqlx::Error> {
+        sqlx::query_as(
+            "SELECT name, bucket_secs, downsample_after_secs FROM xcloud_series ORDER BY name",
+        )
+        .fetch_all(&*self.pool)
+        .await
+    }
+
+    /// Checks whether a time series exists.
+    async fn has_time_series(&self, series: &str) -> Result<bool, sqlx::Error> {
+        let exists: Option<i64> = sqlx::query_scalar("SELECT 1 FROM xcloud_series WHERE name = ?1")
+            .bind(Utils::sanitize(series))
+            .fetch_optional(&*self.pool)
+            .await?;
+        Ok(exists.is_some())
+    }
+
+    /// Deletes a time series with its samples and buckets.
+    ///
+    /// # Arguments
+    ///
+    /// * `series` - The name of the series.
+    ///
+    /// # Returns
+    ///
+    /// * `bool` - Whether the series existed.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the series cannot be deleted.
+    pub async fn delete_time_series(&self, series: &str) -> Result<bool, sqlx::Error> {
+        let series = Utils::sanitize(series);
+        let deleted = sqlx::query("DELETE FROM xcloud_series WHERE name = ?1")
+            .bind(&series)
+            .execute(&*self.pool)
+            .await?
+            .rows_affected();
+        let mut tx = self.shard(&series).begin().await?;
+        for table in ["xcloud_series_points", "xcloud_series_buckets"] {
+            sqlx::query(&format!("DELETE FROM {} WHERE series = ?1", table))
+                .bind(&series)
+                .execute(&mut *tx)
+                .await?;
+        }
+        tx.commit().await?;
+        Ok(deleted > 0)
+    }
+
+    /// Records samples of a time series, replacing any sample of the same key and timestamp.
+    ///
+    /// # Arguments
+    ///
+    /// * `series` - The name of the series.
+    /// * `points` - The samples.
+    ///
+    /// # Returns
+    ///
+    /// * `bool` - Whether the series exists.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the samples cannot be recorded.
+    pub async fn append_points(
+        &self,
+        series: &str,
+        points: &[SeriesPoint],
+    ) -> Result<bool, sqlx::Error> {
+        if !self.has_time_series(series).await? {
+            return Ok(false);
+        }
+        let series = Utils::sanitize(series);
+        let mut tx = self.shard(&series).begin().await?;
+        for point in points {
+            sqlx::query(
+                "INSERT OR REPLACE INTO xcloud_series_points (series, key, timestamp, value)
+                 VALUES (?1, ?2, ?3, ?4)",
+            )
+            .bind(&series)
+            .bind(&point.key)
+            .bind(point.timestamp)
+            .bind(point.value)
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
+        Ok(true)
+    }
+
+    /// Reads the raw samples of a key of a time series within a range of time, oldest first.
+    ///
+    /// Downsampled samples are only available through [`Database::aggregate_points`].
+    ///
+    /// # Arguments
+    ///
+    /// * `series` - The name of the series.
+    /// * `key` - The key.
+    /// * `from` - The unix timestamp in milliseconds of the first sample, inclusive.
+    /// * `to` - The unix timestamp in milliseconds of the last sample, exclusive.
+    /// * `limit` - The maximum number of samples to read, unlimited if `None`.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<Vec<SeriesPoint>>` - The samples, or `None` if the series does not exist.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the samples cannot be read.
+    pub async fn read_points(
+        &self,
+        series: &str,
+        key: &str,
+        from: i64,
+        to: i64,
+        limit: Option<i64>,
+    ) -> Result<Option<Vec<SeriesPoint>>, sqlx::Error> {
+        if !self.has_time_series(series).await? {
+            return Ok(None);
+        }
+        sqlx::query_as(
+            "SELECT key, timestamp, value FROM xcloud_series_points
+             WHERE series = ?1 AND key = ?2 AND timestamp >= ?3 AND timestamp < ?4
+             ORDER BY timestamp LIMIT ?5",
+        )
+        .bind(Utils::sanitize(series))
+        .bind(key)
+        .bind(from)
+        .bind(to)
+        .bind(limit.unwrap_or(-1))
+        .fetch_all(self.read_pool(series))
+        .await
+        .map(Some)
+    }
+
+    /// Aggregates the samples of a key of a time series within a range of time into buckets,
+    /// combining the raw samples with the downsampled ones.
+    ///
+    /// Downsampled samples are counted in the bucket holding the start of their downsampling
+    /// bucket, so buckets narrower than those of the series are only exact for raw samples.
+    ///
+    /// # Arguments
+    ///
+    /// * `series` - The name of the series.
+    /// * `key` - The key.
+    /// * `from` - The unix timestamp in milliseconds of the first sample, inclusive.
+    /// * `to` - The unix timestamp in milliseconds of the last sample, exclusive.
+    /// * `bucket_ms` - The width in milliseconds of the buckets, aligned to the unix epoch.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<Vec<SeriesBucket>>` - The non-empty buckets, oldest first, or `None` if the
+    ///   series does not exist.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the samples cannot be aggregated.
+    pub async fn aggregate_points(
+        &self,
+        series: &str,
+        key: &str,
+        from: i64,
+        to: i64,
+        bucket_ms: i64,
+    ) -> Result<Option<Vec<SeriesBucket>>, sqlx::Error> {
+        if !self.has_time_series(series).await? {
+            return Ok(None);
+        }
+        sqlx::query_as(
+            "SELECT start, SUM(count) AS count, SUM(sum) / SUM(count) AS avg,
+                    MIN(min) AS min, MAX(max) AS max
+             FROM (
+                SELECT (timestamp / ?5) * ?5 AS start, COUNT(*) AS count, SUM(value) AS sum,
+                       MIN(value) AS min, MAX(value) AS max
+                FROM xcloud_series_points
+                WHERE series = ?1 AND key = ?2 AND timestamp >= ?3 AND timestamp < ?4
+                GROUP BY 1
+                UNION ALL
+                SELECT (start / ?5) * ?5, SUM(count), SUM(sum), MIN(min), MAX(max)
+                FROM xcloud_series_buckets
+                WHERE series = ?1 AND key = ?2 AND start >= ?3 AND start < ?4
+                GROUP BY 1
+             )
+             GROUP BY start ORDER BY start",
+        )
+        .bind(Utils::sanitize(series))
+        .bind(key)
+        .bind(from)
+        .bind(to)
+        .bind(bucket_ms.max(1))
+        .fetch_all(self.read_pool(series))
+        .await
+        .map(Some)
+    }
+
+    /// Downsamples the samples of every time series older than its downsampling age into
+    /// buckets, merging them into any bucket already holding older samples.
+    ///
+    /// # Returns
+    ///
+    /// * `u64` - The number of downsampled samples.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the samples cannot be downsampled.
+    pub async fn downsample_time_series(&self) -> Result<u64, sqlx::Error> {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_millis() as i64);
+        let mut downsampled = 0;
+        for series in self.time_series().await? {
+            let Some(after_secs) = series.downsample_after_secs else {
+                continue;
+            };
+            let bucket_ms = series.bucket_secs.max(1).saturating_mul(1000);
+            // Only whole buckets are downsampled, so a bucket never mixes in later samples.
+            let cutoff = (now - after_secs.saturating_mul(1000)) / bucket_ms * bucket_ms;
+            let mut tx = self.shard(&series.name).begin().await?;
+            sqlx::query(
+                "INSERT INTO xcloud_series_buckets (series, key, start, count, sum, min, max)
+                 SELECT series, key, (timestamp / ?3) * ?3, COUNT(*), SUM(value), MIN(value),
+                        MAX(value)
+                 FROM xcloud_series_points
+                 WHERE series = ?1 AND timestamp < ?2
+                 GROUP BY key, 3
+                 ON CONFLICT(series, key, start) DO UPDATE SET
+                    count = count + excluded.count,
+                    sum = sum + excluded.sum,
+                    min = MIN(min, excluded.min),
+                    max = MAX(max, excluded.max)",
+            )
+            .bind(&series.name)
+            .bind(cutoff)
+            .bind(bucket_ms)
+            .execute(&mut *tx)
+            .await?;
+            downsampled += sqlx::query(
+                "DELETE FROM xcloud_series_points WHERE series = ?1 AND timestamp < ?2",
+            )
+            .bind(&series.name)
+            .bind(cutoff)
+            .execute(&mut *tx)
+            .await?
+            .rows_affected();
+            tx.commit().await?;
+        }
+        Ok(downsampled)
+    }
+
+    /// Initializes the sync log holding the version of every written key.
+    ///
+    /// Every write replaces the row of its key, assigning it the next version, so the versions
+    /// order the latest changes of all keys. Deleted keys keep a tombstone.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the table cannot be created.
+    pub async fn init_sync_log(&self) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS xcloud_sync_log (
+                version INTEGER PRIMARY KEY AUTOINCREMENT,
+                table_name TEXT NOT NULL,
+                key TEXT NOT NULL,
+                deleted INTEGER NOT NULL DEFAULT 0,
+                UNIQUE (table_name, key)
+            )",
+        )
+        .execute(&*self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Assigns the next version to a written key, or to every key of a deleted table, advancing
+    /// the consistency token.
+    ///
+    /// # Arguments
+    ///
+    /// * `table` - The table that was written to.
+    /// * `key` - The key that was written, if any.
+    /// * `op` - The kind of write.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the version cannot be recorded.
+    async fn record_sync_version(
+        &self,
+        table: &str,
+        key: Option<&str>,
+        op: &str,
+    ) -> Result<(), sqlx::Error> {
+        let query = match (key, op) {
+            (Some(_), "set" | "update" | "delete") => sqlx::query(
+                "INSERT OR REPLACE INTO xcloud_sync_log (table_name, key, deleted)
+                 VALUES (?1, ?2, ?3)",
+            ),
+            (None, "delete_table") => sqlx::query(
+                "INSERT OR REPLACE INTO xcloud_sync_log (table_name, key, deleted)
+                 SELECT table_name, key, 1 FROM xcloud_sync_log
+                 WHERE table_name = ?1 AND deleted = 0 ORDER BY version",
+            ),
+            _ => return Ok(()),
+        };
+        let result = query
+            .bind(Utils::sanitize(table))
+            .bind(key)
+            .bind(op == "delete")
+            .execute(&*self.pool)
+            .await?;
+        if result.rows_affected() > 0 {
+            self.consistency.record_write(result.last_insert_rowid());
+        }
+        Ok(())
+    }
+
+    /// Retrieves the version of a key.
+    ///
+    /// # Arguments
+    ///
+    /// * `table` - The table holding the key.
+    /// * `key` - The key.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<i64>` - The version of the last write to the key, `None` if it was never
+    ///   written.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the version cannot be retrieved.
+    pub async fn sync_version(&self, table: &str, key: &str) -> Result<Option<i64>, sqlx::Error> {
+        sqlx::query_scalar("SELECT version FROM xcloud_sync_log WHERE table_name = ?1 AND key = ?2")
+            .bind(Utils::sanitize(table))
+            .bind(key)
+            .fetch_optional(&*self.pool)
+            .await
+    }
+
+    /// Retrieves the keys changed after a checkpoint with their current values, oldest change
+    /// first.
+    ///
+    /// # Arguments
+    ///
+    /// * `since` - The checkpoint, the highest version the client already pulled.
+    /// * `table` - The table to restrict the changes to, or `None` for all tables.
+    /// * `limit` - The maximum number of changes to return.
+    ///
+    /// # Returns
+    ///
+    /// * `Vec<RemoteChange>` - The changes, the version of the last one being the next
+    ///   checkpoint.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the changes cannot be retrieved.
+    pub async fn pull_changes(
+        &self,
+        since: i64,
+        table: Option<&str>,
+        limit: i64,
+    ) -> Result<Vec<RemoteChange>, sqlx::Error> {
+        let mut changes: Vec<RemoteChange> = sqlx::query_as(
+            "SELECT table_name, key, version, deleted FROM xcloud_sync_log
+             WHERE version > ?1 AND (?2 IS NULL OR table_name = ?2)
+             ORDER BY version LIMIT ?3",
+        )
+        .bind(since)
+        .bind(table.map(Utils::sanitize))
+        .bind(limit)
+        .fetch_all(&*self.pool)
+        .await?;
+        for change in changes.iter_mut().filter(|change| !change.deleted) {
+            change.value = self.get_data(&change.table, &change.key).await?;
+        }
+        Ok(changes)
+    }
+
+    /// Applies a change a client made while offline, resolving it deterministically if the key
+    /// changed since the version the change was made from.
+    ///
+    /// A change whose value equals the current value is applied without writing, so pushes can
+    /// be retried. A merged value not matching the table schema is rejected.
+    ///
+    /// # Arguments
+    ///
+    /// * `change` - The change.
+    /// * `resolution` - How a conflicting change is resolved.
+    ///
+    /// # Returns
+    ///
+    /// * `SyncOutcome` - Whether the change was applied, with the resulting version and value.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the change cannot be applied.
+    pub async fn apply_client_change(
+        &self,
+        change: &ClientChange,
+        resolution: SyncResolution,
+    ) -> Result<SyncOutcome, sqlx::Error> {
+        let current = self.get_data(&change.table, &change.key).await?;
+        let version = self.sync_version(&change.table, &change.key).await?;
+        let outcome = |status, version, value| SyncOutcome {
+            table: change.table.clone(),
+            key: change.key.clone(),
+            status,
+            version,
+            value,
+        };
+        if current == change.value {
+            return Ok(outcome(SyncStatus::Applied, version, current));
+        }
+        let (status, value) =
+            if version == change.base_version || resolution == SyncResolution::ClientWins {
+                (SyncStatus::Applied, change.value.clone())
+            } else {
+                let merged = match resolution {
+                    SyncResolution::Merge => {
+                        self.merge_client_change(change, current.as_deref()).await?
+                    }
+                    _ => None,
+                };
+                match merged {
+                    Some(merged) => (SyncStatus::Merged, Some(merged)),
+                    None => return Ok(outcome(SyncStatus::Rejected, version, current)),
+                }
+            };
+        match &value {
+            Some(value) => self.set_data(&change.table, &change.key, value).await?,
+            None => self.delete_data(&change.table, &change.key).await?,
+        }
+        let version = self.sync_version(&change.table, &change.key).await?;
+        Ok(outcome(status, version, value))
+    }
+
+    /// Merges the fields a change modified from its base into the current JSON value.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<String>` - The merged value, `None` if the values cannot be merged.
+    async fn merge_client_change(
+        &self,
+        change: &ClientChange,
+        current: Option<&str>,
+    ) -> Result<Option<String>, sqlx::Error> {
+        let (Some(base), Some(value), Some(current)) = (&change.base, &change.value, current)
+        else {
+            return Ok(None);
+        };
+        if self.get_value_type(&change.table).await? != ValueType::Json {
+            return Ok(None);
+        }
+        let Ok(merged) = ValuePatch::diff(base, value).and_then(|patch| patch.apply(current))
+        else {
+            return Ok(None);
+        };
+        Ok(self
+            .validate_value(&change.table, &merged)
+            .await?
+            .is_ok()
+            .then_some(merged))
+    }
+
+    /// Lazily creates the full-text index of a table, kept in sync by triggers.
+    ///
+    /// The triggers index the values as they are stored, so the index is refused while values
+    /// are encrypted, compressed, deduplicated or archived.
+    ///
+    /// # Arguments
+    ///
+    /// * `table` - The table to index.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if values are not stored as written or the index
+    /// cannot be created.
+    pub async fn init_fulltext(&self, table: &str) -> Result<(), sqlx::Error> {
+        if self.keyring.is_some() {
+            return Err(sqlx::Error::Protocol(
+                "full-text search is unavailable while values are encrypted".to_string(),
+            ));
+        }
+        if !self.stores_plain_values() {
+            return Err(sqlx::Error::Protocol(
+                "full-text search is unavailable while values are compressed, deduplicated or archived"
+                    .to_string(),
+            ));
+        }
+        let table = self.table_name(table).await?;
+        let (quoted, fts) = (Ident::quote(&table), Ident::derived("xcloud_fts_", &table));
+        let exists: Option<String> =
+            sqlx::query_scalar("SELECT name FROM sqlite_master WHERE type = 'table' AND name = ?1")
+                .bind(format!("xcloud_fts_{}", table))
+                .fetch_optional(self.shard(&table))
+                .await?;
+        if exists.is_some() {
+            return Ok(());
+        }
+        let triggers = [
+            (
+                "ai",
+                "AFTER INSERT",
+                "INSERT OR REPLACE INTO {0} (rowid, key, value) VALUES (new.rowid, new.key, new.value);",
+            ),
+            (
+                "au",
+                "AFTER UPDATE",
+                "DELETE FROM {0} WHERE rowid = old.rowid;
+                 INSERT INTO {0} (rowid, key, value) VALUES (new.rowid, new.key, new.value);",
+            ),
+            (
+                "ad",
+                "AFTER DELETE",
+                "DELETE FROM {0} WHERE rowid = old.rowid;",
+            ),
+        ];
+        self.init_table(&table).await?;
+        let mut tx = self.shard(&table).begin().await?;
+        sqlx::query(&format!(
+            "CREATE VIRTUAL TABLE {} USING fts5(key UNINDEXED, value)",
+            fts
+        ))
+        .execute(&mut *tx)
+        .await?;
+        for (suffix, event, body) in triggers {
+            sqlx::query(&format!(
+                "CREATE TRIGGER IF NOT EXISTS {} {} ON {} BEGIN {} END",
+                Ident::derived("xcloud_fts_", &format!("{}_{}", table, suffix)),
+                event,
+                quoted,
+                body.replace("{0}", &fts)
+            ))
+            .execute(&mut *tx)
+            .await?;
+        }
+        sqlx::query(&format!(
+            "INSERT INTO {} (rowid, key, value) SELECT rowid, key, value FROM {}",
+            fts, quoted
+        ))
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await
+    }
+
+    /// Searches the values of a table for all words of the query, ranked by relevance.
+    ///
+    /// # Arguments
+    ///
+    /// * `table` - The table to search.
+    /// * `query` - The words to search for.
+    /// * `offset` - The number of hits to skip.
+    /// * `limit` - The maximum number of hits to return.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the search cannot be performed.
+    #[tracing::instrument(level = "debug", skip(self), err)]
+    pub async fn fulltext_search(
+        &self,
+        table: &str,
+        query: &str,
+        offset: i64,
+        limit: i64,
+    ) -> Result<Vec<SearchHit>, sqlx::Error> {
+        self.init_fulltext(table).await?;
+        let terms = query
+            .split_whitespace()
+            .map(Ident::quote)
+            .collect::<Vec<_>>()
+            .join(" ");
+        if terms.is_empty() {
+            return Ok(Vec::new());
+        }
+        let sql = format!(
+            "SELECT t.key, {2}, -{0}.rank
+             FROM {0}
+             JOIN {1} t
+               ON t.rowid = {0}.rowid AND t.key = {0}.key
+             WHERE {0} MATCH ?1
+             ORDER BY {0}.rank
+             LIMIT ?2 OFFSET ?3",
+            Ident::derived("xcloud_fts_", table),
+            self.table(table).await?,
+            Self::stored_columns("t.")
+        );
+        let query = |pool| {
+            sqlx::query_as::<_, (String, String, Option<String>, Option<String>, f64)>(&sql)
+                .bind(&terms)
+                .bind(limit)
+                .bind(offset.max(0))
+                .fetch_all(pool)
+        };
+        let hits = self
+            .timed_read(table, "fulltext_search", &sql, query)
+            .await?;
+        hits.into_iter()
+            .map(|(key, value, codec, tier, score)| {
+                Ok(SearchHit {
+                    key,
+                    value: self.open_stored(value, codec.as_deref(), tier.as_deref())?,
+                    score,
+                })
+            })
+            .collect()
+    }
+
+    /// Initializes the table holding the persisted server settings.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the table cannot be initialized.
+    pub async fn init_settings(&self) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS xcloud_settings (
+                name TEXT PRIMARY KEY,
+                value TEXT NOT NULL
+            )",
+        )
+        .execute(&*self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Retrieves a persisted server setting.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the setting.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<String>` - The value of the setting, or `None` if it is not set.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the setting cannot be retrieved.
+    pub async fn get_setting(&self, name: &str) -> Result<Option<String>, sqlx::Error> {
+        sqlx::query_scalar("SELECT value FROM xcloud_settings WHERE name = ?1")
+            .bind(name)
+            .fetch_optional(&*self.pool)
+            .await
+    }
+
+    /// Persists a server setting, replacing any previous value.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the setting.
+    /// * `value` - The value of the setting.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the setting cannot be stored.
+    pub async fn set_setting(&self, name: &str, value: &str) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            "INSERT INTO xcloud_settings (name, value) VALUES (?1, ?2)
+             ON CONFLICT(name) DO UPDATE SET value = excluded.value",
+        )
+        .bind(name)
+        .bind(value)
+        .execute(&*self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Initializes the table holding the feature flags.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the table cannot be initialized.
+    pub async fn init_feature_flags(&self) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS xcloud_feature_flags (
+                name TEXT PRIMARY KEY,
+                definition TEXT NOT NULL
+            )",
+        )
+        .execute(&*self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Retrieves the feature flags, ordered by name.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the flags cannot be retrieved.
+    pub async fn feature_flags(&self) -> Result<Vec<FeatureFlag>, sqlx::Error> {
+        let rows: Vec<(String, String)> =
+            sqlx::query_as("SELECT name, definition FROM xcloud_feature_flags ORDER BY name")
+                .fetch_all(&*self.pool)
+                .await?;
+        Ok(rows
+            .into_iter()
+            .filter_map(
+                |(name, definition)| match serde_json::from_str(&definition) {
+                    Ok(flag) => Some(flag),
+                    Err(e) => {
+                        log::error!("Ignoring invalid feature flag {}: {}", name, e);
+                        None
+                    }
+                },
+            )
+            .collect())
+    }
+
+    /// Persists a feature flag, replacing any flag of the same name.
+    ///
+    /// # Arguments
+    ///
+    /// * `flag` - The flag.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the flag cannot be stored.
+    pub async fn set_feature_flag(&self, flag: &FeatureFlag) -> Result<(), sqlx::Error> {
+        let definition =
+            serde_json::to_string(flag).map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+        sqlx::query(
+            "INSERT INTO xcloud_feature_flags (name, definition) VALUES (?1, ?2)
+             ON CONFLICT(name) DO UPDATE SET definition = excluded.definition",
+        )
+        .bind(&flag.name)
+        .bind(definition)
+        .execute(&*self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Removes a feature flag.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the flag.
+    ///
+    /// # Returns
+    ///
+    /// * `bool` - Whether the flag existed.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the flag cannot be removed.
+    pub async fn delete_feature_flag(&self, name: &str) -> Result<bool, sqlx::Error> {
+        let removed = sqlx::query("DELETE FROM xcloud_feature_flags WHERE name = ?1")
+            .bind(name)
+            .execute(&*self.pool)
+            .await?
+            .rows_affected();
+        Ok(removed > 0)
+    }
+
+    /// Initializes the table holding the writes awaiting replication.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the table cannot be initialized.
+    pub async fn init_outbox(&self) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS xcloud_outbox (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                table_name TEXT NOT NULL,
+                key TEXT,
+                op TEXT NOT NULL,
+                value TEXT,
+                timestamp INTEGER NOT NULL DEFAULT (CAST(strftime('%s', 'now') AS INTEGER))
+            )",
+        )
+        .execute(&*self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Records a write and updates the keys derived from the written key.
+    ///
+    /// # Arguments
+    ///
+    /// * `table` - The table that was written to.
+    /// * `key` - The key that was written, if any.
+    /// * `op` - The kind of write.
+    /// * `value` - The new value of the key, if any.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the write cannot be recorded.
+    async fn record_change(
+        &self,
+        table: &str,
+        key: Option<&str>,
+        op: &str,
+        value: Option<&str>,
+    ) -> Result<(), sqlx::Error> {
+        self.log_change(table, key, op, value).await?;
+        match (key, op) {
+            (Some(key), "set" | "update" | "delete") => {
+                self.apply_transforms(table, key, value).await
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Records a write in the outbox if replication is enabled, and in the history of the key.
+    ///
+    /// # Arguments
+    ///
+    /// * `table` - The table that was written to.
+    /// * `key` - The key that was written, if any.
+    /// * `op` - The kind of write.
+    /// * `value` - The new value of the key, if any.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the write cannot be recorded.
+    async fn log_change(
+        &self,
+        table: &str,
+        key: Option<&str>,
+        op: &str,
+        value: Option<&str>,
+    ) -> Result<(), sqlx::Error> {
+        match (key, op) {
+            (Some(key), "set" | "update" | "delete") => {
+                self.record_history(table, key, value).await?;
+                self.apply_views(table, key, value).await?;
+            }
+            (None, "delete_table") => self.clear_views(table).await?,
+            _ => {}
+        }
+        self.record_sync_version(table, key, op).await?;
+        self.record_write_time(table, key, op).await?;
+        self.record_activity(table, key, op).await?;
+        if let (Some(stats), Some(key)) = (&self.key_stats, key) {
+            stats.record_write(table, key);
+        }
+        if !self.outbox {
+            return Ok(());
+        }
+        sqlx::query(
+            "INSERT INTO xcloud_outbox (table_name, key, op, value) VALUES (?1, ?2, ?3, ?4)",
+        )
+        .bind(Utils::sanitize(table))
+        .bind(key)
+        .bind(op)
+        .bind(value.map(|value| self.seal_value(value)).transpose()?)
+        .execute(&*self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Retrieves the recorded writes following the given cursor, oldest first.
+    ///
+    /// # Arguments
+    ///
+    /// * `cursor` - The id of the last write already replicated.
+    /// * `limit` - The maximum number of writes to return.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the writes cannot be retrieved.
+    pub async fn outbox_after(
+        &self,
+        cursor: i64,
+        limit: i64,
+    ) -> Result<Vec<OutboxEntry>, sqlx::Error> {
+        let entries: Vec<OutboxEntry> = sqlx::query_as(
+            "SELECT id, table_name AS \"table\", key, op, value, timestamp FROM xcloud_outbox
+             WHERE id > ?1 ORDER BY id LIMIT ?2",
+        )
+        .bind(cursor)
+        .bind(limit)
+        .fetch_all(&*self.pool)
+        .await?;
+        entries
+            .into_iter()
+            .map(|mut entry| -> Result<OutboxEntry, sqlx::Error> {
+                entry.value = entry.value.map(|v| self.open_value(v)).transpose()?;
+                Ok(entry)
+            })
+            .collect()
+    }
+
+    /// Removes the recorded writes up to and including the given cursor.
+    ///
+    /// # Arguments
+    ///
+    /// * `cursor` - The id of the last write acknowledged by the peer.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the writes cannot be removed.
+    pub async fn prune_outbox(&self, cursor: i64) -> Result<u64, sqlx::Error> {
+        Ok(sqlx::query("DELETE FROM xcloud_outbox WHERE id <= ?1")
+            .bind(cursor)
+            .execute(&*self.pool)
+            .await?
+            .rows_affected())
+    }
+
+    /// Initializes the table holding the leases on keys.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the table cannot be initialized.
+    pub async fn init_locks(&self) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS xcloud_locks (
+                table_name TEXT NOT NULL,
+                key TEXT NOT NULL,
+                owner TEXT NOT NULL,
+                expires_at INTEGER NOT NULL,
+                PRIMARY KEY (table_name, key)
+            )",
+        )
+        .execute(&*self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Acquires or renews the lease on a key, unless another owner holds an unexpired lease.
+    ///
+    /// # Arguments
+    ///
+    /// * `table` - The table holding the key.
+    /// * `key` - The key to lock.
+    /// * `owner` - The identity of the editor acquiring the lease.
+    /// * `lease_secs` - The duration of the lease in seconds.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Lock, Lock>` - The acquired lease, or the lease held by another owner.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the lease cannot be acquired.
+    #[tracing::instrument(level = "debug", skip(self), err)]
+    pub async fn acquire_lock(
+        &self,
+        table: &str,
+        key: &str,
+        owner: &str,
+        lease_secs: i64,
+    ) -> Result<Result<Lock, Lock>, sqlx::Error> {
+        loop {
+            let acquired: Option<Lock> = sqlx::query_as(
+                "INSERT INTO xcloud_locks (table_name, key, owner, expires_at)
+                 VALUES (?1, ?2, ?3, CAST(strftime('%s', 'now') AS INTEGER) + ?4)
+                 ON CONFLICT(table_name, key) DO UPDATE
+                 SET owner = excluded.owner, expires_at = excluded.expires_at
+                 WHERE xcloud_locks.owner = excluded.owner
+                    OR xcloud_locks.expires_at <= CAST(strftime('%s', 'now') AS INTEGER)
+                 RETURNING table_name AS \"table\", key, owner, expires_at",
+            )
+            .bind(Utils::sanitize(table))
+            .bind(key)
+            .bind(owner)
+            .bind(lease_secs)
+            .fetch_optional(&*self.pool)
+            .await?;
+            if let Some(lock) = acquired {
+                return Ok(Ok(lock));
+            }
+            // The lease may have expired in between, in which case acquiring is retried.
+            if let Some(held) = self.get_lock(table, key).await? {
+                return Ok(Err(held));
+            }
+        }
+    }
+
+    /// Releases the lease on a key held by the given owner.
+    ///
+    /// # Arguments
+    ///
+    /// * `table` - The table holding the key.
+    /// * `key` - The key to unlock.
+    /// * `owner` - The identity of the editor holding the lease.
+    ///
+    /// # Returns
+    ///
+    /// * `bool` - Whether a lease of the owner was released.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the lease cannot be released.
+    #[tracing::instrument(level = "debug", skip(self), err)]
+    pub async fn release_lock(
+        &self,
+        table: &str,
+        key: &str,
+        owner: &str,
+    ) -> Result<bool, sqlx::Error> {
+        let released = sqlx::query(
+            "DELETE FROM xcloud_locks WHERE table_name = ?1 AND key = ?2 AND owner = ?3",
+        )
+        .bind(Utils::sanitize(table))
+        .bind(key)
+        .bind(owner)
+        .execute(&*self.pool)
+        .await?
+        .rows_affected();
+        Ok(released > 0)
+    }
+
+    /// Initializes the table holding the advisory locks of schema operations.
+    ///
+    /// The locks are kept in the database so they are seen by every instance sharing it.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the table cannot be initialized.
+    pub async fn init_schema_locks(&self) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS xcloud_schema_locks (
+                table_name TEXT PRIMARY KEY,
+                operation TEXT NOT NULL,
+                expires_at INTEGER NOT NULL
+            )",
+        )
+        .execute(&*self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Acquires the schema lock on a table, unless another schema operation holds an unexpired
+    /// one.
+    ///
+    /// # Arguments
+    ///
+    /// * `table` - The table to lock.
+    /// * `operation` - The schema operation acquiring the lock.
+    /// * `lease_secs` - The duration after which the lock expires if never released.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(), SchemaLock>` - Nothing if acquired, or the lock held by another operation.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the lock cannot be acquired.
+    pub async fn acquire_schema_lock(
+        &self,
+        table: &str,
+        operation: &str,
+        lease_secs: i64,
+    ) -> Result<Result<(), SchemaLock>, sqlx::Error> {
+        loop {
+            let acquired = sqlx::query(
+                "INSERT INTO xcloud_schema_locks (table_name, operation, expires_at)
+                 VALUES (?1, ?2, CAST(strftime('%s', 'now') AS INTEGER) + ?3)
+                 ON CONFLICT(table_name) DO UPDATE
+                    SET operation = excluded.operation, expires_at = excluded.expires_at
+                    WHERE xcloud_schema_locks.expires_at <= CAST(strftime('%s', 'now') AS INTEGER)",
+            )
+            .bind(Utils::sanitize(table))
+            .bind(operation)
+            .bind(lease_secs)
+            .execute(&*self.pool)
+            .await?
+            .rows_affected();
+            if acquired > 0 {
+                return Ok(Ok(()));
+            }
+            // The lock may have been released or have expired in the meantime.
+            if let Some(lock) = self.schema_lock(table).await? {
+                return Ok(Err(lock));
+            }
+        }
+    }
+
+    /// Releases the schema lock on a table.
+    ///
+    /// # Arguments
+    ///
+    /// * `table` - The locked table.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the lock cannot be released.
+    pub async fn release_schema_lock(&self, table: &str) -> Result<(), sqlx::Error> {
+        sqlx::query("DELETE FROM xcloud_schema_locks WHERE table_name = ?1")
+            .bind(Utils::sanitize(table))
+            .execute(&*self.pool)
+            .await?;
+        Ok(())
+    }
+
+    /// Retrieves the unexpired schema lock on a table.
+    ///
+    /// # Arguments
+    ///
+    /// * `table` - The table.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<SchemaLock>` - The lock, or `None` if no schema operation is in progress.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the lock cannot be retrieved.
+    pub async fn schema_lock(&self, table: &str) -> Result<Option<SchemaLock>, sqlx::Error> {
+        sqlx::query_as(
+            "SELECT table_name AS \"table\", operation, expires_at FROM xcloud_schema_locks
+             WHERE table_name = ?1 AND expires_at > CAST(strftime('%s', 'now') AS INTEGER)",
+        )
+        .bind(Utils::sanitize(table))
+        .fetch_optional(&*self.pool)
+        .await
+    }
+
+    /// Retrieves the unexpired lease on a key.
+    ///
+    /// # Arguments
+    ///
+    /// * `table` - The table holding the key.
+    /// * `key` - The key whose lease to retrieve.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<Lock>` - The lease, or `None` if the key is not locked.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the lease cannot be retrieved.
+    pub async fn get_lock(&self, table: &str, key: &str) -> Result<Option<Lock>, sqlx::Error> {
+        sqlx::query_as(
+            "SELECT table_name AS \"table\", key, owner, expires_at FROM xcloud_locks
+             WHERE table_name = ?1 AND key = ?2
+               AND expires_at > CAST(strftime('%s', 'now') AS INTEGER)",
+        )
+        .bind(Utils::sanitize(table))
+        .bind(key)
+        .fetch_optional(&*self.pool)
+        .await
+    }
+
+    /// Retrieves the leases held by any of the given owners, expired or not.
+    ///
+    /// # Arguments
+    ///
+    /// * `owners` - The owners.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the leases cannot be retrieved.
+    pub async fn locks_of(&self, owners: &[String]) -> Result<Vec<Lock>, sqlx::Error> {
+        sqlx::query_as(
+            "SELECT table_name AS \"table\", key, owner, expires_at FROM xcloud_locks
+             WHERE owner IN (SELECT value FROM json_each(?1))
+             ORDER BY table_name, key",
+        )
+        .bind(serde_json::to_string(owners).map_err(|e| sqlx::Error::Encode(Box::new(e)))?)
+        .fetch_all(&*self.pool)
+        .await
+    }
+
+    /// Releases the leases held by any of the given owners.
+    ///
+    /// # Arguments
+    ///
+    /// * `owners` - The owners.
+    ///
+    /// # Returns
+    ///
+    /// * `u64` - The number of released leases.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the leases cannot be released.
+    pub async fn release_locks_of(&self, owners: &[String]) -> Result<u64, sqlx::Error> {
+        sqlx::query("DELETE FROM xcloud_locks WHERE owner IN (SELECT value FROM json_each(?1))")
+            .bind(serde_json::to_string(owners).map_err(|e| sqlx::Error::Encode(Box::new(e)))?)
+            .execute(&*self.pool)
+            .await
+            .map(|result| result.rows_affected())
+    }
+
+    /// Initializes the table holding the previous versions of values on every shard.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the table cannot be initialized.
+    pub async fn init_history(&self) -> Result<(), sqlx::Error> {
+        for shard in &self.shards {
+            sqlx::query(
+                "CREATE TABLE IF NOT EXISTS xcloud_history (
+                    seq INTEGER PRIMARY KEY AUTOINCREMENT,
+                    table_name TEXT NOT NULL,
+                    key TEXT NOT NULL,
+                    value TEXT,
+                    changed_at INTEGER NOT NULL DEFAULT (CAST(strftime('%s', 'now') AS INTEGER)),
+                    delta INTEGER NOT NULL DEFAULT 0
+                )",
+            )
+            .execute(&**shard)
+            .await?;
+            let has_delta: bool = sqlx::query_scalar(
+                "SELECT COUNT(*) > 0 FROM pragma_table_info('xcloud_history') WHERE name = 'delta'",
+            )
+            .fetch_one(&**shard)
+            .await?;
+            if !has_delta {
+                sqlx::query(
+                    "ALTER TABLE xcloud_history ADD COLUMN delta INTEGER NOT NULL DEFAULT 0",
+                )
+                .execute(&**shard)
+                .await?;
+            }
+            sqlx::query(
+                "CREATE INDEX IF NOT EXISTS xcloud_history_key
+                 ON xcloud_history (table_name, key, seq)",
+            )
+            .execute(&**shard)
+            .await?;
+        }
+        Ok(())
+    }
+
+    /// Records a new version of a key, keeping only the configured number of versions.
+    ///
+    /// If deltas are enabled, the previous version is replaced by the delta building it from the
+    /// new version when smaller, so only the newest version is stored in full.
+    ///
+    /// # Arguments
+    ///
+    /// * `table` - The table holding the key.
+    /// * `key` - The key that was written.
+    /// * `value` - The new value, or `None` if the key was deleted.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the version cannot be recorded.
+    async fn record_history(
+        &self,
+        table: &str,
+        key: &str,
+        value: Option<&str>,
+    ) -> Result<(), sqlx::Error> {
+        let depth = self.history.depth(table);
+        if depth == 0 {
+            return Ok(());
+        }
+        if let (true, Some(value)) = (self.history.deltas, value) {
+            let previous: Option<(i64, Option<String>)> = sqlx::query_as(
+                "SELECT seq, value FROM xcloud_history WHERE table_name = ?1 AND key = ?2
+                 ORDER BY seq DESC LIMIT 1",
+            )
+            .bind(Utils::sanitize(table))
+            .bind(key)
+            .fetch_optional(self.shard(table))
+            .await?;
+            if let Some((seq, Some(stored))) = previous {
+                let previous = self.open_value(stored)?;
+                let delta = serde_json::to_string(&ValuePatch::delta(value, &previous))
+                    .map_err(|e| sqlx::Error::Encode(Box::new(e)))?;
+                if delta.len() < previous.len() {
+                    sqlx::query("UPDATE xcloud_history SET value = ?1, delta = 1 WHERE seq = ?2")
+                        .bind(self.seal_value(&delta)?)
+                        .bind(seq)
+                        .execute(self.shard(table))
+                        .await?;
+                }
+            }
+        }
+        sqlx::query("INSERT INTO xcloud_history (table_name, key, value) VALUES (?1, ?2, ?3)")
+            .bind(Utils::sanitize(table))
+            .bind(key)
+            .bind(value.map(|value| self.seal_value(value)).transpose()?)
+            .execute(self.shard(table))
+            .await?;
+        sqlx::query(
+            "DELETE FROM xcloud_history WHERE table_name = ?1 AND key = ?2 AND seq NOT IN (
+                SELECT seq FROM xcloud_history WHERE table_name = ?1 AND key = ?2
+                ORDER BY seq DESC LIMIT ?3
+             )",
+        )
+        .bind(Utils::sanitize(table))
+        .bind(key)
+        .bind(depth as i64)
+        .execute(self.shard(table))
+        .await?;
+        Ok(())
+    }
+
+    /// Retrieves the recorded versions of a key, newest first.
+    ///
+    /// # Arguments
+    ///
+    /// * `table` - The table holding the key.
+    /// * `key` - The key whose versions to retrieve.
+    /// * `limit` - The maximum number of versions to return, or `None` for all.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the versions cannot be retrieved.
+    #[tracing::instrument(level = "debug", skip(self), err)]
+    pub async fn history(
+        &self,
+        table: &str,
+        key: &str,
+        limit: Option<i64>,
+    ) -> Result<Vec<HistoryEntry>, sqlx::Error> {
+        let rows = sqlx::query_as(
+            "SELECT value, changed_at, delta FROM xcloud_history WHERE table_name = ?1 AND key = ?2
+             ORDER BY seq DESC LIMIT ?3",
+        )
+        .bind(Utils::sanitize(table))
+        .bind(key)
+        .bind(limit.unwrap_or(-1))
+        .fetch_all(self.read_pool(table))
+        .await?;
+        self.resolve_history(rows)
+    }
+
+    /// Opens recorded versions, newest first, building the versions stored as deltas from the
+    /// version following them.
+    ///
+    /// # Arguments
+    ///
+    /// * `rows` - The stored value, the write time and whether the value is a delta of every
+    ///   version, starting from the newest.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if a value cannot be opened or built.
+    fn resolve_history(
+        &self,
+        rows: Vec<(Option<String>, i64, bool)>,
+    ) -> Result<Vec<HistoryEntry>, sqlx::Error> {
+        let mut entries: Vec<HistoryEntry> = Vec::with_capacity(rows.len());
+        for (stored, changed_at, delta) in rows {
+            let mut value = stored.map(|v| self.open_value(v)).transpose()?;
+            if let (true, Some(ops)) = (delta, &value) {
+                let next = entries
+                    .last()
+                    .and_then(|entry| entry.value.as_deref())
+                    .ok_or_else(|| {
+                        sqlx::Error::Decode("delta without a following version".into())
+                    })?;
+                value = Some(
+                    serde_json::from_str(ops)
+                        .map_err(|e| e.to_string())
+                        .and_then(|ops| ValuePatch::Delta(ops).apply(next))
+                        .map_err(|e| sqlx::Error::Decode(e.into()))?,
+                );
+            }
+            entries.push(HistoryEntry { value, changed_at });
+        }
+        Ok(entries)
+    }
+
+    /// Retrieves the value a key had at the given point in time.
+    ///
+    /// # Arguments
+    ///
+    /// * `table` - The table holding the key.
+    /// * `key` - The key whose value to retrieve.
+    /// * `timestamp` - The unix timestamp in seconds.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<String>` - The value, or `None` if the key did not exist or is beyond the history.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the value cannot be retrieved.
+    #[tracing::instrument(level = "debug", skip(self), err)]
+    pub async fn get_data_at(
+        &self,
+        table: &str,
+        key: &str,
+        timestamp: i64,
+    ) -> Result<Option<String>, sqlx::Error> {
+        let rows = sqlx::query_as(
+            "SELECT value, changed_at, delta FROM xcloud_history
+             WHERE table_name = ?1 AND key = ?2 AND seq >= (
+                SELECT seq FROM xcloud_history
+                WHERE table_name = ?1 AND key = ?2 AND changed_at <= ?3
+                ORDER BY seq DESC LIMIT 1
+             )
+             ORDER BY seq DESC",
+        )
+        .bind(Utils::sanitize(table))
+        .bind(key)
+        .bind(timestamp)
+        .fetch_all(self.read_pool(table))
+        .await?;
+        Ok(self
+            .resolve_history(rows)?
+            .pop()
+            .and_then(|entry| entry.value))
+    }
+
+    /// Removes the versions older than the given age from every shard.
+    ///
+    /// # Arguments
+    ///
+    /// * `max_age_secs` - The age in seconds after which versions are removed.
+    ///
+    /// # Returns
+    ///
+    /// * `u64` - The number of removed versions.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the versions cannot be removed.
+    pub async fn prune_history(&self, max_age_secs: i64) -> Result<u64, sqlx::Error> {
+        let mut count = 0;
+        for shard in &self.shards {
+            count += sqlx::query(
+                "DELETE FROM xcloud_history
+                 WHERE changed_at < CAST(strftime('%s', 'now') AS INTEGER) - ?1",
+            )
+            .bind(max_age_secs)
+            .execute(&**shard)
+            .await?
+            .rows_affected();
+        }
+        Ok(count)
+    }
+
+    /// Retrieves the tables having recorded versions on any shard.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the tables cannot be retrieved.
+    pub async fn history_tables(&self) -> Result<Vec<String>, sqlx::Error> {
+        let mut tables = Vec::new();
+        for shard in &self.shards {
+            let names: Vec<String> =
+                sqlx::query_scalar("SELECT DISTINCT table_name FROM xcloud_history")
+                    .fetch_all(&**shard)
+                    .await?;
+            tables.extend(names);
+        }
+        tables.sort();
+        tables.dedup();
+        Ok(tables)
+    }
+
+    /// Collapses the versions of the keys of a table older than the given age.
+    ///
+    /// Of the older versions, only those read by `get_data_at` at the start of the window or at
+    /// the creation of a snapshot are kept, so every snapshot can still be reconstructed. The
+    /// newest version, matching the stored value, and the versions within the window are always
+    /// kept. Kept versions stored as deltas against a removed version are rebuilt against the
+    /// next kept one.
+    ///
+    /// # Arguments
+    ///
+    /// * `table` - The table whose history to compact.
+    /// * `max_age_secs` - The age in seconds beyond which versions are collapsed.
+    ///
+    /// # Returns
+    ///
+    /// * `u64` - The number of removed versions.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the versions cannot be read or removed.
+    pub async fn compact_history(
+        &self,
+        table: &str,
+        max_age_secs: i64,
+    ) -> Result<u64, sqlx::Error> {
+        let table = Utils::sanitize(table);
+        let shard = self.shard(&table);
+        let cutoff: i64 = sqlx::query_scalar("SELECT CAST(strftime('%s', 'now') AS INTEGER) - ?1")
+            .bind(max_age_secs)
+            .fetch_one(shard)
+            .await?;
+        let mut boundaries: Vec<i64> =
+            sqlx::query_scalar("SELECT created_at FROM xcloud_snapshots WHERE created_at < ?1")
+                .bind(cutoff)
+                .fetch_all(&*self.pool)
+                .await?;
+        boundaries.push(cutoff);
+        let keys: Vec<String> = sqlx::query_scalar(
+            "SELECT key FROM xcloud_history WHERE table_name = ?1 AND changed_at < ?2
+             GROUP BY key HAVING COUNT(*) > 1",
+        )
+        .bind(&table)
+        .bind(cutoff)
+        .fetch_all(shard)
+        .await?;
+        let mut removed = 0;
+        for key in keys {
+            let rows: Vec<(i64, Option<String>, i64, bool)> = sqlx::query_as(
+                "SELECT seq, value, changed_at, delta FROM xcloud_history
+                 WHERE table_name = ?1 AND key = ?2 ORDER BY seq DESC",
+            )
+            .bind(&table)
+            .bind(&key)
+            .fetch_all(shard)
+            .await?;
+            let entries = self.resolve_history(
+                rows.iter()
+                    .map(|(_, value, changed_at, delta)| (value.clone(), *changed_at, *delta))
+                    .collect(),
+            )?;
+            let mut keep: Vec<bool> = entries
+                .iter()
+                .enumerate()
+                .map(|(i, entry)| i == 0 || entry.changed_at >= cutoff)
+                .collect();
+            for boundary in &boundaries {
+                if let Some(i) = entries
+                    .iter()
+                    .position(|entry| entry.changed_at <= *boundary)
+                {
+                    keep[i] = true;
+                }
+            }
+            if keep.iter().all(|keep| *keep) {
+                continue;
+            }
+            let mut tx = shard.begin().await?;
+            let mut next: Option<&str> = None;
+            let mut next_removed = false;
+            for (i, (seq, _, _, delta)) in rows.iter().enumerate() {
+                if !keep[i] {
+                    sqlx::query("DELETE FROM xcloud_history WHERE seq = ?1")
+                        .bind(seq)
+                        .execute(&mut *tx)
+                        .await?;
+                    removed += 1;
+                    next_removed = true;
+                    continue;
+                }
+                if let (true, true, Some(value)) = (*delta, next_removed, &entries[i].value) {
+                    let ops = next
+                        .map(|next| serde_json::to_string(&ValuePatch::delta(next, value)))
+                        .transpose()
+                        .map_err(|e| sqlx::Error::Encode(Box::new(e)))?;
+                    let (stored, delta) = match ops {
+                        Some(ops) if ops.len() < value.len() => (ops, true),
+                        _ => (value.clone(), false),
+                    };
+                    sqlx::query("UPDATE xcloud_history SET value = ?1, delta = ?2 WHERE seq = ?3")
+                        .bind(self.seal_value(&stored)?)
+                        .bind(delta)
+                        .bind(seq)
+                        .execute(&mut *tx)
+                        .await?;
+                }
+                next = entries[i].value.as_deref();
+                next_removed = false;
+            }
+            tx.commit().await?;
+        }
+        Ok(removed)
+    }
+
+    /// Deletes all recorded versions of a key.
+    ///
+    /// # Arguments
+    ///
+    /// * `table` - The table holding the key.
+    /// * `key` - The key whose history to delete.
+    ///
+    /// # Returns
+    ///
+    /// * `u64` - The number of deleted versions.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the history cannot be deleted.
+    pub async fn purge_history(&self, table: &str, key: &str) -> Result<u64, sqlx::Error> {
+        sqlx::query("DELETE FROM xcloud_history WHERE table_name = ?1 AND key = ?2")
+            .bind(Utils::sanitize(table))
+            .bind(key)
+            .execute(self.shard(table))
+            .await
+            .map(|result| result.rows_affected())
+    }
+
+    /// Initializes the table holding the legal holds.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the table cannot be created.
+    pub async fn init_legal_holds(&self) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS xcloud_legal_holds (
+                table_name TEXT PRIMARY KEY,
+                reason TEXT NOT NULL,
+                placed_at INTEGER NOT NULL DEFAULT (CAST(strftime('%s', 'now') AS INTEGER))
+            )",
+        )
+        .execute(&*self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Places a legal hold on a table, which the server then refuses to write to or delete,
+    /// and the retention policies skip.
+    ///
+    /// # Arguments
+    ///
+    /// * `table` - The table.
+    /// * `reason` - The reason of the hold, e.g. a case reference.
+    ///
+    /// # Returns
+    ///
+    /// * `bool` - Whether the hold was placed, `false` if the table is already held.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the hold cannot be placed.
+    pub async fn place_legal_hold(&self, table: &str, reason: &str) -> Result<bool, sqlx::Error> {
+        let placed = sqlx::query(
+            "INSERT OR IGNORE INTO xcloud_legal_holds (table_name, reason) VALUES (?1, ?2)",
+        )
+        .bind(Utils::sanitize(table))
+        .bind(reason)
+        .execute(&*self.pool)
+        .await?;
+        Ok(placed.rows_affected() > 0)
+    }
+
+    /// Retrieves all legal holds.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the holds cannot be retrieved.
+    pub async fn legal_holds(&self) -> Result<Vec<LegalHold>, sqlx::Error> {
+        sqlx::query_as(
+            "SELECT table_name AS \"table\", reason, placed_at FROM xcloud_legal_holds
+             ORDER BY table_name",
+        )
+        .fetch_all(&*self.pool)
+        .await
+    }
+
+    /// Retrieves the legal hold of a table.
+    ///
+    /// # Arguments
+    ///
+    /// * `table` - The table.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<LegalHold>` - The hold, or `None` if the table is not held.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the hold cannot be retrieved.
+    pub async fn legal_hold(&self, table: &str) -> Result<Option<LegalHold>, sqlx::Error> {
+        sqlx::query_as(
+            "SELECT table_name AS \"table\", reason, placed_at FROM xcloud_legal_holds
+             WHERE table_name = ?1",
+        )
+        .bind(Utils::sanitize(table))
+        .fetch_optional(&*self.pool)
+        .await
+    }
+
+    /// Lifts the legal hold of a table.
+    ///
+    /// # Arguments
+    ///
+    /// * `table` - The table.
+    ///
+    /// # Returns
+    ///
+    /// * `bool` - Whether the table was held.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the hold cannot be lifted.
+    pub async fn lift_legal_hold(&self, table: &str) -> Result<bool, sqlx::Error> {
+        let lifted = sqlx::query("DELETE FROM xcloud_legal_holds WHERE table_name = ?1")
+            .bind(Utils::sanitize(table))
+            .execute(&*self.pool)
+            .await?;
+        Ok(lifted.rows_affected() > 0)
+    }
+
+    /// Initializes the table holding the integrations.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the table cannot be created.
+    pub async fn init_integrations(&self) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS xcloud_integrations (
+                name TEXT PRIMARY KEY,
+                url TEXT NOT NULL,
+                interval_secs INTEGER NOT NULL,
+                authorization TEXT,
+                table_name TEXT NOT NULL,
+                items_pointer TEXT NOT NULL,
+                key_pointer TEXT NOT NULL,
+                value_pointer TEXT NOT NULL,
+                last_run_at INTEGER,
+                next_run_at INTEGER NOT NULL,
+                last_rows INTEGER NOT NULL DEFAULT 0,
+                last_error TEXT
+            )",
+        )
+        .execute(&*self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Registers an integration, or replaces the source and mapping of an existing one while
+    /// keeping the outcome of its last poll. Either way it is due immediately.
+    ///
+    /// # Arguments
+    ///
+    /// * `integration` - The integration, whose status fields are ignored.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the integration cannot be stored.
+    pub async fn put_integration(&self, integration: &Integration) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            "INSERT INTO xcloud_integrations (name, url, interval_secs, authorization, table_name,
+                items_pointer, key_pointer, value_pointer, next_run_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, CAST(strftime('%s', 'now') AS INTEGER))
+             ON CONFLICT(name) DO UPDATE SET
+                url = excluded.url,
+                interval_secs = excluded.interval_secs,
+                authorization = excluded.authorization,
+                table_name = excluded.table_name,
+                items_pointer = excluded.items_pointer,
+                key_pointer = excluded.key_pointer,
+                value_pointer = excluded.value_pointer,
+                next_run_at = excluded.next_run_at",
+        )
+        .bind(&integration.name)
+        .bind(&integration.url)
+        .bind(integration.interval_secs)
+        .bind(&integration.authorization)
+        .bind(Utils::sanitize(&integration.table))
+        .bind(&integration.items_pointer)
+        .bind(&integration.key_pointer)
+        .bind(&integration.value_pointer)
+        .execute(&*self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Retrieves the integrations, or only those due for a poll.
+    ///
+    /// # Arguments
+    ///
+    /// * `due` - Whether to only retrieve the integrations due for a poll.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the integrations cannot be retrieved.
+    pub async fn integrations(&self, due: bool) -> Result<Vec<Integration>, sqlx::Error> {
+        sqlx::query_as(
+            "SELECT name, url, interval_secs, authorization, table_name AS \"table\",
+                    items_pointer, key_pointer, value_pointer, last_run_at, next_run_at,
+                    last_rows, last_error
+             FROM xcloud_integrations
+             WHERE NOT ?1 OR next_run_at <= CAST(strftime('%s', 'now') AS INTEGER)
+             ORDER BY name",
+        )
+        .bind(due)
+        .fetch_all(&*self.pool)
+        .await
+    }
+
+    /// Records the outcome of a poll of an integration and schedules the next one.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the integration.
+    /// * `rows` - The number of rows written.
+    /// * `error` - The error of the poll, or the items it skipped.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the outcome cannot be recorded.
+    pub async fn record_integration_run(
+        &self,
+        name: &str,
+        rows: usize,
+        error: Option<&str>,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            "UPDATE xcloud_integrations SET
+                last_run_at = CAST(strftime('%s', 'now') AS INTEGER),
+                next_run_at = CAST(strftime('%s', 'now') AS INTEGER) + MAX(interval_secs, 1),
+                last_rows = ?2,
+                last_error = ?3
+             WHERE name = ?1",
+        )
+        .bind(name)
+        .bind(rows as i64)
+        .bind(error)
+        .execute(&*self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Makes an integration due for a poll immediately.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the integration.
+    ///
+    /// # Returns
+    ///
+    /// * `bool` - Whether the integration exists.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the integration cannot be scheduled.
+    pub async fn schedule_integration(&self, name: &str) -> Result<bool, sqlx::Error> {
+        let scheduled = sqlx::query(
+            "UPDATE xcloud_integrations SET next_run_at = CAST(strftime('%s', 'now') AS INTEGER)
+             WHERE name = ?1",
+        )
+        .bind(name)
+        .execute(&*self.pool)
+        .await?;
+        Ok(scheduled.rows_affected() > 0)
+    }
+
+    /// Deletes an integration.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the integration.
+    ///
+    /// # Returns
+    ///
+    /// * `bool` - Whether the integration existed.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the integration cannot be deleted.
+    pub async fn delete_integration(&self, name: &str) -> Result<bool, sqlx::Error> {
+        let deleted = sqlx::query("DELETE FROM xcloud_integrations WHERE name = ?1")
+            .bind(name)
+            .execute(&*self.pool)
+            .await?;
+        Ok(deleted.rows_affected() > 0)
+    }
+
+    /// Initializes the tables holding the retention policies and the write times they act on.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the tables cannot be initialized.
+    pub async fn init_table_policies(&self) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS xcloud_table_policies (
+                table_name TEXT PRIMARY KEY,
+                max_age_secs INTEGER,
+                max_rows INTEGER
+            )",
+        )
+        .execute(&*self.pool)
+        .await?;
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS xcloud_write_times (
+                table_name TEXT NOT NULL,
+                key TEXT NOT NULL,
+                written_at INTEGER NOT NULL,
+                PRIMARY KEY (table_name, key)
+            )",
+        )
+        .execute(&*self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Records the time of a write to a table with a retention policy.
+    ///
+    /// # Arguments
+    ///
+    /// * `table` - The table that was written to.
+    /// * `key` - The key that was written, if any.
+    /// * `op` - The kind of write.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the time cannot be recorded.
+    async fn record_write_time(
+        &self,
+        table: &str,
+        key: Option<&str>,
+        op: &str,
+    ) -> Result<(), sqlx::Error> {
+        let query = match (key, op) {
+            (Some(_), "set" | "update") => sqlx::query(
+                "INSERT INTO xcloud_write_times (table_name, key, written_at)
+                 SELECT ?1, ?2, CAST(strftime('%s', 'now') AS INTEGER)
+                 WHERE EXISTS (SELECT 1 FROM xcloud_table_policies WHERE table_name = ?1)
+                 ON CONFLICT(table_name, key) DO UPDATE SET written_at = excluded.written_at",
+            ),
+            (Some(_), "delete") => {
+                sqlx::query("DELETE FROM xcloud_write_times WHERE table_name = ?1 AND key = ?2")
+            }
+            (None, "delete_table") => {
+                sqlx::query("DELETE FROM xcloud_write_times WHERE table_name = ?1")
+            }
+            _ => return Ok(()),
+        };
+        query
+            .bind(Utils::sanitize(table))
+            .bind(key)
+            .execute(&*self.pool)
+            .await?;
+        Ok(())
+    }
+
+    /// Sets the retention policy of a table, replacing any previous one.
+    ///
+    /// Rows written before the policy existed are treated as written now.
+    ///
+    /// # Arguments
+    ///
+    /// * `policy` - The retention policy.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the policy cannot be set.
+    pub async fn set_table_policy(&self, policy: &TablePolicy) -> Result<(), sqlx::Error> {
+        let table = Utils::sanitize(&policy.table);
+        let keys = self.list_keys(&table, 0, None).await?;
+        let mut tx = self.pool.begin().await?;
+        sqlx::query(
+            "INSERT OR REPLACE INTO xcloud_table_policies (table_name, max_age_secs, max_rows)
+             VALUES (?1, ?2, ?3)",
+        )
+        .bind(&table)
+        .bind(policy.max_age_secs)
+        .bind(policy.max_rows)
+        .execute(&mut *tx)
+        .await?;
+        for key in &keys {
+            sqlx::query(
+                "INSERT OR IGNORE INTO xcloud_write_times (table_name, key, written_at)
+                 VALUES (?1, ?2, CAST(strftime('%s', 'now') AS INTEGER))",
+            )
+            .bind(&table)
+            .bind(key)
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await
+    }
+
+    /// Retrieves the retention policies of all tables.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the policies cannot be retrieved.
+    pub async fn table_policies(&self) -> Result<Vec<TablePolicy>, sqlx::Error> {
+        sqlx::query_as(
+            "SELECT table_name AS \"table\", max_age_secs, max_rows FROM xcloud_table_policies
+             ORDER BY table_name",
+        )
+        .fetch_all(&*self.pool)
+        .await
+    }
+
+    /// Removes the retention policy of a table.
+    ///
+    /// # Arguments
+    ///
+    /// * `table` - The table whose policy to remove.
+    ///
+    /// # Returns
+    ///
+    /// * `bool` - Whether the table had a policy.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the policy cannot be removed.
+    pub async fn delete_table_policy(&self, table: &str) -> Result<bool, sqlx::Error> {
+        let removed = sqlx::query("DELETE FROM xcloud_table_policies WHERE table_name = ?1")
+            .bind(Utils::sanitize(table))
+            .execute(&*self.pool)
+            .await?
+            .rows_affected();
+        sqlx::query("DELETE FROM xcloud_write_times WHERE table_name = ?1")
+            .bind(Utils::sanitize(table))
+            .execute(&*self.pool)
+            .await?;
+        Ok(removed > 0)
+    }
+
+    /// Prunes the rows of a table exceeding its retention policy.
+    ///
+    /// Pruned rows are not recorded in the history or the outbox. Tables under a legal hold are
+    /// not pruned.
+    ///
+    /// # Arguments
+    ///
+    /// * `policy` - The retention policy of the table.
+    ///
+    /// # Returns
+    ///
+    /// * `Vec<String>` - The keys of the pruned rows.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the rows cannot be pruned.
+    pub async fn apply_table_policy(
+        &self,
+        policy: &TablePolicy,
+    ) -> Result<Vec<String>, sqlx::Error> {
+        let table = Utils::sanitize(&policy.table);
+        let mut keys: Vec<String> = Vec::new();
+        if self.legal_hold(&table).await?.is_some() {
+            return Ok(keys);
+        }
+        if let Some(max_age_secs) = policy.max_age_secs {
+            keys.extend(
+                sqlx::query_scalar::<_, String>(
+                    "SELECT key FROM xcloud_write_times
+                     WHERE table_name = ?1
+                       AND written_at < CAST(strftime('%s', 'now') AS INTEGER) - ?2",
+                )
+                .bind(&table)
+                .bind(max_age_secs)
+                .fetch_all(&*self.pool)
+                .await?,
+            );
+        }
+        if let Some(max_rows) = policy.max_rows {
+            keys.extend(
+                sqlx::query_scalar::<_, String>(
+                    "SELECT key FROM xcloud_write_times
+                     WHERE table_name = ?1
+                     ORDER BY written_at DESC, key DESC
+                     LIMIT -1 OFFSET ?2",
+                )
+                .bind(&table)
+                .bind(max_rows.max(0))
+                .fetch_all(&*self.pool)
+                .await?,
+            );
+        }
+        keys.sort();
+        keys.dedup();
+        self.delete_keys(&table, &keys).await?;
+        for key in &keys {
+            self.apply_views(&table, key, None).await?;
+        }
+        for chunk in keys.chunks(500) {
+            let placeholders = (2..=chunk.len() + 1)
+                .map(|i| format!("?{}", i))
+                .collect::<Vec<_>>()
+                .join(", ");
+            let sql = format!(
+                "DELETE FROM xcloud_write_times WHERE table_name = ?1 AND key IN ({})",
+                placeholders
+            );
+            let mut query = sqlx::query(&sql).bind(&table);
+            for key in chunk {
+                query = query.bind(key);
+            }
+            query.execute(&*self.pool).await?;
+        }
+        Ok(keys)
+    }
+
+    /// Deletes the given keys of a table and their lists in one transaction.
+    ///
+    /// # Arguments
+    ///
+    /// * `table` - The table holding the keys.
+    /// * `keys` - The keys to delete.
+    ///
+    /// # Returns
+    ///
+    /// * `Vec<String>` - The keys that existed and were deleted.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the keys cannot be deleted.
+    async fn delete_keys(&self, table: &str, keys: &[String]) -> Result<Vec<String>, sqlx::Error> {
+        let mut deleted = Vec::new();
+        if let Some(storage) = self.backend(table)? {
+            for key in keys {
+                if storage.get_data(table, key).await?.is_some() {
+                    storage.delete_data(table, key).await?;
+                    deleted.push(key.clone());
+                }
+            }
+            return Ok(deleted);
+        }
+        self.init_table(table).await?;
+        let mut tx = self.shard(table).begin().await?;
+        for chunk in keys.chunks(500) {
+            let placeholders = (2..=chunk.len() + 1)
+                .map(|i| format!("?{}", i))
+                .collect::<Vec<_>>()
+                .join(", ");
+            let sql = format!(
+                "DELETE FROM {} WHERE key IN ({}) RETURNING key",
+                self.table(table).await?,
+                placeholders
+            );
+            let lists_sql = format!(
+                "DELETE FROM xcloud_lists WHERE table_name = ?1 AND key IN ({})",
+                placeholders
+            );
+            let mut query = sqlx::query_scalar::<_, String>(&sql).bind(Utils::sanitize(table));
+            let mut lists_query = sqlx::query(&lists_sql).bind(Utils::sanitize(table));
+            for key in chunk {
+                query = query.bind(key);
+                lists_query = lists_query.bind(key);
+            }
+            deleted.extend(query.fetch_all(&mut *tx).await?);
+            lists_query.execute(&mut *tx).await?;
+        }
+        tx.commit().await?;
+        Ok(deleted)
+    }
+
+    /// Initializes the table holding the persisted slow queries.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the table cannot be initialized.
+    pub async fn init_slow_queries(&self) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS xcloud_slow_queries (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                table_name TEXT NOT NULL,
+                operation TEXT NOT NULL,
+                duration_ms INTEGER NOT NULL,
+                sql TEXT NOT NULL,
+                timestamp INTEGER NOT NULL
+            )",
+        )
+        .execute(&*self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Writes a slow query to the `xcloud_slow_queries` table.
+    ///
+    /// # Arguments
+    ///
+    /// * `query` - The slow query to persist.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the query cannot be persisted.
+    async fn persist_slow_query(&self, query: &SlowQuery) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            "INSERT INTO xcloud_slow_queries (table_name, operation, duration_ms, sql, timestamp)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+        )
+        .bind(&query.table)
+        .bind(&query.operation)
+        .bind(query.duration_ms)
+        .bind(&query.sql)
+        .bind(query.timestamp)
+        .execute(&*self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Returns the recorded slow queries, newest first.
+    ///
+    /// # Arguments
+    ///
+    /// * `table` - The table to filter by, or `None` for all tables.
+    ///
+    /// # Returns
+    ///
+    /// * `Vec<SlowQuery>` - The recorded slow queries, empty if recording is disabled.
+    pub fn slow_queries(&self, table: Option<&str>) -> Vec<SlowQuery> {
+        self.slow_queries
+            .as_ref()
+            .map(|slow_queries| slow_queries.entries(table))
+            .unwrap_or_default()
+    }
+
+    /// Recommends indexes for the slow queries recorded in the slow query log.
+    ///
+    /// # Arguments
+    ///
+    /// * `min_occurrences` - The number of times a query must have been slow to be considered.
+    ///
+    /// # Returns
+    ///
+    /// * `Vec<IndexRecommendation>` - The recommendations, most time consuming first.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if a query plan cannot be retrieved.
+    pub async fn index_recommendations(
+        &self,
+        min_occurrences: usize,
+    ) -> Result<Vec<IndexRecommendation>, sqlx::Error> {
+        self.advise_indexes(&self.slow_queries(None), min_occurrences)
+            .await
+    }
+
+    /// Recommends indexes for the given slow queries.
+    ///
+    /// The queries are grouped by statement, and a statement is reported if its query plan
+    /// scans the whole table to evaluate its WHERE clause. An index is proposed if the clause
+    /// filters on the values, which the primary key on the keys cannot serve.
+    ///
+    /// # Arguments
+    ///
+    /// * `queries` - The slow queries.
+    /// * `min_occurrences` - The number of times a statement must have been slow to be
+    ///   considered.
+    ///
+    /// # Returns
+    ///
+    /// * `Vec<IndexRecommendation>` - The recommendations, most time consuming first, empty for
+    ///   in-memory storage.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if a query plan cannot be retrieved.
+    pub async fn advise_indexes(
+        &self,
+        queries: &[SlowQuery],
+        min_occurrences: usize,
+    ) -> Result<Vec<IndexRecommendation>, sqlx::Error> {
+        if self.storage.is_some() {
+            return Ok(Vec::new());
+        }
+        let mut groups: BTreeMap<(&str, &str), (&str, usize, i64)> = BTreeMap::new();
+        for query in queries {
+            let group = groups
+                .entry((query.table.as_str(), query.sql.as_str()))
+                .or_insert((query.operation.as_str(), 0, 0));
+            group.1 += 1;
+            group.2 += query.duration_ms;
+        }
+        let mut recommendations = Vec::new();
+        for ((table, sql), (operation, occurrences, total_ms)) in groups {
+            let columns = IndexRecommendation::filtered_columns(sql);
+            if occurrences < min_occurrences.max(1) || columns.is_empty() {
+                continue;
+            }
+            let plan: Vec<String> = sqlx::query_as::<_, (i64, i64, i64, String)>(&format!(
+                "EXPLAIN QUERY PLAN {}",
+                sql
+            ))
+            .fetch_all(self.shard(table))
+            .await?
+            .into_iter()
+            .map(|(_, _, _, detail)| detail)
+            .collect();
+            if !IndexRecommendation::scans(&Utils::sanitize(table), &plan) {
+                continue;
+            }
+            recommendations.push(IndexRecommendation {
+                table: table.to_string(),
+                operation: operation.to_string(),
+                sql: sql.to_string(),
+                occurrences,
+                total_ms,
+                plan,
+                index: columns
+                    .contains(&"value")
+                    .then(|| IndexRecommendation::value_index(table)),
+            });
+        }
+        recommendations.sort_by_key(|recommendation| std::cmp::Reverse(recommendation.total_ms));
+        Ok(recommendations)
+    }
+
+    /// Opens a connection to the primary that cannot write, for the admin SQL console.
+    ///
+    /// # Returns
+    ///
+    /// * `SqliteConnection` - A new connection opened read-only, outside the pools.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the data is kept in memory or the connection
+    /// cannot be opened.
+    pub async fn open_read_only(&self) -> Result<sqlx::SqliteConnection, sqlx::Error> {
+        if self.storage.is_some() {
+            return Err(sqlx::Error::InvalidArgument(
+                "the SQL console requires the SQLite backend".to_string(),
+            ));
+        }
+        let options = (*self.pool.connect_options()).clone().read_only(true);
+        sqlx::ConnectOptions::connect(&options).await
+    }
+
+    /// Creates the indexes recommended for the slow queries recorded in the slow query log.
+    ///
+    /// # Arguments
+    ///
+    /// * `min_occurrences` - The number of times a query must have been slow to be considered.
+    ///
+    /// # Returns
+    ///
+    /// * `Vec<String>` - The tables an index was created on.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if an index cannot be created.
+    pub async fn create_recommended_indexes(
+        &self,
+        min_occurrences: usize,
+    ) -> Result<Vec<String>, sqlx::Error> {
+        let mut created = Vec::new();
+        for recommendation in self.index_recommendations(min_occurrences).await? {
+            if let Some(index) = &recommendation.index {
+                if created.contains(&recommendation.table) {
+                    continue;
+                }
+                sqlx::query(index)
+                    .execute(self.shard(&recommendation.table))
+                    .await?;
+                created.push(recommendation.table);
+            }
+        }
+        Ok(created)
+    }
+
+    /// Initializes the tables holding the projects, the tables they group and the templates
+    /// seeding new projects.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the tables cannot be initialized.
+    pub async fn init_projects(&self) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS xcloud_projects (
+                name TEXT PRIMARY KEY,
+                created_at INTEGER NOT NULL
+            )",
+        )
+        .execute(&*self.pool)
+        .await?;
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS xcloud_project_tables (
+                table_name TEXT PRIMARY KEY,
+                project TEXT NOT NULL
+            )",
+        )
+        .execute(&*self.pool)
+        .await?;
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS xcloud_templates (
+                table_name TEXT PRIMARY KEY
+            )",
+        )
+        .execute(&*self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Creates a project.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the project.
+    ///
+    /// # Returns
+    ///
+    /// * `bool` - Whether the project was created, `false` if it already exists.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the project cannot be created.
+    pub async fn create_project(&self, name: &str) -> Result<bool, sqlx::Error> {
+        let created = sqlx::query(
+            "INSERT OR IGNORE INTO xcloud_projects (name, created_at)
+             VALUES (?1, CAST(strftime('%s', 'now') AS INTEGER))",
+        )
+        .bind(name)
+        .execute(&*self.pool)
+        .await?
+        .rows_affected();
+        Ok(created > 0)
+    }
+
+    /// Retrieves all projects.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the projects cannot be retrieved.
+    pub async fn projects(&self) -> Result<Vec<Project>, sqlx::Error> {
+        sqlx::query_as("SELECT name, created_at FROM xcloud_projects ORDER BY name")
+            .fetch_all(&*self.pool)
+            .await
+    }
+
+    /// Retrieves the tables of a project.
+    ///
+    /// # Arguments
+    ///
+    /// * `project` - The name of the project.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<Vec<String>>` - The names of the tables, or `None` if the project does not exist.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the tables cannot be retrieved.
+    pub async fn project_tables(&self, project: &str) -> Result<Option<Vec<String>>, sqlx::Error> {
+        let exists: Option<i64> =
+            sqlx::query_scalar("SELECT 1 FROM xcloud_projects WHERE name = ?1")
+                .bind(project)
+                .fetch_optional(&*self.pool)
+                .await?;
+        if exists.is_none() {
+            return Ok(None);
+        }
+        let tables = sqlx::query_scalar(
+            "SELECT table_name FROM xcloud_project_tables WHERE project = ?1 ORDER BY table_name",
+        )
+        .bind(project)
+        .fetch_all(&*self.pool)
+        .await?;
+        Ok(Some(tables))
+    }
+
+    /// Associates a table with a project, moving it out of any other project.
+    ///
+    /// # Arguments
+    ///
+    /// * `project` - The name of the project.
+    /// * `table` - The table to associate.
+    ///
+    /// # Returns
+    ///
+    /// * `bool` - Whether the table was associated, `false` if the project does not exist.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the table cannot be associated.
+    pub async fn add_project_table(&self, project: &str, table: &str) -> Result<bool, sqlx::Error> {
+        let added = sqlx::query(
+            "INSERT OR REPLACE INTO xcloud_project_tables (table_name, project)
+             SELECT ?1, name FROM xcloud_projects WHERE name = ?2",
+        )
+        .bind(Utils::sanitize(table))
+        .bind(project)
+        .execute(&*self.pool)
+        .await?
+        .rows_affected();
+        Ok(added > 0)
+    }
+
+    /// Removes a table from a project without deleting it.
+    ///
+    /// # Arguments
+    ///
+    /// * `project` - The name of the project.
+    /// * `table` - The table to remove.
+    ///
+    /// # Returns
+    ///
+    /// * `bool` - Whether the table belonged to the project.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the table cannot be removed.
+    pub async fn remove_project_table(
+        &self,
+        project: &str,
+        table: &str,
+    ) -> Result<bool, sqlx::Error> {
+        let removed =
+            sqlx::query("DELETE FROM xcloud_project_tables WHERE table_name = ?1 AND project = ?2")
+                .bind(Utils::sanitize(table))
+                .bind(project)
+                .execute(&*self.pool)
+                .await?
+                .rows_affected();
+        Ok(removed > 0)
+    }
+
+    /// Deletes a project together with all of its tables.
+    ///
+    /// # Arguments
+    ///
+    /// * `project` - The name of the project.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<Vec<String>>` - The names of the deleted tables, or `None` if the project does not exist.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the project or one of its tables cannot be deleted.
+    pub async fn delete_project(&self, project: &str) -> Result<Option<Vec<String>>, sqlx::Error> {
+        let Some(tables) = self.project_tables(project).await? else {
+            return Ok(None);
+        };
+        for table in &tables {
+            self.delete_table(table).await?;
+        }
+        sqlx::query("DELETE FROM xcloud_projects WHERE name = ?1")
+            .bind(project)
+            .execute(&*self.pool)
+            .await?;
+        Ok(Some(tables))
+    }
+
+    /// Copies a table with its declared schema and, optionally, its contents into a new table.
+    /// An untagged target is stored in the region of the source.
+    ///
+    /// Tables on the same shard are copied in a single statement, unless the copied writes have
+    /// to be recorded in the outbox or the history. Otherwise the key-value pairs are copied one
+    /// by one.
+    ///
+    /// # Arguments
+    ///
+    /// * `source` - The table to copy.
+    /// * `target` - The table to create.
+    /// * `with_data` - Whether to copy the key-value pairs.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<u64>` - The number of copied key-value pairs, or `None` if the source does not
+    ///   exist or the target already exists.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the table cannot be copied.
+    pub async fn clone_table(
+        &self,
+        source: &str,
+        target: &str,
+        with_data: bool,
+    ) -> Result<Option<u64>, sqlx::Error> {
+        let tables = self.list_tables().await?;
+        if !tables.contains(&Utils::sanitize(source)) || tables.contains(&Utils::sanitize(target)) {
+            return Ok(None);
+        }
+        if self.table_region(target).is_none() {
+            if let Some(region) = self.table_region(source) {
+                self.set_table_region(target, Some(&region)).await?;
+            }
+        }
+        if self.storage.is_none() {
+            self.init_table_with(target, self.get_key_collation(source).await?)
+                .await?;
+        }
+        sqlx::query(
+            "INSERT OR REPLACE INTO xcloud_tables (table_name, value_type)
+             SELECT ?2, value_type FROM xcloud_tables WHERE table_name = ?1",
+        )
+        .bind(Utils::sanitize(source))
+        .bind(Utils::sanitize(target))
+        .execute(&*self.pool)
+        .await?;
+        sqlx::query(
+            "INSERT OR REPLACE INTO xcloud_json_schemas (table_name, schema)
+             SELECT ?2, schema FROM xcloud_json_schemas WHERE table_name = ?1",
+        )
+        .bind(Utils::sanitize(source))
+        .bind(Utils::sanitize(target))
+        .execute(&*self.pool)
+        .await?;
+        if !with_data {
+            return Ok(Some(0));
+        }
+        if self.storage.is_none()
+            && !self.outbox
+            && self.history.depth(target) == 0
+            && std::ptr::eq(self.shard(source), self.shard(target))
+        {
+            // References are only counted by the triggers created while deduplication is enabled.
+            let stored = match self.dedup {
+                Some(_) => "value, codec, tier, deduplicated".to_string(),
+                None => format!("{}, 0", Self::stored_columns("")),
+            };
+            let copied = sqlx::query(&format!(
+                "INSERT INTO {} (key, value, codec, tier, deduplicated) SELECT key, {} FROM {}",
+                self.table(target).await?,
+                stored,
+                self.table(source).await?
+            ))
+            .execute(self.shard(target))
+            .await?
+            .rows_affected();
+            return Ok(Some(copied));
+        }
+        let mut copied = 0;
+        for key in self.list_keys(source, 0, None).await? {
+            if let Some(value) = self.get_data(source, &key).await? {
+                self.set_data(target, &key, &value).await?;
+                copied += 1;
+            }
+        }
+        Ok(Some(copied))
+    }
+
+    /// Marks a table as a template seeding new projects, or removes the mark.
+    ///
+    /// # Arguments
+    ///
+    /// * `table` - The table to mark.
+    /// * `template` - Whether the table is a template.
+    ///
+    /// # Returns
+    ///
+    /// * `bool` - Whether the table exists when marking it, or whether it was marked when
+    ///   removing the mark.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the mark cannot be changed.
+    pub async fn set_template(&self, table: &str, template: bool) -> Result<bool, sqlx::Error> {
+        let table = Utils::sanitize(table);
+        if !template {
+            let removed = sqlx::query("DELETE FROM xcloud_templates WHERE table_name = ?1")
+                .bind(table)
+                .execute(&*self.pool)
+                .await?
+                .rows_affected();
+            return Ok(removed > 0);
+        }
+        if !self.list_tables().await?.contains(&table) {
+            return Ok(false);
+        }
+        sqlx::query("INSERT OR IGNORE INTO xcloud_templates (table_name) VALUES (?1)")
+            .bind(table)
+            .execute(&*self.pool)
+            .await?;
+        Ok(true)
+    }
+
+    /// Retrieves the tables marked as templates.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the templates cannot be retrieved.
+    pub async fn templates(&self) -> Result<Vec<String>, sqlx::Error> {
+        sqlx::query_scalar("SELECT table_name FROM xcloud_templates ORDER BY table_name")
+            .fetch_all(&*self.pool)
+            .await
+    }
+
+    /// Seeds a project with copies of all template tables, named `<project>_<template>`.
+    ///
+    /// Copies that already exist are left untouched.
+    ///
+    /// # Arguments
+    ///
+    /// * `project` - The name of the project.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<Vec<String>>` - The names of the created tables, or `None` if the project does
+    ///   not exist.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if a template cannot be copied.
+    pub async fn seed_project(&self, project: &str) -> Result<Option<Vec<String>>, sqlx::Error> {
+        if self.project_tables(project).await?.is_none() {
+            return Ok(None);
+        }
+        let mut seeded = Vec::new();
+        for template in self.templates().await? {
+            let table = Utils::sanitize(&format!("{}_{}", project, template));
+            if self.clone_table(&template, &table, true).await?.is_some() {
+                self.add_project_table(project, &table).await?;
+                seeded.push(table);
+            }
+        }
+        Ok(Some(seeded))
+    }
+
+    /// Initializes the table holding the rules deriving keys from other keys.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the table cannot be initialized.
+    pub async fn init_transforms(&self) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS xcloud_transforms (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                source_table TEXT NOT NULL,
+                source_key TEXT NOT NULL,
+                pointer TEXT NOT NULL,
+                target_table TEXT NOT NULL,
+                target_key TEXT NOT NULL,
+                UNIQUE (target_table, target_key)
+            )",
+        )
+        .execute(&*self.pool)
+        .await?;
+        sqlx::query(
+            "CREATE INDEX IF NOT EXISTS xcloud_transforms_source
+             ON xcloud_transforms (source_table, source_key)",
+        )
+        .execute(&*self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Registers a rule keeping a key equal to a field of another key, and derives the key
+    /// from the current value of the source.
+    ///
+    /// # Arguments
+    ///
+    /// * `source_table` - The table holding the source key.
+    /// * `source_key` - The key whose value the field is read from.
+    /// * `pointer` - The RFC 6901 JSON Pointer of the field, empty for the whole value.
+    /// * `target_table` - The table holding the derived key.
+    /// * `target_key` - The derived key.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<i64>` - The id of the rule, or `None` if the target is already derived.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the rule cannot be registered.
+    pub async fn add_transform(
+        &self,
+        source_table: &str,
+        source_key: &str,
+        pointer: &str,
+        target_table: &str,
+        target_key: &str,
+    ) -> Result<Option<i64>, sqlx::Error> {
+        let id: Option<i64> = sqlx::query_scalar(
+            "INSERT INTO xcloud_transforms
+                (source_table, source_key, pointer, target_table, target_key)
+             VALUES (?1, ?2, ?3, ?4, ?5)
+             ON CONFLICT(target_table, target_key) DO NOTHING
+             RETURNING id",
+        )
+        .bind(Utils::sanitize(source_table))
+        .bind(source_key)
+        .bind(pointer)
+        .bind(Utils::sanitize(target_table))
+        .bind(target_key)
+        .fetch_optional(&*self.pool)
+        .await?;
+        if id.is_some() {
+            let value = self.get_data(source_table, source_key).await?;
+            self.apply_transforms(source_table, source_key, value.as_deref())
+                .await?;
+        }
+        Ok(id)
+    }
+
+    /// Retrieves all rules deriving keys from other keys.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the rules cannot be retrieved.
+    pub async fn transforms(&self) -> Result<Vec<Transform>, sqlx::Error> {
+        sqlx::query_as(
+            "SELECT id, source_table, source_key, pointer, target_table, target_key
+             FROM xcloud_transforms ORDER BY id",
+        )
+        .fetch_all(&*self.pool)
+        .await
+    }
+
+    /// Removes a rule deriving a key. The derived key keeps its last value.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The id of the rule.
+    ///
+    /// # Returns
+    ///
+    /// * `bool` - Whether the rule existed.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the rule cannot be removed.
+    pub async fn delete_transform(&self, id: i64) -> Result<bool, sqlx::Error> {
+        let deleted = sqlx::query("DELETE FROM xcloud_transforms WHERE id = ?1")
+            .bind(id)
+            .execute(&*self.pool)
+            .await?;
+        Ok(deleted.rows_affected() > 0)
+    }
+
+    /// Updates the keys derived from a written key.
+    ///
+    /// A derived key is deleted when its source is deleted or lacks the field. Writes of derived
+    /// keys are recorded but do not update the keys derived from them in turn.
+    ///
+    /// # Arguments
+    ///
+    /// * `table` - The table that was written to.
+    /// * `key` - The key that was written.
+    /// * `value` - The new value of the key, or `None` if it was deleted.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if a derived key cannot be written.
+    async fn apply_transforms(
+        &self,
+        table: &str,
+        key: &str,
+        value: Option<&str>,
+    ) -> Result<(), sqlx::Error> {
+        let transforms: Vec<Transform> = sqlx::query_as(
+            "SELECT id, source_table, source_key, pointer, target_table, target_key
+             FROM xcloud_transforms WHERE source_table = ?1 AND source_key = ?2",
+        )
+        .bind(Utils::sanitize(table))
+        .bind(key)
+        .fetch_all(&*self.pool)
+        .await?;
+        if transforms.is_empty() {
+            return Ok(());
+        }
+        let document =
+            value.and_then(|value| serde_json::from_str::<serde_json::Value>(value).ok());
+        for transform in transforms {
+            let derived = if transform.pointer.is_empty() {
+                value.map(str::to_string)
+            } else {
+                document
+                    .as_ref()
+                    .and_then(|document| document.pointer(&transform.pointer))
+                    .map(|field| match field {
+                        serde_json::Value::String(field) => field.clone(),
+                        field => field.to_string(),
+                    })
+            };
+            let (table, key) = (&transform.target_table, &transform.target_key);
+            match &derived {
+                Some(derived) => {
+                    self.write_value(table, key, derived).await?;
+                    self.log_change(table, Some(key), "set", Some(derived))
+                        .await?;
+                }
+                None => {
+                    if let Some(storage) = self.backend(table)? {
+                        storage.delete_data(table, key).await?;
+                    } else {
+                        self.init_table(table).await?;
+                        sqlx::query(&format!(
+                            "DELETE FROM {} WHERE key = ?1",
+                            self.table(table).await?
+                        ))
+                        .bind(key)
+                        .execute(self.shard(table))
+                        .await?;
+                    }
+                    self.log_change(table, Some(key), "delete", None).await?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Initializes the tables holding the instances sharing the database and the lease of the
+    /// leader among them.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the tables cannot be created.
+    pub async fn init_cluster(&self) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS xcloud_cluster_members (
+                instance_id TEXT PRIMARY KEY,
+                address TEXT NOT NULL,
+                started_at INTEGER NOT NULL DEFAULT (CAST(strftime('%s', 'now') AS INTEGER)),
+                heartbeat_at INTEGER NOT NULL
+            )",
+        )
+        .execute(&*self.pool)
+        .await?;
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS xcloud_cluster_leader (
+                id INTEGER PRIMARY KEY CHECK (id = 1),
+                instance_id TEXT NOT NULL,
+                expires_at INTEGER NOT NULL
+            )",
+        )
+        .execute(&*self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Reports an instance as alive and takes or renews the leadership for it, unless another
+    /// instance holds an unexpired lease. Instances silent for ten leases are forgotten.
+    ///
+    /// # Arguments
+    ///
+    /// * `instance_id` - The id of the instance.
+    /// * `address` - The address the instance is reached at.
+    /// * `lease_secs` - The number of seconds the leadership lasts without being renewed.
+    ///
+    /// # Returns
+    ///
+    /// * `bool` - Whether the instance is the leader.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the heartbeat cannot be recorded.
+    pub async fn cluster_heartbeat(
+        &self,
+        instance_id: &str,
+        address: &str,
+        lease_secs: i64,
+    ) -> Result<bool, sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
+        sqlx::query(
+            "INSERT INTO xcloud_cluster_members (instance_id, address, heartbeat_at)
+             VALUES (?1, ?2, CAST(strftime('%s', 'now') AS INTEGER))
+             ON CONFLICT(instance_id) DO UPDATE SET
+                address = excluded.address,
+                heartbeat_at = excluded.heartbeat_at",
+        )
+        .bind(instance_id)
+        .bind(address)
+        .execute(&mut *tx)
+        .await?;
+        sqlx::query(
+            "DELETE FROM xcloud_cluster_members
+             WHERE heartbeat_at < CAST(strftime('%s', 'now') AS INTEGER) - ?1 * 10",
+        )
+        .bind(lease_secs)
+        .execute(&mut *tx)
+        .await?;
+        sqlx::query(
+            "INSERT INTO xcloud_cluster_leader (id, instance_id, expires_at)
+             VALUES (1, ?1, CAST(strftime('%s', 'now') AS INTEGER) + ?2)
+             ON CONFLICT(id) DO UPDATE SET
+                instance_id = excluded.instance_id,
+                expires_at = excluded.expires_at
+             WHERE xcloud_cluster_leader.instance_id = excluded.instance_id
+                OR xcloud_cluster_leader.expires_at <= CAST(strftime('%s', 'now') AS INTEGER)",
+        )
+        .bind(instance_id)
+        .bind(lease_secs)
+        .execute(&mut *tx)
+        .await?;
+        let leader: String =
+            sqlx::query_scalar("SELECT instance_id FROM xcloud_cluster_leader WHERE id = 1")
+                .fetch_one(&mut *tx)
+                .await?;
+        tx.commit().await?;
+        Ok(leader == instance_id)
+    }
+
+    /// Gives up the leadership of an instance, so another instance takes over at its next
+    /// heartbeat instead of after the lease expired, and forgets the instance.
+    ///
+    /// # Arguments
+    ///
+    /// * `instance_id` - The id of the instance.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the leadership cannot be given up.
+    pub async fn leave_cluster(&self, instance_id: &str) -> Result<(), sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
+        for table in ["xcloud_cluster_leader", "xcloud_cluster_members"] {
+            sqlx::query(&format!("DELETE FROM {} WHERE instance_id = ?1", table))
+                .bind(instance_id)
+                .execute(&mut *tx)
+                .await?;
+        }
+        tx.commit().await
+    }
+
+    /// Retrieves the instances that reported themselves within a lease, the leader first.
+    ///
+    /// # Arguments
+    ///
+    /// * `lease_secs` - The number of seconds the leadership lasts without being renewed.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the instances cannot be retrieved.
+    pub async fn cluster_members(
+        &self,
+        lease_secs: i64,
+    ) -> Result<Vec<ClusterMember>, sqlx::Error> {
+        sqlx::query_as(
+            "SELECT m.instance_id, m.address, m.started_at, m.heartbeat_at,
+                    l.instance_id IS NOT NULL AS leader
+             FROM xcloud_cluster_members m
+             LEFT JOIN xcloud_cluster_leader l
+                ON l.instance_id = m.instance_id
+               AND l.expires_at > CAST(strftime('%s', 'now') AS INTEGER)
+             WHERE m.heartbeat_at >= CAST(strftime('%s', 'now') AS INTEGER) - ?1
+             ORDER BY leader DESC, m.started_at, m.instance_id",
+        )
+        .bind(lease_secs)
+        .fetch_all(&*self.pool)
+        .await
+    }
+
+    /// Initializes the tables holding the aggregate views, the amount every key adds to its
+    /// view and the aggregates of every prefix.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the tables cannot be created.
+    pub async fn init_views(&self) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS xcloud_views (
+                name TEXT PRIMARY KEY,
+                table_name TEXT NOT NULL,
+                delimiter TEXT NOT NULL,
+                pointer TEXT NOT NULL
+            )",
+        )
+        .execute(&*self.pool)
+        .await?;
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS xcloud_view_keys (
+                view_name TEXT NOT NULL,
+                key TEXT NOT NULL,
+                prefix TEXT NOT NULL,
+                amount REAL NOT NULL,
+                PRIMARY KEY (view_name, key)
+            ) WITHOUT ROWID",
+        )
+        .execute(&*self.pool)
+        .await?;
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS xcloud_view_groups (
+                view_name TEXT NOT NULL,
+                prefix TEXT NOT NULL,
+                count INTEGER NOT NULL,
+                sum REAL NOT NULL,
+                updated_at INTEGER NOT NULL,
+                PRIMARY KEY (view_name, prefix)
+            ) WITHOUT ROWID",
+        )
+        .execute(&*self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Creates an aggregate view and computes it from the current rows of its table.
+    ///
+    /// # Arguments
+    ///
+    /// * `view` - The view.
+    ///
+    /// # Returns
+    ///
+    /// * `bool` - Whether the view was created, `false` if it already exists.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the view cannot be created or computed.
+    pub async fn create_view(&self, view: &AggregateView) -> Result<bool, sqlx::Error> {
+        let view = AggregateView {
+            name: Utils::sanitize(&view.name),
+            table: Utils::sanitize(&view.table),
+            ..view.clone()
+        };
+        let created = sqlx::query(
+            "INSERT OR IGNORE INTO xcloud_views (name, table_name, delimiter, pointer)
+             VALUES (?1, ?2, ?3, ?4)",
+        )
+        .bind(&view.name)
+        .bind(&view.table)
+        .bind(&view.delimiter)
+        .bind(&view.pointer)
+        .execute(&*self.pool)
+        .await?
+        .rows_affected();
+        if created == 0 {
+            return Ok(false);
+        }
+        let (_, mut rows) = self.consistent_export(&view.table).await?;
+        let mut tx = self.pool.begin().await?;
+        while let Some(row) = rows.recv().await {
+            let (key, value) = row?;
+            Self::apply_view(&mut tx, &view, &key, Some(&value)).await?;
+        }
+        tx.commit().await?;
+        Ok(true)
+    }
+
+    /// Retrieves all aggregate views.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the views cannot be retrieved.
+    pub async fn views(&self) -> Result<Vec<AggregateView>, sqlx::Error> {
+        sqlx::query_as(
+            "SELECT name, table_name AS \"table\", delimiter, pointer FROM xcloud_views
+             ORDER BY name",
+        )
+        .fetch_all(&*self.pool)
+        .await
+    }
+
+    /// Deletes an aggregate view with its aggregates.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the view.
+    ///
+    /// # Returns
+    ///
+    /// * `bool` - Whether the view existed.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the view cannot be deleted.
+    pub async fn delete_view(&self, name: &str) -> Result<bool, sqlx::Error> {
+        let name = Utils::sanitize(name);
+        let mut tx = self.pool.begin().await?;
+        let deleted = sqlx::query("DELETE FROM xcloud_views WHERE name = ?1")
+            .bind(&name)
+            .execute(&mut *tx)
+            .await?
+            .rows_affected();
+        for table in ["xcloud_view_keys", "xcloud_view_groups"] {
+            sqlx::query(&format!("DELETE FROM {} WHERE view_name = ?1", table))
+                .bind(&name)
+                .execute(&mut *tx)
+                .await?;
+        }
+        tx.commit().await?;
+        Ok(deleted > 0)
+    }
+
+    /// Retrieves the aggregates of an aggregate view, optionally of the prefixes starting with
+    /// a string.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the view.
+    /// * `prefix` - The string the prefixes start with, or `None` for all prefixes.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<Vec<ViewGroup>>` - The aggregates by prefix, or `None` if the view does not
+    ///   exist.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the aggregates cannot be retrieved.
+    pub async fn view_groups(
+        &self,
+        name: &str,
+        prefix: Option<&str>,
+    ) -> Result<Option<Vec<ViewGroup>>, sqlx::Error> {
+        let name = Utils::sanitize(name);
+        let exists: Option<i64> = sqlx::query_scalar("SELECT 1 FROM xcloud_views WHERE name = ?1")
+            .bind(&name)
+            .fetch_optional(&*self.pool)
+            .await?;
+        if exists.is_none() {
+            return Ok(None);
+        }
+        sqlx::query_as(
+            "SELECT prefix, count, sum, updated_at FROM xcloud_view_groups
+             WHERE view_name = ?1 AND (?2 IS NULL OR substr(prefix, 1, length(?2)) = ?2)
+             ORDER BY prefix",
+        )
+        .bind(&name)
+        .bind(prefix)
+        .fetch_all(&*self.pool)
+        .await
+        .map(Some)
+    }
+
+    /// Updates the aggregate views of a table for a written key.
+    ///
+    /// # Arguments
+    ///
+    /// * `table` - The table that was written to.
+    /// * `key` - The key that was written.
+    /// * `value` - The new value of the key, or `None` if it was deleted.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if a view cannot be updated.
+    async fn apply_views(
+        &self,
+        table: &str,
+        key: &str,
+        value: Option<&str>,
+    ) -> Result<(), sqlx::Error> {
+        let views: Vec<AggregateView> = sqlx::query_as(
+            "SELECT name, table_name AS \"table\", delimiter, pointer FROM xcloud_views
+             WHERE table_name = ?1",
+        )
+        .bind(Utils::sanitize(table))
+        .fetch_all(&*self.pool)
+        .await?;
+        if views.is_empty() {
+            return Ok(());
+        }
+        let mut tx = self.pool.begin().await?;
+        for view in &views {
+            Self::apply_view(&mut tx, view, key, value).await?;
+        }
+        tx.commit().await
+    }
+
+    /// Moves a key out of the aggregate of its previous value and into that of its new one.
+    ///
+    /// # Arguments
+    ///
+    /// * `conn` - The connection to the metadata database.
+    /// * `view` - The view.
+    /// * `key` - The key.
+    /// * `value` - The new value of the key, or `None` if it was deleted.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the view cannot be updated.
+    async fn apply_view(
+        conn: &mut sqlx::SqliteConnection,
+        view: &AggregateView,
+        key: &str,
+        value: Option<&str>,
+    ) -> Result<(), sqlx::Error> {
+        let previous: Option<(String, f64)> = sqlx::query_as(
+            "DELETE FROM xcloud_view_keys WHERE view_name = ?1 AND key = ?2
+             RETURNING prefix, amount",
+        )
+        .bind(&view.name)
+        .bind(key)
+        .fetch_optional(&mut *conn)
+        .await?;
+        if let Some((prefix, amount)) = previous {
+            sqlx::query(
+                "UPDATE xcloud_view_groups
+                 SET count = count - 1, sum = sum - ?3,
+                     updated_at = CAST(strftime('%s', 'now') AS INTEGER)
+                 WHERE view_name = ?1 AND prefix = ?2",
+            )
+            .bind(&view.name)
+            .bind(&prefix)
+            .bind(amount)
+            .execute(&mut *conn)
+            .await?;
+            sqlx::query("DELETE FROM xcloud_view_groups WHERE view_name = ?1 AND count <= 0")
+                .bind(&view.name)
+                .execute(&mut *conn)
+                .await?;
+        }
+        let Some(value) = value else {
+            return Ok(());
+        };
+        let (prefix, amount) = (view.prefix(key), view.amount(value));
+        sqlx::query(
+            "INSERT INTO xcloud_view_keys (view_name, key, prefix, amount) VALUES (?1, ?2, ?3, ?4)",
+        )
+        .bind(&view.name)
+        .bind(key)
+        .bind(prefix)
+        .bind(amount)
+        .execute(&mut *conn)
+        .await?;
+        sqlx::query(
+            "INSERT INTO xcloud_view_groups (view_name, prefix, count, sum, updated_at)
+             VALUES (?1, ?2, 1, ?3, CAST(strftime('%s', 'now') AS INTEGER))
+             ON CONFLICT(view_name, prefix) DO UPDATE SET
+                count = count + 1,
+                sum = sum + excluded.sum,
+                updated_at = excluded.updated_at",
+        )
+        .bind(&view.name)
+        .bind(prefix)
+        .bind(amount)
+        .execute(&mut *conn)
+        .await?;
+        Ok(())
+    }
+
+    /// Empties the aggregate views of a deleted table.
+    ///
+    /// # Arguments
+    ///
+    /// * `table` - The deleted table.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the views cannot be emptied.
+    async fn clear_views(&self, table: &str) -> Result<(), sqlx::Error> {
+        let table = Utils::sanitize(table);
+        for target in ["xcloud_view_keys", "xcloud_view_groups"] {
+            sqlx::query(&format!(
+                "DELETE FROM {} WHERE view_name IN
+                    (SELECT name FROM xcloud_views WHERE table_name = ?1)",
+                target
+            ))
+            .bind(&table)
+            .execute(&*self.pool)
+            .await?;
+        }
+        Ok(())
+    }
+
+    /// Initializes the table holding the comments on keys.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the table cannot be initialized.
+    pub async fn init_comments(&self) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS xcloud_comments (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                table_name TEXT NOT NULL,
+                key TEXT NOT NULL,
+                parent_id INTEGER,
+                author TEXT NOT NULL,
+                body TEXT NOT NULL,
+                created_at INTEGER NOT NULL,
+                updated_at INTEGER NOT NULL
+            )",
+        )
+        .execute(&*self.pool)
+        .await?;
+        sqlx::query(
+            "CREATE INDEX IF NOT EXISTS xcloud_comments_key ON xcloud_comments (table_name, key)",
+        )
+        .execute(&*self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Posts a comment on a key, optionally as a reply to another comment on the same key.
+    ///
+    /// # Arguments
+    ///
+    /// * `table` - The table holding the key.
+    /// * `key` - The key to comment on.
+    /// * `parent_id` - The id of the comment replied to, if any.
+    /// * `author` - The identity of the author.
+    /// * `body` - The text of the comment.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<Comment>` - The posted comment, or `None` if the parent is not a comment on the key.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the comment cannot be posted.
+    #[tracing::instrument(level = "debug", skip(self, body), err)]
+    pub async fn add_comment(
+        &self,
+        table: &str,
+        key: &str,
+        parent_id: Option<i64>,
+        author: &str,
+        body: &str,
+    ) -> Result<Option<Comment>, sqlx::Error> {
+        sqlx::query_as(
+            "INSERT INTO xcloud_comments
+                (table_name, key, parent_id, author, body, created_at, updated_at)
+             SELECT ?1, ?2, ?3, ?4, ?5,
+                    CAST(strftime('%s', 'now') AS INTEGER), CAST(strftime('%s', 'now') AS INTEGER)
+             WHERE ?3 IS NULL OR EXISTS (
+                SELECT 1 FROM xcloud_comments WHERE id = ?3 AND table_name = ?1 AND key = ?2
+             )
+             RETURNING id, table_name AS \"table\", key, parent_id, author, body, created_at, updated_at",
+        )
+        .bind(Utils::sanitize(table))
+        .bind(key)
+        .bind(parent_id)
+        .bind(author)
+        .bind(body)
+        .fetch_optional(&*self.pool)
+        .await
+    }
+
+    /// Retrieves the comments on a key, oldest first.
+    ///
+    /// # Arguments
+    ///
+    /// * `table` - The table holding the key.
+    /// * `key` - The key whose comments to retrieve.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the comments cannot be retrieved.
+    pub async fn comments(&self, table: &str, key: &str) -> Result<Vec<Comment>, sqlx::Error> {
+        sqlx::query_as(
+            "SELECT id, table_name AS \"table\", key, parent_id, author, body, created_at, updated_at
+             FROM xcloud_comments
+             WHERE table_name = ?1 AND key = ?2
+             ORDER BY id",
+        )
+        .bind(Utils::sanitize(table))
+        .bind(key)
+        .fetch_all(&*self.pool)
+        .await
+    }
+
+    /// Replaces the text of a comment of the given author.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The id of the comment.
+    /// * `author` - The identity of the author.
+    /// * `body` - The new text of the comment.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<Comment>` - The edited comment, or `None` if the author has no comment with the id.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the comment cannot be edited.
+    #[tracing::instrument(level = "debug", skip(self, body), err)]
+    pub async fn edit_comment(
+        &self,
+        id: i64,
+        author: &str,
+        body: &str,
+    ) -> Result<Option<Comment>, sqlx::Error> {
+        sqlx::query_as(
+            "UPDATE xcloud_comments
+             SET body = ?3, updated_at = CAST(strftime('%s', 'now') AS INTEGER)
+             WHERE id = ?1 AND author = ?2
+             RETURNING id, table_name AS \"table\", key, parent_id, author, body, created_at, updated_at",
+        )
+        .bind(id)
+        .bind(author)
+        .bind(body)
+        .fetch_optional(&*self.pool)
+        .await
+    }
+
+    /// Deletes a comment of the given author together with all replies to it.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The id of the comment.
+    /// * `author` - The identity of the author.
+    ///
+    /// # Returns
+    ///
+    /// * `Vec<Comment>` - The deleted comments, empty if the author has no comment with the id.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the comments cannot be deleted.
+    #[tracing::instrument(level = "debug", skip(self), err)]
+    pub async fn delete_comment(&self, id: i64, author: &str) -> Result<Vec<Comment>, sqlx::Error> {
+        sqlx::query_as(
+            "WITH RECURSIVE thread(id) AS (
+                SELECT id FROM xcloud_comments WHERE id = ?1 AND author = ?2
+                UNION ALL
+                SELECT c.id FROM xcloud_comments c JOIN thread t ON c.parent_id = t.id
+             )
+             DELETE FROM xcloud_comments WHERE id IN thread
+             RETURNING id, table_name AS \"table\", key, parent_id, author, body, created_at, updated_at",
+        )
+        .bind(id)
+        .bind(author)
+        .fetch_all(&*self.pool)
+        .await
+    }
+
+    /// Retrieves the comments written by any of the given authors or mentioning any of them,
+    /// oldest first.
+    ///
+    /// # Arguments
+    ///
+    /// * `identities` - The identities of the authors.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the comments cannot be retrieved.
+    pub async fn comments_of(&self, identities: &[String]) -> Result<Vec<Comment>, sqlx::Error> {
+        sqlx::query_as(
+            "SELECT id, table_name AS \"table\", key, parent_id, author, body, created_at, updated_at
+             FROM xcloud_comments
+             WHERE author IN (SELECT value FROM json_each(?1))
+                OR EXISTS (SELECT 1 FROM json_each(?1) WHERE instr(body, value) > 0)
+             ORDER BY id",
+        )
+        .bind(serde_json::to_string(identities).map_err(|e| sqlx::Error::Encode(Box::new(e)))?)
+        .fetch_all(&*self.pool)
+        .await
+    }
+
+    /// Replaces the given identities in the authors and texts of the comments with a
+    /// placeholder, keeping the threads intact.
+    ///
+    /// # Arguments
+    ///
+    /// * `identities` - The identities to replace.
+    /// * `placeholder` - The text replacing them.
+    ///
+    /// # Returns
+    ///
+    /// * `u64` - The number of changed comments.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the comments cannot be changed.
+    pub async fn anonymize_comments(
+        &self,
+        identities: &[String],
+        placeholder: &str,
+    ) -> Result<u64, sqlx::Error> {
+        let comments = self.comments_of(identities).await?;
+        let mut tx = self.pool.begin().await?;
+        for comment in &comments {
+            let author = if identities.contains(&comment.author) {
+                placeholder.to_string()
+            } else {
+                comment.author.clone()
+            };
+            let body = identities
+                .iter()
+                .filter(|identity| !identity.is_empty())
+                .fold(comment.body.clone(), |body, identity| {
+                    body.replace(identity.as_str(), placeholder)
+                });
+            sqlx::query("UPDATE xcloud_comments SET author = ?2, body = ?3 WHERE id = ?1")
+                .bind(comment.id)
+                .bind(author)
+                .bind(body)
+                .execute(&mut *tx)
+                .await?;
+        }
+        tx.commit().await?;
+        Ok(comments.len() as u64)
+    }
+
+    /// Initializes the table holding the recent writes of the activity feed.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the table cannot be initialized.
+    pub async fn init_activity(&self) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS xcloud_activity (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                table_name TEXT NOT NULL,
+                key TEXT,
+                op TEXT NOT NULL,
+                timestamp INTEGER NOT NULL DEFAULT (CAST(strftime('%s', 'now') AS INTEGER))
+            )",
+        )
+        .execute(&*self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Initializes the table holding the read and write counters of the keys.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the table cannot be initialized.
+    pub async fn init_key_stats(&self) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS xcloud_key_stats (
+                table_name TEXT NOT NULL,
+                key TEXT NOT NULL,
+                reads INTEGER NOT NULL DEFAULT 0,
+                writes INTEGER NOT NULL DEFAULT 0,
+                last_read_at INTEGER,
+                last_write_at INTEGER,
+                PRIMARY KEY (table_name, key)
+            )",
+        )
+        .execute(&*self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Records a read of a key served to a client if key statistics are enabled.
+    ///
+    /// The read is buffered in memory until the next flush.
+    ///
+    /// # Arguments
+    ///
+    /// * `table` - The table holding the key.
+    /// * `key` - The key that was read.
+    pub fn record_read(&self, table: &str, key: &str) {
+        if let Some(stats) = &self.key_stats {
+            stats.record_read(table, key);
+        }
+    }
+
+    /// Writes the buffered read and write counters in a single transaction.
+    ///
+    /// # Returns
+    ///
+    /// * `usize` - The number of keys whose counters were written.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the counters cannot be written, in which case
+    /// they are lost.
+    pub async fn flush_key_stats(&self) -> Result<usize, sqlx::Error> {
+        let Some(stats) = &self.key_stats else {
+            return Ok(0);
+        };
+        let pending = stats.drain();
+        if pending.is_empty() {
+            return Ok(0);
+        }
+        let mut tx = self.pool.begin().await?;
+        for (table, stat) in &pending {
+            sqlx::query(
+                "INSERT INTO xcloud_key_stats
+                    (table_name, key, reads, writes, last_read_at, last_write_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+                 ON CONFLICT(table_name, key) DO UPDATE SET
+                    reads = reads + excluded.reads,
+                    writes = writes + excluded.writes,
+                    last_read_at = COALESCE(excluded.last_read_at, last_read_at),
+                    last_write_at = COALESCE(excluded.last_write_at, last_write_at)",
+            )
+            .bind(Utils::sanitize(table))
+            .bind(&stat.key)
+            .bind(stat.reads)
+            .bind(stat.writes)
+            .bind(stat.last_read_at)
+            .bind(stat.last_write_at)
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
+        Ok(pending.len())
+    }
+
+    /// Archives the keys not read nor written for the given time, replacing their values with
+    /// the hashes of their archives and recording the tier in their rows.
+    ///
+    /// The last accesses are taken from the key statistics, flushed first, so nothing is
+    /// archived unless they are enabled, and keys never accessed since they were enabled are
+    /// never archived. Keys written while they are archived keep the written value.
+    ///
+    /// # Arguments
+    ///
+    /// * `idle` - The time since the last access after which a key is cold.
+    /// * `limit` - The largest number of keys to archive.
+    ///
+    /// # Returns
+    ///
+    /// * `usize` - The number of archived keys.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the keys cannot be read or archived.
+    pub async fn archive_cold_keys(
+        &self,
+        idle: std::time::Duration,
+        limit: usize,
+    ) -> Result<usize, sqlx::Error> {
+        let Some(cold) = &self.cold else {
+            return Ok(0);
+        };
+        if self.storage.is_some() || self.key_stats.is_none() {
+            return Ok(0);
+        }
+        self.flush_key_stats().await?;
+        let cutoff = SystemTime::now()
+            .checked_sub(idle)
+            .and_then(|at| at.duration_since(UNIX_EPOCH).ok())
+            .map_or(0, |d| d.as_secs() as i64);
+        let tables = self.list_tables().await?;
+        let mut archived = 0;
+        let mut after = (String::new(), String::new());
+        while archived < limit {
+            let page: Vec<(String, String)> = sqlx::query_as(
+                "SELECT table_name, key FROM xcloud_key_stats
+                 WHERE MAX(COALESCE(last_read_at, 0), COALESCE(last_write_at, 0)) <= ?1
+                   AND (table_name, key) > (?2, ?3)
+                 ORDER BY table_name, key LIMIT 500",
+            )
+            .bind(cutoff)
+            .bind(&after.0)
+            .bind(&after.1)
+            .fetch_all(&*self.pool)
+            .await?;
+            let Some(last) = page.last() else {
+                break;
+            };
+            after = last.clone();
+            let mut by_table: BTreeMap<&str, Vec<&str>> = BTreeMap::new();
+            for (table, key) in &page {
+                if tables.contains(table) {
+                    by_table
+                        .entry(table.as_str())
+                        .or_default()
+                        .push(key.as_str());
+                }
+            }
+            for (table, keys) in by_table {
+                let placeholders = (1..=keys.len())
+                    .map(|i| format!("?{}", i))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                let rows: Vec<(String, String, bool, String, Option<String>)> = keys
+                    .iter()
+                    .fold(
+                        sqlx::query_as(&format!(
+                            "SELECT key, value, deduplicated, {}, {} FROM {}
+                             WHERE key IN ({}) AND tier IS NULL",
+                            Deduplicator::resolve(""),
+                            Deduplicator::resolve_codec(""),
+                            self.table(table).await?,
+                            placeholders
+                        )),
+                        |query, key| query.bind(*key),
+                    )
+                    .fetch_all(self.shard(table))
+                    .await?;
+                for (key, value, deduplicated, stored, codec) in rows {
+                    if archived >= limit {
+                        break;
+                    }
+                    let hash = cold.archive(&stored).map_err(sqlx::Error::Io)?;
+                    archived += sqlx::query(&format!(
+                        "UPDATE {} SET value = ?1, deduplicated = 0, codec = ?5, tier = ?6
+                         WHERE key = ?2 AND value = ?3 AND deduplicated = ?4 AND tier IS NULL",
+                        self.table(table).await?
+                    ))
+                    .bind(hash)
+                    .bind(&key)
+                    .bind(value)
+                    .bind(deduplicated)
+                    .bind(codec)
+                    .bind(tiering::TIER)
+                    .execute(self.shard(table))
+                    .await?
+                    .rows_affected() as usize;
+                }
+            }
+        }
+        Ok(archived)
+    }
+
+    /// Removes the archives no table refers to anymore, e.g. as their keys were read, written
+    /// or deleted since.
+    ///
+    /// # Arguments
+    ///
+    /// * `before` - The instant before which an archive must have been written to be removed.
+    ///
+    /// # Returns
+    ///
+    /// * `usize` - The number of removed archives.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the tables cannot be read or an archive cannot be
+    /// removed.
+    pub async fn remove_unreferenced_archives(
+        &self,
+        before: SystemTime,
+    ) -> Result<usize, sqlx::Error> {
+        let Some(cold) = &self.cold else {
+            return Ok(0);
+        };
+        if self.storage.is_some() {
+            return Ok(0);
+        }
+        let mut referenced = std::collections::HashSet::new();
+        for table in self.list_tables().await? {
+            let hashes: Vec<String> = sqlx::query_scalar(&format!(
+                "SELECT value FROM {} WHERE tier = ?1",
+                Ident::quote(&table)
+            ))
+            .bind(tiering::TIER)
+            .fetch_all(self.shard(&table))
+            .await?;
+            referenced.extend(hashes);
+        }
+        cold.remove_unreferenced(&referenced, before)
+            .map_err(sqlx::Error::Io)
+    }
+
+    /// Retrieves the most accessed keys of a table, or the least recently accessed ones.
+    ///
+    /// The buffered counters are flushed first.
+    ///
+    /// # Arguments
+    ///
+    /// * `table` - The table to get the keys of.
+    /// * `stale` - Whether to return the least recently accessed keys instead.
+    /// * `limit` - The maximum number of keys to return.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the counters cannot be retrieved.
+    pub async fn hot_keys(
+        &self,
+        table: &str,
+        stale: bool,
+        limit: i64,
+    ) -> Result<Vec<KeyStat>, sqlx::Error> {
+        self.flush_key_stats().await?;
+        let order = if stale {
+            "MAX(COALESCE(last_read_at, 0), COALESCE(last_write_at, 0)) ASC"
+        } else {
+            "reads + writes DESC"
+        };
+        sqlx::query_as(&format!(
+            "SELECT key, reads, writes, last_read_at, last_write_at FROM xcloud_key_stats
+             WHERE table_name = ?1 ORDER BY {}, key LIMIT ?2",
+            order
+        ))
+        .bind(Utils::sanitize(table))
+        .bind(limit)
+        .fetch_all(&*self.pool)
+        .await
+    }
+
+    /// Records a write in the activity feed if it is enabled, dropping the oldest writes beyond the limit.
+    ///
+    /// # Arguments
+    ///
+    /// * `table` - The table that was written to.
+    /// * `key` - The key that was written, if any.
+    /// * `op` - The kind of write.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the write cannot be recorded.
+    async fn record_activity(
+        &self,
+        table: &str,
+        key: Option<&str>,
+        op: &str,
+    ) -> Result<(), sqlx::Error> {
+        if !self.activity.enabled {
+            return Ok(());
+        }
+        let id: i64 = sqlx::query_scalar(
+            "INSERT INTO xcloud_activity (table_name, key, op) VALUES (?1, ?2, ?3) RETURNING id",
+        )
+        .bind(Utils::sanitize(table))
+        .bind(key)
+        .bind(op)
+        .fetch_one(&*self.pool)
+        .await?;
+        sqlx::query("DELETE FROM xcloud_activity WHERE id <= ?1")
+            .bind(id - self.activity.max_entries.max(1))
+            .execute(&*self.pool)
+            .await?;
+        Ok(())
+    }
+
+    /// Retrieves the recent writes, newest first, coalescing consecutive writes of the same
+    /// kind to the same table.
+    ///
+    /// # Arguments
+    ///
+    /// * `table` - The table to filter by, or `None` for all tables.
+    /// * `key` - The key to filter by, or `None` for all keys.
+    /// * `offset` - The number of entries to skip.
+    /// * `limit` - The maximum number of entries to return.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the activity cannot be retrieved.
+    pub async fn activity(
+        &self,
+        table: Option<&str>,
+        key: Option<&str>,
+        offset: i64,
+        limit: i64,
+    ) -> Result<Vec<Activity>, sqlx::Error> {
+        let entries: Vec<Activity> = sqlx::query_as(
+            "WITH runs AS (
+                SELECT *,
+                       ROW_NUMBER() OVER (ORDER BY id)
+                       - ROW_NUMBER() OVER (PARTITION BY table_name, op ORDER BY id) AS run
+                FROM xcloud_activity
+                WHERE (?1 IS NULL OR table_name = ?1) AND (?2 IS NULL OR key = ?2)
+             )
+             SELECT table_name AS \"table\", op,
+                    CASE WHEN COUNT(DISTINCT key) = 1 THEN MIN(key) END AS key,
+                    COUNT(DISTINCT key) AS keys, COUNT(*) AS writes,
+                    MIN(timestamp) AS first_at, MAX(timestamp) AS last_at
+             FROM runs
+             GROUP BY table_name, op, run
+             ORDER BY MAX(id) DESC
+             LIMIT ?3 OFFSET ?4",
+        )
+        .bind(table.map(Utils::sanitize))
+        .bind(key)
+        .bind(limit)
+        .bind(offset.max(0))
+        .fetch_all(&*self.pool)
+        .await?;
+        Ok(entries
+            .into_iter()
+            .map(|entry| Activity {
+                summary: entry.describe(),
+                ..entry
+            })
+            .collect())
+    }
+
+    /// Initializes the tables holding the snapshots and the digests of the captured values.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the tables cannot be initialized.
+    pub async fn init_snapshots(&self) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS xcloud_snapshots (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                created_at INTEGER NOT NULL,
+                scheduled INTEGER NOT NULL
+            )",
+        )
+        .execute(&*self.pool)
+        .await?;
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS xcloud_snapshot_rows (
+                snapshot_id INTEGER NOT NULL,
+                table_name TEXT NOT NULL,
+                key TEXT NOT NULL,
+                digest TEXT NOT NULL,
+                PRIMARY KEY (snapshot_id, table_name, key)
+            )",
+        )
+        .execute(&*self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Captures the key-value pairs of the given tables.
+    ///
+    /// Only a digest of every value is kept, which suffices to compare snapshots. Callers
+    /// holding the database lock capture a consistent view of all tables.
+    ///
+    /// # Arguments
+    ///
+    /// * `tables` - The tables to capture, all tables if empty.
+    /// * `scheduled` - Whether the snapshot is captured by the scheduler.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the snapshot cannot be captured.
+    pub async fn create_snapshot(
+        &self,
+        tables: &[String],
+        scheduled: bool,
+    ) -> Result<Snapshot, sqlx::Error> {
+        let existing = self.list_tables().await?;
+        let tables: Vec<String> = if tables.is_empty() {
+            existing
+        } else {
+            tables
+                .iter()
+                .map(|table| Utils::sanitize(table))
+                .filter(|table| existing.contains(table))
+                .collect()
+        };
+        let mut rows = Vec::new();
+        for table in &tables {
+            let keys = self.list_keys(table, 0, None).await?;
+            for (key, value) in self.get_many(table, &keys).await? {
+                rows.push((table, key, hex::encode(Sha256::digest(value.as_bytes()))));
+            }
+        }
+        let mut tx = self.pool.begin().await?;
+        let mut snapshot: Snapshot = sqlx::query_as(
+            "INSERT INTO xcloud_snapshots (created_at, scheduled)
+             VALUES (CAST(strftime('%s', 'now') AS INTEGER), ?1)
+             RETURNING id, created_at, scheduled, 0 AS keys",
+        )
+        .bind(scheduled)
+        .fetch_one(&mut *tx)
+        .await?;
+        for (table, key, digest) in &rows {
+            sqlx::query(
+                "INSERT INTO xcloud_snapshot_rows (snapshot_id, table_name, key, digest)
+                 VALUES (?1, ?2, ?3, ?4)",
+            )
+            .bind(snapshot.id)
+            .bind(table)
+            .bind(key)
+            .bind(digest)
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
+        snapshot.keys = rows.len() as i64;
+        Ok(snapshot)
+    }
+
+    /// Retrieves all snapshots, newest first.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the snapshots cannot be retrieved.
+    pub async fn snapshots(&self) -> Result<Vec<Snapshot>, sqlx::Error> {
+        sqlx::query_as(
+            "SELECT s.id, s.created_at, s.scheduled,
+                    (SELECT COUNT(*) FROM xcloud_snapshot_rows r WHERE r.snapshot_id = s.id) AS keys
+             FROM xcloud_snapshots s ORDER BY s.id DESC",
+        )
+        .fetch_all(&*self.pool)
+        .await
+    }
+
+    /// Deletes a snapshot.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The id of the snapshot.
+    ///
+    /// # Returns
+    ///
+    /// * `bool` - Whether the snapshot existed.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the snapshot cannot be deleted.
+    pub async fn delete_snapshot(&self, id: i64) -> Result<bool, sqlx::Error> {
+        sqlx::query("DELETE FROM xcloud_snapshot_rows WHERE snapshot_id = ?1")
+            .bind(id)
+            .execute(&*self.pool)
+            .await?;
+        let deleted = sqlx::query("DELETE FROM xcloud_snapshots WHERE id = ?1")
+            .bind(id)
+            .execute(&*self.pool)
+            .await?
+            .rows_affected();
+        Ok(deleted > 0)
+    }
+
+    /// Deletes the scheduled snapshots except for the most recent ones.
+    ///
+    /// # Arguments
+    ///
+    /// * `keep` - The number of most recent scheduled snapshots to keep.
+    ///
+    /// # Returns
+    ///
+    /// * `u64` - The number of deleted snapshots.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the snapshots cannot be deleted.
+    pub async fn prune_snapshots(&self, keep: usize) -> Result<u64, sqlx::Error> {
+        let stale = "SELECT id FROM xcloud_snapshots WHERE scheduled = 1
+                     ORDER BY id DESC LIMIT -1 OFFSET ?1";
+        sqlx::query(&format!(
+            "DELETE FROM xcloud_snapshot_rows WHERE snapshot_id IN ({})",
+            stale
+        ))
+        .bind(keep as i64)
+        .execute(&*self.pool)
+        .await?;
+        let pruned = sqlx::query(&format!(
+            "DELETE FROM xcloud_snapshots WHERE id IN ({})",
+            stale
+        ))
+        .bind(keep as i64)
+        .execute(&*self.pool)
+        .await?
+        .rows_affected();
+        Ok(pruned)
+    }
+
+    /// Compares a snapshot against another one.
+    ///
+    /// Keys of tables captured by one of the snapshots only count as added or removed.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The id of the snapshot.
+    /// * `against` - The id of the snapshot to compare against, typically an older one.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<SnapshotDiff>` - The differences, or `None` if one of the snapshots does not exist.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the snapshots cannot be compared.
+    pub async fn diff_snapshots(
+        &self,
+        id: i64,
+        against: i64,
+    ) -> Result<Option<SnapshotDiff>, sqlx::Error> {
+        let found: i64 =
+            sqlx::query_scalar("SELECT COUNT(*) FROM xcloud_snapshots WHERE id IN (?1, ?2)")
+                .bind(id)
+                .bind(against)
+                .fetch_one(&*self.pool)
+                .await?;
+        if found < if id == against { 1 } else { 2 } {
+            return Ok(None);
+        }
+        let only_in = "SELECT a.table_name AS \"table\", a.key FROM xcloud_snapshot_rows a
+                       WHERE a.snapshot_id = ?1 AND NOT EXISTS (
+                           SELECT 1 FROM xcloud_snapshot_rows b
+                           WHERE b.snapshot_id = ?2 AND b.table_name = a.table_name
+                             AND b.key = a.key
+                       )
+                       ORDER BY a.table_name, a.key";
+        let added = sqlx::query_as(only_in)
+            .bind(id)
+            .bind(against)
+            .fetch_all(&*self.pool)
+            .await?;
+        let removed = sqlx::query_as(only_in)
+            .bind(against)
+            .bind(id)
+            .fetch_all(&*self.pool)
+            .await?;
+        let changed = sqlx::query_as(
+            "SELECT a.table_name AS \"table\", a.key FROM xcloud_snapshot_rows a
+             JOIN xcloud_snapshot_rows b
+               ON b.snapshot_id = ?2 AND b.table_name = a.table_name AND b.key = a.key
+             WHERE a.snapshot_id = ?1 AND a.digest <> b.digest
+             ORDER BY a.table_name, a.key",
+        )
+        .bind(id)
+        .bind(against)
+        .fetch_all(&*self.pool)
+        .await?;
+        Ok(Some(SnapshotDiff {
+            added,
+            changed,
+            removed,
+        }))
+    }
+}
@@ -0,0 +1,130 @@
+use sha2::{Digest, Sha256};
+
+use crate::config::DedupConfig;
+use crate::ident::Ident;
+
+/// A struct storing identical values above a size threshold only once.
+///
+/// Tables hold the hex encoded SHA-256 of a deduplicated value in place of the value, with
+/// their `deduplicated` column set, the value being stored in the `xcloud_values` table of
+/// their shard. The references of every table are counted by triggers on the table, and a
+/// value is removed once it is no longer referenced.
+pub struct Deduplicator {
+    min_bytes: usize,
+}
+
+/// Implementation of the `Deduplicator` struct.
+impl Deduplicator {
+    /// Creates a deduplicator from the deduplication configuration.
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - The deduplication configuration holding the threshold.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<Deduplicator>` - The deduplicator, or `None` if deduplication is disabled.
+    pub fn from_config(config: &DedupConfig) -> Option<Self> {
+        config.enabled.then_some(Deduplicator {
+            min_bytes: config.min_bytes,
+        })
+    }
+
+    /// Returns the hash the given value is deduplicated under if it is at least as large as
+    /// the threshold.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The value before it is sealed.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<String>` - The hash, or `None` if the value is stored as is.
+    pub fn reference(&self, value: &str) -> Option<String> {
+        if value.len() < self.min_bytes {
+            return None;
+        }
+        Some(hex::encode(Sha256::digest(value.as_bytes())))
+    }
+
+    /// Returns the SQL expression resolving the value of a row to the stored value.
+    ///
+    /// Rows that do not refer to a deduplicated value are returned as is, whatever they hold.
+    ///
+    /// # Arguments
+    ///
+    /// * `row` - The prefix qualifying the columns of the row, e.g. `t.`, or an empty string.
+    ///
+    /// # Returns
+    ///
+    /// * `String` - The SQL expression.
+    pub fn resolve(row: &str) -> String {
+        format!(
+            "CASE WHEN {0}deduplicated
+                  THEN (SELECT v.stored FROM xcloud_values v WHERE v.hash = {0}value)
+                  ELSE {0}value END",
+            row
+        )
+    }
+
+    /// Returns the SQL expression resolving the codec of a row to that of the stored value.
+    ///
+    /// # Arguments
+    ///
+    /// * `row` - The prefix qualifying the columns of the row, e.g. `t.`, or an empty string.
+    ///
+    /// # Returns
+    ///
+    /// * `String` - The SQL expression.
+    pub fn resolve_codec(row: &str) -> String {
+        format!(
+            "CASE WHEN {0}deduplicated
+                  THEN (SELECT v.codec FROM xcloud_values v WHERE v.hash = {0}value)
+                  ELSE {0}codec END",
+            row
+        )
+    }
+
+    /// Returns the statements creating the triggers counting the references of a table.
+    ///
+    /// # Arguments
+    ///
+    /// * `table` - The sanitized name of the table.
+    ///
+    /// # Returns
+    ///
+    /// * `Vec<String>` - The `CREATE TRIGGER` statements.
+    pub fn triggers(table: &str) -> Vec<String> {
+        let acquire = "UPDATE xcloud_values SET refs = refs + 1
+                       WHERE new.deduplicated AND hash = new.value;";
+        let release = "UPDATE xcloud_values SET refs = refs - 1
+                       WHERE old.deduplicated AND hash = old.value;
+                       DELETE FROM xcloud_values
+                       WHERE old.deduplicated AND hash = old.value AND refs <= 0;";
+        [
+            ("ai", "AFTER INSERT", acquire.to_string()),
+            (
+                "au",
+                "AFTER UPDATE OF value, deduplicated",
+                format!("{} {}", acquire, release),
+            ),
+            ("ad", "AFTER DELETE", release.to_string()),
+        ]
+        .into_iter()
+        .map(|(suffix, event, body)| {
+            format!(
+                "CREATE TRIGGER IF NOT EXISTS {} {} ON {} BEGIN {} END",
+                Self::trigger(table, suffix),
+                event,
+                Ident::quote(table),
+                body
+            )
+        })
+        .collect()
+    }
+
+    /// Returns the quoted name of a trigger of a table.
+    fn trigger(table: &str, suffix: &str) -> String {
+        Ident::derived("xcloud_dedup_", &format!("{}_{}", table, suffix))
+    }
+}
@@ -0,0 +1,27 @@
+use sqlx::Error as SqlxError;
+use std::io::Error as IoError;
+use thiserror::Error;
+
+use crate::crypto::CryptoError;
+
+/// Custom error type for the application.
+#[derive(Error, Debug)]
+pub enum AppError {
+    #[error("Database error: {0}")]
+    Sqlx(#[from] SqlxError),
+
+    #[error("IO error: {0}")]
+    Io(#[from] IoError),
+
+    #[error("Config error: {0}")]
+    Config(#[from] toml::de::Error),
+
+    #[error("Crypto error: {0}")]
+    Crypto(#[from] CryptoError),
+
+    #[error("Config watcher error: {0}")]
+    Watch(#[from] notify::Error),
+
+    #[error("Seed error: {0}")]
+    Seed(String),
+}
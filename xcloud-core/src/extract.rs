@@ -0,0 +1,114 @@
+use std::ops::Deref;
+
+use actix_web::dev::Payload;
+use actix_web::error::InternalError;
+use actix_web::http::StatusCode;
+use actix_web::{web, FromRequest, HttpMessage, HttpRequest, HttpResponse};
+use futures::future::LocalBoxFuture;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use crate::response::ApiResponse;
+
+/// A struct describing why a JSON payload was rejected.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct PayloadError {
+    /// The path of the offending field, e.g. `items[0].key`, or `.` for the payload itself.
+    pub path: String,
+    pub reason: String,
+}
+
+/// A JSON extractor reporting the path of the offending field when a payload is malformed.
+///
+/// The size of the payload is limited by the [`web::PayloadConfig`] of the resource.
+pub struct Json<T>(pub T);
+
+/// Implementation of the `Json` struct.
+impl<T> Json<T> {
+    /// Unwraps the deserialized payload.
+    ///
+    /// # Returns
+    ///
+    /// * `T` - The deserialized payload.
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+/// Implementation of the `Deref` trait for the `Json` struct.
+impl<T> Deref for Json<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+/// Implementation of the `FromRequest` trait for the `Json` struct.
+impl<T: DeserializeOwned + 'static> FromRequest for Json<T> {
+    type Error = actix_web::Error;
+    type Future = LocalBoxFuture<'static, Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, payload: &mut Payload) -> Self::Future {
+        let is_json = req.mime_type().ok().flatten().is_some_and(|mime| {
+            mime.subtype().as_str() == "json" || mime.suffix().is_some_and(|s| s.as_str() == "json")
+        });
+        let body = web::Bytes::from_request(req, payload);
+        Box::pin(async move {
+            if !is_json {
+                return Err(reject(
+                    StatusCode::BAD_REQUEST,
+                    "content_type_must_be_application_json",
+                    None,
+                ));
+            }
+            let body = body.await.map_err(|e| {
+                if e.as_response_error().status_code() == StatusCode::PAYLOAD_TOO_LARGE {
+                    reject(
+                        StatusCode::PAYLOAD_TOO_LARGE,
+                        "request_payload_is_too_large",
+                        None,
+                    )
+                } else {
+                    reject(StatusCode::BAD_REQUEST, "invalid_request_payload", None)
+                }
+            })?;
+            let de = &mut serde_json::Deserializer::from_slice(&body);
+            let result = serde_path_to_error::deserialize(&mut *de)
+                .map_err(|e| PayloadError {
+                    path: e.path().to_string(),
+                    reason: e.inner().to_string(),
+                })
+                .and_then(|value| {
+                    de.end().map(|_| value).map_err(|e| PayloadError {
+                        path: ".".to_string(),
+                        reason: e.to_string(),
+                    })
+                });
+            result.map(Json).map_err(|error| {
+                reject(
+                    StatusCode::BAD_REQUEST,
+                    "invalid_request_payload",
+                    Some(error),
+                )
+            })
+        })
+    }
+}
+
+/// Builds the error rejecting a payload.
+///
+/// # Arguments
+///
+/// * `status` - The status of the response.
+/// * `code` - The code of the error of the response.
+/// * `error` - The offending field, if the payload is malformed.
+///
+/// # Returns
+///
+/// * `actix_web::Error` - The error rendered as an [`ApiResponse`].
+fn reject(status: StatusCode, code: &str, error: Option<PayloadError>) -> actix_web::Error {
+    let response = ApiResponse::error_with_data(code, &[], error);
+    let message = response.message.clone();
+    InternalError::from_response(message, HttpResponse::build(status).json(response)).into()
+}
@@ -0,0 +1,274 @@
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, PoisonError, RwLock};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::db::Database;
+use crate::health::Health;
+
+/// The interval in seconds at which the flags are reloaded, so every instance of a cluster
+/// applies the changes made through another.
+const REFRESH_INTERVAL_SECS: u64 = 10;
+
+/// A struct representing a feature flag gating endpoints or behaviors.
+///
+/// A flag is on for a request if it is enabled globally, if the tenant of the request is listed
+/// or if the request falls within the rolled out percentage. Requests of a tenant always fall
+/// on the same side of the percentage, so a tenant keeps the feature as it is rolled out
+/// further, while requests without a tenant are spread evenly.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(default)]
+pub struct FeatureFlag {
+    pub name: String,
+    pub description: Option<String>,
+    /// Whether the flag is on for every request.
+    pub enabled: bool,
+    /// The tenants the flag is on for, whatever the percentage.
+    pub tenants: Vec<String>,
+    /// The percentage of the other requests the flag is on for, from 0 to 100.
+    pub percentage: u8,
+    /// The path prefixes of the endpoints answering `404 Not Found` while the flag is off.
+    pub routes: Vec<String>,
+}
+
+/// Implementation of the `FeatureFlag` struct.
+impl FeatureFlag {
+    /// Checks that the flag can be stored.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the name is empty or holds other characters than
+    /// letters, digits, `_`, `-` and `.`, the percentage exceeds 100 or a route is no path or
+    /// an admin path.
+    pub fn validate(&self) -> Result<(), sqlx::Error> {
+        let refuse = |reason: String| Err(sqlx::Error::InvalidArgument(reason));
+        if self.name.is_empty()
+            || !self
+                .name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.'))
+        {
+            return refuse(format!("invalid flag name: '{}'", self.name));
+        }
+        if self.percentage > 100 {
+            return refuse("the percentage must be at most 100".to_string());
+        }
+        if let Some(route) = self
+            .routes
+            .iter()
+            .find(|route| !route.starts_with('/') || route.starts_with("/admin"))
+        {
+            return refuse(format!("invalid route: '{}'", route));
+        }
+        Ok(())
+    }
+
+    /// Evaluates the flag for a request.
+    ///
+    /// # Arguments
+    ///
+    /// * `tenant` - The tenant of the request, if any.
+    /// * `sequence` - The number of the request, spreading requests without a tenant.
+    ///
+    /// # Returns
+    ///
+    /// * `bool` - Whether the flag is on for the request.
+    fn is_on(&self, tenant: Option<&str>, sequence: u64) -> bool {
+        if self.enabled || tenant.is_some_and(|tenant| self.tenants.iter().any(|t| t == tenant)) {
+            return true;
+        }
+        let bucket = match tenant {
+            Some(tenant) => {
+                let digest = Sha256::digest(format!("{}:{}", self.name, tenant).as_bytes());
+                u64::from_be_bytes(digest[..8].try_into().unwrap_or_default()) % 100
+            }
+            None => sequence % 100,
+        };
+        bucket < u64::from(self.percentage)
+    }
+}
+
+/// A struct representing the evaluation of a flag for a request.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct FlagState {
+    pub name: String,
+    pub enabled: bool,
+}
+
+/// A struct caching the feature flags persisted in the database, shared by all workers.
+///
+/// The tenant of a request is its `X-xCLOUD-Client` header, authenticated when request
+/// signatures are required. Unknown flags are off.
+#[derive(Default)]
+pub struct FeatureFlags {
+    flags: RwLock<BTreeMap<String, FeatureFlag>>,
+    /// The number of requests evaluated without a tenant.
+    sequence: AtomicU64,
+}
+
+/// Implementation of the `FeatureFlags` struct.
+impl FeatureFlags {
+    /// Loads the flags persisted in the database.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - The database holding the flags.
+    ///
+    /// # Returns
+    ///
+    /// * `FeatureFlags` - The loaded flags.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the flags cannot be retrieved.
+    pub async fn load(db: &Database) -> Result<Self, sqlx::Error> {
+        let flags = FeatureFlags::default();
+        flags.refresh(db).await?;
+        Ok(flags)
+    }
+
+    /// Replaces the cached flags with those persisted in the database.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the flags cannot be retrieved.
+    pub async fn refresh(&self, db: &Database) -> Result<(), sqlx::Error> {
+        let flags = db.feature_flags().await?;
+        *self.flags.write().unwrap_or_else(PoisonError::into_inner) = flags
+            .into_iter()
+            .map(|flag| (flag.name.clone(), flag))
+            .collect();
+        Ok(())
+    }
+
+    /// Reloads the flags in the background.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - A reference to the database wrapped in an Arc and Mutex for thread safety.
+    pub fn spawn(self: &Arc<Self>, db: Arc<tokio::sync::Mutex<Database>>) {
+        let flags = self.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(Duration::from_secs(REFRESH_INTERVAL_SECS));
+            loop {
+                interval.tick().await;
+                if let Err(e) = flags.refresh(&*db.lock().await).await {
+                    log::warn!("Failed to reload feature flags: {}", e);
+                }
+            }
+        });
+    }
+
+    /// Returns the cached flags, ordered by name.
+    pub fn list(&self) -> Vec<FeatureFlag> {
+        self.flags
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .values()
+            .cloned()
+            .collect()
+    }
+
+    /// Persists and applies a flag, replacing any flag of the same name.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - The database persisting the flag.
+    /// * `flag` - The flag.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an `InvalidArgument` error if the flag is invalid, and any
+    /// other error if it cannot be persisted.
+    pub async fn set(&self, db: &Database, flag: FeatureFlag) -> Result<(), sqlx::Error> {
+        flag.validate()?;
+        db.set_feature_flag(&flag).await?;
+        self.flags
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert(flag.name.clone(), flag);
+        Ok(())
+    }
+
+    /// Removes a flag, turning it off.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - The database persisting the flag.
+    /// * `name` - The name of the flag.
+    ///
+    /// # Returns
+    ///
+    /// * `bool` - Whether the flag existed.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the flag cannot be removed.
+    pub async fn remove(&self, db: &Database, name: &str) -> Result<bool, sqlx::Error> {
+        let removed = db.delete_feature_flag(name).await?;
+        self.flags
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .remove(name);
+        Ok(removed)
+    }
+
+    /// Evaluates a flag for a request.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the flag.
+    /// * `tenant` - The tenant of the request, if any.
+    ///
+    /// # Returns
+    ///
+    /// * `bool` - Whether the flag is on for the request, `false` if it does not exist.
+    pub fn is_enabled(&self, name: &str, tenant: Option<&str>) -> bool {
+        let flags = self.flags.read().unwrap_or_else(PoisonError::into_inner);
+        flags
+            .get(name)
+            .is_some_and(|flag| flag.is_on(tenant, self.next_sequence(tenant)))
+    }
+
+    /// Checks whether a request targets an endpoint gated by a flag that is off for it.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path of the request.
+    /// * `tenant` - The tenant of the request, if any.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<String>` - The name of the flag blocking the request, or `None` if it is
+    ///   allowed.
+    pub fn blocks(&self, path: &str, tenant: Option<&str>) -> Option<String> {
+        if path.starts_with("/admin") || Health::is_probe(path) {
+            return None;
+        }
+        let flags = self.flags.read().unwrap_or_else(PoisonError::into_inner);
+        let mut gating = flags
+            .values()
+            .filter(|flag| {
+                flag.routes
+                    .iter()
+                    .any(|route| path.starts_with(route.as_str()))
+            })
+            .peekable();
+        gating.peek()?;
+        let sequence = self.next_sequence(tenant);
+        gating
+            .find(|flag| !flag.is_on(tenant, sequence))
+            .map(|flag| flag.name.clone())
+    }
+
+    /// Returns the number spreading a request over the percentage of the flags.
+    fn next_sequence(&self, tenant: Option<&str>) -> u64 {
+        match tenant {
+            Some(_) => 0,
+            None => self.sequence.fetch_add(1, Ordering::Relaxed),
+        }
+    }
+}
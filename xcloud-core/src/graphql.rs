@@ -0,0 +1,389 @@
+use std::sync::{Arc, PoisonError, RwLock};
+
+use async_graphql::{
+    Context, EmptySubscription, Error, ErrorExtensions, Json, Object, Result, Schema, SimpleObject,
+};
+use serde_json::Value;
+use tokio::sync::Mutex;
+
+use crate::config::Config;
+use crate::db::{Comment, Database, Project};
+use crate::schema::SchemaViolation;
+use crate::server::LEGAL_HOLD_CODE;
+use crate::webhooks::{WebhookEvent, Webhooks};
+
+/// The schema of the GraphQL API.
+pub type GraphQLSchema = Schema<QueryRoot, MutationRoot, EmptySubscription>;
+
+/// The maximum nesting depth of a query.
+const MAX_DEPTH: usize = 12;
+
+/// The lock owner named by the request, checked by the mutations when locks are enforced.
+pub struct LockOwner(pub Option<String>);
+
+/// Builds the schema of the GraphQL API.
+///
+/// # Arguments
+///
+/// * `db` - A reference to the database wrapped in an Arc and Mutex for thread safety.
+/// * `webhooks` - The dispatcher notifying the webhooks of the written tables.
+/// * `config` - The live configuration deciding whether locks are enforced.
+///
+/// # Returns
+///
+/// * `GraphQLSchema` - The schema resolving queries and mutations against the database.
+pub fn schema(
+    db: Arc<Mutex<Database>>,
+    webhooks: Arc<Webhooks>,
+    config: Arc<RwLock<Config>>,
+) -> GraphQLSchema {
+    Schema::build(QueryRoot, MutationRoot, EmptySubscription)
+        .data(db)
+        .data(webhooks)
+        .data(config)
+        .limit_depth(MAX_DEPTH)
+        .finish()
+}
+
+/// Converts a database error into a GraphQL error, logging the cause.
+fn database_error(e: sqlx::Error, message: &str) -> Error {
+    log::error!("{}: {}", message, e);
+    Error::new(message).extend_with(|_, ext| ext.set("code", "database_error"))
+}
+
+/// Creates a GraphQL error carrying a machine-readable code.
+fn rejection(message: impl Into<String>, code: &'static str) -> Error {
+    Error::new(message).extend_with(|_, ext| ext.set("code", code))
+}
+
+/// A table of key-value data.
+pub struct Table {
+    name: String,
+}
+
+#[Object]
+impl Table {
+    async fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The entry of a key, if it exists.
+    async fn entry(&self, ctx: &Context<'_>, key: String) -> Result<Option<Entry>> {
+        let db = ctx.data_unchecked::<Arc<Mutex<Database>>>().lock().await;
+        let value = db
+            .get_data(&self.name, &key)
+            .await
+            .map_err(|e| database_error(e, "Failed to retrieve data"))?;
+        Ok(value.map(|value| {
+            db.record_read(&self.name, &key);
+            Entry {
+                table: self.name.clone(),
+                key,
+                value,
+            }
+        }))
+    }
+
+    /// The entries in ascending key order.
+    async fn entries(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(default)] offset: i64,
+        limit: Option<i64>,
+    ) -> Result<Vec<Entry>> {
+        let db = ctx.data_unchecked::<Arc<Mutex<Database>>>().lock().await;
+        let keys = db
+            .list_keys(&self.name, offset, limit)
+            .await
+            .map_err(|e| database_error(e, "Failed to list keys"))?;
+        let found = db
+            .get_many(&self.name, &keys)
+            .await
+            .map_err(|e| database_error(e, "Failed to retrieve data"))?;
+        Ok(found
+            .into_iter()
+            .map(|(key, value)| Entry {
+                table: self.name.clone(),
+                key,
+                value,
+            })
+            .collect())
+    }
+}
+
+/// A key of a table and its value.
+pub struct Entry {
+    table: String,
+    key: String,
+    value: String,
+}
+
+#[Object]
+impl Entry {
+    async fn table(&self) -> &str {
+        &self.table
+    }
+
+    async fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// The value as stored.
+    async fn value(&self) -> &str {
+        &self.value
+    }
+
+    /// The value decoded according to the value type of the table.
+    async fn json(&self, ctx: &Context<'_>) -> Result<Json<Value>> {
+        let db = ctx.data_unchecked::<Arc<Mutex<Database>>>().lock().await;
+        let value_type = db
+            .get_value_type(&self.table)
+            .await
+            .map_err(|e| database_error(e, "Failed to retrieve data"))?;
+        Ok(Json(value_type.to_json(&self.value)))
+    }
+
+    /// A field of the value parsed as JSON, selected by an RFC 6901 JSON Pointer, or `null` if
+    /// the value is no JSON or lacks the field.
+    async fn field(&self, pointer: String) -> Option<Json<Value>> {
+        let value: Value = serde_json::from_str(&self.value).ok()?;
+        value.pointer(&pointer).cloned().map(Json)
+    }
+
+    /// The comments posted on the key, oldest first.
+    async fn comments(&self, ctx: &Context<'_>) -> Result<Vec<EntryComment>> {
+        let db = ctx.data_unchecked::<Arc<Mutex<Database>>>().lock().await;
+        let comments = db
+            .comments(&self.table, &self.key)
+            .await
+            .map_err(|e| database_error(e, "Failed to retrieve comments"))?;
+        Ok(comments.into_iter().map(EntryComment::from).collect())
+    }
+}
+
+/// A comment posted on a key.
+#[derive(SimpleObject)]
+pub struct EntryComment {
+    id: i64,
+    parent_id: Option<i64>,
+    author: String,
+    body: String,
+    created_at: i64,
+    updated_at: i64,
+}
+
+impl From<Comment> for EntryComment {
+    fn from(comment: Comment) -> Self {
+        EntryComment {
+            id: comment.id,
+            parent_id: comment.parent_id,
+            author: comment.author,
+            body: comment.body,
+            created_at: comment.created_at,
+            updated_at: comment.updated_at,
+        }
+    }
+}
+
+/// A project grouping tables.
+pub struct ProjectNode(Project);
+
+#[Object(name = "Project")]
+impl ProjectNode {
+    async fn name(&self) -> &str {
+        &self.0.name
+    }
+
+    async fn created_at(&self) -> i64 {
+        self.0.created_at
+    }
+
+    /// The tables of the project.
+    async fn tables(&self, ctx: &Context<'_>) -> Result<Vec<Table>> {
+        let db = ctx.data_unchecked::<Arc<Mutex<Database>>>().lock().await;
+        let tables = db
+            .project_tables(&self.0.name)
+            .await
+            .map_err(|e| database_error(e, "Failed to retrieve project tables"))?;
+        Ok(tables
+            .unwrap_or_default()
+            .into_iter()
+            .map(|name| Table { name })
+            .collect())
+    }
+}
+
+/// The root of the queries.
+pub struct QueryRoot;
+
+#[Object]
+impl QueryRoot {
+    /// All tables.
+    async fn tables(&self, ctx: &Context<'_>) -> Result<Vec<Table>> {
+        let db = ctx.data_unchecked::<Arc<Mutex<Database>>>().lock().await;
+        let tables = db
+            .list_tables()
+            .await
+            .map_err(|e| database_error(e, "Failed to list tables"))?;
+        Ok(tables.into_iter().map(|name| Table { name }).collect())
+    }
+
+    /// A table, created empty on first access like through the REST API.
+    async fn table(&self, name: String) -> Table {
+        Table { name }
+    }
+
+    /// All projects.
+    async fn projects(&self, ctx: &Context<'_>) -> Result<Vec<ProjectNode>> {
+        let db = ctx.data_unchecked::<Arc<Mutex<Database>>>().lock().await;
+        let projects = db
+            .projects()
+            .await
+            .map_err(|e| database_error(e, "Failed to retrieve projects"))?;
+        Ok(projects.into_iter().map(ProjectNode).collect())
+    }
+
+    /// A project, if it exists.
+    async fn project(&self, ctx: &Context<'_>, name: String) -> Result<Option<ProjectNode>> {
+        let db = ctx.data_unchecked::<Arc<Mutex<Database>>>().lock().await;
+        let projects = db
+            .projects()
+            .await
+            .map_err(|e| database_error(e, "Failed to retrieve projects"))?;
+        Ok(projects
+            .into_iter()
+            .find(|project| project.name == name)
+            .map(ProjectNode))
+    }
+}
+
+/// The root of the mutations, applying the same checks and notifying the same webhooks as the
+/// REST API.
+pub struct MutationRoot;
+
+/// Implementation of the `MutationRoot` struct.
+impl MutationRoot {
+    /// Rejects a write to a table under a legal hold or during a schema operation, or to a key
+    /// locked by another owner when lock enforcement is enabled.
+    async fn check_write(ctx: &Context<'_>, db: &Database, table: &str, key: &str) -> Result<()> {
+        if let Some(hold) = db
+            .legal_hold(table)
+            .await
+            .map_err(|e| database_error(e, "Failed to check legal hold"))?
+        {
+            return Err(rejection(
+                format!("Table is under legal hold: {}", hold.reason),
+                LEGAL_HOLD_CODE,
+            ));
+        }
+        if let Some(lock) = db
+            .schema_lock(table)
+            .await
+            .map_err(|e| database_error(e, "Failed to check schema lock"))?
+        {
+            return Err(rejection(
+                format!("Table is locked by a {} operation", lock.operation),
+                "schema_locked",
+            ));
+        }
+        let enforce = ctx
+            .data_unchecked::<Arc<RwLock<Config>>>()
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .locks
+            .enforce;
+        if !enforce {
+            return Ok(());
+        }
+        let owner = ctx
+            .data_opt::<LockOwner>()
+            .and_then(|owner| owner.0.as_deref());
+        match db
+            .get_lock(table, key)
+            .await
+            .map_err(|e| database_error(e, "Failed to check lock"))?
+        {
+            Some(lock) if Some(lock.owner.as_str()) != owner => Err(rejection(
+                format!("Key is locked by {}", lock.owner),
+                "locked",
+            )),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[Object]
+impl MutationRoot {
+    /// Stores a value under a key.
+    async fn set_data(
+        &self,
+        ctx: &Context<'_>,
+        table: String,
+        key: String,
+        value: String,
+    ) -> Result<Entry> {
+        let db = ctx.data_unchecked::<Arc<Mutex<Database>>>().lock().await;
+        Self::check_write(ctx, &db, &table, &key).await?;
+        if let Err(violations) = db
+            .validate_value(&table, &value)
+            .await
+            .map_err(|e| database_error(e, "Failed to set data"))?
+        {
+            return Err(rejection(
+                format!(
+                    "Value does not match the table schema: {}",
+                    SchemaViolation::describe(&violations)
+                ),
+                "schema_violation",
+            ));
+        }
+        db.set_data(&table, &key, &value)
+            .await
+            .map_err(|e| database_error(e, "Failed to set data"))?;
+        ctx.data_unchecked::<Arc<Webhooks>>()
+            .notify(
+                &db,
+                WebhookEvent::new("set", &table, Some(&key), Some(&value)),
+            )
+            .await;
+        Ok(Entry { table, key, value })
+    }
+
+    /// Deletes a key.
+    async fn delete_data(&self, ctx: &Context<'_>, table: String, key: String) -> Result<bool> {
+        let db = ctx.data_unchecked::<Arc<Mutex<Database>>>().lock().await;
+        Self::check_write(ctx, &db, &table, &key).await?;
+        db.delete_data(&table, &key)
+            .await
+            .map_err(|e| database_error(e, "Failed to delete data"))?;
+        ctx.data_unchecked::<Arc<Webhooks>>()
+            .notify(&db, WebhookEvent::new("delete", &table, Some(&key), None))
+            .await;
+        Ok(true)
+    }
+
+    /// Creates a project.
+    ///
+    /// Returns `false` if a project with the name already exists.
+    async fn create_project(&self, ctx: &Context<'_>, name: String) -> Result<bool> {
+        let db = ctx.data_unchecked::<Arc<Mutex<Database>>>().lock().await;
+        db.create_project(&name)
+            .await
+            .map_err(|e| database_error(e, "Failed to create project"))
+    }
+
+    /// Adds a table to a project.
+    ///
+    /// Returns `false` if the project does not exist.
+    async fn add_project_table(
+        &self,
+        ctx: &Context<'_>,
+        project: String,
+        table: String,
+    ) -> Result<bool> {
+        let db = ctx.data_unchecked::<Arc<Mutex<Database>>>().lock().await;
+        db.add_project_table(&project, &table)
+            .await
+            .map_err(|e| database_error(e, "Failed to add project table"))
+    }
+}
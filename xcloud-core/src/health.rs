@@ -0,0 +1,176 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use serde::{Deserialize, Serialize};
+
+use crate::breaker::CircuitBreaker;
+use crate::config::HealthConfig;
+use crate::db::Database;
+use crate::maintenance::{Maintenance, MaintenanceMode};
+
+/// The paths of the probes, served regardless of the maintenance mode and the circuit breaker.
+pub const PROBE_PATHS: [&str; 3] = ["/startupz", "/livez", "/readyz"];
+
+/// A struct representing the outcome of a single check of a probe.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ProbeCheck {
+    pub name: String,
+    pub passed: bool,
+    /// Why the check failed.
+    pub detail: Option<String>,
+}
+
+/// A struct representing the outcome of a probe.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ProbeResult {
+    /// Whether every check passed.
+    pub passed: bool,
+    pub checks: Vec<ProbeCheck>,
+}
+
+/// Implementation of the `ProbeResult` struct.
+impl ProbeResult {
+    /// Builds the result of the given checks.
+    fn new(checks: Vec<ProbeCheck>) -> Self {
+        ProbeResult {
+            passed: checks.iter().all(|check| check.passed),
+            checks,
+        }
+    }
+}
+
+/// Implementation of the `ProbeCheck` struct.
+impl ProbeCheck {
+    /// Builds a check from its outcome.
+    fn new(name: &str, outcome: Result<(), String>) -> Self {
+        ProbeCheck {
+            name: name.to_string(),
+            passed: outcome.is_ok(),
+            detail: outcome.err(),
+        }
+    }
+}
+
+/// A struct answering the startup, liveness and readiness probes of orchestrators.
+///
+/// The startup probe passes once the database has been reached and keeps passing from then
+/// on. The liveness probe only fails when a restart could help, so by default it does not
+/// depend on the database. The readiness probe fails while requests would be rejected: when
+/// the database is unreachable, the circuit breaker is open or the server is disabled for
+/// maintenance, and optionally while it is read-only.
+pub struct Health {
+    config: HealthConfig,
+    started: AtomicBool,
+}
+
+/// Implementation of the `Health` struct.
+impl Health {
+    /// Creates a new instance of the Health probes.
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - The health configuration deciding which checks the probes run.
+    ///
+    /// # Returns
+    ///
+    /// * `Health` - A new instance of the Health probes, not started yet.
+    pub fn new(config: HealthConfig) -> Self {
+        Health {
+            config,
+            started: AtomicBool::new(false),
+        }
+    }
+
+    /// Checks whether the given path is a probe.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path of the request.
+    ///
+    /// # Returns
+    ///
+    /// * `bool` - Whether the path is one of [`PROBE_PATHS`].
+    pub fn is_probe(path: &str) -> bool {
+        PROBE_PATHS.contains(&path)
+    }
+
+    /// Answers the startup probe.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - The database to reach.
+    ///
+    /// # Returns
+    ///
+    /// * `ProbeResult` - Whether the server has started.
+    pub async fn startup(&self, db: &Database) -> ProbeResult {
+        if self.started.load(Ordering::Relaxed) {
+            return ProbeResult::new(vec![ProbeCheck::new("database", Ok(()))]);
+        }
+        let check = Self::database(db).await;
+        if check.passed {
+            self.started.store(true, Ordering::Relaxed);
+        }
+        ProbeResult::new(vec![check])
+    }
+
+    /// Answers the liveness probe.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - The database to reach, if the probe is configured to check it.
+    ///
+    /// # Returns
+    ///
+    /// * `ProbeResult` - Whether the server is alive.
+    pub async fn liveness(&self, db: &Database) -> ProbeResult {
+        let mut checks = vec![ProbeCheck::new("process", Ok(()))];
+        if self.config.liveness_checks_database {
+            checks.push(Self::database(db).await);
+        }
+        ProbeResult::new(checks)
+    }
+
+    /// Answers the readiness probe.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - The database to reach.
+    /// * `breaker` - The circuit breaker of the database.
+    /// * `maintenance` - The maintenance state of the server.
+    ///
+    /// # Returns
+    ///
+    /// * `ProbeResult` - Whether the server is ready to serve requests.
+    pub async fn readiness(
+        &self,
+        db: &Database,
+        breaker: &CircuitBreaker,
+        maintenance: &Maintenance,
+    ) -> ProbeResult {
+        ProbeResult::new(vec![
+            Self::database(db).await,
+            ProbeCheck::new(
+                "circuit_breaker",
+                match breaker.open_for() {
+                    Some(secs) => Err(format!("open for {} more second(s)", secs)),
+                    None => Ok(()),
+                },
+            ),
+            ProbeCheck::new(
+                "maintenance",
+                match maintenance.state().mode {
+                    MaintenanceMode::ReadOnly if self.config.unready_when_read_only => {
+                        Err("read-only".to_string())
+                    }
+                    MaintenanceMode::Disabled => Err("disabled".to_string()),
+                    _ => Ok(()),
+                },
+            ),
+        ])
+    }
+
+    /// Checks that every shard of the database is reachable.
+    async fn database(db: &Database) -> ProbeCheck {
+        ProbeCheck::new("database", db.ping().await.map_err(|e| e.to_string()))
+    }
+}
@@ -0,0 +1,96 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::sync::Mutex;
+
+use crate::cluster::Cluster;
+use crate::config::HistoryConfig;
+use crate::db::Database;
+
+/// A struct periodically removing versions older than the configured retention and
+/// compacting the versions of high-churn tables beyond their compaction age.
+pub struct HistorySweeper {
+    db: Arc<Mutex<Database>>,
+    config: HistoryConfig,
+}
+
+/// Implementation of the `HistorySweeper` struct.
+impl HistorySweeper {
+    /// Creates a new instance of the HistorySweeper.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - A reference to the database wrapped in an Arc and Mutex for thread safety.
+    /// * `config` - The history configuration holding the retention and the compaction ages.
+    ///
+    /// # Returns
+    ///
+    /// * `HistorySweeper` - A new instance of the HistorySweeper.
+    pub fn new(db: Arc<Mutex<Database>>, config: HistoryConfig) -> Self {
+        HistorySweeper { db, config }
+    }
+
+    /// Sweeps the history in the background at the configured interval, while the instance
+    /// leads the cluster.
+    ///
+    /// # Arguments
+    ///
+    /// * `cluster` - The cluster electing the instance running the background jobs.
+    pub fn spawn(self, cluster: Arc<Cluster>) {
+        if self.config.retention_secs.is_none() && !self.config.compacts() {
+            return;
+        }
+        tokio::spawn(async move {
+            let mut interval =
+                tokio::time::interval(Duration::from_secs(self.config.sweep_interval_secs.max(1)));
+            loop {
+                interval.tick().await;
+                if !cluster.is_leader() {
+                    continue;
+                }
+                let db = self.db.lock().await;
+                if let Some(retention_secs) = self.config.retention_secs {
+                    match db.prune_history(retention_secs as i64).await {
+                        Ok(0) => {}
+                        Ok(count) => log::info!("Pruned {} expired version(s)", count),
+                        Err(e) => log::error!("Failed to prune history: {}", e),
+                    }
+                }
+                if let Err(e) = self.compact(&db).await {
+                    log::error!("Failed to compact history: {}", e);
+                }
+            }
+        });
+    }
+
+    /// Compacts the history of every table with a compaction age.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - The database holding the history.
+    ///
+    /// # Returns
+    ///
+    /// * `u64` - The number of removed versions.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if a table cannot be compacted.
+    pub async fn compact(&self, db: &Database) -> Result<u64, sqlx::Error> {
+        if !self.config.compacts() {
+            return Ok(0);
+        }
+        let mut removed = 0;
+        for table in db.history_tables().await? {
+            let Some(max_age_secs) = self.config.compact_after(&table) else {
+                continue;
+            };
+            let count = db.compact_history(&table, max_age_secs as i64).await?;
+            if count > 0 {
+                log::info!("Compacted {} version(s) of {}", count, table);
+            }
+            removed += count;
+        }
+        Ok(removed)
+    }
+}
@@ -0,0 +1,277 @@
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::LazyLock;
+
+use fluent::concurrent::FluentBundle;
+use fluent::{FluentArgs, FluentResource};
+use fluent_syntax::ast::{Entry, Expression, InlineExpression, PatternElement};
+use rust_embed::RustEmbed;
+use unic_langid::LanguageIdentifier;
+
+/// The language of the catalog defining the error codes, used when a request asks for none.
+pub const DEFAULT_LANGUAGE: &str = "en";
+
+/// The message catalogs, one Fluent file per language named by its language tag, e.g. `de.ftl`.
+#[derive(RustEmbed)]
+#[folder = "locales/"]
+struct Locales;
+
+tokio::task_local! {
+    /// The language the error messages of the current request are built in.
+    static LANGUAGE: String;
+}
+
+/// A struct holding the catalogs of the error messages, keyed by stable error codes.
+///
+/// The catalogs are Fluent files bundled into the binary. The catalog of the default language
+/// defines the codes, and the catalogs of the other languages translate them, using the same
+/// variables. Error responses are built from their code in the language of the request they
+/// answer, falling back to the default language for codes without a translation.
+#[derive(Default)]
+pub struct Catalog {
+    /// The bundles of the messages by language.
+    bundles: HashMap<String, FluentBundle<FluentResource>>,
+}
+
+/// Implementation of the `Catalog` struct.
+impl Catalog {
+    /// Returns the catalogs bundled into the binary, loaded on first use.
+    ///
+    /// # Returns
+    ///
+    /// * `&Catalog` - The bundled catalogs, or an empty catalog if one of them is invalid.
+    pub fn bundled() -> &'static Self {
+        static BUNDLED: LazyLock<Catalog> = LazyLock::new(|| {
+            let sources: Vec<(String, String)> = Locales::iter()
+                .filter_map(|file| {
+                    let language = file.strip_suffix(".ftl")?.to_string();
+                    let source = Locales::get(&file)?;
+                    Some((language, String::from_utf8_lossy(&source.data).into_owned()))
+                })
+                .collect();
+            Catalog::from_sources(
+                sources
+                    .iter()
+                    .map(|(language, source)| (language.as_str(), source.as_str())),
+            )
+            .unwrap_or_else(|e| {
+                log::error!("Failed to load the message catalogs: {}", e);
+                Catalog::default()
+            })
+        });
+        &BUNDLED
+    }
+
+    /// Loads catalogs from their sources.
+    ///
+    /// # Arguments
+    ///
+    /// * `sources` - The language tag and the Fluent source of every catalog, including the
+    ///   catalog of the default language.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Catalog, String>` - The catalogs, or the reason they are invalid.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if a source cannot be parsed, the default language
+    /// has no catalog, or a translation has no code in it or uses other variables.
+    pub fn from_sources<'a>(
+        sources: impl IntoIterator<Item = (&'a str, &'a str)>,
+    ) -> Result<Self, String> {
+        let mut resources = Vec::new();
+        for (language, source) in sources {
+            let resource = FluentResource::try_new(source.to_string()).map_err(|(_, errors)| {
+                let reason = errors.first().map(ToString::to_string).unwrap_or_default();
+                format!("{}: {}", language, reason)
+            })?;
+            resources.push((language.to_ascii_lowercase(), resource));
+        }
+        let Some(codes) = resources
+            .iter()
+            .find(|(language, _)| language == DEFAULT_LANGUAGE)
+            .map(|(_, resource)| Self::variables(resource))
+        else {
+            return Err(format!("{}: the catalog is missing", DEFAULT_LANGUAGE));
+        };
+        let mut catalog = Catalog::default();
+        for (language, resource) in resources {
+            for (code, variables) in Self::variables(&resource) {
+                match codes.get(&code) {
+                    None => return Err(format!("{}: unknown code {}", language, code)),
+                    Some(original) if *original != variables => {
+                        return Err(format!("{}: {} uses other variables", language, code));
+                    }
+                    Some(_) => {}
+                }
+            }
+            let tag: LanguageIdentifier = language
+                .parse()
+                .map_err(|e| format!("{}: {}", language, e))?;
+            let mut bundle = FluentBundle::new_concurrent(vec![tag]);
+            bundle.set_use_isolating(false);
+            bundle
+                .add_resource(resource)
+                .map_err(|e| format!("{}: {:?}", language, e))?;
+            catalog.bundles.insert(language, bundle);
+        }
+        Ok(catalog)
+    }
+
+    /// Returns the languages with a catalog.
+    ///
+    /// # Returns
+    ///
+    /// * `Vec<&str>` - The default language followed by the translated languages, sorted.
+    pub fn languages(&self) -> Vec<&str> {
+        let mut languages: Vec<&str> = self
+            .bundles
+            .keys()
+            .map(String::as_str)
+            .filter(|language| *language != DEFAULT_LANGUAGE)
+            .collect();
+        languages.sort_unstable();
+        languages.insert(0, DEFAULT_LANGUAGE);
+        languages
+    }
+
+    /// Chooses the language of a response from the `Accept-Language` header of its request.
+    ///
+    /// # Arguments
+    ///
+    /// * `accept_language` - The value of the header, e.g. `de-CH, de;q=0.9, en;q=0.5`.
+    ///
+    /// # Returns
+    ///
+    /// * `&str` - The preferred language with a catalog, matched by its primary subtag if the
+    ///   full tag has none, or the default language.
+    pub fn negotiate(&self, accept_language: &str) -> &str {
+        let mut ranges: Vec<(f32, &str)> = accept_language
+            .split(',')
+            .filter_map(|range| {
+                let mut parts = range.split(';');
+                let tag = parts.next()?.trim();
+                let quality = parts
+                    .find_map(|param| param.trim().strip_prefix("q="))
+                    .map_or(Some(1.0), |q| q.trim().parse::<f32>().ok())?;
+                (!tag.is_empty() && quality > 0.0).then_some((quality, tag))
+            })
+            .collect();
+        ranges.sort_by(|a, b| b.0.total_cmp(&a.0));
+        for (_, tag) in ranges {
+            let tag = tag.to_ascii_lowercase();
+            let primary = tag.split('-').next().unwrap_or_default();
+            if tag == "*" || primary == DEFAULT_LANGUAGE {
+                return DEFAULT_LANGUAGE;
+            }
+            if let Some((language, _)) = self
+                .bundles
+                .get_key_value(&tag)
+                .or_else(|| self.bundles.get_key_value(primary))
+            {
+                return language;
+            }
+        }
+        DEFAULT_LANGUAGE
+    }
+
+    /// Translates the message of an error code to a language.
+    ///
+    /// # Arguments
+    ///
+    /// * `language` - The language to translate the message to.
+    /// * `code` - The code of the error.
+    /// * `args` - The values of the variables of the message.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<String>` - The message, or `None` if the catalog of the language has no
+    ///   message for the code or it cannot be formatted.
+    pub fn translate(&self, language: &str, code: &str, args: &[(&str, &str)]) -> Option<String> {
+        let bundle = self.bundles.get(language)?;
+        let pattern = bundle.get_message(code)?.value()?;
+        let mut values = FluentArgs::new();
+        for (name, value) in args {
+            values.set(*name, *value);
+        }
+        let mut errors = Vec::new();
+        let message = bundle.format_pattern(pattern, Some(&values), &mut errors);
+        errors.is_empty().then(|| message.into_owned())
+    }
+
+    /// Builds the message of an error code in the language of the current request.
+    ///
+    /// # Arguments
+    ///
+    /// * `code` - The code of the error.
+    /// * `args` - The values of the variables of the message.
+    ///
+    /// # Returns
+    ///
+    /// * `String` - The message in the language of the request, or in the default language if
+    ///   it has no translation, or the code itself if the catalogs have no message for it.
+    pub fn message(&self, code: &str, args: &[(&str, &str)]) -> String {
+        LANGUAGE
+            .try_with(|language| self.translate(language, code, args))
+            .ok()
+            .flatten()
+            .or_else(|| self.translate(DEFAULT_LANGUAGE, code, args))
+            .unwrap_or_else(|| code.to_string())
+    }
+
+    /// Runs a function building error responses in a language.
+    ///
+    /// # Arguments
+    ///
+    /// * `language` - The language of the error messages.
+    /// * `f` - The function to run.
+    ///
+    /// # Returns
+    ///
+    /// * `R` - The result of the function.
+    pub fn with_language<R>(language: &str, f: impl FnOnce() -> R) -> R {
+        LANGUAGE.sync_scope(language.to_string(), f)
+    }
+
+    /// Runs a future building error responses in a language.
+    ///
+    /// # Arguments
+    ///
+    /// * `language` - The language of the error messages.
+    /// * `future` - The future to run.
+    ///
+    /// # Returns
+    ///
+    /// * `F::Output` - The output of the future.
+    pub async fn in_language<F: Future>(language: String, future: F) -> F::Output {
+        LANGUAGE.scope(language, future).await
+    }
+
+    /// Returns the sorted names of the variables of every message of a resource.
+    fn variables(resource: &FluentResource) -> HashMap<String, Vec<String>> {
+        resource
+            .entries()
+            .filter_map(|entry| match entry {
+                Entry::Message(message) => Some(message),
+                _ => None,
+            })
+            .map(|message| {
+                let mut names: Vec<String> = message
+                    .value
+                    .iter()
+                    .flat_map(|pattern| &pattern.elements)
+                    .filter_map(|element| match element {
+                        PatternElement::Placeable {
+                            expression:
+                                Expression::Inline(InlineExpression::VariableReference { id }),
+                        } => Some(id.name.to_string()),
+                        _ => None,
+                    })
+                    .collect();
+                names.sort_unstable();
+                (message.id.name.to_string(), names)
+            })
+            .collect()
+    }
+}
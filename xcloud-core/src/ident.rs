@@ -0,0 +1,130 @@
+use std::collections::HashSet;
+use std::sync::{PoisonError, RwLock};
+
+use crate::utils::Utils;
+
+/// The prefixes of the tables managed by the database itself, which are never user tables.
+const RESERVED_PREFIXES: [&str; 2] = ["sqlite_", "xcloud_"];
+
+/// A struct quoting the identifiers interpolated into SQL statements.
+///
+/// Table names cannot be bound as parameters, so every statement naming a table builds the
+/// name through this struct: the names of new user tables are checked with
+/// [`Ident::table_name`], existing user tables are resolved through a [`TableRegistry`], and
+/// the internal tables derived from them are named with [`Ident::derived`].
+pub struct Ident;
+
+/// Implementation of the `Ident` struct.
+impl Ident {
+    /// Quotes an identifier, doubling any quote it contains.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The identifier to quote.
+    ///
+    /// # Returns
+    ///
+    /// * `String` - The quoted identifier.
+    pub fn quote(name: &str) -> String {
+        format!("\"{}\"", name.replace('"', "\"\""))
+    }
+
+    /// Checks the name of a user table.
+    ///
+    /// # Arguments
+    ///
+    /// * `table` - The name of the table as requested.
+    ///
+    /// # Returns
+    ///
+    /// * `String` - The sanitized name of the table.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the name is empty once sanitized or is reserved
+    /// for the internal tables.
+    pub fn table_name(table: &str) -> Result<String, sqlx::Error> {
+        let name = Utils::sanitize(table);
+        let lowercase = name.to_ascii_lowercase();
+        if name.is_empty()
+            || RESERVED_PREFIXES
+                .iter()
+                .any(|prefix| lowercase.starts_with(prefix))
+        {
+            return Err(sqlx::Error::InvalidArgument(format!(
+                "invalid table name: {}",
+                table
+            )));
+        }
+        Ok(name)
+    }
+
+    /// Quotes the name of an internal table, index or trigger derived from a user table.
+    ///
+    /// # Arguments
+    ///
+    /// * `prefix` - The prefix of the internal object, e.g. `xcloud_fts_`.
+    /// * `name` - The name of the user table, with any suffix.
+    ///
+    /// # Returns
+    ///
+    /// * `String` - The quoted name of the internal object.
+    pub fn derived(prefix: &str, name: &str) -> String {
+        Self::quote(&format!("{}{}", prefix, Utils::sanitize(name)))
+    }
+}
+
+/// A struct registering the user tables managed by the database.
+///
+/// Tables enter the registry when they are created, or when they are found on the shards, e.g.
+/// as the database is opened or after another instance sharing it created them, and leave it
+/// when they are deleted. Statements on existing tables
+/// resolve their names through the registry, so a table that was never created is refused
+/// before its name reaches SQL.
+#[derive(Default)]
+pub struct TableRegistry {
+    tables: RwLock<HashSet<String>>,
+}
+
+/// Implementation of the `TableRegistry` struct.
+impl TableRegistry {
+    /// Registers tables that exist.
+    ///
+    /// # Arguments
+    ///
+    /// * `tables` - The sanitized names of the tables.
+    pub fn register(&self, tables: impl IntoIterator<Item = String>) {
+        self.tables
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .extend(tables);
+    }
+
+    /// Removes a deleted table from the registry.
+    ///
+    /// # Arguments
+    ///
+    /// * `table` - The sanitized name of the table.
+    pub fn unregister(&self, table: &str) {
+        self.tables
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .remove(table);
+    }
+
+    /// Returns whether a table is registered.
+    ///
+    /// # Arguments
+    ///
+    /// * `table` - The sanitized name of the table.
+    ///
+    /// # Returns
+    ///
+    /// * `bool` - Whether the table exists.
+    pub fn contains(&self, table: &str) -> bool {
+        self.tables
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .contains(table)
+    }
+}
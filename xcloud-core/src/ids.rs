@@ -0,0 +1,101 @@
+use std::sync::{Mutex, PoisonError};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::config::{IdConfig, IdFormat};
+
+/// The unix timestamp in milliseconds the snowflake timestamps count from, 2024-01-01.
+const SNOWFLAKE_EPOCH_MS: u64 = 1_704_067_200_000;
+
+/// The number of bits of the per-millisecond sequence of a snowflake id.
+const SNOWFLAKE_SEQUENCE_BITS: u32 = 12;
+
+/// The number of bits of the node id of a snowflake id.
+const SNOWFLAKE_NODE_BITS: u32 = 10;
+
+/// A struct representing the last snowflake id generated.
+#[derive(Default)]
+struct SnowflakeState {
+    /// The millisecond of the last id, relative to the snowflake epoch.
+    millis: u64,
+    sequence: u64,
+}
+
+/// A struct generating the keys of the values appended without a key.
+///
+/// Every format sorts by creation time when the keys are compared as strings, so the keys of
+/// an append-only table list in insertion order. Keys generated by one process are strictly
+/// increasing.
+pub struct KeyGenerator {
+    config: IdConfig,
+    ulid: Mutex<ulid::Generator>,
+    snowflake: Mutex<SnowflakeState>,
+}
+
+/// Implementation of the `KeyGenerator` struct.
+impl KeyGenerator {
+    /// Creates a new instance of the KeyGenerator.
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - The id configuration holding the format and the node id of snowflake ids.
+    ///
+    /// # Returns
+    ///
+    /// * `KeyGenerator` - A new instance of the KeyGenerator.
+    pub fn new(config: IdConfig) -> Self {
+        KeyGenerator {
+            config,
+            ulid: Mutex::new(ulid::Generator::new()),
+            snowflake: Mutex::new(SnowflakeState::default()),
+        }
+    }
+
+    /// Generates a new key in the configured format.
+    ///
+    /// # Returns
+    ///
+    /// * `String` - The key.
+    pub fn generate(&self) -> String {
+        match self.config.format {
+            IdFormat::UuidV7 => uuid::Uuid::now_v7().to_string(),
+            IdFormat::Ulid => {
+                let mut generator = self.ulid.lock().unwrap_or_else(PoisonError::into_inner);
+                generator
+                    .generate()
+                    .unwrap_or_else(|_| ulid::Ulid::new())
+                    .to_string()
+            }
+            IdFormat::Snowflake => format!("{:020}", self.snowflake()),
+        }
+    }
+
+    /// Generates a snowflake id, made of the milliseconds since the snowflake epoch, the node
+    /// id and a sequence distinguishing the ids of the same millisecond.
+    ///
+    /// When the sequence of a millisecond is exhausted, or the clock goes backwards, the ids
+    /// continue from the following millisecond rather than waiting for the clock.
+    fn snowflake(&self) -> u64 {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_millis() as u64)
+            .saturating_sub(SNOWFLAKE_EPOCH_MS);
+        let mut state = self
+            .snowflake
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        if now > state.millis {
+            state.millis = now;
+            state.sequence = 0;
+        } else {
+            state.sequence += 1;
+            if state.sequence >> SNOWFLAKE_SEQUENCE_BITS > 0 {
+                state.millis += 1;
+                state.sequence = 0;
+            }
+        }
+        let node = u64::from(self.config.node_id) & ((1 << SNOWFLAKE_NODE_BITS) - 1);
+        (state.millis << (SNOWFLAKE_NODE_BITS + SNOWFLAKE_SEQUENCE_BITS))
+            | (node << SNOWFLAKE_SEQUENCE_BITS)
+            | state.sequence
+    }
+}
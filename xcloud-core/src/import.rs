@@ -0,0 +1,563 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, PoisonError, RwLock};
+use std::time::Instant;
+
+use async_trait::async_trait;
+use csv_async::{AsyncReaderBuilder, StringRecord};
+use futures::StreamExt;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use tokio::io::AsyncBufReadExt;
+use tokio::sync::Mutex;
+
+use crate::concurrency::ConcurrencyLimits;
+use crate::db::{Database, DRY_RUN_SAMPLE};
+use crate::jobs::Job;
+use crate::schema::{SchemaViolation, ValueType, ValueValidator};
+
+/// The number of rows written per transaction.
+pub const BATCH_SIZE: usize = 1000;
+
+/// The maximum number of rejected lines reported by the progress of an import.
+const MAX_REJECTED: usize = 100;
+
+/// An enum representing the state of an import.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportState {
+    Running,
+    Completed,
+    Failed,
+}
+
+/// A struct representing a line rejected by an import.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct RejectedLine {
+    pub line: u64,
+    pub reason: String,
+}
+
+/// A struct representing the progress of an import.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ImportProgress {
+    pub id: u64,
+    pub table: String,
+    pub state: ImportState,
+    /// Whether the rows are only validated, not written.
+    pub dry_run: bool,
+    /// The number of rows written, or that would be written by a dry run.
+    pub rows: u64,
+    /// The first keys written.
+    pub sample: Vec<String>,
+    pub errors: u64,
+    /// The first rejected lines, capped at a hundred.
+    pub rejected: Vec<RejectedLine>,
+    /// The estimated number of seconds until the import completes.
+    pub eta_secs: Option<u64>,
+    /// The reason the import failed.
+    pub error: Option<String>,
+}
+
+/// A struct representing a row of an NDJSON import, in the format of `/stream_table`.
+#[derive(Deserialize)]
+struct ImportedRow {
+    key: String,
+    value: Value,
+}
+
+/// An enum representing the reason a CSV column mapping is invalid.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum MappingError {
+    /// The column holding the keys is not named.
+    MissingKey,
+    /// The delimiter cannot separate cells, e.g. a quote.
+    InvalidDelimiter(char),
+}
+
+/// A struct representing how the columns of a CSV import map to keys and values.
+#[derive(Clone, Debug)]
+pub struct CsvMapping {
+    /// The column holding the keys.
+    pub key: String,
+    /// The columns composing the JSON object values and the fields they are stored under, all
+    /// columns but the key under their own names if empty.
+    pub fields: Vec<(String, String)>,
+    pub delimiter: u8,
+    /// Whether cells holding JSON numbers or booleans are stored as such and empty cells as
+    /// `null`, instead of storing every cell as a string.
+    pub typed: bool,
+}
+
+/// Implementation of the `CsvMapping` struct.
+impl CsvMapping {
+    /// Parses a column mapping.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The column holding the keys.
+    /// * `columns` - The comma-separated value columns, each optionally renamed as
+    ///   `column:field`, or `None` for all columns but the key.
+    /// * `delimiter` - The delimiter of the cells, a comma if `None`.
+    /// * `typed` - Whether cells are stored as JSON numbers, booleans and `null` when possible.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<CsvMapping, MappingError>` - The mapping, or the reason it is invalid.
+    pub fn new(
+        key: &str,
+        columns: Option<&str>,
+        delimiter: Option<char>,
+        typed: bool,
+    ) -> Result<Self, MappingError> {
+        let key = key.trim();
+        if key.is_empty() {
+            return Err(MappingError::MissingKey);
+        }
+        let delimiter = match delimiter.unwrap_or(',') {
+            c if c.is_ascii() && c != '"' && c != '\n' => c as u8,
+            c => return Err(MappingError::InvalidDelimiter(c)),
+        };
+        let fields = columns
+            .unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .filter(|column| !column.is_empty())
+            .map(|column| match column.split_once(':') {
+                Some((column, field)) => (column.trim().to_string(), field.trim().to_string()),
+                None => (column.to_string(), column.to_string()),
+            })
+            .collect();
+        Ok(CsvMapping {
+            key: key.to_string(),
+            fields,
+            delimiter,
+            typed,
+        })
+    }
+
+    /// Locates the mapped columns in the header of a CSV file.
+    ///
+    /// # Arguments
+    ///
+    /// * `headers` - The header record.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(usize, Vec<(usize, String)>), String>` - The index of the key column and the
+    ///   indices of the value columns with their fields, or the reason a column is missing.
+    fn resolve(&self, headers: &StringRecord) -> Result<(usize, Vec<(usize, String)>), String> {
+        let index = |column: &str| {
+            headers
+                .iter()
+                .position(|header| header.trim() == column)
+                .ok_or_else(|| format!("Missing column: {}", column))
+        };
+        let key = index(&self.key)?;
+        let fields = if self.fields.is_empty() {
+            headers
+                .iter()
+                .enumerate()
+                .filter(|(i, _)| *i != key)
+                .map(|(i, header)| (i, header.trim().to_string()))
+                .collect()
+        } else {
+            self.fields
+                .iter()
+                .map(|(column, field)| Ok((index(column)?, field.clone())))
+                .collect::<Result<_, String>>()?
+        };
+        Ok((key, fields))
+    }
+
+    /// Creates a reader of a spooled CSV file.
+    async fn reader(
+        &self,
+        path: &std::path::Path,
+    ) -> Result<csv_async::AsyncReader<tokio::fs::File>, String> {
+        let file = tokio::fs::File::open(path)
+            .await
+            .map_err(|e| e.to_string())?;
+        Ok(AsyncReaderBuilder::new()
+            .delimiter(self.delimiter)
+            .flexible(true)
+            .create_reader(file))
+    }
+
+    /// Checks that the header of a spooled CSV file holds the mapped columns.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The spooled file.
+    ///
+    /// # Errors
+    ///
+    /// This function will return the reason the header does not match the mapping.
+    pub async fn check(&self, path: &std::path::Path) -> Result<(), String> {
+        let mut reader = self.reader(path).await?;
+        let headers = reader.headers().await.map_err(|e| e.to_string())?;
+        self.resolve(headers).map(|_| ())
+    }
+
+    /// Converts a cell into a JSON value.
+    ///
+    /// # Arguments
+    ///
+    /// * `cell` - The text of the cell.
+    ///
+    /// # Returns
+    ///
+    /// * `Value` - The typed value, or the text if the mapping is untyped.
+    pub fn cell(&self, cell: &str) -> Value {
+        if !self.typed {
+            return Value::String(cell.to_string());
+        }
+        match cell.trim() {
+            "" => Value::Null,
+            "true" => Value::Bool(true),
+            "false" => Value::Bool(false),
+            trimmed => serde_json::from_str::<serde_json::Number>(trimmed)
+                .map(Value::Number)
+                .unwrap_or_else(|_| Value::String(cell.to_string())),
+        }
+    }
+}
+
+/// A struct keeping track of the progress of the imports.
+#[derive(Clone, Default)]
+pub struct Imports {
+    next_id: Arc<AtomicU64>,
+    progress: Arc<RwLock<HashMap<u64, ImportProgress>>>,
+}
+
+/// Implementation of the `Imports` struct.
+impl Imports {
+    /// Registers a new running import.
+    ///
+    /// # Arguments
+    ///
+    /// * `table` - The table the rows are imported into.
+    /// * `dry_run` - Whether the rows are only validated, not written.
+    ///
+    /// # Returns
+    ///
+    /// * `ImportProgress` - The progress of the new import.
+    pub fn start(&self, table: &str, dry_run: bool) -> ImportProgress {
+        let progress = ImportProgress {
+            id: self.next_id.fetch_add(1, Ordering::Relaxed) + 1,
+            table: table.to_string(),
+            state: ImportState::Running,
+            dry_run,
+            rows: 0,
+            sample: Vec::new(),
+            errors: 0,
+            rejected: Vec::new(),
+            eta_secs: None,
+            error: None,
+        };
+        self.progress
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert(progress.id, progress.clone());
+        progress
+    }
+
+    /// Returns the progress of an import.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The id of the import.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<ImportProgress>` - The progress, or `None` if the import is unknown.
+    pub fn get(&self, id: u64) -> Option<ImportProgress> {
+        self.progress
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(&id)
+            .cloned()
+    }
+
+    /// Applies a change to the progress of an import.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The id of the import.
+    /// * `change` - The change to apply.
+    pub fn update(&self, id: u64, change: impl FnOnce(&mut ImportProgress)) {
+        if let Some(progress) = self
+            .progress
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get_mut(&id)
+        {
+            change(progress);
+        }
+    }
+
+    /// Marks an import as failed.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The id of the import.
+    /// * `error` - The reason the import failed.
+    pub fn fail(&self, id: u64, error: &str) {
+        self.update(id, |progress| {
+            progress.state = ImportState::Failed;
+            progress.eta_secs = None;
+            progress.error = Some(error.to_string());
+        });
+    }
+}
+
+/// A job importing an NDJSON or CSV file into a table in batched transactions.
+pub struct ImportJob {
+    pub db: Arc<Mutex<Database>>,
+    /// The limits whose interactive lane every batch yields to before it is written.
+    pub limits: Arc<ConcurrencyLimits>,
+    pub imports: Imports,
+    pub id: u64,
+    pub table: String,
+    /// The validator of the value type and JSON Schema of the table.
+    pub validator: ValueValidator,
+    /// Whether the rows are only validated, not written.
+    pub dry_run: bool,
+    /// The spooled, decompressed upload, removed once the import completes.
+    pub path: PathBuf,
+    /// The column mapping if the upload is a CSV file, otherwise it is NDJSON.
+    pub csv: Option<CsvMapping>,
+}
+
+/// Implementation of the `ImportJob` struct.
+impl ImportJob {
+    /// Parses a line into a key-value pair, validated against the schema of the table.
+    ///
+    /// # Arguments
+    ///
+    /// * `line` - The line to parse.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(String, String), String>` - The key-value pair, or the reason it was rejected.
+    fn parse(&self, line: &str) -> Result<(String, String), String> {
+        let row: ImportedRow = serde_json::from_str(line).map_err(|e| e.to_string())?;
+        let value = match row.value {
+            Value::String(value) if self.validator.value_type() != ValueType::Json => value,
+            value => value.to_string(),
+        };
+        self.validator
+            .validate(&value)
+            .map_err(|violations| SchemaViolation::describe(&violations))?;
+        Ok((row.key, value))
+    }
+
+    /// Converts a CSV record into a key-value pair, validated against the schema of the table.
+    ///
+    /// # Arguments
+    ///
+    /// * `mapping` - The column mapping.
+    /// * `key` - The index of the key column.
+    /// * `fields` - The indices of the value columns and their fields.
+    /// * `record` - The record to convert.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(String, String), String>` - The key-value pair, or the reason it was rejected.
+    fn parse_record(
+        &self,
+        mapping: &CsvMapping,
+        key: usize,
+        fields: &[(usize, String)],
+        record: &StringRecord,
+    ) -> Result<(String, String), String> {
+        let key = record.get(key).map(str::trim).unwrap_or_default();
+        if key.is_empty() {
+            return Err("Empty key".to_string());
+        }
+        let value: Map<String, Value> = fields
+            .iter()
+            .map(|(i, field)| {
+                (
+                    field.clone(),
+                    mapping.cell(record.get(*i).unwrap_or_default()),
+                )
+            })
+            .collect();
+        let value = Value::Object(value).to_string();
+        self.validator
+            .validate(&value)
+            .map_err(|violations| SchemaViolation::describe(&violations))?;
+        Ok((key.to_string(), value))
+    }
+
+    /// Records a rejected line in the progress of the import.
+    ///
+    /// # Arguments
+    ///
+    /// * `line` - The number of the line.
+    /// * `reason` - The reason the line was rejected.
+    fn reject(&self, line: u64, reason: String) {
+        self.imports.update(self.id, |progress| {
+            progress.errors += 1;
+            if progress.rejected.len() < MAX_REJECTED {
+                progress.rejected.push(RejectedLine { line, reason });
+            }
+        });
+    }
+
+    /// Writes a batch of rows and updates the progress of the import.
+    ///
+    /// # Arguments
+    ///
+    /// * `batch` - The rows to write, drained on success.
+    /// * `read` - The number of bytes of the file read so far.
+    /// * `total` - The size of the file in bytes.
+    /// * `started` - The moment the import started.
+    async fn flush(
+        &self,
+        batch: &mut Vec<(String, String)>,
+        read: u64,
+        total: u64,
+        started: Instant,
+    ) -> Result<(), String> {
+        if !batch.is_empty() && !self.dry_run {
+            self.limits.yield_to_interactive().await;
+            self.db
+                .lock()
+                .await
+                .set_many(&self.table, batch)
+                .await
+                .map_err(|e| e.to_string())?;
+        }
+        let written = batch.len() as u64;
+        let sample: Vec<String> = batch
+            .drain(..)
+            .take(DRY_RUN_SAMPLE)
+            .map(|(key, _)| key)
+            .collect();
+        let elapsed = started.elapsed().as_secs_f64();
+        let eta = (read > 0)
+            .then(|| (elapsed * (total.saturating_sub(read)) as f64 / read as f64) as u64);
+        self.imports.update(self.id, |progress| {
+            progress.rows += written;
+            let missing = DRY_RUN_SAMPLE.saturating_sub(progress.sample.len());
+            progress.sample.extend(sample.into_iter().take(missing));
+            progress.eta_secs = eta;
+        });
+        Ok(())
+    }
+
+    /// Imports the spooled file.
+    async fn import(&self) -> Result<(), String> {
+        let file = tokio::fs::File::open(&self.path)
+            .await
+            .map_err(|e| e.to_string())?;
+        let total = file.metadata().await.map_err(|e| e.to_string())?.len();
+        let started = Instant::now();
+        let mut lines = tokio::io::BufReader::new(file).lines();
+        let mut batch = Vec::with_capacity(BATCH_SIZE);
+        let (mut read, mut number) = (0, 0);
+        while let Some(line) = lines.next_line().await.map_err(|e| e.to_string())? {
+            read += line.len() as u64 + 1;
+            number += 1;
+            if line.trim().is_empty() {
+                continue;
+            }
+            match self.parse(&line) {
+                Ok(pair) => batch.push(pair),
+                Err(reason) => self.reject(number, reason),
+            }
+            if batch.len() >= BATCH_SIZE {
+                self.flush(&mut batch, read, total, started).await?;
+            }
+        }
+        self.flush(&mut batch, read, total, started).await
+    }
+
+    /// Imports the spooled CSV file, whose first line names the columns.
+    ///
+    /// # Arguments
+    ///
+    /// * `mapping` - The column mapping.
+    async fn import_csv(&self, mapping: &CsvMapping) -> Result<(), String> {
+        let total = tokio::fs::metadata(&self.path)
+            .await
+            .map_err(|e| e.to_string())?
+            .len();
+        let started = Instant::now();
+        let mut reader = mapping.reader(&self.path).await?;
+        let headers = reader.headers().await.map_err(|e| e.to_string())?.clone();
+        let (key, fields) = mapping.resolve(&headers)?;
+        let mut records = reader.records();
+        let mut batch = Vec::with_capacity(BATCH_SIZE);
+        let mut read = 0;
+        while let Some(record) = records.next().await {
+            let record = match record {
+                Ok(record) => record,
+                Err(e) if e.is_io_error() => return Err(e.to_string()),
+                Err(e) => {
+                    let line = e.position().map(|p| p.line()).unwrap_or_default();
+                    self.reject(line, e.to_string());
+                    continue;
+                }
+            };
+            let (byte, line) = record
+                .position()
+                .map(|p| (p.byte(), p.line()))
+                .unwrap_or_default();
+            read = byte;
+            match self.parse_record(mapping, key, &fields, &record) {
+                Ok(pair) => batch.push(pair),
+                Err(reason) => self.reject(line, reason),
+            }
+            if batch.len() >= BATCH_SIZE {
+                self.flush(&mut batch, read, total, started).await?;
+            }
+        }
+        self.flush(&mut batch, read.max(total), total, started)
+            .await
+    }
+}
+
+#[async_trait]
+impl Job for ImportJob {
+    fn name(&self) -> String {
+        format!("import {} into {}", self.id, self.table)
+    }
+
+    async fn run(&self) -> Result<(), String> {
+        self.imports.update(self.id, |progress| {
+            progress.state = ImportState::Running;
+            progress.rows = 0;
+            progress.sample.clear();
+            progress.errors = 0;
+            progress.rejected.clear();
+            progress.error = None;
+        });
+        let imported = match &self.csv {
+            Some(mapping) => self.import_csv(mapping).await,
+            None => self.import().await,
+        };
+        if let Err(e) = imported {
+            self.imports.fail(self.id, &e);
+            return Err(e);
+        }
+        let _ = tokio::fs::remove_file(&self.path).await;
+        self.imports.update(self.id, |progress| {
+            progress.state = ImportState::Completed;
+            progress.eta_secs = Some(0);
+        });
+        if let Some(progress) = self.imports.get(self.id).filter(|_| !self.dry_run) {
+            tracing::info!(
+                target: "audit",
+                table = %self.table,
+                rows = progress.rows,
+                errors = progress.errors,
+                "Table imported"
+            );
+        }
+        Ok(())
+    }
+}
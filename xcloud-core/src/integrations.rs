@@ -0,0 +1,226 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use serde_json::Value;
+use tokio::sync::Mutex;
+
+use crate::cluster::Cluster;
+use crate::config::IntegrationConfig;
+use crate::db::{Database, Integration};
+use crate::import::BATCH_SIZE;
+use crate::schema::{SchemaViolation, ValueType, ValueValidator};
+
+/// A struct representing the rows extracted from the response of a source.
+#[derive(Debug, Default, PartialEq)]
+pub struct ExtractedRows {
+    pub rows: Vec<(String, String)>,
+    /// The number of items skipped for lacking a key or value or not matching the schema.
+    pub skipped: usize,
+    /// The reason the first item was skipped.
+    pub reason: Option<String>,
+}
+
+/// A struct polling the external HTTP sources of the integrations when they are due and
+/// writing the items of their JSON responses into tables.
+///
+/// The outcome of every poll is recorded with the integration, so failing sources are visible
+/// at `/admin/integrations` instead of in the logs of a cron job.
+pub struct IntegrationScheduler {
+    db: Arc<Mutex<Database>>,
+    client: reqwest::Client,
+    config: IntegrationConfig,
+}
+
+/// Implementation of the `IntegrationScheduler` struct.
+impl IntegrationScheduler {
+    /// Creates a new instance of the IntegrationScheduler.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - A reference to the database wrapped in an Arc and Mutex for thread safety.
+    /// * `config` - The integration configuration holding the tick and the timeout.
+    ///
+    /// # Returns
+    ///
+    /// * `IntegrationScheduler` - A new instance of the IntegrationScheduler.
+    pub fn new(db: Arc<Mutex<Database>>, config: IntegrationConfig) -> Self {
+        IntegrationScheduler {
+            db,
+            client: reqwest::Client::builder()
+                .timeout(Duration::from_secs(config.timeout_secs.max(1)))
+                .build()
+                .unwrap_or_default(),
+            config,
+        }
+    }
+
+    /// Polls the due integrations in the background at the configured tick, while the
+    /// instance leads the cluster.
+    ///
+    /// # Arguments
+    ///
+    /// * `cluster` - The cluster electing the instance running the background jobs.
+    pub fn spawn(self, cluster: Arc<Cluster>) {
+        tokio::spawn(async move {
+            let mut interval =
+                tokio::time::interval(Duration::from_secs(self.config.tick_secs.max(1)));
+            loop {
+                interval.tick().await;
+                if !cluster.is_leader() {
+                    continue;
+                }
+                if let Err(e) = self.run_due().await {
+                    log::error!("Failed to run integrations: {}", e);
+                }
+            }
+        });
+    }
+
+    /// Polls every due integration once and records the outcomes.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the integrations cannot be looked up or an
+    /// outcome cannot be recorded.
+    pub async fn run_due(&self) -> Result<(), sqlx::Error> {
+        let due = self.db.lock().await.integrations(true).await?;
+        for integration in due {
+            let (rows, error) = match self.poll(&integration).await {
+                Ok(extracted) => {
+                    log::info!(
+                        "Integration {} wrote {} row(s) to {}",
+                        integration.name,
+                        extracted.rows.len(),
+                        integration.table
+                    );
+                    let error = extracted
+                        .reason
+                        .map(|reason| format!("Skipped {} item(s): {}", extracted.skipped, reason));
+                    (extracted.rows.len(), error)
+                }
+                Err(e) => {
+                    log::warn!("Integration {} failed: {}", integration.name, e);
+                    (0, Some(e))
+                }
+            };
+            self.db
+                .lock()
+                .await
+                .record_integration_run(&integration.name, rows, error.as_deref())
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// Fetches the source of an integration and writes its items in batched transactions.
+    ///
+    /// # Arguments
+    ///
+    /// * `integration` - The integration to poll.
+    ///
+    /// # Returns
+    ///
+    /// * `ExtractedRows` - The written rows and the skipped items.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the source cannot be fetched, its response has
+    /// no items or the table cannot be written.
+    async fn poll(&self, integration: &Integration) -> Result<ExtractedRows, String> {
+        let mut request = self.client.get(&integration.url);
+        if let Some(authorization) = &integration.authorization {
+            request = request.header(reqwest::header::AUTHORIZATION, authorization);
+        }
+        let response = request.send().await.map_err(|e| e.to_string())?;
+        if !response.status().is_success() {
+            return Err(format!("unexpected status {}", response.status()));
+        }
+        let body: Value = response.json().await.map_err(|e| e.to_string())?;
+        let db = self.db.lock().await;
+        if let Some(hold) = db
+            .legal_hold(&integration.table)
+            .await
+            .map_err(|e| e.to_string())?
+        {
+            return Err(format!("table is under legal hold: {}", hold.reason));
+        }
+        let validator = db
+            .value_validator(&integration.table)
+            .await
+            .map_err(|e| e.to_string())?;
+        let extracted = Self::extract(integration, &body, &validator)?;
+        for batch in extracted.rows.chunks(BATCH_SIZE) {
+            db.set_many(&integration.table, batch)
+                .await
+                .map_err(|e| e.to_string())?;
+        }
+        Ok(extracted)
+    }
+
+    /// Extracts the key-value pairs of the items of a response, validated against the schema
+    /// of the table.
+    ///
+    /// # Arguments
+    ///
+    /// * `integration` - The integration mapping the items.
+    /// * `body` - The JSON response of the source.
+    /// * `validator` - The validator of the value type and JSON Schema of the table.
+    ///
+    /// # Returns
+    ///
+    /// * `ExtractedRows` - The rows and the skipped items.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the response has nothing at the items pointer.
+    pub fn extract(
+        integration: &Integration,
+        body: &Value,
+        validator: &ValueValidator,
+    ) -> Result<ExtractedRows, String> {
+        let items = body
+            .pointer(&integration.items_pointer)
+            .ok_or_else(|| format!("no items at {:?}", integration.items_pointer))?;
+        let items = match items {
+            Value::Array(items) => items.as_slice(),
+            item => std::slice::from_ref(item),
+        };
+        let mut extracted = ExtractedRows::default();
+        for item in items {
+            let row = Self::row(integration, item, validator.value_type()).and_then(|row| {
+                validator
+                    .validate(&row.1)
+                    .map_err(|violations| SchemaViolation::describe(&violations))?;
+                Ok(row)
+            });
+            match row {
+                Ok(row) => extracted.rows.push(row),
+                Err(reason) => {
+                    extracted.skipped += 1;
+                    extracted.reason.get_or_insert(reason);
+                }
+            }
+        }
+        Ok(extracted)
+    }
+
+    /// Maps an item to its key and value, strings stored as they are unless the table holds
+    /// JSON.
+    fn row(
+        integration: &Integration,
+        item: &Value,
+        value_type: ValueType,
+    ) -> Result<(String, String), String> {
+        let key = match item.pointer(&integration.key_pointer) {
+            Some(Value::String(key)) if !key.is_empty() => key.clone(),
+            Some(Value::Number(key)) => key.to_string(),
+            _ => return Err(format!("no key at {:?}", integration.key_pointer)),
+        };
+        let value = match item.pointer(&integration.value_pointer) {
+            Some(Value::String(value)) if value_type != ValueType::Json => value.clone(),
+            Some(value) => value.to_string(),
+            None => return Err(format!("no value at {:?}", integration.value_pointer)),
+        };
+        Ok((key, value))
+    }
+}
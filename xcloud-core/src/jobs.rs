@@ -0,0 +1,96 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use tokio::sync::Semaphore;
+
+/// A trait representing a unit of background work executed by the [`JobQueue`].
+#[async_trait]
+pub trait Job: Send + Sync {
+    /// Returns a human readable name of the job, used for logging.
+    fn name(&self) -> String;
+
+    /// Runs a single attempt of the job.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the attempt failed and should be retried.
+    async fn run(&self) -> Result<(), String>;
+}
+
+/// A struct representing a bounded background job queue with retries.
+#[derive(Clone)]
+pub struct JobQueue {
+    permits: Arc<Semaphore>,
+    max_attempts: u32,
+    base_delay: Duration,
+}
+
+/// Implementation of the `JobQueue` struct.
+impl JobQueue {
+    /// Creates a new instance of the JobQueue.
+    ///
+    /// # Arguments
+    ///
+    /// * `concurrency` - The maximum number of jobs running at the same time.
+    /// * `max_attempts` - The maximum number of attempts before a job is dropped.
+    /// * `base_delay` - The delay before the first retry, doubled on every further retry.
+    ///
+    /// # Returns
+    ///
+    /// * `JobQueue` - A new instance of the JobQueue.
+    pub fn new(concurrency: usize, max_attempts: u32, base_delay: Duration) -> Self {
+        JobQueue {
+            permits: Arc::new(Semaphore::new(concurrency)),
+            max_attempts,
+            base_delay,
+        }
+    }
+
+    /// Enqueues a job, retrying it with exponential backoff until it succeeds
+    /// or the maximum number of attempts is reached.
+    ///
+    /// # Arguments
+    ///
+    /// * `job` - The job to run in the background.
+    pub fn enqueue(&self, job: Arc<dyn Job>) {
+        let queue = self.clone();
+        tokio::spawn(async move {
+            for attempt in 0..queue.max_attempts {
+                let result = {
+                    let _permit = queue.permits.acquire().await;
+                    job.run().await
+                };
+                match result {
+                    Ok(()) => return,
+                    Err(e) => {
+                        log::warn!(
+                            "Job {} failed (attempt {}/{}): {}",
+                            job.name(),
+                            attempt + 1,
+                            queue.max_attempts,
+                            e
+                        );
+                        if attempt + 1 < queue.max_attempts {
+                            tokio::time::sleep(queue.backoff(attempt)).await;
+                        }
+                    }
+                }
+            }
+            log::error!("Job {} exhausted all retries", job.name());
+        });
+    }
+
+    /// Calculates the delay before the next retry of a job.
+    ///
+    /// # Arguments
+    ///
+    /// * `attempt` - The zero-based number of the attempt that failed.
+    ///
+    /// # Returns
+    ///
+    /// * `Duration` - The delay before the next attempt.
+    fn backoff(&self, attempt: u32) -> Duration {
+        self.base_delay.saturating_mul(2u32.saturating_pow(attempt))
+    }
+}
@@ -0,0 +1,125 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::KeyStatsConfig;
+use crate::db::Database;
+
+/// A struct representing the access counters of a key.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, sqlx::FromRow)]
+pub struct KeyStat {
+    pub key: String,
+    pub reads: i64,
+    pub writes: i64,
+    /// The unix timestamp in seconds of the last read, if the key was read.
+    pub last_read_at: Option<i64>,
+    /// The unix timestamp in seconds of the last write, if the key was written.
+    pub last_write_at: Option<i64>,
+}
+
+/// A struct buffering the accesses to the keys in memory until they are flushed.
+#[derive(Default)]
+pub struct KeyStats {
+    pending: Mutex<HashMap<(String, String), KeyStat>>,
+}
+
+/// Implementation of the `KeyStats` struct.
+impl KeyStats {
+    /// Records a read of a key.
+    ///
+    /// # Arguments
+    ///
+    /// * `table` - The table holding the key.
+    /// * `key` - The key that was read.
+    pub fn record_read(&self, table: &str, key: &str) {
+        self.record(table, key, |stat, now| {
+            stat.reads += 1;
+            stat.last_read_at = Some(now);
+        });
+    }
+
+    /// Records a write of a key.
+    ///
+    /// # Arguments
+    ///
+    /// * `table` - The table holding the key.
+    /// * `key` - The key that was written.
+    pub fn record_write(&self, table: &str, key: &str) {
+        self.record(table, key, |stat, now| {
+            stat.writes += 1;
+            stat.last_write_at = Some(now);
+        });
+    }
+
+    /// Applies an access to the buffered counters of a key.
+    fn record(&self, table: &str, key: &str, access: impl FnOnce(&mut KeyStat, i64)) {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs() as i64);
+        let mut pending = self.pending.lock().unwrap_or_else(PoisonError::into_inner);
+        let stat = pending
+            .entry((table.to_string(), key.to_string()))
+            .or_insert_with(|| KeyStat {
+                key: key.to_string(),
+                ..KeyStat::default()
+            });
+        access(stat, now);
+    }
+
+    /// Takes the buffered counters.
+    ///
+    /// # Returns
+    ///
+    /// * `Vec<(String, KeyStat)>` - The counters accumulated since the last call, with the
+    ///   table holding each key.
+    pub fn drain(&self) -> Vec<(String, KeyStat)> {
+        self.pending
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .drain()
+            .map(|((table, _), stat)| (table, stat))
+            .collect()
+    }
+}
+
+/// A struct periodically flushing the buffered access counters to the database.
+pub struct KeyStatsFlusher {
+    db: Arc<tokio::sync::Mutex<Database>>,
+    config: KeyStatsConfig,
+}
+
+/// Implementation of the `KeyStatsFlusher` struct.
+impl KeyStatsFlusher {
+    /// Creates a new instance of the KeyStatsFlusher.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - A reference to the database wrapped in an Arc and Mutex for thread safety.
+    /// * `config` - The key statistics configuration holding the flush interval.
+    ///
+    /// # Returns
+    ///
+    /// * `KeyStatsFlusher` - A new instance of the KeyStatsFlusher.
+    pub fn new(db: Arc<tokio::sync::Mutex<Database>>, config: KeyStatsConfig) -> Self {
+        KeyStatsFlusher { db, config }
+    }
+
+    /// Flushes the counters in the background at the configured interval, if enabled.
+    pub fn spawn(self) {
+        if !self.config.enabled {
+            return;
+        }
+        tokio::spawn(async move {
+            let mut interval =
+                tokio::time::interval(Duration::from_secs(self.config.flush_interval_secs.max(1)));
+            loop {
+                interval.tick().await;
+                if let Err(e) = self.db.lock().await.flush_key_stats().await {
+                    log::error!("Failed to flush key statistics: {}", e);
+                }
+            }
+        });
+    }
+}
@@ -0,0 +1,90 @@
+//! The core of the xCLOUD backend service.
+//!
+//! The `xcloud` binary is a thin wrapper around this crate. Other services can embed the
+//! storage server in-process by opening a [`Database`] and running a [`Server`] on it:
+//!
+//! ```no_run
+//! use xcloud_core::logging::Logging;
+//! use xcloud_core::{AppError, Config, Database, Server};
+//!
+//! # async fn embed() -> Result<(), AppError> {
+//! let config = Config::default();
+//! let logging = Logging::init(&config.logging);
+//! let db = Database::new(&config.database).await?;
+//! Server::new(db, config, logging).run().await?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! With [`StorageBackend::Memory`](config::StorageBackend::Memory) nothing is written to disk.
+//! To serve the routes from an existing actix-web application instead, pass
+//! [`Server::configure`] to `App::configure`.
+
+pub mod advisor;
+pub mod alerts;
+pub mod bench;
+pub mod blobs;
+pub mod breaker;
+pub mod capture;
+pub mod check;
+pub mod cluster;
+pub mod compression;
+pub mod concurrency;
+pub mod config;
+pub mod consistency;
+pub mod console;
+pub mod crypto;
+pub mod dashboard;
+pub mod db;
+pub mod dedup;
+pub mod errors;
+pub mod extract;
+pub mod flags;
+pub mod graphql;
+pub mod health;
+pub mod history;
+pub mod i18n;
+pub mod ident;
+pub mod ids;
+pub mod import;
+pub mod integrations;
+pub mod jobs;
+pub mod keystats;
+pub mod logging;
+pub mod maintenance;
+pub mod metrics;
+pub mod middleware;
+pub mod mqtt;
+pub mod patch;
+pub mod plugin;
+pub mod privacy;
+pub mod projection;
+pub mod replication;
+pub mod response;
+pub mod retention;
+pub mod retry;
+pub mod runtime;
+pub mod schema;
+pub mod seed;
+pub mod server;
+pub mod sharding;
+pub mod signing;
+pub mod slowlog;
+pub mod snapshot;
+pub mod stats;
+pub mod storage;
+#[cfg(test)]
+mod tests;
+pub mod tiering;
+pub mod totp;
+pub mod utils;
+pub mod watcher;
+pub mod webhooks;
+pub mod writebehind;
+
+pub use config::Config;
+pub use db::Database;
+pub use errors::AppError;
+pub use response::ApiResponse;
+pub use server::Server;
+pub use storage::{MemoryStorage, Storage};
@@ -0,0 +1,78 @@
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::{fmt, reload, EnvFilter, Registry};
+
+use crate::config::{LogFormat, LoggingConfig};
+
+/// A struct holding the handle used to change the log level at runtime.
+pub struct Logging {
+    handle: reload::Handle<EnvFilter, Registry>,
+    level: std::sync::Mutex<String>,
+}
+
+/// Implementation of the `Logging` struct.
+impl Logging {
+    /// Installs the global tracing subscriber.
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - The logging configuration.
+    ///
+    /// # Returns
+    ///
+    /// * `Logging` - The handle used to change the log level at runtime.
+    pub fn init(config: &LoggingConfig) -> Self {
+        let level = std::env::var("RUST_LOG").unwrap_or_else(|_| config.level.clone());
+        let filter = EnvFilter::try_new(&level).unwrap_or_else(|_| EnvFilter::new("info"));
+        let (filter, handle) = reload::Layer::new(filter);
+        let registry = tracing_subscriber::registry().with(filter);
+        let installed = match config.format {
+            LogFormat::Pretty => registry.with(fmt::layer()).try_init(),
+            LogFormat::Json => registry
+                .with(
+                    fmt::layer()
+                        .json()
+                        .with_current_span(true)
+                        .with_span_list(true),
+                )
+                .try_init(),
+        };
+        if let Err(e) = installed {
+            eprintln!("Failed to install log subscriber: {}", e);
+        }
+        Logging {
+            handle,
+            level: std::sync::Mutex::new(level),
+        }
+    }
+
+    /// Returns the currently active log filter.
+    ///
+    /// # Returns
+    ///
+    /// * `String` - The active log filter.
+    pub fn level(&self) -> String {
+        self.level
+            .lock()
+            .map(|level| level.clone())
+            .unwrap_or_default()
+    }
+
+    /// Replaces the active log filter.
+    ///
+    /// # Arguments
+    ///
+    /// * `level` - The new log filter, e.g. `info` or `xcloud=debug,sqlx=warn`.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the filter is invalid or cannot be applied.
+    pub fn set_level(&self, level: &str) -> Result<(), String> {
+        let filter = EnvFilter::try_new(level).map_err(|e| e.to_string())?;
+        self.handle.reload(filter).map_err(|e| e.to_string())?;
+        if let Ok(mut current) = self.level.lock() {
+            *current = level.to_string();
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,133 @@
+use std::sync::{PoisonError, RwLock};
+
+use actix_web::http::Method;
+use serde::{Deserialize, Serialize};
+
+use crate::db::Database;
+use crate::health::Health;
+
+/// The name of the setting persisting the maintenance state.
+const SETTING: &str = "maintenance";
+
+/// An enum representing the maintenance mode of the server.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum MaintenanceMode {
+    /// All operations are served.
+    #[default]
+    Off,
+    /// Only reads are served, writes are rejected.
+    ReadOnly,
+    /// All operations except the admin endpoints are rejected.
+    Disabled,
+}
+
+/// A struct representing the maintenance state of the server.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+#[serde(default)]
+pub struct MaintenanceState {
+    pub mode: MaintenanceMode,
+    /// The number of seconds clients are asked to wait before retrying.
+    pub retry_after: u64,
+}
+
+/// Implementation of the `Default` trait for the `MaintenanceState` struct.
+impl Default for MaintenanceState {
+    fn default() -> Self {
+        MaintenanceState {
+            mode: MaintenanceMode::Off,
+            retry_after: 300,
+        }
+    }
+}
+
+/// A struct holding the maintenance state shared by all workers.
+#[derive(Default)]
+pub struct Maintenance {
+    state: RwLock<MaintenanceState>,
+}
+
+/// Implementation of the `Maintenance` struct.
+impl Maintenance {
+    /// Restores the maintenance state persisted in the database.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - The database holding the persisted state.
+    ///
+    /// # Returns
+    ///
+    /// * `Maintenance` - The restored state, or maintenance off if none was persisted.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the state cannot be retrieved.
+    pub async fn load(db: &Database) -> Result<Self, sqlx::Error> {
+        let state = match db.get_setting(SETTING).await? {
+            Some(raw) => serde_json::from_str(&raw).unwrap_or_else(|e| {
+                log::error!("Ignoring invalid maintenance state: {}", e);
+                MaintenanceState::default()
+            }),
+            None => MaintenanceState::default(),
+        };
+        if state.mode != MaintenanceMode::Off {
+            log::warn!("Starting in maintenance mode {:?}", state.mode);
+        }
+        Ok(Maintenance {
+            state: RwLock::new(state),
+        })
+    }
+
+    /// Returns the current maintenance state.
+    ///
+    /// # Returns
+    ///
+    /// * `MaintenanceState` - The current state.
+    pub fn state(&self) -> MaintenanceState {
+        *self.state.read().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Persists and applies a new maintenance state.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - The database persisting the state.
+    /// * `state` - The new state.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the state cannot be persisted.
+    pub async fn set(&self, db: &Database, state: MaintenanceState) -> Result<(), sqlx::Error> {
+        let raw =
+            serde_json::to_string(&state).map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+        db.set_setting(SETTING, &raw).await?;
+        *self.state.write().unwrap_or_else(PoisonError::into_inner) = state;
+        Ok(())
+    }
+
+    /// Checks whether a request is blocked by the current maintenance mode.
+    ///
+    /// # Arguments
+    ///
+    /// * `method` - The method of the request.
+    /// * `path` - The path of the request.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<u64>` - The seconds to wait before retrying, or `None` if the request is allowed.
+    pub fn blocks(&self, method: &Method, path: &str) -> Option<u64> {
+        let state = self.state();
+        let blocked = match state.mode {
+            MaintenanceMode::Off => false,
+            _ if path.starts_with("/admin") || Health::is_probe(path) => false,
+            MaintenanceMode::ReadOnly => !Self::is_read(method, path),
+            MaintenanceMode::Disabled => true,
+        };
+        blocked.then_some(state.retry_after)
+    }
+
+    /// Checks whether a request only reads data.
+    fn is_read(method: &Method, path: &str) -> bool {
+        matches!(*method, Method::GET | Method::HEAD | Method::OPTIONS) || path == "/get_many"
+    }
+}
@@ -0,0 +1,390 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::net::UdpSocket;
+use tokio::sync::Mutex;
+
+use crate::concurrency::{ConcurrencyLimits, LaneStats};
+use crate::config::MetricsConfig;
+use crate::db::Database;
+use crate::stats::{LatencyHistogram, RequestStats};
+
+/// The status classes of the request counters, in the order of [`RequestStats::counts`].
+const STATUS_CLASSES: [&str; 5] = ["1xx", "2xx", "3xx", "4xx", "5xx"];
+
+/// The name of the request latency histogram.
+const LATENCY_METRIC: &str = "xcloud_request_duration_seconds";
+
+/// A metric of the traffic lanes: its name, whether it is a counter and how it is read from the
+/// statistics of a lane.
+type LaneMetric = (&'static str, bool, fn(&LaneStats) -> f64);
+
+/// The content type of the OpenMetrics text format.
+pub const OPENMETRICS_CONTENT_TYPE: &str =
+    "application/openmetrics-text; version=1.0.0; charset=utf-8";
+
+/// A struct representing a single value of a metric.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Sample {
+    pub name: &'static str,
+    pub labels: Vec<(&'static str, String)>,
+    pub value: f64,
+    /// Whether the value only ever grows, as opposed to a gauge.
+    pub counter: bool,
+}
+
+/// Implementation of the `Sample` struct.
+impl Sample {
+    /// Creates a gauge sample without labels.
+    fn gauge(name: &'static str, value: f64) -> Self {
+        Sample {
+            name,
+            labels: Vec::new(),
+            value,
+            counter: false,
+        }
+    }
+
+    /// Collects the metrics of the server.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - The database to describe.
+    /// * `stats` - The request counters of the server.
+    /// * `concurrency` - The limits holding the lanes of the traffic classes.
+    ///
+    /// # Returns
+    ///
+    /// * `Vec<Sample>` - The uptime, request counts, table sizes, connection pool usage and
+    ///   queue depths of the traffic classes.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the tables cannot be described.
+    pub async fn collect(
+        db: &Database,
+        stats: &RequestStats,
+        concurrency: &ConcurrencyLimits,
+    ) -> Result<Vec<Sample>, sqlx::Error> {
+        let tables = db.describe_tables().await?;
+        let counts = stats.counts();
+        let mut samples = vec![Sample::gauge(
+            "xcloud_uptime_seconds",
+            stats.uptime_secs() as f64,
+        )];
+        for (class, count) in STATUS_CLASSES.iter().zip([
+            counts.informational,
+            counts.success,
+            counts.redirection,
+            counts.client_error,
+            counts.server_error,
+        ]) {
+            samples.push(Sample {
+                name: "xcloud_requests_total",
+                labels: vec![("class", class.to_string())],
+                value: count as f64,
+                counter: true,
+            });
+        }
+        samples.push(Sample::gauge("xcloud_tables", tables.len() as f64));
+        samples.push(Sample::gauge(
+            "xcloud_keys",
+            tables.iter().map(|table| table.rows).sum::<i64>() as f64,
+        ));
+        samples.push(Sample::gauge(
+            "xcloud_bytes",
+            tables.iter().map(|table| table.approx_bytes).sum::<i64>() as f64,
+        ));
+        for pool in db.pool_stats() {
+            let labels = vec![("shard", pool.shard.to_string())];
+            samples.push(Sample {
+                name: "xcloud_pool_connections",
+                labels: labels.clone(),
+                value: pool.connections as f64,
+                counter: false,
+            });
+            samples.push(Sample {
+                name: "xcloud_pool_idle_connections",
+                labels,
+                value: pool.idle as f64,
+                counter: false,
+            });
+        }
+        let lanes = concurrency.lane_stats();
+        let metrics: [LaneMetric; 4] = [
+            ("xcloud_lane_queued", false, |lane| lane.queued as f64),
+            ("xcloud_lane_in_flight", false, |lane| lane.in_flight as f64),
+            ("xcloud_lane_served_total", true, |lane| lane.served as f64),
+            ("xcloud_lane_latency_ms", false, |lane| lane.latency_ms),
+        ];
+        for (name, counter, value) in metrics {
+            for lane in &lanes {
+                samples.push(Sample {
+                    name,
+                    labels: vec![("class", format!("{:?}", lane.class).to_lowercase())],
+                    value: value(lane),
+                    counter,
+                });
+            }
+        }
+        Ok(samples)
+    }
+
+    /// Renders samples in the Prometheus text exposition format.
+    ///
+    /// # Arguments
+    ///
+    /// * `samples` - The samples to render, grouped by name.
+    ///
+    /// # Returns
+    ///
+    /// * `String` - The samples, each metric preceded by its type.
+    pub fn to_prometheus(samples: &[Sample]) -> String {
+        let mut text = String::new();
+        let mut previous = None;
+        for sample in samples {
+            if previous != Some(sample.name) {
+                let kind = if sample.counter { "counter" } else { "gauge" };
+                text.push_str(&format!("# TYPE {} {}\n", sample.name, kind));
+                previous = Some(sample.name);
+            }
+            text.push_str(&format!(
+                "{}{} {}\n",
+                sample.name,
+                Self::render_labels(&sample.labels),
+                sample.value
+            ));
+        }
+        text
+    }
+
+    /// Renders the request latency histogram in the Prometheus text exposition format, which
+    /// has no exemplars.
+    ///
+    /// # Arguments
+    ///
+    /// * `latency` - The latency histogram of the served requests.
+    ///
+    /// # Returns
+    ///
+    /// * `String` - The buckets, the count and the sum of the histogram.
+    pub fn latency_to_prometheus(latency: &LatencyHistogram) -> String {
+        Self::render_latency(latency, false)
+    }
+
+    /// Renders samples and the request latency histogram in the OpenMetrics text format, each
+    /// bucket of the histogram carrying the trace id of the most recent traced request it
+    /// counted as an exemplar, so dashboards can jump from a slow bucket to its trace.
+    ///
+    /// # Arguments
+    ///
+    /// * `samples` - The samples to render, grouped by name.
+    /// * `latency` - The latency histogram of the served requests.
+    ///
+    /// # Returns
+    ///
+    /// * `String` - The metric families, ending with the `# EOF` marker.
+    pub fn to_openmetrics(samples: &[Sample], latency: &LatencyHistogram) -> String {
+        let mut text = String::new();
+        let mut previous = None;
+        for sample in samples {
+            if previous != Some(sample.name) {
+                let (family, kind) = if sample.counter {
+                    (
+                        sample.name.strip_suffix("_total").unwrap_or(sample.name),
+                        "counter",
+                    )
+                } else {
+                    (sample.name, "gauge")
+                };
+                text.push_str(&format!("# TYPE {} {}\n", family, kind));
+                previous = Some(sample.name);
+            }
+            text.push_str(&format!(
+                "{}{} {}\n",
+                sample.name,
+                Self::render_labels(&sample.labels),
+                sample.value
+            ));
+        }
+        text.push_str(&Self::render_latency(latency, true));
+        text.push_str("# EOF\n");
+        text
+    }
+
+    /// Renders labels as `{name="value",...}`, or nothing if there are none.
+    fn render_labels(labels: &[(&'static str, String)]) -> String {
+        if labels.is_empty() {
+            return String::new();
+        }
+        let labels: Vec<String> = labels
+            .iter()
+            .map(|(name, value)| format!("{}=\"{}\"", name, value.replace('"', "\\\"")))
+            .collect();
+        format!("{{{}}}", labels.join(","))
+    }
+
+    /// Renders the request latency histogram, with or without the exemplars of its buckets.
+    fn render_latency(latency: &LatencyHistogram, exemplars: bool) -> String {
+        let mut text = format!("# TYPE {} histogram\n", LATENCY_METRIC);
+        for (bound, count, exemplar) in &latency.buckets {
+            let le = if bound.is_infinite() {
+                "+Inf".to_string()
+            } else {
+                format!("{:?}", bound)
+            };
+            text.push_str(&format!(
+                "{}_bucket{{le=\"{}\"}} {}",
+                LATENCY_METRIC, le, count
+            ));
+            if let Some(exemplar) = exemplar.as_ref().filter(|_| exemplars) {
+                text.push_str(&format!(
+                    " # {{trace_id=\"{}\"}} {} {:.3}",
+                    exemplar.trace_id, exemplar.value, exemplar.timestamp
+                ));
+            }
+            text.push('\n');
+        }
+        text.push_str(&format!("{}_count {}\n", LATENCY_METRIC, latency.count));
+        text.push_str(&format!("{}_sum {}\n", LATENCY_METRIC, latency.sum));
+        text
+    }
+
+    /// Renders the sample as a StatsD line, its label values appended to the name.
+    ///
+    /// # Arguments
+    ///
+    /// * `prefix` - The prefix of the metric names.
+    /// * `previous` - The values of the counters at the previous push, updated in place.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<String>` - The line, a gauge or the increase of a counter since the previous
+    ///   push, or `None` if the counter did not increase.
+    pub fn to_statsd(&self, prefix: &str, previous: &mut HashMap<String, f64>) -> Option<String> {
+        let mut name = self
+            .name
+            .strip_prefix("xcloud_")
+            .unwrap_or(self.name)
+            .to_string();
+        for (_, value) in &self.labels {
+            name.push('.');
+            name.push_str(value);
+        }
+        if !prefix.is_empty() {
+            name = format!("{}.{}", prefix, name);
+        }
+        if !self.counter {
+            return Some(format!("{}:{}|g", name, self.value));
+        }
+        let last = previous.insert(name.clone(), self.value).unwrap_or(0.0);
+        let increase = self.value - last;
+        (increase > 0.0).then(|| format!("{}:{}|c", name, increase))
+    }
+}
+
+/// A struct periodically pushing the metrics to a Prometheus Pushgateway and a StatsD daemon,
+/// for deployments that cannot be scraped.
+pub struct MetricsExporter {
+    db: Arc<Mutex<Database>>,
+    stats: Arc<RequestStats>,
+    concurrency: Arc<ConcurrencyLimits>,
+    client: reqwest::Client,
+    config: MetricsConfig,
+}
+
+/// Implementation of the `MetricsExporter` struct.
+impl MetricsExporter {
+    /// Creates a new instance of the MetricsExporter.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - A reference to the database wrapped in an Arc and Mutex for thread safety.
+    /// * `stats` - The request counters of the server.
+    /// * `concurrency` - The limits holding the lanes of the traffic classes.
+    /// * `config` - The metrics configuration naming the destinations.
+    ///
+    /// # Returns
+    ///
+    /// * `MetricsExporter` - A new instance of the MetricsExporter.
+    pub fn new(
+        db: Arc<Mutex<Database>>,
+        stats: Arc<RequestStats>,
+        concurrency: Arc<ConcurrencyLimits>,
+        config: MetricsConfig,
+    ) -> Self {
+        MetricsExporter {
+            db,
+            stats,
+            concurrency,
+            client: reqwest::Client::new(),
+            config,
+        }
+    }
+
+    /// Pushes the metrics in the background at the configured interval, if a destination is set.
+    pub fn spawn(self) {
+        if self.config.pushgateway.is_none() && self.config.statsd.is_none() {
+            return;
+        }
+        tokio::spawn(async move {
+            let mut previous = HashMap::new();
+            let mut interval =
+                tokio::time::interval(Duration::from_secs(self.config.push_interval_secs.max(1)));
+            loop {
+                interval.tick().await;
+                if let Err(e) = self.push(&mut previous).await {
+                    log::warn!("Failed to push metrics, retrying later: {}", e);
+                }
+            }
+        });
+    }
+
+    /// Collects the metrics and pushes them to every configured destination.
+    ///
+    /// # Arguments
+    ///
+    /// * `previous` - The values of the counters at the previous push, updated in place.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the metrics cannot be collected or delivered.
+    pub async fn push(&self, previous: &mut HashMap<String, f64>) -> Result<(), String> {
+        let samples = Sample::collect(&*self.db.lock().await, &self.stats, &self.concurrency)
+            .await
+            .map_err(|e| e.to_string())?;
+        if let Some(pushgateway) = &self.config.pushgateway {
+            self.client
+                .put(format!(
+                    "{}/metrics/job/{}",
+                    pushgateway.trim_end_matches('/'),
+                    self.config.job
+                ))
+                .header("Content-Type", "text/plain; version=0.0.4")
+                .body(
+                    Sample::to_prometheus(&samples)
+                        + &Sample::latency_to_prometheus(&self.stats.latency()),
+                )
+                .send()
+                .await
+                .and_then(|response| response.error_for_status())
+                .map_err(|e| format!("Pushgateway: {}", e))?;
+        }
+        if let Some(statsd) = &self.config.statsd {
+            let socket = UdpSocket::bind("0.0.0.0:0")
+                .await
+                .map_err(|e| format!("StatsD: {}", e))?;
+            for line in samples
+                .iter()
+                .filter_map(|sample| sample.to_statsd(&self.config.statsd_prefix, previous))
+            {
+                socket
+                    .send_to(line.as_bytes(), statsd)
+                    .await
+                    .map_err(|e| format!("StatsD: {}", e))?;
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,1445 @@
+use actix_service::Service;
+use actix_web::{
+    body::{self, BodySize, BoxBody, EitherBody, MessageBody},
+    dev::{Payload, ServiceRequest, ServiceResponse},
+    error::PayloadError,
+    http::header::{
+        HeaderName, HeaderValue, ACCEPT_LANGUAGE, CONTENT_LANGUAGE, CONTENT_LENGTH, RETRY_AFTER,
+        VARY,
+    },
+    http::Method,
+    web, Error, HttpMessage, HttpResponse,
+};
+use futures::future::{ok, Ready};
+use futures::StreamExt;
+use std::pin::Pin;
+use std::rc::Rc;
+use std::sync::{Arc, PoisonError, RwLock};
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+use tokio::sync::OwnedSemaphorePermit;
+use tracing::Instrument;
+
+use crate::breaker::CircuitBreaker;
+use crate::capture::{Capture, TrafficCapture, STREAMED_BODY};
+use crate::concurrency::{ConcurrencyLimits, LaneTicket, PRIORITY_HEADER};
+use crate::config::Config;
+use crate::consistency::{Consistency, CONSISTENCY_TOKEN_HEADER};
+use crate::flags::FeatureFlags;
+use crate::health::Health;
+use crate::i18n::{Catalog, DEFAULT_LANGUAGE};
+use crate::maintenance::Maintenance;
+use crate::response::ApiResponse;
+use crate::signing::{
+    RequestVerifier, SignedQuery, SignedRequest, UrlSigner, CLIENT_HEADER,
+    REQUEST_SIGNATURE_HEADER, SIGNED_PREFIX, TIMESTAMP_HEADER,
+};
+use crate::stats::{Exemplar, RequestStats, TRACEPARENT_HEADER};
+
+/// Middleware for logging requests.
+pub struct RequestLogger;
+
+/// Implementation of the `Transform` trait for the `RequestLogger` struct.
+impl<S, B> actix_service::Transform<S, ServiceRequest> for RequestLogger
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Transform = RequestLoggerMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ok(RequestLoggerMiddleware { service })
+    }
+}
+
+/// Middleware for logging requests.
+pub struct RequestLoggerMiddleware<S> {
+    service: S,
+}
+
+/// Implementation of the `Service` trait for the `RequestLoggerMiddleware` struct.
+impl<S, B> Service<ServiceRequest> for RequestLoggerMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = Pin<Box<dyn futures::Future<Output = Result<Self::Response, Self::Error>>>>;
+
+    /// Polls the service to determine if it is ready to process a request.
+    ///
+    /// # Parameters
+    ///
+    /// - `ctx` - The context for the service.
+    ///
+    /// # Returns
+    ///
+    /// A `Poll` containing a `Result` with the result of the poll.
+    fn poll_ready(
+        &self,
+        ctx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Result<(), Self::Error>> {
+        self.service.poll_ready(ctx)
+    }
+
+    /// Calls the service to process a request.
+    ///
+    /// # Parameters
+    ///
+    /// - `req` - The request to process.
+    ///
+    /// # Returns
+    ///
+    /// A future containing the result of the request processing.
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let peer_addr = req.peer_addr();
+        let user_agent = req
+            .headers()
+            .get("User-Agent")
+            .and_then(|v| v.to_str().ok())
+            .unwrap_or("Unknown");
+        let trace_id = req
+            .headers()
+            .get(TRACEPARENT_HEADER)
+            .and_then(|v| v.to_str().ok())
+            .and_then(Exemplar::trace_id);
+
+        let span = tracing::info_span!(
+            "request",
+            method = %req.method(),
+            path = %req.path(),
+            peer = ?peer_addr,
+            trace_id = trace_id.as_deref(),
+        );
+        span.in_scope(|| tracing::info!(user_agent, "Request received"));
+
+        let fut = span.in_scope(|| self.service.call(req));
+        Box::pin(
+            async move {
+                let res = fut.await?;
+                tracing::info!(status = res.status().as_u16(), "Request completed");
+                Ok(res)
+            }
+            .instrument(span),
+        )
+    }
+}
+
+/// Middleware rejecting requests whose body exceeds a maximum size.
+pub struct BodySizeLimit {
+    max_size: usize,
+}
+
+/// Implementation of the `BodySizeLimit` struct.
+impl BodySizeLimit {
+    /// Creates a new instance of the BodySizeLimit middleware.
+    ///
+    /// # Arguments
+    ///
+    /// * `max_size` - The maximum size in bytes of a request body.
+    ///
+    /// # Returns
+    ///
+    /// * `BodySizeLimit` - A new instance of the BodySizeLimit middleware.
+    pub fn new(max_size: usize) -> Self {
+        BodySizeLimit { max_size }
+    }
+}
+
+/// Implementation of the `Transform` trait for the `BodySizeLimit` struct.
+impl<S, B> actix_service::Transform<S, ServiceRequest> for BodySizeLimit
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Transform = BodySizeLimitMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ok(BodySizeLimitMiddleware {
+            service,
+            max_size: self.max_size,
+        })
+    }
+}
+
+/// Middleware rejecting requests whose body exceeds a maximum size.
+pub struct BodySizeLimitMiddleware<S> {
+    service: S,
+    max_size: usize,
+}
+
+/// Implementation of the `Service` trait for the `BodySizeLimitMiddleware` struct.
+impl<S, B> Service<ServiceRequest> for BodySizeLimitMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Future = Pin<Box<dyn futures::Future<Output = Result<Self::Response, Self::Error>>>>;
+
+    /// Polls the service to determine if it is ready to process a request.
+    ///
+    /// # Parameters
+    ///
+    /// - `ctx` - The context for the service.
+    ///
+    /// # Returns
+    ///
+    /// A `Poll` containing a `Result` with the result of the poll.
+    fn poll_ready(
+        &self,
+        ctx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Result<(), Self::Error>> {
+        self.service.poll_ready(ctx)
+    }
+
+    /// Calls the service to process a request, unless its declared body size is too large.
+    /// Bodies without a declared size, such as chunked ones, fail with `413 Payload Too
+    /// Large` as soon as more bytes than the maximum size are read.
+    ///
+    /// # Parameters
+    ///
+    /// - `req` - The request to process.
+    ///
+    /// # Returns
+    ///
+    /// A future containing the result of the request processing.
+    fn call(&self, mut req: ServiceRequest) -> Self::Future {
+        let too_large = req
+            .headers()
+            .get(CONTENT_LENGTH)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.parse::<usize>().ok())
+            .is_some_and(|len| len > self.max_size);
+
+        if too_large {
+            log::warn!("Rejected oversized request to {}", req.path());
+            let response = HttpResponse::PayloadTooLarge()
+                .json(ApiResponse::error("request_body_is_too_large"));
+            return Box::pin(async move { Ok(req.into_response(response).map_into_right_body()) });
+        }
+
+        let max_size = self.max_size;
+        let mut received = 0;
+        let payload = req.take_payload().map(move |chunk| {
+            let chunk = chunk?;
+            received += chunk.len();
+            if received > max_size {
+                return Err(PayloadError::Overflow);
+            }
+            Ok(chunk)
+        });
+        let payload: Pin<Box<dyn futures::Stream<Item = Result<web::Bytes, PayloadError>>>> =
+            Box::pin(payload);
+        req.set_payload(Payload::from(payload));
+        let fut = self.service.call(req);
+        Box::pin(async move {
+            let res = fut.await?;
+            Ok(res.map_into_left_body())
+        })
+    }
+}
+
+/// Middleware rejecting the requests blocked by the maintenance mode.
+pub struct MaintenanceGuard {
+    maintenance: Arc<Maintenance>,
+}
+
+/// Implementation of the `MaintenanceGuard` struct.
+impl MaintenanceGuard {
+    /// Creates a new instance of the MaintenanceGuard middleware.
+    ///
+    /// # Arguments
+    ///
+    /// * `maintenance` - The maintenance state shared by all workers.
+    ///
+    /// # Returns
+    ///
+    /// * `MaintenanceGuard` - A new instance of the MaintenanceGuard middleware.
+    pub fn new(maintenance: Arc<Maintenance>) -> Self {
+        MaintenanceGuard { maintenance }
+    }
+}
+
+/// Implementation of the `Transform` trait for the `MaintenanceGuard` struct.
+impl<S, B> actix_service::Transform<S, ServiceRequest> for MaintenanceGuard
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Transform = MaintenanceGuardMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ok(MaintenanceGuardMiddleware {
+            service,
+            maintenance: self.maintenance.clone(),
+        })
+    }
+}
+
+/// Middleware rejecting the requests blocked by the maintenance mode.
+pub struct MaintenanceGuardMiddleware<S> {
+    service: S,
+    maintenance: Arc<Maintenance>,
+}
+
+/// Implementation of the `Service` trait for the `MaintenanceGuardMiddleware` struct.
+impl<S, B> Service<ServiceRequest> for MaintenanceGuardMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Future = Pin<Box<dyn futures::Future<Output = Result<Self::Response, Self::Error>>>>;
+
+    /// Polls the service to determine if it is ready to process a request.
+    ///
+    /// # Parameters
+    ///
+    /// - `ctx` - The context for the service.
+    ///
+    /// # Returns
+    ///
+    /// A `Poll` containing a `Result` with the result of the poll.
+    fn poll_ready(
+        &self,
+        ctx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Result<(), Self::Error>> {
+        self.service.poll_ready(ctx)
+    }
+
+    /// Calls the service to process a request, unless the maintenance mode blocks it.
+    ///
+    /// # Parameters
+    ///
+    /// - `req` - The request to process.
+    ///
+    /// # Returns
+    ///
+    /// A future containing the result of the request processing.
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        if let Some(retry_after) = self.maintenance.blocks(req.method(), req.path()) {
+            let response = HttpResponse::ServiceUnavailable()
+                .insert_header((RETRY_AFTER, retry_after.to_string()))
+                .json(ApiResponse::error("the_server_is_in_maintenance_mode"));
+            return Box::pin(async move { Ok(req.into_response(response).map_into_right_body()) });
+        }
+
+        let fut = self.service.call(req);
+        Box::pin(async move {
+            let res = fut.await?;
+            Ok(res.map_into_left_body())
+        })
+    }
+}
+
+/// Middleware hiding the endpoints gated by feature flags that are off for a request.
+pub struct FeatureGuard {
+    flags: Arc<FeatureFlags>,
+}
+
+/// Implementation of the `FeatureGuard` struct.
+impl FeatureGuard {
+    /// Creates a new instance of the FeatureGuard middleware.
+    ///
+    /// # Arguments
+    ///
+    /// * `flags` - The feature flags shared by all workers.
+    ///
+    /// # Returns
+    ///
+    /// * `FeatureGuard` - A new instance of the FeatureGuard middleware.
+    pub fn new(flags: Arc<FeatureFlags>) -> Self {
+        FeatureGuard { flags }
+    }
+}
+
+/// Implementation of the `Transform` trait for the `FeatureGuard` struct.
+impl<S, B> actix_service::Transform<S, ServiceRequest> for FeatureGuard
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Transform = FeatureGuardMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ok(FeatureGuardMiddleware {
+            service,
+            flags: self.flags.clone(),
+        })
+    }
+}
+
+/// Middleware hiding the endpoints gated by feature flags that are off for a request.
+pub struct FeatureGuardMiddleware<S> {
+    service: S,
+    flags: Arc<FeatureFlags>,
+}
+
+/// Implementation of the `Service` trait for the `FeatureGuardMiddleware` struct.
+impl<S, B> Service<ServiceRequest> for FeatureGuardMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Future = Pin<Box<dyn futures::Future<Output = Result<Self::Response, Self::Error>>>>;
+
+    /// Polls the service to determine if it is ready to process a request.
+    ///
+    /// # Parameters
+    ///
+    /// - `ctx` - The context for the service.
+    ///
+    /// # Returns
+    ///
+    /// A `Poll` containing a `Result` with the result of the poll.
+    fn poll_ready(
+        &self,
+        ctx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Result<(), Self::Error>> {
+        self.service.poll_ready(ctx)
+    }
+
+    /// Calls the service to process a request, unless a feature flag gating its endpoint is
+    /// off for it.
+    ///
+    /// # Parameters
+    ///
+    /// - `req` - The request to process.
+    ///
+    /// # Returns
+    ///
+    /// A future containing the result of the request processing.
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let tenant = req
+            .headers()
+            .get(CLIENT_HEADER)
+            .and_then(|v| v.to_str().ok());
+        if let Some(flag) = self.flags.blocks(req.path(), tenant) {
+            log::debug!("Feature flag {} hides {}", flag, req.path());
+            let response =
+                HttpResponse::NotFound().json(ApiResponse::error("the_endpoint_is_not_available"));
+            return Box::pin(async move { Ok(req.into_response(response).map_into_right_body()) });
+        }
+
+        let fut = self.service.call(req);
+        Box::pin(async move {
+            let res = fut.await?;
+            Ok(res.map_into_left_body())
+        })
+    }
+}
+
+/// Middleware rejecting requests while the circuit breaker is open.
+pub struct CircuitBreakerGuard {
+    breaker: Arc<CircuitBreaker>,
+}
+
+/// Implementation of the `CircuitBreakerGuard` struct.
+impl CircuitBreakerGuard {
+    /// Creates a new instance of the CircuitBreakerGuard middleware.
+    ///
+    /// # Arguments
+    ///
+    /// * `breaker` - The circuit breaker shared with the database.
+    ///
+    /// # Returns
+    ///
+    /// * `CircuitBreakerGuard` - A new instance of the CircuitBreakerGuard middleware.
+    pub fn new(breaker: Arc<CircuitBreaker>) -> Self {
+        CircuitBreakerGuard { breaker }
+    }
+}
+
+/// Implementation of the `Transform` trait for the `CircuitBreakerGuard` struct.
+impl<S, B> actix_service::Transform<S, ServiceRequest> for CircuitBreakerGuard
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Transform = CircuitBreakerGuardMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ok(CircuitBreakerGuardMiddleware {
+            service,
+            breaker: self.breaker.clone(),
+        })
+    }
+}
+
+/// Middleware rejecting requests while the circuit breaker is open.
+pub struct CircuitBreakerGuardMiddleware<S> {
+    service: S,
+    breaker: Arc<CircuitBreaker>,
+}
+
+/// Implementation of the `Service` trait for the `CircuitBreakerGuardMiddleware` struct.
+impl<S, B> Service<ServiceRequest> for CircuitBreakerGuardMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Future = Pin<Box<dyn futures::Future<Output = Result<Self::Response, Self::Error>>>>;
+
+    /// Polls the service to determine if it is ready to process a request.
+    ///
+    /// # Parameters
+    ///
+    /// - `ctx` - The context for the service.
+    ///
+    /// # Returns
+    ///
+    /// A `Poll` containing a `Result` with the result of the poll.
+    fn poll_ready(
+        &self,
+        ctx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Result<(), Self::Error>> {
+        self.service.poll_ready(ctx)
+    }
+
+    /// Calls the service to process a request, unless the circuit is open.
+    ///
+    /// The admin endpoints and the probes are always served, so the server can still be inspected.
+    ///
+    /// # Parameters
+    ///
+    /// - `req` - The request to process.
+    ///
+    /// # Returns
+    ///
+    /// A future containing the result of the request processing.
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        if !req.path().starts_with("/admin") && !Health::is_probe(req.path()) {
+            if let Some(retry_after) = self.breaker.open_for() {
+                let response = HttpResponse::ServiceUnavailable()
+                    .insert_header((RETRY_AFTER, retry_after.to_string()))
+                    .json(ApiResponse::error(
+                        "the_database_is_temporarily_unavailable",
+                    ));
+                return Box::pin(
+                    async move { Ok(req.into_response(response).map_into_right_body()) },
+                );
+            }
+        }
+
+        let fut = self.service.call(req);
+        Box::pin(async move {
+            let res = fut.await?;
+            Ok(res.map_into_left_body())
+        })
+    }
+}
+
+/// Middleware rejecting requests to signed routes whose URL is not validly signed.
+pub struct SignedUrlGuard {
+    config: Arc<RwLock<Config>>,
+}
+
+/// Implementation of the `SignedUrlGuard` struct.
+impl SignedUrlGuard {
+    /// Creates a new instance of the SignedUrlGuard middleware.
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - The live configuration holding the signing secret.
+    ///
+    /// # Returns
+    ///
+    /// * `SignedUrlGuard` - A new instance of the SignedUrlGuard middleware.
+    pub fn new(config: Arc<RwLock<Config>>) -> Self {
+        SignedUrlGuard { config }
+    }
+}
+
+/// Implementation of the `Transform` trait for the `SignedUrlGuard` struct.
+impl<S, B> actix_service::Transform<S, ServiceRequest> for SignedUrlGuard
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Transform = SignedUrlGuardMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ok(SignedUrlGuardMiddleware {
+            service,
+            config: self.config.clone(),
+        })
+    }
+}
+
+/// Middleware rejecting requests to signed routes whose URL is not validly signed.
+pub struct SignedUrlGuardMiddleware<S> {
+    service: S,
+    config: Arc<RwLock<Config>>,
+}
+
+/// Implementation of the `SignedUrlGuardMiddleware` struct.
+impl<S> SignedUrlGuardMiddleware<S> {
+    /// Checks whether the request carries a valid, unexpired signature.
+    fn is_signed(&self, req: &ServiceRequest) -> bool {
+        let secret = self
+            .config
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .signed_urls
+            .secret
+            .clone();
+        if secret.is_empty() {
+            return false;
+        }
+        match web::Query::<SignedQuery>::from_query(req.query_string()) {
+            Ok(query) => UrlSigner::new(&secret).verify(&query),
+            Err(_) => false,
+        }
+    }
+}
+
+/// Implementation of the `Service` trait for the `SignedUrlGuardMiddleware` struct.
+impl<S, B> Service<ServiceRequest> for SignedUrlGuardMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Future = Pin<Box<dyn futures::Future<Output = Result<Self::Response, Self::Error>>>>;
+
+    /// Polls the service to determine if it is ready to process a request.
+    ///
+    /// # Parameters
+    ///
+    /// - `ctx` - The context for the service.
+    ///
+    /// # Returns
+    ///
+    /// A `Poll` containing a `Result` with the result of the poll.
+    fn poll_ready(
+        &self,
+        ctx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Result<(), Self::Error>> {
+        self.service.poll_ready(ctx)
+    }
+
+    /// Calls the service to process a request, unless it targets a signed route without a valid signature.
+    ///
+    /// # Parameters
+    ///
+    /// - `req` - The request to process.
+    ///
+    /// # Returns
+    ///
+    /// A future containing the result of the request processing.
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        if req.path().starts_with(SIGNED_PREFIX) && !self.is_signed(&req) {
+            let response =
+                HttpResponse::Forbidden().json(ApiResponse::error("invalid_or_expired_signature"));
+            return Box::pin(async move { Ok(req.into_response(response).map_into_right_body()) });
+        }
+
+        let fut = self.service.call(req);
+        Box::pin(async move {
+            let res = fut.await?;
+            Ok(res.map_into_left_body())
+        })
+    }
+}
+
+/// Middleware rejecting mutating requests without a valid HMAC signature while request signing
+/// is enabled.
+pub struct RequestSignatureGuard {
+    config: Arc<RwLock<Config>>,
+    verifier: Arc<RequestVerifier>,
+}
+
+/// Implementation of the `RequestSignatureGuard` struct.
+impl RequestSignatureGuard {
+    /// Creates a new instance of the RequestSignatureGuard middleware.
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - The live configuration holding the client secrets.
+    /// * `verifier` - The verifier remembering the accepted signatures, shared by all workers.
+    ///
+    /// # Returns
+    ///
+    /// * `RequestSignatureGuard` - A new instance of the RequestSignatureGuard middleware.
+    pub fn new(config: Arc<RwLock<Config>>, verifier: Arc<RequestVerifier>) -> Self {
+        RequestSignatureGuard { config, verifier }
+    }
+}
+
+/// Implementation of the `Transform` trait for the `RequestSignatureGuard` struct.
+impl<S, B> actix_service::Transform<S, ServiceRequest> for RequestSignatureGuard
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Transform = RequestSignatureGuardMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ok(RequestSignatureGuardMiddleware {
+            service: Rc::new(service),
+            config: self.config.clone(),
+            verifier: self.verifier.clone(),
+        })
+    }
+}
+
+/// Middleware rejecting mutating requests without a valid HMAC signature while request signing
+/// is enabled.
+pub struct RequestSignatureGuardMiddleware<S> {
+    service: Rc<S>,
+    config: Arc<RwLock<Config>>,
+    verifier: Arc<RequestVerifier>,
+}
+
+/// Implementation of the `Service` trait for the `RequestSignatureGuardMiddleware` struct.
+impl<S, B> Service<ServiceRequest> for RequestSignatureGuardMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Future = Pin<Box<dyn futures::Future<Output = Result<Self::Response, Self::Error>>>>;
+
+    /// Polls the service to determine if it is ready to process a request.
+    ///
+    /// # Parameters
+    ///
+    /// - `ctx` - The context for the service.
+    ///
+    /// # Returns
+    ///
+    /// A `Poll` containing a `Result` with the result of the poll.
+    fn poll_ready(
+        &self,
+        ctx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Result<(), Self::Error>> {
+        self.service.poll_ready(ctx)
+    }
+
+    /// Calls the service to process a request, unless it is a mutating request whose signature
+    /// is missing, invalid, stale or replayed.
+    ///
+    /// # Parameters
+    ///
+    /// - `req` - The request to process.
+    ///
+    /// # Returns
+    ///
+    /// A future containing the result of the request processing.
+    fn call(&self, mut req: ServiceRequest) -> Self::Future {
+        let service = self.service.clone();
+        let config = self
+            .config
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .request_signing
+            .clone();
+        let method = req.method().clone();
+        if !config.enabled || method.is_safe() {
+            return Box::pin(async move { Ok(service.call(req).await?.map_into_left_body()) });
+        }
+        let verifier = self.verifier.clone();
+        Box::pin(async move {
+            let header = |name: &str| {
+                req.headers()
+                    .get(name)
+                    .and_then(|value| value.to_str().ok())
+                    .map(str::to_string)
+            };
+            let (client, timestamp, signature) = (
+                header(CLIENT_HEADER),
+                header(TIMESTAMP_HEADER).and_then(|value| value.parse().ok()),
+                header(REQUEST_SIGNATURE_HEADER),
+            );
+            let (Some(client), Some(timestamp), Some(signature)) = (client, timestamp, signature)
+            else {
+                let response = HttpResponse::Unauthorized()
+                    .json(ApiResponse::error("request_signature_is_missing"));
+                return Ok(req.into_response(response).map_into_right_body());
+            };
+
+            let mut payload = req.take_payload();
+            let mut body = web::BytesMut::new();
+            while let Some(chunk) = payload.next().await {
+                body.extend_from_slice(&chunk?);
+            }
+            let body = body.freeze();
+            let path = req
+                .uri()
+                .path_and_query()
+                .map_or(req.path(), |path| path.as_str())
+                .to_string();
+            let signed = SignedRequest {
+                method: method.as_str(),
+                path: &path,
+                timestamp,
+                body: &body,
+            };
+            if let Err(reason) = verifier.verify(&config, &client, &signature, &signed) {
+                log::warn!("Rejected signed request from {}: {}", client, reason);
+                let response = HttpResponse::Unauthorized().json(ApiResponse::error(reason));
+                return Ok(req.into_response(response).map_into_right_body());
+            }
+            req.set_payload(Payload::from(body));
+            Ok(service.call(req).await?.map_into_left_body())
+        })
+    }
+}
+
+/// Middleware limiting the number of requests served concurrently per group of routes and
+/// admitting them in the lanes of their traffic classes.
+pub struct ConcurrencyGuard {
+    limits: Arc<ConcurrencyLimits>,
+}
+
+/// Implementation of the `ConcurrencyGuard` struct.
+impl ConcurrencyGuard {
+    /// Creates a new instance of the ConcurrencyGuard middleware.
+    ///
+    /// # Arguments
+    ///
+    /// * `limits` - The limits shared by all workers.
+    ///
+    /// # Returns
+    ///
+    /// * `ConcurrencyGuard` - A new instance of the ConcurrencyGuard middleware.
+    pub fn new(limits: Arc<ConcurrencyLimits>) -> Self {
+        ConcurrencyGuard { limits }
+    }
+}
+
+/// Implementation of the `Transform` trait for the `ConcurrencyGuard` struct.
+impl<S, B> actix_service::Transform<S, ServiceRequest> for ConcurrencyGuard
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: MessageBody + 'static,
+{
+    type Response = ServiceResponse<EitherBody<AdmittedBody>>;
+    type Error = Error;
+    type Transform = ConcurrencyGuardMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ok(ConcurrencyGuardMiddleware {
+            service: Rc::new(service),
+            limits: self.limits.clone(),
+        })
+    }
+}
+
+/// A response body holding the slot and the admission of its request until it is sent, so
+/// streamed responses keep counting against their group and lane while they stream.
+pub struct AdmittedBody {
+    body: BoxBody,
+    _permit: Option<OwnedSemaphorePermit>,
+    _ticket: LaneTicket,
+}
+
+/// Implementation of the `MessageBody` trait for the `AdmittedBody` struct.
+impl MessageBody for AdmittedBody {
+    type Error = Box<dyn std::error::Error>;
+
+    fn size(&self) -> BodySize {
+        self.body.size()
+    }
+
+    fn poll_next(
+        mut self: Pin<&mut Self>,
+        ctx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Option<Result<web::Bytes, Self::Error>>> {
+        Pin::new(&mut self.body).poll_next(ctx)
+    }
+}
+
+/// Middleware limiting the number of requests served concurrently per group of routes.
+pub struct ConcurrencyGuardMiddleware<S> {
+    service: Rc<S>,
+    limits: Arc<ConcurrencyLimits>,
+}
+
+/// Implementation of the `Service` trait for the `ConcurrencyGuardMiddleware` struct.
+impl<S, B> Service<ServiceRequest> for ConcurrencyGuardMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: MessageBody + 'static,
+{
+    type Response = ServiceResponse<EitherBody<AdmittedBody>>;
+    type Error = Error;
+    type Future = Pin<Box<dyn futures::Future<Output = Result<Self::Response, Self::Error>>>>;
+
+    /// Polls the service to determine if it is ready to process a request.
+    ///
+    /// # Parameters
+    ///
+    /// - `ctx` - The context for the service.
+    ///
+    /// # Returns
+    ///
+    /// A `Poll` containing a `Result` with the result of the poll.
+    fn poll_ready(
+        &self,
+        ctx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Result<(), Self::Error>> {
+        self.service.poll_ready(ctx)
+    }
+
+    /// Calls the service to process a request once its group has a free slot and its lane
+    /// admits it.
+    ///
+    /// # Parameters
+    ///
+    /// - `req` - The request to process.
+    ///
+    /// # Returns
+    ///
+    /// A future containing the result of the request processing.
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let service = self.service.clone();
+        let limits = self.limits.clone();
+        Box::pin(async move {
+            let permit = match limits.acquire(req.path()).await {
+                Ok(permit) => permit,
+                Err(group) => {
+                    log::warn!(
+                        "Rejected request to {}: {} is at capacity",
+                        req.path(),
+                        group
+                    );
+                    let response = HttpResponse::ServiceUnavailable()
+                        .insert_header((RETRY_AFTER, "1"))
+                        .json(ApiResponse::error("too_many_concurrent_requests"));
+                    return Ok(req.into_response(response).map_into_right_body());
+                }
+            };
+            let priority = req
+                .headers()
+                .get(PRIORITY_HEADER)
+                .and_then(|value| value.to_str().ok())
+                .map(str::to_string);
+            let ticket = match limits.admit(req.path(), priority.as_deref()).await {
+                Ok(ticket) => ticket,
+                Err(class) => {
+                    log::warn!(
+                        "Rejected {:?} request to {}: its lane is at capacity",
+                        class,
+                        req.path()
+                    );
+                    let response = HttpResponse::ServiceUnavailable()
+                        .insert_header((RETRY_AFTER, "1"))
+                        .json(ApiResponse::error("too_many_concurrent_requests"));
+                    return Ok(req.into_response(response).map_into_right_body());
+                }
+            };
+            let res = service.call(req).await?;
+            Ok(res
+                .map_body(|_, body| AdmittedBody {
+                    body: body.boxed(),
+                    _permit: permit,
+                    _ticket: ticket,
+                })
+                .map_into_left_body())
+        })
+    }
+}
+
+/// Middleware counting the served requests per status class and observing their latency,
+/// requests carrying a `traceparent` header becoming the exemplars of the latency histogram.
+pub struct RequestCounter {
+    stats: Arc<RequestStats>,
+}
+
+/// Implementation of the `RequestCounter` struct.
+impl RequestCounter {
+    /// Creates a new instance of the RequestCounter middleware.
+    ///
+    /// # Arguments
+    ///
+    /// * `stats` - The counters shared by all workers.
+    ///
+    /// # Returns
+    ///
+    /// * `RequestCounter` - A new instance of the RequestCounter middleware.
+    pub fn new(stats: Arc<RequestStats>) -> Self {
+        RequestCounter { stats }
+    }
+}
+
+/// Implementation of the `Transform` trait for the `RequestCounter` struct.
+impl<S, B> actix_service::Transform<S, ServiceRequest> for RequestCounter
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Transform = RequestCounterMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ok(RequestCounterMiddleware {
+            service,
+            stats: self.stats.clone(),
+        })
+    }
+}
+
+/// Middleware counting the served requests per status class.
+pub struct RequestCounterMiddleware<S> {
+    service: S,
+    stats: Arc<RequestStats>,
+}
+
+/// Implementation of the `Service` trait for the `RequestCounterMiddleware` struct.
+impl<S, B> Service<ServiceRequest> for RequestCounterMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = Pin<Box<dyn futures::Future<Output = Result<Self::Response, Self::Error>>>>;
+
+    /// Polls the service to determine if it is ready to process a request.
+    ///
+    /// # Parameters
+    ///
+    /// - `ctx` - The context for the service.
+    ///
+    /// # Returns
+    ///
+    /// A `Poll` containing a `Result` with the result of the poll.
+    fn poll_ready(
+        &self,
+        ctx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Result<(), Self::Error>> {
+        self.service.poll_ready(ctx)
+    }
+
+    /// Calls the service to process a request and counts the status and the latency of its
+    /// response.
+    ///
+    /// # Parameters
+    ///
+    /// - `req` - The request to process.
+    ///
+    /// # Returns
+    ///
+    /// A future containing the result of the request processing.
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let stats = self.stats.clone();
+        let started = Instant::now();
+        let trace_id = req
+            .headers()
+            .get(TRACEPARENT_HEADER)
+            .and_then(|v| v.to_str().ok())
+            .and_then(Exemplar::trace_id);
+        let fut = self.service.call(req);
+        Box::pin(async move {
+            let res = fut.await;
+            match &res {
+                Ok(res) => stats.record(res.status().as_u16()),
+                Err(e) => stats.record(e.as_response_error().status_code().as_u16()),
+            }
+            stats.record_latency(started.elapsed(), trace_id);
+            res
+        })
+    }
+}
+
+/// Middleware recording sanitized requests and responses of the selected routes while traffic
+/// capture is enabled.
+pub struct TrafficRecorder {
+    config: Arc<RwLock<Config>>,
+    capture: Arc<TrafficCapture>,
+}
+
+/// Implementation of the `TrafficRecorder` struct.
+impl TrafficRecorder {
+    /// Creates a new instance of the TrafficRecorder middleware.
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - The live configuration selecting the recorded routes.
+    /// * `capture` - The recorded exchanges, shared by all workers.
+    ///
+    /// # Returns
+    ///
+    /// * `TrafficRecorder` - A new instance of the TrafficRecorder middleware.
+    pub fn new(config: Arc<RwLock<Config>>, capture: Arc<TrafficCapture>) -> Self {
+        TrafficRecorder { config, capture }
+    }
+}
+
+/// Implementation of the `Transform` trait for the `TrafficRecorder` struct.
+impl<S, B> actix_service::Transform<S, ServiceRequest> for TrafficRecorder
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: MessageBody + 'static,
+{
+    type Response = ServiceResponse<EitherBody<B, BoxBody>>;
+    type Error = Error;
+    type Transform = TrafficRecorderMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ok(TrafficRecorderMiddleware {
+            service: Rc::new(service),
+            config: self.config.clone(),
+            capture: self.capture.clone(),
+        })
+    }
+}
+
+/// Middleware recording sanitized requests and responses of the selected routes while traffic
+/// capture is enabled.
+pub struct TrafficRecorderMiddleware<S> {
+    service: Rc<S>,
+    config: Arc<RwLock<Config>>,
+    capture: Arc<TrafficCapture>,
+}
+
+/// Implementation of the `Service` trait for the `TrafficRecorderMiddleware` struct.
+impl<S, B> Service<ServiceRequest> for TrafficRecorderMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: MessageBody + 'static,
+{
+    type Response = ServiceResponse<EitherBody<B, BoxBody>>;
+    type Error = Error;
+    type Future = Pin<Box<dyn futures::Future<Output = Result<Self::Response, Self::Error>>>>;
+
+    /// Polls the service to determine if it is ready to process a request.
+    ///
+    /// # Parameters
+    ///
+    /// - `ctx` - The context for the service.
+    ///
+    /// # Returns
+    ///
+    /// A `Poll` containing a `Result` with the result of the poll.
+    fn poll_ready(
+        &self,
+        ctx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Result<(), Self::Error>> {
+        self.service.poll_ready(ctx)
+    }
+
+    /// Calls the service to process a request, buffering and recording the request and its
+    /// response if its route is captured. Streamed response bodies are passed through and
+    /// recorded as [`STREAMED_BODY`].
+    ///
+    /// # Parameters
+    ///
+    /// - `req` - The request to process.
+    ///
+    /// # Returns
+    ///
+    /// A future containing the result of the request processing.
+    fn call(&self, mut req: ServiceRequest) -> Self::Future {
+        let service = self.service.clone();
+        let config = self
+            .config
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .capture
+            .clone();
+        if !TrafficCapture::captures(&config, req.path()) {
+            return Box::pin(async move { Ok(service.call(req).await?.map_into_left_body()) });
+        }
+        let capture = self.capture.clone();
+        Box::pin(async move {
+            let started = Instant::now();
+            let timestamp = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|elapsed| elapsed.as_millis() as i64)
+                .unwrap_or_default();
+            let mut payload = req.take_payload();
+            let mut request_body = web::BytesMut::new();
+            while let Some(chunk) = payload.next().await {
+                request_body.extend_from_slice(&chunk?);
+            }
+            let request_body = request_body.freeze();
+            let path = req
+                .uri()
+                .path_and_query()
+                .map_or(req.path(), |path| path.as_str())
+                .to_string();
+            let method = req.method().to_string();
+            let request_headers = TrafficCapture::sanitize_headers(req.headers());
+            let sanitized_request = TrafficCapture::sanitize_body(&config, &request_body);
+            req.set_payload(Payload::from(request_body));
+
+            let res = service.call(req).await?;
+            let status = res.status().as_u16();
+            let (res, response_body) = if res.response().body().size() == BodySize::Stream {
+                (res.map_into_left_body(), STREAMED_BODY.to_string())
+            } else {
+                let (http_req, res) = res.into_parts();
+                let (res, response_body) = res.into_parts();
+                let response_body = body::to_bytes(response_body).await.map_err(|e| {
+                    let e: Box<dyn std::error::Error> = e.into();
+                    actix_web::error::ErrorInternalServerError(e.to_string())
+                })?;
+                let sanitized = TrafficCapture::sanitize_body(&config, &response_body);
+                let res = res.set_body(response_body).map_into_boxed_body();
+                (
+                    ServiceResponse::new(http_req, res).map_into_right_body(),
+                    sanitized,
+                )
+            };
+            capture.record(
+                &config,
+                Capture {
+                    timestamp,
+                    method,
+                    path: TrafficCapture::sanitize_path(&config, &path),
+                    request_headers,
+                    request_body: sanitized_request,
+                    status,
+                    response_body,
+                    duration_ms: started.elapsed().as_millis() as i64,
+                },
+            );
+            Ok(res)
+        })
+    }
+}
+
+/// Middleware returning a consistency token on writes and routing the reads of requests that
+/// carry one to the read replicas that applied the write.
+pub struct ConsistencyTokens {
+    consistency: Arc<Consistency>,
+}
+
+/// Implementation of the `ConsistencyTokens` struct.
+impl ConsistencyTokens {
+    /// Creates a new instance of the ConsistencyTokens middleware.
+    ///
+    /// # Arguments
+    ///
+    /// * `consistency` - The positions of the primary and of the read replicas.
+    ///
+    /// # Returns
+    ///
+    /// * `ConsistencyTokens` - A new instance of the ConsistencyTokens middleware.
+    pub fn new(consistency: Arc<Consistency>) -> Self {
+        ConsistencyTokens { consistency }
+    }
+}
+
+/// Implementation of the `Transform` trait for the `ConsistencyTokens` struct.
+impl<S, B> actix_service::Transform<S, ServiceRequest> for ConsistencyTokens
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Transform = ConsistencyTokensMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ok(ConsistencyTokensMiddleware {
+            service,
+            consistency: self.consistency.clone(),
+        })
+    }
+}
+
+/// Middleware returning a consistency token on writes and routing the reads of requests that
+/// carry one to the read replicas that applied the write.
+pub struct ConsistencyTokensMiddleware<S> {
+    service: S,
+    consistency: Arc<Consistency>,
+}
+
+/// Implementation of the `Service` trait for the `ConsistencyTokensMiddleware` struct.
+impl<S, B> Service<ServiceRequest> for ConsistencyTokensMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = Pin<Box<dyn futures::Future<Output = Result<Self::Response, Self::Error>>>>;
+
+    /// Polls the service to determine if it is ready to process a request.
+    ///
+    /// # Parameters
+    ///
+    /// - `ctx` - The context for the service.
+    ///
+    /// # Returns
+    ///
+    /// A `Poll` containing a `Result` with the result of the poll.
+    fn poll_ready(
+        &self,
+        ctx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Result<(), Self::Error>> {
+        self.service.poll_ready(ctx)
+    }
+
+    /// Calls the service to process a request within the token it carries, and adds the token
+    /// of the primary to the response of a successful write.
+    ///
+    /// # Parameters
+    ///
+    /// - `req` - The request to process.
+    ///
+    /// # Returns
+    ///
+    /// A future containing the result of the request processing.
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let token = req
+            .headers()
+            .get(CONSISTENCY_TOKEN_HEADER)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.trim().parse::<i64>().ok());
+        let write = !matches!(*req.method(), Method::GET | Method::HEAD | Method::OPTIONS);
+        let consistency = self.consistency.clone();
+        let fut = self.service.call(req);
+        Box::pin(async move {
+            let mut res = Consistency::scope(token, fut).await?;
+            if write && res.status().is_success() {
+                if let Ok(name) = HeaderName::from_bytes(CONSISTENCY_TOKEN_HEADER.as_bytes()) {
+                    res.headers_mut()
+                        .insert(name, HeaderValue::from(consistency.token()));
+                }
+            }
+            Ok(res)
+        })
+    }
+}
+
+/// Middleware building the error responses of a request in the language chosen by its
+/// `Accept-Language` header.
+///
+/// The language is negotiated before the request is processed and scoped to it, so error
+/// responses translate their code as they are built. Error responses name their language in
+/// a `Content-Language` header.
+pub struct ErrorLocalizer;
+
+/// Implementation of the `Transform` trait for the `ErrorLocalizer` struct.
+impl<S, B> actix_service::Transform<S, ServiceRequest> for ErrorLocalizer
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: MessageBody + 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Transform = ErrorLocalizerMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ok(ErrorLocalizerMiddleware {
+            service: Rc::new(service),
+        })
+    }
+}
+
+/// Middleware building the error responses of a request in its language.
+pub struct ErrorLocalizerMiddleware<S> {
+    service: Rc<S>,
+}
+
+/// Implementation of the `Service` trait for the `ErrorLocalizerMiddleware` struct.
+impl<S, B> Service<ServiceRequest> for ErrorLocalizerMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: MessageBody + 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = Pin<Box<dyn futures::Future<Output = Result<Self::Response, Self::Error>>>>;
+
+    /// Polls the service to determine if it is ready to process a request.
+    ///
+    /// # Parameters
+    ///
+    /// - `ctx` - The context for the service.
+    ///
+    /// # Returns
+    ///
+    /// A `Poll` containing a `Result` with the result of the poll.
+    fn poll_ready(
+        &self,
+        ctx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Result<(), Self::Error>> {
+        self.service.poll_ready(ctx)
+    }
+
+    /// Calls the service to process a request in the language it asks for, naming the
+    /// language of its response if it is an error.
+    ///
+    /// # Parameters
+    ///
+    /// - `req` - The request to process.
+    ///
+    /// # Returns
+    ///
+    /// A future containing the result of the request processing.
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let language = req
+            .headers()
+            .get(ACCEPT_LANGUAGE)
+            .and_then(|value| value.to_str().ok())
+            .map_or(DEFAULT_LANGUAGE, |value| {
+                Catalog::bundled().negotiate(value)
+            })
+            .to_string();
+        let fut = Catalog::with_language(&language, || self.service.call(req));
+        Box::pin(Catalog::in_language(language.clone(), async move {
+            let mut res = fut.await?;
+            if res.status().is_client_error() || res.status().is_server_error() {
+                if let Ok(language) = HeaderValue::from_str(&language) {
+                    res.headers_mut().insert(CONTENT_LANGUAGE, language);
+                }
+                res.headers_mut()
+                    .append(VARY, HeaderValue::from_static("Accept-Language"));
+            }
+            Ok(res)
+        }))
+    }
+}
@@ -0,0 +1,268 @@
+use std::sync::{Arc, PoisonError, RwLock};
+use std::time::Duration;
+
+use rumqttc::{AsyncClient, Event, EventLoop, MqttOptions, Packet, QoS};
+use tokio::sync::Mutex;
+
+use crate::config::{Config, MqttConfig};
+use crate::db::Database;
+use crate::schema::SchemaViolation;
+use crate::webhooks::{WebhookEvent, Webhooks};
+
+/// The default port of MQTT brokers.
+const DEFAULT_PORT: u16 = 1883;
+
+/// The number of requests queued for the broker before publishing drops events.
+const QUEUE_CAPACITY: usize = 256;
+
+/// The number of seconds waited before reconnecting to an unreachable broker.
+const RECONNECT_SECS: u64 = 5;
+
+/// A struct publishing the changes of the tables to the MQTT broker.
+#[derive(Clone)]
+pub struct MqttPublisher {
+    client: AsyncClient,
+    topic: String,
+    qos: QoS,
+}
+
+/// Implementation of the `MqttPublisher` struct.
+impl MqttPublisher {
+    /// Publishes a change as JSON under `<topic>/<table>/<key>`, or `<topic>/<table>` for
+    /// changes of a whole table.
+    ///
+    /// Events are dropped with a warning while the queue to the broker is full, so a slow
+    /// broker never delays writes.
+    ///
+    /// # Arguments
+    ///
+    /// * `event` - The change to publish.
+    pub fn publish(&self, event: &WebhookEvent) {
+        let topic = match &event.key {
+            Some(key) => format!("{}/{}/{}", self.topic, event.table, key),
+            None => format!("{}/{}", self.topic, event.table),
+        };
+        let body = match serde_json::to_vec(event) {
+            Ok(body) => body,
+            Err(e) => {
+                log::error!("Failed to serialize MQTT event: {}", e);
+                return;
+            }
+        };
+        if let Err(e) = self.client.try_publish(topic, self.qos, false, body) {
+            log::warn!("Failed to publish MQTT event: {}", e);
+        }
+    }
+}
+
+/// A struct bridging an MQTT broker and the tables, letting devices without an HTTP stack
+/// write data by publishing messages.
+///
+/// Messages of topics matching a configured route are written into its table under the key
+/// derived from the topic, after the same legal hold, schema lock, key lock and schema checks
+/// as writes through the API, and notify the webhooks of the table. Messages failing a check
+/// are dropped with a warning. The connection is reestablished and the topics resubscribed
+/// whenever the broker becomes unreachable.
+pub struct MqttBridge {
+    config: MqttConfig,
+    client: AsyncClient,
+    eventloop: EventLoop,
+    qos: QoS,
+}
+
+/// Implementation of the `MqttBridge` struct.
+impl MqttBridge {
+    /// Creates a new instance of the MqttBridge, without connecting yet.
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - The MQTT configuration holding the broker and the routes.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<MqttBridge>` - The bridge, or `None` if no broker is configured.
+    pub fn new(config: MqttConfig) -> Option<Self> {
+        let broker = config.broker.as_deref()?;
+        let (host, port) = match broker.rsplit_once(':') {
+            Some((host, port)) => match port.parse() {
+                Ok(port) => (host, port),
+                Err(_) => {
+                    log::error!("Invalid MQTT broker port: {}", broker);
+                    return None;
+                }
+            },
+            None => (broker, DEFAULT_PORT),
+        };
+        let mut options = MqttOptions::new(config.client_id.clone(), host, port);
+        options.set_keep_alive(Duration::from_secs(config.keep_alive_secs.max(5)));
+        if let Some(username) = &config.username {
+            options.set_credentials(username.clone(), config.password.clone());
+        }
+        let qos = match config.qos {
+            0 => QoS::AtMostOnce,
+            1 => QoS::AtLeastOnce,
+            _ => QoS::ExactlyOnce,
+        };
+        let (client, eventloop) = AsyncClient::new(options, QUEUE_CAPACITY);
+        Some(MqttBridge {
+            config,
+            client,
+            eventloop,
+            qos,
+        })
+    }
+
+    /// Returns the publisher of the changes of the tables.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<MqttPublisher>` - The publisher, or `None` if no publish topic is configured.
+    pub fn publisher(&self) -> Option<MqttPublisher> {
+        Some(MqttPublisher {
+            client: self.client.clone(),
+            topic: self.config.publish_topic.clone()?,
+            qos: self.qos,
+        })
+    }
+
+    /// Connects to the broker and writes the received messages in the background.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - A reference to the database wrapped in an Arc and Mutex for thread safety.
+    /// * `webhooks` - The dispatcher notifying the webhooks of the written tables.
+    /// * `config` - The live configuration deciding whether locks are enforced.
+    pub fn spawn(
+        self,
+        db: Arc<Mutex<Database>>,
+        webhooks: Arc<Webhooks>,
+        config: Arc<RwLock<Config>>,
+    ) {
+        let MqttBridge {
+            config: mqtt,
+            client,
+            mut eventloop,
+            qos,
+        } = self;
+        tokio::spawn(async move {
+            loop {
+                match eventloop.poll().await {
+                    Ok(Event::Incoming(Packet::ConnAck(_))) => {
+                        log::info!("Connected to the MQTT broker");
+                        for route in &mqtt.routes {
+                            if let Err(e) = client.try_subscribe(&route.filter, qos) {
+                                log::error!("Failed to subscribe to {}: {}", route.filter, e);
+                            }
+                        }
+                    }
+                    Ok(Event::Incoming(Packet::Publish(message))) => {
+                        let db = db.lock().await;
+                        Self::write(
+                            &mqtt,
+                            &db,
+                            &webhooks,
+                            &config,
+                            &message.topic,
+                            &message.payload,
+                        )
+                        .await;
+                    }
+                    Ok(_) => {}
+                    Err(e) => {
+                        log::warn!("MQTT connection failed, reconnecting: {}", e);
+                        tokio::time::sleep(Duration::from_secs(RECONNECT_SECS)).await;
+                    }
+                }
+            }
+        });
+    }
+
+    /// Writes a message into the table of the first route matching its topic.
+    ///
+    /// # Arguments
+    ///
+    /// * `mqtt` - The MQTT configuration holding the routes.
+    /// * `db` - The database to write to.
+    /// * `webhooks` - The dispatcher notifying the webhooks of the written table.
+    /// * `config` - The live configuration deciding whether locks are enforced.
+    /// * `topic` - The topic of the message.
+    /// * `payload` - The payload of the message, written as the value.
+    async fn write(
+        mqtt: &MqttConfig,
+        db: &Database,
+        webhooks: &Webhooks,
+        config: &RwLock<Config>,
+        topic: &str,
+        payload: &[u8],
+    ) {
+        if let Some(prefix) = &mqtt.publish_topic {
+            if topic == prefix || topic.starts_with(&format!("{}/", prefix)) {
+                return;
+            }
+        }
+        let Some((table, key)) = mqtt
+            .routes
+            .iter()
+            .find_map(|route| Some((&route.table, route.key_for(topic)?)))
+        else {
+            log::debug!("No MQTT route matches {}", topic);
+            return;
+        };
+        let Ok(value) = std::str::from_utf8(payload) else {
+            log::warn!("Dropped MQTT message of {}: payload is not UTF-8", topic);
+            return;
+        };
+        if let Err(reason) = Self::check(db, config, table, &key, value).await {
+            log::warn!("Dropped MQTT message of {}: {}", topic, reason);
+            return;
+        }
+        match db.set_data(table, &key, value).await {
+            Ok(()) => {
+                log::debug!("Wrote MQTT message of {} to {}/{}", topic, table, key);
+                webhooks
+                    .notify(db, WebhookEvent::new("set", table, Some(&key), Some(value)))
+                    .await;
+            }
+            Err(e) => log::error!("Failed to write MQTT message of {}: {}", topic, e),
+        }
+    }
+
+    /// Checks a write against the legal holds, schema locks, key locks and the schema of the
+    /// table. Devices name no lock owner, so locked keys reject them while locks are enforced.
+    ///
+    /// # Errors
+    ///
+    /// This function will return the reason the write is rejected.
+    async fn check(
+        db: &Database,
+        config: &RwLock<Config>,
+        table: &str,
+        key: &str,
+        value: &str,
+    ) -> Result<(), String> {
+        if let Some(hold) = db.legal_hold(table).await.map_err(|e| e.to_string())? {
+            return Err(format!("table is under legal hold: {}", hold.reason));
+        }
+        if let Some(lock) = db.schema_lock(table).await.map_err(|e| e.to_string())? {
+            return Err(format!("table is locked by a {} operation", lock.operation));
+        }
+        let enforce = config
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .locks
+            .enforce;
+        if enforce {
+            if let Some(lock) = db.get_lock(table, key).await.map_err(|e| e.to_string())? {
+                return Err(format!("key is locked by {}", lock.owner));
+            }
+        }
+        match db.validate_value(table, value).await {
+            Ok(Ok(())) => Ok(()),
+            Ok(Err(violations)) => Err(format!(
+                "value does not match the table schema: {}",
+                SchemaViolation::describe(&violations)
+            )),
+            Err(e) => Err(e.to_string()),
+        }
+    }
+}
@@ -0,0 +1,165 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// An enum representing a change to a value applied by the server.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum ValuePatch {
+    /// An RFC 6902 JSON Patch, applied all or nothing.
+    Json(json_patch::Patch),
+    /// An RFC 7396 JSON Merge Patch.
+    Merge(Value),
+    /// A delta building the new value from parts of the current value of any type.
+    Delta(Vec<DeltaOp>),
+}
+
+/// An enum representing an operation of a delta, appending to the value being built.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum DeltaOp {
+    /// Copies the given number of bytes of the base value from the given byte offset.
+    Copy(usize, usize),
+    /// Inserts the given text.
+    Insert(String),
+}
+
+/// Implementation of the `ValuePatch` enum.
+impl ValuePatch {
+    /// Applies the patch to a value, which must be JSON unless the patch is a delta.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The text of the value to patch.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<String, String>` - The text of the patched value, or the reason the patch
+    ///   cannot be applied.
+    pub fn apply(&self, value: &str) -> Result<String, String> {
+        if let ValuePatch::Delta(ops) = self {
+            return apply_delta(value, ops);
+        }
+        let mut document: Value =
+            serde_json::from_str(value).map_err(|e| format!("value is not valid JSON: {}", e))?;
+        match self {
+            ValuePatch::Json(patch) => {
+                json_patch::patch(&mut document, &patch.0).map_err(|e| e.to_string())?
+            }
+            ValuePatch::Merge(patch) => json_patch::merge(&mut document, patch),
+            ValuePatch::Delta(_) => unreachable!("deltas are applied to the text of the value"),
+        }
+        Ok(document.to_string())
+    }
+
+    /// Computes the merge patch turning a base value into a newer value, field by field.
+    ///
+    /// Applied to a value concurrently changed from the same base, the patch keeps the fields
+    /// changed by both writers from the newer value and every other field as it is, so the last
+    /// writer wins per field. A field set to `null` is removed, as merge patches cannot express
+    /// `null` values.
+    ///
+    /// # Arguments
+    ///
+    /// * `base` - The JSON text of the value the change was made from.
+    /// * `value` - The JSON text of the changed value.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<ValuePatch, String>` - The merge patch, or the reason a value is not valid JSON.
+    pub fn diff(base: &str, value: &str) -> Result<ValuePatch, String> {
+        let base: Value =
+            serde_json::from_str(base).map_err(|e| format!("base is not valid JSON: {}", e))?;
+        let value: Value =
+            serde_json::from_str(value).map_err(|e| format!("value is not valid JSON: {}", e))?;
+        Ok(ValuePatch::Merge(merge_diff(&base, &value)))
+    }
+
+    /// Computes the delta building a value from a base value, copying their common prefix and
+    /// suffix and inserting the rest.
+    ///
+    /// # Arguments
+    ///
+    /// * `base` - The base value.
+    /// * `value` - The value to build.
+    ///
+    /// # Returns
+    ///
+    /// * `Vec<DeltaOp>` - The operations of the delta.
+    pub fn delta(base: &str, value: &str) -> Vec<DeltaOp> {
+        let mut prefix = base
+            .bytes()
+            .zip(value.bytes())
+            .take_while(|(a, b)| a == b)
+            .count();
+        while !value.is_char_boundary(prefix) {
+            prefix -= 1;
+        }
+        let mut suffix = base[prefix..]
+            .bytes()
+            .rev()
+            .zip(value[prefix..].bytes().rev())
+            .take_while(|(a, b)| a == b)
+            .count();
+        while !value.is_char_boundary(value.len() - suffix) {
+            suffix -= 1;
+        }
+        let mut ops = Vec::new();
+        if prefix > 0 {
+            ops.push(DeltaOp::Copy(0, prefix));
+        }
+        if value.len() > prefix + suffix {
+            ops.push(DeltaOp::Insert(
+                value[prefix..value.len() - suffix].to_string(),
+            ));
+        }
+        if suffix > 0 {
+            ops.push(DeltaOp::Copy(base.len() - suffix, suffix));
+        }
+        ops
+    }
+}
+
+/// Builds a value from the operations of a delta against a base value.
+fn apply_delta(base: &str, ops: &[DeltaOp]) -> Result<String, String> {
+    let mut value = String::new();
+    for op in ops {
+        match op {
+            DeltaOp::Copy(offset, len) => {
+                let part = offset
+                    .checked_add(*len)
+                    .and_then(|end| base.get(*offset..end))
+                    .ok_or_else(|| {
+                        format!(
+                            "copy of {} bytes at offset {} is outside the value",
+                            len, offset
+                        )
+                    })?;
+                value.push_str(part);
+            }
+            DeltaOp::Insert(text) => value.push_str(text),
+        }
+    }
+    Ok(value)
+}
+
+/// Computes the RFC 7396 merge patch turning one JSON value into another.
+fn merge_diff(base: &Value, value: &Value) -> Value {
+    let (Value::Object(base), Value::Object(value)) = (base, value) else {
+        return value.clone();
+    };
+    let mut patch: serde_json::Map<String, Value> = value
+        .iter()
+        .filter(|(field, changed)| base.get(*field) != Some(*changed))
+        .map(|(field, changed)| {
+            let change = match base.get(field) {
+                Some(original) => merge_diff(original, changed),
+                None => changed.clone(),
+            };
+            (field.clone(), change)
+        })
+        .collect();
+    for field in base.keys().filter(|field| !value.contains_key(*field)) {
+        patch.insert(field.clone(), Value::Null);
+    }
+    Value::Object(patch)
+}
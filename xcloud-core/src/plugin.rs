@@ -0,0 +1,159 @@
+use std::borrow::Cow;
+use std::sync::{Arc, PoisonError, RwLock};
+
+use async_trait::async_trait;
+
+/// A trait representing a compiled-in extension of the reads and writes of single keys.
+///
+/// Plugins are registered with [`crate::Server::with_plugin`] and run in registration order.
+/// The hooks apply to the reads, writes and deletes of single keys and batches of keys, by
+/// the routes as well as by operations built on them such as cloning and syncing. All hooks do
+/// nothing by default, so a plugin implements only those it needs.
+#[async_trait]
+pub trait Plugin: Send + Sync {
+    /// Returns the name of the plugin, used in logs.
+    fn name(&self) -> &str;
+
+    /// Runs before a value is written, e.g. to validate or enrich it.
+    ///
+    /// # Arguments
+    ///
+    /// * `table` - The table of the key.
+    /// * `key` - The key.
+    /// * `value` - The value about to be written, as returned by the previous plugin.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<String>` - The value to write instead, or `None` to keep it.
+    ///
+    /// # Errors
+    ///
+    /// This function will return the reason to reject the write with, which is reported to
+    /// the client as a bad request.
+    async fn on_before_write(
+        &self,
+        _table: &str,
+        _key: &str,
+        _value: &str,
+    ) -> Result<Option<String>, String> {
+        Ok(None)
+    }
+
+    /// Runs after a value is read, e.g. to enrich or mask it.
+    ///
+    /// # Arguments
+    ///
+    /// * `table` - The table of the key.
+    /// * `key` - The key.
+    /// * `value` - The value read, as returned by the previous plugin.
+    ///
+    /// # Returns
+    ///
+    /// * `String` - The value to return.
+    async fn on_after_read(&self, _table: &str, _key: &str, value: String) -> String {
+        value
+    }
+
+    /// Runs after a key is deleted, e.g. to replicate the deletion elsewhere.
+    ///
+    /// # Arguments
+    ///
+    /// * `table` - The table of the key.
+    /// * `key` - The deleted key.
+    async fn on_delete(&self, _table: &str, _key: &str) {}
+}
+
+/// A struct holding the registered plugins, shared by the server and its database.
+#[derive(Clone, Default)]
+pub struct Plugins {
+    plugins: Arc<RwLock<Vec<Arc<dyn Plugin>>>>,
+}
+
+/// Implementation of the `Plugins` struct.
+impl Plugins {
+    /// Registers a plugin, running after the plugins registered before it.
+    ///
+    /// # Arguments
+    ///
+    /// * `plugin` - The plugin.
+    pub fn register(&self, plugin: Arc<dyn Plugin>) {
+        log::info!("Registered plugin {}", plugin.name());
+        self.plugins
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .push(plugin);
+    }
+
+    /// Returns the registered plugins, so no lock is held while their hooks run.
+    fn snapshot(&self) -> Vec<Arc<dyn Plugin>> {
+        self.plugins
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    /// Runs the write hooks of all plugins.
+    ///
+    /// # Arguments
+    ///
+    /// * `table` - The table of the key.
+    /// * `key` - The key.
+    /// * `value` - The value about to be written.
+    ///
+    /// # Returns
+    ///
+    /// * `Cow<str>` - The value to write.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if a plugin rejects the write.
+    pub async fn before_write<'a>(
+        &self,
+        table: &str,
+        key: &str,
+        value: &'a str,
+    ) -> Result<Cow<'a, str>, sqlx::Error> {
+        let mut value = Cow::Borrowed(value);
+        for plugin in self.snapshot() {
+            match plugin.on_before_write(table, key, &value).await {
+                Ok(Some(replaced)) => value = Cow::Owned(replaced),
+                Ok(None) => {}
+                Err(reason) => {
+                    log::warn!("Plugin {} rejected a write: {}", plugin.name(), reason);
+                    return Err(sqlx::Error::InvalidArgument(reason));
+                }
+            }
+        }
+        Ok(value)
+    }
+
+    /// Runs the read hooks of all plugins.
+    ///
+    /// # Arguments
+    ///
+    /// * `table` - The table of the key.
+    /// * `key` - The key.
+    /// * `value` - The value read.
+    ///
+    /// # Returns
+    ///
+    /// * `String` - The value to return.
+    pub async fn after_read(&self, table: &str, key: &str, mut value: String) -> String {
+        for plugin in self.snapshot() {
+            value = plugin.on_after_read(table, key, value).await;
+        }
+        value
+    }
+
+    /// Runs the delete hooks of all plugins.
+    ///
+    /// # Arguments
+    ///
+    /// * `table` - The table of the key.
+    /// * `key` - The deleted key.
+    pub async fn deleted(&self, table: &str, key: &str) {
+        for plugin in self.snapshot() {
+            plugin.on_delete(table, key).await;
+        }
+    }
+}
@@ -0,0 +1,318 @@
+use std::collections::{BTreeMap, HashMap};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, PoisonError, RwLock};
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tokio::sync::Mutex;
+
+use crate::db::{Comment, Database, Lock};
+use crate::jobs::Job;
+
+/// The placeholder replacing the identities of an anonymized person.
+pub const ANONYMIZED: &str = "<anonymized>";
+
+/// An enum representing what a privacy request does with the located data.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum PrivacyAction {
+    /// Collects the data into a downloadable archive.
+    Export,
+    /// Irreversibly replaces the identities in the data and deletes the previous versions.
+    Anonymize,
+}
+
+/// An enum representing the state of a privacy request.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum PrivacyState {
+    Running,
+    Completed,
+    Failed,
+}
+
+/// A struct representing the report of what a privacy request located and touched.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct PrivacyReport {
+    pub id: u64,
+    pub action: PrivacyAction,
+    pub state: PrivacyState,
+    /// The keys located by table.
+    pub keys: BTreeMap<String, Vec<String>>,
+    /// The number of comments written by or mentioning the person.
+    pub comments: u64,
+    /// The number of leases held by the person.
+    pub locks: u64,
+    /// The tables under a legal hold, located but left untouched by an anonymization.
+    pub held_tables: Vec<String>,
+    /// The reason the request failed.
+    pub error: Option<String>,
+}
+
+/// A struct representing a row belonging to a person.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SubjectRecord {
+    pub table: String,
+    pub key: String,
+    pub value: Value,
+}
+
+/// A struct representing the machine-readable archive of the data of a person.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct SubjectArchive {
+    /// The identities of the person, e.g. their user id and email address.
+    pub identities: Vec<String>,
+    pub records: Vec<SubjectRecord>,
+    pub comments: Vec<Comment>,
+    pub locks: Vec<Lock>,
+}
+
+/// A struct keeping track of the privacy requests and the archives of the exports.
+#[derive(Clone, Default)]
+pub struct PrivacyRequests {
+    next_id: Arc<AtomicU64>,
+    reports: Arc<RwLock<HashMap<u64, PrivacyReport>>>,
+    archives: Arc<RwLock<HashMap<u64, SubjectArchive>>>,
+}
+
+/// Implementation of the `PrivacyRequests` struct.
+impl PrivacyRequests {
+    /// Registers a new running request.
+    ///
+    /// # Arguments
+    ///
+    /// * `action` - What the request does with the located data.
+    ///
+    /// # Returns
+    ///
+    /// * `PrivacyReport` - The report of the new request.
+    pub fn start(&self, action: PrivacyAction) -> PrivacyReport {
+        let report = PrivacyReport {
+            id: self.next_id.fetch_add(1, Ordering::Relaxed) + 1,
+            action,
+            state: PrivacyState::Running,
+            keys: BTreeMap::new(),
+            comments: 0,
+            locks: 0,
+            held_tables: Vec::new(),
+            error: None,
+        };
+        self.reports
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert(report.id, report.clone());
+        report
+    }
+
+    /// Returns the report of a request.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The id of the request.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<PrivacyReport>` - The report, or `None` if the request is unknown.
+    pub fn get(&self, id: u64) -> Option<PrivacyReport> {
+        self.reports
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(&id)
+            .cloned()
+    }
+
+    /// Returns the archive of a completed export.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The id of the request.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<SubjectArchive>` - The archive, or `None` if the export is unknown or running.
+    pub fn archive(&self, id: u64) -> Option<SubjectArchive> {
+        self.archives
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(&id)
+            .cloned()
+    }
+
+    /// Applies a change to the report of a request.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The id of the request.
+    /// * `change` - The change to apply.
+    fn update(&self, id: u64, change: impl FnOnce(&mut PrivacyReport)) {
+        if let Some(report) = self
+            .reports
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get_mut(&id)
+        {
+            change(report);
+        }
+    }
+}
+
+/// A job locating the data of a person in the rows of all tables, the comments and the leases,
+/// then exporting or anonymizing it.
+///
+/// A row belongs to the person if one of the owner fields of its JSON value holds one of
+/// their identities. Anonymizing replaces every string equal to an identity in those rows, and
+/// the identities in the authors and texts of comments, with [`ANONYMIZED`], releases their
+/// leases and deletes the history of the rows. Snapshots are point-in-time copies and are left
+/// as they are.
+pub struct PrivacyJob {
+    pub db: Arc<Mutex<Database>>,
+    pub requests: PrivacyRequests,
+    pub id: u64,
+    pub action: PrivacyAction,
+    /// The identities of the person, e.g. their user id and email address.
+    pub identities: Vec<String>,
+    /// The top-level fields of JSON values naming the person a row belongs to.
+    pub owner_fields: Vec<String>,
+}
+
+/// Implementation of the `PrivacyJob` struct.
+impl PrivacyJob {
+    /// Returns whether a value belongs to the person.
+    fn owns(&self, value: &Value) -> bool {
+        self.owner_fields.iter().any(|field| {
+            value
+                .get(field)
+                .and_then(Value::as_str)
+                .is_some_and(|owner| self.identities.iter().any(|identity| identity == owner))
+        })
+    }
+
+    /// Replaces every string equal to an identity of the person, at any depth.
+    fn scrub(&self, value: &mut Value) {
+        match value {
+            Value::String(text) if self.identities.contains(text) => {
+                *text = ANONYMIZED.to_string();
+            }
+            Value::Array(items) => items.iter_mut().for_each(|item| self.scrub(item)),
+            Value::Object(fields) => fields.values_mut().for_each(|field| self.scrub(field)),
+            _ => {}
+        }
+    }
+
+    /// Locates the rows belonging to the person in all tables.
+    async fn records(&self) -> Result<Vec<SubjectRecord>, sqlx::Error> {
+        let mut records = Vec::new();
+        let tables = self.db.lock().await.list_tables().await?;
+        for table in tables {
+            let (_, mut rows) = self.db.lock().await.consistent_export(&table).await?;
+            while let Some(row) = rows.recv().await {
+                let (key, value) = row?;
+                let Ok(value) = serde_json::from_str::<Value>(&value) else {
+                    continue;
+                };
+                if self.owns(&value) {
+                    records.push(SubjectRecord {
+                        table: table.clone(),
+                        key,
+                        value,
+                    });
+                }
+            }
+        }
+        Ok(records)
+    }
+
+    /// Locates the data of the person, then exports or anonymizes it.
+    async fn process(&self) -> Result<(), sqlx::Error> {
+        let records = self.records().await?;
+        let db = self.db.lock().await;
+        let comments = db.comments_of(&self.identities).await?;
+        let locks = db.locks_of(&self.identities).await?;
+        let mut keys: BTreeMap<String, Vec<String>> = BTreeMap::new();
+        for record in &records {
+            keys.entry(record.table.clone())
+                .or_default()
+                .push(record.key.clone());
+        }
+        let mut held_tables = Vec::new();
+        for table in keys.keys() {
+            if db.legal_hold(table).await?.is_some() {
+                held_tables.push(table.clone());
+            }
+        }
+        self.requests.update(self.id, |report| {
+            report.keys = keys;
+            report.comments = comments.len() as u64;
+            report.locks = locks.len() as u64;
+            report.held_tables = held_tables.clone();
+        });
+        match self.action {
+            PrivacyAction::Export => {
+                let archive = SubjectArchive {
+                    identities: self.identities.clone(),
+                    records,
+                    comments,
+                    locks,
+                };
+                self.requests
+                    .archives
+                    .write()
+                    .unwrap_or_else(PoisonError::into_inner)
+                    .insert(self.id, archive);
+            }
+            PrivacyAction::Anonymize => {
+                for mut record in records {
+                    if held_tables.contains(&record.table) {
+                        continue;
+                    }
+                    self.scrub(&mut record.value);
+                    db.set_data(&record.table, &record.key, &record.value.to_string())
+                        .await?;
+                    db.purge_history(&record.table, &record.key).await?;
+                }
+                db.anonymize_comments(&self.identities, ANONYMIZED).await?;
+                db.release_locks_of(&self.identities).await?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Job for PrivacyJob {
+    fn name(&self) -> String {
+        format!("privacy request {}", self.id)
+    }
+
+    async fn run(&self) -> Result<(), String> {
+        self.requests.update(self.id, |report| {
+            report.state = PrivacyState::Running;
+            report.error = None;
+        });
+        if let Err(e) = self.process().await {
+            let error = e.to_string();
+            self.requests.update(self.id, |report| {
+                report.state = PrivacyState::Failed;
+                report.error = Some(error.clone());
+            });
+            return Err(error);
+        }
+        let report = self.requests.get(self.id);
+        self.requests.update(self.id, |report| {
+            report.state = PrivacyState::Completed;
+        });
+        if let Some(report) = report {
+            tracing::info!(
+                target: "audit",
+                id = self.id,
+                action = ?self.action,
+                keys = report.keys.values().map(Vec::len).sum::<usize>(),
+                comments = report.comments,
+                locks = report.locks,
+                "Privacy request completed"
+            );
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,137 @@
+use serde_json::{Map, Value};
+
+/// The largest number of fields a projection may select.
+const MAX_FIELDS: usize = 64;
+
+/// A struct selecting fields of the JSON documents returned to clients.
+///
+/// Fields are dotted paths, e.g. `name,properties.height`. The selected fields keep their
+/// position in the document and any other field is left out, so `properties.height` of
+/// `{"name": "W1", "properties": {"height": 3, "width": 1}}` returns
+/// `{"properties": {"height": 3}}`. A path crossing an array selects the field in every
+/// element. Values stored as strings holding a JSON document are projected as that document,
+/// while other values are returned unchanged.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Projection {
+    paths: Vec<Vec<String>>,
+}
+
+/// Implementation of the `Projection` struct.
+impl Projection {
+    /// Parses a comma-separated list of dotted paths.
+    ///
+    /// # Arguments
+    ///
+    /// * `fields` - The fields as requested, e.g. `name,properties.height`.
+    ///
+    /// # Returns
+    ///
+    /// * `Projection` - The projection selecting the fields.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if no field is given, a path has an empty segment
+    /// or too many fields are given.
+    pub fn parse(fields: &str) -> Result<Self, String> {
+        let mut paths: Vec<Vec<String>> = Vec::new();
+        for field in fields.split(',').map(str::trim) {
+            let path: Vec<String> = field.split('.').map(str::to_string).collect();
+            if path.iter().any(String::is_empty) {
+                return Err(format!("invalid field: '{}'", field));
+            }
+            if !paths.contains(&path) {
+                paths.push(path);
+            }
+        }
+        if paths.len() > MAX_FIELDS {
+            return Err(format!("at most {} fields may be selected", MAX_FIELDS));
+        }
+        Ok(Projection { paths })
+    }
+
+    /// Parses the fields of a request, if any.
+    ///
+    /// # Arguments
+    ///
+    /// * `fields` - The fields as requested, if any.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<Projection>` - The projection, or `None` to return whole documents.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the fields are invalid.
+    pub fn from_request(fields: Option<&str>) -> Result<Option<Self>, String> {
+        fields.map(Self::parse).transpose()
+    }
+
+    /// Selects the fields of a value.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The value as returned without a projection.
+    ///
+    /// # Returns
+    ///
+    /// * `Value` - The document with the selected fields only, or the value unchanged if it is
+    ///   not a JSON document.
+    pub fn apply(&self, value: Value) -> Value {
+        let document = match value {
+            Value::String(ref raw) => match serde_json::from_str::<Value>(raw) {
+                Ok(document) if document.is_object() || document.is_array() => document,
+                _ => return value,
+            },
+            Value::Object(_) | Value::Array(_) => value,
+            _ => return value,
+        };
+        let paths: Vec<&[String]> = self.paths.iter().map(Vec::as_slice).collect();
+        match Self::project(&document, &paths) {
+            Some(projected) => projected,
+            None if document.is_array() => Value::Array(Vec::new()),
+            None => Value::Object(Map::new()),
+        }
+    }
+
+    /// Selects the fields at the given paths of a value.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<Value>` - The selected fields, or `None` if no path leads to a field.
+    fn project(value: &Value, paths: &[&[String]]) -> Option<Value> {
+        if paths.iter().any(|path| path.is_empty()) {
+            return Some(value.clone());
+        }
+        match value {
+            Value::Object(object) => {
+                let mut projected = Map::new();
+                for (index, path) in paths.iter().enumerate() {
+                    let field = &path[0];
+                    if paths[..index].iter().any(|other| other[0] == *field) {
+                        continue;
+                    }
+                    let rest: Vec<&[String]> = paths
+                        .iter()
+                        .filter(|other| other[0] == *field)
+                        .map(|other| &other[1..])
+                        .collect();
+                    if let Some(child) = object
+                        .get(field)
+                        .and_then(|child| Self::project(child, &rest))
+                    {
+                        projected.insert(field.clone(), child);
+                    }
+                }
+                (!projected.is_empty()).then_some(Value::Object(projected))
+            }
+            Value::Array(items) => {
+                let projected: Vec<Value> = items
+                    .iter()
+                    .filter_map(|item| Self::project(item, paths))
+                    .collect();
+                (!projected.is_empty()).then_some(Value::Array(projected))
+            }
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,273 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use tokio::sync::Mutex;
+
+use crate::cluster::Cluster;
+use crate::config::{ConflictPolicy, ReplicationConfig};
+use crate::db::{Database, OutboxEntry};
+use crate::webhooks::{Webhooks, SIGNATURE_HEADER};
+
+/// The name of the setting persisting the id of the last replicated write.
+const CURSOR_SETTING: &str = "replication_cursor";
+
+/// The name of the setting persisting the id the instance sends its batches under.
+const ORIGIN_SETTING: &str = "replication_origin";
+
+/// The prefix of the settings persisting the id of the last write applied from each peer.
+const APPLIED_SETTING_PREFIX: &str = "replication_applied:";
+
+/// A struct representing a batch of writes sent to the peer.
+#[derive(Serialize, Deserialize)]
+pub struct ReplicationBatch {
+    /// The id of the sending instance, which the peer tracks the applied writes of.
+    pub origin: String,
+    pub entries: Vec<OutboxEntry>,
+}
+
+/// A struct shipping the writes recorded in the outbox to the peer instance.
+///
+/// The id of the last acknowledged write is persisted, so shipping resumes
+/// where it stopped after a restart or an outage of the peer.
+pub struct Replicator {
+    db: Arc<Mutex<Database>>,
+    client: reqwest::Client,
+    config: ReplicationConfig,
+}
+
+/// Implementation of the `Replicator` struct.
+impl Replicator {
+    /// Creates a new instance of the Replicator.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - A reference to the database wrapped in an Arc and Mutex for thread safety.
+    /// * `config` - The replication configuration.
+    ///
+    /// # Returns
+    ///
+    /// * `Replicator` - A new instance of the Replicator.
+    pub fn new(db: Arc<Mutex<Database>>, config: ReplicationConfig) -> Self {
+        Replicator {
+            db,
+            client: reqwest::Client::new(),
+            config,
+        }
+    }
+
+    /// Ships the outbox in the background, polling it at the configured interval, while the
+    /// instance leads the cluster.
+    ///
+    /// # Arguments
+    ///
+    /// * `cluster` - The cluster electing the instance running the background jobs.
+    pub fn spawn(self, cluster: Arc<Cluster>) {
+        tokio::spawn(async move {
+            let mut interval =
+                tokio::time::interval(Duration::from_secs(self.config.interval_secs.max(1)));
+            loop {
+                interval.tick().await;
+                if !cluster.is_leader() {
+                    continue;
+                }
+                match self.ship().await {
+                    Ok(0) => {}
+                    Ok(count) => log::debug!("Replicated {} write(s)", count),
+                    Err(e) => log::warn!("Replication failed, retrying later: {}", e),
+                }
+            }
+        });
+    }
+
+    /// Sends the next batch of writes to the peer and advances the cursor once it is acknowledged.
+    ///
+    /// # Returns
+    ///
+    /// * `usize` - The number of replicated writes.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the batch cannot be delivered.
+    pub async fn ship(&self) -> Result<usize, String> {
+        let Some(peer) = &self.config.peer else {
+            return Ok(0);
+        };
+        let (origin, entries) = {
+            let db = self.db.lock().await;
+            let origin = Self::origin(&db).await.map_err(|e| e.to_string())?;
+            let cursor = db
+                .get_setting(CURSOR_SETTING)
+                .await
+                .map_err(|e| e.to_string())?
+                .and_then(|cursor| cursor.parse().ok())
+                .unwrap_or(0);
+            let entries = db
+                .outbox_after(cursor, self.config.batch_size)
+                .await
+                .map_err(|e| e.to_string())?;
+            (origin, entries)
+        };
+        let Some(last) = entries.last().map(|entry| entry.id) else {
+            return Ok(0);
+        };
+        let count = entries.len();
+        let body = serde_json::to_string(&ReplicationBatch { origin, entries })
+            .map_err(|e| e.to_string())?;
+        let response = self
+            .client
+            .post(format!("{}/replication/apply", peer.trim_end_matches('/')))
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .header(
+                SIGNATURE_HEADER,
+                format!("sha256={}", Webhooks::sign(&self.config.secret, &body)),
+            )
+            .body(body)
+            .send()
+            .await
+            .map_err(|e| e.to_string())?;
+        if !response.status().is_success() {
+            return Err(format!("unexpected status {}", response.status()));
+        }
+        let db = self.db.lock().await;
+        db.set_setting(CURSOR_SETTING, &last.to_string())
+            .await
+            .map_err(|e| e.to_string())?;
+        db.prune_outbox(last).await.map_err(|e| e.to_string())?;
+        Ok(count)
+    }
+
+    /// Returns the id the instance sends its batches under, generating it on first use.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - The local database persisting the id.
+    ///
+    /// # Returns
+    ///
+    /// * `String` - The id of the instance.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the id cannot be read or persisted.
+    async fn origin(db: &Database) -> Result<String, sqlx::Error> {
+        if let Some(origin) = db.get_setting(ORIGIN_SETTING).await? {
+            return Ok(origin);
+        }
+        let origin = uuid::Uuid::now_v7().to_string();
+        db.set_setting(ORIGIN_SETTING, &origin).await?;
+        Ok(origin)
+    }
+
+    /// Returns the id of the last write applied from a peer, so a batch sent again is not
+    /// applied twice.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - The local database.
+    /// * `origin` - The id of the peer.
+    ///
+    /// # Returns
+    ///
+    /// * `i64` - The id of the last applied write, or `0` if none was applied.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the id cannot be read.
+    pub async fn last_applied(db: &Database, origin: &str) -> Result<i64, sqlx::Error> {
+        Ok(db
+            .get_setting(&format!("{}{}", APPLIED_SETTING_PREFIX, origin))
+            .await?
+            .and_then(|id| id.parse().ok())
+            .unwrap_or(0))
+    }
+
+    /// Records the id of the last write applied from a peer.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - The local database.
+    /// * `origin` - The id of the peer.
+    /// * `id` - The id of the applied write.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the id cannot be persisted.
+    pub async fn record_applied(db: &Database, origin: &str, id: i64) -> Result<(), sqlx::Error> {
+        db.set_setting(
+            &format!("{}{}", APPLIED_SETTING_PREFIX, origin),
+            &id.to_string(),
+        )
+        .await
+    }
+
+    /// Verifies the signature of a received batch.
+    ///
+    /// # Arguments
+    ///
+    /// * `secret` - The secret shared with the sending instance.
+    /// * `body` - The raw body of the request.
+    /// * `signature` - The value of the signature header, e.g. `sha256=<hex>`.
+    ///
+    /// # Returns
+    ///
+    /// * `bool` - Whether the signature is valid.
+    pub fn verify(secret: &str, body: &[u8], signature: Option<&str>) -> bool {
+        let Some(signature) = signature
+            .and_then(|signature| signature.strip_prefix("sha256="))
+            .and_then(|signature| hex::decode(signature).ok())
+        else {
+            return false;
+        };
+        let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes())
+            .expect("HMAC accepts keys of any size");
+        mac.update(body);
+        mac.verify_slice(&signature).is_ok()
+    }
+
+    /// Applies a replicated write to the local database.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - The local database.
+    /// * `entry` - The replicated write.
+    /// * `policy` - How writes to keys that already exist locally are resolved.
+    ///
+    /// # Returns
+    ///
+    /// * `bool` - Whether the write was applied or skipped by the conflict policy.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the write cannot be applied.
+    pub async fn apply(
+        db: &Database,
+        entry: &OutboxEntry,
+        policy: ConflictPolicy,
+    ) -> Result<bool, sqlx::Error> {
+        let key = entry.key.as_deref().unwrap_or_default();
+        let value = entry.value.as_deref().unwrap_or_default();
+        if policy == ConflictPolicy::TargetWins {
+            return match entry.op.as_str() {
+                "set" | "update" if db.get_data(&entry.table, key).await?.is_none() => {
+                    db.set_data(&entry.table, key, value).await?;
+                    Ok(true)
+                }
+                _ => Ok(false),
+            };
+        }
+        match entry.op.as_str() {
+            "set" => db.set_data(&entry.table, key, value).await?,
+            "update" => db.update_data(&entry.table, key, value).await?,
+            "delete" => db.delete_data(&entry.table, key).await?,
+            "delete_table" => db.delete_table(&entry.table).await?,
+            op => {
+                log::warn!("Skipping replicated write with unknown operation '{}'", op);
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+}
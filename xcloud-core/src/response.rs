@@ -0,0 +1,67 @@
+use serde::{Deserialize, Serialize};
+
+use crate::i18n::Catalog;
+
+/// A struct representing the response of an API request.
+#[derive(Serialize, Deserialize)]
+pub struct ApiResponse<T> {
+    pub status: String,
+    /// The stable code of the error, independent of the language of the message.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub code: Option<String>,
+    pub message: String,
+    pub data: Option<T>,
+}
+
+/// Implementation of the `ApiResponse` struct.
+impl<T> ApiResponse<T> {
+    /// Creates a new error response carrying data, e.g. the conflicting value.
+    ///
+    /// # Arguments
+    ///
+    /// * `code` - The code of the error, whose message is built in the language of the request.
+    /// * `args` - The values of the variables of the message.
+    /// * `data` - The data of the response.
+    ///
+    /// # Returns
+    ///
+    /// * `ApiResponse<T>` - A new error response.
+    pub fn error_with_data(code: &str, args: &[(&str, &str)], data: Option<T>) -> Self {
+        ApiResponse {
+            status: "error".to_string(),
+            code: Some(code.to_string()),
+            message: Catalog::bundled().message(code, args),
+            data,
+        }
+    }
+}
+
+/// Implementation of the `ApiResponse` struct.
+impl ApiResponse<()> {
+    /// Creates a new error response without data.
+    ///
+    /// # Arguments
+    ///
+    /// * `code` - The code of the error, whose message is built in the language of the request.
+    ///
+    /// # Returns
+    ///
+    /// * `ApiResponse<()>` - A new error response.
+    pub fn error(code: &str) -> Self {
+        Self::error_with_data(code, &[], None)
+    }
+
+    /// Creates a new error response without data whose message has variables.
+    ///
+    /// # Arguments
+    ///
+    /// * `code` - The code of the error, whose message is built in the language of the request.
+    /// * `args` - The values of the variables of the message.
+    ///
+    /// # Returns
+    ///
+    /// * `ApiResponse<()>` - A new error response.
+    pub fn error_with(code: &str, args: &[(&str, &str)]) -> Self {
+        Self::error_with_data(code, args, None)
+    }
+}
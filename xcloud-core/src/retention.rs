@@ -0,0 +1,94 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::sync::Mutex;
+
+use crate::cluster::Cluster;
+use crate::config::RetentionConfig;
+use crate::db::Database;
+
+/// A struct periodically pruning the rows exceeding the retention policies of their tables.
+pub struct RetentionSweeper {
+    db: Arc<Mutex<Database>>,
+    config: RetentionConfig,
+}
+
+/// Implementation of the `RetentionSweeper` struct.
+impl RetentionSweeper {
+    /// Creates a new instance of the RetentionSweeper.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - A reference to the database wrapped in an Arc and Mutex for thread safety.
+    /// * `config` - The retention configuration holding the sweep interval.
+    ///
+    /// # Returns
+    ///
+    /// * `RetentionSweeper` - A new instance of the RetentionSweeper.
+    pub fn new(db: Arc<Mutex<Database>>, config: RetentionConfig) -> Self {
+        RetentionSweeper { db, config }
+    }
+
+    /// Applies the retention policies in the background at the configured interval, while the
+    /// instance leads the cluster.
+    ///
+    /// # Arguments
+    ///
+    /// * `cluster` - The cluster electing the instance running the background jobs.
+    pub fn spawn(self, cluster: Arc<Cluster>) {
+        tokio::spawn(async move {
+            let mut interval =
+                tokio::time::interval(Duration::from_secs(self.config.sweep_interval_secs.max(1)));
+            loop {
+                interval.tick().await;
+                if !cluster.is_leader() {
+                    continue;
+                }
+                if let Err(e) = self.sweep().await {
+                    log::error!("Failed to apply retention policies: {}", e);
+                }
+            }
+        });
+    }
+
+    /// Applies the retention policy of every table and the retention of every event log, and
+    /// downsamples the time series, once.
+    ///
+    /// Every pruned row, and the number of pruned events and downsampled samples, is reported
+    /// on the `audit` log target.
+    ///
+    /// # Returns
+    ///
+    /// * `usize` - The total number of pruned rows and events and downsampled samples.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if a policy cannot be applied.
+    pub async fn sweep(&self) -> Result<usize, sqlx::Error> {
+        let db = self.db.lock().await;
+        let mut total = 0;
+        for policy in db.table_policies().await? {
+            let keys = db.apply_table_policy(&policy).await?;
+            if keys.is_empty() {
+                continue;
+            }
+            tracing::info!(
+                target: "audit",
+                table = %policy.table,
+                count = keys.len(),
+                keys = ?keys,
+                "Pruned rows by retention policy"
+            );
+            total += keys.len();
+        }
+        let events = db.prune_event_logs().await?;
+        if events > 0 {
+            tracing::info!(target: "audit", count = events, "Pruned events by event log retention");
+        }
+        let points = db.downsample_time_series().await?;
+        if points > 0 {
+            tracing::info!(target: "audit", count = points, "Downsampled time series samples");
+        }
+        Ok(total + events as usize + points as usize)
+    }
+}
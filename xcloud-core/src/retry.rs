@@ -0,0 +1,99 @@
+use std::collections::hash_map::RandomState;
+use std::future::Future;
+use std::hash::{BuildHasher, Hasher};
+use std::time::Duration;
+
+use crate::config::RetryConfig;
+
+/// The SQLite result codes of a database that is temporarily busy or locked.
+const SQLITE_BUSY: i32 = 5;
+const SQLITE_LOCKED: i32 = 6;
+
+/// A struct retrying database operations failing with transient errors.
+///
+/// Attempts are spaced by an exponential backoff with full jitter, capped at the configured
+/// maximum delay.
+pub struct RetryPolicy {
+    config: RetryConfig,
+}
+
+/// Implementation of the `RetryPolicy` struct.
+impl RetryPolicy {
+    /// Creates a new retry policy.
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - The retry configuration holding the budget and the delays.
+    ///
+    /// # Returns
+    ///
+    /// * `RetryPolicy` - A new instance of the RetryPolicy.
+    pub fn new(config: RetryConfig) -> Self {
+        RetryPolicy { config }
+    }
+
+    /// Returns whether an error is transient, so the operation may succeed when retried.
+    ///
+    /// # Arguments
+    ///
+    /// * `error` - The error of the operation.
+    ///
+    /// # Returns
+    ///
+    /// * `bool` - Whether the error is a lost connection, an exhausted pool or a busy database.
+    pub fn is_transient(error: &sqlx::Error) -> bool {
+        match error {
+            sqlx::Error::Io(_) | sqlx::Error::PoolTimedOut => true,
+            sqlx::Error::Database(e) => e
+                .code()
+                .and_then(|code| code.parse::<i32>().ok())
+                .is_some_and(|code| matches!(code & 0xff, SQLITE_BUSY | SQLITE_LOCKED)),
+            _ => false,
+        }
+    }
+
+    /// Runs an operation, retrying it while it fails with a transient error and the budget lasts.
+    ///
+    /// # Arguments
+    ///
+    /// * `operation` - The operation, called once per attempt.
+    ///
+    /// # Errors
+    ///
+    /// This function will return the error of the last attempt.
+    pub async fn run<T, F, Fut>(&self, mut operation: F) -> Result<T, sqlx::Error>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, sqlx::Error>>,
+    {
+        let mut attempt = 1;
+        loop {
+            match operation().await {
+                Err(e) if attempt < self.config.max_attempts && Self::is_transient(&e) => {
+                    let delay = self.delay(attempt);
+                    log::warn!(
+                        "Transient database error, retrying in {:?} (attempt {}/{}): {}",
+                        delay,
+                        attempt,
+                        self.config.max_attempts,
+                        e
+                    );
+                    tokio::time::sleep(delay).await;
+                    attempt += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    /// Returns the jittered delay before the attempt following the given one.
+    fn delay(&self, attempt: u32) -> Duration {
+        let ceiling = self
+            .config
+            .base_delay_ms
+            .saturating_mul(1 << attempt.saturating_sub(1).min(16))
+            .min(self.config.max_delay_ms);
+        let jitter = RandomState::new().build_hasher().finish();
+        Duration::from_millis(jitter % (ceiling + 1))
+    }
+}
@@ -0,0 +1,102 @@
+use serde::{Deserialize, Serialize};
+
+use crate::config::RuntimeConfig;
+
+/// The blocking threads actix shares between its HTTP workers when not configured.
+const DEFAULT_BLOCKING_THREAD_BUDGET: usize = 512;
+
+/// A struct representing the activity of a thread of a tokio runtime.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct RuntimeWorker {
+    pub index: usize,
+    /// The time in milliseconds the thread spent polling tasks.
+    pub busy_ms: u64,
+    /// The number of times the thread parked for lack of work.
+    pub park_count: u64,
+}
+
+/// A struct representing the state of the tokio runtime serving a request.
+///
+/// Every HTTP worker runs its own single-threaded runtime with its own blocking pool, so the
+/// figures describe the worker that served the request. A deep queue or a worker that is
+/// never parked indicates the worker is starved.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct RuntimeStats {
+    /// The number of HTTP workers the server runs.
+    pub http_workers: usize,
+    /// The maximum number of threads of the blocking pool of every HTTP worker.
+    pub max_blocking_threads: usize,
+    /// The number of threads of the runtime.
+    pub runtime_workers: usize,
+    /// The number of tasks of the runtime not completed yet.
+    pub alive_tasks: usize,
+    /// The number of tasks waiting in the queue shared by the threads of the runtime.
+    pub queued_tasks: usize,
+    pub workers: Vec<RuntimeWorker>,
+}
+
+/// Implementation of the `RuntimeStats` struct.
+impl RuntimeStats {
+    /// Collects the state of the current runtime.
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - The runtime configuration the server was started with.
+    ///
+    /// # Returns
+    ///
+    /// * `RuntimeStats` - The state of the runtime, must be called from within one.
+    pub fn collect(config: &RuntimeConfig) -> Self {
+        let metrics = tokio::runtime::Handle::current().metrics();
+        RuntimeStats {
+            http_workers: http_workers(config),
+            max_blocking_threads: max_blocking_threads(config),
+            runtime_workers: metrics.num_workers(),
+            alive_tasks: metrics.num_alive_tasks(),
+            queued_tasks: metrics.global_queue_depth(),
+            workers: (0..metrics.num_workers())
+                .map(|index| RuntimeWorker {
+                    index,
+                    busy_ms: metrics.worker_total_busy_duration(index).as_millis() as u64,
+                    park_count: metrics.worker_park_count(index),
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Returns the number of HTTP workers, one per available core unless configured.
+///
+/// # Arguments
+///
+/// * `config` - The runtime configuration.
+///
+/// # Returns
+///
+/// * `usize` - The number of HTTP workers, at least one.
+pub fn http_workers(config: &RuntimeConfig) -> usize {
+    config
+        .http_workers
+        .unwrap_or_else(|| std::thread::available_parallelism().map_or(1, |n| n.get()))
+        .max(1)
+}
+
+/// Returns the maximum number of threads of the blocking pool of every HTTP worker, the
+/// blocking thread budget of actix split between the available cores unless configured.
+///
+/// # Arguments
+///
+/// * `config` - The runtime configuration.
+///
+/// # Returns
+///
+/// * `usize` - The maximum number of blocking threads, at least one.
+pub fn max_blocking_threads(config: &RuntimeConfig) -> usize {
+    config
+        .max_blocking_threads
+        .unwrap_or_else(|| {
+            DEFAULT_BLOCKING_THREAD_BUDGET
+                / std::thread::available_parallelism().map_or(1, |n| n.get())
+        })
+        .max(1)
+}
@@ -0,0 +1,254 @@
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// A struct representing a way in which a value does not match the schema of its table.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct SchemaViolation {
+    /// The JSON Pointer of the offending part of the value, empty for the whole value.
+    pub path: String,
+    pub message: String,
+}
+
+/// Implementation of the `SchemaViolation` struct.
+impl SchemaViolation {
+    /// Joins the given violations into a single description.
+    ///
+    /// # Arguments
+    ///
+    /// * `violations` - The violations to describe.
+    ///
+    /// # Returns
+    ///
+    /// * `String` - The violations, each prefixed with its path if it has one.
+    pub fn describe(violations: &[SchemaViolation]) -> String {
+        violations
+            .iter()
+            .map(|violation| match violation.path.as_str() {
+                "" => violation.message.clone(),
+                path => format!("{}: {}", path, violation.message),
+            })
+            .collect::<Vec<_>>()
+            .join("; ")
+    }
+}
+
+/// An enum representing the declared type of the values of a table.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum ValueType {
+    #[default]
+    String,
+    Integer,
+    Float,
+    Json,
+    Blob,
+}
+
+/// Implementation of the `ValueType` enum.
+impl ValueType {
+    /// Returns the name under which the type is stored.
+    ///
+    /// # Returns
+    ///
+    /// * `&str` - The name of the type.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ValueType::String => "string",
+            ValueType::Integer => "integer",
+            ValueType::Float => "float",
+            ValueType::Json => "json",
+            ValueType::Blob => "blob",
+        }
+    }
+
+    /// Parses a type from its stored name.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the type.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<ValueType>` - The type, or `None` if the name is unknown.
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "string" => Some(ValueType::String),
+            "integer" => Some(ValueType::Integer),
+            "float" => Some(ValueType::Float),
+            "json" => Some(ValueType::Json),
+            "blob" => Some(ValueType::Blob),
+            _ => None,
+        }
+    }
+
+    /// Validates that the given raw value matches the type.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The raw value to validate.
+    ///
+    /// # Errors
+    ///
+    /// This function will return a description of the mismatch if the value is invalid.
+    pub fn validate(&self, value: &str) -> Result<(), String> {
+        match self {
+            ValueType::String => Ok(()),
+            ValueType::Integer => value
+                .trim()
+                .parse::<i64>()
+                .map(|_| ())
+                .map_err(|_| format!("'{}' is not an integer", value)),
+            ValueType::Float => value
+                .trim()
+                .parse::<f64>()
+                .ok()
+                .filter(|f| f.is_finite())
+                .map(|_| ())
+                .ok_or_else(|| format!("'{}' is not a finite float", value)),
+            ValueType::Json => serde_json::from_str::<Value>(value)
+                .map(|_| ())
+                .map_err(|e| format!("value is not valid JSON: {}", e)),
+            ValueType::Blob => base64::engine::general_purpose::STANDARD
+                .decode(value)
+                .map(|_| ())
+                .map_err(|e| format!("value is not valid base64: {}", e)),
+        }
+    }
+
+    /// Converts the given raw stored value into a typed JSON value.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The raw stored value.
+    ///
+    /// # Returns
+    ///
+    /// * `Value` - The typed JSON value, falling back to a string if the value cannot be converted.
+    pub fn to_json(&self, value: &str) -> Value {
+        let typed = match self {
+            ValueType::String | ValueType::Blob => None,
+            ValueType::Integer => value.trim().parse::<i64>().ok().map(Value::from),
+            ValueType::Float => value
+                .trim()
+                .parse::<f64>()
+                .ok()
+                .and_then(serde_json::Number::from_f64)
+                .map(Value::Number),
+            ValueType::Json => serde_json::from_str(value).ok(),
+        };
+        typed.unwrap_or_else(|| Value::String(value.to_string()))
+    }
+}
+
+/// A struct representing the declared schema of a table.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct TableSchema {
+    pub table: String,
+    pub value_type: ValueType,
+    /// Whether keys differing only by case are distinct keys, the configured key collation
+    /// of new tables if not given. Changing it for an existing table rebuilds the table.
+    #[serde(default)]
+    pub case_sensitive: Option<bool>,
+}
+
+/// A struct representing a JSON Schema attached to a table.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct JsonSchema {
+    pub table: String,
+    pub schema: Value,
+}
+
+/// A struct validating values against the declared value type and JSON Schema of a table.
+pub struct ValueValidator {
+    value_type: ValueType,
+    schema: Option<jsonschema::Validator>,
+}
+
+/// Implementation of the `ValueValidator` struct.
+impl ValueValidator {
+    /// Creates a new instance of the ValueValidator checking only the value type.
+    ///
+    /// # Arguments
+    ///
+    /// * `value_type` - The declared value type of the table.
+    ///
+    /// # Returns
+    ///
+    /// * `ValueValidator` - A new instance of the ValueValidator.
+    pub fn new(value_type: ValueType) -> Self {
+        ValueValidator {
+            value_type,
+            schema: None,
+        }
+    }
+
+    /// Additionally checks the values against a JSON Schema.
+    ///
+    /// # Arguments
+    ///
+    /// * `schema` - The JSON Schema of the table.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<ValueValidator, String>` - The validator, or the reason the schema is invalid.
+    pub fn with_schema(self, schema: &Value) -> Result<Self, String> {
+        let schema = jsonschema::validator_for(schema).map_err(|e| e.to_string())?;
+        Ok(ValueValidator {
+            schema: Some(schema),
+            ..self
+        })
+    }
+
+    /// Returns the declared value type of the table.
+    ///
+    /// # Returns
+    ///
+    /// * `ValueType` - The value type checked by the validator.
+    pub fn value_type(&self) -> ValueType {
+        self.value_type
+    }
+
+    /// Validates that the given raw value matches the value type and JSON Schema.
+    ///
+    /// Values are validated against the JSON Schema as typed by [`ValueType::to_json`], so a
+    /// schema can constrain the values of tables of any type. Values of string tables are
+    /// validated as the JSON they hold, or as a JSON string if they hold no valid JSON.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The raw value to validate.
+    ///
+    /// # Errors
+    ///
+    /// This function will return every violation if the value is invalid.
+    pub fn validate(&self, value: &str) -> Result<(), Vec<SchemaViolation>> {
+        self.value_type.validate(value).map_err(|message| {
+            vec![SchemaViolation {
+                path: String::new(),
+                message,
+            }]
+        })?;
+        let Some(schema) = &self.schema else {
+            return Ok(());
+        };
+        let instance = match self.value_type {
+            ValueType::String => {
+                serde_json::from_str(value).unwrap_or_else(|_| Value::String(value.to_string()))
+            }
+            value_type => value_type.to_json(value),
+        };
+        let violations: Vec<SchemaViolation> = schema
+            .iter_errors(&instance)
+            .map(|error| SchemaViolation {
+                path: error.instance_path.to_string(),
+                message: error.to_string(),
+            })
+            .collect();
+        if violations.is_empty() {
+            Ok(())
+        } else {
+            Err(violations)
+        }
+    }
+}
@@ -0,0 +1,214 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::config::SeedConfig;
+use crate::db::Database;
+use crate::errors::AppError;
+use crate::schema::{JsonSchema, TableSchema, ValueType};
+use crate::utils::Utils;
+
+/// A struct representing a fixture file of the seeds directory.
+#[derive(Serialize, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct Fixture {
+    pub tables: Vec<TableFixture>,
+}
+
+/// A struct representing a table and the keys seeded into it.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct TableFixture {
+    pub name: String,
+    /// The declared type of the values, applied if the table is created by the seed.
+    #[serde(default)]
+    pub value_type: Option<ValueType>,
+    /// Whether keys differing only by case are distinct keys, applied if the table is created
+    /// by the seed.
+    #[serde(default)]
+    pub case_sensitive: Option<bool>,
+    /// The JSON Schema of the values, applied if the table is created by the seed.
+    #[serde(default)]
+    pub json_schema: Option<Value>,
+    /// The values of the keys, strings being stored as is and other values as JSON.
+    #[serde(default)]
+    pub keys: BTreeMap<String, Value>,
+}
+
+/// A struct representing what applying the fixtures changed.
+#[derive(Serialize, Deserialize, Clone, Default, Debug, PartialEq)]
+pub struct SeedReport {
+    /// The number of fixture files applied.
+    pub files: usize,
+    /// The number of tables created.
+    pub tables_created: usize,
+    /// The number of keys written.
+    pub keys_written: usize,
+    /// The number of keys left alone because they already exist.
+    pub keys_skipped: usize,
+}
+
+/// Implementation of the `SeedReport` struct.
+impl SeedReport {
+    /// Renders the report for deployment pipelines.
+    ///
+    /// # Returns
+    ///
+    /// * `String` - The report as pretty-printed JSON.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap_or_default()
+    }
+}
+
+/// A struct applying the JSON and TOML fixtures of a seeds directory to the database.
+///
+/// Fixtures only create what is missing: a table is declared with the type and schemas of its
+/// fixture if it does not exist yet, and a key is written if it does not exist yet, so seeding
+/// again leaves the data unchanged however it was modified since. Files are applied in the
+/// order of their names.
+pub struct Seeder;
+
+/// Implementation of the `Seeder` struct.
+impl Seeder {
+    /// Returns the configured seeds directory.
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - The seed configuration.
+    ///
+    /// # Returns
+    ///
+    /// * `PathBuf` - The configured directory, or the `seeds` directory next to the
+    ///   configuration file.
+    pub fn dir(config: &SeedConfig) -> PathBuf {
+        config
+            .dir
+            .as_ref()
+            .map(PathBuf::from)
+            .unwrap_or_else(|| Utils::get_path(&["xcloud", "seeds"]))
+    }
+
+    /// Reads the fixture files of a directory.
+    ///
+    /// # Arguments
+    ///
+    /// * `dir` - The seeds directory.
+    ///
+    /// # Returns
+    ///
+    /// * `Vec<(PathBuf, Fixture)>` - The fixtures by file, in the order of their names, empty if
+    ///   the directory does not exist.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the directory or a file cannot be read or a file
+    /// is not a valid fixture.
+    pub fn load(dir: &Path) -> Result<Vec<(PathBuf, Fixture)>, AppError> {
+        if !dir.exists() {
+            return Ok(Vec::new());
+        }
+        let mut paths = Vec::new();
+        for entry in std::fs::read_dir(dir)? {
+            let path = entry?.path();
+            if matches!(
+                path.extension().and_then(|ext| ext.to_str()),
+                Some("json" | "toml")
+            ) {
+                paths.push(path);
+            }
+        }
+        paths.sort();
+        paths
+            .into_iter()
+            .map(|path| {
+                let content = std::fs::read_to_string(&path)?;
+                let fixture = if path.extension().is_some_and(|ext| ext == "json") {
+                    serde_json::from_str(&content).map_err(|e| e.to_string())
+                } else {
+                    toml::from_str(&content).map_err(|e| e.to_string())
+                }
+                .map_err(|e| AppError::Seed(format!("{}: {}", path.display(), e)))?;
+                Ok((path, fixture))
+            })
+            .collect()
+    }
+
+    /// Applies the fixtures of a directory.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - The database to seed.
+    /// * `dir` - The seeds directory.
+    ///
+    /// # Returns
+    ///
+    /// * `SeedReport` - What the fixtures changed.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the fixtures cannot be read or written.
+    pub async fn run(db: &Database, dir: &Path) -> Result<SeedReport, AppError> {
+        let fixtures = Self::load(dir)?;
+        let mut report = SeedReport::default();
+        for (path, fixture) in &fixtures {
+            log::info!("Applying seed {:?}", path);
+            Self::apply(db, fixture, &mut report).await?;
+            report.files += 1;
+        }
+        Ok(report)
+    }
+
+    /// Applies a single fixture.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - The database to seed.
+    /// * `fixture` - The tables and keys to create if missing.
+    /// * `report` - The report counting the changes.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if a table or a key cannot be read or written.
+    pub async fn apply(
+        db: &Database,
+        fixture: &Fixture,
+        report: &mut SeedReport,
+    ) -> Result<(), sqlx::Error> {
+        for table in &fixture.tables {
+            if !db
+                .list_tables()
+                .await?
+                .contains(&Utils::sanitize(&table.name))
+            {
+                db.create_table(&TableSchema {
+                    table: table.name.clone(),
+                    value_type: table.value_type.unwrap_or_default(),
+                    case_sensitive: table.case_sensitive,
+                })
+                .await?;
+                if let Some(schema) = &table.json_schema {
+                    db.set_json_schema(&JsonSchema {
+                        table: table.name.clone(),
+                        schema: schema.clone(),
+                    })
+                    .await?;
+                }
+                report.tables_created += 1;
+            }
+            for (key, value) in &table.keys {
+                if db.get_data(&table.name, key).await?.is_some() {
+                    report.keys_skipped += 1;
+                    continue;
+                }
+                let value = match value {
+                    Value::String(value) => value.clone(),
+                    value => value.to_string(),
+                };
+                db.set_data(&table.name, key, &value).await?;
+                report.keys_written += 1;
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,6869 @@
+use std::collections::{BTreeMap, HashMap};
+use std::str::FromStr;
+use std::sync::{Arc, PoisonError, RwLock};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use actix_cors::Cors;
+use actix_web::body::{BoxBody, EitherBody};
+use actix_web::dev::{ServiceFactory, ServiceRequest, ServiceResponse};
+use actix_web::{http, web, App, HttpRequest, HttpResponse, HttpServer, Responder};
+use async_graphql_actix_web::{GraphQLRequest, GraphQLResponse};
+use futures::StreamExt;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use tokio::io::AsyncWriteExt;
+use tokio::sync::Mutex;
+
+use crate::advisor::{IndexAdvisor, IndexRecommendation};
+use crate::alerts::{Alert, Alerter};
+use crate::breaker::CircuitBreaker;
+use crate::capture::TrafficCapture;
+use crate::cluster::{Cluster, ClusterState};
+use crate::concurrency::{ConcurrencyLimits, LaneStats};
+use crate::config::{Config, CorsScope, LimitsConfig, REDACTED};
+use crate::consistency::{Consistency, CONSISTENCY_TOKEN_HEADER};
+use crate::console::{ConsoleResult, SqlConsole};
+use crate::crypto::ReencryptJob;
+use crate::dashboard::Dashboard;
+use crate::db::{
+    Activity, AggregateView, ClientChange, Comment, ConsumerOffset, Database, DryRunReport, Event,
+    EventLog, HistoryEntry, Integration, LegalHold, Lock, PoolState, PoolStats, Project,
+    RemoteChange, RowStream, SchemaLock, SearchHit, SeriesBucket, SeriesPoint, Snapshot,
+    SnapshotDiff, SyncOutcome, SyncResolution, SyncStatus, TableInfo, TablePolicy, TableRegion,
+    TimeSeries, Transform, TreeNode, ViewGroup,
+};
+use crate::extract::Json;
+use crate::flags::{FeatureFlag, FeatureFlags, FlagState};
+use crate::graphql::{self, GraphQLSchema, LockOwner};
+use crate::health::{Health, ProbeResult};
+use crate::history::HistorySweeper;
+use crate::ids::KeyGenerator;
+use crate::import::{CsvMapping, ImportJob, ImportProgress, Imports, MappingError};
+use crate::integrations::IntegrationScheduler;
+use crate::jobs::JobQueue;
+use crate::keystats::{KeyStat, KeyStatsFlusher};
+use crate::logging::Logging;
+use crate::maintenance::{Maintenance, MaintenanceState};
+use crate::metrics::{MetricsExporter, Sample, OPENMETRICS_CONTENT_TYPE};
+use crate::middleware::{
+    BodySizeLimit, CircuitBreakerGuard, ConcurrencyGuard, ConsistencyTokens, ErrorLocalizer,
+    FeatureGuard, MaintenanceGuard, RequestCounter, RequestLogger, RequestSignatureGuard,
+    SignedUrlGuard, TrafficRecorder,
+};
+use crate::mqtt::MqttBridge;
+use crate::patch::{DeltaOp, ValuePatch};
+use crate::plugin::{Plugin, Plugins};
+use crate::privacy::{PrivacyAction, PrivacyJob, PrivacyReport, PrivacyRequests};
+use crate::projection::Projection;
+use crate::replication::{ReplicationBatch, Replicator};
+use crate::response::ApiResponse;
+use crate::retention::RetentionSweeper;
+use crate::retry::RetryPolicy;
+use crate::runtime::{self, RuntimeStats};
+use crate::schema::{JsonSchema, SchemaViolation, TableSchema, ValueType, ValueValidator};
+use crate::signing::{RequestVerifier, UrlSigner, CLIENT_HEADER};
+use crate::slowlog::SlowQuery;
+use crate::snapshot::SnapshotScheduler;
+use crate::stats::{RequestCounts, RequestStats};
+use crate::tiering::{TieringJob, TieringReport};
+use crate::totp::Totp;
+use crate::utils::Utils;
+use crate::watcher::ConfigWatcher;
+use crate::webhooks::{WebhookEvent, Webhooks, SIGNATURE_HEADER};
+use crate::writebehind::WriteBehindFlusher;
+
+/// The header naming the lock owner on whose behalf a write is made.
+pub const LOCK_OWNER_HEADER: &str = "X-xCLOUD-Lock-Owner";
+
+/// The error code of the rejection of a write to a table under a legal hold.
+pub const LEGAL_HOLD_CODE: &str = "legal_hold";
+
+/// The header holding the number of rows of a consistent export.
+pub const ROW_COUNT_HEADER: &str = "X-xCLOUD-Row-Count";
+
+/// The setting holding the time step of the last accepted code lifting a legal hold.
+const HOLD_TOTP_STEP_SETTING: &str = "hold_totp_step";
+
+/// The duration in seconds after which the schema lock of an interrupted operation expires.
+const SCHEMA_LOCK_LEASE_SECS: i64 = 300;
+
+/// A struct representing a key-value pair for a table.
+#[derive(Serialize, Deserialize)]
+struct TableKeyValue {
+    table: String,
+    key: String,
+    value: String,
+}
+
+/// A struct representing a key of a table with a typed value.
+#[derive(Serialize, Deserialize)]
+struct TypedTableKeyValue<T> {
+    table: String,
+    key: String,
+    value: T,
+}
+
+/// A trait for the primitive types read and written by the typed value routes, stored as
+/// their string representation.
+trait TypedValue: FromStr + ToString + Serialize + DeserializeOwned + 'static {
+    /// The code of the error returned when a value is not of the type.
+    const ERROR_CODE: &'static str;
+}
+
+impl TypedValue for i64 {
+    const ERROR_CODE: &'static str = "value_is_not_an_integer";
+}
+
+impl TypedValue for f64 {
+    const ERROR_CODE: &'static str = "value_is_not_a_number";
+}
+
+impl TypedValue for bool {
+    const ERROR_CODE: &'static str = "value_is_not_a_boolean";
+}
+
+/// A struct representing a value appended to a table under a generated key.
+#[derive(Serialize, Deserialize)]
+struct TableValue {
+    table: String,
+    value: String,
+}
+
+/// A struct representing the key generated for an appended value.
+#[derive(Serialize, Deserialize)]
+struct AppendedKey {
+    key: String,
+}
+
+/// A struct representing a key for a table.
+#[derive(Serialize, Deserialize)]
+struct TableKey {
+    table: String,
+    key: String,
+}
+
+/// A struct representing a key of a table with the fields of its value to return.
+#[derive(Serialize, Deserialize)]
+struct TableKeyProjection {
+    table: String,
+    key: String,
+    /// The comma-separated dotted paths of the fields to return, the whole value if unset.
+    fields: Option<String>,
+}
+
+/// A struct representing a key of a table at a point in time.
+#[derive(Serialize, Deserialize)]
+struct TableKeyAt {
+    table: String,
+    key: String,
+    /// The unix timestamp in seconds.
+    timestamp: i64,
+}
+
+/// A struct representing a query of the versions of a key.
+#[derive(Serialize, Deserialize)]
+struct HistoryQuery {
+    table: String,
+    key: String,
+    limit: Option<i64>,
+}
+
+/// A struct representing the compaction of the history of a table.
+#[derive(Serialize, Deserialize)]
+struct HistoryCompaction {
+    table: String,
+    /// The age in seconds beyond which versions are collapsed, the configured age if unset.
+    max_age_secs: Option<u64>,
+}
+
+/// A struct representing a statement submitted to the admin SQL console.
+#[derive(Serialize, Deserialize)]
+struct SqlStatement {
+    sql: String,
+    /// The largest number of rows returned, the configured cap if unset or above it.
+    max_rows: Option<usize>,
+    /// The longest time in milliseconds the statement may run, the configured timeout if unset
+    /// or above it.
+    timeout_ms: Option<u64>,
+}
+
+/// A struct representing the name of a feature flag.
+#[derive(Serialize, Deserialize)]
+struct FlagName {
+    name: String,
+}
+
+/// A struct representing a single line of a streamed table.
+#[derive(Serialize)]
+struct StreamedRow {
+    key: String,
+    value: serde_json::Value,
+}
+
+/// A struct representing a request for many keys, of one table or of many tables.
+#[derive(Serialize, Deserialize)]
+struct GetMany {
+    table: Option<String>,
+    #[serde(default)]
+    keys: Vec<String>,
+    #[serde(default)]
+    items: Vec<TableKey>,
+    /// The comma-separated dotted paths of the fields to return, the whole values if unset.
+    fields: Option<String>,
+}
+
+/// A struct representing a value found by a multi-get.
+#[derive(Serialize)]
+struct FoundValue {
+    table: String,
+    key: String,
+    value: serde_json::Value,
+}
+
+/// A struct representing the result of a multi-get.
+#[derive(Serialize)]
+struct GetManyResult {
+    found: Vec<FoundValue>,
+    missing: Vec<TableKey>,
+}
+
+/// A struct representing a search hit with the selected fields of its value.
+#[derive(Serialize)]
+struct ProjectedHit {
+    key: String,
+    value: serde_json::Value,
+    score: f64,
+}
+
+/// A struct representing an increment of a numeric value of a table.
+#[derive(Serialize, Deserialize)]
+struct TableKeyDelta {
+    table: String,
+    key: String,
+    delta: i64,
+}
+
+/// A struct representing a conditional write of a value of a table.
+#[derive(Serialize, Deserialize)]
+struct CompareAndSet {
+    table: String,
+    key: String,
+    /// The expected current value, or `None` if the key is expected to be absent.
+    expected: Option<String>,
+    value: String,
+    /// Whether to merge the changes from the expected value into a diverged JSON value instead
+    /// of conflicting, the last writer winning per field.
+    #[serde(default)]
+    merge: bool,
+}
+
+/// A struct representing a value pushed onto a list.
+#[derive(Serialize, Deserialize)]
+struct ListPush {
+    table: String,
+    key: String,
+    value: String,
+    #[serde(default)]
+    front: bool,
+}
+
+/// A struct representing a value popped from a list.
+#[derive(Serialize, Deserialize)]
+struct ListPop {
+    table: String,
+    key: String,
+    #[serde(default)]
+    front: bool,
+}
+
+/// A struct representing a range of a list.
+#[derive(Serialize, Deserialize)]
+struct ListRange {
+    table: String,
+    key: String,
+    #[serde(default)]
+    offset: i64,
+    limit: Option<i64>,
+}
+
+/// A struct representing events to append to an event log.
+#[derive(Serialize, Deserialize)]
+struct AppendEvents {
+    log: String,
+    values: Vec<String>,
+}
+
+/// A struct representing the events appended to an event log.
+#[derive(Serialize, Deserialize)]
+struct AppendedEvents {
+    /// The offset of the first appended event, the others following consecutively.
+    first_offset: i64,
+    count: usize,
+}
+
+/// A struct representing a range of events to read from an event log.
+#[derive(Serialize, Deserialize)]
+struct EventRange {
+    log: String,
+    #[serde(default)]
+    offset: i64,
+    /// The unix timestamp in milliseconds before which events are skipped.
+    since: Option<i64>,
+    limit: Option<i64>,
+}
+
+/// A struct representing an event log.
+#[derive(Serialize, Deserialize)]
+struct LogName {
+    log: String,
+}
+
+/// A struct representing an offset committed by a consumer group.
+#[derive(Serialize, Deserialize)]
+struct CommitOffset {
+    log: String,
+    group: String,
+    offset: i64,
+}
+
+/// A struct representing an aggregate view.
+#[derive(Serialize, Deserialize)]
+struct ViewName {
+    name: String,
+}
+
+/// A struct representing a filter of the aggregates of a view by the start of their prefix.
+#[derive(Serialize, Deserialize)]
+struct ViewFilter {
+    prefix: Option<String>,
+}
+
+/// A struct representing a time series.
+#[derive(Serialize, Deserialize)]
+struct SeriesName {
+    series: String,
+}
+
+/// A struct representing a sample to record, at the current time if no timestamp is given.
+#[derive(Serialize, Deserialize)]
+struct NewPoint {
+    key: String,
+    /// The unix timestamp in milliseconds of the sample.
+    timestamp: Option<i64>,
+    value: f64,
+}
+
+/// A struct representing samples to record in a time series.
+#[derive(Serialize, Deserialize)]
+struct AppendPoints {
+    series: String,
+    points: Vec<NewPoint>,
+}
+
+/// A struct representing a range of samples of a key of a time series.
+#[derive(Serialize, Deserialize)]
+struct PointRange {
+    series: String,
+    key: String,
+    /// The unix timestamp in milliseconds of the first sample, inclusive.
+    #[serde(default)]
+    from: i64,
+    /// The unix timestamp in milliseconds of the last sample, exclusive, unbounded if unset.
+    to: Option<i64>,
+    limit: Option<i64>,
+}
+
+/// A struct representing an aggregation of the samples of a key of a time series.
+#[derive(Serialize, Deserialize)]
+struct PointAggregation {
+    series: String,
+    key: String,
+    /// The unix timestamp in milliseconds of the first sample, inclusive.
+    #[serde(default)]
+    from: i64,
+    /// The unix timestamp in milliseconds of the last sample, exclusive, unbounded if unset.
+    to: Option<i64>,
+    /// The width in seconds of the buckets.
+    bucket_secs: i64,
+}
+
+/// A struct representing a batch of changes a client made while offline.
+#[derive(Serialize, Deserialize)]
+struct SyncPush {
+    changes: Vec<ClientChange>,
+    #[serde(default)]
+    resolution: SyncResolution,
+}
+
+/// A struct representing a pull of the changes made after a checkpoint.
+#[derive(Serialize, Deserialize)]
+struct SyncPull {
+    #[serde(default)]
+    since: i64,
+    table: Option<String>,
+    #[serde(default = "SyncPull::default_limit")]
+    limit: i64,
+}
+
+/// Implementation of the `SyncPull` struct.
+impl SyncPull {
+    /// Returns the default number of changes per pull.
+    fn default_limit() -> i64 {
+        1000
+    }
+}
+
+/// A struct representing the changes made after a checkpoint.
+#[derive(Serialize, Deserialize)]
+struct SyncChanges {
+    changes: Vec<RemoteChange>,
+    /// The checkpoint to pull the following changes from.
+    checkpoint: i64,
+}
+
+/// A struct representing a full-text search query.
+#[derive(Serialize, Deserialize)]
+struct FulltextQuery {
+    table: String,
+    q: String,
+    #[serde(default)]
+    offset: i64,
+    #[serde(default = "FulltextQuery::default_limit")]
+    limit: i64,
+    /// The comma-separated dotted paths of the fields to return, the whole values if unset.
+    fields: Option<String>,
+}
+
+/// Implementation of the `FulltextQuery` struct.
+impl FulltextQuery {
+    /// Returns the default number of hits per page.
+    fn default_limit() -> i64 {
+        20
+    }
+}
+
+/// A struct representing a query of the activity feed.
+#[derive(Serialize, Deserialize)]
+struct ActivityQuery {
+    table: Option<String>,
+    key: Option<String>,
+    #[serde(default)]
+    offset: i64,
+    #[serde(default = "ActivityQuery::default_limit")]
+    limit: i64,
+}
+
+/// Implementation of the `ActivityQuery` struct.
+impl ActivityQuery {
+    /// Returns the default number of entries per page.
+    fn default_limit() -> i64 {
+        50
+    }
+}
+
+/// A struct representing a query of the access counters of the keys of a table.
+#[derive(Serialize, Deserialize)]
+struct HotKeysQuery {
+    table: String,
+    /// Whether to list the least recently accessed keys instead of the most accessed ones.
+    #[serde(default)]
+    stale: bool,
+    #[serde(default = "HotKeysQuery::default_limit")]
+    limit: i64,
+}
+
+/// Implementation of the `HotKeysQuery` struct.
+impl HotKeysQuery {
+    /// Returns the default number of keys listed.
+    fn default_limit() -> i64 {
+        20
+    }
+}
+
+/// A struct representing a patch of the JSON value of a key.
+#[derive(Serialize, Deserialize)]
+struct PatchData {
+    table: String,
+    key: String,
+    /// The RFC 6902 JSON Patch operations to apply.
+    patch: Option<json_patch::Patch>,
+    /// The RFC 7396 JSON Merge Patch to apply.
+    merge: Option<serde_json::Value>,
+    /// The delta against the current value to apply, to values of any type.
+    delta: Option<Vec<DeltaOp>>,
+}
+
+/// A struct representing a bulk delete of the keys of a table.
+#[derive(Serialize, Deserialize)]
+struct DeleteMany {
+    table: String,
+    /// The prefix of the keys to delete.
+    prefix: Option<String>,
+    /// The keys to delete.
+    keys: Option<Vec<String>>,
+}
+
+/// A struct representing a table name.
+#[derive(Serialize, Deserialize)]
+struct Table {
+    table: String,
+}
+
+/// A struct representing a request to export or anonymize the data of a person.
+#[derive(Serialize, Deserialize)]
+struct NewPrivacyRequest {
+    /// The identities of the person, e.g. their user id and email address.
+    identities: Vec<String>,
+    action: PrivacyAction,
+}
+
+/// A struct representing a legal hold to place on a table.
+#[derive(Serialize, Deserialize)]
+struct NewLegalHold {
+    table: String,
+    reason: String,
+}
+
+/// A struct representing the lifting of a legal hold, confirmed with a second factor.
+#[derive(Serialize, Deserialize)]
+struct LiftLegalHold {
+    table: String,
+    /// The current time-based one-time password of the admin.
+    code: String,
+}
+
+/// A struct representing the rejection of a write to a table under a legal hold.
+#[derive(Serialize, Deserialize)]
+struct HeldTable {
+    /// Always [`LEGAL_HOLD_CODE`].
+    code: String,
+    hold: LegalHold,
+}
+
+/// A struct representing a copy of a table.
+#[derive(Serialize, Deserialize)]
+struct CloneTable {
+    source: String,
+    target: String,
+    /// Whether to copy the key-value pairs, or only the schema.
+    #[serde(default)]
+    with_data: bool,
+    /// Whether the target may be stored in another region than the source.
+    #[serde(default)]
+    allow_cross_region: bool,
+}
+
+/// A struct representing the region to tag a table with.
+#[derive(Serialize, Deserialize)]
+struct SetTableRegion {
+    table: String,
+    /// The configured region, or `None` to store the table on the shards.
+    #[serde(default)]
+    region: Option<String>,
+}
+
+/// A struct representing the configured regions and the tables tagged with them.
+#[derive(Serialize, Deserialize)]
+struct Regions {
+    regions: Vec<String>,
+    tables: Vec<TableRegion>,
+}
+
+/// A struct representing a comment to post on a key.
+#[derive(Serialize, Deserialize)]
+struct PostComment {
+    table: String,
+    key: String,
+    /// The id of the comment replied to, if any.
+    parent_id: Option<i64>,
+    author: String,
+    body: String,
+}
+
+/// A struct representing an edit of a comment.
+#[derive(Serialize, Deserialize)]
+struct EditComment {
+    id: i64,
+    author: String,
+    body: String,
+}
+
+/// A struct representing a comment of an author.
+#[derive(Serialize, Deserialize)]
+struct CommentId {
+    id: i64,
+    author: String,
+}
+
+/// A struct representing a project name.
+#[derive(Serialize, Deserialize)]
+struct ProjectName {
+    name: String,
+}
+
+/// A struct representing the tables to capture in a snapshot.
+#[derive(Serialize, Deserialize)]
+struct SnapshotRequest {
+    /// The tables to capture, all tables if empty.
+    #[serde(default)]
+    tables: Vec<String>,
+}
+
+/// A struct representing a snapshot id.
+#[derive(Serialize, Deserialize)]
+struct SnapshotId {
+    id: i64,
+}
+
+/// A struct representing whether a destructive request only reports what it would affect.
+#[derive(Serialize, Deserialize)]
+struct DryRunQuery {
+    #[serde(default, rename = "dryRun")]
+    dry_run: bool,
+}
+
+/// A struct representing the table and column mapping of a CSV import.
+#[derive(Deserialize)]
+struct CsvImportQuery {
+    table: String,
+    /// The column holding the keys.
+    key: String,
+    /// The comma-separated value columns, each optionally renamed as `column:field`.
+    columns: Option<String>,
+    delimiter: Option<char>,
+    /// Whether numbers, booleans and empty cells are stored as JSON values.
+    #[serde(default = "CsvImportQuery::default_typed")]
+    typed: bool,
+    #[serde(default, rename = "dryRun")]
+    dry_run: bool,
+}
+
+/// Implementation of the `CsvImportQuery` struct.
+impl CsvImportQuery {
+    /// Returns whether cells are typed by default.
+    fn default_typed() -> bool {
+        true
+    }
+}
+
+/// A struct representing the snapshot to compare against.
+#[derive(Serialize, Deserialize)]
+struct DiffQuery {
+    against: i64,
+}
+
+/// A struct representing a project to create.
+#[derive(Serialize, Deserialize)]
+struct NewProject {
+    name: String,
+    /// Whether to seed the project with copies of the template tables.
+    #[serde(default)]
+    templates: bool,
+}
+
+/// A struct representing a table of a project.
+#[derive(Serialize, Deserialize)]
+struct ProjectTable {
+    project: String,
+    table: String,
+}
+
+/// A struct representing a page of the keys of a table.
+#[derive(Serialize, Deserialize)]
+struct KeysQuery {
+    table: String,
+    #[serde(default)]
+    offset: i64,
+    limit: Option<i64>,
+}
+
+/// A struct representing a path in the key hierarchy of a table.
+#[derive(Serialize, Deserialize)]
+struct TreeQuery {
+    table: String,
+    /// The `/`-separated path to list, the root if empty.
+    #[serde(default)]
+    path: String,
+}
+
+/// A struct representing a webhook registration for a table.
+#[derive(Serialize, Deserialize)]
+struct WebhookRegistration {
+    table: String,
+    url: String,
+    secret: String,
+}
+
+/// A struct representing a webhook id.
+#[derive(Serialize, Deserialize)]
+struct WebhookId {
+    id: i64,
+}
+
+/// A struct representing a rule deriving a key from a field of another key.
+#[derive(Serialize, Deserialize)]
+struct NewTransform {
+    source_table: String,
+    source_key: String,
+    /// The JSON Pointer of the field, empty for the whole value.
+    #[serde(default)]
+    pointer: String,
+    target_table: String,
+    target_key: String,
+    /// Whether the target key may be stored in another region than the source key.
+    #[serde(default)]
+    allow_cross_region: bool,
+}
+
+/// A struct representing a transform id.
+#[derive(Serialize, Deserialize)]
+struct TransformId {
+    id: i64,
+}
+
+/// A struct representing a log level.
+#[derive(Serialize, Deserialize)]
+struct LogLevel {
+    level: String,
+}
+
+/// A struct representing a request to lock a key.
+#[derive(Serialize, Deserialize)]
+struct LockRequest {
+    table: String,
+    key: String,
+    owner: String,
+    /// The duration of the lease in seconds, defaults to the configured lease duration.
+    lease_secs: Option<u64>,
+}
+
+/// A struct representing a request to unlock a key.
+#[derive(Serialize, Deserialize)]
+struct UnlockRequest {
+    table: String,
+    key: String,
+    owner: String,
+}
+
+/// A struct representing a request to mint a signed URL.
+#[derive(Serialize, Deserialize)]
+struct SignUrlRequest {
+    table: String,
+    key: String,
+    /// The lifetime of the URL in seconds, defaults to the configured lifetime.
+    ttl_secs: Option<u64>,
+}
+
+/// A struct representing a minted signed URL.
+#[derive(Serialize, Deserialize)]
+struct SignedUrl {
+    /// The path and query of the URL, relative to the address of the server.
+    url: String,
+    /// The unix timestamp in seconds after which the URL is rejected.
+    expires: u64,
+}
+
+/// A struct representing a filter of the slow query log.
+#[derive(Serialize, Deserialize)]
+struct SlowQueryFilter {
+    table: Option<String>,
+}
+
+/// A struct representing an overview of the health of the server.
+#[derive(Serialize, Deserialize)]
+struct AdminStats {
+    uptime_secs: u64,
+    tables: usize,
+    keys: i64,
+    /// The approximate size in bytes of all stored keys and values.
+    bytes: i64,
+    requests: RequestCounts,
+    pools: Vec<PoolStats>,
+    /// The queue depth and latency of the interactive and bulk traffic.
+    lanes: Vec<LaneStats>,
+}
+
+/// A struct representing the outcome of recycling the connection pools.
+#[derive(Serialize, Deserialize)]
+struct RecycledPools {
+    /// The number of closed idle connections.
+    closed: usize,
+    pools: Vec<PoolState>,
+}
+
+/// A struct representing the outcome of applying a replicated batch.
+#[derive(Serialize, Deserialize)]
+struct ReplicationResult {
+    applied: usize,
+    skipped: usize,
+}
+
+/// A struct representing the server.
+pub struct Server {
+    db: Arc<Mutex<Database>>,
+    jobs: JobQueue,
+    webhooks: Arc<Webhooks>,
+    logging: Arc<Logging>,
+    config: Arc<RwLock<Config>>,
+    maintenance: Arc<Maintenance>,
+    flags: Arc<FeatureFlags>,
+    stats: Arc<RequestStats>,
+    breaker: Arc<CircuitBreaker>,
+    concurrency: Arc<ConcurrencyLimits>,
+    signatures: Arc<RequestVerifier>,
+    health: Arc<Health>,
+    ids: Arc<KeyGenerator>,
+    imports: Imports,
+    privacy: PrivacyRequests,
+    captures: Arc<TrafficCapture>,
+    plugins: Plugins,
+    cluster: Arc<Cluster>,
+    mqtt: std::sync::Mutex<Option<MqttBridge>>,
+    alerts: Arc<Alerter>,
+    consistency: Arc<Consistency>,
+}
+
+/// Implementation of the `Server` struct.
+impl Server {
+    /// Creates a new instance of the Server.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - The database instance to be used by the server.
+    /// * `config` - The configuration of the server.
+    /// * `logging` - The handle used to change the log level at runtime.
+    ///
+    /// # Returns
+    ///
+    /// * `Server` - A new instance of the Server.
+    pub fn new(db: Database, config: Config, logging: Logging) -> Self {
+        let jobs = JobQueue::new(16, 5, Duration::from_secs(1));
+        let breaker = Arc::new(CircuitBreaker::new(config.circuit_breaker.clone()));
+        let concurrency = Arc::new(ConcurrencyLimits::new(&config.concurrency));
+        let ids = Arc::new(KeyGenerator::new(config.ids.clone()));
+        let health = Arc::new(Health::new(config.health.clone()));
+        let plugins = Plugins::default();
+        let cluster = Arc::new(Cluster::new(config.cluster.clone(), &config.bind_address));
+        let mqtt = MqttBridge::new(config.mqtt.clone());
+        let stats = Arc::new(RequestStats::new());
+        let alerts = Arc::new(Alerter::new(
+            config.alerts.clone(),
+            stats.clone(),
+            breaker.clone(),
+            jobs.clone(),
+        ));
+        let consistency = db.consistency();
+        Server {
+            db: Arc::new(Mutex::new(
+                db.with_circuit_breaker(breaker.clone())
+                    .with_plugins(plugins.clone()),
+            )),
+            webhooks: Arc::new(
+                Webhooks::new(jobs.clone())
+                    .with_mqtt(mqtt.as_ref().and_then(MqttBridge::publisher)),
+            ),
+            jobs,
+            logging: Arc::new(logging),
+            config: Arc::new(RwLock::new(config)),
+            maintenance: Arc::new(Maintenance::default()),
+            flags: Arc::new(FeatureFlags::default()),
+            stats,
+            breaker,
+            concurrency,
+            signatures: Arc::new(RequestVerifier::default()),
+            health,
+            ids,
+            imports: Imports::default(),
+            privacy: PrivacyRequests::default(),
+            captures: Arc::new(TrafficCapture::default()),
+            plugins,
+            cluster,
+            mqtt: std::sync::Mutex::new(mqtt),
+            alerts,
+            consistency,
+        }
+    }
+
+    /// Applies a previously persisted maintenance state.
+    ///
+    /// # Arguments
+    ///
+    /// * `maintenance` - The maintenance state restored from the database.
+    ///
+    /// # Returns
+    ///
+    /// * `Server` - The server with the maintenance state applied.
+    pub fn with_maintenance(mut self, maintenance: Maintenance) -> Self {
+        self.maintenance = Arc::new(maintenance);
+        self
+    }
+
+    /// Applies the feature flags loaded from the database.
+    ///
+    /// # Arguments
+    ///
+    /// * `flags` - The feature flags loaded from the database.
+    ///
+    /// # Returns
+    ///
+    /// * `Server` - The server evaluating the flags.
+    pub fn with_feature_flags(mut self, flags: FeatureFlags) -> Self {
+        self.flags = Arc::new(flags);
+        self
+    }
+
+    /// Registers a compiled-in plugin, whose hooks run after those of the plugins registered
+    /// before it.
+    ///
+    /// # Arguments
+    ///
+    /// * `plugin` - The plugin.
+    ///
+    /// # Returns
+    ///
+    /// * `Server` - The server running the hooks of the plugin.
+    pub fn with_plugin(self, plugin: impl Plugin + 'static) -> Self {
+        self.plugins.register(Arc::new(plugin));
+        self
+    }
+
+    /// Returns the middleware rejecting the requests blocked by the maintenance mode.
+    ///
+    /// # Returns
+    ///
+    /// * `MaintenanceGuard` - The middleware sharing the maintenance state of the server.
+    pub fn maintenance_guard(&self) -> MaintenanceGuard {
+        MaintenanceGuard::new(self.maintenance.clone())
+    }
+
+    /// Returns the middleware hiding the endpoints gated by feature flags that are off.
+    ///
+    /// # Returns
+    ///
+    /// * `FeatureGuard` - The middleware sharing the feature flags of the server.
+    pub fn feature_guard(&self) -> FeatureGuard {
+        FeatureGuard::new(self.flags.clone())
+    }
+
+    /// Returns the middleware rejecting requests to signed routes without a valid signature.
+    ///
+    /// # Returns
+    ///
+    /// * `SignedUrlGuard` - The middleware reading the signing secret from the live configuration.
+    pub fn signed_url_guard(&self) -> SignedUrlGuard {
+        SignedUrlGuard::new(self.config.clone())
+    }
+
+    /// Returns the middleware rejecting mutating requests without a valid HMAC signature.
+    ///
+    /// # Returns
+    ///
+    /// * `RequestSignatureGuard` - The middleware sharing the accepted signatures of the server.
+    pub fn request_signature_guard(&self) -> RequestSignatureGuard {
+        RequestSignatureGuard::new(self.config.clone(), self.signatures.clone())
+    }
+
+    /// Returns the middleware rejecting requests while the database is unavailable.
+    ///
+    /// # Returns
+    ///
+    /// * `CircuitBreakerGuard` - The middleware sharing the circuit breaker of the database.
+    pub fn circuit_breaker_guard(&self) -> CircuitBreakerGuard {
+        CircuitBreakerGuard::new(self.breaker.clone())
+    }
+
+    /// Returns the middleware limiting the requests served concurrently per group of routes.
+    ///
+    /// # Returns
+    ///
+    /// * `ConcurrencyGuard` - The middleware sharing the limits of the server.
+    pub fn concurrency_guard(&self) -> ConcurrencyGuard {
+        ConcurrencyGuard::new(self.concurrency.clone())
+    }
+
+    /// Returns the middleware building error responses in the language of the request.
+    ///
+    /// # Returns
+    ///
+    /// * `ErrorLocalizer` - The middleware negotiating the language of the request.
+    pub fn error_localizer(&self) -> ErrorLocalizer {
+        ErrorLocalizer
+    }
+
+    /// Returns the middleware counting the served requests reported by `/admin/stats`.
+    ///
+    /// # Returns
+    ///
+    /// * `RequestCounter` - The middleware sharing the counters of the server.
+    pub fn request_counter(&self) -> RequestCounter {
+        RequestCounter::new(self.stats.clone())
+    }
+
+    /// Returns the middleware returning and accepting the consistency tokens of the writes.
+    ///
+    /// # Returns
+    ///
+    /// * `ConsistencyTokens` - The middleware sharing the replica positions of the server.
+    pub fn consistency_tokens(&self) -> ConsistencyTokens {
+        ConsistencyTokens::new(self.consistency.clone())
+    }
+
+    /// Returns the middleware recording the requests and responses of the captured routes.
+    ///
+    /// # Returns
+    ///
+    /// * `TrafficRecorder` - The middleware sharing the recorded exchanges of the server.
+    pub fn traffic_recorder(&self) -> TrafficRecorder {
+        TrafficRecorder::new(self.config.clone(), self.captures.clone())
+    }
+
+    /// Builds the CORS middleware of the routes of a scope.
+    ///
+    /// Preflight requests are scoped by the method they ask for, not by `OPTIONS`.
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - The live configuration holding the CORS policies.
+    /// * `scope` - The scope of the routes, given the method they are called with.
+    ///
+    /// # Returns
+    ///
+    /// * `Cors` - The CORS middleware.
+    fn cors(config: Arc<RwLock<Config>>, scope: fn(&str) -> CorsScope) -> Cors {
+        Cors::default()
+            .allowed_origin_fn(move |origin, req| {
+                let method = match req.headers.get(http::header::ACCESS_CONTROL_REQUEST_METHOD) {
+                    Some(requested) if req.method == http::Method::OPTIONS => {
+                        requested.to_str().unwrap_or_default()
+                    }
+                    _ => req.method.as_str(),
+                };
+                config
+                    .read()
+                    .unwrap_or_else(PoisonError::into_inner)
+                    .cors
+                    .allows(scope(method), origin.as_bytes())
+            })
+            .allowed_methods(vec!["GET", "POST", "PUT", "PATCH", "DELETE"])
+            .allowed_headers(vec![http::header::CONTENT_TYPE])
+            .allowed_header(CONSISTENCY_TOKEN_HEADER)
+            .expose_headers(vec![CONSISTENCY_TOKEN_HEADER])
+            .supports_credentials()
+    }
+
+    /// Runs the server and listens for incoming HTTP requests.
+    ///
+    /// # Returns
+    ///
+    /// * `std::io::Result<()>` - The result of the server execution.
+    pub async fn run(&self) -> std::io::Result<()> {
+        let configure = self.configure();
+        let config = self.config();
+        let live = self.config.clone();
+        let maintenance = self.maintenance.clone();
+        let flags = self.flags.clone();
+        let stats = self.stats.clone();
+        let breaker = self.breaker.clone();
+        let concurrency = self.concurrency.clone();
+        let signatures = self.signatures.clone();
+        let captures = self.captures.clone();
+        let consistency = self.consistency.clone();
+        self.consistency.spawn();
+        self.cluster.spawn(self.db.clone());
+        self.alerts.spawn(self.db.clone(), self.cluster.clone());
+        if let Some(bridge) = self
+            .mqtt
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .take()
+        {
+            bridge.spawn(self.db.clone(), self.webhooks.clone(), self.config.clone());
+        }
+        if config.replication.peer.is_some() {
+            Replicator::new(self.db.clone(), config.replication.clone())
+                .spawn(self.cluster.clone());
+        }
+        HistorySweeper::new(self.db.clone(), config.history.clone()).spawn(self.cluster.clone());
+        RetentionSweeper::new(self.db.clone(), config.retention.clone())
+            .spawn(self.cluster.clone());
+        TieringJob::new(self.db.clone(), config.tiering.clone()).spawn(self.cluster.clone());
+        SnapshotScheduler::new(self.db.clone(), config.snapshots.clone())
+            .spawn(self.cluster.clone());
+        IntegrationScheduler::new(self.db.clone(), config.integrations.clone())
+            .spawn(self.cluster.clone());
+        KeyStatsFlusher::new(self.db.clone(), config.key_stats.clone()).spawn();
+        IndexAdvisor::new(self.db.clone(), config.index_advisor.clone()).spawn();
+        MetricsExporter::new(
+            self.db.clone(),
+            self.stats.clone(),
+            self.concurrency.clone(),
+            config.metrics.clone(),
+        )
+        .spawn();
+        WriteBehindFlusher::new(self.db.clone(), config.write_behind.clone()).spawn();
+        self.breaker.spawn_probe(self.db.clone());
+        self.flags.spawn(self.db.clone());
+        HttpServer::new(move || {
+            App::new()
+                .wrap(SignedUrlGuard::new(live.clone()))
+                .wrap(RequestSignatureGuard::new(live.clone(), signatures.clone()))
+                .wrap(CircuitBreakerGuard::new(breaker.clone()))
+                .wrap(FeatureGuard::new(flags.clone()))
+                .wrap(MaintenanceGuard::new(maintenance.clone()))
+                .wrap(ConcurrencyGuard::new(concurrency.clone()))
+                .wrap(TrafficRecorder::new(live.clone(), captures.clone()))
+                .wrap(BodySizeLimit::new(config.limits.max_body_size))
+                .wrap(ErrorLocalizer)
+                .wrap(RequestCounter::new(stats.clone()))
+                .wrap(ConsistencyTokens::new(consistency.clone()))
+                .wrap(RequestLogger)
+                .configure(configure.clone())
+        })
+        .workers(runtime::http_workers(&config.runtime))
+        .worker_max_blocking_threads(runtime::max_blocking_threads(&config.runtime))
+        .bind(&config.bind_address)?
+        .run()
+        .await?;
+        match self.db.lock().await.flush_writes(true).await {
+            Ok(flushed) if flushed > 0 => log::info!("Flushed {} buffered writes", flushed),
+            Ok(_) => {}
+            Err(e) => log::error!("Failed to flush buffered writes: {}", e),
+        }
+        self.cluster.leave(&*self.db.lock().await).await;
+        Ok(())
+    }
+
+    /// Returns a snapshot of the current configuration.
+    ///
+    /// # Returns
+    ///
+    /// * `Config` - The current configuration.
+    fn config(&self) -> Config {
+        self.config
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    /// Starts applying changes of the configuration file at runtime.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path of the configuration file.
+    ///
+    /// # Returns
+    ///
+    /// * `ConfigWatcher` - The watcher, which stops watching when dropped.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the file cannot be watched.
+    pub fn watch_config(&self, path: std::path::PathBuf) -> notify::Result<ConfigWatcher> {
+        ConfigWatcher::spawn(path, self.config.clone(), self.logging.clone())
+    }
+
+    /// Returns a function registering the application data and routes of the server.
+    ///
+    /// # Returns
+    ///
+    /// * `impl Fn(&mut ServiceConfig)` - The function configuring an application.
+    pub fn configure(&self) -> impl Fn(&mut web::ServiceConfig) + Clone + Send + 'static {
+        let db = web::Data::new(self.db.clone());
+        let jobs = web::Data::new(self.jobs.clone());
+        let webhooks = web::Data::from(self.webhooks.clone());
+        let logging = web::Data::from(self.logging.clone());
+        let live = web::Data::from(self.config.clone());
+        let maintenance = web::Data::from(self.maintenance.clone());
+        let flags = web::Data::from(self.flags.clone());
+        let stats = web::Data::from(self.stats.clone());
+        let ids = web::Data::from(self.ids.clone());
+        let health = web::Data::from(self.health.clone());
+        let breaker = web::Data::from(self.breaker.clone());
+        let concurrency = web::Data::from(self.concurrency.clone());
+        let imports = web::Data::new(self.imports.clone());
+        let privacy = web::Data::new(self.privacy.clone());
+        let captures = web::Data::from(self.captures.clone());
+        let cluster = web::Data::from(self.cluster.clone());
+        let alerts = web::Data::from(self.alerts.clone());
+        let graphql = web::Data::new(graphql::schema(
+            self.db.clone(),
+            self.webhooks.clone(),
+            self.config.clone(),
+        ));
+        let cors = self.config.clone();
+        let config = self.config();
+        let limits = config.limits.clone();
+        let dashboard = config.admin.dashboard;
+        move |cfg| {
+            cfg.app_data(db.clone())
+                .app_data(jobs.clone())
+                .app_data(webhooks.clone())
+                .app_data(logging.clone())
+                .app_data(live.clone())
+                .app_data(maintenance.clone())
+                .app_data(flags.clone())
+                .app_data(stats.clone())
+                .app_data(ids.clone())
+                .app_data(health.clone())
+                .app_data(breaker.clone())
+                .app_data(concurrency.clone())
+                .app_data(imports.clone())
+                .app_data(privacy.clone())
+                .app_data(captures.clone())
+                .app_data(cluster.clone())
+                .app_data(alerts.clone())
+                .app_data(graphql.clone());
+            Self::configure_routes(cfg, &limits, &cors);
+            if dashboard {
+                Dashboard::configure(cfg);
+            }
+        }
+    }
+
+    /// Registers the routes of the API, each with the CORS policy of its scope.
+    ///
+    /// # Arguments
+    ///
+    /// * `cfg` - The service configuration to register the routes on.
+    /// * `limits` - The configured request size limits.
+    /// * `config` - The live configuration holding the CORS policies.
+    fn configure_routes(
+        cfg: &mut web::ServiceConfig,
+        limits: &LimitsConfig,
+        config: &Arc<RwLock<Config>>,
+    ) {
+        let data = || Self::cors(config.clone(), CorsScope::of);
+        let read = || Self::cors(config.clone(), |_| CorsScope::Read);
+        let admin = || Self::cors(config.clone(), |_| CorsScope::Admin);
+        cfg.service(
+            Self::resource("/set_data", limits, data()).route(web::post().to(Self::set_data)),
+        )
+        .service(
+            Self::resource("/append_data", limits, data()).route(web::post().to(Self::append_data)),
+        )
+        .service(Self::resource("/get_data", limits, data()).route(web::get().to(Self::get_data)))
+        .service(
+            Self::resource("/set_int", limits, data())
+                .route(web::post().to(Self::set_typed::<i64>)),
+        )
+        .service(
+            Self::resource("/get_int", limits, data()).route(web::get().to(Self::get_typed::<i64>)),
+        )
+        .service(
+            Self::resource("/set_float", limits, data())
+                .route(web::post().to(Self::set_typed::<f64>)),
+        )
+        .service(
+            Self::resource("/get_float", limits, data())
+                .route(web::get().to(Self::get_typed::<f64>)),
+        )
+        .service(
+            Self::resource("/set_bool", limits, data())
+                .route(web::post().to(Self::set_typed::<bool>)),
+        )
+        .service(
+            Self::resource("/get_bool", limits, data())
+                .route(web::get().to(Self::get_typed::<bool>)),
+        )
+        .service(
+            Self::resource("/graphql", limits, data())
+                .route(web::get().to(Self::graphql))
+                .route(web::post().to(Self::graphql)),
+        )
+        .service(
+            Self::resource("/get_data_at", limits, data()).route(web::get().to(Self::get_data_at)),
+        )
+        .service(Self::resource("/history", limits, data()).route(web::get().to(Self::history)))
+        .service(
+            Self::resource("/admin/history/compact", limits, admin())
+                .route(web::post().to(Self::compact_history)),
+        )
+        .service(
+            Self::resource("/consistent_export", limits, data())
+                .route(web::get().to(Self::consistent_export)),
+        )
+        .service(
+            Self::resource("/stream_table/{table}", limits, data())
+                .route(web::get().to(Self::stream_table)),
+        )
+        .service(
+            Self::resource("/import/{table}", limits, data())
+                .route(web::post().to(Self::import_table)),
+        )
+        .service(
+            Self::resource("/import_csv", limits, data()).route(web::post().to(Self::import_csv)),
+        )
+        .service(
+            Self::resource("/imports/{id}", limits, data())
+                .route(web::get().to(Self::import_progress)),
+        )
+        .service(Self::resource("/get_many", limits, read()).route(web::post().to(Self::get_many)))
+        .service(
+            Self::resource("/update_data", limits, data()).route(web::put().to(Self::update_data)),
+        )
+        .service(
+            Self::resource("/patch_data", limits, data()).route(web::patch().to(Self::patch_data)),
+        )
+        .service(
+            Self::resource("/increment", limits, data()).route(web::post().to(Self::increment)),
+        )
+        .service(
+            Self::resource("/compare_and_set", limits, data())
+                .route(web::post().to(Self::compare_and_set)),
+        )
+        .service(
+            Self::resource("/list/push", limits, data()).route(web::post().to(Self::list_push)),
+        )
+        .service(Self::resource("/list/pop", limits, data()).route(web::post().to(Self::list_pop)))
+        .service(
+            Self::resource("/list/range", limits, data()).route(web::get().to(Self::list_range)),
+        )
+        .service(
+            Self::resource("/events", limits, data())
+                .route(web::get().to(Self::event_logs))
+                .route(web::post().to(Self::create_event_log))
+                .route(web::delete().to(Self::delete_event_log)),
+        )
+        .service(
+            Self::resource("/events/append", limits, data())
+                .route(web::post().to(Self::append_events)),
+        )
+        .service(
+            Self::resource("/events/read", limits, data()).route(web::get().to(Self::read_events)),
+        )
+        .service(
+            Self::resource("/events/consumers", limits, data())
+                .route(web::get().to(Self::consumer_offsets))
+                .route(web::put().to(Self::commit_consumer_offset)),
+        )
+        .service(
+            Self::resource("/series", limits, data())
+                .route(web::get().to(Self::time_series))
+                .route(web::post().to(Self::create_time_series))
+                .route(web::delete().to(Self::delete_time_series)),
+        )
+        .service(
+            Self::resource("/series/append", limits, data())
+                .route(web::post().to(Self::append_points)),
+        )
+        .service(
+            Self::resource("/series/points", limits, data())
+                .route(web::get().to(Self::read_points)),
+        )
+        .service(
+            Self::resource("/series/aggregate", limits, data())
+                .route(web::get().to(Self::aggregate_points)),
+        )
+        .service(
+            Self::resource("/views", limits, data())
+                .route(web::get().to(Self::views))
+                .route(web::post().to(Self::create_view))
+                .route(web::delete().to(Self::delete_view)),
+        )
+        .service(
+            Self::resource("/views/{name}", limits, data()).route(web::get().to(Self::view_groups)),
+        )
+        .service(Self::resource("/fulltext", limits, data()).route(web::get().to(Self::fulltext)))
+        .service(
+            Self::resource("/sync/push", limits, data()).route(web::post().to(Self::sync_push)),
+        )
+        .service(Self::resource("/sync/pull", limits, data()).route(web::get().to(Self::sync_pull)))
+        .service(
+            Self::resource("/lock", limits, data())
+                .route(web::get().to(Self::get_lock))
+                .route(web::post().to(Self::lock)),
+        )
+        .service(Self::resource("/unlock", limits, data()).route(web::post().to(Self::unlock)))
+        .service(
+            Self::resource("/delete_data", limits, data())
+                .route(web::delete().to(Self::delete_data)),
+        )
+        .service(
+            Self::resource("/delete_many", limits, data()).route(web::post().to(Self::delete_many)),
+        )
+        .service(
+            Self::resource("/delete_table", limits, data())
+                .route(web::delete().to(Self::delete_table)),
+        )
+        .service(
+            Self::resource("/tables", limits, data())
+                .route(web::get().to(Self::list_tables))
+                .route(web::post().to(Self::create_table)),
+        )
+        .service(
+            Self::resource("/clone_table", limits, data()).route(web::post().to(Self::clone_table)),
+        )
+        .service(
+            Self::resource("/snapshots", limits, data())
+                .route(web::get().to(Self::snapshots))
+                .route(web::post().to(Self::create_snapshot))
+                .route(web::delete().to(Self::delete_snapshot)),
+        )
+        .service(
+            Self::resource("/snapshots/{id}/diff", limits, data())
+                .route(web::get().to(Self::snapshot_diff)),
+        )
+        .service(
+            Self::resource("/templates", limits, data())
+                .route(web::get().to(Self::templates))
+                .route(web::put().to(Self::add_template))
+                .route(web::delete().to(Self::remove_template)),
+        )
+        .service(Self::resource("/keys", limits, data()).route(web::get().to(Self::list_keys)))
+        .service(Self::resource("/tree", limits, data()).route(web::get().to(Self::tree)))
+        .service(Self::resource("/activity", limits, data()).route(web::get().to(Self::activity)))
+        .service(
+            Self::resource("/comments", limits, data())
+                .route(web::get().to(Self::comments))
+                .route(web::post().to(Self::post_comment))
+                .route(web::put().to(Self::edit_comment))
+                .route(web::delete().to(Self::delete_comment)),
+        )
+        .service(
+            Self::resource("/projects", limits, data())
+                .route(web::get().to(Self::list_projects))
+                .route(web::post().to(Self::create_project))
+                .route(web::delete().to(Self::delete_project)),
+        )
+        .service(
+            Self::resource("/projects/tables", limits, data())
+                .route(web::get().to(Self::project_tables))
+                .route(web::post().to(Self::add_project_table))
+                .route(web::delete().to(Self::remove_project_table)),
+        )
+        .service(
+            Self::resource("/admin/log_level", limits, admin())
+                .route(web::get().to(Self::get_log_level))
+                .route(web::put().to(Self::set_log_level)),
+        )
+        .service(
+            Self::resource("/startupz", limits, data()).route(web::get().to(Self::startup_probe)),
+        )
+        .service(
+            Self::resource("/livez", limits, data()).route(web::get().to(Self::liveness_probe)),
+        )
+        .service(
+            Self::resource("/readyz", limits, data()).route(web::get().to(Self::readiness_probe)),
+        )
+        .service(
+            Self::resource("/admin/config", limits, admin()).route(web::get().to(Self::get_config)),
+        )
+        .service(
+            Self::resource("/admin/schema", limits, admin()).route(web::get().to(Self::get_schema)),
+        )
+        .service(
+            Self::resource("/stats/hot_keys", limits, data()).route(web::get().to(Self::hot_keys)),
+        )
+        .service(
+            Self::resource("/admin/stats", limits, admin()).route(web::get().to(Self::get_stats)),
+        )
+        .service(
+            Self::resource("/admin/metrics", limits, admin())
+                .route(web::get().to(Self::get_metrics)),
+        )
+        .service(
+            Self::resource("/admin/captures", limits, admin())
+                .route(web::get().to(Self::captures))
+                .route(web::delete().to(Self::clear_captures)),
+        )
+        .service(
+            Self::resource("/admin/privacy", limits, admin())
+                .route(web::post().to(Self::start_privacy_request)),
+        )
+        .service(
+            Self::resource("/admin/privacy/{id}", limits, admin())
+                .route(web::get().to(Self::privacy_report)),
+        )
+        .service(
+            Self::resource("/admin/privacy/{id}/export", limits, admin())
+                .route(web::get().to(Self::privacy_export)),
+        )
+        .service(
+            Self::resource("/admin/pool", limits, admin()).route(web::get().to(Self::get_pool)),
+        )
+        .service(
+            Self::resource("/admin/pool/recycle", limits, admin())
+                .route(web::post().to(Self::recycle_pool)),
+        )
+        .service(
+            Self::resource("/admin/runtime", limits, admin())
+                .route(web::get().to(Self::get_runtime)),
+        )
+        .service(
+            Self::resource("/admin/cluster", limits, admin())
+                .route(web::get().to(Self::get_cluster)),
+        )
+        .service(
+            Self::resource("/admin/alerts", limits, admin()).route(web::get().to(Self::get_alerts)),
+        )
+        .service(Self::resource("/admin/sql", limits, admin()).route(web::post().to(Self::run_sql)))
+        .service(
+            Self::resource("/admin/tiering", limits, admin())
+                .route(web::post().to(Self::run_tiering)),
+        )
+        .service(
+            Self::resource("/admin/maintenance", limits, admin())
+                .route(web::get().to(Self::get_maintenance))
+                .route(web::put().to(Self::set_maintenance)),
+        )
+        .service(
+            Self::resource("/admin/slow_queries", limits, admin())
+                .route(web::get().to(Self::slow_queries)),
+        )
+        .service(
+            Self::resource("/admin/index_advisor", limits, admin())
+                .route(web::get().to(Self::index_advisor)),
+        )
+        .service(
+            Self::resource("/admin/reencrypt", limits, admin())
+                .route(web::post().to(Self::reencrypt)),
+        )
+        .service(
+            Self::resource("/admin/flags", limits, admin())
+                .route(web::get().to(Self::feature_flags))
+                .route(web::put().to(Self::set_feature_flag))
+                .route(web::delete().to(Self::delete_feature_flag)),
+        )
+        .service(Self::resource("/flags", limits, data()).route(web::get().to(Self::feature_flag)))
+        .service(
+            Self::resource("/admin/table_policies", limits, admin())
+                .route(web::get().to(Self::table_policies))
+                .route(web::put().to(Self::set_table_policy))
+                .route(web::delete().to(Self::delete_table_policy)),
+        )
+        .service(
+            Self::resource("/admin/holds", limits, admin())
+                .route(web::get().to(Self::legal_holds))
+                .route(web::post().to(Self::place_legal_hold))
+                .route(web::delete().to(Self::lift_legal_hold)),
+        )
+        .service(
+            Self::resource("/admin/integrations", limits, admin())
+                .route(web::get().to(Self::integrations))
+                .route(web::put().to(Self::put_integration)),
+        )
+        .service(
+            Self::resource("/admin/integrations/{name}", limits, admin())
+                .route(web::delete().to(Self::delete_integration)),
+        )
+        .service(
+            Self::resource("/admin/integrations/{name}/run", limits, admin())
+                .route(web::post().to(Self::run_integration)),
+        )
+        .service(
+            Self::resource("/admin/json_schemas", limits, admin())
+                .route(web::get().to(Self::json_schemas))
+                .route(web::put().to(Self::set_json_schema))
+                .route(web::delete().to(Self::delete_json_schema)),
+        )
+        .service(
+            Self::resource("/admin/regions", limits, admin())
+                .route(web::get().to(Self::table_regions))
+                .route(web::put().to(Self::set_table_region)),
+        )
+        .service(
+            Self::resource("/admin/transforms", limits, admin())
+                .route(web::get().to(Self::transforms))
+                .route(web::post().to(Self::add_transform))
+                .route(web::delete().to(Self::delete_transform)),
+        )
+        .service(
+            Self::resource("/admin/sign_url", limits, admin())
+                .route(web::post().to(Self::sign_url)),
+        )
+        .service(
+            Self::resource("/signed/get_data", limits, data())
+                .route(web::get().to(Self::signed_get_data)),
+        )
+        .service(
+            Self::resource("/replication/apply", limits, data())
+                .app_data(web::PayloadConfig::new(limits.max_body_size))
+                .route(web::post().to(Self::apply_replication)),
+        )
+        .service(
+            Self::resource("/webhooks", limits, data())
+                .route(web::post().to(Self::register_webhook))
+                .route(web::delete().to(Self::delete_webhook)),
+        );
+    }
+
+    /// Builds the response to a failed database operation.
+    ///
+    /// # Arguments
+    ///
+    /// * `e` - The error of the operation.
+    /// * `code` - The code of the error of the response.
+    ///
+    /// # Returns
+    ///
+    /// * `HttpResponse` - A `400` if the request named a table outside the managed tables, a
+    ///   `503` with a `Retry-After` header if the error is transient and retrying it exhausted
+    ///   the budget, a `500` otherwise.
+    fn database_error(e: &sqlx::Error, code: &str) -> HttpResponse {
+        if let sqlx::Error::InvalidArgument(reason) = e {
+            let mut response = ApiResponse::error(code);
+            response.message = format!("{}: {}", response.message, reason);
+            HttpResponse::BadRequest().json(response)
+        } else if RetryPolicy::is_transient(e) {
+            HttpResponse::ServiceUnavailable()
+                .insert_header((http::header::RETRY_AFTER, "1"))
+                .json(ApiResponse::error(code))
+        } else {
+            HttpResponse::InternalServerError().json(ApiResponse::error(code))
+        }
+    }
+
+    /// Validates a value written to a table against the value type and JSON Schema of the table.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - The database holding the schema of the table.
+    /// * `table` - The table the value is written to.
+    /// * `value` - The raw value to validate.
+    /// * `code` - The code of the error of the response if the schema cannot be retrieved.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<HttpResponse>` - A `422` listing every violation if the value is invalid, a `500`
+    ///   if the schema cannot be retrieved, or `None` if the value is valid.
+    async fn check_value(
+        db: &Database,
+        table: &str,
+        value: &str,
+        code: &str,
+    ) -> Option<HttpResponse> {
+        match db.validate_value(table, value).await {
+            Ok(Ok(())) => None,
+            Ok(Err(violations)) => Some(HttpResponse::UnprocessableEntity().json(ApiResponse::<
+                Vec<SchemaViolation>,
+            >::error_with_data(
+                "value_does_not_match_the_table_schema",
+                &[("violations", &SchemaViolation::describe(&violations))],
+                Some(violations),
+            ))),
+            Err(e) => {
+                log::error!("Failed to retrieve the schema of {}: {}", table, e);
+                Some(HttpResponse::InternalServerError().json(ApiResponse::error(code)))
+            }
+        }
+    }
+
+    /// Creates a resource for the given path with its JSON payload limit and CORS policy applied.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path of the resource.
+    /// * `limits` - The configured request size limits.
+    /// * `cors` - The CORS middleware of the scope of the resource.
+    ///
+    /// # Returns
+    ///
+    /// * `Resource` - The resource with its payload configuration, read by the [`Json`] extractor.
+    fn resource(
+        path: &str,
+        limits: &LimitsConfig,
+        cors: Cors,
+    ) -> actix_web::Resource<
+        impl ServiceFactory<
+            ServiceRequest,
+            Config = (),
+            Response = ServiceResponse<EitherBody<BoxBody>>,
+            Error = actix_web::Error,
+            InitError = (),
+        >,
+    > {
+        web::resource(path)
+            .app_data(web::PayloadConfig::new(limits.json_limit(path)))
+            .wrap(cors)
+    }
+
+    /// Rejects a write to a table under a legal hold.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - The database holding the legal holds.
+    /// * `table` - The table being written to.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<HttpResponse>` - The rejection, or `None` if the write may proceed.
+    async fn check_hold(db: &Database, table: &str) -> Option<HttpResponse> {
+        match db.legal_hold(table).await {
+            Ok(Some(hold)) => Some(HttpResponse::Forbidden().json(
+                ApiResponse::<HeldTable>::error_with_data(
+                    LEGAL_HOLD_CODE,
+                    &[("reason", &hold.reason.clone())],
+                    Some(HeldTable {
+                        code: LEGAL_HOLD_CODE.to_string(),
+                        hold,
+                    }),
+                ),
+            )),
+            Ok(None) => None,
+            Err(e) => {
+                log::error!("Failed to check legal hold: {}", e);
+                Some(
+                    HttpResponse::InternalServerError()
+                        .json(ApiResponse::error("failed_to_check_legal_hold")),
+                )
+            }
+        }
+    }
+
+    /// Rejects a write to a table under a legal hold or during a schema operation, or to a key
+    /// locked by another owner when lock enforcement is enabled.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - The database holding the leases.
+    /// * `config` - The live configuration deciding whether locks are enforced.
+    /// * `req` - The request naming the lock owner of the writer.
+    /// * `table` - The table being written to.
+    /// * `key` - The key being written.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<HttpResponse>` - The rejection, or `None` if the write may proceed.
+    async fn check_lock(
+        db: &Database,
+        config: &RwLock<Config>,
+        req: &HttpRequest,
+        table: &str,
+        key: &str,
+    ) -> Option<HttpResponse> {
+        if let Some(response) = Self::check_hold(db, table).await {
+            return Some(response);
+        }
+        match db.schema_lock(table).await {
+            Ok(Some(lock)) => {
+                return Some(HttpResponse::Conflict().json(
+                    ApiResponse::<SchemaLock>::error_with_data(
+                        "table_is_locked",
+                        &[("operation", &lock.operation.clone())],
+                        Some(lock),
+                    ),
+                ))
+            }
+            Ok(None) => {}
+            Err(e) => {
+                log::error!("Failed to check schema lock: {}", e);
+                return Some(
+                    HttpResponse::InternalServerError()
+                        .json(ApiResponse::error("failed_to_check_schema_lock")),
+                );
+            }
+        }
+        if !config
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .locks
+            .enforce
+        {
+            return None;
+        }
+        let owner = req
+            .headers()
+            .get(LOCK_OWNER_HEADER)
+            .and_then(|v| v.to_str().ok());
+        match db.get_lock(table, key).await {
+            Ok(Some(lock)) if Some(lock.owner.as_str()) != owner => Some(
+                HttpResponse::Locked().json(ApiResponse::<Lock>::error_with_data(
+                    "key_is_locked",
+                    &[("owner", &lock.owner.clone())],
+                    Some(lock),
+                )),
+            ),
+            Ok(_) => None,
+            Err(e) => {
+                log::error!("Failed to check lock: {}", e);
+                Some(
+                    HttpResponse::InternalServerError()
+                        .json(ApiResponse::error("failed_to_check_lock")),
+                )
+            }
+        }
+    }
+
+    /// Runs a schema operation on a table while holding its schema lock, so writes to the table
+    /// are rejected until it completes.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - The database holding the locks.
+    /// * `table` - The table the operation changes.
+    /// * `operation` - The name of the operation.
+    /// * `run` - The operation.
+    ///
+    /// # Returns
+    ///
+    /// * `HttpResponse` - The response of the operation, or a conflict if another schema
+    ///   operation holds the lock.
+    async fn with_schema_lock<F, Fut>(
+        db: &Database,
+        table: &str,
+        operation: &str,
+        run: F,
+    ) -> HttpResponse
+    where
+        F: FnOnce() -> Fut,
+        Fut: std::future::Future<Output = HttpResponse>,
+    {
+        match db
+            .acquire_schema_lock(table, operation, SCHEMA_LOCK_LEASE_SECS)
+            .await
+        {
+            Ok(Ok(())) => {}
+            Ok(Err(lock)) => {
+                return HttpResponse::Conflict().json(ApiResponse::<SchemaLock>::error_with_data(
+                    "table_is_locked",
+                    &[("operation", &lock.operation.clone())],
+                    Some(lock),
+                ))
+            }
+            Err(e) => {
+                log::error!("Failed to acquire schema lock: {}", e);
+                return Self::database_error(&e, "failed_to_acquire_schema_lock");
+            }
+        }
+        let response = run().await;
+        if let Err(e) = db.release_schema_lock(table).await {
+            log::error!("Failed to release schema lock: {}", e);
+        }
+        response
+    }
+
+    /// Sets data in the database based on the provided key-value pair.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - A reference to the database wrapped in an Arc and Mutex for thread safety.
+    /// * `webhooks` - The dispatcher notifying the webhooks of the table.
+    /// * `config` - The live configuration deciding whether locks are enforced.
+    /// * `req` - The request naming the lock owner of the writer.
+    /// * `item` - The key-value pair to be set in the database.
+    ///
+    /// # Returns
+    ///
+    /// * `HttpResponse` - The HTTP response indicating success or failure.
+    async fn set_data(
+        db: web::Data<Arc<Mutex<Database>>>,
+        webhooks: web::Data<Webhooks>,
+        config: web::Data<RwLock<Config>>,
+        req: HttpRequest,
+        item: Json<TableKeyValue>,
+    ) -> impl Responder {
+        let db = db.lock().await;
+        if let Some(response) = Self::check_lock(&db, &config, &req, &item.table, &item.key).await {
+            return response;
+        }
+        if let Some(response) =
+            Self::check_value(&db, &item.table, &item.value, "failed_to_set_data").await
+        {
+            return response;
+        }
+        match db.set_data(&item.table, &item.key, &item.value).await {
+            Ok(_) => {
+                webhooks
+                    .notify(
+                        &db,
+                        WebhookEvent::new("set", &item.table, Some(&item.key), Some(&item.value)),
+                    )
+                    .await;
+                HttpResponse::Ok().json(ApiResponse::<()> {
+                    status: "success".to_string(),
+                    code: None,
+                    message: "Data set successfully".to_string(),
+                    data: None,
+                })
+            }
+            Err(e) => {
+                log::error!("Failed to set data: {}", e);
+                Self::database_error(&e, "failed_to_set_data")
+            }
+        }
+    }
+
+    /// Stores a value under a key generated by the server, for clients appending events
+    /// without coordinating unique keys.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - A reference to the database wrapped in an Arc and Mutex for thread safety.
+    /// * `webhooks` - The dispatcher notifying the webhooks of the table.
+    /// * `ids` - The generator of the keys in the configured format.
+    /// * `item` - The table and the value to append.
+    ///
+    /// # Returns
+    ///
+    /// * `HttpResponse` - The HTTP response containing the generated key.
+    async fn append_data(
+        db: web::Data<Arc<Mutex<Database>>>,
+        webhooks: web::Data<Webhooks>,
+        ids: web::Data<KeyGenerator>,
+        item: Json<TableValue>,
+    ) -> impl Responder {
+        let db = db.lock().await;
+        if let Some(response) = Self::check_hold(&db, &item.table).await {
+            return response;
+        }
+        if let Some(response) =
+            Self::check_value(&db, &item.table, &item.value, "failed_to_append_data").await
+        {
+            return response;
+        }
+        let key = ids.generate();
+        match db.set_data(&item.table, &key, &item.value).await {
+            Ok(_) => {
+                webhooks
+                    .notify(
+                        &db,
+                        WebhookEvent::new("set", &item.table, Some(&key), Some(&item.value)),
+                    )
+                    .await;
+                HttpResponse::Ok().json(ApiResponse::<AppendedKey> {
+                    status: "success".to_string(),
+                    code: None,
+                    message: "Data appended successfully".to_string(),
+                    data: Some(AppendedKey { key }),
+                })
+            }
+            Err(e) => {
+                log::error!("Failed to append data: {}", e);
+                Self::database_error(&e, "failed_to_append_data")
+            }
+        }
+    }
+
+    /// Retrieves data from the database based on the provided key.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - A reference to the database wrapped in an Arc and Mutex for thread safety.
+    /// * `item` - The key for which the data needs to be retrieved and the fields to return, as
+    ///   JSON body or query string.
+    ///
+    /// # Returns
+    ///
+    /// * `HttpResponse` - The HTTP response containing the data or an error message.
+    async fn get_data(
+        db: web::Data<Arc<Mutex<Database>>>,
+        item: web::Either<Json<TableKeyProjection>, web::Query<TableKeyProjection>>,
+    ) -> impl Responder {
+        let item = match item {
+            web::Either::Left(json) => json.into_inner(),
+            web::Either::Right(query) => query.into_inner(),
+        };
+        let projection = match Projection::from_request(item.fields.as_deref()) {
+            Ok(projection) => projection,
+            Err(reason) => {
+                return HttpResponse::BadRequest().json(ApiResponse::error_with(
+                    "invalid_fields",
+                    &[("reason", &reason)],
+                ))
+            }
+        };
+        let db = db.lock().await;
+        let value_type = match db.get_value_type(&item.table).await {
+            Ok(value_type) => value_type,
+            Err(e) => {
+                log::error!("Failed to get data: {}", e);
+                return HttpResponse::InternalServerError()
+                    .json(ApiResponse::error("failed_to_retrieve_data"));
+            }
+        };
+        match db.get_data(&item.table, &item.key).await {
+            Ok(Some(value)) => {
+                db.record_read(&item.table, &item.key);
+                let value = value_type.to_json(&value);
+                HttpResponse::Ok().json(ApiResponse::<serde_json::Value> {
+                    status: "success".to_string(),
+                    code: None,
+                    message: "Data retrieved successfully".to_string(),
+                    data: Some(match &projection {
+                        Some(projection) => projection.apply(value),
+                        None => value,
+                    }),
+                })
+            }
+            Ok(None) => HttpResponse::NotFound().json(ApiResponse::error("data_not_found")),
+            Err(e) => {
+                log::error!("Failed to get data: {}", e);
+                Self::database_error(&e, "failed_to_retrieve_data")
+            }
+        }
+    }
+
+    /// Sets a typed value, stored as its string representation like any value set with
+    /// `/set_data`.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - A reference to the database wrapped in an Arc and Mutex for thread safety.
+    /// * `webhooks` - The dispatcher notifying the webhooks of the table.
+    /// * `config` - The live configuration deciding whether locks are enforced.
+    /// * `req` - The request naming the lock owner of the writer.
+    /// * `item` - The key and the typed value, any value of another type being refused.
+    ///
+    /// # Returns
+    ///
+    /// * `HttpResponse` - The HTTP response indicating success or failure.
+    async fn set_typed<T: TypedValue>(
+        db: web::Data<Arc<Mutex<Database>>>,
+        webhooks: web::Data<Webhooks>,
+        config: web::Data<RwLock<Config>>,
+        req: HttpRequest,
+        item: Json<TypedTableKeyValue<T>>,
+    ) -> impl Responder {
+        let item = item.into_inner();
+        let item = TableKeyValue {
+            table: item.table,
+            key: item.key,
+            value: item.value.to_string(),
+        };
+        Self::set_data(db, webhooks, config, req, Json(item)).await
+    }
+
+    /// Retrieves a value converted to a primitive type.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - A reference to the database wrapped in an Arc and Mutex for thread safety.
+    /// * `item` - The key for which the data needs to be retrieved, as JSON body or query string.
+    ///
+    /// # Returns
+    ///
+    /// * `HttpResponse` - The HTTP response containing the typed value, a `404` if the key does
+    ///   not exist, or a `422` if the value cannot be converted.
+    async fn get_typed<T: TypedValue>(
+        db: web::Data<Arc<Mutex<Database>>>,
+        item: web::Either<Json<TableKey>, web::Query<TableKey>>,
+    ) -> impl Responder {
+        let item = match item {
+            web::Either::Left(json) => json.into_inner(),
+            web::Either::Right(query) => query.into_inner(),
+        };
+        let db = db.lock().await;
+        match db.get_data(&item.table, &item.key).await {
+            Ok(Some(value)) => match value.trim().parse::<T>() {
+                Ok(typed) => {
+                    db.record_read(&item.table, &item.key);
+                    HttpResponse::Ok().json(ApiResponse::<T> {
+                        status: "success".to_string(),
+                        code: None,
+                        message: "Data retrieved successfully".to_string(),
+                        data: Some(typed),
+                    })
+                }
+                Err(_) => HttpResponse::UnprocessableEntity().json(ApiResponse::error_with(
+                    T::ERROR_CODE,
+                    &[("key", &item.key)],
+                )),
+            },
+            Ok(None) => HttpResponse::NotFound().json(ApiResponse::error("data_not_found")),
+            Err(e) => {
+                log::error!("Failed to get data: {}", e);
+                Self::database_error(&e, "failed_to_retrieve_data")
+            }
+        }
+    }
+
+    /// Executes a GraphQL query or mutation over the tables, their entries and comments, and
+    /// the projects.
+    ///
+    /// # Arguments
+    ///
+    /// * `schema` - The schema resolving the request against the database.
+    /// * `req` - The request naming the lock owner of the writer.
+    /// * `request` - The GraphQL request, from the query string or the JSON body.
+    ///
+    /// # Returns
+    ///
+    /// * `GraphQLResponse` - The GraphQL response holding the data and the errors.
+    async fn graphql(
+        schema: web::Data<GraphQLSchema>,
+        req: HttpRequest,
+        request: GraphQLRequest,
+    ) -> GraphQLResponse {
+        let owner = req
+            .headers()
+            .get(LOCK_OWNER_HEADER)
+            .and_then(|v| v.to_str().ok())
+            .map(str::to_string);
+        schema
+            .execute(request.into_inner().data(LockOwner(owner)))
+            .await
+            .into()
+    }
+
+    /// Retrieves the values of many keys in one round trip.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - A reference to the database wrapped in an Arc and Mutex for thread safety.
+    /// * `item` - A table with a list of keys, and/or a list of table-key pairs, with the fields
+    ///   to return.
+    ///
+    /// # Returns
+    ///
+    /// * `HttpResponse` - The HTTP response containing the found values and the missing keys.
+    async fn get_many(db: web::Data<Arc<Mutex<Database>>>, item: Json<GetMany>) -> impl Responder {
+        let GetMany {
+            table,
+            keys,
+            items,
+            fields,
+        } = item.into_inner();
+        let projection = match Projection::from_request(fields.as_deref()) {
+            Ok(projection) => projection,
+            Err(reason) => {
+                return HttpResponse::BadRequest().json(ApiResponse::error_with(
+                    "invalid_fields",
+                    &[("reason", &reason)],
+                ))
+            }
+        };
+        let mut requested: BTreeMap<String, Vec<String>> = BTreeMap::new();
+        if let Some(table) = table {
+            requested.entry(table).or_default().extend(keys);
+        }
+        for item in items {
+            requested.entry(item.table).or_default().push(item.key);
+        }
+
+        let db = db.lock().await;
+        let mut result = GetManyResult {
+            found: Vec::new(),
+            missing: Vec::new(),
+        };
+        for (table, keys) in requested {
+            let found = match db.get_value_type(&table).await {
+                Ok(value_type) => db.get_many(&table, &keys).await.map(|found| {
+                    found
+                        .into_iter()
+                        .map(|(key, value)| (key, value_type.to_json(&value)))
+                        .collect::<HashMap<_, _>>()
+                }),
+                Err(e) => Err(e),
+            };
+            let mut found = match found {
+                Ok(found) => found,
+                Err(e) => {
+                    log::error!("Failed to get data: {}", e);
+                    return Self::database_error(&e, "failed_to_retrieve_data");
+                }
+            };
+            for key in keys {
+                match found.remove(&key) {
+                    Some(value) => {
+                        db.record_read(&table, &key);
+                        result.found.push(FoundValue {
+                            table: table.clone(),
+                            key,
+                            value: match &projection {
+                                Some(projection) => projection.apply(value),
+                                None => value,
+                            },
+                        })
+                    }
+                    None => result.missing.push(TableKey {
+                        table: table.clone(),
+                        key,
+                    }),
+                }
+            }
+        }
+        HttpResponse::Ok().json(ApiResponse::<GetManyResult> {
+            status: "success".to_string(),
+            code: None,
+            message: "Data retrieved successfully".to_string(),
+            data: Some(result),
+        })
+    }
+    /// Streams all key-value pairs of a table as newline-delimited JSON.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - A reference to the database wrapped in an Arc and Mutex for thread safety.
+    /// * `table` - The table to stream.
+    ///
+    /// # Returns
+    ///
+    /// * `HttpResponse` - The HTTP response streaming one JSON object per line.
+    async fn stream_table(
+        db: web::Data<Arc<Mutex<Database>>>,
+        table: web::Path<String>,
+    ) -> impl Responder {
+        let db = db.lock().await;
+        let value_type = match db.get_value_type(&table).await {
+            Ok(value_type) => value_type,
+            Err(e) => {
+                log::error!("Failed to stream table: {}", e);
+                return HttpResponse::InternalServerError()
+                    .json(ApiResponse::error("failed_to_stream_table"));
+            }
+        };
+        let rows = match db.stream_table(&table).await {
+            Ok(rows) => rows,
+            Err(e) => {
+                log::error!("Failed to stream table: {}", e);
+                return HttpResponse::InternalServerError()
+                    .json(ApiResponse::error("failed_to_stream_table"));
+            }
+        };
+        // The rows are read on a connection of their own, so other requests are served while
+        // the table streams.
+        drop(db);
+        HttpResponse::Ok()
+            .content_type("application/x-ndjson")
+            .streaming(Self::ndjson(rows, value_type))
+    }
+
+    /// Streams a table exported within one read transaction as newline-delimited JSON, so the
+    /// export represents a single point in time while writes continue.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - A reference to the database wrapped in an Arc and Mutex for thread safety.
+    /// * `item` - The table to export.
+    ///
+    /// # Returns
+    ///
+    /// * `HttpResponse` - The HTTP response streaming one JSON object per line, with the number
+    ///   of exported rows in the `X-Row-Count` header.
+    async fn consistent_export(
+        db: web::Data<Arc<Mutex<Database>>>,
+        item: web::Query<Table>,
+    ) -> impl Responder {
+        let db = db.lock().await;
+        let value_type = match db.get_value_type(&item.table).await {
+            Ok(value_type) => value_type,
+            Err(e) => {
+                log::error!("Failed to export table: {}", e);
+                return HttpResponse::InternalServerError()
+                    .json(ApiResponse::error("failed_to_export_table"));
+            }
+        };
+        let (count, rows) = match db.consistent_export(&item.table).await {
+            Ok(export) => export,
+            Err(e) => {
+                log::error!("Failed to export table: {}", e);
+                return Self::database_error(&e, "failed_to_export_table");
+            }
+        };
+        drop(db);
+        HttpResponse::Ok()
+            .content_type("application/x-ndjson")
+            .insert_header((ROW_COUNT_HEADER, count.to_string()))
+            .streaming(Self::ndjson(rows, value_type))
+    }
+
+    /// Encodes streamed key-value pairs as newline-delimited JSON.
+    ///
+    /// # Arguments
+    ///
+    /// * `rows` - The receiver yielding the key-value pairs.
+    /// * `value_type` - The value type of the table, typing the values.
+    ///
+    /// # Returns
+    ///
+    /// * `impl Stream` - The stream of lines, ending with an error if reading a row fails.
+    fn ndjson(
+        rows: RowStream,
+        value_type: ValueType,
+    ) -> impl futures::Stream<Item = Result<web::Bytes, actix_web::Error>> {
+        futures::stream::unfold(rows, move |mut rows| async move {
+            let line = rows.recv().await?.map_err(|e| {
+                log::error!("Failed to stream table: {}", e);
+                actix_web::error::ErrorInternalServerError("failed_to_stream_table")
+            });
+            let line = line.and_then(|(key, value)| {
+                let mut line = serde_json::to_vec(&StreamedRow {
+                    value: value_type.to_json(&value),
+                    key,
+                })?;
+                line.push(b'\n');
+                Ok(web::Bytes::from(line))
+            });
+            Some((line, rows))
+        })
+    }
+
+    /// Imports NDJSON rows, in the format of `/stream_table`, into a table in the background.
+    ///
+    /// The body may be gzip compressed, in which case it is decompressed while it is spooled
+    /// to a temporary file. Rows that cannot be parsed or do not match the value type of the
+    /// table are skipped and reported in the progress of the import.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - A reference to the database wrapped in an Arc and Mutex for thread safety.
+    /// * `jobs` - The job queue running the import.
+    /// * `imports` - The registry tracking the progress of the imports.
+    /// * `concurrency` - The limits whose interactive lane the import yields to.
+    /// * `req` - The request carrying the `Content-Encoding` of the body.
+    /// * `table` - The table to import into.
+    /// * `query` - Whether to only validate the rows without writing them.
+    /// * `payload` - The body of the request.
+    ///
+    /// # Returns
+    ///
+    /// * `HttpResponse` - The HTTP response containing the progress of the accepted import.
+    #[allow(clippy::too_many_arguments)]
+    async fn import_table(
+        db: web::Data<Arc<Mutex<Database>>>,
+        jobs: web::Data<JobQueue>,
+        imports: web::Data<Imports>,
+        concurrency: web::Data<ConcurrencyLimits>,
+        req: HttpRequest,
+        table: web::Path<String>,
+        query: web::Query<DryRunQuery>,
+        payload: web::Payload,
+    ) -> impl Responder {
+        let table = table.into_inner();
+        Self::start_import(
+            &db,
+            &jobs,
+            &imports,
+            concurrency.into_inner(),
+            &req,
+            table,
+            query.dry_run,
+            payload,
+            None,
+        )
+        .await
+    }
+
+    /// Imports the rows of a CSV file into a table in the background.
+    ///
+    /// The first line of the file names the columns. One column holds the keys and the mapped
+    /// columns compose JSON object values, written in batched transactions. Rows with an empty
+    /// key or a value not matching the schema of the table are skipped and reported with their
+    /// line in the progress of the import, polled at `/imports/{id}`. The body may be gzip
+    /// compressed.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - A reference to the database wrapped in an Arc and Mutex for thread safety.
+    /// * `jobs` - The job queue running the import.
+    /// * `imports` - The registry tracking the progress of the imports.
+    /// * `concurrency` - The limits whose interactive lane the import yields to.
+    /// * `req` - The request carrying the `Content-Encoding` of the body.
+    /// * `query` - The table, the column mapping and whether to only validate the rows.
+    /// * `payload` - The body of the request.
+    ///
+    /// # Returns
+    ///
+    /// * `HttpResponse` - The HTTP response containing the progress of the accepted import.
+    async fn import_csv(
+        db: web::Data<Arc<Mutex<Database>>>,
+        jobs: web::Data<JobQueue>,
+        imports: web::Data<Imports>,
+        concurrency: web::Data<ConcurrencyLimits>,
+        req: HttpRequest,
+        query: web::Query<CsvImportQuery>,
+        payload: web::Payload,
+    ) -> impl Responder {
+        let query = query.into_inner();
+        let mapping = match CsvMapping::new(
+            &query.key,
+            query.columns.as_deref(),
+            query.delimiter,
+            query.typed,
+        ) {
+            Ok(mapping) => mapping,
+            Err(MappingError::MissingKey) => {
+                return HttpResponse::BadRequest()
+                    .json(ApiResponse::error("the_key_column_is_required"))
+            }
+            Err(MappingError::InvalidDelimiter(delimiter)) => {
+                return HttpResponse::BadRequest().json(ApiResponse::error_with(
+                    "invalid_delimiter",
+                    &[("delimiter", &format!("{:?}", delimiter))],
+                ))
+            }
+        };
+        Self::start_import(
+            &db,
+            &jobs,
+            &imports,
+            concurrency.into_inner(),
+            &req,
+            query.table,
+            query.dry_run,
+            payload,
+            Some(mapping),
+        )
+        .await
+    }
+
+    /// Spools the body of an import and starts importing it in the background.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - A reference to the database wrapped in an Arc and Mutex for thread safety.
+    /// * `jobs` - The job queue running the import.
+    /// * `imports` - The registry tracking the progress of the imports.
+    /// * `limits` - The limits whose interactive lane the import yields to.
+    /// * `req` - The request carrying the `Content-Encoding` of the body.
+    /// * `table` - The table to import into.
+    /// * `dry_run` - Whether to only validate the rows without writing them.
+    /// * `payload` - The body of the request.
+    /// * `csv` - The column mapping if the body is a CSV file, otherwise it is NDJSON.
+    ///
+    /// # Returns
+    ///
+    /// * `HttpResponse` - The HTTP response containing the progress of the accepted import.
+    #[allow(clippy::too_many_arguments)]
+    async fn start_import(
+        db: &Arc<Mutex<Database>>,
+        jobs: &JobQueue,
+        imports: &Imports,
+        limits: Arc<ConcurrencyLimits>,
+        req: &HttpRequest,
+        table: String,
+        dry_run: bool,
+        payload: web::Payload,
+        csv: Option<CsvMapping>,
+    ) -> HttpResponse {
+        if !dry_run {
+            if let Some(response) = Self::check_hold(&*db.lock().await, &table).await {
+                return response;
+            }
+        }
+        let validator = match db.lock().await.value_validator(&table).await {
+            Ok(validator) => validator,
+            Err(e) => {
+                log::error!("Failed to import table: {}", e);
+                return HttpResponse::InternalServerError()
+                    .json(ApiResponse::error("failed_to_import_table"));
+            }
+        };
+        let progress = imports.start(&table, dry_run);
+        let path = std::env::temp_dir().join(format!(
+            "xcloud-import-{}-{}.{}",
+            std::process::id(),
+            progress.id,
+            if csv.is_some() { "csv" } else { "ndjson" }
+        ));
+        let spooled = match Self::spool(req, payload, &path).await {
+            Ok(()) => match &csv {
+                Some(mapping) => mapping.check(&path).await,
+                None => Ok(()),
+            },
+            Err(e) => Err(e),
+        };
+        if let Err(e) = spooled {
+            let _ = tokio::fs::remove_file(&path).await;
+            imports.fail(progress.id, &e);
+            return HttpResponse::BadRequest().json(ApiResponse::error_with(
+                "failed_to_read_import",
+                &[("reason", &e)],
+            ));
+        }
+        jobs.enqueue(Arc::new(ImportJob {
+            db: db.clone(),
+            limits,
+            imports: imports.clone(),
+            id: progress.id,
+            table,
+            validator,
+            dry_run,
+            path,
+            csv,
+        }));
+        HttpResponse::Accepted().json(ApiResponse::<ImportProgress> {
+            status: "success".to_string(),
+            code: None,
+            message: "Import started".to_string(),
+            data: Some(progress),
+        })
+    }
+
+    /// Writes the decompressed body of an import to a file.
+    ///
+    /// # Arguments
+    ///
+    /// * `req` - The request carrying the `Content-Encoding` of the body.
+    /// * `payload` - The body of the request.
+    /// * `path` - The file to write the body to.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the body cannot be read or written.
+    async fn spool(
+        req: &HttpRequest,
+        payload: web::Payload,
+        path: &std::path::Path,
+    ) -> Result<(), String> {
+        let mut body = Box::pin(actix_web::dev::Decompress::from_headers(
+            payload,
+            req.headers(),
+        ));
+        let mut file = tokio::fs::File::create(path)
+            .await
+            .map_err(|e| e.to_string())?;
+        while let Some(chunk) = body.next().await {
+            let chunk = chunk.map_err(|e| e.to_string())?;
+            file.write_all(&chunk).await.map_err(|e| e.to_string())?;
+        }
+        file.flush().await.map_err(|e| e.to_string())
+    }
+
+    /// Retrieves the progress of an import.
+    ///
+    /// # Arguments
+    ///
+    /// * `imports` - The registry tracking the progress of the imports.
+    /// * `id` - The id of the import.
+    ///
+    /// # Returns
+    ///
+    /// * `HttpResponse` - The HTTP response containing the progress or an error message.
+    async fn import_progress(imports: web::Data<Imports>, id: web::Path<u64>) -> impl Responder {
+        match imports.get(id.into_inner()) {
+            Some(progress) => HttpResponse::Ok().json(ApiResponse::<ImportProgress> {
+                status: "success".to_string(),
+                code: None,
+                message: "Import progress retrieved successfully".to_string(),
+                data: Some(progress),
+            }),
+            None => HttpResponse::NotFound().json(ApiResponse::error("import_not_found")),
+        }
+    }
+
+    /// Updates data in the database based on the provided key-value pair.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - A reference to the database wrapped in an Arc and Mutex for thread safety.
+    /// * `webhooks` - The dispatcher notifying the webhooks of the table.
+    /// * `config` - The live configuration deciding whether locks are enforced.
+    /// * `req` - The request naming the lock owner of the writer.
+    /// * `item` - The key-value pair to be updated in the database.
+    ///
+    /// # Returns
+    ///
+    /// * `HttpResponse` - The HTTP response indicating success or failure.
+    async fn update_data(
+        db: web::Data<Arc<Mutex<Database>>>,
+        webhooks: web::Data<Webhooks>,
+        config: web::Data<RwLock<Config>>,
+        req: HttpRequest,
+        item: Json<TableKeyValue>,
+    ) -> impl Responder {
+        let db = db.lock().await;
+        if let Some(response) = Self::check_lock(&db, &config, &req, &item.table, &item.key).await {
+            return response;
+        }
+        if let Some(response) =
+            Self::check_value(&db, &item.table, &item.value, "failed_to_update_data").await
+        {
+            return response;
+        }
+        match db.update_data(&item.table, &item.key, &item.value).await {
+            Ok(_) => {
+                webhooks
+                    .notify(
+                        &db,
+                        WebhookEvent::new(
+                            "update",
+                            &item.table,
+                            Some(&item.key),
+                            Some(&item.value),
+                        ),
+                    )
+                    .await;
+                HttpResponse::Ok().json(ApiResponse::<()> {
+                    status: "success".to_string(),
+                    code: None,
+                    message: "Data updated successfully".to_string(),
+                    data: None,
+                })
+            }
+            Err(e) => {
+                log::error!("Failed to update data: {}", e);
+                Self::database_error(&e, "failed_to_update_data")
+            }
+        }
+    }
+
+    /// Applies a JSON Patch or a JSON Merge Patch to the JSON value of a key, or a delta to the
+    /// value of a key of any type.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - A reference to the database wrapped in an Arc and Mutex for thread safety.
+    /// * `webhooks` - The dispatcher notifying the webhooks of the table.
+    /// * `config` - The live configuration deciding whether locks are enforced.
+    /// * `req` - The request naming the lock owner of the writer.
+    /// * `item` - The key and either the patch operations, the merge patch or the delta.
+    ///
+    /// # Returns
+    ///
+    /// * `HttpResponse` - The HTTP response containing the patched value unless a delta was
+    ///   applied, or an error message.
+    async fn patch_data(
+        db: web::Data<Arc<Mutex<Database>>>,
+        webhooks: web::Data<Webhooks>,
+        config: web::Data<RwLock<Config>>,
+        req: HttpRequest,
+        item: Json<PatchData>,
+    ) -> impl Responder {
+        let PatchData {
+            table,
+            key,
+            patch,
+            merge,
+            delta,
+        } = item.into_inner();
+        let patch = match (patch, merge, delta) {
+            (Some(patch), None, None) => ValuePatch::Json(patch),
+            (None, Some(merge), None) => ValuePatch::Merge(merge),
+            (None, None, Some(delta)) => ValuePatch::Delta(delta),
+            _ => {
+                return HttpResponse::BadRequest().json(ApiResponse::error(
+                    "exactly_one_of_a_patch_a_merge_patch_or_a_delta_is_required",
+                ))
+            }
+        };
+        // The patched value is not sent back for deltas, which spare transferring it.
+        let is_delta = matches!(patch, ValuePatch::Delta(_));
+        let db = db.lock().await;
+        if let Some(response) = Self::check_lock(&db, &config, &req, &table, &key).await {
+            return response;
+        }
+        match db.patch_data(&table, &key, &patch).await {
+            Ok(Some(Ok(value))) => {
+                webhooks
+                    .notify(
+                        &db,
+                        WebhookEvent::new("update", &table, Some(&key), Some(&value)),
+                    )
+                    .await;
+                HttpResponse::Ok().json(ApiResponse::<serde_json::Value> {
+                    status: "success".to_string(),
+                    code: None,
+                    message: "Data patched successfully".to_string(),
+                    data: (!is_delta)
+                        .then(|| serde_json::from_str(&value).ok())
+                        .flatten(),
+                })
+            }
+            Ok(Some(Err(reason))) => HttpResponse::UnprocessableEntity().json(
+                ApiResponse::error_with("patch_cannot_be_applied", &[("reason", &reason)]),
+            ),
+            Ok(None) => HttpResponse::NotFound().json(ApiResponse::error("data_not_found")),
+            Err(e) => {
+                log::error!("Failed to patch data: {}", e);
+                Self::database_error(&e, "failed_to_patch_data")
+            }
+        }
+    }
+
+    /// Atomically increments a numeric value, creating it at zero if missing.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - A reference to the database wrapped in an Arc and Mutex for thread safety.
+    /// * `webhooks` - The dispatcher notifying the webhooks of the table.
+    /// * `config` - The live configuration deciding whether locks are enforced.
+    /// * `req` - The request naming the lock owner of the writer.
+    /// * `item` - The key and the amount to increment it by.
+    ///
+    /// # Returns
+    ///
+    /// * `HttpResponse` - The HTTP response containing the new value or an error message.
+    async fn increment(
+        db: web::Data<Arc<Mutex<Database>>>,
+        webhooks: web::Data<Webhooks>,
+        config: web::Data<RwLock<Config>>,
+        req: HttpRequest,
+        item: Json<TableKeyDelta>,
+    ) -> impl Responder {
+        let db = db.lock().await;
+        if let Some(response) = Self::check_lock(&db, &config, &req, &item.table, &item.key).await {
+            return response;
+        }
+        match db.get_value_type(&item.table).await {
+            Ok(ValueType::Integer | ValueType::String) => {}
+            Ok(_) => {
+                return HttpResponse::UnprocessableEntity()
+                    .json(ApiResponse::error("table_does_not_hold_integer_values"))
+            }
+            Err(e) => {
+                log::error!("Failed to increment data: {}", e);
+                return HttpResponse::InternalServerError()
+                    .json(ApiResponse::error("failed_to_increment_data"));
+            }
+        }
+        match db.increment(&item.table, &item.key, item.delta).await {
+            Ok(Some(value)) => {
+                webhooks
+                    .notify(
+                        &db,
+                        WebhookEvent::new(
+                            "update",
+                            &item.table,
+                            Some(&item.key),
+                            Some(&value.to_string()),
+                        ),
+                    )
+                    .await;
+                HttpResponse::Ok().json(ApiResponse::<i64> {
+                    status: "success".to_string(),
+                    code: None,
+                    message: "Data incremented successfully".to_string(),
+                    data: Some(value),
+                })
+            }
+            Ok(None) => {
+                HttpResponse::Conflict().json(ApiResponse::error("current_value_is_not_an_integer"))
+            }
+            Err(e) => {
+                log::error!("Failed to increment data: {}", e);
+                Self::database_error(&e, "failed_to_increment_data")
+            }
+        }
+    }
+
+    /// Sets a value only if the current value of the key is the expected one.
+    ///
+    /// With `merge` set on a JSON table, a diverged value is merged with the changes made from
+    /// the expected value instead, so concurrent writers of different fields do not conflict.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - A reference to the database wrapped in an Arc and Mutex for thread safety.
+    /// * `webhooks` - The dispatcher notifying the webhooks of the table.
+    /// * `config` - The live configuration deciding whether locks are enforced.
+    /// * `req` - The request naming the lock owner of the writer.
+    /// * `item` - The key, the expected current value and the new value.
+    ///
+    /// # Returns
+    ///
+    /// * `HttpResponse` - The HTTP response indicating success, containing the merged value if
+    ///   merged, or the current value on conflict.
+    async fn compare_and_set(
+        db: web::Data<Arc<Mutex<Database>>>,
+        webhooks: web::Data<Webhooks>,
+        config: web::Data<RwLock<Config>>,
+        req: HttpRequest,
+        item: Json<CompareAndSet>,
+    ) -> impl Responder {
+        let db = db.lock().await;
+        if let Some(response) = Self::check_lock(&db, &config, &req, &item.table, &item.key).await {
+            return response;
+        }
+        if let Some(response) = Self::check_value(
+            &db,
+            &item.table,
+            &item.value,
+            "failed_to_compare_and_set_data",
+        )
+        .await
+        {
+            return response;
+        }
+        let value_type = match db.get_value_type(&item.table).await {
+            Ok(value_type) => value_type,
+            Err(e) => {
+                log::error!("Failed to compare and set data: {}", e);
+                return HttpResponse::InternalServerError()
+                    .json(ApiResponse::error("failed_to_compare_and_set_data"));
+            }
+        };
+        match db
+            .compare_and_set(
+                &item.table,
+                &item.key,
+                item.expected.as_deref(),
+                &item.value,
+            )
+            .await
+        {
+            Ok(Ok(())) => {
+                webhooks
+                    .notify(
+                        &db,
+                        WebhookEvent::new("set", &item.table, Some(&item.key), Some(&item.value)),
+                    )
+                    .await;
+                HttpResponse::Ok().json(ApiResponse::<()> {
+                    status: "success".to_string(),
+                    code: None,
+                    message: "Data set successfully".to_string(),
+                    data: None,
+                })
+            }
+            Ok(Err(Some(current)))
+                if item.merge && value_type == ValueType::Json && item.expected.is_some() =>
+            {
+                Self::merge_and_set(&db, &webhooks, &item, &current).await
+            }
+            Ok(Err(current)) => HttpResponse::Conflict().json(ApiResponse::error_with_data(
+                "current_value_does_not_match_the_expected_value",
+                &[],
+                current.map(|current| value_type.to_json(&current)),
+            )),
+            Err(e) => {
+                log::error!("Failed to compare and set data: {}", e);
+                HttpResponse::InternalServerError()
+                    .json(ApiResponse::error("failed_to_compare_and_set_data"))
+            }
+        }
+    }
+
+    /// Merges the changes a conditional write made from its expected value into the diverged
+    /// current value and sets the result.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - The database, locked by the caller so the current value cannot change.
+    /// * `webhooks` - The dispatcher notifying the webhooks of the table.
+    /// * `item` - The conditional write, holding the expected value it was made from.
+    /// * `current` - The current value of the key.
+    ///
+    /// # Returns
+    ///
+    /// * `HttpResponse` - The HTTP response containing the merged value.
+    async fn merge_and_set(
+        db: &Database,
+        webhooks: &Webhooks,
+        item: &CompareAndSet,
+        current: &str,
+    ) -> HttpResponse {
+        let merged = match ValuePatch::diff(item.expected.as_deref().unwrap_or("null"), &item.value)
+            .and_then(|patch| patch.apply(current))
+        {
+            Ok(merged) => merged,
+            Err(reason) => {
+                return HttpResponse::UnprocessableEntity().json(ApiResponse::error_with(
+                    "values_cannot_be_merged",
+                    &[("reason", &reason)],
+                ))
+            }
+        };
+        if let Some(response) =
+            Self::check_value(db, &item.table, &merged, "failed_to_merge_data").await
+        {
+            return response;
+        }
+        match db
+            .compare_and_set(&item.table, &item.key, Some(current), &merged)
+            .await
+        {
+            Ok(Ok(())) => {
+                tracing::info!(target: "audit", table = %item.table, key = %item.key, "Merged diverged value");
+                webhooks
+                    .notify(
+                        db,
+                        WebhookEvent::new("set", &item.table, Some(&item.key), Some(&merged)),
+                    )
+                    .await;
+                HttpResponse::Ok().json(ApiResponse::<serde_json::Value> {
+                    status: "success".to_string(),
+                    code: None,
+                    message: "Data merged successfully".to_string(),
+                    data: Some(ValueType::Json.to_json(&merged)),
+                })
+            }
+            Ok(Err(current)) => HttpResponse::Conflict().json(ApiResponse::error_with_data(
+                "current_value_changed_while_merging",
+                &[],
+                current.map(|current| ValueType::Json.to_json(&current)),
+            )),
+            Err(e) => {
+                log::error!("Failed to merge data: {}", e);
+                HttpResponse::InternalServerError().json(ApiResponse::error("failed_to_merge_data"))
+            }
+        }
+    }
+
+    /// Pushes a value onto the list of a key.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - A reference to the database wrapped in an Arc and Mutex for thread safety.
+    /// * `webhooks` - The dispatcher notifying the webhooks of the table.
+    /// * `config` - The live configuration deciding whether locks are enforced.
+    /// * `req` - The request naming the lock owner of the writer.
+    /// * `item` - The key of the list and the value to push.
+    ///
+    /// # Returns
+    ///
+    /// * `HttpResponse` - The HTTP response containing the new length of the list.
+    async fn list_push(
+        db: web::Data<Arc<Mutex<Database>>>,
+        webhooks: web::Data<Webhooks>,
+        config: web::Data<RwLock<Config>>,
+        req: HttpRequest,
+        item: Json<ListPush>,
+    ) -> impl Responder {
+        let db = db.lock().await;
+        if let Some(response) = Self::check_lock(&db, &config, &req, &item.table, &item.key).await {
+            return response;
+        }
+        match db
+            .list_push(&item.table, &item.key, &item.value, item.front)
+            .await
+        {
+            Ok(length) => {
+                webhooks
+                    .notify(
+                        &db,
+                        WebhookEvent::new("push", &item.table, Some(&item.key), Some(&item.value)),
+                    )
+                    .await;
+                HttpResponse::Ok().json(ApiResponse::<i64> {
+                    status: "success".to_string(),
+                    code: None,
+                    message: "Value pushed successfully".to_string(),
+                    data: Some(length),
+                })
+            }
+            Err(e) => {
+                log::error!("Failed to push value: {}", e);
+                HttpResponse::InternalServerError().json(ApiResponse::error("failed_to_push_value"))
+            }
+        }
+    }
+
+    /// Pops a value from the list of a key.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - A reference to the database wrapped in an Arc and Mutex for thread safety.
+    /// * `webhooks` - The dispatcher notifying the webhooks of the table.
+    /// * `config` - The live configuration deciding whether locks are enforced.
+    /// * `req` - The request naming the lock owner of the writer.
+    /// * `item` - The key of the list and the side to pop from.
+    ///
+    /// # Returns
+    ///
+    /// * `HttpResponse` - The HTTP response containing the popped value or an error message.
+    async fn list_pop(
+        db: web::Data<Arc<Mutex<Database>>>,
+        webhooks: web::Data<Webhooks>,
+        config: web::Data<RwLock<Config>>,
+        req: HttpRequest,
+        item: Json<ListPop>,
+    ) -> impl Responder {
+        let db = db.lock().await;
+        if let Some(response) = Self::check_lock(&db, &config, &req, &item.table, &item.key).await {
+            return response;
+        }
+        match db.list_pop(&item.table, &item.key, item.front).await {
+            Ok(Some(value)) => {
+                webhooks
+                    .notify(
+                        &db,
+                        WebhookEvent::new("pop", &item.table, Some(&item.key), Some(&value)),
+                    )
+                    .await;
+                HttpResponse::Ok().json(ApiResponse::<String> {
+                    status: "success".to_string(),
+                    code: None,
+                    message: "Value popped successfully".to_string(),
+                    data: Some(value),
+                })
+            }
+            Ok(None) => HttpResponse::NotFound().json(ApiResponse::error("list_is_empty")),
+            Err(e) => {
+                log::error!("Failed to pop value: {}", e);
+                HttpResponse::InternalServerError().json(ApiResponse::error("failed_to_pop_value"))
+            }
+        }
+    }
+
+    /// Retrieves a range of the list of a key.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - A reference to the database wrapped in an Arc and Mutex for thread safety.
+    /// * `query` - The key of the list and the range to retrieve.
+    ///
+    /// # Returns
+    ///
+    /// * `HttpResponse` - The HTTP response containing the values or an error message.
+    async fn list_range(
+        db: web::Data<Arc<Mutex<Database>>>,
+        query: web::Query<ListRange>,
+    ) -> impl Responder {
+        let db = db.lock().await;
+        match db
+            .list_range(&query.table, &query.key, query.offset, query.limit)
+            .await
+        {
+            Ok(values) => HttpResponse::Ok().json(ApiResponse::<Vec<String>> {
+                status: "success".to_string(),
+                code: None,
+                message: "List retrieved successfully".to_string(),
+                data: Some(values),
+            }),
+            Err(e) => {
+                log::error!("Failed to get list range: {}", e);
+                HttpResponse::InternalServerError()
+                    .json(ApiResponse::error("failed_to_retrieve_list"))
+            }
+        }
+    }
+
+    /// Lists the event logs with their retention.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - A reference to the database wrapped in an Arc and Mutex for thread safety.
+    ///
+    /// # Returns
+    ///
+    /// * `HttpResponse` - The HTTP response containing the event logs or an error message.
+    async fn event_logs(db: web::Data<Arc<Mutex<Database>>>) -> impl Responder {
+        match db.lock().await.event_logs().await {
+            Ok(logs) => HttpResponse::Ok().json(ApiResponse::<Vec<EventLog>> {
+                status: "success".to_string(),
+                code: None,
+                message: "Event logs retrieved successfully".to_string(),
+                data: Some(logs),
+            }),
+            Err(e) => {
+                log::error!("Failed to list event logs: {}", e);
+                Self::database_error(&e, "failed_to_list_event_logs")
+            }
+        }
+    }
+
+    /// Creates an event log.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - A reference to the database wrapped in an Arc and Mutex for thread safety.
+    /// * `item` - The name and the retention of the log.
+    ///
+    /// # Returns
+    ///
+    /// * `HttpResponse` - The HTTP response indicating success or failure.
+    async fn create_event_log(
+        db: web::Data<Arc<Mutex<Database>>>,
+        item: Json<EventLog>,
+    ) -> impl Responder {
+        if Utils::sanitize(&item.name).is_empty() {
+            return HttpResponse::BadRequest()
+                .json(ApiResponse::error("event_log_name_must_not_be_empty"));
+        }
+        match db.lock().await.create_event_log(&item).await {
+            Ok(true) => {
+                tracing::info!(target: "audit", log = %item.name, "Event log created");
+                HttpResponse::Ok().json(ApiResponse::<()> {
+                    status: "success".to_string(),
+                    code: None,
+                    message: "Event log created successfully".to_string(),
+                    data: None,
+                })
+            }
+            Ok(false) => {
+                HttpResponse::Conflict().json(ApiResponse::error("event_log_already_exists"))
+            }
+            Err(e) => {
+                log::error!("Failed to create event log: {}", e);
+                Self::database_error(&e, "failed_to_create_event_log")
+            }
+        }
+    }
+
+    /// Deletes an event log with its events and consumer offsets.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - A reference to the database wrapped in an Arc and Mutex for thread safety.
+    /// * `item` - The name of the log.
+    ///
+    /// # Returns
+    ///
+    /// * `HttpResponse` - The HTTP response indicating success or failure.
+    async fn delete_event_log(
+        db: web::Data<Arc<Mutex<Database>>>,
+        item: Json<LogName>,
+    ) -> impl Responder {
+        match db.lock().await.delete_event_log(&item.log).await {
+            Ok(true) => {
+                tracing::info!(target: "audit", log = %item.log, "Event log deleted");
+                HttpResponse::Ok().json(ApiResponse::<()> {
+                    status: "success".to_string(),
+                    code: None,
+                    message: "Event log deleted successfully".to_string(),
+                    data: None,
+                })
+            }
+            Ok(false) => HttpResponse::NotFound().json(ApiResponse::error("event_log_not_found")),
+            Err(e) => {
+                log::error!("Failed to delete event log: {}", e);
+                Self::database_error(&e, "failed_to_delete_event_log")
+            }
+        }
+    }
+
+    /// Appends events to an event log.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - A reference to the database wrapped in an Arc and Mutex for thread safety.
+    /// * `item` - The log and the values of the events, in order.
+    ///
+    /// # Returns
+    ///
+    /// * `HttpResponse` - The HTTP response containing the offset of the first event or an error
+    ///   message.
+    async fn append_events(
+        db: web::Data<Arc<Mutex<Database>>>,
+        item: Json<AppendEvents>,
+    ) -> impl Responder {
+        if item.values.is_empty() {
+            return HttpResponse::BadRequest()
+                .json(ApiResponse::error("at_least_one_event_must_be_appended"));
+        }
+        match db.lock().await.append_events(&item.log, &item.values).await {
+            Ok(Some(first_offset)) => HttpResponse::Ok().json(ApiResponse::<AppendedEvents> {
+                status: "success".to_string(),
+                code: None,
+                message: "Events appended successfully".to_string(),
+                data: Some(AppendedEvents {
+                    first_offset,
+                    count: item.values.len(),
+                }),
+            }),
+            Ok(None) => HttpResponse::NotFound().json(ApiResponse::error("event_log_not_found")),
+            Err(e) => {
+                log::error!("Failed to append events: {}", e);
+                Self::database_error(&e, "failed_to_append_events")
+            }
+        }
+    }
+
+    /// Reads the events of an event log from an offset, optionally skipping older events.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - A reference to the database wrapped in an Arc and Mutex for thread safety.
+    /// * `query` - The log and the range of events to read.
+    ///
+    /// # Returns
+    ///
+    /// * `HttpResponse` - The HTTP response containing the events or an error message.
+    async fn read_events(
+        db: web::Data<Arc<Mutex<Database>>>,
+        query: web::Query<EventRange>,
+    ) -> impl Responder {
+        let db = db.lock().await;
+        match db
+            .read_events(&query.log, query.offset, query.since, query.limit)
+            .await
+        {
+            Ok(Some(events)) => HttpResponse::Ok().json(ApiResponse::<Vec<Event>> {
+                status: "success".to_string(),
+                code: None,
+                message: "Events retrieved successfully".to_string(),
+                data: Some(events),
+            }),
+            Ok(None) => HttpResponse::NotFound().json(ApiResponse::error("event_log_not_found")),
+            Err(e) => {
+                log::error!("Failed to read events: {}", e);
+                Self::database_error(&e, "failed_to_read_events")
+            }
+        }
+    }
+
+    /// Retrieves all aggregate views.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - A reference to the database wrapped in an Arc and Mutex for thread safety.
+    ///
+    /// # Returns
+    ///
+    /// * `HttpResponse` - The HTTP response containing the views or an error message.
+    async fn views(db: web::Data<Arc<Mutex<Database>>>) -> impl Responder {
+        match db.lock().await.views().await {
+            Ok(views) => HttpResponse::Ok().json(ApiResponse::<Vec<AggregateView>> {
+                status: "success".to_string(),
+                code: None,
+                message: "Views retrieved successfully".to_string(),
+                data: Some(views),
+            }),
+            Err(e) => {
+                log::error!("Failed to retrieve views: {}", e);
+                Self::database_error(&e, "failed_to_retrieve_views")
+            }
+        }
+    }
+
+    /// Creates an aggregate view and computes it from the current rows of its table.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - A reference to the database wrapped in an Arc and Mutex for thread safety.
+    /// * `item` - The name, the table, the delimiter and the summed field of the view.
+    ///
+    /// # Returns
+    ///
+    /// * `HttpResponse` - The HTTP response indicating success or failure.
+    async fn create_view(
+        db: web::Data<Arc<Mutex<Database>>>,
+        item: Json<AggregateView>,
+    ) -> impl Responder {
+        if Utils::sanitize(&item.name).is_empty() || Utils::sanitize(&item.table).is_empty() {
+            return HttpResponse::BadRequest()
+                .json(ApiResponse::error("view_and_table_names_must_not_be_empty"));
+        }
+        if !item.pointer.is_empty() && !item.pointer.starts_with('/') {
+            return HttpResponse::BadRequest()
+                .json(ApiResponse::error("pointer_must_be_empty_or_start_with"));
+        }
+        match db.lock().await.create_view(&item).await {
+            Ok(true) => {
+                tracing::info!(target: "audit", view = %item.name, table = %item.table, "View created");
+                HttpResponse::Ok().json(ApiResponse::<()> {
+                    status: "success".to_string(),
+                    code: None,
+                    message: "View created successfully".to_string(),
+                    data: None,
+                })
+            }
+            Ok(false) => HttpResponse::Conflict().json(ApiResponse::error("view_already_exists")),
+            Err(e) => {
+                log::error!("Failed to create view: {}", e);
+                Self::database_error(&e, "failed_to_create_view")
+            }
+        }
+    }
+
+    /// Deletes an aggregate view with its aggregates.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - A reference to the database wrapped in an Arc and Mutex for thread safety.
+    /// * `item` - The name of the view.
+    ///
+    /// # Returns
+    ///
+    /// * `HttpResponse` - The HTTP response indicating success or failure.
+    async fn delete_view(
+        db: web::Data<Arc<Mutex<Database>>>,
+        item: Json<ViewName>,
+    ) -> impl Responder {
+        match db.lock().await.delete_view(&item.name).await {
+            Ok(true) => {
+                tracing::info!(target: "audit", view = %item.name, "View deleted");
+                HttpResponse::Ok().json(ApiResponse::<()> {
+                    status: "success".to_string(),
+                    code: None,
+                    message: "View deleted successfully".to_string(),
+                    data: None,
+                })
+            }
+            Ok(false) => HttpResponse::NotFound().json(ApiResponse::error("view_not_found")),
+            Err(e) => {
+                log::error!("Failed to delete view: {}", e);
+                Self::database_error(&e, "failed_to_delete_view")
+            }
+        }
+    }
+
+    /// Retrieves the count, sum and time of the last write of the keys of every prefix of an
+    /// aggregate view, without scanning its table.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - A reference to the database wrapped in an Arc and Mutex for thread safety.
+    /// * `name` - The name of the view.
+    /// * `query` - The start of the prefixes to retrieve, all if unset.
+    ///
+    /// # Returns
+    ///
+    /// * `HttpResponse` - The HTTP response containing the aggregates or an error message.
+    async fn view_groups(
+        db: web::Data<Arc<Mutex<Database>>>,
+        name: web::Path<String>,
+        query: web::Query<ViewFilter>,
+    ) -> impl Responder {
+        match db
+            .lock()
+            .await
+            .view_groups(&name, query.prefix.as_deref())
+            .await
+        {
+            Ok(Some(groups)) => HttpResponse::Ok().json(ApiResponse::<Vec<ViewGroup>> {
+                status: "success".to_string(),
+                code: None,
+                message: "View retrieved successfully".to_string(),
+                data: Some(groups),
+            }),
+            Ok(None) => HttpResponse::NotFound().json(ApiResponse::error("view_not_found")),
+            Err(e) => {
+                log::error!("Failed to retrieve view: {}", e);
+                Self::database_error(&e, "failed_to_retrieve_view")
+            }
+        }
+    }
+
+    /// Retrieves all time series.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - A reference to the database wrapped in an Arc and Mutex for thread safety.
+    ///
+    /// # Returns
+    ///
+    /// * `HttpResponse` - The HTTP response containing the series or an error message.
+    async fn time_series(db: web::Data<Arc<Mutex<Database>>>) -> impl Responder {
+        match db.lock().await.time_series().await {
+            Ok(series) => HttpResponse::Ok().json(ApiResponse::<Vec<TimeSeries>> {
+                status: "success".to_string(),
+                code: None,
+                message: "Time series retrieved successfully".to_string(),
+                data: Some(series),
+            }),
+            Err(e) => {
+                log::error!("Failed to retrieve time series: {}", e);
+                Self::database_error(&e, "failed_to_retrieve_time_series")
+            }
+        }
+    }
+
+    /// Creates a time series.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - A reference to the database wrapped in an Arc and Mutex for thread safety.
+    /// * `item` - The name and the downsampling of the series.
+    ///
+    /// # Returns
+    ///
+    /// * `HttpResponse` - The HTTP response indicating success or failure.
+    async fn create_time_series(
+        db: web::Data<Arc<Mutex<Database>>>,
+        item: Json<TimeSeries>,
+    ) -> impl Responder {
+        if Utils::sanitize(&item.name).is_empty() {
+            return HttpResponse::BadRequest()
+                .json(ApiResponse::error("time_series_name_must_not_be_empty"));
+        }
+        match db.lock().await.create_time_series(&item).await {
+            Ok(true) => {
+                tracing::info!(target: "audit", series = %item.name, "Time series created");
+                HttpResponse::Ok().json(ApiResponse::<()> {
+                    status: "success".to_string(),
+                    code: None,
+                    message: "Time series created successfully".to_string(),
+                    data: None,
+                })
+            }
+            Ok(false) => {
+                HttpResponse::Conflict().json(ApiResponse::error("time_series_already_exists"))
+            }
+            Err(e) => {
+                log::error!("Failed to create time series: {}", e);
+                Self::database_error(&e, "failed_to_create_time_series")
+            }
+        }
+    }
+
+    /// Deletes a time series with its samples.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - A reference to the database wrapped in an Arc and Mutex for thread safety.
+    /// * `item` - The name of the series.
+    ///
+    /// # Returns
+    ///
+    /// * `HttpResponse` - The HTTP response indicating success or failure.
+    async fn delete_time_series(
+        db: web::Data<Arc<Mutex<Database>>>,
+        item: Json<SeriesName>,
+    ) -> impl Responder {
+        match db.lock().await.delete_time_series(&item.series).await {
+            Ok(true) => {
+                tracing::info!(target: "audit", series = %item.series, "Time series deleted");
+                HttpResponse::Ok().json(ApiResponse::<()> {
+                    status: "success".to_string(),
+                    code: None,
+                    message: "Time series deleted successfully".to_string(),
+                    data: None,
+                })
+            }
+            Ok(false) => HttpResponse::NotFound().json(ApiResponse::error("time_series_not_found")),
+            Err(e) => {
+                log::error!("Failed to delete time series: {}", e);
+                Self::database_error(&e, "failed_to_delete_time_series")
+            }
+        }
+    }
+
+    /// Records samples in a time series.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - A reference to the database wrapped in an Arc and Mutex for thread safety.
+    /// * `item` - The series and the samples.
+    ///
+    /// # Returns
+    ///
+    /// * `HttpResponse` - The HTTP response containing the number of recorded samples or an
+    ///   error message.
+    async fn append_points(
+        db: web::Data<Arc<Mutex<Database>>>,
+        item: Json<AppendPoints>,
+    ) -> impl Responder {
+        if item.points.is_empty() {
+            return HttpResponse::BadRequest()
+                .json(ApiResponse::error("at_least_one_sample_must_be_recorded"));
+        }
+        if item.points.iter().any(|point| !point.value.is_finite()) {
+            return HttpResponse::BadRequest()
+                .json(ApiResponse::error("sample_values_must_be_finite"));
+        }
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_millis() as i64);
+        let points: Vec<SeriesPoint> = item
+            .points
+            .iter()
+            .map(|point| SeriesPoint {
+                key: point.key.clone(),
+                timestamp: point.timestamp.unwrap_or(now),
+                value: point.value,
+            })
+            .collect();
+        match db.lock().await.append_points(&item.series, &points).await {
+            Ok(true) => HttpResponse::Ok().json(ApiResponse::<usize> {
+                status: "success".to_string(),
+                code: None,
+                message: "Samples recorded successfully".to_string(),
+                data: Some(points.len()),
+            }),
+            Ok(false) => HttpResponse::NotFound().json(ApiResponse::error("time_series_not_found")),
+            Err(e) => {
+                log::error!("Failed to record samples: {}", e);
+                Self::database_error(&e, "failed_to_record_samples")
+            }
+        }
+    }
+
+    /// Reads the raw samples of a key of a time series within a range of time.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - A reference to the database wrapped in an Arc and Mutex for thread safety.
+    /// * `query` - The series, the key and the range of time.
+    ///
+    /// # Returns
+    ///
+    /// * `HttpResponse` - The HTTP response containing the samples or an error message.
+    async fn read_points(
+        db: web::Data<Arc<Mutex<Database>>>,
+        query: web::Query<PointRange>,
+    ) -> impl Responder {
+        let to = query.to.unwrap_or(i64::MAX);
+        match db
+            .lock()
+            .await
+            .read_points(&query.series, &query.key, query.from, to, query.limit)
+            .await
+        {
+            Ok(Some(points)) => HttpResponse::Ok().json(ApiResponse::<Vec<SeriesPoint>> {
+                status: "success".to_string(),
+                code: None,
+                message: "Samples retrieved successfully".to_string(),
+                data: Some(points),
+            }),
+            Ok(None) => HttpResponse::NotFound().json(ApiResponse::error("time_series_not_found")),
+            Err(e) => {
+                log::error!("Failed to read samples: {}", e);
+                Self::database_error(&e, "failed_to_read_samples")
+            }
+        }
+    }
+
+    /// Aggregates the samples of a key of a time series into buckets of the average, minimum
+    /// and maximum.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - A reference to the database wrapped in an Arc and Mutex for thread safety.
+    /// * `query` - The series, the key, the range of time and the width of the buckets.
+    ///
+    /// # Returns
+    ///
+    /// * `HttpResponse` - The HTTP response containing the buckets or an error message.
+    async fn aggregate_points(
+        db: web::Data<Arc<Mutex<Database>>>,
+        query: web::Query<PointAggregation>,
+    ) -> impl Responder {
+        if query.bucket_secs <= 0 {
+            return HttpResponse::BadRequest()
+                .json(ApiResponse::error("bucket_width_must_be_positive"));
+        }
+        let to = query.to.unwrap_or(i64::MAX);
+        let bucket_ms = query.bucket_secs.saturating_mul(1000);
+        match db
+            .lock()
+            .await
+            .aggregate_points(&query.series, &query.key, query.from, to, bucket_ms)
+            .await
+        {
+            Ok(Some(buckets)) => HttpResponse::Ok().json(ApiResponse::<Vec<SeriesBucket>> {
+                status: "success".to_string(),
+                code: None,
+                message: "Samples aggregated successfully".to_string(),
+                data: Some(buckets),
+            }),
+            Ok(None) => HttpResponse::NotFound().json(ApiResponse::error("time_series_not_found")),
+            Err(e) => {
+                log::error!("Failed to aggregate samples: {}", e);
+                Self::database_error(&e, "failed_to_aggregate_samples")
+            }
+        }
+    }
+
+    /// Retrieves the offsets and lags of the consumer groups of an event log.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - A reference to the database wrapped in an Arc and Mutex for thread safety.
+    /// * `query` - The log.
+    ///
+    /// # Returns
+    ///
+    /// * `HttpResponse` - The HTTP response containing the offsets or an error message.
+    async fn consumer_offsets(
+        db: web::Data<Arc<Mutex<Database>>>,
+        query: web::Query<LogName>,
+    ) -> impl Responder {
+        match db.lock().await.consumer_offsets(&query.log).await {
+            Ok(Some(offsets)) => HttpResponse::Ok().json(ApiResponse::<Vec<ConsumerOffset>> {
+                status: "success".to_string(),
+                code: None,
+                message: "Consumer offsets retrieved successfully".to_string(),
+                data: Some(offsets),
+            }),
+            Ok(None) => HttpResponse::NotFound().json(ApiResponse::error("event_log_not_found")),
+            Err(e) => {
+                log::error!("Failed to retrieve consumer offsets: {}", e);
+                Self::database_error(&e, "failed_to_retrieve_consumer_offsets")
+            }
+        }
+    }
+
+    /// Commits the offset of the next event a consumer group reads.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - A reference to the database wrapped in an Arc and Mutex for thread safety.
+    /// * `item` - The log, the group and the offset.
+    ///
+    /// # Returns
+    ///
+    /// * `HttpResponse` - The HTTP response indicating success or failure.
+    async fn commit_consumer_offset(
+        db: web::Data<Arc<Mutex<Database>>>,
+        item: Json<CommitOffset>,
+    ) -> impl Responder {
+        if item.group.is_empty() || item.offset < 0 {
+            return HttpResponse::BadRequest().json(ApiResponse::error(
+                "group_must_not_be_empty_and_offset_must_not_be_negative",
+            ));
+        }
+        match db
+            .lock()
+            .await
+            .commit_consumer_offset(&item.log, &item.group, item.offset)
+            .await
+        {
+            Ok(true) => HttpResponse::Ok().json(ApiResponse::<()> {
+                status: "success".to_string(),
+                code: None,
+                message: "Consumer offset committed successfully".to_string(),
+                data: None,
+            }),
+            Ok(false) => HttpResponse::NotFound().json(ApiResponse::error("event_log_not_found")),
+            Err(e) => {
+                log::error!("Failed to commit consumer offset: {}", e);
+                Self::database_error(&e, "failed_to_commit_consumer_offset")
+            }
+        }
+    }
+
+    /// Applies the changes a client made while offline, in order, resolving the changes to keys
+    /// changed since their base version as requested.
+    ///
+    /// The whole batch is refused if a key is locked or a value does not match its table schema.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - A reference to the database wrapped in an Arc and Mutex for thread safety.
+    /// * `webhooks` - The dispatcher notifying the webhooks of the tables.
+    /// * `config` - The live configuration deciding whether locks are enforced.
+    /// * `req` - The request naming the lock owner of the client.
+    /// * `item` - The changes and how conflicts are resolved.
+    ///
+    /// # Returns
+    ///
+    /// * `HttpResponse` - The HTTP response containing the outcome of every change or an error
+    ///   message.
+    async fn sync_push(
+        db: web::Data<Arc<Mutex<Database>>>,
+        webhooks: web::Data<Webhooks>,
+        config: web::Data<RwLock<Config>>,
+        req: HttpRequest,
+        item: Json<SyncPush>,
+    ) -> impl Responder {
+        let db = db.lock().await;
+        for change in &item.changes {
+            if let Some(response) =
+                Self::check_lock(&db, &config, &req, &change.table, &change.key).await
+            {
+                return response;
+            }
+            if let Some(value) = &change.value {
+                if let Some(response) =
+                    Self::check_value(&db, &change.table, value, "failed_to_push_changes").await
+                {
+                    return response;
+                }
+            }
+        }
+        let mut outcomes = Vec::with_capacity(item.changes.len());
+        for change in &item.changes {
+            let outcome = match db.apply_client_change(change, item.resolution).await {
+                Ok(outcome) => outcome,
+                Err(e) => {
+                    log::error!("Failed to push changes: {}", e);
+                    return Self::database_error(&e, "failed_to_push_changes");
+                }
+            };
+            if outcome.status != SyncStatus::Rejected {
+                let op = if outcome.value.is_some() {
+                    "set"
+                } else {
+                    "delete"
+                };
+                webhooks
+                    .notify(
+                        &db,
+                        WebhookEvent::new(
+                            op,
+                            &outcome.table,
+                            Some(&outcome.key),
+                            outcome.value.as_deref(),
+                        ),
+                    )
+                    .await;
+            }
+            outcomes.push(outcome);
+        }
+        HttpResponse::Ok().json(ApiResponse::<Vec<SyncOutcome>> {
+            status: "success".to_string(),
+            code: None,
+            message: "Changes pushed successfully".to_string(),
+            data: Some(outcomes),
+        })
+    }
+
+    /// Retrieves the current values of the keys changed after a checkpoint, deleted keys having
+    /// no value.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - A reference to the database wrapped in an Arc and Mutex for thread safety.
+    /// * `query` - The checkpoint, the table and the maximum number of changes.
+    ///
+    /// # Returns
+    ///
+    /// * `HttpResponse` - The HTTP response containing the changes and the next checkpoint or an
+    ///   error message.
+    async fn sync_pull(
+        db: web::Data<Arc<Mutex<Database>>>,
+        query: web::Query<SyncPull>,
+    ) -> impl Responder {
+        let db = db.lock().await;
+        match db
+            .pull_changes(query.since, query.table.as_deref(), query.limit)
+            .await
+        {
+            Ok(changes) => HttpResponse::Ok().json(ApiResponse::<SyncChanges> {
+                status: "success".to_string(),
+                code: None,
+                message: "Changes retrieved successfully".to_string(),
+                data: Some(SyncChanges {
+                    checkpoint: changes.last().map_or(query.since, |change| change.version),
+                    changes,
+                }),
+            }),
+            Err(e) => {
+                log::error!("Failed to pull changes: {}", e);
+                Self::database_error(&e, "failed_to_pull_changes")
+            }
+        }
+    }
+
+    /// Searches the values of a table for words, ranked by relevance.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - A reference to the database wrapped in an Arc and Mutex for thread safety.
+    /// * `query` - The table, the words to search for and the page to return.
+    ///
+    /// # Returns
+    ///
+    /// * `HttpResponse` - The HTTP response containing the hits or an error message.
+    async fn fulltext(
+        db: web::Data<Arc<Mutex<Database>>>,
+        query: web::Query<FulltextQuery>,
+    ) -> impl Responder {
+        let projection = match Projection::from_request(query.fields.as_deref()) {
+            Ok(projection) => projection,
+            Err(reason) => {
+                return HttpResponse::BadRequest().json(ApiResponse::error_with(
+                    "invalid_fields",
+                    &[("reason", &reason)],
+                ))
+            }
+        };
+        let db = db.lock().await;
+        if db.encrypts_values() {
+            return HttpResponse::Conflict().json(ApiResponse::error(
+                "full_text_search_is_unavailable_while_values_are_encrypted",
+            ));
+        }
+        if !db.stores_plain_values() {
+            return HttpResponse::Conflict().json(ApiResponse::error(
+                "full_text_search_is_unavailable_while_values_are_compressed_deduplicated_or_archived",
+            ));
+        }
+        match db
+            .fulltext_search(
+                &query.table,
+                &query.q,
+                query.offset,
+                query.limit.clamp(1, 100),
+            )
+            .await
+        {
+            Ok(hits) => match projection {
+                Some(projection) => HttpResponse::Ok().json(ApiResponse::<Vec<ProjectedHit>> {
+                    status: "success".to_string(),
+                    code: None,
+                    message: "Search completed successfully".to_string(),
+                    data: Some(
+                        hits.into_iter()
+                            .map(|hit| ProjectedHit {
+                                key: hit.key,
+                                value: projection.apply(serde_json::Value::String(hit.value)),
+                                score: hit.score,
+                            })
+                            .collect(),
+                    ),
+                }),
+                None => HttpResponse::Ok().json(ApiResponse::<Vec<SearchHit>> {
+                    status: "success".to_string(),
+                    code: None,
+                    message: "Search completed successfully".to_string(),
+                    data: Some(hits),
+                }),
+            },
+            Err(e) => {
+                log::error!("Failed to search data: {}", e);
+                Self::database_error(&e, "failed_to_search_data")
+            }
+        }
+    }
+
+    /// Deletes data from the database based on the provided key.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - A reference to the database wrapped in an Arc and Mutex for thread safety.
+    /// * `webhooks` - The dispatcher notifying the webhooks of the table.
+    /// * `config` - The live configuration deciding whether locks are enforced.
+    /// * `req` - The request naming the lock owner of the writer.
+    /// * `item` - The key for which the data needs to be deleted.
+    ///
+    /// # Returns
+    ///
+    /// * `HttpResponse` - The HTTP response indicating success or failure.
+    async fn delete_data(
+        db: web::Data<Arc<Mutex<Database>>>,
+        webhooks: web::Data<Webhooks>,
+        config: web::Data<RwLock<Config>>,
+        req: HttpRequest,
+        item: Json<TableKey>,
+    ) -> impl Responder {
+        let db = db.lock().await;
+        if let Some(response) = Self::check_lock(&db, &config, &req, &item.table, &item.key).await {
+            return response;
+        }
+        match db.delete_data(&item.table, &item.key).await {
+            Ok(_) => {
+                webhooks
+                    .notify(
+                        &db,
+                        WebhookEvent::new("delete", &item.table, Some(&item.key), None),
+                    )
+                    .await;
+                HttpResponse::Ok().json(ApiResponse::<()> {
+                    status: "success".to_string(),
+                    code: None,
+                    message: "Data deleted successfully".to_string(),
+                    data: None,
+                })
+            }
+            Err(e) => {
+                log::error!("Failed to delete data: {}", e);
+                Self::database_error(&e, "failed_to_delete_data")
+            }
+        }
+    }
+
+    /// Deletes many keys of a table at once, given either as a list or by a common prefix.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - A reference to the database wrapped in an Arc and Mutex for thread safety.
+    /// * `webhooks` - The dispatcher notifying the webhooks of the table.
+    /// * `config` - The live configuration deciding whether locks are enforced.
+    /// * `req` - The request naming the lock owner of the writer.
+    /// * `query` - Whether to only report the keys that would be deleted.
+    /// * `item` - The table and the keys or key prefix to delete.
+    ///
+    /// # Returns
+    ///
+    /// * `HttpResponse` - The HTTP response containing the number of deleted keys, or the
+    ///   report of a dry run.
+    async fn delete_many(
+        db: web::Data<Arc<Mutex<Database>>>,
+        webhooks: web::Data<Webhooks>,
+        config: web::Data<RwLock<Config>>,
+        req: HttpRequest,
+        query: web::Query<DryRunQuery>,
+        item: Json<DeleteMany>,
+    ) -> impl Responder {
+        let db = db.lock().await;
+        let (prefix, keys) = match (&item.prefix, &item.keys) {
+            (Some(prefix), None) if !prefix.is_empty() => (Some(prefix.as_str()), None),
+            (None, Some(keys)) => (None, Some(keys.as_slice())),
+            _ => {
+                return HttpResponse::BadRequest().json(ApiResponse::error(
+                    "exactly_one_of_a_non_empty_prefix_or_a_list_of_keys_is_required",
+                ))
+            }
+        };
+        for key in keys.unwrap_or_default() {
+            if let Some(response) = Self::check_lock(&db, &config, &req, &item.table, key).await {
+                return response;
+            }
+        }
+        if query.dry_run {
+            return Self::dry_run(db.preview_delete(&item.table, prefix, keys).await);
+        }
+        let result = match keys {
+            Some(keys) => db.delete_many(&item.table, keys).await,
+            None => {
+                db.delete_prefix(&item.table, prefix.unwrap_or_default())
+                    .await
+            }
+        };
+        match result {
+            Ok(count) => {
+                if count > 0 {
+                    webhooks
+                        .notify(
+                            &db,
+                            WebhookEvent::new("delete_many", &item.table, None, None),
+                        )
+                        .await;
+                }
+                HttpResponse::Ok().json(ApiResponse::<u64> {
+                    status: "success".to_string(),
+                    code: None,
+                    message: "Data deleted successfully".to_string(),
+                    data: Some(count),
+                })
+            }
+            Err(e) => {
+                log::error!("Failed to delete data: {}", e);
+                HttpResponse::InternalServerError()
+                    .json(ApiResponse::error("failed_to_delete_data"))
+            }
+        }
+    }
+
+    /// Deletes the entire table from the database.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - A reference to the database wrapped in an Arc and Mutex for thread safety.
+    /// * `webhooks` - The dispatcher notifying the webhooks of the table.
+    /// * `query` - Whether to only report the keys that would be deleted.
+    /// * `item` - The name of the table to be deleted.
+    ///
+    /// # Returns
+    ///
+    /// * `HttpResponse` - The HTTP response indicating success or failure, or the report of a
+    ///   dry run.
+    async fn delete_table(
+        db: web::Data<Arc<Mutex<Database>>>,
+        webhooks: web::Data<Webhooks>,
+        query: web::Query<DryRunQuery>,
+        item: Json<Table>,
+    ) -> impl Responder {
+        let db = db.lock().await;
+        if query.dry_run {
+            return Self::dry_run(db.preview_delete(&item.table, None, None).await);
+        }
+        if let Some(response) = Self::check_hold(&db, &item.table).await {
+            return response;
+        }
+        Self::with_schema_lock(&db, &item.table, "delete_table", || async {
+            match db.delete_table(&item.table).await {
+                Ok(_) => {
+                    webhooks
+                        .notify(
+                            &db,
+                            WebhookEvent::new("delete_table", &item.table, None, None),
+                        )
+                        .await;
+                    HttpResponse::Ok().json(ApiResponse::<()> {
+                        status: "success".to_string(),
+                        code: None,
+                        message: "Table deleted successfully".to_string(),
+                        data: None,
+                    })
+                }
+                Err(e) => {
+                    log::error!("Failed to delete table: {}", e);
+                    HttpResponse::InternalServerError()
+                        .json(ApiResponse::error("failed_to_delete_table"))
+                }
+            }
+        })
+        .await
+    }
+
+    /// Builds the response to a dry run.
+    ///
+    /// # Arguments
+    ///
+    /// * `result` - The report of what the operation would affect.
+    ///
+    /// # Returns
+    ///
+    /// * `HttpResponse` - The HTTP response containing the report or an error message.
+    fn dry_run(result: Result<DryRunReport, sqlx::Error>) -> HttpResponse {
+        match result {
+            Ok(report) => HttpResponse::Ok().json(ApiResponse::<DryRunReport> {
+                status: "success".to_string(),
+                code: None,
+                message: "Dry run completed, nothing was changed".to_string(),
+                data: Some(report),
+            }),
+            Err(e) => {
+                log::error!("Failed to complete dry run: {}", e);
+                Self::database_error(&e, "failed_to_complete_dry_run")
+            }
+        }
+    }
+
+    /// Lists the names of all tables.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - A reference to the database wrapped in an Arc and Mutex for thread safety.
+    ///
+    /// # Returns
+    ///
+    /// * `HttpResponse` - The HTTP response containing the table names or an error message.
+    async fn list_tables(db: web::Data<Arc<Mutex<Database>>>) -> impl Responder {
+        let db = db.lock().await;
+        match db.list_tables().await {
+            Ok(tables) => HttpResponse::Ok().json(ApiResponse::<Vec<String>> {
+                status: "success".to_string(),
+                code: None,
+                message: "Tables retrieved successfully".to_string(),
+                data: Some(tables),
+            }),
+            Err(e) => {
+                log::error!("Failed to list tables: {}", e);
+                HttpResponse::InternalServerError()
+                    .json(ApiResponse::error("failed_to_list_tables"))
+            }
+        }
+    }
+
+    /// Lists the keys of a table.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - A reference to the database wrapped in an Arc and Mutex for thread safety.
+    /// * `query` - The table and the page of keys to list.
+    ///
+    /// # Returns
+    ///
+    /// * `HttpResponse` - The HTTP response containing the keys or an error message.
+    async fn list_keys(
+        db: web::Data<Arc<Mutex<Database>>>,
+        query: web::Query<KeysQuery>,
+    ) -> impl Responder {
+        let db = db.lock().await;
+        match db.list_keys(&query.table, query.offset, query.limit).await {
+            Ok(keys) => HttpResponse::Ok().json(ApiResponse::<Vec<String>> {
+                status: "success".to_string(),
+                code: None,
+                message: "Keys retrieved successfully".to_string(),
+                data: Some(keys),
+            }),
+            Err(e) => {
+                log::error!("Failed to list keys: {}", e);
+                Self::database_error(&e, "failed_to_list_keys")
+            }
+        }
+    }
+
+    /// Lists the immediate children of a path in the `/`-separated hierarchy of the keys.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - A reference to the database wrapped in an Arc and Mutex for thread safety.
+    /// * `query` - The table and the path to list.
+    ///
+    /// # Returns
+    ///
+    /// * `HttpResponse` - The HTTP response containing the children or an error message.
+    async fn tree(
+        db: web::Data<Arc<Mutex<Database>>>,
+        query: web::Query<TreeQuery>,
+    ) -> impl Responder {
+        match db.lock().await.tree(&query.table, &query.path).await {
+            Ok(nodes) => HttpResponse::Ok().json(ApiResponse::<Vec<TreeNode>> {
+                status: "success".to_string(),
+                code: None,
+                message: "Tree retrieved successfully".to_string(),
+                data: Some(nodes),
+            }),
+            Err(e) => {
+                log::error!("Failed to list tree: {}", e);
+                Self::database_error(&e, "failed_to_list_tree")
+            }
+        }
+    }
+
+    /// Creates a table with a declared value type and, optionally, case sensitivity of its keys.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - A reference to the database wrapped in an Arc and Mutex for thread safety.
+    /// * `item` - The schema of the table to be created.
+    ///
+    /// # Returns
+    ///
+    /// * `HttpResponse` - The HTTP response indicating success or failure.
+    async fn create_table(
+        db: web::Data<Arc<Mutex<Database>>>,
+        item: Json<TableSchema>,
+    ) -> impl Responder {
+        let db = db.lock().await;
+        Self::with_schema_lock(&db, &item.table, "create_table", || async {
+            match db.create_table(&item).await {
+                Ok(_) => HttpResponse::Ok().json(ApiResponse::<()> {
+                    status: "success".to_string(),
+                    code: None,
+                    message: "Table created successfully".to_string(),
+                    data: None,
+                }),
+                Err(e)
+                    if e.as_database_error()
+                        .is_some_and(|e| e.is_unique_violation()) =>
+                {
+                    HttpResponse::Conflict().json(ApiResponse::error(
+                        "keys_of_the_table_collide_without_case_sensitivity",
+                    ))
+                }
+                Err(e @ sqlx::Error::InvalidArgument(_)) => {
+                    Self::database_error(&e, "failed_to_create_table")
+                }
+                Err(e) => {
+                    log::error!("Failed to create table: {}", e);
+                    HttpResponse::InternalServerError()
+                        .json(ApiResponse::error("failed_to_create_table"))
+                }
+            }
+        })
+        .await
+    }
+
+    /// Copies a table with its schema and, optionally, its contents into a new table.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - A reference to the database wrapped in an Arc and Mutex for thread safety.
+    /// * `item` - The source and target tables and whether to copy the contents.
+    ///
+    /// # Returns
+    ///
+    /// * `HttpResponse` - The HTTP response containing the number of copied keys or an error message.
+    async fn clone_table(
+        db: web::Data<Arc<Mutex<Database>>>,
+        item: Json<CloneTable>,
+    ) -> impl Responder {
+        if Utils::sanitize(&item.target).is_empty() {
+            return HttpResponse::BadRequest()
+                .json(ApiResponse::error("target_table_name_must_not_be_empty"));
+        }
+        let db = db.lock().await;
+        let source_region = db.table_region(&item.source);
+        let target_region = db.table_region(&item.target).or(source_region.clone());
+        if target_region != source_region && !item.allow_cross_region {
+            return HttpResponse::Forbidden()
+                .json(ApiResponse::error("copying_the_table_would_cross_regions"));
+        }
+        Self::with_schema_lock(&db, &item.target, "clone_table", || async {
+            match db
+                .clone_table(&item.source, &item.target, item.with_data)
+                .await
+            {
+                Ok(Some(copied)) => {
+                    tracing::info!(target: "audit", source = %item.source, target_table = %item.target, copied, "Table cloned");
+                    HttpResponse::Ok().json(ApiResponse::<u64> {
+                        status: "success".to_string(),
+                        code: None,
+                        message: "Table cloned successfully".to_string(),
+                        data: Some(copied),
+                    })
+                }
+                Ok(None) => HttpResponse::Conflict().json(ApiResponse::error(
+                    "source_table_does_not_exist_or_target_table_already_exists",
+                )),
+                Err(e) => {
+                    log::error!("Failed to clone table: {}", e);
+                    Self::database_error(&e, "failed_to_clone_table")
+                }
+            }
+        })
+        .await
+    }
+
+    /// Lists the tables marked as templates.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - A reference to the database wrapped in an Arc and Mutex for thread safety.
+    ///
+    /// # Returns
+    ///
+    /// * `HttpResponse` - The HTTP response containing the template tables or an error message.
+    async fn templates(db: web::Data<Arc<Mutex<Database>>>) -> impl Responder {
+        match db.lock().await.templates().await {
+            Ok(tables) => HttpResponse::Ok().json(ApiResponse::<Vec<String>> {
+                status: "success".to_string(),
+                code: None,
+                message: "Templates retrieved successfully".to_string(),
+                data: Some(tables),
+            }),
+            Err(e) => {
+                log::error!("Failed to list templates: {}", e);
+                HttpResponse::InternalServerError()
+                    .json(ApiResponse::error("failed_to_list_templates"))
+            }
+        }
+    }
+
+    /// Marks a table as a template seeding new projects.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - A reference to the database wrapped in an Arc and Mutex for thread safety.
+    /// * `item` - The table to mark.
+    ///
+    /// # Returns
+    ///
+    /// * `HttpResponse` - The HTTP response indicating success or failure.
+    async fn add_template(
+        db: web::Data<Arc<Mutex<Database>>>,
+        item: Json<Table>,
+    ) -> impl Responder {
+        match db.lock().await.set_template(&item.table, true).await {
+            Ok(true) => HttpResponse::Ok().json(ApiResponse::<()> {
+                status: "success".to_string(),
+                code: None,
+                message: "Template added successfully".to_string(),
+                data: None,
+            }),
+            Ok(false) => HttpResponse::NotFound().json(ApiResponse::error("table_not_found")),
+            Err(e) => {
+                log::error!("Failed to add template: {}", e);
+                HttpResponse::InternalServerError()
+                    .json(ApiResponse::error("failed_to_add_template"))
+            }
+        }
+    }
+
+    /// Removes the template mark of a table without deleting the table.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - A reference to the database wrapped in an Arc and Mutex for thread safety.
+    /// * `item` - The table to unmark.
+    ///
+    /// # Returns
+    ///
+    /// * `HttpResponse` - The HTTP response indicating success or failure.
+    async fn remove_template(
+        db: web::Data<Arc<Mutex<Database>>>,
+        item: Json<Table>,
+    ) -> impl Responder {
+        match db.lock().await.set_template(&item.table, false).await {
+            Ok(true) => HttpResponse::Ok().json(ApiResponse::<()> {
+                status: "success".to_string(),
+                code: None,
+                message: "Template removed successfully".to_string(),
+                data: None,
+            }),
+            Ok(false) => {
+                HttpResponse::NotFound().json(ApiResponse::error("table_is_not_a_template"))
+            }
+            Err(e) => {
+                log::error!("Failed to remove template: {}", e);
+                HttpResponse::InternalServerError()
+                    .json(ApiResponse::error("failed_to_remove_template"))
+            }
+        }
+    }
+
+    /// Captures a snapshot of the given tables.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - A reference to the database wrapped in an Arc and Mutex for thread safety.
+    /// * `item` - The tables to capture.
+    ///
+    /// # Returns
+    ///
+    /// * `HttpResponse` - The HTTP response containing the snapshot or an error message.
+    async fn create_snapshot(
+        db: web::Data<Arc<Mutex<Database>>>,
+        item: Json<SnapshotRequest>,
+    ) -> impl Responder {
+        match db.lock().await.create_snapshot(&item.tables, false).await {
+            Ok(snapshot) => HttpResponse::Ok().json(ApiResponse::<Snapshot> {
+                status: "success".to_string(),
+                code: None,
+                message: "Snapshot created successfully".to_string(),
+                data: Some(snapshot),
+            }),
+            Err(e) => {
+                log::error!("Failed to create snapshot: {}", e);
+                Self::database_error(&e, "failed_to_create_snapshot")
+            }
+        }
+    }
+
+    /// Lists all snapshots, newest first.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - A reference to the database wrapped in an Arc and Mutex for thread safety.
+    ///
+    /// # Returns
+    ///
+    /// * `HttpResponse` - The HTTP response containing the snapshots or an error message.
+    async fn snapshots(db: web::Data<Arc<Mutex<Database>>>) -> impl Responder {
+        match db.lock().await.snapshots().await {
+            Ok(snapshots) => HttpResponse::Ok().json(ApiResponse::<Vec<Snapshot>> {
+                status: "success".to_string(),
+                code: None,
+                message: "Snapshots retrieved successfully".to_string(),
+                data: Some(snapshots),
+            }),
+            Err(e) => {
+                log::error!("Failed to list snapshots: {}", e);
+                HttpResponse::InternalServerError()
+                    .json(ApiResponse::error("failed_to_list_snapshots"))
+            }
+        }
+    }
+
+    /// Deletes a snapshot.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - A reference to the database wrapped in an Arc and Mutex for thread safety.
+    /// * `item` - The id of the snapshot to be deleted.
+    ///
+    /// # Returns
+    ///
+    /// * `HttpResponse` - The HTTP response indicating success or failure.
+    async fn delete_snapshot(
+        db: web::Data<Arc<Mutex<Database>>>,
+        item: Json<SnapshotId>,
+    ) -> impl Responder {
+        match db.lock().await.delete_snapshot(item.id).await {
+            Ok(true) => HttpResponse::Ok().json(ApiResponse::<()> {
+                status: "success".to_string(),
+                code: None,
+                message: "Snapshot deleted successfully".to_string(),
+                data: None,
+            }),
+            Ok(false) => HttpResponse::NotFound().json(ApiResponse::error("snapshot_not_found")),
+            Err(e) => {
+                log::error!("Failed to delete snapshot: {}", e);
+                HttpResponse::InternalServerError()
+                    .json(ApiResponse::error("failed_to_delete_snapshot"))
+            }
+        }
+    }
+
+    /// Compares a snapshot against another one.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - A reference to the database wrapped in an Arc and Mutex for thread safety.
+    /// * `id` - The id of the snapshot.
+    /// * `query` - The id of the snapshot to compare against.
+    ///
+    /// # Returns
+    ///
+    /// * `HttpResponse` - The HTTP response containing the added, changed and removed keys or
+    ///   an error message.
+    async fn snapshot_diff(
+        db: web::Data<Arc<Mutex<Database>>>,
+        id: web::Path<i64>,
+        query: web::Query<DiffQuery>,
+    ) -> impl Responder {
+        match db
+            .lock()
+            .await
+            .diff_snapshots(id.into_inner(), query.against)
+            .await
+        {
+            Ok(Some(diff)) => HttpResponse::Ok().json(ApiResponse::<SnapshotDiff> {
+                status: "success".to_string(),
+                code: None,
+                message: "Snapshots compared successfully".to_string(),
+                data: Some(diff),
+            }),
+            Ok(None) => HttpResponse::NotFound().json(ApiResponse::error("snapshot_not_found")),
+            Err(e) => {
+                log::error!("Failed to compare snapshots: {}", e);
+                HttpResponse::InternalServerError()
+                    .json(ApiResponse::error("failed_to_compare_snapshots"))
+            }
+        }
+    }
+
+    /// Lists the most accessed keys of a table, or the least recently accessed ones.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - A reference to the database wrapped in an Arc and Mutex for thread safety.
+    /// * `query` - The table, the order and the maximum number of keys to list.
+    ///
+    /// # Returns
+    ///
+    /// * `HttpResponse` - The HTTP response containing the access counters or an error message.
+    async fn hot_keys(
+        db: web::Data<Arc<Mutex<Database>>>,
+        query: web::Query<HotKeysQuery>,
+    ) -> impl Responder {
+        match db
+            .lock()
+            .await
+            .hot_keys(&query.table, query.stale, query.limit.clamp(1, 1000))
+            .await
+        {
+            Ok(keys) => HttpResponse::Ok().json(ApiResponse::<Vec<KeyStat>> {
+                status: "success".to_string(),
+                code: None,
+                message: "Key statistics retrieved successfully".to_string(),
+                data: Some(keys),
+            }),
+            Err(e) => {
+                log::error!("Failed to retrieve key statistics: {}", e);
+                Self::database_error(&e, "failed_to_retrieve_key_statistics")
+            }
+        }
+    }
+
+    /// Retrieves the recent writes, newest first, with consecutive writes of the same kind
+    /// to the same table coalesced.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - A reference to the database wrapped in an Arc and Mutex for thread safety.
+    /// * `query` - The table and key to filter by and the page of entries to return.
+    ///
+    /// # Returns
+    ///
+    /// * `HttpResponse` - The HTTP response containing the activity or an error message.
+    async fn activity(
+        db: web::Data<Arc<Mutex<Database>>>,
+        query: web::Query<ActivityQuery>,
+    ) -> impl Responder {
+        if query.key.is_some() && query.table.is_none() {
+            return HttpResponse::BadRequest()
+                .json(ApiResponse::error("a_key_filter_requires_a_table"));
+        }
+        match db
+            .lock()
+            .await
+            .activity(
+                query.table.as_deref(),
+                query.key.as_deref(),
+                query.offset,
+                query.limit,
+            )
+            .await
+        {
+            Ok(activity) => HttpResponse::Ok().json(ApiResponse::<Vec<Activity>> {
+                status: "success".to_string(),
+                code: None,
+                message: "Activity retrieved successfully".to_string(),
+                data: Some(activity),
+            }),
+            Err(e) => {
+                log::error!("Failed to retrieve activity: {}", e);
+                HttpResponse::InternalServerError()
+                    .json(ApiResponse::error("failed_to_retrieve_activity"))
+            }
+        }
+    }
+
+    /// Lists the comments on a key, oldest first.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - A reference to the database wrapped in an Arc and Mutex for thread safety.
+    /// * `query` - The table and the key whose comments to list.
+    ///
+    /// # Returns
+    ///
+    /// * `HttpResponse` - The HTTP response containing the comments or an error message.
+    async fn comments(
+        db: web::Data<Arc<Mutex<Database>>>,
+        query: web::Query<TableKey>,
+    ) -> impl Responder {
+        match db.lock().await.comments(&query.table, &query.key).await {
+            Ok(comments) => HttpResponse::Ok().json(ApiResponse::<Vec<Comment>> {
+                status: "success".to_string(),
+                code: None,
+                message: "Comments retrieved successfully".to_string(),
+                data: Some(comments),
+            }),
+            Err(e) => {
+                log::error!("Failed to list comments: {}", e);
+                HttpResponse::InternalServerError()
+                    .json(ApiResponse::error("failed_to_list_comments"))
+            }
+        }
+    }
+
+    /// Posts a comment on a key and notifies the webhooks of the table.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - A reference to the database wrapped in an Arc and Mutex for thread safety.
+    /// * `webhooks` - The dispatcher notifying the webhooks of the table.
+    /// * `item` - The key, the parent, the author and the text of the comment.
+    ///
+    /// # Returns
+    ///
+    /// * `HttpResponse` - The HTTP response containing the posted comment or an error message.
+    async fn post_comment(
+        db: web::Data<Arc<Mutex<Database>>>,
+        webhooks: web::Data<Webhooks>,
+        item: Json<PostComment>,
+    ) -> impl Responder {
+        if item.author.is_empty() || item.body.is_empty() {
+            return HttpResponse::BadRequest()
+                .json(ApiResponse::error("author_and_body_must_not_be_empty"));
+        }
+        let db = db.lock().await;
+        match db
+            .add_comment(
+                &item.table,
+                &item.key,
+                item.parent_id,
+                &item.author,
+                &item.body,
+            )
+            .await
+        {
+            Ok(Some(comment)) => {
+                webhooks
+                    .notify(
+                        &db,
+                        WebhookEvent::new(
+                            "comment",
+                            &item.table,
+                            Some(&item.key),
+                            Some(&item.body),
+                        ),
+                    )
+                    .await;
+                HttpResponse::Ok().json(ApiResponse::<Comment> {
+                    status: "success".to_string(),
+                    code: None,
+                    message: "Comment posted successfully".to_string(),
+                    data: Some(comment),
+                })
+            }
+            Ok(None) => HttpResponse::NotFound()
+                .json(ApiResponse::error("parent_comment_not_found_on_this_key")),
+            Err(e) => {
+                log::error!("Failed to post comment: {}", e);
+                HttpResponse::InternalServerError()
+                    .json(ApiResponse::error("failed_to_post_comment"))
+            }
+        }
+    }
+
+    /// Edits a comment of the given author and notifies the webhooks of its table.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - A reference to the database wrapped in an Arc and Mutex for thread safety.
+    /// * `webhooks` - The dispatcher notifying the webhooks of the table.
+    /// * `item` - The id, the author and the new text of the comment.
+    ///
+    /// # Returns
+    ///
+    /// * `HttpResponse` - The HTTP response containing the edited comment or an error message.
+    async fn edit_comment(
+        db: web::Data<Arc<Mutex<Database>>>,
+        webhooks: web::Data<Webhooks>,
+        item: Json<EditComment>,
+    ) -> impl Responder {
+        if item.body.is_empty() {
+            return HttpResponse::BadRequest().json(ApiResponse::error("body_must_not_be_empty"));
+        }
+        let db = db.lock().await;
+        match db.edit_comment(item.id, &item.author, &item.body).await {
+            Ok(Some(comment)) => {
+                webhooks
+                    .notify(
+                        &db,
+                        WebhookEvent::new(
+                            "comment_edit",
+                            &comment.table,
+                            Some(&comment.key),
+                            Some(&comment.body),
+                        ),
+                    )
+                    .await;
+                HttpResponse::Ok().json(ApiResponse::<Comment> {
+                    status: "success".to_string(),
+                    code: None,
+                    message: "Comment edited successfully".to_string(),
+                    data: Some(comment),
+                })
+            }
+            Ok(None) => HttpResponse::NotFound().json(ApiResponse::error("comment_not_found")),
+            Err(e) => {
+                log::error!("Failed to edit comment: {}", e);
+                HttpResponse::InternalServerError()
+                    .json(ApiResponse::error("failed_to_edit_comment"))
+            }
+        }
+    }
+
+    /// Deletes a comment of the given author with all replies to it and notifies the webhooks of its table.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - A reference to the database wrapped in an Arc and Mutex for thread safety.
+    /// * `webhooks` - The dispatcher notifying the webhooks of the table.
+    /// * `item` - The id and the author of the comment.
+    ///
+    /// # Returns
+    ///
+    /// * `HttpResponse` - The HTTP response containing the number of deleted comments or an error message.
+    async fn delete_comment(
+        db: web::Data<Arc<Mutex<Database>>>,
+        webhooks: web::Data<Webhooks>,
+        item: Json<CommentId>,
+    ) -> impl Responder {
+        let db = db.lock().await;
+        match db.delete_comment(item.id, &item.author).await {
+            Ok(deleted) => match deleted.first() {
+                Some(comment) => {
+                    webhooks
+                        .notify(
+                            &db,
+                            WebhookEvent::new(
+                                "comment_delete",
+                                &comment.table,
+                                Some(&comment.key),
+                                None,
+                            ),
+                        )
+                        .await;
+                    HttpResponse::Ok().json(ApiResponse::<usize> {
+                        status: "success".to_string(),
+                        code: None,
+                        message: "Comment deleted successfully".to_string(),
+                        data: Some(deleted.len()),
+                    })
+                }
+                None => HttpResponse::NotFound().json(ApiResponse::error("comment_not_found")),
+            },
+            Err(e) => {
+                log::error!("Failed to delete comment: {}", e);
+                HttpResponse::InternalServerError()
+                    .json(ApiResponse::error("failed_to_delete_comment"))
+            }
+        }
+    }
+
+    /// Lists all projects.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - A reference to the database wrapped in an Arc and Mutex for thread safety.
+    ///
+    /// # Returns
+    ///
+    /// * `HttpResponse` - The HTTP response containing the projects or an error message.
+    async fn list_projects(db: web::Data<Arc<Mutex<Database>>>) -> impl Responder {
+        match db.lock().await.projects().await {
+            Ok(projects) => HttpResponse::Ok().json(ApiResponse::<Vec<Project>> {
+                status: "success".to_string(),
+                code: None,
+                message: "Projects retrieved successfully".to_string(),
+                data: Some(projects),
+            }),
+            Err(e) => {
+                log::error!("Failed to list projects: {}", e);
+                HttpResponse::InternalServerError()
+                    .json(ApiResponse::error("failed_to_list_projects"))
+            }
+        }
+    }
+
+    /// Creates a project, optionally seeded with copies of the template tables.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - A reference to the database wrapped in an Arc and Mutex for thread safety.
+    /// * `item` - The name of the project to be created and whether to seed it.
+    ///
+    /// # Returns
+    ///
+    /// * `HttpResponse` - The HTTP response containing the seeded tables or an error message.
+    async fn create_project(
+        db: web::Data<Arc<Mutex<Database>>>,
+        item: Json<NewProject>,
+    ) -> impl Responder {
+        if item.name.is_empty() {
+            return HttpResponse::BadRequest()
+                .json(ApiResponse::error("project_name_must_not_be_empty"));
+        }
+        let db = db.lock().await;
+        let created = match db.create_project(&item.name).await {
+            Ok(true) if item.templates => db
+                .seed_project(&item.name)
+                .await
+                .map(|seeded| (true, seeded.unwrap_or_default())),
+            result => result.map(|created| (created, Vec::new())),
+        };
+        match created {
+            Ok((true, seeded)) => HttpResponse::Ok().json(ApiResponse::<Vec<String>> {
+                status: "success".to_string(),
+                code: None,
+                message: "Project created successfully".to_string(),
+                data: Some(seeded),
+            }),
+            Ok((false, _)) => {
+                HttpResponse::Conflict().json(ApiResponse::error("project_already_exists"))
+            }
+            Err(e) => {
+                log::error!("Failed to create project: {}", e);
+                HttpResponse::InternalServerError()
+                    .json(ApiResponse::error("failed_to_create_project"))
+            }
+        }
+    }
+
+    /// Deletes a project together with all of its tables.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - A reference to the database wrapped in an Arc and Mutex for thread safety.
+    /// * `webhooks` - The dispatcher notifying the webhooks of the deleted tables.
+    /// * `item` - The name of the project to be deleted.
+    ///
+    /// # Returns
+    ///
+    /// * `HttpResponse` - The HTTP response containing the deleted tables or an error message.
+    async fn delete_project(
+        db: web::Data<Arc<Mutex<Database>>>,
+        webhooks: web::Data<Webhooks>,
+        item: Json<ProjectName>,
+    ) -> impl Responder {
+        let db = db.lock().await;
+        match db.delete_project(&item.name).await {
+            Ok(Some(tables)) => {
+                for table in &tables {
+                    webhooks
+                        .notify(&db, WebhookEvent::new("delete_table", table, None, None))
+                        .await;
+                }
+                tracing::info!(target: "audit", project = %item.name, tables = tables.len(), "Project deleted");
+                HttpResponse::Ok().json(ApiResponse::<Vec<String>> {
+                    status: "success".to_string(),
+                    code: None,
+                    message: "Project deleted successfully".to_string(),
+                    data: Some(tables),
+                })
+            }
+            Ok(None) => HttpResponse::NotFound().json(ApiResponse::error("project_not_found")),
+            Err(e) => {
+                log::error!("Failed to delete project: {}", e);
+                HttpResponse::InternalServerError()
+                    .json(ApiResponse::error("failed_to_delete_project"))
+            }
+        }
+    }
+
+    /// Lists the tables of a project.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - A reference to the database wrapped in an Arc and Mutex for thread safety.
+    /// * `query` - The name of the project.
+    ///
+    /// # Returns
+    ///
+    /// * `HttpResponse` - The HTTP response containing the table names or an error message.
+    async fn project_tables(
+        db: web::Data<Arc<Mutex<Database>>>,
+        query: web::Query<ProjectName>,
+    ) -> impl Responder {
+        match db.lock().await.project_tables(&query.name).await {
+            Ok(Some(tables)) => HttpResponse::Ok().json(ApiResponse::<Vec<String>> {
+                status: "success".to_string(),
+                code: None,
+                message: "Project tables retrieved successfully".to_string(),
+                data: Some(tables),
+            }),
+            Ok(None) => HttpResponse::NotFound().json(ApiResponse::error("project_not_found")),
+            Err(e) => {
+                log::error!("Failed to list project tables: {}", e);
+                HttpResponse::InternalServerError()
+                    .json(ApiResponse::error("failed_to_list_project_tables"))
+            }
+        }
+    }
+
+    /// Associates a table with a project, moving it out of any other project.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - A reference to the database wrapped in an Arc and Mutex for thread safety.
+    /// * `item` - The project and the table to associate with it.
+    ///
+    /// # Returns
+    ///
+    /// * `HttpResponse` - The HTTP response indicating success or failure.
+    async fn add_project_table(
+        db: web::Data<Arc<Mutex<Database>>>,
+        item: Json<ProjectTable>,
+    ) -> impl Responder {
+        match db
+            .lock()
+            .await
+            .add_project_table(&item.project, &item.table)
+            .await
+        {
+            Ok(true) => HttpResponse::Ok().json(ApiResponse::<()> {
+                status: "success".to_string(),
+                code: None,
+                message: "Table added to project successfully".to_string(),
+                data: None,
+            }),
+            Ok(false) => HttpResponse::NotFound().json(ApiResponse::error("project_not_found")),
+            Err(e) => {
+                log::error!("Failed to add table to project: {}", e);
+                HttpResponse::InternalServerError()
+                    .json(ApiResponse::error("failed_to_add_table_to_project"))
+            }
+        }
+    }
+
+    /// Removes a table from a project without deleting the table.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - A reference to the database wrapped in an Arc and Mutex for thread safety.
+    /// * `item` - The project and the table to remove from it.
+    ///
+    /// # Returns
+    ///
+    /// * `HttpResponse` - The HTTP response indicating success or failure.
+    async fn remove_project_table(
+        db: web::Data<Arc<Mutex<Database>>>,
+        item: Json<ProjectTable>,
+    ) -> impl Responder {
+        match db
+            .lock()
+            .await
+            .remove_project_table(&item.project, &item.table)
+            .await
+        {
+            Ok(true) => HttpResponse::Ok().json(ApiResponse::<()> {
+                status: "success".to_string(),
+                code: None,
+                message: "Table removed from project successfully".to_string(),
+                data: None,
+            }),
+            Ok(false) => HttpResponse::NotFound()
+                .json(ApiResponse::error("table_does_not_belong_to_the_project")),
+            Err(e) => {
+                log::error!("Failed to remove table from project: {}", e);
+                HttpResponse::InternalServerError()
+                    .json(ApiResponse::error("failed_to_remove_table_from_project"))
+            }
+        }
+    }
+
+    /// Registers a webhook that is notified of all changes to a table.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - A reference to the database wrapped in an Arc and Mutex for thread safety.
+    /// * `item` - The table, target URL and signing secret of the webhook.
+    ///
+    /// # Returns
+    ///
+    /// * `HttpResponse` - The HTTP response containing the webhook id or an error message.
+    async fn register_webhook(
+        db: web::Data<Arc<Mutex<Database>>>,
+        item: Json<WebhookRegistration>,
+    ) -> impl Responder {
+        let db = db.lock().await;
+        match db.add_webhook(&item.table, &item.url, &item.secret).await {
+            Ok(id) => HttpResponse::Ok().json(ApiResponse::<WebhookId> {
+                status: "success".to_string(),
+                code: None,
+                message: "Webhook registered successfully".to_string(),
+                data: Some(WebhookId { id }),
+            }),
+            Err(e) => {
+                log::error!("Failed to register webhook: {}", e);
+                HttpResponse::InternalServerError()
+                    .json(ApiResponse::error("failed_to_register_webhook"))
+            }
+        }
+    }
+
+    /// Deletes a registered webhook.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - A reference to the database wrapped in an Arc and Mutex for thread safety.
+    /// * `item` - The id of the webhook to be deleted.
+    ///
+    /// # Returns
+    ///
+    /// * `HttpResponse` - The HTTP response indicating success or failure.
+    async fn delete_webhook(
+        db: web::Data<Arc<Mutex<Database>>>,
+        item: Json<WebhookId>,
+    ) -> impl Responder {
+        let db = db.lock().await;
+        match db.delete_webhook(item.id).await {
+            Ok(_) => HttpResponse::Ok().json(ApiResponse::<()> {
+                status: "success".to_string(),
+                code: None,
+                message: "Webhook deleted successfully".to_string(),
+                data: None,
+            }),
+            Err(e) => {
+                log::error!("Failed to delete webhook: {}", e);
+                HttpResponse::InternalServerError()
+                    .json(ApiResponse::error("failed_to_delete_webhook"))
+            }
+        }
+    }
+
+    /// Retrieves the currently active log level.
+    ///
+    /// # Arguments
+    ///
+    /// * `logging` - The handle used to change the log level at runtime.
+    ///
+    /// # Returns
+    ///
+    /// * `HttpResponse` - The HTTP response containing the active log level.
+    async fn get_log_level(logging: web::Data<Logging>) -> impl Responder {
+        HttpResponse::Ok().json(ApiResponse::<LogLevel> {
+            status: "success".to_string(),
+            code: None,
+            message: "Log level retrieved successfully".to_string(),
+            data: Some(LogLevel {
+                level: logging.level(),
+            }),
+        })
+    }
+
+    /// Changes the active log level without restarting the server.
+    ///
+    /// # Arguments
+    ///
+    /// * `logging` - The handle used to change the log level at runtime.
+    /// * `item` - The new log level, e.g. `info` or `xcloud=debug,sqlx=warn`.
+    ///
+    /// # Returns
+    ///
+    /// * `HttpResponse` - The HTTP response indicating success or failure.
+    async fn set_log_level(logging: web::Data<Logging>, item: Json<LogLevel>) -> impl Responder {
+        match logging.set_level(&item.level) {
+            Ok(_) => {
+                tracing::info!(level = %item.level, "Log level changed");
+                HttpResponse::Ok().json(ApiResponse::<()> {
+                    status: "success".to_string(),
+                    code: None,
+                    message: "Log level changed successfully".to_string(),
+                    data: None,
+                })
+            }
+            Err(e) => HttpResponse::BadRequest().json(ApiResponse::error_with(
+                "invalid_log_level",
+                &[("reason", &e.to_string())],
+            )),
+        }
+    }
+
+    /// Starts a background job re-encrypting all values with the active key.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - A reference to the database wrapped in an Arc and Mutex for thread safety.
+    /// * `jobs` - The job queue running the re-encryption.
+    ///
+    /// # Returns
+    ///
+    /// * `HttpResponse` - The HTTP response indicating the job was accepted.
+    async fn reencrypt(
+        db: web::Data<Arc<Mutex<Database>>>,
+        jobs: web::Data<JobQueue>,
+    ) -> impl Responder {
+        jobs.enqueue(Arc::new(ReencryptJob {
+            db: db.get_ref().clone(),
+        }));
+        HttpResponse::Accepted().json(ApiResponse::<()> {
+            status: "success".to_string(),
+            code: None,
+            message: "Re-encryption started".to_string(),
+            data: None,
+        })
+    }
+
+    /// Retrieves the effective configuration with all secrets redacted.
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - The live configuration of the server.
+    ///
+    /// # Returns
+    ///
+    /// * `HttpResponse` - The HTTP response containing the configuration.
+    async fn get_config(config: web::Data<RwLock<Config>>) -> impl Responder {
+        let config = config
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .redacted();
+        HttpResponse::Ok().json(ApiResponse::<Config> {
+            status: "success".to_string(),
+            code: None,
+            message: "Config retrieved successfully".to_string(),
+            data: Some(config),
+        })
+    }
+
+    /// Describes all stored tables with their columns, row counts and approximate sizes.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - A reference to the database wrapped in an Arc and Mutex for thread safety.
+    ///
+    /// # Returns
+    ///
+    /// * `HttpResponse` - The HTTP response containing the descriptions of the tables.
+    async fn get_schema(db: web::Data<Arc<Mutex<Database>>>) -> impl Responder {
+        match db.lock().await.describe_tables().await {
+            Ok(tables) => HttpResponse::Ok().json(ApiResponse::<Vec<TableInfo>> {
+                status: "success".to_string(),
+                code: None,
+                message: "Schema retrieved successfully".to_string(),
+                data: Some(tables),
+            }),
+            Err(e) => {
+                log::error!("Failed to retrieve schema: {}", e);
+                HttpResponse::InternalServerError()
+                    .json(ApiResponse::error("failed_to_retrieve_schema"))
+            }
+        }
+    }
+
+    /// Retrieves an overview of the health of the server.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - A reference to the database wrapped in an Arc and Mutex for thread safety.
+    /// * `stats` - The counters of the served requests.
+    /// * `concurrency` - The limits holding the lanes of the traffic classes.
+    ///
+    /// # Returns
+    ///
+    /// * `HttpResponse` - The HTTP response containing the statistics.
+    async fn get_stats(
+        db: web::Data<Arc<Mutex<Database>>>,
+        stats: web::Data<RequestStats>,
+        concurrency: web::Data<ConcurrencyLimits>,
+    ) -> impl Responder {
+        let db = db.lock().await;
+        match db.describe_tables().await {
+            Ok(tables) => HttpResponse::Ok().json(ApiResponse::<AdminStats> {
+                status: "success".to_string(),
+                code: None,
+                message: "Statistics retrieved successfully".to_string(),
+                data: Some(AdminStats {
+                    uptime_secs: stats.uptime_secs(),
+                    tables: tables.len(),
+                    keys: tables.iter().map(|table| table.rows).sum(),
+                    bytes: tables.iter().map(|table| table.approx_bytes).sum(),
+                    requests: stats.counts(),
+                    pools: db.pool_stats(),
+                    lanes: concurrency.lane_stats(),
+                }),
+            }),
+            Err(e) => {
+                log::error!("Failed to retrieve statistics: {}", e);
+                HttpResponse::InternalServerError()
+                    .json(ApiResponse::error("failed_to_retrieve_statistics"))
+            }
+        }
+    }
+
+    /// Exposes the metrics for scraping, in the OpenMetrics format with the trace exemplars of
+    /// the latency histogram if the scraper accepts it and in the Prometheus text format
+    /// otherwise.
+    ///
+    /// # Arguments
+    ///
+    /// * `req` - The request, whose `Accept` header selects the format.
+    /// * `db` - A reference to the database wrapped in an Arc and Mutex for thread safety.
+    /// * `stats` - The counters of the served requests.
+    /// * `concurrency` - The limits holding the lanes of the traffic classes.
+    ///
+    /// # Returns
+    ///
+    /// * `HttpResponse` - The HTTP response containing the metrics.
+    async fn get_metrics(
+        req: HttpRequest,
+        db: web::Data<Arc<Mutex<Database>>>,
+        stats: web::Data<RequestStats>,
+        concurrency: web::Data<ConcurrencyLimits>,
+    ) -> impl Responder {
+        let samples = match Sample::collect(&*db.lock().await, &stats, &concurrency).await {
+            Ok(samples) => samples,
+            Err(e) => {
+                log::error!("Failed to collect metrics: {}", e);
+                return HttpResponse::InternalServerError()
+                    .json(ApiResponse::error("failed_to_collect_metrics"));
+            }
+        };
+        let openmetrics = req
+            .headers()
+            .get(http::header::ACCEPT)
+            .and_then(|accept| accept.to_str().ok())
+            .is_some_and(|accept| accept.contains("application/openmetrics-text"));
+        if openmetrics {
+            HttpResponse::Ok()
+                .content_type(OPENMETRICS_CONTENT_TYPE)
+                .body(Sample::to_openmetrics(&samples, &stats.latency()))
+        } else {
+            HttpResponse::Ok()
+                .content_type("text/plain; version=0.0.4")
+                .body(
+                    Sample::to_prometheus(&samples)
+                        + &Sample::latency_to_prometheus(&stats.latency()),
+                )
+        }
+    }
+
+    /// Downloads the recorded requests and responses as a bundle of newline-delimited JSON,
+    /// oldest first.
+    ///
+    /// # Arguments
+    ///
+    /// * `captures` - The recorded exchanges.
+    ///
+    /// # Returns
+    ///
+    /// * `HttpResponse` - The HTTP response containing the bundle.
+    async fn captures(captures: web::Data<TrafficCapture>) -> impl Responder {
+        let mut bundle = String::new();
+        for capture in captures.entries() {
+            bundle.push_str(&serde_json::to_string(&capture).unwrap_or_default());
+            bundle.push('\n');
+        }
+        HttpResponse::Ok()
+            .content_type("application/x-ndjson")
+            .insert_header((
+                http::header::CONTENT_DISPOSITION,
+                "attachment; filename=\"xcloud-capture.ndjson\"",
+            ))
+            .body(bundle)
+    }
+
+    /// Discards the recorded requests and responses.
+    ///
+    /// # Arguments
+    ///
+    /// * `captures` - The recorded exchanges.
+    ///
+    /// # Returns
+    ///
+    /// * `HttpResponse` - The HTTP response containing the number of discarded exchanges.
+    async fn clear_captures(captures: web::Data<TrafficCapture>) -> impl Responder {
+        HttpResponse::Ok().json(ApiResponse::<usize> {
+            status: "success".to_string(),
+            code: None,
+            message: "Captures cleared successfully".to_string(),
+            data: Some(captures.clear()),
+        })
+    }
+
+    /// Starts locating the data of a person in the background, to export or anonymize it.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - A reference to the database wrapped in an Arc and Mutex for thread safety.
+    /// * `config` - The live configuration holding the owner fields.
+    /// * `jobs` - The job queue running the request.
+    /// * `privacy` - The registry tracking the privacy requests.
+    /// * `item` - The identities of the person and the action.
+    ///
+    /// # Returns
+    ///
+    /// * `HttpResponse` - The HTTP response containing the report of the request.
+    async fn start_privacy_request(
+        db: web::Data<Arc<Mutex<Database>>>,
+        config: web::Data<RwLock<Config>>,
+        jobs: web::Data<JobQueue>,
+        privacy: web::Data<PrivacyRequests>,
+        item: Json<NewPrivacyRequest>,
+    ) -> impl Responder {
+        let identities: Vec<String> = item
+            .identities
+            .iter()
+            .map(|identity| identity.trim().to_string())
+            .filter(|identity| !identity.is_empty())
+            .collect();
+        if identities.is_empty() {
+            return HttpResponse::BadRequest()
+                .json(ApiResponse::error("at_least_one_identity_must_be_given"));
+        }
+        let owner_fields = config
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .privacy
+            .owner_fields
+            .clone();
+        let report = privacy.start(item.action);
+        tracing::info!(target: "audit", id = report.id, action = ?item.action, "Privacy request started");
+        jobs.enqueue(Arc::new(PrivacyJob {
+            db: db.get_ref().clone(),
+            requests: privacy.get_ref().clone(),
+            id: report.id,
+            action: item.action,
+            identities,
+            owner_fields,
+        }));
+        HttpResponse::Accepted().json(ApiResponse::<PrivacyReport> {
+            status: "success".to_string(),
+            code: None,
+            message: "Privacy request started".to_string(),
+            data: Some(report),
+        })
+    }
+
+    /// Retrieves the report of what a privacy request located and touched.
+    ///
+    /// # Arguments
+    ///
+    /// * `privacy` - The registry tracking the privacy requests.
+    /// * `id` - The id of the request.
+    ///
+    /// # Returns
+    ///
+    /// * `HttpResponse` - The HTTP response containing the report or an error message.
+    async fn privacy_report(
+        privacy: web::Data<PrivacyRequests>,
+        id: web::Path<u64>,
+    ) -> impl Responder {
+        match privacy.get(id.into_inner()) {
+            Some(report) => HttpResponse::Ok().json(ApiResponse::<PrivacyReport> {
+                status: "success".to_string(),
+                code: None,
+                message: "Privacy request retrieved successfully".to_string(),
+                data: Some(report),
+            }),
+            None => HttpResponse::NotFound().json(ApiResponse::error("privacy_request_not_found")),
+        }
+    }
+
+    /// Downloads the archive of the data of a person collected by a completed export.
+    ///
+    /// # Arguments
+    ///
+    /// * `privacy` - The registry tracking the privacy requests.
+    /// * `id` - The id of the request.
+    ///
+    /// # Returns
+    ///
+    /// * `HttpResponse` - The HTTP response containing the archive as a JSON attachment.
+    async fn privacy_export(
+        privacy: web::Data<PrivacyRequests>,
+        id: web::Path<u64>,
+    ) -> impl Responder {
+        let id = id.into_inner();
+        match privacy.archive(id) {
+            Some(archive) => HttpResponse::Ok()
+                .insert_header((
+                    http::header::CONTENT_DISPOSITION,
+                    format!("attachment; filename=\"xcloud-privacy-{}.json\"", id),
+                ))
+                .json(archive),
+            None if privacy.get(id).is_some() => HttpResponse::Conflict().json(ApiResponse::error(
+                "privacy_request_is_not_a_completed_export",
+            )),
+            None => HttpResponse::NotFound().json(ApiResponse::error("privacy_request_not_found")),
+        }
+    }
+
+    /// Retrieves the state of the connections of the pools of all shards and replicas.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - A reference to the database wrapped in an Arc and Mutex for thread safety.
+    ///
+    /// # Returns
+    ///
+    /// * `HttpResponse` - The HTTP response containing the state of the pools.
+    async fn get_pool(db: web::Data<Arc<Mutex<Database>>>) -> impl Responder {
+        let db = db.lock().await;
+        HttpResponse::Ok().json(ApiResponse::<Vec<PoolState>> {
+            status: "success".to_string(),
+            code: None,
+            message: "Pools retrieved successfully".to_string(),
+            data: Some(db.pool_states()),
+        })
+    }
+
+    /// Closes the idle connections of all pools, so fresh ones are opened on demand.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - A reference to the database wrapped in an Arc and Mutex for thread safety.
+    ///
+    /// # Returns
+    ///
+    /// * `HttpResponse` - The HTTP response containing the number of closed connections and
+    ///   the state of the pools.
+    async fn recycle_pool(db: web::Data<Arc<Mutex<Database>>>) -> impl Responder {
+        let db = db.lock().await;
+        let closed = db.recycle_pools().await;
+        HttpResponse::Ok().json(ApiResponse::<RecycledPools> {
+            status: "success".to_string(),
+            code: None,
+            message: "Pools recycled successfully".to_string(),
+            data: Some(RecycledPools {
+                closed,
+                pools: db.pool_states(),
+            }),
+        })
+    }
+
+    /// Retrieves the state of the tokio runtime of the HTTP worker serving the request and the
+    /// sizes of the thread pools.
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - The live configuration of the server.
+    ///
+    /// # Returns
+    ///
+    /// * `HttpResponse` - The HTTP response containing the state of the runtime.
+    async fn get_runtime(config: web::Data<RwLock<Config>>) -> impl Responder {
+        let config = config
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .runtime
+            .clone();
+        HttpResponse::Ok().json(ApiResponse::<RuntimeStats> {
+            status: "success".to_string(),
+            code: None,
+            message: "Runtime retrieved successfully".to_string(),
+            data: Some(RuntimeStats::collect(&config)),
+        })
+    }
+
+    /// Retrieves the instances of the cluster and the one running the background jobs.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - A reference to the database wrapped in an Arc and Mutex for thread safety.
+    /// * `cluster` - The cluster the instance belongs to.
+    ///
+    /// # Returns
+    ///
+    /// * `HttpResponse` - The HTTP response containing the membership of the cluster.
+    async fn get_cluster(
+        db: web::Data<Arc<Mutex<Database>>>,
+        cluster: web::Data<Cluster>,
+    ) -> impl Responder {
+        match cluster.state(&*db.lock().await).await {
+            Ok(state) => HttpResponse::Ok().json(ApiResponse::<ClusterState> {
+                status: "success".to_string(),
+                code: None,
+                message: "Cluster retrieved successfully".to_string(),
+                data: Some(state),
+            }),
+            Err(e) => {
+                log::error!("Failed to retrieve cluster members: {}", e);
+                HttpResponse::InternalServerError()
+                    .json(ApiResponse::error("failed_to_retrieve_cluster_members"))
+            }
+        }
+    }
+
+    /// Retrieves the most recently sent alerts of the instance, newest first.
+    ///
+    /// # Arguments
+    ///
+    /// * `alerts` - The alerter of the instance.
+    ///
+    /// # Returns
+    ///
+    /// * `HttpResponse` - The HTTP response containing the alerts.
+    async fn get_alerts(alerts: web::Data<Alerter>) -> impl Responder {
+        HttpResponse::Ok().json(ApiResponse::<Vec<Alert>> {
+            status: "success".to_string(),
+            code: None,
+            message: "Alerts retrieved successfully".to_string(),
+            data: Some(alerts.recent()),
+        })
+    }
+
+    /// Runs a read-only statement against the primary for the admin SQL console.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - A reference to the database wrapped in an Arc and Mutex for thread safety.
+    /// * `config` - The live configuration enabling the console and holding its limits.
+    /// * `item` - The statement with the optional lower limits.
+    ///
+    /// # Returns
+    ///
+    /// * `HttpResponse` - The HTTP response containing the columns and the rows, a `400` if the
+    ///   statement is refused, fails or times out, or a `403` if the console is disabled.
+    async fn run_sql(
+        db: web::Data<Arc<Mutex<Database>>>,
+        config: web::Data<RwLock<Config>>,
+        item: Json<SqlStatement>,
+    ) -> impl Responder {
+        let console = config
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .sql_console
+            .clone();
+        if !console.enabled {
+            return HttpResponse::Forbidden()
+                .json(ApiResponse::error("the_sql_console_is_disabled"));
+        }
+        let max_rows = item
+            .max_rows
+            .map_or(console.max_rows, |rows| rows.min(console.max_rows));
+        let timeout_ms = item
+            .timeout_ms
+            .map_or(console.timeout_ms, |ms| ms.min(console.timeout_ms));
+        tracing::info!(target: "audit", sql = %item.sql, "SQL console statement submitted");
+        let conn = db.lock().await.open_read_only().await;
+        let result = match conn {
+            Ok(conn) => {
+                SqlConsole::run(conn, &item.sql, max_rows, Duration::from_millis(timeout_ms)).await
+            }
+            Err(e) => Err(e),
+        };
+        match result {
+            Ok(result) => HttpResponse::Ok().json(ApiResponse::<ConsoleResult> {
+                status: "success".to_string(),
+                code: None,
+                message: "Statement run successfully".to_string(),
+                data: Some(result),
+            }),
+            Err(e) => {
+                log::warn!("Failed to run console statement: {}", e);
+                Self::database_error(&e, "failed_to_run_statement")
+            }
+        }
+    }
+
+    /// Archives the cold keys and removes the unreferenced archives now, without waiting for
+    /// the background job, if tiering is enabled.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - A reference to the database wrapped in an Arc and Mutex for thread safety.
+    /// * `config` - The live configuration holding the tiering policy.
+    ///
+    /// # Returns
+    ///
+    /// * `HttpResponse` - The HTTP response containing the number of archived keys and removed
+    ///   archives.
+    async fn run_tiering(
+        db: web::Data<Arc<Mutex<Database>>>,
+        config: web::Data<RwLock<Config>>,
+    ) -> impl Responder {
+        let tiering = config
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .tiering
+            .clone();
+        match TieringJob::run_with(&*db.lock().await, &tiering).await {
+            Ok(report) => HttpResponse::Ok().json(ApiResponse::<TieringReport> {
+                status: "success".to_string(),
+                code: None,
+                message: "Cold keys archived successfully".to_string(),
+                data: Some(report),
+            }),
+            Err(e) => {
+                log::error!("Failed to archive cold keys: {}", e);
+                Self::database_error(&e, "failed_to_archive_cold_keys")
+            }
+        }
+    }
+
+    /// Answers the startup probe, passing once the database has been reached.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - A reference to the database wrapped in an Arc and Mutex for thread safety.
+    /// * `health` - The probes of the server.
+    ///
+    /// # Returns
+    ///
+    /// * `HttpResponse` - A `200` if the server has started, a `503` otherwise, with the checks.
+    async fn startup_probe(
+        db: web::Data<Arc<Mutex<Database>>>,
+        health: web::Data<Health>,
+    ) -> impl Responder {
+        let result = health.startup(&*db.lock().await).await;
+        Self::probe_response(result, "Started", "not_started")
+    }
+
+    /// Answers the liveness probe, failing only when restarting the server could help.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - A reference to the database wrapped in an Arc and Mutex for thread safety.
+    /// * `health` - The probes of the server.
+    ///
+    /// # Returns
+    ///
+    /// * `HttpResponse` - A `200` if the server is alive, a `503` otherwise, with the checks.
+    async fn liveness_probe(
+        db: web::Data<Arc<Mutex<Database>>>,
+        health: web::Data<Health>,
+    ) -> impl Responder {
+        let result = health.liveness(&*db.lock().await).await;
+        Self::probe_response(result, "Alive", "not_alive")
+    }
+
+    /// Answers the readiness probe, failing while requests would be rejected.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - A reference to the database wrapped in an Arc and Mutex for thread safety.
+    /// * `health` - The probes of the server.
+    /// * `breaker` - The circuit breaker of the database.
+    /// * `maintenance` - The maintenance state shared by all workers.
+    ///
+    /// # Returns
+    ///
+    /// * `HttpResponse` - A `200` if the server is ready, a `503` otherwise, with the checks.
+    async fn readiness_probe(
+        db: web::Data<Arc<Mutex<Database>>>,
+        health: web::Data<Health>,
+        breaker: web::Data<CircuitBreaker>,
+        maintenance: web::Data<Maintenance>,
+    ) -> impl Responder {
+        let result = health
+            .readiness(&*db.lock().await, &breaker, &maintenance)
+            .await;
+        Self::probe_response(result, "Ready", "not_ready")
+    }
+
+    /// Builds the response to a probe.
+    ///
+    /// # Arguments
+    ///
+    /// * `result` - The outcome of the probe.
+    /// * `passed` - The message of the response if the probe passed.
+    /// * `failed` - The code of the error of the response if the probe failed.
+    ///
+    /// # Returns
+    ///
+    /// * `HttpResponse` - A `200` if the probe passed, a `503` otherwise, with the checks.
+    fn probe_response(result: ProbeResult, passed: &str, failed: &str) -> HttpResponse {
+        if !result.passed {
+            return HttpResponse::ServiceUnavailable().json(ApiResponse::error_with_data(
+                failed,
+                &[],
+                Some(result),
+            ));
+        }
+        HttpResponse::Ok().json(ApiResponse::<ProbeResult> {
+            status: "success".to_string(),
+            code: None,
+            message: passed.to_string(),
+            data: Some(result),
+        })
+    }
+
+    /// Retrieves the current maintenance state.
+    ///
+    /// # Arguments
+    ///
+    /// * `maintenance` - The maintenance state shared by all workers.
+    ///
+    /// # Returns
+    ///
+    /// * `HttpResponse` - The HTTP response containing the maintenance state.
+    async fn get_maintenance(maintenance: web::Data<Maintenance>) -> impl Responder {
+        HttpResponse::Ok().json(ApiResponse::<MaintenanceState> {
+            status: "success".to_string(),
+            code: None,
+            message: "Maintenance state retrieved successfully".to_string(),
+            data: Some(maintenance.state()),
+        })
+    }
+
+    /// Switches the server into or out of maintenance mode, persisting the state across restarts.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - A reference to the database wrapped in an Arc and Mutex for thread safety.
+    /// * `maintenance` - The maintenance state shared by all workers.
+    /// * `item` - The new maintenance state.
+    ///
+    /// # Returns
+    ///
+    /// * `HttpResponse` - The HTTP response indicating success or failure.
+    async fn set_maintenance(
+        db: web::Data<Arc<Mutex<Database>>>,
+        maintenance: web::Data<Maintenance>,
+        item: Json<MaintenanceState>,
+    ) -> impl Responder {
+        let db = db.lock().await;
+        match maintenance.set(&db, item.into_inner()).await {
+            Ok(_) => {
+                let state = maintenance.state();
+                tracing::warn!(mode = ?state.mode, "Maintenance mode changed");
+                HttpResponse::Ok().json(ApiResponse::<MaintenanceState> {
+                    status: "success".to_string(),
+                    code: None,
+                    message: "Maintenance mode changed successfully".to_string(),
+                    data: Some(state),
+                })
+            }
+            Err(e) => {
+                log::error!("Failed to change maintenance mode: {}", e);
+                HttpResponse::InternalServerError()
+                    .json(ApiResponse::error("failed_to_change_maintenance_mode"))
+            }
+        }
+    }
+
+    /// Applies a signed batch of writes replicated from a peer instance.
+    ///
+    /// Writes at or before the last one applied from the same peer are skipped, so a batch
+    /// that is sent again is not applied twice, and writes to tables under a legal hold are
+    /// rejected like local writes.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - A reference to the database wrapped in an Arc and Mutex for thread safety.
+    /// * `config` - The live configuration holding the shared secret and conflict policy.
+    /// * `req` - The request carrying the signature header.
+    /// * `body` - The raw body of the batch.
+    ///
+    /// # Returns
+    ///
+    /// * `HttpResponse` - The HTTP response containing the number of applied and skipped writes.
+    async fn apply_replication(
+        db: web::Data<Arc<Mutex<Database>>>,
+        config: web::Data<RwLock<Config>>,
+        req: HttpRequest,
+        body: web::Bytes,
+    ) -> impl Responder {
+        let replication = config
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .replication
+            .clone();
+        if replication.secret.is_empty() {
+            return HttpResponse::Forbidden()
+                .json(ApiResponse::error("replication_is_not_configured"));
+        }
+        let signature = req
+            .headers()
+            .get(SIGNATURE_HEADER)
+            .and_then(|v| v.to_str().ok());
+        if !Replicator::verify(&replication.secret, &body, signature) {
+            return HttpResponse::Unauthorized().json(ApiResponse::error("invalid_signature"));
+        }
+        let batch: ReplicationBatch = match serde_json::from_slice(&body) {
+            Ok(batch) => batch,
+            Err(e) => {
+                return HttpResponse::BadRequest().json(ApiResponse::error_with(
+                    "invalid_batch",
+                    &[("reason", &e.to_string())],
+                ))
+            }
+        };
+        if batch.origin.is_empty() {
+            return HttpResponse::BadRequest().json(ApiResponse::error_with(
+                "invalid_batch",
+                &[("reason", "the origin must not be empty")],
+            ));
+        }
+        let db = db.lock().await;
+        let mut last = match Replicator::last_applied(&db, &batch.origin).await {
+            Ok(last) => last,
+            Err(e) => {
+                log::error!(
+                    "Failed to read the last write applied from {}: {}",
+                    batch.origin,
+                    e
+                );
+                return HttpResponse::InternalServerError()
+                    .json(ApiResponse::error("failed_to_apply_replicated_writes"));
+            }
+        };
+        let mut result = ReplicationResult {
+            applied: 0,
+            skipped: 0,
+        };
+        for entry in &batch.entries {
+            if entry.id <= last {
+                result.skipped += 1;
+                continue;
+            }
+            if let Some(response) = Self::check_hold(&db, &entry.table).await {
+                return response;
+            }
+            let outcome = match Replicator::apply(&db, entry, replication.conflict).await {
+                Ok(applied) => Replicator::record_applied(&db, &batch.origin, entry.id)
+                    .await
+                    .map(|()| applied),
+                Err(e) => Err(e),
+            };
+            match outcome {
+                Ok(true) => result.applied += 1,
+                Ok(false) => result.skipped += 1,
+                Err(e) => {
+                    log::error!("Failed to apply replicated write #{}: {}", entry.id, e);
+                    return HttpResponse::InternalServerError()
+                        .json(ApiResponse::error("failed_to_apply_replicated_writes"));
+                }
+            }
+            last = entry.id;
+        }
+        HttpResponse::Ok().json(ApiResponse::<ReplicationResult> {
+            status: "success".to_string(),
+            code: None,
+            message: "Replicated writes applied successfully".to_string(),
+            data: Some(result),
+        })
+    }
+
+    /// Retrieves the feature flags.
+    ///
+    /// # Arguments
+    ///
+    /// * `flags` - The feature flags shared by all workers.
+    ///
+    /// # Returns
+    ///
+    /// * `HttpResponse` - The HTTP response containing the flags.
+    async fn feature_flags(flags: web::Data<FeatureFlags>) -> impl Responder {
+        HttpResponse::Ok().json(ApiResponse::<Vec<FeatureFlag>> {
+            status: "success".to_string(),
+            code: None,
+            message: "Feature flags retrieved successfully".to_string(),
+            data: Some(flags.list()),
+        })
+    }
+
+    /// Creates or replaces a feature flag, applied by every instance within seconds.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - A reference to the database wrapped in an Arc and Mutex for thread safety.
+    /// * `flags` - The feature flags shared by all workers.
+    /// * `item` - The flag.
+    ///
+    /// # Returns
+    ///
+    /// * `HttpResponse` - The HTTP response indicating success or failure.
+    async fn set_feature_flag(
+        db: web::Data<Arc<Mutex<Database>>>,
+        flags: web::Data<FeatureFlags>,
+        item: Json<FeatureFlag>,
+    ) -> impl Responder {
+        let flag = item.into_inner();
+        let name = flag.name.clone();
+        match flags.set(&*db.lock().await, flag).await {
+            Ok(()) => {
+                tracing::info!(target: "audit", flag = %name, "Feature flag set");
+                HttpResponse::Ok().json(ApiResponse::<()> {
+                    status: "success".to_string(),
+                    code: None,
+                    message: "Feature flag set successfully".to_string(),
+                    data: None,
+                })
+            }
+            Err(e) => {
+                log::warn!("Failed to set feature flag: {}", e);
+                Self::database_error(&e, "failed_to_set_feature_flag")
+            }
+        }
+    }
+
+    /// Removes a feature flag, turning it off.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - A reference to the database wrapped in an Arc and Mutex for thread safety.
+    /// * `flags` - The feature flags shared by all workers.
+    /// * `item` - The name of the flag to remove.
+    ///
+    /// # Returns
+    ///
+    /// * `HttpResponse` - The HTTP response indicating success or failure.
+    async fn delete_feature_flag(
+        db: web::Data<Arc<Mutex<Database>>>,
+        flags: web::Data<FeatureFlags>,
+        item: Json<FlagName>,
+    ) -> impl Responder {
+        match flags.remove(&*db.lock().await, &item.name).await {
+            Ok(true) => {
+                tracing::info!(target: "audit", flag = %item.name, "Feature flag removed");
+                HttpResponse::Ok().json(ApiResponse::<()> {
+                    status: "success".to_string(),
+                    code: None,
+                    message: "Feature flag removed successfully".to_string(),
+                    data: None,
+                })
+            }
+            Ok(false) => {
+                HttpResponse::NotFound().json(ApiResponse::error("feature_flag_not_found"))
+            }
+            Err(e) => {
+                log::error!("Failed to remove feature flag: {}", e);
+                HttpResponse::InternalServerError()
+                    .json(ApiResponse::error("failed_to_remove_feature_flag"))
+            }
+        }
+    }
+
+    /// Evaluates a feature flag for the calling tenant, so clients can switch behaviors too.
+    ///
+    /// # Arguments
+    ///
+    /// * `req` - The request, whose `X-xCLOUD-Client` header names the tenant.
+    /// * `flags` - The feature flags shared by all workers.
+    /// * `item` - The name of the flag.
+    ///
+    /// # Returns
+    ///
+    /// * `HttpResponse` - The HTTP response containing whether the flag is on, unknown flags
+    ///   being off.
+    async fn feature_flag(
+        req: HttpRequest,
+        flags: web::Data<FeatureFlags>,
+        item: web::Query<FlagName>,
+    ) -> impl Responder {
+        let tenant = req
+            .headers()
+            .get(CLIENT_HEADER)
+            .and_then(|v| v.to_str().ok());
+        HttpResponse::Ok().json(ApiResponse::<FlagState> {
+            status: "success".to_string(),
+            code: None,
+            message: "Feature flag evaluated successfully".to_string(),
+            data: Some(FlagState {
+                enabled: flags.is_enabled(&item.name, tenant),
+                name: item.into_inner().name,
+            }),
+        })
+    }
+
+    /// Retrieves the retention policies of all tables.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - A reference to the database wrapped in an Arc and Mutex for thread safety.
+    ///
+    /// # Returns
+    ///
+    /// * `HttpResponse` - The HTTP response containing the retention policies.
+    async fn table_policies(db: web::Data<Arc<Mutex<Database>>>) -> impl Responder {
+        match db.lock().await.table_policies().await {
+            Ok(policies) => HttpResponse::Ok().json(ApiResponse::<Vec<TablePolicy>> {
+                status: "success".to_string(),
+                code: None,
+                message: "Table policies retrieved successfully".to_string(),
+                data: Some(policies),
+            }),
+            Err(e) => {
+                log::error!("Failed to retrieve table policies: {}", e);
+                HttpResponse::InternalServerError()
+                    .json(ApiResponse::error("failed_to_retrieve_table_policies"))
+            }
+        }
+    }
+
+    /// Sets the retention policy of a table.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - A reference to the database wrapped in an Arc and Mutex for thread safety.
+    /// * `item` - The retention policy.
+    ///
+    /// # Returns
+    ///
+    /// * `HttpResponse` - The HTTP response indicating success or failure.
+    async fn set_table_policy(
+        db: web::Data<Arc<Mutex<Database>>>,
+        item: Json<TablePolicy>,
+    ) -> impl Responder {
+        if item.max_age_secs.unwrap_or(0) < 0 || item.max_rows.unwrap_or(0) < 0 {
+            return HttpResponse::BadRequest()
+                .json(ApiResponse::error("retention_limits_must_not_be_negative"));
+        }
+        match db.lock().await.set_table_policy(&item).await {
+            Ok(_) => {
+                tracing::info!(target: "audit", table = %item.table, "Table policy set");
+                HttpResponse::Ok().json(ApiResponse::<()> {
+                    status: "success".to_string(),
+                    code: None,
+                    message: "Table policy set successfully".to_string(),
+                    data: None,
+                })
+            }
+            Err(e) => {
+                log::error!("Failed to set table policy: {}", e);
+                HttpResponse::InternalServerError()
+                    .json(ApiResponse::error("failed_to_set_table_policy"))
+            }
+        }
+    }
+
+    /// Removes the retention policy of a table.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - A reference to the database wrapped in an Arc and Mutex for thread safety.
+    /// * `item` - The table whose policy to remove.
+    ///
+    /// # Returns
+    ///
+    /// * `HttpResponse` - The HTTP response indicating success or failure.
+    async fn delete_table_policy(
+        db: web::Data<Arc<Mutex<Database>>>,
+        item: Json<Table>,
+    ) -> impl Responder {
+        match db.lock().await.delete_table_policy(&item.table).await {
+            Ok(true) => {
+                tracing::info!(target: "audit", table = %item.table, "Table policy removed");
+                HttpResponse::Ok().json(ApiResponse::<()> {
+                    status: "success".to_string(),
+                    code: None,
+                    message: "Table policy removed successfully".to_string(),
+                    data: None,
+                })
+            }
+            Ok(false) => HttpResponse::NotFound().json(ApiResponse::error("table_has_no_policy")),
+            Err(e) => {
+                log::error!("Failed to remove table policy: {}", e);
+                HttpResponse::InternalServerError()
+                    .json(ApiResponse::error("failed_to_remove_table_policy"))
+            }
+        }
+    }
+
+    /// Retrieves the legal holds.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - A reference to the database wrapped in an Arc and Mutex for thread safety.
+    ///
+    /// # Returns
+    ///
+    /// * `HttpResponse` - The HTTP response containing the holds.
+    async fn legal_holds(db: web::Data<Arc<Mutex<Database>>>) -> impl Responder {
+        match db.lock().await.legal_holds().await {
+            Ok(holds) => HttpResponse::Ok().json(ApiResponse::<Vec<LegalHold>> {
+                status: "success".to_string(),
+                code: None,
+                message: "Legal holds retrieved successfully".to_string(),
+                data: Some(holds),
+            }),
+            Err(e) => {
+                log::error!("Failed to retrieve legal holds: {}", e);
+                HttpResponse::InternalServerError()
+                    .json(ApiResponse::error("failed_to_retrieve_legal_holds"))
+            }
+        }
+    }
+
+    /// Places a legal hold on a table, blocking all writes to it and its deletion.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - A reference to the database wrapped in an Arc and Mutex for thread safety.
+    /// * `item` - The table and the reason of the hold.
+    ///
+    /// # Returns
+    ///
+    /// * `HttpResponse` - The HTTP response indicating success or failure.
+    async fn place_legal_hold(
+        db: web::Data<Arc<Mutex<Database>>>,
+        item: Json<NewLegalHold>,
+    ) -> impl Responder {
+        if Utils::sanitize(&item.table).is_empty() {
+            return HttpResponse::BadRequest()
+                .json(ApiResponse::error("table_name_must_not_be_empty"));
+        }
+        match db
+            .lock()
+            .await
+            .place_legal_hold(&item.table, &item.reason)
+            .await
+        {
+            Ok(true) => {
+                tracing::info!(target: "audit", table = %item.table, reason = %item.reason, "Legal hold placed");
+                HttpResponse::Ok().json(ApiResponse::<()> {
+                    status: "success".to_string(),
+                    code: None,
+                    message: "Legal hold placed successfully".to_string(),
+                    data: None,
+                })
+            }
+            Ok(false) => HttpResponse::Conflict()
+                .json(ApiResponse::error("table_is_already_under_legal_hold")),
+            Err(e) => {
+                log::error!("Failed to place legal hold: {}", e);
+                HttpResponse::InternalServerError()
+                    .json(ApiResponse::error("failed_to_place_legal_hold"))
+            }
+        }
+    }
+
+    /// Lifts the legal hold of a table, once confirmed with the current time-based one-time
+    /// password derived from the configured secret.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - A reference to the database wrapped in an Arc and Mutex for thread safety.
+    /// * `config` - The live configuration holding the secret of the second factor.
+    /// * `item` - The table and the one-time password.
+    ///
+    /// # Returns
+    ///
+    /// * `HttpResponse` - The HTTP response indicating success or failure.
+    async fn lift_legal_hold(
+        db: web::Data<Arc<Mutex<Database>>>,
+        config: web::Data<RwLock<Config>>,
+        item: Json<LiftLegalHold>,
+    ) -> impl Responder {
+        let secret = config
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .admin
+            .hold_totp_secret
+            .clone();
+        if secret.is_empty() {
+            return HttpResponse::Forbidden().json(ApiResponse::error(
+                "lifting_legal_holds_requires_a_second_factor_which_is_not_configured",
+            ));
+        }
+        let totp = match Totp::new(&secret) {
+            Ok(totp) => totp,
+            Err(e) => {
+                log::error!("Invalid second-factor secret: {}", e);
+                return HttpResponse::InternalServerError()
+                    .json(ApiResponse::error("failed_to_lift_legal_hold"));
+            }
+        };
+        let db = db.lock().await;
+        let last_step = match db.get_setting(HOLD_TOTP_STEP_SETTING).await {
+            Ok(step) => step.and_then(|step| step.parse().ok()),
+            Err(e) => {
+                log::error!("Failed to read the last second-factor step: {}", e);
+                return HttpResponse::InternalServerError()
+                    .json(ApiResponse::error("failed_to_lift_legal_hold"));
+            }
+        };
+        let Some(step) = totp.verify(&item.code, UrlSigner::now(), last_step) else {
+            tracing::warn!(target: "audit", table = %item.table, "Legal hold lift rejected");
+            return HttpResponse::Forbidden()
+                .json(ApiResponse::error("invalid_second_factor_code"));
+        };
+        if let Err(e) = db
+            .set_setting(HOLD_TOTP_STEP_SETTING, &step.to_string())
+            .await
+        {
+            log::error!("Failed to record the second-factor step: {}", e);
+            return HttpResponse::InternalServerError()
+                .json(ApiResponse::error("failed_to_lift_legal_hold"));
+        }
+        match db.lift_legal_hold(&item.table).await {
+            Ok(true) => {
+                tracing::info!(target: "audit", table = %item.table, "Legal hold lifted");
+                HttpResponse::Ok().json(ApiResponse::<()> {
+                    status: "success".to_string(),
+                    code: None,
+                    message: "Legal hold lifted successfully".to_string(),
+                    data: None,
+                })
+            }
+            Ok(false) => {
+                HttpResponse::NotFound().json(ApiResponse::error("table_is_not_under_legal_hold"))
+            }
+            Err(e) => {
+                log::error!("Failed to lift legal hold: {}", e);
+                HttpResponse::InternalServerError()
+                    .json(ApiResponse::error("failed_to_lift_legal_hold"))
+            }
+        }
+    }
+
+    /// Retrieves the integrations and the outcomes of their last polls, with their
+    /// `Authorization` headers redacted.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - A reference to the database wrapped in an Arc and Mutex for thread safety.
+    ///
+    /// # Returns
+    ///
+    /// * `HttpResponse` - The HTTP response containing the integrations.
+    async fn integrations(db: web::Data<Arc<Mutex<Database>>>) -> impl Responder {
+        match db.lock().await.integrations(false).await {
+            Ok(mut integrations) => {
+                for integration in &mut integrations {
+                    if integration.authorization.is_some() {
+                        integration.authorization = Some(REDACTED.to_string());
+                    }
+                }
+                HttpResponse::Ok().json(ApiResponse::<Vec<Integration>> {
+                    status: "success".to_string(),
+                    code: None,
+                    message: "Integrations retrieved successfully".to_string(),
+                    data: Some(integrations),
+                })
+            }
+            Err(e) => {
+                log::error!("Failed to retrieve integrations: {}", e);
+                Self::database_error(&e, "failed_to_retrieve_integrations")
+            }
+        }
+    }
+
+    /// Registers or replaces an integration, polled for the first time at the next tick of the
+    /// scheduler.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - A reference to the database wrapped in an Arc and Mutex for thread safety.
+    /// * `item` - The source, interval, authorization and mapping of the integration.
+    ///
+    /// # Returns
+    ///
+    /// * `HttpResponse` - The HTTP response indicating success or failure.
+    async fn put_integration(
+        db: web::Data<Arc<Mutex<Database>>>,
+        item: Json<Integration>,
+    ) -> impl Responder {
+        if item.name.trim().is_empty() || Utils::sanitize(&item.table).is_empty() {
+            return HttpResponse::BadRequest()
+                .json(ApiResponse::error("name_and_table_must_not_be_empty"));
+        }
+        if !item.url.starts_with("http://") && !item.url.starts_with("https://") {
+            return HttpResponse::BadRequest()
+                .json(ApiResponse::error("url_must_use_http_or_https"));
+        }
+        if item.interval_secs <= 0 {
+            return HttpResponse::BadRequest()
+                .json(ApiResponse::error("interval_must_be_positive"));
+        }
+        let pointers = [&item.items_pointer, &item.key_pointer, &item.value_pointer];
+        if pointers
+            .iter()
+            .any(|pointer| !pointer.is_empty() && !pointer.starts_with('/'))
+        {
+            return HttpResponse::BadRequest().json(ApiResponse::error(
+                "json_pointers_must_be_empty_or_start_with",
+            ));
+        }
+        match db.lock().await.put_integration(&item).await {
+            Ok(()) => {
+                tracing::info!(target: "audit", name = %item.name, table = %item.table, url = %item.url, "Integration registered");
+                HttpResponse::Ok().json(ApiResponse::<()> {
+                    status: "success".to_string(),
+                    code: None,
+                    message: "Integration registered successfully".to_string(),
+                    data: None,
+                })
+            }
+            Err(e) => {
+                log::error!("Failed to register integration: {}", e);
+                Self::database_error(&e, "failed_to_register_integration")
+            }
+        }
+    }
+
+    /// Removes an integration.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - A reference to the database wrapped in an Arc and Mutex for thread safety.
+    /// * `name` - The name of the integration.
+    ///
+    /// # Returns
+    ///
+    /// * `HttpResponse` - The HTTP response indicating success or failure.
+    async fn delete_integration(
+        db: web::Data<Arc<Mutex<Database>>>,
+        name: web::Path<String>,
+    ) -> impl Responder {
+        match db.lock().await.delete_integration(&name).await {
+            Ok(true) => {
+                tracing::info!(target: "audit", name = %name, "Integration removed");
+                HttpResponse::Ok().json(ApiResponse::<()> {
+                    status: "success".to_string(),
+                    code: None,
+                    message: "Integration removed successfully".to_string(),
+                    data: None,
+                })
+            }
+            Ok(false) => HttpResponse::NotFound().json(ApiResponse::error("integration_not_found")),
+            Err(e) => {
+                log::error!("Failed to remove integration: {}", e);
+                Self::database_error(&e, "failed_to_remove_integration")
+            }
+        }
+    }
+
+    /// Makes an integration due, so it is polled at the next tick of the scheduler.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - A reference to the database wrapped in an Arc and Mutex for thread safety.
+    /// * `name` - The name of the integration.
+    ///
+    /// # Returns
+    ///
+    /// * `HttpResponse` - The HTTP response indicating whether the poll was scheduled.
+    async fn run_integration(
+        db: web::Data<Arc<Mutex<Database>>>,
+        name: web::Path<String>,
+    ) -> impl Responder {
+        match db.lock().await.schedule_integration(&name).await {
+            Ok(true) => HttpResponse::Accepted().json(ApiResponse::<()> {
+                status: "success".to_string(),
+                code: None,
+                message: "Integration scheduled successfully".to_string(),
+                data: None,
+            }),
+            Ok(false) => HttpResponse::NotFound().json(ApiResponse::error("integration_not_found")),
+            Err(e) => {
+                log::error!("Failed to schedule integration: {}", e);
+                Self::database_error(&e, "failed_to_schedule_integration")
+            }
+        }
+    }
+
+    /// Retrieves the JSON Schemas attached to the tables.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - A reference to the database wrapped in an Arc and Mutex for thread safety.
+    ///
+    /// # Returns
+    ///
+    /// * `HttpResponse` - The HTTP response containing the JSON Schemas.
+    async fn json_schemas(db: web::Data<Arc<Mutex<Database>>>) -> impl Responder {
+        match db.lock().await.json_schemas().await {
+            Ok(schemas) => HttpResponse::Ok().json(ApiResponse::<Vec<JsonSchema>> {
+                status: "success".to_string(),
+                code: None,
+                message: "JSON Schemas retrieved successfully".to_string(),
+                data: Some(schemas),
+            }),
+            Err(e) => {
+                log::error!("Failed to retrieve JSON Schemas: {}", e);
+                HttpResponse::InternalServerError()
+                    .json(ApiResponse::error("failed_to_retrieve_json_schemas"))
+            }
+        }
+    }
+
+    /// Lists the configured regions and the tables tagged with them.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - A reference to the database wrapped in an Arc and Mutex for thread safety.
+    ///
+    /// # Returns
+    ///
+    /// * `HttpResponse` - The HTTP response containing the regions and the tagged tables.
+    async fn table_regions(db: web::Data<Arc<Mutex<Database>>>) -> impl Responder {
+        let db = db.lock().await;
+        HttpResponse::Ok().json(ApiResponse::<Regions> {
+            status: "success".to_string(),
+            code: None,
+            message: "Regions retrieved successfully".to_string(),
+            data: Some(Regions {
+                regions: db.regions(),
+                tables: db.table_regions(),
+            }),
+        })
+    }
+
+    /// Tags a table that does not exist yet with the region its data must be stored in, or
+    /// removes the tag.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - A reference to the database wrapped in an Arc and Mutex for thread safety.
+    /// * `item` - The table and its region.
+    ///
+    /// # Returns
+    ///
+    /// * `HttpResponse` - The HTTP response indicating success or failure.
+    async fn set_table_region(
+        db: web::Data<Arc<Mutex<Database>>>,
+        item: Json<SetTableRegion>,
+    ) -> impl Responder {
+        let db = db.lock().await;
+        if let Some(region) = &item.region {
+            if !db.regions().contains(region) {
+                return HttpResponse::BadRequest()
+                    .json(ApiResponse::error("region_is_not_configured"));
+            }
+        }
+        match db
+            .set_table_region(&item.table, item.region.as_deref())
+            .await
+        {
+            Ok(true) => {
+                tracing::info!(target: "audit", table = %item.table, region = ?item.region, "Table region set");
+                HttpResponse::Ok().json(ApiResponse::<()> {
+                    status: "success".to_string(),
+                    code: None,
+                    message: "Table region set successfully".to_string(),
+                    data: None,
+                })
+            }
+            Ok(false) => HttpResponse::Conflict().json(ApiResponse::error(
+                "table_already_exists_its_region_cannot_change",
+            )),
+            Err(e) => {
+                log::error!("Failed to set table region: {}", e);
+                Self::database_error(&e, "failed_to_set_table_region")
+            }
+        }
+    }
+
+    /// Attaches a JSON Schema to a table, validating every later write to the table.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - A reference to the database wrapped in an Arc and Mutex for thread safety.
+    /// * `item` - The table and the JSON Schema its values must match.
+    ///
+    /// # Returns
+    ///
+    /// * `HttpResponse` - The HTTP response indicating success or failure.
+    async fn set_json_schema(
+        db: web::Data<Arc<Mutex<Database>>>,
+        item: Json<JsonSchema>,
+    ) -> impl Responder {
+        if let Err(reason) = ValueValidator::new(ValueType::Json).with_schema(&item.schema) {
+            return HttpResponse::BadRequest().json(ApiResponse::error_with(
+                "invalid_json_schema",
+                &[("reason", &reason)],
+            ));
+        }
+        let db = db.lock().await;
+        Self::with_schema_lock(&db, &item.table, "set_json_schema", || async {
+            match db.set_json_schema(&item).await {
+                Ok(_) => {
+                    tracing::info!(target: "audit", table = %item.table, "JSON Schema attached");
+                    HttpResponse::Ok().json(ApiResponse::<()> {
+                        status: "success".to_string(),
+                        code: None,
+                        message: "JSON Schema attached successfully".to_string(),
+                        data: None,
+                    })
+                }
+                Err(e) => {
+                    log::error!("Failed to attach JSON Schema: {}", e);
+                    HttpResponse::InternalServerError()
+                        .json(ApiResponse::error("failed_to_attach_json_schema"))
+                }
+            }
+        })
+        .await
+    }
+
+    /// Detaches the JSON Schema of a table.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - A reference to the database wrapped in an Arc and Mutex for thread safety.
+    /// * `item` - The table whose schema to detach.
+    ///
+    /// # Returns
+    ///
+    /// * `HttpResponse` - The HTTP response indicating success or failure.
+    async fn delete_json_schema(
+        db: web::Data<Arc<Mutex<Database>>>,
+        item: Json<Table>,
+    ) -> impl Responder {
+        let db = db.lock().await;
+        Self::with_schema_lock(&db, &item.table, "delete_json_schema", || async {
+            match db.delete_json_schema(&item.table).await {
+                Ok(true) => {
+                    tracing::info!(target: "audit", table = %item.table, "JSON Schema detached");
+                    HttpResponse::Ok().json(ApiResponse::<()> {
+                        status: "success".to_string(),
+                        code: None,
+                        message: "JSON Schema detached successfully".to_string(),
+                        data: None,
+                    })
+                }
+                Ok(false) => {
+                    HttpResponse::NotFound().json(ApiResponse::error("table_has_no_json_schema"))
+                }
+                Err(e) => {
+                    log::error!("Failed to detach JSON Schema: {}", e);
+                    HttpResponse::InternalServerError()
+                        .json(ApiResponse::error("failed_to_detach_json_schema"))
+                }
+            }
+        })
+        .await
+    }
+
+    /// Retrieves the rules deriving keys from fields of other keys.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - A reference to the database wrapped in an Arc and Mutex for thread safety.
+    ///
+    /// # Returns
+    ///
+    /// * `HttpResponse` - The HTTP response containing the transforms.
+    async fn transforms(db: web::Data<Arc<Mutex<Database>>>) -> impl Responder {
+        match db.lock().await.transforms().await {
+            Ok(transforms) => HttpResponse::Ok().json(ApiResponse::<Vec<Transform>> {
+                status: "success".to_string(),
+                code: None,
+                message: "Transforms retrieved successfully".to_string(),
+                data: Some(transforms),
+            }),
+            Err(e) => {
+                log::error!("Failed to retrieve transforms: {}", e);
+                HttpResponse::InternalServerError()
+                    .json(ApiResponse::error("failed_to_retrieve_transforms"))
+            }
+        }
+    }
+
+    /// Adds a rule deriving a key from a field of another key, and derives it right away.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - A reference to the database wrapped in an Arc and Mutex for thread safety.
+    /// * `item` - The source key, the pointer to the field and the derived key.
+    ///
+    /// # Returns
+    ///
+    /// * `HttpResponse` - The HTTP response containing the id of the transform.
+    async fn add_transform(
+        db: web::Data<Arc<Mutex<Database>>>,
+        item: Json<NewTransform>,
+    ) -> impl Responder {
+        if !item.pointer.is_empty() && !item.pointer.starts_with('/') {
+            return HttpResponse::BadRequest()
+                .json(ApiResponse::error("pointer_must_be_empty_or_start_with"));
+        }
+        if item.source_table == item.target_table && item.source_key == item.target_key {
+            return HttpResponse::BadRequest()
+                .json(ApiResponse::error("a_key_cannot_be_derived_from_itself"));
+        }
+        let db = db.lock().await;
+        if db.table_region(&item.source_table) != db.table_region(&item.target_table)
+            && !item.allow_cross_region
+        {
+            return HttpResponse::Forbidden()
+                .json(ApiResponse::error("deriving_the_key_would_cross_regions"));
+        }
+        let result = db
+            .add_transform(
+                &item.source_table,
+                &item.source_key,
+                &item.pointer,
+                &item.target_table,
+                &item.target_key,
+            )
+            .await;
+        match result {
+            Ok(Some(id)) => {
+                tracing::info!(
+                    target: "audit",
+                    id,
+                    source_table = %item.source_table,
+                    source_key = %item.source_key,
+                    target_table = %item.target_table,
+                    target_key = %item.target_key,
+                    "Transform added"
+                );
+                HttpResponse::Ok().json(ApiResponse::<TransformId> {
+                    status: "success".to_string(),
+                    code: None,
+                    message: "Transform added successfully".to_string(),
+                    data: Some(TransformId { id }),
+                })
+            }
+            Ok(None) => {
+                HttpResponse::Conflict().json(ApiResponse::error("target_key_is_already_derived"))
+            }
+            Err(e) => {
+                log::error!("Failed to add transform: {}", e);
+                HttpResponse::InternalServerError()
+                    .json(ApiResponse::error("failed_to_add_transform"))
+            }
+        }
+    }
+
+    /// Removes a rule deriving a key. The derived key keeps its last value.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - A reference to the database wrapped in an Arc and Mutex for thread safety.
+    /// * `item` - The id of the transform.
+    ///
+    /// # Returns
+    ///
+    /// * `HttpResponse` - The HTTP response indicating success or failure.
+    async fn delete_transform(
+        db: web::Data<Arc<Mutex<Database>>>,
+        item: Json<TransformId>,
+    ) -> impl Responder {
+        match db.lock().await.delete_transform(item.id).await {
+            Ok(true) => {
+                tracing::info!(target: "audit", id = item.id, "Transform removed");
+                HttpResponse::Ok().json(ApiResponse::<()> {
+                    status: "success".to_string(),
+                    code: None,
+                    message: "Transform removed successfully".to_string(),
+                    data: None,
+                })
+            }
+            Ok(false) => HttpResponse::NotFound().json(ApiResponse::error("transform_not_found")),
+            Err(e) => {
+                log::error!("Failed to remove transform: {}", e);
+                HttpResponse::InternalServerError()
+                    .json(ApiResponse::error("failed_to_remove_transform"))
+            }
+        }
+    }
+
+    /// Mints a time-limited signed URL reading a single key without further authentication.
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - The live configuration holding the signing secret and lifetimes.
+    /// * `item` - The key to grant access to and the lifetime of the URL.
+    ///
+    /// # Returns
+    ///
+    /// * `HttpResponse` - The HTTP response containing the signed URL and its expiry.
+    async fn sign_url(
+        config: web::Data<RwLock<Config>>,
+        item: Json<SignUrlRequest>,
+    ) -> impl Responder {
+        let signed_urls = config
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .signed_urls
+            .clone();
+        if signed_urls.secret.is_empty() {
+            return HttpResponse::Forbidden()
+                .json(ApiResponse::error("signed_urls_are_not_configured"));
+        }
+        let ttl_secs = item
+            .ttl_secs
+            .unwrap_or(signed_urls.default_ttl_secs)
+            .clamp(1, signed_urls.max_ttl_secs.max(1));
+        let expires = UrlSigner::now() + ttl_secs;
+        HttpResponse::Ok().json(ApiResponse::<SignedUrl> {
+            status: "success".to_string(),
+            code: None,
+            message: "Signed URL created successfully".to_string(),
+            data: Some(SignedUrl {
+                url: UrlSigner::new(&signed_urls.secret).url(&item.table, &item.key, expires),
+                expires,
+            }),
+        })
+    }
+
+    /// Serves the raw value of a key to the holder of a signed URL.
+    ///
+    /// The signature and expiry are checked by the [`SignedUrlGuard`] middleware.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - A reference to the database wrapped in an Arc and Mutex for thread safety.
+    /// * `item` - The table and key named by the signed URL.
+    ///
+    /// # Returns
+    ///
+    /// * `HttpResponse` - The HTTP response containing the raw value.
+    async fn signed_get_data(
+        db: web::Data<Arc<Mutex<Database>>>,
+        item: web::Query<TableKey>,
+    ) -> impl Responder {
+        match db.lock().await.get_data(&item.table, &item.key).await {
+            Ok(Some(value)) => HttpResponse::Ok()
+                .content_type("text/plain; charset=utf-8")
+                .body(value),
+            Ok(None) => HttpResponse::NotFound().json(ApiResponse::error("data_not_found")),
+            Err(e) => {
+                log::error!("Failed to get data: {}", e);
+                HttpResponse::InternalServerError()
+                    .json(ApiResponse::error("failed_to_retrieve_data"))
+            }
+        }
+    }
+
+    /// Acquires or renews the lease of an editor on a key.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - A reference to the database wrapped in an Arc and Mutex for thread safety.
+    /// * `config` - The live configuration holding the lease durations.
+    /// * `item` - The key to lock and the editor locking it.
+    ///
+    /// # Returns
+    ///
+    /// * `HttpResponse` - The HTTP response containing the lease, or the lease held by another editor.
+    async fn lock(
+        db: web::Data<Arc<Mutex<Database>>>,
+        config: web::Data<RwLock<Config>>,
+        item: Json<LockRequest>,
+    ) -> impl Responder {
+        let locks = config
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .locks
+            .clone();
+        let lease_secs = item
+            .lease_secs
+            .unwrap_or(locks.default_lease_secs)
+            .clamp(1, locks.max_lease_secs.max(1));
+        let db = db.lock().await;
+        match db
+            .acquire_lock(&item.table, &item.key, &item.owner, lease_secs as i64)
+            .await
+        {
+            Ok(Ok(lock)) => HttpResponse::Ok().json(ApiResponse::<Lock> {
+                status: "success".to_string(),
+                code: None,
+                message: "Lock acquired successfully".to_string(),
+                data: Some(lock),
+            }),
+            Ok(Err(held)) => HttpResponse::Locked().json(ApiResponse::<Lock>::error_with_data(
+                "key_is_locked",
+                &[("owner", &held.owner.clone())],
+                Some(held),
+            )),
+            Err(e) => {
+                log::error!("Failed to acquire lock: {}", e);
+                HttpResponse::InternalServerError()
+                    .json(ApiResponse::error("failed_to_acquire_lock"))
+            }
+        }
+    }
+
+    /// Releases the lease of an editor on a key.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - A reference to the database wrapped in an Arc and Mutex for thread safety.
+    /// * `item` - The key to unlock and the editor holding the lease.
+    ///
+    /// # Returns
+    ///
+    /// * `HttpResponse` - The HTTP response indicating success or failure.
+    async fn unlock(
+        db: web::Data<Arc<Mutex<Database>>>,
+        item: Json<UnlockRequest>,
+    ) -> impl Responder {
+        let db = db.lock().await;
+        match db.release_lock(&item.table, &item.key, &item.owner).await {
+            Ok(true) => HttpResponse::Ok().json(ApiResponse::<()> {
+                status: "success".to_string(),
+                code: None,
+                message: "Lock released successfully".to_string(),
+                data: None,
+            }),
+            Ok(false) => {
+                HttpResponse::Conflict().json(ApiResponse::error("lock_is_not_held_by_this_owner"))
+            }
+            Err(e) => {
+                log::error!("Failed to release lock: {}", e);
+                HttpResponse::InternalServerError()
+                    .json(ApiResponse::error("failed_to_release_lock"))
+            }
+        }
+    }
+
+    /// Retrieves the lease on a key.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - A reference to the database wrapped in an Arc and Mutex for thread safety.
+    /// * `item` - The key whose lease to retrieve.
+    ///
+    /// # Returns
+    ///
+    /// * `HttpResponse` - The HTTP response containing the lease or an error message.
+    async fn get_lock(
+        db: web::Data<Arc<Mutex<Database>>>,
+        item: web::Query<TableKey>,
+    ) -> impl Responder {
+        let db = db.lock().await;
+        match db.get_lock(&item.table, &item.key).await {
+            Ok(Some(lock)) => HttpResponse::Ok().json(ApiResponse::<Lock> {
+                status: "success".to_string(),
+                code: None,
+                message: "Lock retrieved successfully".to_string(),
+                data: Some(lock),
+            }),
+            Ok(None) => HttpResponse::NotFound().json(ApiResponse::error("key_is_not_locked")),
+            Err(e) => {
+                log::error!("Failed to get lock: {}", e);
+                HttpResponse::InternalServerError()
+                    .json(ApiResponse::error("failed_to_retrieve_lock"))
+            }
+        }
+    }
+
+    /// Retrieves the recorded versions of a key, newest first.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - A reference to the database wrapped in an Arc and Mutex for thread safety.
+    /// * `item` - The key whose versions to retrieve.
+    ///
+    /// # Returns
+    ///
+    /// * `HttpResponse` - The HTTP response containing the versions or an error message.
+    async fn history(
+        db: web::Data<Arc<Mutex<Database>>>,
+        item: web::Query<HistoryQuery>,
+    ) -> impl Responder {
+        let db = db.lock().await;
+        match db.history(&item.table, &item.key, item.limit).await {
+            Ok(entries) => HttpResponse::Ok().json(ApiResponse::<Vec<HistoryEntry>> {
+                status: "success".to_string(),
+                code: None,
+                message: "History retrieved successfully".to_string(),
+                data: Some(entries),
+            }),
+            Err(e) => {
+                log::error!("Failed to get history: {}", e);
+                HttpResponse::InternalServerError()
+                    .json(ApiResponse::error("failed_to_retrieve_history"))
+            }
+        }
+    }
+
+    /// Collapses the versions of the keys of a table older than the given or configured age,
+    /// keeping the versions current at the snapshots and at the start of the window.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - A reference to the database wrapped in an Arc and Mutex for thread safety.
+    /// * `config` - The live configuration holding the compaction ages.
+    /// * `item` - The table and the age beyond which versions are collapsed.
+    ///
+    /// # Returns
+    ///
+    /// * `HttpResponse` - The HTTP response containing the number of removed versions.
+    async fn compact_history(
+        db: web::Data<Arc<Mutex<Database>>>,
+        config: web::Data<RwLock<Config>>,
+        item: Json<HistoryCompaction>,
+    ) -> impl Responder {
+        let max_age_secs = item.max_age_secs.or_else(|| {
+            config
+                .read()
+                .unwrap_or_else(PoisonError::into_inner)
+                .history
+                .compact_after(&item.table)
+        });
+        let Some(max_age_secs) = max_age_secs else {
+            return HttpResponse::BadRequest().json(ApiResponse::error(
+                "no_compaction_age_is_configured_for_the_table",
+            ));
+        };
+        match db
+            .lock()
+            .await
+            .compact_history(&item.table, max_age_secs as i64)
+            .await
+        {
+            Ok(count) => {
+                tracing::info!(target: "audit", table = %item.table, removed = count, "History compacted");
+                HttpResponse::Ok().json(ApiResponse::<u64> {
+                    status: "success".to_string(),
+                    code: None,
+                    message: "History compacted successfully".to_string(),
+                    data: Some(count),
+                })
+            }
+            Err(e) => {
+                log::error!("Failed to compact history: {}", e);
+                Self::database_error(&e, "failed_to_compact_history")
+            }
+        }
+    }
+
+    /// Retrieves the value a key had at the given point in time.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - A reference to the database wrapped in an Arc and Mutex for thread safety.
+    /// * `item` - The key and the unix timestamp in seconds.
+    ///
+    /// # Returns
+    ///
+    /// * `HttpResponse` - The HTTP response containing the data or an error message.
+    async fn get_data_at(
+        db: web::Data<Arc<Mutex<Database>>>,
+        item: web::Query<TableKeyAt>,
+    ) -> impl Responder {
+        let db = db.lock().await;
+        let value_type = match db.get_value_type(&item.table).await {
+            Ok(value_type) => value_type,
+            Err(e) => {
+                log::error!("Failed to get data: {}", e);
+                return HttpResponse::InternalServerError()
+                    .json(ApiResponse::error("failed_to_retrieve_data"));
+            }
+        };
+        match db.get_data_at(&item.table, &item.key, item.timestamp).await {
+            Ok(Some(value)) => HttpResponse::Ok().json(ApiResponse::<serde_json::Value> {
+                status: "success".to_string(),
+                code: None,
+                message: "Data retrieved successfully".to_string(),
+                data: Some(value_type.to_json(&value)),
+            }),
+            Ok(None) => HttpResponse::NotFound().json(ApiResponse::error("data_not_found")),
+            Err(e) => {
+                log::error!("Failed to get data: {}", e);
+                HttpResponse::InternalServerError()
+                    .json(ApiResponse::error("failed_to_retrieve_data"))
+            }
+        }
+    }
+
+    /// Retrieves the most recent queries that exceeded the slow query threshold.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - A reference to the database wrapped in an Arc and Mutex for thread safety.
+    /// * `item` - The optional table to filter by.
+    ///
+    /// # Returns
+    ///
+    /// * `HttpResponse` - The HTTP response containing the slow queries, newest first.
+    async fn slow_queries(
+        db: web::Data<Arc<Mutex<Database>>>,
+        item: web::Query<SlowQueryFilter>,
+    ) -> impl Responder {
+        let db = db.lock().await;
+        HttpResponse::Ok().json(ApiResponse::<Vec<SlowQuery>> {
+            status: "success".to_string(),
+            code: None,
+            message: "Slow queries retrieved successfully".to_string(),
+            data: Some(db.slow_queries(item.table.as_deref())),
+        })
+    }
+
+    /// Recommends indexes for the queries recorded as slow repeatedly.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - A reference to the database wrapped in an Arc and Mutex for thread safety.
+    /// * `config` - The live configuration of the server.
+    ///
+    /// # Returns
+    ///
+    /// * `HttpResponse` - The HTTP response containing the recommendations, most time consuming
+    ///   first.
+    async fn index_advisor(
+        db: web::Data<Arc<Mutex<Database>>>,
+        config: web::Data<RwLock<Config>>,
+    ) -> impl Responder {
+        let min_occurrences = config
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .index_advisor
+            .min_occurrences;
+        match db.lock().await.index_recommendations(min_occurrences).await {
+            Ok(recommendations) => {
+                HttpResponse::Ok().json(ApiResponse::<Vec<IndexRecommendation>> {
+                    status: "success".to_string(),
+                    code: None,
+                    message: "Index recommendations retrieved successfully".to_string(),
+                    data: Some(recommendations),
+                })
+            }
+            Err(e) => {
+                log::error!("Failed to recommend indexes: {}", e);
+                HttpResponse::InternalServerError()
+                    .json(ApiResponse::error("failed_to_recommend_indexes"))
+            }
+        }
+    }
+}
@@ -0,0 +1,64 @@
+use std::collections::BTreeMap;
+
+use sha2::{Digest, Sha256};
+
+/// The number of points each shard occupies on the ring, smoothing the distribution.
+const VIRTUAL_NODES: usize = 64;
+
+/// A struct mapping tables to shards by consistent hashing.
+///
+/// Every shard is placed on a hash ring at several points derived from its URL,
+/// and a table belongs to the first shard found clockwise from the hash of its
+/// name. Adding or removing a shard therefore only moves the tables between it
+/// and its neighbours.
+pub struct ShardRing {
+    ring: BTreeMap<u64, usize>,
+}
+
+/// Implementation of the `ShardRing` struct.
+impl ShardRing {
+    /// Creates a new ring for the given shards.
+    ///
+    /// # Arguments
+    ///
+    /// * `shards` - The identities of the shards, usually their URLs, in index order.
+    ///
+    /// # Returns
+    ///
+    /// * `ShardRing` - A new instance of the ShardRing.
+    pub fn new<S: AsRef<str>>(shards: &[S]) -> Self {
+        let mut ring = BTreeMap::new();
+        for (index, shard) in shards.iter().enumerate() {
+            for node in 0..VIRTUAL_NODES {
+                ring.insert(Self::hash(&format!("{}#{}", shard.as_ref(), node)), index);
+            }
+        }
+        ShardRing { ring }
+    }
+
+    /// Returns the index of the shard responsible for the given table.
+    ///
+    /// # Arguments
+    ///
+    /// * `table` - The name of the table.
+    ///
+    /// # Returns
+    ///
+    /// * `usize` - The index of the shard, `0` if the ring is empty.
+    pub fn shard_for(&self, table: &str) -> usize {
+        let hash = Self::hash(table);
+        self.ring
+            .range(hash..)
+            .next()
+            .or_else(|| self.ring.iter().next())
+            .map_or(0, |(_, index)| *index)
+    }
+
+    /// Hashes a value onto the ring.
+    fn hash(value: &str) -> u64 {
+        let digest = Sha256::digest(value.as_bytes());
+        let mut bytes = [0u8; 8];
+        bytes.copy_from_slice(&digest[..8]);
+        u64::from_be_bytes(bytes)
+    }
+}
@@ -0,0 +1,229 @@
+use std::collections::HashMap;
+use std::sync::{Mutex, PoisonError};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::config::RequestSigningConfig;
+
+/// The path prefix of the routes only reachable through signed URLs.
+pub const SIGNED_PREFIX: &str = "/signed/";
+
+/// The header identifying the client that signed a request.
+pub const CLIENT_HEADER: &str = "X-xCLOUD-Client";
+
+/// The header holding the unix timestamp in seconds at which a request was signed.
+pub const TIMESTAMP_HEADER: &str = "X-xCLOUD-Timestamp";
+
+/// The header holding the hex encoded HMAC-SHA256 signature of a request.
+pub const REQUEST_SIGNATURE_HEADER: &str = "X-xCLOUD-Request-Signature";
+
+/// A struct representing the query of a signed URL.
+#[derive(Serialize, Deserialize)]
+pub struct SignedQuery {
+    pub table: String,
+    pub key: String,
+    /// The unix timestamp in seconds after which the URL is rejected.
+    pub expires: u64,
+    /// The hex encoded HMAC-SHA256 of the table, key and expiry.
+    pub signature: String,
+}
+
+/// A struct minting and verifying time-limited URLs granting read access to a single key.
+pub struct UrlSigner {
+    secret: String,
+}
+
+/// Implementation of the `UrlSigner` struct.
+impl UrlSigner {
+    /// Creates a new instance of the UrlSigner.
+    ///
+    /// # Arguments
+    ///
+    /// * `secret` - The secret the signatures are derived from.
+    ///
+    /// # Returns
+    ///
+    /// * `UrlSigner` - A new instance of the UrlSigner.
+    pub fn new(secret: &str) -> Self {
+        UrlSigner {
+            secret: secret.to_string(),
+        }
+    }
+
+    /// Mints a signed URL reading a key until the given time.
+    ///
+    /// # Arguments
+    ///
+    /// * `table` - The table holding the key.
+    /// * `key` - The key that may be read.
+    /// * `expires` - The unix timestamp in seconds after which the URL is rejected.
+    ///
+    /// # Returns
+    ///
+    /// * `String` - The path and query of the signed URL.
+    pub fn url(&self, table: &str, key: &str, expires: u64) -> String {
+        let query = SignedQuery {
+            table: table.to_string(),
+            key: key.to_string(),
+            expires,
+            signature: hex::encode(self.mac(table, key, expires).finalize().into_bytes()),
+        };
+        format!(
+            "{}get_data?{}",
+            SIGNED_PREFIX,
+            serde_urlencoded::to_string(&query).unwrap_or_default()
+        )
+    }
+
+    /// Verifies the signature and expiry of a signed URL.
+    ///
+    /// # Arguments
+    ///
+    /// * `query` - The query of the signed URL.
+    ///
+    /// # Returns
+    ///
+    /// * `bool` - Whether the URL is authentic and not yet expired.
+    pub fn verify(&self, query: &SignedQuery) -> bool {
+        if query.expires < Self::now() {
+            return false;
+        }
+        let Ok(signature) = hex::decode(&query.signature) else {
+            return false;
+        };
+        self.mac(&query.table, &query.key, query.expires)
+            .verify_slice(&signature)
+            .is_ok()
+    }
+
+    /// Returns the current unix timestamp in seconds.
+    ///
+    /// # Returns
+    ///
+    /// * `u64` - The current unix timestamp.
+    pub fn now() -> u64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default()
+    }
+
+    /// Computes the MAC over the signed fields, encoded so no two field combinations collide.
+    fn mac(&self, table: &str, key: &str, expires: u64) -> Hmac<Sha256> {
+        let mut mac = Hmac::<Sha256>::new_from_slice(self.secret.as_bytes())
+            .expect("HMAC accepts keys of any size");
+        mac.update(
+            serde_json::to_string(&(table, key, expires))
+                .unwrap_or_default()
+                .as_bytes(),
+        );
+        mac
+    }
+}
+
+/// A struct representing the signed parts of a request.
+pub struct SignedRequest<'a> {
+    pub method: &'a str,
+    /// The path and the query string, if any.
+    pub path: &'a str,
+    pub timestamp: u64,
+    pub body: &'a [u8],
+}
+
+/// Implementation of the `SignedRequest` struct.
+impl SignedRequest<'_> {
+    /// Signs the request with a client secret.
+    ///
+    /// The signature is the HMAC-SHA256 of the method, the path with the query, the timestamp
+    /// and the hex encoded SHA-256 of the body, each on its own line.
+    ///
+    /// # Arguments
+    ///
+    /// * `secret` - The secret of the client.
+    ///
+    /// # Returns
+    ///
+    /// * `String` - The hex encoded signature.
+    pub fn sign(&self, secret: &str) -> String {
+        hex::encode(self.mac(secret).finalize().into_bytes())
+    }
+
+    /// Computes the MAC over the signed parts.
+    fn mac(&self, secret: &str) -> Hmac<Sha256> {
+        let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes())
+            .expect("HMAC accepts keys of any size");
+        mac.update(
+            format!(
+                "{}\n{}\n{}\n{}",
+                self.method,
+                self.path,
+                self.timestamp,
+                hex::encode(Sha256::digest(self.body))
+            )
+            .as_bytes(),
+        );
+        mac
+    }
+}
+
+/// A struct verifying the signatures of server-to-server requests and rejecting replays.
+///
+/// A signature is accepted once within the allowed clock skew. Signatures are remembered until
+/// their timestamp leaves that window, after which the request is rejected as stale anyway.
+#[derive(Default)]
+pub struct RequestVerifier {
+    /// The signatures accepted, with the unix timestamp in seconds until which they are kept.
+    seen: Mutex<HashMap<String, u64>>,
+}
+
+/// Implementation of the `RequestVerifier` struct.
+impl RequestVerifier {
+    /// Verifies the signature of a request and records it.
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - The request signing configuration holding the client secrets.
+    /// * `client` - The id of the client that signed the request.
+    /// * `signature` - The hex encoded signature of the request.
+    /// * `request` - The signed parts of the request.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(), &str>` - Whether the request is authentic, fresh and not replayed, or the
+    ///   error code of the reason it is rejected.
+    pub fn verify(
+        &self,
+        config: &RequestSigningConfig,
+        client: &str,
+        signature: &str,
+        request: &SignedRequest,
+    ) -> Result<(), &'static str> {
+        let Some(secret) = config.clients.get(client) else {
+            return Err("unknown_client");
+        };
+        let now = UrlSigner::now();
+        if now.abs_diff(request.timestamp) > config.max_skew_secs {
+            return Err("request_timestamp_is_outside_the_allowed_window");
+        }
+        let valid = hex::decode(signature)
+            .is_ok_and(|signature| request.mac(secret).verify_slice(&signature).is_ok());
+        if !valid {
+            return Err("invalid_request_signature");
+        }
+        let mut seen = self.seen.lock().unwrap_or_else(PoisonError::into_inner);
+        seen.retain(|_, until| *until >= now);
+        if seen
+            .insert(
+                signature.to_ascii_lowercase(),
+                request.timestamp + config.max_skew_secs,
+            )
+            .is_some()
+        {
+            return Err("request_was_already_received");
+        }
+        Ok(())
+    }
+}
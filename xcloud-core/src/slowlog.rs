@@ -0,0 +1,124 @@
+use std::collections::VecDeque;
+use std::sync::{Mutex, PoisonError};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::SlowQueryConfig;
+
+/// A struct representing a query that exceeded the slow query threshold.
+#[derive(Serialize, Deserialize, Clone, Debug, sqlx::FromRow)]
+pub struct SlowQuery {
+    pub table: String,
+    pub operation: String,
+    pub duration_ms: i64,
+    /// The statement with placeholders instead of values.
+    pub sql: String,
+    /// The unix timestamp in seconds at which the query finished.
+    pub timestamp: i64,
+}
+
+/// A struct keeping the most recent slow queries in a ring buffer.
+pub struct SlowQueryLog {
+    config: SlowQueryConfig,
+    entries: Mutex<VecDeque<SlowQuery>>,
+}
+
+/// Implementation of the `SlowQueryLog` struct.
+impl SlowQueryLog {
+    /// Creates a new, empty slow query log.
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - The slow query configuration holding the threshold and capacity.
+    ///
+    /// # Returns
+    ///
+    /// * `SlowQueryLog` - A new instance of the SlowQueryLog.
+    pub fn new(config: SlowQueryConfig) -> Self {
+        SlowQueryLog {
+            entries: Mutex::new(VecDeque::with_capacity(config.capacity)),
+            config,
+        }
+    }
+
+    /// Records a query if it exceeded the threshold.
+    ///
+    /// # Arguments
+    ///
+    /// * `table` - The table the query ran against.
+    /// * `operation` - The database operation that ran the query.
+    /// * `sql` - The statement with placeholders instead of values.
+    /// * `duration` - The time the query took.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<SlowQuery>` - The recorded query, or `None` if it was fast enough.
+    pub fn record(
+        &self,
+        table: &str,
+        operation: &str,
+        sql: &str,
+        duration: Duration,
+    ) -> Option<SlowQuery> {
+        if duration < Duration::from_millis(self.config.threshold_ms) {
+            return None;
+        }
+        let query = SlowQuery {
+            table: table.to_string(),
+            operation: operation.to_string(),
+            duration_ms: duration.as_millis() as i64,
+            sql: sql.split_whitespace().collect::<Vec<_>>().join(" "),
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs() as i64)
+                .unwrap_or_default(),
+        };
+        tracing::warn!(
+            table = %query.table,
+            operation = %query.operation,
+            duration_ms = query.duration_ms,
+            "Slow query"
+        );
+        if self.config.capacity > 0 {
+            let mut entries = self.entries.lock().unwrap_or_else(PoisonError::into_inner);
+            if entries.len() == self.config.capacity {
+                entries.pop_front();
+            }
+            entries.push_back(query.clone());
+        }
+        Some(query)
+    }
+
+    /// Returns whether slow queries are also persisted to the database.
+    ///
+    /// # Returns
+    ///
+    /// * `bool` - Whether slow queries are persisted.
+    pub fn persists(&self) -> bool {
+        self.config.persist
+    }
+
+    /// Returns the recorded slow queries, newest first.
+    ///
+    /// # Arguments
+    ///
+    /// * `table` - The table to filter by, or `None` for all tables.
+    ///
+    /// # Returns
+    ///
+    /// * `Vec<SlowQuery>` - The recorded slow queries.
+    pub fn entries(&self, table: Option<&str>) -> Vec<SlowQuery> {
+        self.entries
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .iter()
+            .rev()
+            .filter(|query| match table {
+                Some(table) => query.table == table,
+                None => true,
+            })
+            .cloned()
+            .collect()
+    }
+}
@@ -0,0 +1,80 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::sync::Mutex;
+
+use crate::cluster::Cluster;
+use crate::config::SnapshotConfig;
+use crate::db::Database;
+
+/// A struct periodically capturing snapshots of the tables.
+pub struct SnapshotScheduler {
+    db: Arc<Mutex<Database>>,
+    config: SnapshotConfig,
+}
+
+/// Implementation of the `SnapshotScheduler` struct.
+impl SnapshotScheduler {
+    /// Creates a new instance of the SnapshotScheduler.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - A reference to the database wrapped in an Arc and Mutex for thread safety.
+    /// * `config` - The snapshot configuration holding the interval and the captured tables.
+    ///
+    /// # Returns
+    ///
+    /// * `SnapshotScheduler` - A new instance of the SnapshotScheduler.
+    pub fn new(db: Arc<Mutex<Database>>, config: SnapshotConfig) -> Self {
+        SnapshotScheduler { db, config }
+    }
+
+    /// Captures snapshots in the background at the configured interval, if enabled, while the
+    /// instance leads the cluster.
+    ///
+    /// # Arguments
+    ///
+    /// * `cluster` - The cluster electing the instance running the background jobs.
+    pub fn spawn(self, cluster: Arc<Cluster>) {
+        if self.config.interval_secs == 0 {
+            return;
+        }
+        tokio::spawn(async move {
+            let mut interval =
+                tokio::time::interval(Duration::from_secs(self.config.interval_secs));
+            interval.tick().await;
+            loop {
+                interval.tick().await;
+                if !cluster.is_leader() {
+                    continue;
+                }
+                if let Err(e) = self.capture().await {
+                    log::error!("Failed to capture scheduled snapshot: {}", e);
+                }
+            }
+        });
+    }
+
+    /// Captures a snapshot and deletes the scheduled snapshots exceeding the configured number.
+    ///
+    /// # Returns
+    ///
+    /// * `i64` - The id of the captured snapshot.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the snapshot cannot be captured.
+    pub async fn capture(&self) -> Result<i64, sqlx::Error> {
+        let db = self.db.lock().await;
+        let snapshot = db.create_snapshot(&self.config.tables, true).await?;
+        let pruned = db.prune_snapshots(self.config.keep.max(1)).await?;
+        tracing::info!(
+            target: "audit",
+            snapshot = snapshot.id,
+            keys = snapshot.keys,
+            pruned,
+            "Captured scheduled snapshot"
+        );
+        Ok(snapshot.id)
+    }
+}
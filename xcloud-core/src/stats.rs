@@ -0,0 +1,209 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex, PoisonError};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+/// A struct representing the number of served requests per status class.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct RequestCounts {
+    pub informational: u64,
+    pub success: u64,
+    pub redirection: u64,
+    pub client_error: u64,
+    pub server_error: u64,
+}
+
+/// The header of the W3C Trace Context carrying the trace of a request.
+pub const TRACEPARENT_HEADER: &str = "traceparent";
+
+/// The upper bounds in seconds of the buckets of the request latency histogram, the last
+/// bucket counting every request.
+pub const LATENCY_BUCKETS: [f64; 11] = [
+    0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0,
+];
+
+/// A struct representing a traced request observed in a bucket of the latency histogram.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Exemplar {
+    /// The trace id of the request, as 32 lowercase hexadecimal digits.
+    pub trace_id: String,
+    /// The latency of the request in seconds.
+    pub value: f64,
+    /// The unix timestamp in seconds at which the request completed.
+    pub timestamp: f64,
+}
+
+/// Implementation of the `Exemplar` struct.
+impl Exemplar {
+    /// Extracts the trace id of a `traceparent` header.
+    ///
+    /// # Arguments
+    ///
+    /// * `traceparent` - The header, e.g. `00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01`.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<String>` - The trace id, or `None` if the header is malformed or names the
+    ///   invalid all-zero trace.
+    pub fn trace_id(traceparent: &str) -> Option<String> {
+        let mut parts = traceparent.trim().split('-');
+        let version = parts.next()?;
+        let trace_id = parts.next()?.to_ascii_lowercase();
+        let parent_id = parts.next()?;
+        let flags = parts.next()?;
+        let hex = |part: &str, len: usize| {
+            part.len() == len && part.bytes().all(|byte| byte.is_ascii_hexdigit())
+        };
+        (hex(version, 2)
+            && version != "ff"
+            && hex(&trace_id, 32)
+            && hex(parent_id, 16)
+            && hex(flags, 2)
+            && trace_id.bytes().any(|byte| byte != b'0'))
+        .then_some(trace_id)
+    }
+}
+
+/// A struct representing the request latency histogram, its bucket counts being cumulative.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct LatencyHistogram {
+    /// The upper bound in seconds, the number of requests at or below it and the most recent
+    /// traced request falling in the bucket, ending with the bucket of every request.
+    pub buckets: Vec<(f64, u64, Option<Exemplar>)>,
+    /// The number of requests observed.
+    pub count: u64,
+    /// The total latency of the requests in seconds.
+    pub sum: f64,
+}
+
+/// A struct representing the observations of the latency histogram, per bucket.
+#[derive(Default)]
+struct LatencyState {
+    counts: [u64; LATENCY_BUCKETS.len() + 1],
+    exemplars: [Option<Exemplar>; LATENCY_BUCKETS.len() + 1],
+    sum: f64,
+}
+
+/// A struct counting the requests served since the server started.
+pub struct RequestStats {
+    started: Instant,
+    /// The counters of the `1xx` to `5xx` status classes.
+    classes: [AtomicU64; 5],
+    /// The latency of the served requests.
+    latency: Mutex<LatencyState>,
+}
+
+/// Implementation of the `RequestStats` struct.
+impl RequestStats {
+    /// Creates new counters, starting the uptime now.
+    ///
+    /// # Returns
+    ///
+    /// * `RequestStats` - A new instance of the RequestStats.
+    pub fn new() -> Self {
+        RequestStats {
+            started: Instant::now(),
+            classes: Default::default(),
+            latency: Mutex::new(LatencyState::default()),
+        }
+    }
+
+    /// Counts a served request.
+    ///
+    /// # Arguments
+    ///
+    /// * `status` - The status code of the response.
+    pub fn record(&self, status: u16) {
+        if let Some(counter) = (status / 100)
+            .checked_sub(1)
+            .and_then(|class| self.classes.get(class as usize))
+        {
+            counter.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    /// Observes the latency of a served request, keeping it as the exemplar of its bucket if
+    /// the request was traced.
+    ///
+    /// # Arguments
+    ///
+    /// * `elapsed` - The time the request took.
+    /// * `trace_id` - The trace id of the request, if any.
+    pub fn record_latency(&self, elapsed: Duration, trace_id: Option<String>) {
+        let value = elapsed.as_secs_f64();
+        let bucket = LATENCY_BUCKETS
+            .iter()
+            .position(|bound| value <= *bound)
+            .unwrap_or(LATENCY_BUCKETS.len());
+        let mut state = self.latency.lock().unwrap_or_else(PoisonError::into_inner);
+        state.counts[bucket] += 1;
+        state.sum += value;
+        if let Some(trace_id) = trace_id {
+            state.exemplars[bucket] = Some(Exemplar {
+                trace_id,
+                value,
+                timestamp: SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .map(|d| d.as_secs_f64())
+                    .unwrap_or_default(),
+            });
+        }
+    }
+
+    /// Returns the latency histogram of the served requests.
+    ///
+    /// # Returns
+    ///
+    /// * `LatencyHistogram` - The cumulative bucket counts and their exemplars.
+    pub fn latency(&self) -> LatencyHistogram {
+        let state = self.latency.lock().unwrap_or_else(PoisonError::into_inner);
+        let mut count = 0;
+        let buckets = LATENCY_BUCKETS
+            .iter()
+            .copied()
+            .chain([f64::INFINITY])
+            .zip(state.counts.iter().zip(&state.exemplars))
+            .map(|(bound, (observed, exemplar))| {
+                count += observed;
+                (bound, count, exemplar.clone())
+            })
+            .collect();
+        LatencyHistogram {
+            buckets,
+            count,
+            sum: state.sum,
+        }
+    }
+
+    /// Returns the number of seconds since the server started.
+    ///
+    /// # Returns
+    ///
+    /// * `u64` - The uptime in seconds.
+    pub fn uptime_secs(&self) -> u64 {
+        self.started.elapsed().as_secs()
+    }
+
+    /// Returns the number of served requests per status class.
+    ///
+    /// # Returns
+    ///
+    /// * `RequestCounts` - The current counters.
+    pub fn counts(&self) -> RequestCounts {
+        let count = |class: usize| self.classes[class].load(Ordering::Relaxed);
+        RequestCounts {
+            informational: count(0),
+            success: count(1),
+            redirection: count(2),
+            client_error: count(3),
+            server_error: count(4),
+        }
+    }
+}
+
+impl Default for RequestStats {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,312 @@
+use std::collections::{BTreeMap, HashMap};
+
+use async_trait::async_trait;
+use tokio::sync::RwLock;
+
+use crate::db::Database;
+use crate::utils::Utils;
+
+/// A trait representing a backend storing the key-value pairs of the tables.
+///
+/// The [`Database`] stores them in SQLite, the [`MemoryStorage`] keeps them in memory.
+#[async_trait]
+pub trait Storage: Send + Sync {
+    /// Sets the value of a key, replacing any previous value.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the value cannot be stored.
+    async fn set_data(&self, table: &str, key: &str, value: &str) -> Result<(), sqlx::Error>;
+
+    /// Updates the value of a key, doing nothing if the key does not exist.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the value cannot be stored.
+    async fn update_data(&self, table: &str, key: &str, value: &str) -> Result<(), sqlx::Error>;
+
+    /// Gets the value of a key.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the value cannot be retrieved.
+    async fn get_data(&self, table: &str, key: &str) -> Result<Option<String>, sqlx::Error>;
+
+    /// Gets the values of many keys, missing keys are omitted.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the values cannot be retrieved.
+    async fn get_many(
+        &self,
+        table: &str,
+        keys: &[String],
+    ) -> Result<Vec<(String, String)>, sqlx::Error>;
+
+    /// Increments the integer value of a key, creating it at zero if missing.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<i64>` - The new value, or `None` if the current value is not an integer.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an `InvalidArgument` error if the new value would overflow a
+    /// 64-bit integer, and any other error if the value cannot be incremented.
+    async fn increment(
+        &self,
+        table: &str,
+        key: &str,
+        delta: i64,
+    ) -> Result<Option<i64>, sqlx::Error>;
+
+    /// Sets the value of a key only if its current value is the expected one.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(), Option<String>>` - Nothing if the value was set, or the current value if it
+    ///   did not match.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the value cannot be compared or set.
+    async fn compare_and_set(
+        &self,
+        table: &str,
+        key: &str,
+        expected: Option<&str>,
+        value: &str,
+    ) -> Result<Result<(), Option<String>>, sqlx::Error>;
+
+    /// Deletes a key.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the key cannot be deleted.
+    async fn delete_data(&self, table: &str, key: &str) -> Result<(), sqlx::Error>;
+
+    /// Deletes a table with all its keys.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the table cannot be deleted.
+    async fn delete_table(&self, table: &str) -> Result<(), sqlx::Error>;
+
+    /// Lists the names of all tables in ascending order.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the tables cannot be listed.
+    async fn list_tables(&self) -> Result<Vec<String>, sqlx::Error>;
+
+    /// Lists the keys of a table in ascending order.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the keys cannot be listed.
+    async fn list_keys(
+        &self,
+        table: &str,
+        offset: i64,
+        limit: Option<i64>,
+    ) -> Result<Vec<String>, sqlx::Error>;
+}
+
+/// A struct storing the tables in memory, losing them when the server stops.
+///
+/// Table names are sanitized like their SQLite counterparts, so both backends agree on them.
+#[derive(Default)]
+pub struct MemoryStorage {
+    tables: RwLock<HashMap<String, BTreeMap<String, String>>>,
+}
+
+#[async_trait]
+impl Storage for MemoryStorage {
+    async fn set_data(&self, table: &str, key: &str, value: &str) -> Result<(), sqlx::Error> {
+        self.tables
+            .write()
+            .await
+            .entry(Utils::sanitize(table))
+            .or_default()
+            .insert(key.to_string(), value.to_string());
+        Ok(())
+    }
+
+    async fn update_data(&self, table: &str, key: &str, value: &str) -> Result<(), sqlx::Error> {
+        if let Some(current) = self
+            .tables
+            .write()
+            .await
+            .get_mut(&Utils::sanitize(table))
+            .and_then(|keys| keys.get_mut(key))
+        {
+            *current = value.to_string();
+        }
+        Ok(())
+    }
+
+    async fn get_data(&self, table: &str, key: &str) -> Result<Option<String>, sqlx::Error> {
+        Ok(self
+            .tables
+            .read()
+            .await
+            .get(&Utils::sanitize(table))
+            .and_then(|keys| keys.get(key))
+            .cloned())
+    }
+
+    async fn get_many(
+        &self,
+        table: &str,
+        keys: &[String],
+    ) -> Result<Vec<(String, String)>, sqlx::Error> {
+        let tables = self.tables.read().await;
+        let Some(stored) = tables.get(&Utils::sanitize(table)) else {
+            return Ok(Vec::new());
+        };
+        Ok(keys
+            .iter()
+            .filter_map(|key| stored.get(key).map(|value| (key.clone(), value.clone())))
+            .collect())
+    }
+
+    async fn increment(
+        &self,
+        table: &str,
+        key: &str,
+        delta: i64,
+    ) -> Result<Option<i64>, sqlx::Error> {
+        let mut tables = self.tables.write().await;
+        let keys = tables.entry(Utils::sanitize(table)).or_default();
+        let current = match keys.get(key) {
+            Some(value) => match value.parse::<i64>() {
+                Ok(current) if current.to_string() == *value => current,
+                _ => return Ok(None),
+            },
+            None => 0,
+        };
+        let value = current
+            .checked_add(delta)
+            .ok_or_else(|| Database::overflow(delta))?;
+        keys.insert(key.to_string(), value.to_string());
+        Ok(Some(value))
+    }
+
+    async fn compare_and_set(
+        &self,
+        table: &str,
+        key: &str,
+        expected: Option<&str>,
+        value: &str,
+    ) -> Result<Result<(), Option<String>>, sqlx::Error> {
+        let mut tables = self.tables.write().await;
+        let table = Utils::sanitize(table);
+        let current = tables.get(&table).and_then(|keys| keys.get(key));
+        if current.map(String::as_str) != expected {
+            return Ok(Err(current.cloned()));
+        }
+        tables
+            .entry(table)
+            .or_default()
+            .insert(key.to_string(), value.to_string());
+        Ok(Ok(()))
+    }
+
+    async fn delete_data(&self, table: &str, key: &str) -> Result<(), sqlx::Error> {
+        if let Some(keys) = self.tables.write().await.get_mut(&Utils::sanitize(table)) {
+            keys.remove(key);
+        }
+        Ok(())
+    }
+
+    async fn delete_table(&self, table: &str) -> Result<(), sqlx::Error> {
+        self.tables.write().await.remove(&Utils::sanitize(table));
+        Ok(())
+    }
+
+    async fn list_tables(&self) -> Result<Vec<String>, sqlx::Error> {
+        let mut tables: Vec<String> = self.tables.read().await.keys().cloned().collect();
+        tables.sort();
+        Ok(tables)
+    }
+
+    async fn list_keys(
+        &self,
+        table: &str,
+        offset: i64,
+        limit: Option<i64>,
+    ) -> Result<Vec<String>, sqlx::Error> {
+        let tables = self.tables.read().await;
+        let Some(keys) = tables.get(&Utils::sanitize(table)) else {
+            return Ok(Vec::new());
+        };
+        let keys = keys.keys().skip(offset.max(0) as usize).cloned();
+        Ok(match limit {
+            Some(limit) => keys.take(limit.max(0) as usize).collect(),
+            None => keys.collect(),
+        })
+    }
+}
+
+#[async_trait]
+impl Storage for Database {
+    async fn set_data(&self, table: &str, key: &str, value: &str) -> Result<(), sqlx::Error> {
+        Database::set_data(self, table, key, value).await
+    }
+
+    async fn update_data(&self, table: &str, key: &str, value: &str) -> Result<(), sqlx::Error> {
+        Database::update_data(self, table, key, value).await
+    }
+
+    async fn get_data(&self, table: &str, key: &str) -> Result<Option<String>, sqlx::Error> {
+        Database::get_data(self, table, key).await
+    }
+
+    async fn get_many(
+        &self,
+        table: &str,
+        keys: &[String],
+    ) -> Result<Vec<(String, String)>, sqlx::Error> {
+        Database::get_many(self, table, keys).await
+    }
+
+    async fn increment(
+        &self,
+        table: &str,
+        key: &str,
+        delta: i64,
+    ) -> Result<Option<i64>, sqlx::Error> {
+        Database::increment(self, table, key, delta).await
+    }
+
+    async fn compare_and_set(
+        &self,
+        table: &str,
+        key: &str,
+        expected: Option<&str>,
+        value: &str,
+    ) -> Result<Result<(), Option<String>>, sqlx::Error> {
+        Database::compare_and_set(self, table, key, expected, value).await
+    }
+
+    async fn delete_data(&self, table: &str, key: &str) -> Result<(), sqlx::Error> {
+        Database::delete_data(self, table, key).await
+    }
+
+    async fn delete_table(&self, table: &str) -> Result<(), sqlx::Error> {
+        Database::delete_table(self, table).await
+    }
+
+    async fn list_tables(&self) -> Result<Vec<String>, sqlx::Error> {
+        Database::list_tables(self).await
+    }
+
+    async fn list_keys(
+        &self,
+        table: &str,
+        offset: i64,
+        limit: Option<i64>,
+    ) -> Result<Vec<String>, sqlx::Error> {
+        Database::list_keys(self, table, offset, limit).await
+    }
+}
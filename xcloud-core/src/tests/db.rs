@@ -0,0 +1,3232 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+
+use crate::alerts::{AlertKind, Alerter};
+use crate::bench::{BenchOptions, KeyDistribution, LatencySummary};
+use crate::blobs::{BlobStore, FsBlobStore};
+use crate::breaker::CircuitBreaker;
+use crate::capture::{Capture, TrafficCapture};
+use crate::check::{CheckReport, CheckStatus};
+use crate::concurrency::ConcurrencyLimits;
+use crate::config::{
+    ActivityConfig, AlertConfig, CaptureConfig, CircuitBreakerConfig, CompressionConfig,
+    ConcurrencyConfig, Config, DatabaseConfig, DedupConfig, EncryptionConfig, HistoryConfig,
+    KeyCollation, KeyStatsConfig, MqttRoute, RetryConfig, SlowQueryConfig, TieringConfig,
+    WriteBehindConfig,
+};
+use crate::consistency::Consistency;
+use crate::console::SqlConsole;
+use crate::crypto::KeyRing;
+use crate::db::{
+    AggregateView, ClientChange, Database, DryRunReport, EventLog, Integration, PoolRole,
+    SeriesBucket, SeriesPoint, SnapshotKey, SyncResolution, SyncStatus, TablePolicy, TimeSeries,
+    TreeNode, ViewGroup,
+};
+use crate::errors::AppError;
+use crate::flags::{FeatureFlag, FeatureFlags};
+use crate::i18n::{Catalog, DEFAULT_LANGUAGE};
+use crate::ident::{Ident, TableRegistry};
+use crate::integrations::{ExtractedRows, IntegrationScheduler};
+use crate::jobs::JobQueue;
+use crate::metrics::Sample;
+use crate::patch::{DeltaOp, ValuePatch};
+use crate::projection::Projection;
+use crate::retry::RetryPolicy;
+use crate::schema::{JsonSchema, TableSchema, ValueType, ValueValidator};
+use crate::seed::{SeedReport, Seeder};
+use crate::server::LEGAL_HOLD_CODE;
+use crate::sharding::ShardRing;
+use crate::slowlog::SlowQuery;
+use crate::stats::{Exemplar, RequestStats};
+use crate::tiering::{self, TieringJob, TieringReport};
+use crate::totp::Totp;
+
+use super::harness::TestDatabase;
+
+/// Builds a key ring holding one key per given version.
+fn keyring(versions: &[u32], active_key: u32) -> Option<KeyRing> {
+    let keys: HashMap<String, String> = versions
+        .iter()
+        .map(|version| (version.to_string(), STANDARD.encode([*version as u8; 32])))
+        .collect();
+    KeyRing::from_config(&EncryptionConfig {
+        enabled: true,
+        keys,
+        active_key,
+    })
+    .expect("Failed to create key ring")
+}
+
+/// Counts the deduplicated values and their references.
+async fn value_store(pool: &sqlx::SqlitePool) -> (i64, i64) {
+    sqlx::query_as("SELECT COUNT(*), COALESCE(SUM(refs), 0) FROM xcloud_values")
+        .fetch_one(pool)
+        .await
+        .expect("Failed to count deduplicated values")
+}
+
+#[actix_web::test]
+async fn set_get_update_delete_data() {
+    let test = TestDatabase::new().await;
+    let db = &test.db;
+
+    db.set_data("items", "a", "1").await.unwrap();
+    assert_eq!(
+        db.get_data("items", "a").await.unwrap().as_deref(),
+        Some("1")
+    );
+
+    db.update_data("items", "a", "2").await.unwrap();
+    assert_eq!(
+        db.get_data("items", "a").await.unwrap().as_deref(),
+        Some("2")
+    );
+
+    db.delete_data("items", "a").await.unwrap();
+    assert_eq!(db.get_data("items", "a").await.unwrap(), None);
+}
+
+#[actix_web::test]
+async fn list_and_delete_tables() {
+    let test = TestDatabase::new().await;
+    let db = &test.db;
+
+    db.set_data("alpha", "b", "1").await.unwrap();
+    db.set_data("alpha", "a", "1").await.unwrap();
+    db.set_data("beta", "a", "1").await.unwrap();
+
+    assert_eq!(db.list_tables().await.unwrap(), vec!["alpha", "beta"]);
+    assert_eq!(
+        db.list_keys("alpha", 0, None).await.unwrap(),
+        vec!["a", "b"]
+    );
+    assert_eq!(db.list_keys("alpha", 1, Some(1)).await.unwrap(), vec!["b"]);
+
+    db.delete_table("alpha").await.unwrap();
+    assert_eq!(db.list_tables().await.unwrap(), vec!["beta"]);
+}
+
+#[actix_web::test]
+async fn table_schemas() {
+    let test = TestDatabase::new().await;
+    let db = &test.db;
+
+    assert_eq!(
+        db.get_value_type("counts").await.unwrap(),
+        ValueType::String
+    );
+    db.create_table(&TableSchema {
+        table: "counts".to_string(),
+        value_type: ValueType::Integer,
+        case_sensitive: None,
+    })
+    .await
+    .unwrap();
+    assert_eq!(
+        db.get_value_type("counts").await.unwrap(),
+        ValueType::Integer
+    );
+
+    db.create_table(&TableSchema {
+        table: "labels".to_string(),
+        value_type: ValueType::String,
+        case_sensitive: None,
+    })
+    .await
+    .unwrap();
+    db.set_data("labels", "a", "12").await.unwrap();
+    db.set_data("labels", "b", "twelve").await.unwrap();
+    let retype = TableSchema {
+        table: "labels".to_string(),
+        value_type: ValueType::Integer,
+        case_sensitive: None,
+    };
+    assert!(matches!(
+        db.create_table(&retype).await,
+        Err(sqlx::Error::InvalidArgument(reason)) if reason.contains("'b'")
+    ));
+    assert_eq!(
+        db.get_value_type("labels").await.unwrap(),
+        ValueType::String
+    );
+    db.set_data("labels", "b", "13").await.unwrap();
+    db.create_table(&retype).await.unwrap();
+    assert_eq!(
+        db.get_value_type("labels").await.unwrap(),
+        ValueType::Integer
+    );
+
+    db.delete_table("counts").await.unwrap();
+    assert_eq!(
+        db.get_value_type("counts").await.unwrap(),
+        ValueType::String
+    );
+}
+
+#[actix_web::test]
+async fn increment() {
+    let test = TestDatabase::new().await;
+    let db = &test.db;
+
+    assert_eq!(db.increment("counters", "hits", 5).await.unwrap(), Some(5));
+    assert_eq!(db.increment("counters", "hits", -2).await.unwrap(), Some(3));
+
+    db.set_data("counters", "name", "abc").await.unwrap();
+    assert_eq!(db.increment("counters", "name", 1).await.unwrap(), None);
+
+    db.set_data("counters", "max", &i64::MAX.to_string())
+        .await
+        .unwrap();
+    assert!(matches!(
+        db.increment("counters", "max", 1).await,
+        Err(sqlx::Error::InvalidArgument(_))
+    ));
+    assert_eq!(
+        db.get_data("counters", "max").await.unwrap(),
+        Some(i64::MAX.to_string())
+    );
+    assert_eq!(
+        db.increment("counters", "max", -1).await.unwrap(),
+        Some(i64::MAX - 1)
+    );
+}
+
+#[actix_web::test]
+async fn lists() {
+    let test = TestDatabase::new().await;
+    let db = &test.db;
+
+    assert_eq!(db.list_push("logs", "k", "b", false).await.unwrap(), 1);
+    assert_eq!(db.list_push("logs", "k", "c", false).await.unwrap(), 2);
+    assert_eq!(db.list_push("logs", "k", "a", true).await.unwrap(), 3);
+    assert_eq!(
+        db.list_range("logs", "k", 0, None).await.unwrap(),
+        vec!["a", "b", "c"]
+    );
+    assert_eq!(
+        db.list_range("logs", "k", 1, Some(1)).await.unwrap(),
+        vec!["b"]
+    );
+
+    assert_eq!(
+        db.list_pop("logs", "k", true).await.unwrap().as_deref(),
+        Some("a")
+    );
+    assert_eq!(
+        db.list_pop("logs", "k", false).await.unwrap().as_deref(),
+        Some("c")
+    );
+
+    db.delete_data("logs", "k").await.unwrap();
+    assert_eq!(db.list_pop("logs", "k", false).await.unwrap(), None);
+}
+
+#[actix_web::test]
+async fn fulltext_search() {
+    let test = TestDatabase::new().await;
+    let db = &test.db;
+
+    db.set_data("docs", "wall", "concrete wall with insulation")
+        .await
+        .unwrap();
+    db.set_data("docs", "door", "wooden door").await.unwrap();
+
+    let hits = db.fulltext_search("docs", "wall", 0, 10).await.unwrap();
+    assert_eq!(hits.len(), 1);
+    assert_eq!(hits[0].key, "wall");
+
+    db.set_data("docs", "door", "wooden door in concrete wall")
+        .await
+        .unwrap();
+    let hits = db
+        .fulltext_search("docs", "concrete wall", 0, 10)
+        .await
+        .unwrap();
+    assert_eq!(hits.len(), 2);
+
+    db.delete_data("docs", "wall").await.unwrap();
+    let hits = db
+        .fulltext_search("docs", "insulation", 0, 10)
+        .await
+        .unwrap();
+    assert!(hits.is_empty());
+
+    let compressed = Database::new(&TestDatabase::config(&test.dir))
+        .await
+        .unwrap()
+        .with_compression(CompressionConfig {
+            enabled: true,
+            threshold_bytes: 8,
+            ..CompressionConfig::default()
+        });
+    compressed
+        .set_data("notes", "wall", &"concrete wall ".repeat(100))
+        .await
+        .unwrap();
+    assert!(matches!(
+        compressed.fulltext_search("notes", "concrete", 0, 10).await,
+        Err(sqlx::Error::Protocol(_))
+    ));
+    assert!(matches!(
+        compressed.fulltext_search("docs", "wooden", 0, 10).await,
+        Err(sqlx::Error::Protocol(_))
+    ));
+}
+
+#[actix_web::test]
+async fn webhooks() {
+    let test = TestDatabase::new().await;
+    let db = &test.db;
+
+    let id = db
+        .add_webhook("items", "http://localhost/hook", "secret")
+        .await
+        .unwrap();
+    let webhooks = db.get_webhooks("items").await.unwrap();
+    assert_eq!(webhooks.len(), 1);
+    assert_eq!(webhooks[0].url, "http://localhost/hook");
+
+    db.delete_webhook(id).await.unwrap();
+    assert!(db.get_webhooks("items").await.unwrap().is_empty());
+}
+
+#[actix_web::test]
+async fn encryption_and_key_rotation() {
+    let test = TestDatabase::new().await;
+    test.db
+        .set_data("secrets", "plain", "legacy")
+        .await
+        .unwrap();
+
+    let config = TestDatabase::config(&test.dir);
+    let db = Database::new(&config)
+        .await
+        .unwrap()
+        .with_keyring(keyring(&[1], 1));
+    db.set_data("secrets", "sealed", "value").await.unwrap();
+    assert_eq!(
+        db.get_data("secrets", "plain").await.unwrap().as_deref(),
+        Some("legacy")
+    );
+    assert_eq!(db.reencrypt_table("secrets").await.unwrap(), 1);
+    assert_eq!(db.reencrypt_table("secrets").await.unwrap(), 0);
+
+    let db = Database::new(&config)
+        .await
+        .unwrap()
+        .with_keyring(keyring(&[1, 2], 2));
+    assert_eq!(db.reencrypt_table("secrets").await.unwrap(), 2);
+    assert_eq!(
+        db.get_data("secrets", "sealed").await.unwrap().as_deref(),
+        Some("value")
+    );
+    assert_eq!(
+        db.get_data("secrets", "plain").await.unwrap().as_deref(),
+        Some("legacy")
+    );
+}
+
+#[actix_web::test]
+async fn get_many() {
+    let test = TestDatabase::new().await;
+    let db = &test.db;
+
+    db.set_data("items", "a", "1").await.unwrap();
+    db.set_data("items", "b", "2").await.unwrap();
+
+    let mut found = db
+        .get_many(
+            "items",
+            &["a".to_string(), "b".to_string(), "c".to_string()],
+        )
+        .await
+        .unwrap();
+    found.sort();
+    assert_eq!(
+        found,
+        vec![
+            ("a".to_string(), "1".to_string()),
+            ("b".to_string(), "2".to_string())
+        ]
+    );
+}
+
+#[actix_web::test]
+async fn settings() {
+    let test = TestDatabase::new().await;
+    let db = &test.db;
+
+    assert_eq!(db.get_setting("mode").await.unwrap(), None);
+    db.set_setting("mode", "a").await.unwrap();
+    db.set_setting("mode", "b").await.unwrap();
+    assert_eq!(db.get_setting("mode").await.unwrap().as_deref(), Some("b"));
+    assert!(db.list_tables().await.unwrap().is_empty());
+}
+
+#[actix_web::test]
+async fn sharded_tables() {
+    let test = TestDatabase::new().await;
+    let shard = |name: &str| format!("sqlite://{}?mode=rwc", test.dir.path().join(name).display());
+    let config = DatabaseConfig {
+        shards: vec![shard("shard1.db"), shard("shard2.db")],
+        ..TestDatabase::config(&test.dir)
+    };
+    let db = Database::new(&config).await.unwrap();
+
+    let tables: Vec<String> = (0..48).map(|i| format!("table{:02}", i)).collect();
+    for table in &tables {
+        db.set_data(table, "a", table).await.unwrap();
+        db.list_push(table, "l", "x", false).await.unwrap();
+    }
+    assert_eq!(db.list_tables().await.unwrap(), tables);
+    for table in &tables {
+        assert_eq!(
+            db.get_data(table, "a").await.unwrap().as_deref(),
+            Some(table.as_str())
+        );
+        assert_eq!(db.list_range(table, "l", 0, None).await.unwrap(), vec!["x"]);
+    }
+
+    let primary = test.db.list_tables().await.unwrap();
+    assert!(!primary.is_empty() && primary.len() < tables.len());
+}
+
+#[test]
+fn shard_ring_moves_few_tables() {
+    let before = ShardRing::new(&["a", "b", "c"]);
+    let after = ShardRing::new(&["a", "b", "c", "d"]);
+    let tables: Vec<String> = (0..1000).map(|i| format!("table{}", i)).collect();
+    let moved = tables
+        .iter()
+        .filter(|table| before.shard_for(table) != after.shard_for(table))
+        .count();
+    assert!(moved < 500, "{} of 1000 tables moved", moved);
+    assert!(tables
+        .iter()
+        .filter(|table| before.shard_for(table) != after.shard_for(table))
+        .all(|table| after.shard_for(table) == 3));
+}
+
+#[actix_web::test]
+async fn outbox() {
+    let test = TestDatabase::new().await;
+    let db = Database::new(&TestDatabase::config(&test.dir))
+        .await
+        .unwrap()
+        .with_outbox(true);
+
+    db.set_data("items", "a", "1").await.unwrap();
+    db.increment("items", "n", 2).await.unwrap();
+    db.delete_data("items", "a").await.unwrap();
+    test.db.set_data("items", "b", "1").await.unwrap();
+
+    let entries = db.outbox_after(0, 10).await.unwrap();
+    let ops: Vec<_> = entries.iter().map(|entry| entry.op.as_str()).collect();
+    assert_eq!(ops, vec!["set", "set", "delete"]);
+    assert_eq!(entries[1].value.as_deref(), Some("2"));
+
+    assert_eq!(db.prune_outbox(entries[1].id).await.unwrap(), 2);
+    assert_eq!(db.outbox_after(0, 10).await.unwrap().len(), 1);
+}
+
+#[actix_web::test]
+async fn locks() {
+    let test = TestDatabase::new().await;
+    let db = &test.db;
+
+    let lock = db
+        .acquire_lock("models", "wall", "alice", 60)
+        .await
+        .unwrap();
+    assert_eq!(lock.unwrap().owner, "alice");
+    let held = db.acquire_lock("models", "wall", "bob", 60).await.unwrap();
+    assert_eq!(held.unwrap_err().owner, "alice");
+    assert!(db
+        .acquire_lock("models", "wall", "alice", 120)
+        .await
+        .unwrap()
+        .is_ok());
+
+    assert!(!db.release_lock("models", "wall", "bob").await.unwrap());
+    assert!(db.release_lock("models", "wall", "alice").await.unwrap());
+    assert!(db.get_lock("models", "wall").await.unwrap().is_none());
+
+    db.acquire_lock("models", "door", "alice", 0)
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(db.get_lock("models", "door").await.unwrap().is_none());
+    assert!(db
+        .acquire_lock("models", "door", "bob", 60)
+        .await
+        .unwrap()
+        .is_ok());
+}
+
+#[actix_web::test]
+async fn value_history() {
+    let test = TestDatabase::new().await;
+    let mut history = HistoryConfig {
+        depth: 2,
+        ..HistoryConfig::default()
+    };
+    history.tables.insert("untracked".to_string(), 0);
+    let db = Database::new(&TestDatabase::config(&test.dir))
+        .await
+        .unwrap()
+        .with_history(history);
+
+    db.set_data("props", "height", "1").await.unwrap();
+    db.set_data("props", "height", "2").await.unwrap();
+    db.update_data("props", "height", "3").await.unwrap();
+    db.set_data("untracked", "height", "1").await.unwrap();
+
+    let entries = db.history("props", "height", None).await.unwrap();
+    let values: Vec<_> = entries.iter().map(|entry| entry.value.as_deref()).collect();
+    assert_eq!(values, vec![Some("3"), Some("2")]);
+    assert!(db
+        .history("untracked", "height", None)
+        .await
+        .unwrap()
+        .is_empty());
+
+    let now = entries[0].changed_at;
+    assert_eq!(
+        db.get_data_at("props", "height", now)
+            .await
+            .unwrap()
+            .as_deref(),
+        Some("3")
+    );
+    assert_eq!(
+        db.get_data_at("props", "height", now - 60).await.unwrap(),
+        None
+    );
+
+    db.delete_data("props", "height").await.unwrap();
+    assert_eq!(
+        db.get_data_at("props", "height", now + 60).await.unwrap(),
+        None
+    );
+
+    assert_eq!(db.prune_history(-60).await.unwrap(), 2);
+}
+
+#[actix_web::test]
+async fn stream_table() {
+    let test = TestDatabase::new().await;
+    let db = Database::new(&TestDatabase::config(&test.dir))
+        .await
+        .unwrap()
+        .with_keyring(keyring(&[1], 1));
+
+    for i in 0..200 {
+        db.set_data("items", &format!("{:03}", i), &i.to_string())
+            .await
+            .unwrap();
+    }
+    let mut rows = db.stream_table("items").await.unwrap();
+    let mut count = 0;
+    while let Some(row) = rows.recv().await {
+        let (key, value) = row.unwrap();
+        assert_eq!(key, format!("{:03}", count));
+        assert_eq!(value, count.to_string());
+        count += 1;
+    }
+    assert_eq!(count, 200);
+
+    db.set_data("items", "200", "200").await.unwrap();
+    let (total, mut rows) = db.consistent_export("items").await.unwrap();
+    assert_eq!(total, 201);
+    let mut exported = Vec::new();
+    while let Some(row) = rows.recv().await {
+        exported.push(row.unwrap());
+    }
+    assert_eq!(exported.len(), 201);
+    assert_eq!(exported[0], ("000".to_string(), "0".to_string()));
+}
+
+#[actix_web::test]
+async fn slow_query_log() {
+    let test = TestDatabase::new().await;
+    let db = Database::new(&TestDatabase::config(&test.dir))
+        .await
+        .unwrap()
+        .with_slow_query_log(SlowQueryConfig {
+            threshold_ms: 0,
+            capacity: 2,
+            persist: true,
+        });
+
+    db.set_data("items", "a", "1").await.unwrap();
+    db.get_data("items", "a").await.unwrap();
+    db.set_data("other", "a", "1").await.unwrap();
+
+    let operations: Vec<_> = db
+        .slow_queries(None)
+        .into_iter()
+        .map(|query| (query.table, query.operation))
+        .collect();
+    assert_eq!(
+        operations,
+        vec![
+            ("other".to_string(), "set_data".to_string()),
+            ("items".to_string(), "get_data".to_string())
+        ]
+    );
+    assert_eq!(db.slow_queries(Some("items")).len(), 1);
+    assert!(!db.slow_queries(None)[0].sql.contains('\n'));
+}
+
+#[actix_web::test]
+async fn index_advisor() {
+    let test = TestDatabase::new().await;
+    let db = Database::new(&TestDatabase::config(&test.dir))
+        .await
+        .unwrap()
+        .with_slow_query_log(SlowQueryConfig {
+            threshold_ms: 0,
+            capacity: 100,
+            persist: false,
+        });
+    db.set_data("items", "a", "1").await.unwrap();
+    for _ in 0..3 {
+        db.get_data("items", "a").await.unwrap();
+    }
+    assert!(db.index_recommendations(1).await.unwrap().is_empty());
+
+    let slow = |sql: &str, duration_ms: i64| SlowQuery {
+        table: "items".to_string(),
+        operation: "search".to_string(),
+        duration_ms,
+        sql: sql.to_string(),
+        timestamp: 0,
+    };
+    let by_value = "SELECT key FROM \"items\" WHERE value = ?1 ORDER BY key";
+    let by_prefix = "SELECT key FROM \"items\" WHERE substr(key, 1, ?2) = ?1";
+    let queries = [
+        slow(by_value, 10),
+        slow(by_value, 10),
+        slow(by_prefix, 50),
+        slow(by_prefix, 50),
+        slow("SELECT key FROM \"items\" WHERE key = ?1", 90),
+    ];
+    let recommendations = db.advise_indexes(&queries, 2).await.unwrap();
+    assert_eq!(recommendations.len(), 2);
+    assert_eq!(recommendations[0].sql, by_prefix);
+    assert_eq!(recommendations[0].occurrences, 2);
+    assert_eq!(recommendations[0].total_ms, 100);
+    assert!(recommendations[0].index.is_none());
+    assert_eq!(recommendations[1].sql, by_value);
+    assert!(recommendations[1]
+        .index
+        .as_deref()
+        .unwrap()
+        .contains("(value)"));
+    assert!(db.advise_indexes(&queries, 3).await.unwrap().is_empty());
+}
+
+#[actix_web::test]
+async fn table_regions() {
+    let test = TestDatabase::new().await;
+    let config = DatabaseConfig {
+        regions: HashMap::from([(
+            "eu".to_string(),
+            format!(
+                "sqlite://{}?mode=rwc",
+                test.dir.path().join("eu.db").display()
+            ),
+        )]),
+        ..TestDatabase::config(&test.dir)
+    };
+    let db = Database::new(&config).await.unwrap();
+    assert_eq!(db.regions(), ["eu"]);
+
+    assert!(db.set_table_region("users", Some("eu")).await.unwrap());
+    db.set_data("users", "a", "1").await.unwrap();
+    db.set_data("plain", "a", "1").await.unwrap();
+    assert!(!db.set_table_region("users", None).await.unwrap());
+    assert!(!db.set_table_region("plain", Some("eu")).await.unwrap());
+
+    let shards: Vec<(String, usize)> = db
+        .describe_tables()
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|table| (table.table, table.shard))
+        .collect();
+    assert_eq!(shards, [("plain".to_string(), 0), ("users".to_string(), 1)]);
+
+    db.clone_table("users", "copy", true).await.unwrap();
+    assert_eq!(db.table_region("copy").as_deref(), Some("eu"));
+
+    let db = Database::new(&config).await.unwrap();
+    assert_eq!(db.table_regions().len(), 2);
+    assert_eq!(
+        db.get_data("copy", "a").await.unwrap().as_deref(),
+        Some("1")
+    );
+    let primary = Database::new(&TestDatabase::config(&test.dir))
+        .await
+        .unwrap();
+    assert_eq!(primary.get_data("users", "a").await.unwrap(), None);
+}
+
+#[actix_web::test]
+async fn memory_storage() {
+    let db = Database::in_memory().await.unwrap();
+
+    db.set_data("items", "b", "1").await.unwrap();
+    db.set_data("items", "a", "x").await.unwrap();
+    db.update_data("items", "missing", "1").await.unwrap();
+    assert_eq!(db.increment("items", "b", 2).await.unwrap(), Some(3));
+    assert_eq!(db.increment("items", "a", 1).await.unwrap(), None);
+    assert_eq!(
+        db.list_keys("items", 0, None).await.unwrap(),
+        vec!["a".to_string(), "b".to_string()]
+    );
+    assert_eq!(
+        db.get_many("items", &["b".to_string(), "missing".to_string()])
+            .await
+            .unwrap(),
+        vec![("b".to_string(), "3".to_string())]
+    );
+    assert_eq!(db.list_tables().await.unwrap(), vec!["items".to_string()]);
+
+    db.delete_data("items", "a").await.unwrap();
+    assert_eq!(db.get_data("items", "a").await.unwrap(), None);
+    db.delete_table("items").await.unwrap();
+    assert!(db.list_tables().await.unwrap().is_empty());
+}
+
+#[actix_web::test]
+async fn table_policies() {
+    let test = TestDatabase::new().await;
+    let db = &test.db;
+
+    db.set_data("telemetry", "a", "1").await.unwrap();
+    let policy = TablePolicy {
+        table: "telemetry".to_string(),
+        max_age_secs: Some(3600),
+        max_rows: Some(2),
+    };
+    db.set_table_policy(&policy).await.unwrap();
+    db.set_data("telemetry", "b", "2").await.unwrap();
+    db.set_data("telemetry", "c", "3").await.unwrap();
+    db.set_data("other", "a", "1").await.unwrap();
+
+    assert_eq!(db.table_policies().await.unwrap().len(), 1);
+    assert_eq!(
+        db.apply_table_policy(&policy).await.unwrap(),
+        vec!["a".to_string()]
+    );
+    assert_eq!(
+        db.list_keys("telemetry", 0, None).await.unwrap(),
+        vec!["b".to_string(), "c".to_string()]
+    );
+    assert!(db.apply_table_policy(&policy).await.unwrap().is_empty());
+
+    assert!(db.delete_table_policy("telemetry").await.unwrap());
+    assert!(!db.delete_table_policy("telemetry").await.unwrap());
+    assert!(db.table_policies().await.unwrap().is_empty());
+}
+
+#[actix_web::test]
+async fn compare_and_set() {
+    let test = TestDatabase::new().await;
+    let db = &test.db;
+    let sealed = Database::new(&TestDatabase::config(&test.dir))
+        .await
+        .unwrap()
+        .with_keyring(keyring(&[1], 1));
+    let memory = Database::in_memory().await.unwrap();
+
+    for (db, table) in [(db, "plain"), (&sealed, "sealed"), (&memory, "memory")] {
+        assert_eq!(
+            db.compare_and_set(table, "leader", None, "agent-1")
+                .await
+                .unwrap(),
+            Ok(())
+        );
+        assert_eq!(
+            db.compare_and_set(table, "leader", None, "agent-2")
+                .await
+                .unwrap(),
+            Err(Some("agent-1".to_string()))
+        );
+        assert_eq!(
+            db.compare_and_set(table, "leader", Some("agent-1"), "agent-2")
+                .await
+                .unwrap(),
+            Ok(())
+        );
+        assert_eq!(
+            db.compare_and_set(table, "missing", Some("agent-1"), "agent-2")
+                .await
+                .unwrap(),
+            Err(None)
+        );
+        assert_eq!(
+            db.get_data(table, "leader").await.unwrap().as_deref(),
+            Some("agent-2")
+        );
+    }
+}
+
+#[actix_web::test]
+async fn delete_many() {
+    let test = TestDatabase::new().await;
+    let memory = Database::in_memory().await.unwrap();
+
+    for db in [&test.db, &memory] {
+        for key in ["p1/a", "p1/b", "p1%/c", "p2/a"] {
+            db.set_data("items", key, "1").await.unwrap();
+        }
+        assert_eq!(db.delete_prefix("items", "p1/").await.unwrap(), 2);
+        assert_eq!(
+            db.delete_many("items", &["p2/a".to_string(), "missing".to_string()])
+                .await
+                .unwrap(),
+            1
+        );
+        assert_eq!(
+            db.list_keys("items", 0, None).await.unwrap(),
+            vec!["p1%/c".to_string()]
+        );
+    }
+}
+
+#[actix_web::test]
+async fn describe_tables() {
+    let test = TestDatabase::new().await;
+    let db = &test.db;
+
+    db.create_table(&TableSchema {
+        table: "counts".to_string(),
+        value_type: ValueType::Integer,
+        case_sensitive: None,
+    })
+    .await
+    .unwrap();
+    db.set_data("counts", "a", "12").await.unwrap();
+    db.set_data("counts", "bb", "3").await.unwrap();
+
+    let tables = db.describe_tables().await.unwrap();
+    assert_eq!(tables.len(), 1);
+    let counts = &tables[0];
+    assert_eq!(counts.table, "counts");
+    assert_eq!(counts.value_type, ValueType::Integer);
+    assert_eq!(counts.rows, 2);
+    assert_eq!(counts.approx_bytes, 6);
+    assert_eq!(
+        counts
+            .columns
+            .iter()
+            .map(|column| column.name.as_str())
+            .collect::<Vec<_>>(),
+        vec!["key", "value"]
+    );
+}
+
+#[actix_web::test]
+async fn value_compression() {
+    let test = TestDatabase::new().await;
+    let config = TestDatabase::config(&test.dir);
+    let compression = CompressionConfig {
+        enabled: true,
+        threshold_bytes: 64,
+        ..CompressionConfig::default()
+    };
+    let large = "wall ".repeat(1000);
+
+    for (db, table) in [
+        (
+            Database::new(&config)
+                .await
+                .unwrap()
+                .with_compression(compression.clone()),
+            "plain",
+        ),
+        (
+            Database::new(&config)
+                .await
+                .unwrap()
+                .with_keyring(keyring(&[1], 1))
+                .with_compression(compression.clone()),
+            "sealed",
+        ),
+    ] {
+        db.set_data(table, "model", &large).await.unwrap();
+        db.set_data(table, "small", "door").await.unwrap();
+        assert_eq!(
+            db.get_data(table, "model").await.unwrap().as_deref(),
+            Some(large.as_str())
+        );
+        assert_eq!(
+            db.compare_and_set(table, "model", Some(&large), "window")
+                .await
+                .unwrap(),
+            Ok(())
+        );
+        db.set_data(table, "model", &large).await.unwrap();
+    }
+
+    let tables = test.db.describe_tables().await.unwrap();
+    let plain = tables.iter().find(|t| t.table == "plain").unwrap();
+    assert!(plain.approx_bytes < large.len() as i64 / 4);
+    assert_eq!(
+        test.db.get_data("plain", "model").await.unwrap().as_deref(),
+        Some(large.as_str())
+    );
+    assert_eq!(
+        test.db.get_data("plain", "small").await.unwrap().as_deref(),
+        Some("door")
+    );
+
+    let raw = sqlx::SqlitePool::connect(config.url.as_deref().unwrap())
+        .await
+        .unwrap();
+    let (compressed, codec): (String, Option<String>) =
+        sqlx::query_as("SELECT value, codec FROM plain WHERE key = 'model'")
+            .fetch_one(&raw)
+            .await
+            .unwrap();
+    assert_eq!(codec.as_deref(), Some("zstd"));
+    test.db
+        .set_data("plain", "copy", &compressed)
+        .await
+        .unwrap();
+    assert_eq!(
+        test.db.get_data("plain", "copy").await.unwrap(),
+        Some(compressed)
+    );
+}
+
+#[actix_web::test]
+async fn value_dedup() {
+    let test = TestDatabase::new().await;
+    let config = TestDatabase::config(&test.dir);
+    let db = Database::new(&config)
+        .await
+        .unwrap()
+        .with_keyring(keyring(&[1], 1))
+        .with_dedup(DedupConfig {
+            enabled: true,
+            min_bytes: 16,
+        });
+    let raw = sqlx::SqlitePool::connect(config.url.as_deref().unwrap())
+        .await
+        .unwrap();
+    let part = "bolt ".repeat(100);
+    let other = "beam ".repeat(100);
+
+    db.set_data("parts", "a", &part).await.unwrap();
+    db.set_data("parts", "b", &part).await.unwrap();
+    db.set_data("parts", "small", "door").await.unwrap();
+    db.set_data("copies", "c", &part).await.unwrap();
+    assert_eq!(value_store(&raw).await, (1, 3));
+    let hash: String = sqlx::query_scalar("SELECT hash FROM xcloud_values")
+        .fetch_one(&raw)
+        .await
+        .unwrap();
+    test.db.set_data("notes", "hash", &hash).await.unwrap();
+    assert_eq!(test.db.get_data("notes", "hash").await.unwrap(), Some(hash));
+    assert_eq!(
+        db.get_data("parts", "a").await.unwrap().as_deref(),
+        Some(part.as_str())
+    );
+    assert_eq!(
+        db.get_many("parts", &["b".to_string()]).await.unwrap()[0].1,
+        part
+    );
+
+    assert_eq!(
+        db.compare_and_set("parts", "a", Some(&part), "window")
+            .await
+            .unwrap(),
+        Ok(())
+    );
+    db.update_data("parts", "missing", &other).await.unwrap();
+    assert_eq!(value_store(&raw).await, (1, 2));
+
+    db.set_data("parts", "b", &other).await.unwrap();
+    assert_eq!(value_store(&raw).await, (2, 2));
+    db.delete_table("copies").await.unwrap();
+    assert_eq!(value_store(&raw).await, (1, 1));
+    db.delete_data("parts", "b").await.unwrap();
+    assert_eq!(value_store(&raw).await, (0, 0));
+    assert_eq!(
+        db.get_data("parts", "small").await.unwrap().as_deref(),
+        Some("door")
+    );
+}
+
+#[actix_web::test]
+async fn projects() {
+    let test = TestDatabase::new().await;
+    let db = &test.db;
+
+    assert!(db.create_project("house").await.unwrap());
+    assert!(!db.create_project("house").await.unwrap());
+    assert!(db.create_project("shed").await.unwrap());
+    assert!(!db.add_project_table("garage", "walls").await.unwrap());
+    assert_eq!(db.project_tables("garage").await.unwrap(), None);
+
+    db.set_data("walls", "a", "1").await.unwrap();
+    db.set_data("doors", "a", "1").await.unwrap();
+    assert!(db.add_project_table("house", "walls").await.unwrap());
+    assert!(db.add_project_table("house", "doors").await.unwrap());
+    assert!(db.add_project_table("shed", "doors").await.unwrap());
+    assert_eq!(
+        db.project_tables("house").await.unwrap(),
+        Some(vec!["walls".to_string()])
+    );
+    assert!(!db.remove_project_table("house", "doors").await.unwrap());
+
+    assert_eq!(
+        db.delete_project("shed").await.unwrap(),
+        Some(vec!["doors".to_string()])
+    );
+    assert_eq!(db.list_tables().await.unwrap(), vec!["walls".to_string()]);
+    assert_eq!(
+        db.projects()
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|project| project.name)
+            .collect::<Vec<_>>(),
+        vec!["house".to_string()]
+    );
+    assert_eq!(db.delete_project("shed").await.unwrap(), None);
+}
+
+#[actix_web::test]
+async fn configured_table_schema() {
+    let test = TestDatabase::new().await;
+    let config = DatabaseConfig {
+        key_collation: KeyCollation::Nocase,
+        index_values: true,
+        ..TestDatabase::config(&test.dir)
+    };
+    let db = Database::new(&config).await.unwrap();
+
+    db.set_data("walls", "Wall", "1").await.unwrap();
+    db.set_data("walls", "WALL", "2").await.unwrap();
+    assert_eq!(
+        db.get_data("walls", "wall").await.unwrap().as_deref(),
+        Some("2")
+    );
+    assert_eq!(db.list_keys("walls", 0, None).await.unwrap().len(), 1);
+
+    let raw = sqlx::SqlitePool::connect(config.url.as_deref().unwrap())
+        .await
+        .unwrap();
+    let indexes: i64 = sqlx::query_scalar(
+        "SELECT COUNT(*) FROM sqlite_master WHERE type = 'index' AND tbl_name = 'walls'
+           AND name = 'xcloud_index_walls_value'",
+    )
+    .fetch_one(&raw)
+    .await
+    .unwrap();
+    assert_eq!(indexes, 1);
+
+    test.db.set_data("doors", "Door", "1").await.unwrap();
+    assert_eq!(test.db.get_data("doors", "door").await.unwrap(), None);
+}
+
+#[actix_web::test]
+async fn table_cloning() {
+    let test = TestDatabase::new().await;
+    let db = &test.db;
+
+    db.create_table(&TableSchema {
+        table: "walls".to_string(),
+        value_type: ValueType::Json,
+        case_sensitive: None,
+    })
+    .await
+    .unwrap();
+    db.set_data("walls", "a", "{}").await.unwrap();
+    db.set_data("walls", "b", "[]").await.unwrap();
+
+    assert_eq!(
+        db.clone_table("walls", "copy", true).await.unwrap(),
+        Some(2)
+    );
+    assert_eq!(
+        db.get_data("copy", "b").await.unwrap(),
+        Some("[]".to_string())
+    );
+    assert_eq!(db.get_value_type("copy").await.unwrap(), ValueType::Json);
+    assert_eq!(
+        db.clone_table("walls", "empty", false).await.unwrap(),
+        Some(0)
+    );
+    assert!(db.list_keys("empty", 0, None).await.unwrap().is_empty());
+    assert_eq!(db.clone_table("walls", "copy", true).await.unwrap(), None);
+    assert_eq!(
+        db.clone_table("missing", "other", true).await.unwrap(),
+        None
+    );
+
+    assert!(!db.set_template("missing", true).await.unwrap());
+    assert!(db.set_template("walls", true).await.unwrap());
+    assert!(db.create_project("house").await.unwrap());
+    assert_eq!(
+        db.seed_project("house").await.unwrap(),
+        Some(vec!["house_walls".to_string()])
+    );
+    assert_eq!(
+        db.project_tables("house").await.unwrap(),
+        Some(vec!["house_walls".to_string()])
+    );
+    assert_eq!(
+        db.get_data("house_walls", "a").await.unwrap(),
+        Some("{}".to_string())
+    );
+    assert_eq!(db.seed_project("shed").await.unwrap(), None);
+
+    db.delete_table("walls").await.unwrap();
+    assert!(db.templates().await.unwrap().is_empty());
+}
+
+#[actix_web::test]
+async fn comments() {
+    let test = TestDatabase::new().await;
+    let db = &test.db;
+
+    let root = db
+        .add_comment("walls", "w1", None, "ana", "Too thin?")
+        .await
+        .unwrap()
+        .unwrap();
+    let reply = db
+        .add_comment("walls", "w1", Some(root.id), "ben", "Fire rating is fine")
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(reply.parent_id, Some(root.id));
+    assert!(db
+        .add_comment("walls", "w2", Some(root.id), "ben", "Wrong key")
+        .await
+        .unwrap()
+        .is_none());
+    db.add_comment("walls", "w2", None, "ben", "Other wall")
+        .await
+        .unwrap()
+        .unwrap();
+
+    assert!(db
+        .edit_comment(root.id, "ben", "Not mine")
+        .await
+        .unwrap()
+        .is_none());
+    let edited = db
+        .edit_comment(root.id, "ana", "Too thin for 60 minutes?")
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(edited.body, "Too thin for 60 minutes?");
+    assert_eq!(db.comments("walls", "w1").await.unwrap().len(), 2);
+
+    assert!(db.delete_comment(root.id, "ben").await.unwrap().is_empty());
+    assert_eq!(db.delete_comment(root.id, "ana").await.unwrap().len(), 2);
+    assert!(db.comments("walls", "w1").await.unwrap().is_empty());
+
+    db.delete_table("walls").await.unwrap();
+    assert!(db.comments("walls", "w2").await.unwrap().is_empty());
+}
+
+#[actix_web::test]
+async fn activity_feed() {
+    let test = TestDatabase::new().await;
+    let db = Database::new(&TestDatabase::config(&test.dir))
+        .await
+        .unwrap()
+        .with_activity(ActivityConfig {
+            enabled: true,
+            max_entries: 6,
+        });
+
+    for key in ["w1", "w2", "w3"] {
+        db.set_data("walls", key, "1").await.unwrap();
+    }
+    db.update_data("walls", "w1", "2").await.unwrap();
+    db.update_data("walls", "w1", "3").await.unwrap();
+    db.set_data("doors", "d1", "1").await.unwrap();
+    db.delete_table("doors").await.unwrap();
+
+    let activity = db.activity(None, None, 0, 10).await.unwrap();
+    assert_eq!(
+        activity
+            .iter()
+            .map(|entry| entry.summary.as_str())
+            .collect::<Vec<_>>(),
+        vec![
+            "deleted table doors",
+            "set key d1 in table doors",
+            "updated key w1 in table walls",
+            "set 2 keys in table walls",
+        ]
+    );
+    assert_eq!(activity[2].writes, 2);
+
+    let activity = db.activity(Some("walls"), Some("w1"), 0, 10).await.unwrap();
+    assert_eq!(activity.len(), 1);
+}
+
+#[actix_web::test]
+async fn retry_policy() {
+    let policy = RetryPolicy::new(RetryConfig {
+        max_attempts: 3,
+        base_delay_ms: 1,
+        max_delay_ms: 2,
+    });
+
+    let mut attempts = 0;
+    let result = policy
+        .run(|| {
+            attempts += 1;
+            let attempt = attempts;
+            async move {
+                match attempt {
+                    1 | 2 => Err(sqlx::Error::PoolTimedOut),
+                    _ => Ok(attempt),
+                }
+            }
+        })
+        .await;
+    assert_eq!(result.unwrap(), 3);
+
+    let mut attempts = 0;
+    let result: Result<(), _> = policy
+        .run(|| {
+            attempts += 1;
+            async { Err(sqlx::Error::PoolTimedOut) }
+        })
+        .await;
+    assert!(RetryPolicy::is_transient(&result.unwrap_err()));
+    assert_eq!(attempts, 3);
+
+    let mut attempts = 0;
+    let result: Result<(), _> = policy
+        .run(|| {
+            attempts += 1;
+            async { Err(sqlx::Error::RowNotFound) }
+        })
+        .await;
+    assert!(!RetryPolicy::is_transient(&result.unwrap_err()));
+    assert_eq!(attempts, 1);
+}
+
+#[actix_web::test]
+async fn snapshots() {
+    let test = TestDatabase::new().await;
+    let db = &test.db;
+    let key = |table: &str, key: &str| SnapshotKey {
+        table: table.to_string(),
+        key: key.to_string(),
+    };
+
+    db.set_data("walls", "a", "1").await.unwrap();
+    db.set_data("walls", "b", "1").await.unwrap();
+    db.set_data("doors", "a", "1").await.unwrap();
+    let before = db.create_snapshot(&[], false).await.unwrap();
+    assert_eq!(before.keys, 3);
+
+    db.set_data("walls", "a", "2").await.unwrap();
+    db.delete_data("walls", "b").await.unwrap();
+    db.set_data("walls", "c", "1").await.unwrap();
+    let after = db
+        .create_snapshot(&["walls".to_string(), "missing".to_string()], false)
+        .await
+        .unwrap();
+    assert_eq!(after.keys, 2);
+
+    let diff = db
+        .diff_snapshots(after.id, before.id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(diff.added, vec![key("walls", "c")]);
+    assert_eq!(diff.changed, vec![key("walls", "a")]);
+    assert_eq!(diff.removed, vec![key("doors", "a"), key("walls", "b")]);
+    assert!(db.diff_snapshots(after.id, 999).await.unwrap().is_none());
+
+    for _ in 0..3 {
+        db.create_snapshot(&[], true).await.unwrap();
+    }
+    assert_eq!(db.prune_snapshots(1).await.unwrap(), 2);
+    assert_eq!(db.snapshots().await.unwrap().len(), 3);
+    assert!(db.delete_snapshot(before.id).await.unwrap());
+    assert!(!db.delete_snapshot(before.id).await.unwrap());
+}
+
+#[actix_web::test]
+async fn key_tree() {
+    let test = TestDatabase::new().await;
+    let db = &test.db;
+    let node = |name: &str, leaf: bool, descendants: i64| TreeNode {
+        name: name.to_string(),
+        leaf,
+        descendants,
+    };
+
+    for key in [
+        "floors/1/walls/a",
+        "floors/1/walls/b",
+        "floors/1",
+        "floors/2/doors/a",
+        "site",
+    ] {
+        db.set_data("house", key, "1").await.unwrap();
+    }
+
+    assert_eq!(
+        db.tree("house", "").await.unwrap(),
+        vec![node("floors", false, 4), node("site", true, 0)]
+    );
+    assert_eq!(
+        db.tree("house", "/floors/").await.unwrap(),
+        vec![node("1", true, 2), node("2", false, 1)]
+    );
+    assert_eq!(
+        db.tree("house", "floors/1/walls").await.unwrap(),
+        vec![node("a", true, 0), node("b", true, 0)]
+    );
+    assert!(db.tree("house", "site").await.unwrap().is_empty());
+}
+
+#[actix_web::test]
+async fn batched_writes() {
+    let test = TestDatabase::new().await;
+    let db = &test.db;
+
+    db.set_data("walls", "a", "old").await.unwrap();
+    let pairs: Vec<(String, String)> = (0..3)
+        .map(|i| (["a", "b", "c"][i].to_string(), i.to_string()))
+        .collect();
+    db.set_many("walls", &pairs).await.unwrap();
+    assert_eq!(
+        db.get_data("walls", "a").await.unwrap(),
+        Some("0".to_string())
+    );
+    assert_eq!(
+        db.get_data("walls", "c").await.unwrap(),
+        Some("2".to_string())
+    );
+    assert_eq!(db.list_keys("walls", 0, None).await.unwrap().len(), 3);
+}
+
+#[actix_web::test]
+async fn key_case_sensitivity() {
+    let test = TestDatabase::new().await;
+    let db = &test.db;
+    let schema = |case_sensitive: Option<bool>| TableSchema {
+        table: "walls".to_string(),
+        value_type: ValueType::String,
+        case_sensitive,
+    };
+
+    db.set_data("walls", "Wall", "1").await.unwrap();
+    db.set_data("walls", "wall", "2").await.unwrap();
+    let err = db.create_table(&schema(Some(false))).await.unwrap_err();
+    assert!(err.as_database_error().unwrap().is_unique_violation());
+    assert_eq!(
+        db.get_data("walls", "Wall").await.unwrap().as_deref(),
+        Some("1")
+    );
+
+    db.delete_data("walls", "wall").await.unwrap();
+    db.create_table(&schema(Some(false))).await.unwrap();
+    assert_eq!(
+        db.get_key_collation("walls").await.unwrap(),
+        KeyCollation::Nocase
+    );
+    assert_eq!(
+        db.get_data("walls", "WALL").await.unwrap().as_deref(),
+        Some("1")
+    );
+    db.set_data("walls", "wALL", "3").await.unwrap();
+    assert_eq!(db.list_keys("walls", 0, None).await.unwrap(), vec!["Wall"]);
+
+    db.create_table(&schema(None)).await.unwrap();
+    assert_eq!(
+        db.get_key_collation("walls").await.unwrap(),
+        KeyCollation::Nocase
+    );
+    db.create_table(&schema(Some(true))).await.unwrap();
+    assert_eq!(db.get_data("walls", "wall").await.unwrap(), None);
+    assert_eq!(
+        db.get_data("walls", "Wall").await.unwrap().as_deref(),
+        Some("3")
+    );
+}
+
+#[actix_web::test]
+async fn delete_previews() {
+    let test = TestDatabase::new().await;
+    let db = &test.db;
+
+    for key in ["a/1", "a/2", "b/1"] {
+        db.set_data("walls", key, "1").await.unwrap();
+    }
+    let report = db.preview_delete("walls", Some("a/"), None).await.unwrap();
+    assert_eq!(report.count, 2);
+    assert_eq!(report.sample, vec!["a/1", "a/2"]);
+    let keys = vec!["b/1".to_string(), "b/1".to_string(), "c/1".to_string()];
+    let report = db.preview_delete("walls", None, Some(&keys)).await.unwrap();
+    assert_eq!(report.count, 1);
+    assert_eq!(
+        db.preview_delete("walls", None, None).await.unwrap().count,
+        3
+    );
+    assert_eq!(
+        db.preview_delete("doors", None, None).await.unwrap(),
+        DryRunReport::default()
+    );
+    assert_eq!(db.list_keys("walls", 0, None).await.unwrap().len(), 3);
+    assert!(!db
+        .list_tables()
+        .await
+        .unwrap()
+        .contains(&"doors".to_string()));
+}
+
+#[actix_web::test]
+async fn key_access_stats() {
+    let test = TestDatabase::new().await;
+    let db = Database::new(&TestDatabase::config(&test.dir))
+        .await
+        .unwrap()
+        .with_key_stats(KeyStatsConfig {
+            enabled: true,
+            ..KeyStatsConfig::default()
+        });
+
+    db.set_data("walls", "a", "1").await.unwrap();
+    db.set_data("walls", "a", "2").await.unwrap();
+    db.set_data("walls", "b", "1").await.unwrap();
+    for _ in 0..3 {
+        db.record_read("walls", "b");
+    }
+    assert_eq!(db.flush_key_stats().await.unwrap(), 2);
+    db.record_read("walls", "a");
+
+    let hot = db.hot_keys("walls", false, 10).await.unwrap();
+    assert_eq!(
+        hot.iter()
+            .map(|stat| (stat.key.as_str(), stat.reads, stat.writes))
+            .collect::<Vec<_>>(),
+        vec![("b", 3, 1), ("a", 1, 2)]
+    );
+    assert!(hot[0].last_read_at.is_some());
+
+    db.delete_table("walls").await.unwrap();
+    assert!(db.hot_keys("walls", true, 10).await.unwrap().is_empty());
+    assert_eq!(test.db.flush_key_stats().await.unwrap(), 0);
+}
+
+#[actix_web::test]
+async fn json_patches() {
+    let test = TestDatabase::new().await;
+    let db = &test.db;
+    let json_patch =
+        |ops: serde_json::Value| ValuePatch::Json(serde_json::from_value(ops).unwrap());
+
+    db.set_data("walls", "a", r#"{"height":3,"layers":["brick"]}"#)
+        .await
+        .unwrap();
+    let patched = db
+        .patch_data(
+            "walls",
+            "a",
+            &json_patch(serde_json::json!([
+                {"op": "replace", "path": "/height", "value": 4},
+                {"op": "add", "path": "/layers/-", "value": "plaster"}
+            ])),
+        )
+        .await
+        .unwrap();
+    let parse = |value: Option<Result<String, String>>| {
+        serde_json::from_str::<serde_json::Value>(&value.unwrap().unwrap()).unwrap()
+    };
+    assert_eq!(
+        parse(patched),
+        serde_json::json!({"height": 4, "layers": ["brick", "plaster"]})
+    );
+
+    let failed = db
+        .patch_data(
+            "walls",
+            "a",
+            &json_patch(serde_json::json!([
+                {"op": "replace", "path": "/height", "value": 5},
+                {"op": "test", "path": "/height", "value": 3}
+            ])),
+        )
+        .await
+        .unwrap();
+    assert!(matches!(failed, Some(Err(_))));
+
+    let merged = db
+        .patch_data(
+            "walls",
+            "a",
+            &ValuePatch::Merge(serde_json::json!({"height": null, "color": "red"})),
+        )
+        .await
+        .unwrap();
+    assert_eq!(
+        parse(merged),
+        serde_json::json!({"color": "red", "layers": ["brick", "plaster"]})
+    );
+    assert_eq!(
+        db.patch_data(
+            "walls",
+            "missing",
+            &ValuePatch::Merge(serde_json::json!({}))
+        )
+        .await
+        .unwrap(),
+        None
+    );
+}
+
+#[actix_web::test]
+async fn key_transforms() {
+    let test = TestDatabase::new().await;
+    let db = &test.db;
+
+    db.set_data("walls", "a", r#"{"summary":{"total":3}}"#)
+        .await
+        .unwrap();
+    let id = db
+        .add_transform("walls", "a", "/summary/total", "dash", "total")
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        db.get_data("dash", "total").await.unwrap(),
+        Some("3".to_string())
+    );
+    assert_eq!(
+        db.add_transform("walls", "b", "", "dash", "total")
+            .await
+            .unwrap(),
+        None
+    );
+
+    db.set_data("walls", "a", r#"{"summary":{"total":"many"}}"#)
+        .await
+        .unwrap();
+    assert_eq!(
+        db.get_data("dash", "total").await.unwrap(),
+        Some("many".to_string())
+    );
+
+    db.add_transform("dash", "total", "", "dash", "copy")
+        .await
+        .unwrap()
+        .unwrap();
+    db.set_data("walls", "a", r#"{"summary":{"total":4}}"#)
+        .await
+        .unwrap();
+    assert_eq!(
+        db.get_data("dash", "copy").await.unwrap(),
+        Some("many".to_string())
+    );
+
+    db.set_data("walls", "a", r#"{"summary":{}}"#)
+        .await
+        .unwrap();
+    assert_eq!(db.get_data("dash", "total").await.unwrap(), None);
+
+    assert!(db.delete_transform(id).await.unwrap());
+    assert!(!db.delete_transform(id).await.unwrap());
+    assert_eq!(db.transforms().await.unwrap().len(), 1);
+}
+
+#[actix_web::test]
+async fn json_schema_validation() {
+    let test = TestDatabase::new().await;
+    let db = &test.db;
+    let schema = serde_json::json!({
+        "type": "object",
+        "properties": {"height": {"type": "integer", "minimum": 0}},
+        "required": ["height"]
+    });
+
+    db.set_json_schema(&JsonSchema {
+        table: "walls".to_string(),
+        schema: schema.clone(),
+    })
+    .await
+    .unwrap();
+    assert!(db
+        .validate_value("walls", r#"{"height":3}"#)
+        .await
+        .unwrap()
+        .is_ok());
+    let violations = db
+        .validate_value("walls", r#"{"height":-1}"#)
+        .await
+        .unwrap()
+        .unwrap_err();
+    assert_eq!(violations.len(), 1);
+    assert_eq!(violations[0].path, "/height");
+    assert!(db
+        .validate_value("walls", "not json")
+        .await
+        .unwrap()
+        .is_err());
+    assert!(db
+        .validate_value("other", "not json")
+        .await
+        .unwrap()
+        .is_ok());
+
+    db.set_data("walls", "a", r#"{"height":3}"#).await.unwrap();
+    let patched = db
+        .patch_data(
+            "walls",
+            "a",
+            &ValuePatch::Merge(serde_json::json!({"height": null})),
+        )
+        .await
+        .unwrap();
+    assert!(matches!(patched, Some(Err(_))));
+
+    db.clone_table("walls", "copy", false).await.unwrap();
+    assert_eq!(db.json_schemas().await.unwrap().len(), 2);
+    assert!(db.delete_json_schema("walls").await.unwrap());
+    assert!(!db.delete_json_schema("walls").await.unwrap());
+    db.delete_table("copy").await.unwrap();
+    assert!(db.json_schemas().await.unwrap().is_empty());
+}
+
+#[actix_web::test]
+async fn managed_tables() {
+    let test = TestDatabase::new().await;
+    let db = &test.db;
+
+    assert_eq!(Ident::quote("wa\"lls"), "\"wa\"\"lls\"");
+    let registry = TableRegistry::default();
+    assert!(!registry.contains("walls"));
+    registry.register(["walls".to_string()]);
+    assert!(registry.contains("walls"));
+    registry.unregister("walls");
+    assert!(!registry.contains("walls"));
+    assert_eq!(
+        Ident::derived("xcloud_fts_", "walls"),
+        "\"xcloud_fts_walls\""
+    );
+    for table in ["", "--", "xcloud_locks", "XCLOUD_history", "sqlite_master"] {
+        assert!(matches!(
+            Ident::table_name(table),
+            Err(sqlx::Error::InvalidArgument(_))
+        ));
+    }
+
+    db.set_data("walls", "a", "1").await.unwrap();
+    assert!(matches!(
+        db.set_data("xcloud_locks", "a", "1").await,
+        Err(sqlx::Error::InvalidArgument(_))
+    ));
+    assert!(db.get_data("xcloud_history", "a").await.is_err());
+    assert!(db.delete_table("sqlite_master").await.is_err());
+    assert_eq!(db.list_tables().await.unwrap(), vec!["walls".to_string()]);
+    assert!(matches!(
+        db.fulltext_search("floors", "a", 0, 10).await,
+        Err(sqlx::Error::InvalidArgument(reason)) if reason == "unknown table: floors"
+    ));
+
+    let reopened = Database::new(&TestDatabase::config(&test.dir))
+        .await
+        .unwrap();
+    assert!(reopened.fulltext_search("walls", "a", 0, 10).await.is_ok());
+    reopened.delete_table("walls").await.unwrap();
+    assert!(reopened.fulltext_search("walls", "a", 0, 10).await.is_err());
+}
+
+#[actix_web::test]
+async fn metrics_exporters() {
+    let test = TestDatabase::new().await;
+    let db = &test.db;
+    let stats = RequestStats::new();
+    let concurrency = Arc::new(ConcurrencyLimits::new(&ConcurrencyConfig::default()));
+
+    db.set_data("walls", "a", "1").await.unwrap();
+    stats.record(200);
+    stats.record(404);
+    let ticket = concurrency.admit("/import/walls", None).await.unwrap();
+    let samples = Sample::collect(db, &stats, &concurrency).await.unwrap();
+    drop(ticket);
+
+    let text = Sample::to_prometheus(&samples);
+    assert_eq!(
+        text.matches("# TYPE xcloud_requests_total counter").count(),
+        1
+    );
+    assert!(text.contains("xcloud_requests_total{class=\"2xx\"} 1\n"));
+    assert!(text.contains("xcloud_keys 1\n"));
+    assert!(text.contains("xcloud_lane_in_flight{class=\"bulk\"} 1\n"));
+    assert!(text.contains("xcloud_lane_queued{class=\"interactive\"} 0\n"));
+
+    let mut previous = HashMap::new();
+    let lines: Vec<String> = samples
+        .iter()
+        .filter_map(|sample| sample.to_statsd("edge", &mut previous))
+        .collect();
+    assert!(lines.contains(&"edge.requests_total.2xx:1|c".to_string()));
+    assert!(lines.contains(&"edge.keys:1|g".to_string()));
+    assert!(!lines
+        .iter()
+        .any(|line| line.starts_with("edge.requests_total.5xx")));
+
+    stats.record(201);
+    let samples = Sample::collect(db, &stats, &concurrency).await.unwrap();
+    let lines: Vec<String> = samples
+        .iter()
+        .filter_map(|sample| sample.to_statsd("edge", &mut previous))
+        .collect();
+    assert!(lines.contains(&"edge.requests_total.2xx:1|c".to_string()));
+    assert!(!lines
+        .iter()
+        .any(|line| line.starts_with("edge.requests_total.4xx")));
+}
+
+#[actix_web::test]
+async fn self_check() {
+    let dir = tempfile::tempdir().unwrap();
+    let config = Config {
+        database: TestDatabase::config(&dir),
+        ..Config::default()
+    };
+    let report = CheckReport::run(Ok(config.clone())).await;
+    assert!(report.passed);
+    assert!(report
+        .checks
+        .iter()
+        .all(|check| check.status == CheckStatus::Passed));
+
+    let mut broken = config;
+    broken.bind_address = "not an address".to_string();
+    broken.database.url = Some("sqlite:///missing/dir/xcloud.db".to_string());
+    let report = CheckReport::run(Ok(broken)).await;
+    assert!(!report.passed);
+    let failed: Vec<&str> = report
+        .checks
+        .iter()
+        .filter(|check| check.status == CheckStatus::Failed)
+        .map(|check| check.name.as_str())
+        .collect();
+    assert_eq!(failed, ["bind_address", "database"]);
+
+    let report = CheckReport::run(Err(AppError::Io(std::io::Error::other("unreadable")))).await;
+    assert!(!report.passed);
+    assert_eq!(report.checks[1].status, CheckStatus::Skipped);
+}
+
+#[test]
+fn bench_options() {
+    let args = |args: &[&str]| args.iter().map(|arg| arg.to_string()).collect::<Vec<_>>();
+    let options = BenchOptions::parse(args(&[
+        "--target",
+        "http://db:8080/",
+        "--keys",
+        "50",
+        "--read-ratio",
+        "0.5",
+        "--distribution",
+        "zipf",
+    ]))
+    .unwrap();
+    assert_eq!(options.target, "http://db:8080");
+    assert_eq!(options.keys, 50);
+    assert_eq!(options.read_ratio, 0.5);
+    assert_eq!(options.distribution, KeyDistribution::Zipf);
+    assert_eq!(options.concurrency, BenchOptions::default().concurrency);
+
+    assert!(BenchOptions::parse(args(&["--read-ratio", "2"])).is_err());
+    assert!(BenchOptions::parse(args(&["--distribution", "normal"])).is_err());
+    assert!(BenchOptions::parse(args(&["--concurrency", "0"])).is_err());
+    assert!(BenchOptions::parse(args(&["--keys"])).is_err());
+    assert!(BenchOptions::parse(args(&["--unknown", "1"])).is_err());
+
+    let mut latencies: Vec<Duration> = (1..=100).rev().map(Duration::from_millis).collect();
+    let summary = LatencySummary::new(&mut latencies, 3);
+    assert_eq!(summary.count, 100);
+    assert_eq!(summary.errors, 3);
+    assert_eq!(summary.p50_ms, 50.0);
+    assert_eq!(summary.p99_ms, 99.0);
+    assert_eq!(summary.max_ms, 100.0);
+    assert_eq!(LatencySummary::new(&mut [], 1).count, 0);
+}
+
+#[actix_web::test]
+async fn event_logs() {
+    let test = TestDatabase::new().await;
+    let db = &test.db;
+    let log = EventLog {
+        name: "orders".to_string(),
+        max_age_secs: None,
+        max_events: Some(3),
+    };
+    assert!(db.create_event_log(&log).await.unwrap());
+    assert!(!db.create_event_log(&log).await.unwrap());
+    assert!(db
+        .append_events("missing", &["a".to_string()])
+        .await
+        .unwrap()
+        .is_none());
+
+    let values: Vec<String> = (0..4).map(|i| i.to_string()).collect();
+    assert_eq!(
+        db.append_events("orders", &values[..2]).await.unwrap(),
+        Some(0)
+    );
+    assert_eq!(
+        db.append_events("orders", &values[2..]).await.unwrap(),
+        Some(2)
+    );
+    let events = db
+        .read_events("orders", 1, None, Some(2))
+        .await
+        .unwrap()
+        .unwrap();
+    let read: Vec<(i64, &str)> = events
+        .iter()
+        .map(|e| (e.offset, e.value.as_str()))
+        .collect();
+    assert_eq!(read, [(1, "1"), (2, "2")]);
+
+    assert!(db
+        .commit_consumer_offset("orders", "billing", 3)
+        .await
+        .unwrap());
+    assert!(!db
+        .commit_consumer_offset("missing", "billing", 3)
+        .await
+        .unwrap());
+    let offsets = db.consumer_offsets("orders").await.unwrap().unwrap();
+    assert_eq!((offsets[0].offset, offsets[0].lag), (3, 1));
+
+    assert_eq!(db.prune_event_logs().await.unwrap(), 1);
+    let events = db
+        .read_events("orders", 0, None, None)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(events.first().map(|e| e.offset), Some(1));
+    assert_eq!(
+        db.append_events("orders", &values[..1]).await.unwrap(),
+        Some(4)
+    );
+
+    assert!(db.delete_event_log("orders").await.unwrap());
+    assert!(db
+        .read_events("orders", 0, None, None)
+        .await
+        .unwrap()
+        .is_none());
+    assert!(db.create_event_log(&log).await.unwrap());
+    assert_eq!(
+        db.append_events("orders", &values[..1]).await.unwrap(),
+        Some(0)
+    );
+}
+
+#[test]
+fn value_patch_diff() {
+    let base = r#"{"name": "a", "tags": {"x": 1, "y": 2}, "size": 1}"#;
+    let ours = r#"{"name": "b", "tags": {"x": 1, "y": 3}}"#;
+    let theirs = r#"{"name": "a", "tags": {"x": 5, "y": 2}, "size": 1, "color": "red"}"#;
+    let merged = ValuePatch::diff(base, ours).unwrap().apply(theirs).unwrap();
+    assert_eq!(
+        serde_json::from_str::<serde_json::Value>(&merged).unwrap(),
+        serde_json::json!({"name": "b", "tags": {"x": 5, "y": 3}, "color": "red"})
+    );
+    assert!(ValuePatch::diff("not json", ours).is_err());
+}
+
+#[test]
+fn error_catalog() {
+    let catalog = Catalog::bundled();
+    assert_eq!(catalog.languages(), vec!["en", "de", "fr", "nl"]);
+    assert_eq!(catalog.negotiate("de-CH, fr;q=0.9"), "de");
+    assert_eq!(catalog.negotiate("es, fr;q=0.5, de;q=0.2"), "fr");
+    assert_eq!(catalog.negotiate("nl;q=0, en-GB"), "en");
+    assert_eq!(catalog.negotiate("es"), DEFAULT_LANGUAGE);
+
+    assert_eq!(
+        catalog.translate("de", "table_not_found", &[]).unwrap(),
+        "Tabelle nicht gefunden"
+    );
+    assert_eq!(
+        catalog
+            .translate("nl", "key_is_locked", &[("owner", "alice")])
+            .unwrap(),
+        "De sleutel is vergrendeld door alice"
+    );
+    assert_eq!(
+        catalog
+            .translate("fr", LEGAL_HOLD_CODE, &[("reason", "audit 2026")])
+            .unwrap(),
+        "La table est sous conservation légale : audit 2026"
+    );
+    assert_eq!(catalog.translate("de", "something_unexpected", &[]), None);
+    assert_eq!(catalog.message("data_not_found", &[]), "Data not found");
+    assert_eq!(
+        Catalog::with_language("de", || catalog.message("data_not_found", &[])),
+        "Daten nicht gefunden"
+    );
+    assert_eq!(
+        Catalog::with_language("de", || catalog.message("something_unexpected", &[])),
+        "something_unexpected"
+    );
+
+    let en = "locked = Key is locked by { $owner }";
+    assert!(
+        Catalog::from_sources([("en", en), ("de", "locked = Gesperrt von { $user }")]).is_err()
+    );
+    assert!(Catalog::from_sources([("en", en), ("de", "missing = Fehlt")]).is_err());
+    assert!(Catalog::from_sources([("en", "pair = { $a")]).is_err());
+    assert!(Catalog::from_sources([("de", "locked = Gesperrt")]).is_err());
+}
+
+#[actix_web::test]
+async fn sync_changes() {
+    let test = TestDatabase::new().await;
+    let db = &test.db;
+    db.set_data("drawings", "a", "1").await.unwrap();
+    db.set_data("drawings", "b", "1").await.unwrap();
+    let pulled = db.pull_changes(0, None, 100).await.unwrap();
+    let checkpoint = pulled.last().unwrap().version;
+    let base_a = db.sync_version("drawings", "a").await.unwrap();
+
+    db.set_data("drawings", "a", "2").await.unwrap();
+    db.delete_data("drawings", "b").await.unwrap();
+    let pulled = db.pull_changes(checkpoint, None, 100).await.unwrap();
+    let changes: Vec<(&str, Option<&str>)> = pulled
+        .iter()
+        .map(|change| (change.key.as_str(), change.value.as_deref()))
+        .collect();
+    assert_eq!(changes, [("a", Some("2")), ("b", None)]);
+    assert!(db
+        .pull_changes(0, Some("other"), 100)
+        .await
+        .unwrap()
+        .is_empty());
+
+    let change = |key: &str, base_version, value: &str| ClientChange {
+        table: "drawings".to_string(),
+        key: key.to_string(),
+        base_version,
+        base: None,
+        value: Some(value.to_string()),
+    };
+    let outcome = db
+        .apply_client_change(&change("a", base_a, "3"), SyncResolution::ServerWins)
+        .await
+        .unwrap();
+    assert_eq!(outcome.status, SyncStatus::Rejected);
+    assert_eq!(outcome.value.as_deref(), Some("2"));
+    let outcome = db
+        .apply_client_change(
+            &change("a", outcome.version, "3"),
+            SyncResolution::ServerWins,
+        )
+        .await
+        .unwrap();
+    assert_eq!(outcome.status, SyncStatus::Applied);
+    let outcome = db
+        .apply_client_change(&change("a", base_a, "3"), SyncResolution::ServerWins)
+        .await
+        .unwrap();
+    assert_eq!(outcome.status, SyncStatus::Applied);
+    let outcome = db
+        .apply_client_change(&change("c", None, "1"), SyncResolution::ClientWins)
+        .await
+        .unwrap();
+    assert_eq!(outcome.status, SyncStatus::Applied);
+    assert_eq!(
+        db.get_data("drawings", "c").await.unwrap().as_deref(),
+        Some("1")
+    );
+}
+
+#[actix_web::test]
+async fn value_history_deltas() {
+    let test = TestDatabase::new().await;
+    let db = Database::new(&TestDatabase::config(&test.dir))
+        .await
+        .unwrap()
+        .with_history(HistoryConfig {
+            depth: 4,
+            deltas: true,
+            ..HistoryConfig::default()
+        });
+
+    let drawing =
+        |revision: u32| format!("{}-rev{}-{}", "a".repeat(100), revision, "b".repeat(100));
+    db.set_data("drawings", "d1", &drawing(1)).await.unwrap();
+    db.set_data("drawings", "d1", &drawing(2)).await.unwrap();
+    db.delete_data("drawings", "d1").await.unwrap();
+    db.set_data("drawings", "d1", &drawing(3)).await.unwrap();
+    db.set_data("drawings", "d1", &drawing(4)).await.unwrap();
+
+    let raw = sqlx::SqlitePool::connect(TestDatabase::config(&test.dir).url.as_deref().unwrap())
+        .await
+        .unwrap();
+    let stored: Vec<(i64, bool)> = sqlx::query_as(
+        "SELECT LENGTH(value), delta FROM xcloud_history WHERE value IS NOT NULL ORDER BY seq DESC",
+    )
+    .fetch_all(&raw)
+    .await
+    .unwrap();
+    assert_eq!(
+        stored.iter().map(|(_, delta)| *delta).collect::<Vec<_>>(),
+        [false, true, false]
+    );
+    assert!(stored[1].0 < stored[0].0 / 2);
+
+    let values: Vec<Option<String>> = db
+        .history("drawings", "d1", None)
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|entry| entry.value)
+        .collect();
+    assert_eq!(
+        values,
+        [Some(drawing(4)), Some(drawing(3)), None, Some(drawing(2))]
+    );
+    let now = db.history("drawings", "d1", Some(1)).await.unwrap()[0].changed_at;
+    assert_eq!(
+        db.get_data_at("drawings", "d1", now).await.unwrap(),
+        Some(drawing(4))
+    );
+}
+
+#[test]
+fn value_patch_delta() {
+    let base = "héllo wörld";
+    for value in ["héllo wörld", "héllo brave wörld", "wörld", "", "hé", "ö"] {
+        let delta = ValuePatch::delta(base, value);
+        assert_eq!(ValuePatch::Delta(delta).apply(base).unwrap(), value);
+    }
+    assert_eq!(
+        ValuePatch::delta("abcdef", "abXYef"),
+        [
+            DeltaOp::Copy(0, 2),
+            DeltaOp::Insert("XY".to_string()),
+            DeltaOp::Copy(4, 2)
+        ]
+    );
+    assert!(ValuePatch::Delta(vec![DeltaOp::Copy(4, 10)])
+        .apply("short")
+        .is_err());
+    assert!(ValuePatch::Delta(vec![DeltaOp::Copy(2, 1)])
+        .apply("hé")
+        .is_err());
+}
+
+#[actix_web::test]
+async fn schema_locks() {
+    let test = TestDatabase::new().await;
+    let db = &test.db;
+
+    assert!(db.schema_lock("walls").await.unwrap().is_none());
+    assert!(db
+        .acquire_schema_lock("walls", "delete_table", 60)
+        .await
+        .unwrap()
+        .is_ok());
+    let held = db
+        .acquire_schema_lock("walls", "create_table", 60)
+        .await
+        .unwrap()
+        .unwrap_err();
+    assert_eq!(held.operation, "delete_table");
+    assert_eq!(
+        db.schema_lock("walls").await.unwrap().unwrap().table,
+        "walls"
+    );
+    assert!(db.schema_lock("doors").await.unwrap().is_none());
+
+    db.release_schema_lock("walls").await.unwrap();
+    assert!(db.schema_lock("walls").await.unwrap().is_none());
+
+    assert!(db
+        .acquire_schema_lock("walls", "delete_table", -1)
+        .await
+        .unwrap()
+        .is_ok());
+    assert!(db.schema_lock("walls").await.unwrap().is_none());
+    assert!(db
+        .acquire_schema_lock("walls", "create_table", 60)
+        .await
+        .unwrap()
+        .is_ok());
+}
+
+#[actix_web::test]
+async fn write_behind() {
+    let test = TestDatabase::new().await;
+    let db = Database::new(&TestDatabase::config(&test.dir))
+        .await
+        .unwrap()
+        .with_history(HistoryConfig {
+            depth: 10,
+            ..HistoryConfig::default()
+        })
+        .with_write_behind(WriteBehindConfig {
+            tables: HashMap::from([("telemetry".to_string(), 60_000)]),
+            max_pending: 3,
+        });
+
+    for value in ["1", "2", "3"] {
+        db.set_data("telemetry", "rpm", value).await.unwrap();
+    }
+    db.set_data("telemetry", "temp", "20").await.unwrap();
+    db.set_data("settings", "mode", "eco").await.unwrap();
+    assert!(db.list_keys("telemetry", 0, None).await.unwrap().is_empty());
+    assert_eq!(db.list_keys("settings", 0, None).await.unwrap().len(), 1);
+    assert_eq!(db.flush_writes(false).await.unwrap(), 0);
+
+    assert_eq!(
+        db.get_data("telemetry", "rpm").await.unwrap().as_deref(),
+        Some("3")
+    );
+    assert_eq!(db.history("telemetry", "rpm", None).await.unwrap().len(), 1);
+
+    db.set_data("telemetry", "temp", "21").await.unwrap();
+    db.delete_data("telemetry", "temp").await.unwrap();
+    assert_eq!(db.flush_writes(true).await.unwrap(), 0);
+    assert_eq!(db.get_data("telemetry", "temp").await.unwrap(), None);
+
+    db.set_data("telemetry", "a", "1").await.unwrap();
+    db.set_data("telemetry", "b", "1").await.unwrap();
+    assert_eq!(db.list_keys("telemetry", 0, None).await.unwrap().len(), 1);
+    db.set_data("telemetry", "c", "1").await.unwrap();
+    assert_eq!(db.list_keys("telemetry", 0, None).await.unwrap().len(), 4);
+}
+
+#[actix_web::test]
+async fn pool_recycling() {
+    let ctx = TestDatabase::new().await;
+    let db = &ctx.db;
+    db.set_data("items", "a", "1").await.unwrap();
+
+    // sqlx returns released connections to the pool in the background.
+    let states = tokio::time::timeout(Duration::from_secs(5), async {
+        loop {
+            let states = db.pool_states();
+            if states[0].in_use == 0 {
+                break states;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .expect("Connections were not returned to the pool");
+    assert_eq!(states.len(), 1);
+    assert_eq!(states[0].role, PoolRole::Shard);
+    let idle = states[0].idle;
+    assert!(idle > 0);
+    assert_eq!(idle, states[0].connections as usize);
+
+    assert_eq!(db.recycle_pools().await, idle);
+    assert_eq!(
+        db.get_data("items", "a").await.unwrap().as_deref(),
+        Some("1")
+    );
+
+    let memory = Database::in_memory().await.unwrap();
+    assert_eq!(memory.recycle_pools().await, 0);
+    assert_eq!(memory.pool_states()[0].connections, 1);
+}
+
+#[test]
+fn capture_sanitizing() {
+    let config = CaptureConfig {
+        max_body_bytes: 40,
+        ..CaptureConfig::default()
+    };
+    assert!(!TrafficCapture::captures(&config, "/set_data"));
+    let config = CaptureConfig {
+        enabled: true,
+        routes: vec!["/set_data".to_string()],
+        ..config
+    };
+    assert!(TrafficCapture::captures(&config, "/set_data"));
+    assert!(!TrafficCapture::captures(&config, "/get_data"));
+
+    assert_eq!(
+        TrafficCapture::sanitize_path(&config, "/signed/get_data?key=a&signature=abc"),
+        "/signed/get_data?key=a&signature=<redacted>"
+    );
+    assert_eq!(
+        TrafficCapture::sanitize_body(&config, br#"{"nested":[{"Token":"t"}]}"#),
+        r#"{"nested":[{"Token":"<redacted>"}]}"#
+    );
+    assert_eq!(
+        TrafficCapture::sanitize_body(&config, "é".repeat(30).as_bytes()).len(),
+        40
+    );
+
+    let capture = TrafficCapture::default();
+    for path in ["/a", "/b", "/c"] {
+        capture.record(
+            &CaptureConfig {
+                capacity: 2,
+                ..config.clone()
+            },
+            Capture {
+                timestamp: 0,
+                method: "GET".to_string(),
+                path: path.to_string(),
+                request_headers: Default::default(),
+                request_body: String::new(),
+                status: 200,
+                response_body: String::new(),
+                duration_ms: 0,
+            },
+        );
+    }
+    let paths: Vec<_> = capture.entries().into_iter().map(|c| c.path).collect();
+    assert_eq!(paths, ["/b", "/c"]);
+    assert_eq!(capture.clear(), 2);
+}
+
+#[actix_web::test]
+async fn time_series() {
+    let test = TestDatabase::new().await;
+    let db = &test.db;
+    let series = TimeSeries {
+        name: "sensors".to_string(),
+        bucket_secs: 60,
+        downsample_after_secs: Some(0),
+    };
+    assert!(db.create_time_series(&series).await.unwrap());
+    assert!(!db.create_time_series(&series).await.unwrap());
+    assert_eq!(db.time_series().await.unwrap(), [series]);
+
+    let point = |key: &str, timestamp, value| SeriesPoint {
+        key: key.to_string(),
+        timestamp,
+        value,
+    };
+    let points = [
+        point("a", 1_000, 1.0),
+        point("a", 2_000, 3.0),
+        point("a", 61_000, 5.0),
+        point("b", 1_000, 9.0),
+    ];
+    assert!(db.append_points("sensors", &points).await.unwrap());
+    assert!(!db.append_points("missing", &points).await.unwrap());
+    assert_eq!(
+        db.read_points("sensors", "a", 1_500, i64::MAX, None)
+            .await
+            .unwrap()
+            .unwrap(),
+        [point("a", 2_000, 3.0), point("a", 61_000, 5.0)]
+    );
+
+    let expected = [
+        SeriesBucket {
+            start: 0,
+            count: 2,
+            avg: 2.0,
+            min: 1.0,
+            max: 3.0,
+        },
+        SeriesBucket {
+            start: 60_000,
+            count: 1,
+            avg: 5.0,
+            min: 5.0,
+            max: 5.0,
+        },
+    ];
+    let aggregate = || db.aggregate_points("sensors", "a", 0, i64::MAX, 60_000);
+    assert_eq!(aggregate().await.unwrap().unwrap(), expected);
+
+    assert_eq!(db.downsample_time_series().await.unwrap(), 4);
+    assert!(db
+        .read_points("sensors", "a", 0, i64::MAX, None)
+        .await
+        .unwrap()
+        .unwrap()
+        .is_empty());
+    assert_eq!(aggregate().await.unwrap().unwrap(), expected);
+
+    assert!(db
+        .append_points("sensors", &[point("a", 30_000, 7.0)])
+        .await
+        .unwrap());
+    assert_eq!(db.downsample_time_series().await.unwrap(), 1);
+    assert_eq!(
+        aggregate().await.unwrap().unwrap()[0],
+        SeriesBucket {
+            start: 0,
+            count: 3,
+            avg: 11.0 / 3.0,
+            min: 1.0,
+            max: 7.0,
+        }
+    );
+
+    assert!(db.delete_time_series("sensors").await.unwrap());
+    assert!(!db.delete_time_series("sensors").await.unwrap());
+    assert!(db
+        .aggregate_points("sensors", "a", 0, i64::MAX, 60_000)
+        .await
+        .unwrap()
+        .is_none());
+}
+
+#[actix_web::test]
+async fn aggregate_views() {
+    let test = TestDatabase::new().await;
+    let db = &test.db;
+    db.set_data("orders", "eu:1", r#"{"total": 10}"#)
+        .await
+        .unwrap();
+    let view = AggregateView {
+        name: "totals".to_string(),
+        table: "orders".to_string(),
+        delimiter: ":".to_string(),
+        pointer: "/total".to_string(),
+    };
+    assert!(db.create_view(&view).await.unwrap());
+    assert!(!db.create_view(&view).await.unwrap());
+    assert_eq!(db.views().await.unwrap(), [view]);
+
+    db.set_data("orders", "eu:2", r#"{"total": "5.5"}"#)
+        .await
+        .unwrap();
+    db.set_data("orders", "us:1", r#"{"total": 7}"#)
+        .await
+        .unwrap();
+    db.set_data("orders", "eu:1", r#"{"total": 20}"#)
+        .await
+        .unwrap();
+    db.set_data("orders", "misc", "not json").await.unwrap();
+    let groups = |prefix: Option<&'static str>| db.view_groups("totals", prefix);
+    let summary = |groups: Vec<ViewGroup>| -> Vec<(String, i64, f64)> {
+        groups
+            .into_iter()
+            .map(|group| (group.prefix, group.count, group.sum))
+            .collect()
+    };
+    assert_eq!(
+        summary(groups(None).await.unwrap().unwrap()),
+        [
+            ("eu".to_string(), 2, 25.5),
+            ("misc".to_string(), 1, 0.0),
+            ("us".to_string(), 1, 7.0)
+        ]
+    );
+
+    db.delete_data("orders", "us:1").await.unwrap();
+    db.delete_data("orders", "eu:2").await.unwrap();
+    assert_eq!(
+        summary(groups(Some("e")).await.unwrap().unwrap()),
+        [("eu".to_string(), 1, 20.0)]
+    );
+    assert!(groups(Some("us")).await.unwrap().unwrap().is_empty());
+
+    db.delete_table("orders").await.unwrap();
+    assert!(groups(None).await.unwrap().unwrap().is_empty());
+    assert!(db.delete_view("totals").await.unwrap());
+    assert!(groups(None).await.unwrap().is_none());
+}
+
+#[actix_web::test]
+async fn legal_holds() {
+    let test = TestDatabase::new().await;
+    let db = &test.db;
+    db.set_data("audit", "a", "1").await.unwrap();
+    assert!(db.place_legal_hold("audit", "case 42").await.unwrap());
+    assert!(!db.place_legal_hold("audit", "case 43").await.unwrap());
+    let holds = db.legal_holds().await.unwrap();
+    assert_eq!(holds.len(), 1);
+    assert_eq!(holds[0].reason, "case 42");
+    assert_eq!(
+        db.legal_hold("audit").await.unwrap(),
+        holds.first().cloned()
+    );
+
+    let policy = TablePolicy {
+        table: "audit".to_string(),
+        max_age_secs: None,
+        max_rows: Some(0),
+    };
+    db.set_table_policy(&policy).await.unwrap();
+    assert!(db.apply_table_policy(&policy).await.unwrap().is_empty());
+    assert!(db.lift_legal_hold("audit").await.unwrap());
+    assert!(!db.lift_legal_hold("audit").await.unwrap());
+    assert_eq!(db.apply_table_policy(&policy).await.unwrap(), ["a"]);
+}
+
+#[test]
+fn totp_codes() {
+    // The SHA1 test vectors of RFC 6238, truncated to 6 digits.
+    let totp = Totp::new("gezd gnbv gy3t qojq gezd gnbv gy3t qojq").unwrap();
+    assert_eq!(totp.code(59), "287082");
+    assert_eq!(totp.code(1111111109), "081804");
+    assert_eq!(totp.code(1234567890), "005924");
+    assert_eq!(totp.verify("081804", 1111111109 + 30, None), Some(37037036));
+    assert_eq!(totp.verify("081804", 1111111109 + 60, None), None);
+    assert_eq!(totp.verify("000000", 1111111109, None), None);
+    assert!(Totp::new("not base32!").is_err());
+}
+
+#[test]
+fn totp_replay() {
+    let totp = Totp::new("JBSWY3DPEHPK3PXP").unwrap();
+    let code = totp.code(1000);
+    let step = totp.verify(&code, 1000, None).unwrap();
+    assert_eq!(totp.verify(&code, 1000, Some(step)), None);
+    assert_eq!(totp.verify(&code, 1010, Some(step)), None);
+    let next = totp.code(1030);
+    assert_eq!(totp.verify(&next, 1030, Some(step)), Some(step + 1));
+    assert_eq!(totp.verify(&code, 1000, Some(step - 1)), Some(step));
+}
+
+#[actix_web::test]
+async fn privacy_lookups() {
+    let test = TestDatabase::new().await;
+    let db = &test.db;
+    let identities = ["u1".to_string(), "ana@example.com".to_string()];
+    db.add_comment("walls", "w1", None, "u1", "Too thin?")
+        .await
+        .unwrap();
+    db.add_comment("walls", "w1", None, "bob", "Ask ana@example.com")
+        .await
+        .unwrap();
+    db.add_comment("walls", "w1", None, "bob", "Fine")
+        .await
+        .unwrap();
+    db.acquire_lock("walls", "w1", "u1", 60)
+        .await
+        .unwrap()
+        .unwrap();
+    db.acquire_lock("walls", "w2", "bob", 60)
+        .await
+        .unwrap()
+        .unwrap();
+
+    assert_eq!(db.comments_of(&identities).await.unwrap().len(), 2);
+    assert_eq!(db.locks_of(&identities).await.unwrap()[0].key, "w1");
+    assert_eq!(
+        db.anonymize_comments(&identities, "<anonymized>")
+            .await
+            .unwrap(),
+        2
+    );
+    let comments = db.comments("walls", "w1").await.unwrap();
+    assert_eq!(comments[0].author, "<anonymized>");
+    assert_eq!(comments[1].author, "bob");
+    assert_eq!(comments[1].body, "Ask <anonymized>");
+    assert!(db.comments_of(&identities).await.unwrap().is_empty());
+    assert_eq!(db.release_locks_of(&identities).await.unwrap(), 1);
+    assert!(db.get_lock("walls", "w2").await.unwrap().is_some());
+}
+
+#[actix_web::test]
+async fn cluster_leadership() {
+    let test = TestDatabase::new().await;
+    let db = &test.db;
+
+    assert!(db
+        .cluster_heartbeat("a", "10.0.0.1:8080", 60)
+        .await
+        .unwrap());
+    assert!(!db
+        .cluster_heartbeat("b", "10.0.0.2:8080", 60)
+        .await
+        .unwrap());
+    assert!(db
+        .cluster_heartbeat("a", "10.0.0.1:8080", 60)
+        .await
+        .unwrap());
+    let members = db.cluster_members(60).await.unwrap();
+    assert_eq!(members.len(), 2);
+    assert_eq!(members[0].instance_id, "a");
+    assert!(members[0].leader);
+    assert!(!members[1].leader);
+
+    db.leave_cluster("a").await.unwrap();
+    assert!(db
+        .cluster_heartbeat("b", "10.0.0.2:8080", 60)
+        .await
+        .unwrap());
+    assert!(!db
+        .cluster_heartbeat("a", "10.0.0.1:8080", 60)
+        .await
+        .unwrap());
+
+    // An expired lease is taken over by the next instance reporting itself.
+    assert!(db.cluster_heartbeat("b", "10.0.0.2:8080", 0).await.unwrap());
+    assert!(db
+        .cluster_heartbeat("a", "10.0.0.1:8080", 60)
+        .await
+        .unwrap());
+}
+
+#[test]
+fn mqtt_routes() {
+    let route = |filter: &str, key: &str| MqttRoute {
+        filter: filter.to_string(),
+        table: "sensors".to_string(),
+        key: key.to_string(),
+    };
+    let levels = route("plant/+/temperature", "{1}");
+    assert_eq!(
+        levels.key_for("plant/oven-3/temperature"),
+        Some("oven-3".to_string())
+    );
+    assert_eq!(levels.key_for("plant/oven-3/humidity"), None);
+    assert_eq!(levels.key_for("plant/oven-3/temperature/raw"), None);
+
+    let any = route("plant/#", "{topic}");
+    assert_eq!(any.key_for("plant"), Some("plant".to_string()));
+    assert_eq!(
+        any.key_for("plant/line-1/press"),
+        Some("plant/line-1/press".to_string())
+    );
+    assert_eq!(any.key_for("office/door"), None);
+    assert_eq!(route("plant/#", "").key_for("plant/a"), None);
+}
+
+#[tokio::test]
+async fn integrations() {
+    let test = TestDatabase::new().await;
+    let db = &test.db;
+    let mut integration = Integration {
+        name: "weather".to_string(),
+        url: "https://weather.example.com/stations".to_string(),
+        interval_secs: 300,
+        authorization: Some("Bearer secret".to_string()),
+        table: "stations".to_string(),
+        items_pointer: "/stations".to_string(),
+        key_pointer: "/id".to_string(),
+        value_pointer: "/reading".to_string(),
+        last_run_at: None,
+        next_run_at: 0,
+        last_rows: 0,
+        last_error: None,
+    };
+    db.put_integration(&integration).await.unwrap();
+    let due = db.integrations(true).await.unwrap();
+    assert_eq!(due.len(), 1);
+    assert_eq!(due[0].authorization.as_deref(), Some("Bearer secret"));
+    assert_eq!(due[0].last_run_at, None);
+
+    db.record_integration_run("weather", 2, Some("Skipped 1 item(s): no key at \"/id\""))
+        .await
+        .unwrap();
+    assert!(db.integrations(true).await.unwrap().is_empty());
+    let all = db.integrations(false).await.unwrap();
+    assert_eq!(all[0].last_rows, 2);
+    assert!(all[0].last_run_at.is_some());
+    assert!(all[0].next_run_at > all[0].last_run_at.unwrap());
+    assert!(all[0].last_error.is_some());
+
+    // Replacing an integration keeps the outcome of its last poll but makes it due again.
+    integration.interval_secs = 60;
+    db.put_integration(&integration).await.unwrap();
+    let due = db.integrations(true).await.unwrap();
+    assert_eq!(due[0].interval_secs, 60);
+    assert_eq!(due[0].last_rows, 2);
+
+    db.record_integration_run("weather", 3, None).await.unwrap();
+    assert!(db.schedule_integration("weather").await.unwrap());
+    assert_eq!(db.integrations(true).await.unwrap()[0].last_error, None);
+    assert!(!db.schedule_integration("missing").await.unwrap());
+
+    assert!(db.delete_integration("weather").await.unwrap());
+    assert!(!db.delete_integration("weather").await.unwrap());
+    assert!(db.integrations(false).await.unwrap().is_empty());
+}
+
+#[test]
+fn integration_extract() {
+    let integration = Integration {
+        name: "weather".to_string(),
+        url: "https://weather.example.com/stations".to_string(),
+        interval_secs: 300,
+        authorization: None,
+        table: "stations".to_string(),
+        items_pointer: "/stations".to_string(),
+        key_pointer: "/id".to_string(),
+        value_pointer: "/reading".to_string(),
+        last_run_at: None,
+        next_run_at: 0,
+        last_rows: 0,
+        last_error: None,
+    };
+    let body = serde_json::json!({"stations": [
+        {"id": "s1", "reading": {"temperature": 21.5}},
+        {"id": 2, "reading": "calm"},
+        {"reading": {"temperature": 19}},
+    ]});
+
+    let extracted =
+        IntegrationScheduler::extract(&integration, &body, &ValueValidator::new(ValueType::String))
+            .unwrap();
+    assert_eq!(
+        extracted,
+        ExtractedRows {
+            rows: vec![
+                ("s1".to_string(), "{\"temperature\":21.5}".to_string()),
+                ("2".to_string(), "calm".to_string()),
+            ],
+            skipped: 1,
+            reason: Some("no key at \"/id\"".to_string()),
+        }
+    );
+
+    // Strings are encoded when the table holds JSON, and values of another type are skipped.
+    let extracted =
+        IntegrationScheduler::extract(&integration, &body, &ValueValidator::new(ValueType::Json))
+            .unwrap();
+    assert_eq!(extracted.rows[1].1, "\"calm\"");
+    let extracted = IntegrationScheduler::extract(
+        &integration,
+        &body,
+        &ValueValidator::new(ValueType::Integer),
+    )
+    .unwrap();
+    assert!(extracted.rows.is_empty());
+    assert_eq!(extracted.skipped, 3);
+
+    assert!(IntegrationScheduler::extract(
+        &integration,
+        &serde_json::json!({"data": []}),
+        &ValueValidator::new(ValueType::String)
+    )
+    .is_err());
+}
+
+#[tokio::test]
+async fn alerts() {
+    let test = TestDatabase::new().await;
+    let db = &test.db;
+    db.set_data("readings", "r1", &"x".repeat(100))
+        .await
+        .unwrap();
+    let stats = Arc::new(RequestStats::new());
+    let breaker = Arc::new(CircuitBreaker::new(CircuitBreakerConfig {
+        failure_threshold: 1,
+        open_secs: 30,
+        probe_interval_secs: 1,
+    }));
+    let config = AlertConfig {
+        min_requests: 10,
+        storage_quota_bytes: Some(100),
+        ..AlertConfig::default()
+    };
+    let alerter = Alerter::new(
+        config,
+        stats.clone(),
+        breaker.clone(),
+        JobQueue::new(1, 1, Duration::ZERO),
+    );
+    assert!(alerter.evaluate(None).await.unwrap().is_empty());
+
+    for status in [200; 8].into_iter().chain([503; 2]) {
+        stats.record(status);
+    }
+    breaker.record_failure();
+    let alerts = alerter.evaluate(Some(db)).await.unwrap();
+    let kinds: Vec<AlertKind> = alerts.iter().map(|alert| alert.kind).collect();
+    assert_eq!(
+        kinds,
+        vec![
+            AlertKind::ErrorRate,
+            AlertKind::CircuitBreaker,
+            AlertKind::StorageQuota
+        ]
+    );
+    assert_eq!(alerts[0].value, 0.2);
+    assert_eq!(alerter.dispatch(alerts.clone()), alerts);
+
+    // The error rate is measured since the previous check, and alerts already sent are
+    // suppressed during the cooldown.
+    for _ in 0..5 {
+        stats.record(500);
+    }
+    let alerts = alerter.evaluate(Some(db)).await.unwrap();
+    assert_eq!(alerts.len(), 1);
+    assert_eq!(alerts[0].kind, AlertKind::StorageQuota);
+    assert!(alerter.dispatch(alerts).is_empty());
+    let recent = alerter.recent();
+    assert_eq!(recent.len(), 3);
+    assert_eq!(recent[0].kind, AlertKind::StorageQuota);
+}
+
+#[actix_web::test]
+async fn history_compaction() {
+    let test = TestDatabase::new().await;
+    let db = Database::new(&TestDatabase::config(&test.dir))
+        .await
+        .unwrap()
+        .with_history(HistoryConfig {
+            depth: 10,
+            deltas: true,
+            ..HistoryConfig::default()
+        });
+    let drawing =
+        |revision: u32| format!("{}-rev{}-{}", "a".repeat(100), revision, "b".repeat(100));
+    for revision in 1..=6 {
+        db.set_data("drawings", "d1", &drawing(revision))
+            .await
+            .unwrap();
+    }
+
+    // Backdate the versions and a snapshot, the window being the last 100 seconds.
+    let raw = sqlx::SqlitePool::connect(TestDatabase::config(&test.dir).url.as_deref().unwrap())
+        .await
+        .unwrap();
+    let now: i64 = sqlx::query_scalar("SELECT CAST(strftime('%s', 'now') AS INTEGER)")
+        .fetch_one(&raw)
+        .await
+        .unwrap();
+    let seqs: Vec<i64> = sqlx::query_scalar("SELECT seq FROM xcloud_history ORDER BY seq")
+        .fetch_all(&raw)
+        .await
+        .unwrap();
+    for (seq, age) in seqs.iter().zip([500, 400, 300, 200, 10, 0]) {
+        sqlx::query("UPDATE xcloud_history SET changed_at = ?1 WHERE seq = ?2")
+            .bind(now - age)
+            .bind(seq)
+            .execute(&raw)
+            .await
+            .unwrap();
+    }
+    sqlx::query("INSERT INTO xcloud_snapshots (created_at, scheduled) VALUES (?1, 0)")
+        .bind(now - 350)
+        .execute(&raw)
+        .await
+        .unwrap();
+
+    assert_eq!(db.history_tables().await.unwrap(), vec!["drawings"]);
+    assert_eq!(db.compact_history("drawings", 100).await.unwrap(), 2);
+    let values: Vec<String> = db
+        .history("drawings", "d1", None)
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|entry| entry.value.unwrap())
+        .collect();
+    assert_eq!(values, vec![drawing(6), drawing(5), drawing(4), drawing(2)]);
+    assert_eq!(
+        db.get_data_at("drawings", "d1", now - 350).await.unwrap(),
+        Some(drawing(2))
+    );
+    assert_eq!(
+        db.get_data_at("drawings", "d1", now - 150).await.unwrap(),
+        Some(drawing(4))
+    );
+    assert_eq!(db.compact_history("drawings", 100).await.unwrap(), 0);
+}
+
+#[tokio::test]
+async fn consistency_tokens() {
+    let test = TestDatabase::new().await;
+    let replica_url = format!(
+        "sqlite://{}?mode=rwc",
+        test.dir.path().join("replica.db").display()
+    );
+    let replica = Database::new(&DatabaseConfig {
+        url: Some(replica_url.clone()),
+        ..DatabaseConfig::default()
+    })
+    .await
+    .unwrap();
+    replica.set_data("carts", "c1", "stale").await.unwrap();
+    let db = Database::new(&DatabaseConfig {
+        replicas: vec![replica_url],
+        ..TestDatabase::config(&test.dir)
+    })
+    .await
+    .unwrap();
+    let consistency = db.consistency();
+    assert_eq!(consistency.token(), 0);
+
+    db.set_data("carts", "c0", "first").await.unwrap();
+    db.set_data("carts", "c1", "fresh").await.unwrap();
+    let token = consistency.token();
+    assert_eq!(token, 2);
+    consistency.refresh().await;
+
+    assert_eq!(
+        db.get_data("carts", "c1").await.unwrap(),
+        Some("stale".to_string())
+    );
+    assert_eq!(
+        Consistency::scope(Some(token), db.get_data("carts", "c1"))
+            .await
+            .unwrap(),
+        Some("fresh".to_string())
+    );
+
+    replica.set_data("carts", "c2", "caught up").await.unwrap();
+    replica.set_data("carts", "c3", "caught up").await.unwrap();
+    consistency.refresh().await;
+    assert_eq!(
+        Consistency::scope(Some(token), db.get_data("carts", "c1"))
+            .await
+            .unwrap(),
+        Some("stale".to_string())
+    );
+}
+
+#[tokio::test]
+async fn sql_console() {
+    let test = TestDatabase::new().await;
+    let db = &test.db;
+    db.set_data("walls", "w1", "12").await.unwrap();
+    db.set_data("walls", "w2", "24").await.unwrap();
+
+    for sql in [
+        "",
+        "DELETE FROM walls",
+        "SELECT 1; DROP TABLE walls",
+        "WITH copy AS (SELECT * FROM walls) INSERT INTO walls SELECT * FROM copy",
+        "select key from walls where key in (select key from walls); pragma query_only = 0",
+        "SELECT 1 /* unterminated",
+    ] {
+        assert!(
+            matches!(
+                SqlConsole::validate(sql),
+                Err(sqlx::Error::InvalidArgument(_))
+            ),
+            "{}",
+            sql
+        );
+    }
+    assert_eq!(
+        SqlConsole::validate("SELECT 'DROP TABLE walls' AS text; -- DELETE\n").unwrap(),
+        "SELECT 'DROP TABLE walls' AS text"
+    );
+
+    let result = SqlConsole::run(
+        db.open_read_only().await.unwrap(),
+        "SELECT key, replace(value, '2', '3') AS value, NULL AS note FROM walls ORDER BY key",
+        1,
+        Duration::from_secs(1),
+    )
+    .await
+    .unwrap();
+    assert_eq!(result.columns, vec!["key", "value", "note"]);
+    assert_eq!(
+        serde_json::Value::from(result.rows),
+        serde_json::json!([{"key": "w1", "value": "13", "note": null}])
+    );
+    assert!(result.truncated);
+
+    let mut conn = db.open_read_only().await.unwrap();
+    assert!(sqlx::query("DELETE FROM walls")
+        .execute(&mut conn)
+        .await
+        .is_err());
+    assert_eq!(
+        db.get_data("walls", "w1").await.unwrap(),
+        Some("12".to_string())
+    );
+
+    let timed_out = SqlConsole::run(
+        db.open_read_only().await.unwrap(),
+        "WITH RECURSIVE n(i) AS (SELECT 1 UNION ALL SELECT i + 1 FROM n) SELECT count(*) FROM n",
+        10,
+        Duration::from_millis(50),
+    )
+    .await;
+    assert!(
+        matches!(timed_out, Err(sqlx::Error::InvalidArgument(reason)) if reason.contains("50 ms"))
+    );
+}
+
+#[tokio::test]
+async fn seed_fixtures() {
+    let test = TestDatabase::new().await;
+    let db = &test.db;
+    let dir = test.dir.path().join("seeds");
+    std::fs::create_dir(&dir).unwrap();
+    std::fs::write(
+        dir.join("01-walls.json"),
+        r#"{"tables": [{"name": "walls", "value_type": "integer", "keys": {"w1": 12, "w2": "24"}}]}"#,
+    )
+    .unwrap();
+    std::fs::write(
+        dir.join("02-settings.toml"),
+        "[[tables]]\nname = \"settings\"\n\n[tables.keys]\ntheme = \"dark\"\nunits = { length = \"mm\" }\n",
+    )
+    .unwrap();
+    std::fs::write(dir.join("notes.txt"), "ignored").unwrap();
+
+    let report = Seeder::run(db, &dir).await.unwrap();
+    assert_eq!(
+        report,
+        SeedReport {
+            files: 2,
+            tables_created: 2,
+            keys_written: 4,
+            keys_skipped: 0,
+        }
+    );
+    assert_eq!(
+        db.get_value_type("walls").await.unwrap(),
+        ValueType::Integer
+    );
+    assert_eq!(
+        db.get_data("walls", "w1").await.unwrap(),
+        Some("12".to_string())
+    );
+    assert_eq!(
+        db.get_data("settings", "units").await.unwrap(),
+        Some(r#"{"length":"mm"}"#.to_string())
+    );
+
+    db.set_data("walls", "w1", "13").await.unwrap();
+    let report = Seeder::run(db, &dir).await.unwrap();
+    assert_eq!((report.tables_created, report.keys_written), (0, 0));
+    assert_eq!(report.keys_skipped, 4);
+    assert_eq!(
+        db.get_data("walls", "w1").await.unwrap(),
+        Some("13".to_string())
+    );
+
+    std::fs::write(dir.join("03-broken.json"), "{").unwrap();
+    assert!(matches!(
+        Seeder::run(db, &dir).await,
+        Err(AppError::Seed(reason)) if reason.contains("03-broken.json")
+    ));
+    assert!(Seeder::load(&test.dir.path().join("missing"))
+        .unwrap()
+        .is_empty());
+}
+
+#[test]
+fn projection() {
+    let projection = Projection::parse("id, rooms.area ,rooms.area").unwrap();
+    assert_eq!(
+        projection.apply(serde_json::json!({
+            "id": 7,
+            "rooms": [{"area": 12.5, "name": "hall"}, {"name": "void"}, 3],
+            "level": 2
+        })),
+        serde_json::json!({"id": 7, "rooms": [{"area": 12.5}]})
+    );
+    assert_eq!(
+        projection.apply(serde_json::json!("{\"id\": 1, \"level\": 2}")),
+        serde_json::json!({"id": 1})
+    );
+    assert_eq!(
+        projection.apply(serde_json::json!({"level": 2})),
+        serde_json::json!({})
+    );
+    assert_eq!(
+        projection.apply(serde_json::json!("plain text")),
+        serde_json::json!("plain text")
+    );
+    assert_eq!(
+        projection.apply(serde_json::json!(42)),
+        serde_json::json!(42)
+    );
+    assert!(Projection::parse("").is_err());
+    assert!(Projection::parse("a,.b").is_err());
+    let many = (0..65)
+        .map(|i| format!("f{}", i))
+        .collect::<Vec<_>>()
+        .join(",");
+    assert!(Projection::parse(&many).is_err());
+}
+
+#[test]
+fn latency_exemplars() {
+    let trace = "4bf92f3577b34da6a3ce929d0e0e4736";
+    assert_eq!(
+        Exemplar::trace_id(&format!("00-{}-00f067aa0ba902b7-01", trace.to_uppercase())),
+        Some(trace.to_string())
+    );
+    assert_eq!(
+        Exemplar::trace_id("00-00000000000000000000000000000000-00f067aa0ba902b7-01"),
+        None
+    );
+    assert_eq!(Exemplar::trace_id("00-4bf92f35-00f067aa0ba902b7-01"), None);
+    assert_eq!(Exemplar::trace_id("not a traceparent"), None);
+
+    let stats = RequestStats::new();
+    stats.record_latency(Duration::from_millis(3), None);
+    stats.record_latency(Duration::from_millis(70), Some(trace.to_string()));
+    stats.record_latency(Duration::from_secs(20), None);
+    let latency = stats.latency();
+    assert_eq!(latency.count, 3);
+    assert_eq!(latency.buckets.len(), 12);
+    assert_eq!(latency.buckets[0].1, 1);
+    assert_eq!(latency.buckets[4].1, 2);
+    assert_eq!(latency.buckets[4].2.as_ref().unwrap().trace_id, trace);
+    assert!(latency.buckets[3].2.is_none());
+    assert_eq!(latency.buckets[11].1, 3);
+
+    let text = Sample::to_openmetrics(
+        &[Sample {
+            name: "xcloud_requests_total",
+            labels: vec![("class", "2xx".to_string())],
+            value: 3.0,
+            counter: true,
+        }],
+        &latency,
+    );
+    assert!(text.contains("# TYPE xcloud_requests counter\n"));
+    assert!(text.contains("xcloud_requests_total{class=\"2xx\"} 3\n"));
+    assert!(text.contains("# TYPE xcloud_request_duration_seconds histogram\n"));
+    assert!(text.contains(&format!(
+        "xcloud_request_duration_seconds_bucket{{le=\"0.1\"}} 2 # {{trace_id=\"{}\"}} 0.07 ",
+        trace
+    )));
+    assert!(text.contains("xcloud_request_duration_seconds_bucket{le=\"+Inf\"} 3\n"));
+    assert!(text.contains("xcloud_request_duration_seconds_count 3\n"));
+    assert!(text.ends_with("# EOF\n"));
+    assert!(!Sample::latency_to_prometheus(&latency).contains("trace_id"));
+}
+
+#[actix_web::test]
+async fn tiered_storage() {
+    let test = TestDatabase::new().await;
+    let config = TestDatabase::config(&test.dir);
+    let archive = test.dir.path().join("archive");
+    let tiering = TieringConfig {
+        enabled: true,
+        dir: Some(archive.display().to_string()),
+        cold_after_days: 0,
+        ..TieringConfig::default()
+    };
+    let db = Database::new(&config)
+        .await
+        .unwrap()
+        .with_keyring(keyring(&[1], 1))
+        .with_dedup(DedupConfig {
+            enabled: true,
+            min_bytes: 16,
+        })
+        .with_key_stats(KeyStatsConfig {
+            enabled: true,
+            ..KeyStatsConfig::default()
+        })
+        .with_tiering(tiering.clone());
+    let raw = sqlx::SqlitePool::connect(config.url.as_deref().unwrap())
+        .await
+        .unwrap();
+    let tier = |key: &'static str| {
+        sqlx::query_scalar::<_, Option<String>>("SELECT tier FROM parts WHERE key = ?1")
+            .bind(key)
+            .fetch_one(&raw)
+    };
+    let part = "bolt ".repeat(100);
+
+    db.set_data("parts", "a", &part).await.unwrap();
+    db.set_data("parts", "b", "door").await.unwrap();
+    db.set_data("parts", "c", &part).await.unwrap();
+    assert_eq!(
+        TieringJob::run_with(&db, &tiering).await.unwrap(),
+        TieringReport {
+            archived: 3,
+            removed: 0
+        }
+    );
+    assert_eq!(FsBlobStore::new(archive.clone()).list().unwrap().len(), 2);
+    assert_eq!(tier("b").await.unwrap().as_deref(), Some(tiering::TIER));
+    assert_eq!(value_store(&raw).await, (0, 0));
+
+    assert_eq!(
+        db.get_many("parts", &["c".to_string()]).await.unwrap()[0].1,
+        part
+    );
+    assert_eq!(tier("c").await.unwrap().as_deref(), Some(tiering::TIER));
+    assert_eq!(
+        db.get_data("parts", "a").await.unwrap().as_deref(),
+        Some(part.as_str())
+    );
+    assert!(
+        sqlx::query_scalar::<_, bool>("SELECT deduplicated FROM parts WHERE key = 'a'")
+            .fetch_one(&raw)
+            .await
+            .unwrap()
+    );
+    assert_eq!(value_store(&raw).await, (1, 1));
+
+    db.set_data("parts", "b", "window").await.unwrap();
+    assert_eq!(
+        db.remove_unreferenced_archives(std::time::SystemTime::now())
+            .await
+            .unwrap(),
+        1
+    );
+    assert_eq!(
+        db.get_data("parts", "b").await.unwrap().as_deref(),
+        Some("window")
+    );
+    assert_eq!(
+        db.get_data("parts", "c").await.unwrap().as_deref(),
+        Some(part.as_str())
+    );
+
+    db.set_data("parts", "n", &i64::MAX.to_string())
+        .await
+        .unwrap();
+    TieringJob::run_with(&db, &tiering).await.unwrap();
+    assert_eq!(tier("n").await.unwrap().as_deref(), Some(tiering::TIER));
+    let mut rows = db.stream_table("parts").await.unwrap();
+    let mut values = Vec::new();
+    while let Some(row) = rows.recv().await {
+        values.push(row.unwrap());
+    }
+    assert!(values.contains(&("n".to_string(), i64::MAX.to_string())));
+    assert!(matches!(
+        db.increment("parts", "n", 1).await,
+        Err(sqlx::Error::InvalidArgument(_))
+    ));
+    assert_eq!(
+        db.increment("parts", "n", -1).await.unwrap(),
+        Some(i64::MAX - 1)
+    );
+    assert_eq!(tier("n").await.unwrap(), None);
+}
+
+#[actix_web::test]
+async fn feature_flags() {
+    let test = TestDatabase::new().await;
+    let db = &test.db;
+    let flags = FeatureFlags::load(db).await.unwrap();
+    assert!(!flags.is_enabled("soft_delete", None));
+
+    for invalid in [
+        FeatureFlag {
+            name: "soft delete".to_string(),
+            ..FeatureFlag::default()
+        },
+        FeatureFlag {
+            name: "soft_delete".to_string(),
+            percentage: 101,
+            ..FeatureFlag::default()
+        },
+        FeatureFlag {
+            name: "soft_delete".to_string(),
+            routes: vec!["/admin/trash".to_string()],
+            ..FeatureFlag::default()
+        },
+    ] {
+        assert!(matches!(
+            flags.set(db, invalid).await,
+            Err(sqlx::Error::InvalidArgument(_))
+        ));
+    }
+
+    flags
+        .set(
+            db,
+            FeatureFlag {
+                name: "soft_delete".to_string(),
+                tenants: vec!["acme".to_string()],
+                percentage: 25,
+                routes: vec!["/trash".to_string()],
+                ..FeatureFlag::default()
+            },
+        )
+        .await
+        .unwrap();
+    assert!(flags.is_enabled("soft_delete", Some("acme")));
+    assert_eq!(
+        (0..100)
+            .filter(|_| flags.is_enabled("soft_delete", None))
+            .count(),
+        25
+    );
+    let tenants: Vec<String> = (0..200).map(|i| format!("tenant{}", i)).collect();
+    let rollout: Vec<bool> = tenants
+        .iter()
+        .map(|tenant| flags.is_enabled("soft_delete", Some(tenant)))
+        .collect();
+    assert!(tenants
+        .iter()
+        .zip(&rollout)
+        .all(|(tenant, on)| flags.is_enabled("soft_delete", Some(tenant)) == *on));
+    let share = rollout.iter().filter(|on| **on).count();
+    assert!(share > 20 && share < 80, "{} of 200 tenants", share);
+
+    let outside = &tenants[rollout.iter().position(|on| !on).unwrap()];
+    assert_eq!(
+        flags.blocks("/trash/items", Some(outside)),
+        Some("soft_delete".to_string())
+    );
+    assert_eq!(flags.blocks("/trash/items", Some("acme")), None);
+    assert_eq!(flags.blocks("/get_data", Some(outside)), None);
+
+    let reloaded = FeatureFlags::load(db).await.unwrap();
+    assert_eq!(reloaded.list(), flags.list());
+    assert!(flags.remove(db, "soft_delete").await.unwrap());
+    assert!(!flags.remove(db, "soft_delete").await.unwrap());
+    reloaded.refresh(db).await.unwrap();
+    assert!(reloaded.list().is_empty());
+    assert_eq!(flags.blocks("/trash/items", Some(outside)), None);
+}
+
+#[tokio::test]
+async fn read_replicas() {
+    let test = TestDatabase::new().await;
+    let replica_url = format!(
+        "sqlite://{}?mode=rwc",
+        test.dir.path().join("replica.db").display()
+    );
+    let replica = Database::new(&DatabaseConfig {
+        url: Some(replica_url.clone()),
+        ..DatabaseConfig::default()
+    })
+    .await
+    .unwrap();
+    replica
+        .set_data("notes", "n1", "replica copy")
+        .await
+        .unwrap();
+    replica
+        .fulltext_search("notes", "copy", 0, 10)
+        .await
+        .unwrap();
+    test.db
+        .set_data("notes", "n1", "primary copy")
+        .await
+        .unwrap();
+    let db = Database::new(&DatabaseConfig {
+        replicas: vec![replica_url],
+        ..TestDatabase::config(&test.dir)
+    })
+    .await
+    .unwrap();
+
+    assert_eq!(db.get_data("drafts", "d1").await.unwrap(), None);
+    assert!(db
+        .get_many("drafts", &["d1".to_string()])
+        .await
+        .unwrap()
+        .is_empty());
+    assert!(!db
+        .list_tables()
+        .await
+        .unwrap()
+        .contains(&"drafts".to_string()));
+
+    let hits = db.fulltext_search("notes", "copy", 0, 10).await.unwrap();
+    assert_eq!(hits.len(), 1);
+    assert_eq!(hits[0].value, "replica copy");
+}
@@ -0,0 +1,135 @@
+use std::time::{Duration, Instant};
+
+use actix_web::{test, App};
+use serde_json::Value;
+use tempfile::TempDir;
+
+use crate::config::{Config, DatabaseConfig, TieringConfig};
+use crate::db::Database;
+use crate::logging::Logging;
+use crate::server::Server;
+
+/// The maximum time the tests wait for a background import to end.
+const IMPORT_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// A struct holding a database backed by a file in a temporary directory.
+pub struct TestDatabase {
+    pub db: Database,
+    pub dir: TempDir,
+}
+
+/// Implementation of the `TestDatabase` struct.
+impl TestDatabase {
+    /// Creates a new, empty test database.
+    ///
+    /// # Returns
+    ///
+    /// * `TestDatabase` - A new instance of the TestDatabase.
+    pub async fn new() -> Self {
+        let dir = tempfile::tempdir().expect("Failed to create temporary directory");
+        let db = Database::new(&Self::config(&dir))
+            .await
+            .expect("Failed to open test database");
+        TestDatabase { db, dir }
+    }
+
+    /// Returns the database configuration pointing into the given directory.
+    ///
+    /// # Arguments
+    ///
+    /// * `dir` - The directory holding the database file.
+    ///
+    /// # Returns
+    ///
+    /// * `DatabaseConfig` - The database configuration.
+    pub fn config(dir: &TempDir) -> DatabaseConfig {
+        DatabaseConfig {
+            url: Some(format!(
+                "sqlite://{}?mode=rwc",
+                dir.path().join("xcloud.db").display()
+            )),
+            ..DatabaseConfig::default()
+        }
+    }
+}
+
+/// A struct holding a server backed by a test database.
+pub struct TestServer {
+    pub server: Server,
+    _dir: TempDir,
+}
+
+/// Implementation of the `TestServer` struct.
+impl TestServer {
+    /// Creates a new test server with the default configuration.
+    ///
+    /// # Returns
+    ///
+    /// * `TestServer` - A new instance of the TestServer.
+    pub async fn new() -> Self {
+        Self::with_config(Config::default()).await
+    }
+
+    /// Creates a new test server with the given configuration.
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - The configuration, whose database URL is replaced. The replicas are kept in
+    ///   the configuration but not connected to.
+    ///
+    /// # Returns
+    ///
+    /// * `TestServer` - A new instance of the TestServer.
+    pub async fn with_config(mut config: Config) -> Self {
+        let dir = tempfile::tempdir().expect("Failed to create temporary directory");
+        config.database.url = TestDatabase::config(&dir).url;
+        let database = DatabaseConfig {
+            replicas: Vec::new(),
+            ..config.database.clone()
+        };
+        let db = Database::new(&database)
+            .await
+            .expect("Failed to open test database")
+            .with_activity(config.activity.clone())
+            .with_compression(config.compression.clone())
+            .with_dedup(config.dedup.clone())
+            .with_history(config.history.clone())
+            .with_key_stats(config.key_stats.clone())
+            .with_retry(config.retry.clone())
+            .with_slow_query_log(config.slow_queries.clone())
+            .with_tiering(TieringConfig {
+                dir: Some(dir.path().join("archive").display().to_string()),
+                ..config.tiering.clone()
+            });
+        let logging = Logging::init(&config.logging);
+        TestServer {
+            server: Server::new(db, config, logging),
+            _dir: dir,
+        }
+    }
+
+    /// Polls the progress of an import through `GET /imports/{id}` until it is no longer
+    /// running.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The id of the import.
+    ///
+    /// # Returns
+    ///
+    /// * `Value` - The last progress of the import, still running if the timeout elapsed.
+    pub async fn wait_for_import(&self, id: u64) -> Value {
+        let app = test::init_service(App::new().configure(self.server.configure())).await;
+        let deadline = Instant::now() + IMPORT_TIMEOUT;
+        loop {
+            let req = test::TestRequest::get()
+                .uri(&format!("/imports/{}", id))
+                .to_request();
+            let body: Value = test::call_and_read_body_json(&app, req).await;
+            if body["data"]["state"] != "running" || Instant::now() >= deadline {
+                return body["data"].clone();
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+    }
+}
@@ -0,0 +1,3 @@
+mod db;
+mod harness;
+mod routes;
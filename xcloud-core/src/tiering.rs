@@ -0,0 +1,266 @@
+use std::collections::HashSet;
+use std::io;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tokio::sync::Mutex;
+
+use crate::blobs::{BlobStore, FsBlobStore};
+use crate::cluster::Cluster;
+use crate::config::TieringConfig;
+use crate::db::Database;
+use crate::utils::Utils;
+
+/// The tier recorded in the `tier` column of the rows whose value is archived.
+pub const TIER: &str = "archive";
+
+/// The zstd level the archives are compressed with, favouring size as they are rarely read.
+const LEVEL: i32 = 19;
+
+/// A struct storing the values of cold keys as compressed objects outside the database.
+///
+/// Archived rows hold the hex encoded SHA-256 of the stored value in place of the value, with
+/// their `tier` column set to [`TIER`], the stored value being compressed into an object of
+/// that name in the blob store. Values are archived as stored, so encrypted values stay
+/// encrypted in the archive, and identical values share an object. Archives no longer
+/// referenced by any table are removed by the tiering job.
+#[derive(Clone)]
+pub struct ColdStorage {
+    store: Arc<dyn BlobStore>,
+}
+
+/// Implementation of the `ColdStorage` struct.
+impl ColdStorage {
+    /// Creates a cold storage from the tiering configuration.
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - The tiering configuration naming the archive directory.
+    ///
+    /// # Returns
+    ///
+    /// * `ColdStorage` - The cold storage keeping the archives in the configured directory, or
+    ///   the `archive` directory next to the configuration file.
+    pub fn new(config: &TieringConfig) -> Self {
+        let dir = config
+            .dir
+            .as_ref()
+            .map(PathBuf::from)
+            .unwrap_or_else(|| Utils::get_path(&["xcloud", "archive"]));
+        ColdStorage {
+            store: Arc::new(FsBlobStore::new(dir)),
+        }
+    }
+
+    /// Reads the stored value of a row back from the tier holding it.
+    ///
+    /// # Arguments
+    ///
+    /// * `cold` - The cold storage, or `None` if tiering is disabled.
+    /// * `tier` - The tier recorded for the row, or `None` if the row holds the value itself.
+    /// * `stored` - The value held by the row.
+    ///
+    /// # Returns
+    ///
+    /// * `String` - The value as it was stored in the table.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the value is archived while tiering is disabled,
+    /// the tier is unknown, or the archive is missing or corrupt.
+    pub fn open(
+        cold: Option<&ColdStorage>,
+        tier: Option<&str>,
+        stored: String,
+    ) -> io::Result<String> {
+        match (tier, cold) {
+            (None, _) => Ok(stored),
+            (Some(TIER), Some(cold)) => cold.fetch(&stored),
+            (Some(TIER), None) => Err(io::Error::other(
+                "the value is archived while tiering is disabled",
+            )),
+            (Some(tier), _) => Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("unknown tier {}", tier),
+            )),
+        }
+    }
+
+    /// Writes a stored value to the blob store unless it is archived already.
+    ///
+    /// # Arguments
+    ///
+    /// * `stored` - The value as stored in the table.
+    ///
+    /// # Returns
+    ///
+    /// * `String` - The hash to store in place of the value.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the archive cannot be written.
+    pub fn archive(&self, stored: &str) -> io::Result<String> {
+        let hash = hex::encode(Sha256::digest(stored.as_bytes()));
+        self.store
+            .put(&hash, &zstd::encode_all(stored.as_bytes(), LEVEL)?)?;
+        Ok(hash)
+    }
+
+    /// Reads an archived value.
+    ///
+    /// # Arguments
+    ///
+    /// * `hash` - The hash held by the archived row.
+    ///
+    /// # Returns
+    ///
+    /// * `String` - The value as it was stored in the table.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the archive is missing or corrupt.
+    pub fn fetch(&self, hash: &str) -> io::Result<String> {
+        let bytes = zstd::decode_all(self.store.get(hash)?.as_slice())?;
+        String::from_utf8(bytes).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+
+    /// Removes the archives no table refers to.
+    ///
+    /// # Arguments
+    ///
+    /// * `referenced` - The hashes held by the archived rows of the tables.
+    /// * `before` - The instant before which an archive must have been written to be removed,
+    ///   sparing the archives whose row may not be updated yet.
+    ///
+    /// # Returns
+    ///
+    /// * `usize` - The number of removed archives.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the archives cannot be listed or an archive
+    /// cannot be removed.
+    pub fn remove_unreferenced(
+        &self,
+        referenced: &HashSet<String>,
+        before: SystemTime,
+    ) -> io::Result<usize> {
+        let mut removed = 0;
+        for (hash, written) in self.store.list()? {
+            if referenced.contains(&hash) || written >= before {
+                continue;
+            }
+            self.store.delete(&hash)?;
+            removed += 1;
+        }
+        Ok(removed)
+    }
+}
+
+/// A struct representing what a run of the tiering job changed.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct TieringReport {
+    /// The number of keys archived.
+    pub archived: usize,
+    /// The number of archives removed as no table refers to them anymore.
+    pub removed: usize,
+}
+
+/// A struct periodically archiving the keys not accessed for the configured number of days.
+pub struct TieringJob {
+    db: Arc<Mutex<Database>>,
+    config: TieringConfig,
+}
+
+/// Implementation of the `TieringJob` struct.
+impl TieringJob {
+    /// Creates a new instance of the TieringJob.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - A reference to the database wrapped in an Arc and Mutex for thread safety.
+    /// * `config` - The tiering configuration holding the policy and the interval.
+    ///
+    /// # Returns
+    ///
+    /// * `TieringJob` - A new instance of the TieringJob.
+    pub fn new(db: Arc<Mutex<Database>>, config: TieringConfig) -> Self {
+        TieringJob { db, config }
+    }
+
+    /// Archives the cold keys in the background at the configured interval, while the instance
+    /// leads the cluster, if tiering is enabled.
+    ///
+    /// # Arguments
+    ///
+    /// * `cluster` - The cluster electing the instance running the background jobs.
+    pub fn spawn(self, cluster: Arc<Cluster>) {
+        if !self.config.enabled {
+            return;
+        }
+        tokio::spawn(async move {
+            let mut interval =
+                tokio::time::interval(Duration::from_secs(self.config.interval_secs.max(1)));
+            loop {
+                interval.tick().await;
+                if !cluster.is_leader() {
+                    continue;
+                }
+                if let Err(e) = self.run().await {
+                    log::error!("Failed to archive cold keys: {}", e);
+                }
+            }
+        });
+    }
+
+    /// Archives the cold keys and removes the unreferenced archives once.
+    ///
+    /// # Returns
+    ///
+    /// * `TieringReport` - The number of archived keys and removed archives.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the keys cannot be read or archived.
+    pub async fn run(&self) -> Result<TieringReport, sqlx::Error> {
+        Self::run_with(&*self.db.lock().await, &self.config).await
+    }
+
+    /// Archives the cold keys of a database and removes its unreferenced archives once.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - The database holding the keys.
+    /// * `config` - The tiering configuration holding the policy.
+    ///
+    /// # Returns
+    ///
+    /// * `TieringReport` - The number of archived keys and removed archives.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the keys cannot be read or archived.
+    pub async fn run_with(
+        db: &Database,
+        config: &TieringConfig,
+    ) -> Result<TieringReport, sqlx::Error> {
+        let started = SystemTime::now();
+        let idle = Duration::from_secs(config.cold_after_days.saturating_mul(86_400));
+        let report = TieringReport {
+            archived: db.archive_cold_keys(idle, config.batch_size).await?,
+            removed: db.remove_unreferenced_archives(started).await?,
+        };
+        if report.archived > 0 || report.removed > 0 {
+            tracing::info!(
+                target: "audit",
+                archived = report.archived,
+                removed = report.removed,
+                "Archived cold keys"
+            );
+        }
+        Ok(report)
+    }
+}
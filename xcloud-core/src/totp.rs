@@ -0,0 +1,109 @@
+use data_encoding::BASE32_NOPAD;
+use hmac::{Hmac, Mac};
+use sha1::Sha1;
+use subtle::ConstantTimeEq;
+
+/// The number of seconds a code is valid for.
+const STEP_SECS: u64 = 30;
+
+/// The number of digits of a code.
+const DIGITS: u32 = 6;
+
+/// A struct computing and verifying RFC 6238 time-based one-time passwords with the defaults
+/// of authenticator apps (HMAC-SHA1, 30-second steps, 6 digits), confirming sensitive admin
+/// operations with a second factor.
+pub struct Totp {
+    secret: Vec<u8>,
+}
+
+/// Implementation of the `Totp` struct.
+impl Totp {
+    /// Creates a new instance of the Totp.
+    ///
+    /// # Arguments
+    ///
+    /// * `secret` - The base32-encoded secret shared with the authenticator of the admin, as
+    ///   shown by authenticator apps, ignoring case, spaces and padding.
+    ///
+    /// # Returns
+    ///
+    /// * `Totp` - A new instance of the Totp.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the secret is not valid base32.
+    pub fn new(secret: &str) -> Result<Self, String> {
+        let normalized: String = secret
+            .chars()
+            .filter(|c| !c.is_whitespace() && *c != '=')
+            .map(|c| c.to_ascii_uppercase())
+            .collect();
+        let secret = BASE32_NOPAD
+            .decode(normalized.as_bytes())
+            .map_err(|e| format!("invalid base32 secret: {}", e))?;
+        if secret.is_empty() {
+            return Err("empty secret".to_string());
+        }
+        Ok(Totp { secret })
+    }
+
+    /// Computes the code valid at a time.
+    ///
+    /// # Arguments
+    ///
+    /// * `at` - The unix timestamp in seconds.
+    ///
+    /// # Returns
+    ///
+    /// * `String` - The zero-padded code.
+    pub fn code(&self, at: u64) -> String {
+        self.code_at_step(at / STEP_SECS)
+    }
+
+    /// Computes the code of a time step.
+    fn code_at_step(&self, step: u64) -> String {
+        let mut mac =
+            Hmac::<Sha1>::new_from_slice(&self.secret).expect("HMAC accepts keys of any size");
+        mac.update(&step.to_be_bytes());
+        let digest = mac.finalize().into_bytes();
+        let offset = (digest[digest.len() - 1] & 0x0f) as usize;
+        let binary = u32::from_be_bytes([
+            digest[offset] & 0x7f,
+            digest[offset + 1],
+            digest[offset + 2],
+            digest[offset + 3],
+        ]);
+        format!(
+            "{:0width$}",
+            binary % 10u32.pow(DIGITS),
+            width = DIGITS as usize
+        )
+    }
+
+    /// Verifies a code, accepting those of the adjacent steps to tolerate clock drift, but
+    /// none of a step at or before the last accepted one, so a code cannot be replayed.
+    ///
+    /// # Arguments
+    ///
+    /// * `code` - The code entered by the admin.
+    /// * `now` - The current unix timestamp in seconds.
+    /// * `last_step` - The time step of the last accepted code, if any.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<u64>` - The time step of the code to record as the last accepted one, or
+    ///   `None` if the code is invalid or was already used.
+    pub fn verify(&self, code: &str, now: u64, last_step: Option<u64>) -> Option<u64> {
+        let code = code.trim().as_bytes();
+        let step = now / STEP_SECS;
+        // Every candidate is compared, so the time taken does not reveal which one matched.
+        let mut accepted = None;
+        for candidate in [step.saturating_sub(1), step, step + 1] {
+            let matches: bool = self.code_at_step(candidate).as_bytes().ct_eq(code).into();
+            if matches && last_step.is_none_or(|last| candidate > last) {
+                accepted = Some(candidate);
+            }
+        }
+        accepted
+    }
+}
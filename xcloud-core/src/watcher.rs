@@ -0,0 +1,85 @@
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, PoisonError, RwLock};
+
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+
+use crate::config::Config;
+use crate::logging::Logging;
+
+/// A struct watching the configuration file and applying changes at runtime.
+///
+/// The log level and the CORS origins take effect immediately, other settings
+/// are only shown by `/admin/config` and require a restart.
+pub struct ConfigWatcher {
+    _watcher: RecommendedWatcher,
+}
+
+/// Implementation of the `ConfigWatcher` struct.
+impl ConfigWatcher {
+    /// Starts watching the configuration file.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path of the configuration file.
+    /// * `config` - The live configuration to update.
+    /// * `logging` - The handle used to change the log level at runtime.
+    ///
+    /// # Returns
+    ///
+    /// * `ConfigWatcher` - The watcher, which stops watching when dropped.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the file cannot be watched.
+    pub fn spawn(
+        path: PathBuf,
+        config: Arc<RwLock<Config>>,
+        logging: Arc<Logging>,
+    ) -> notify::Result<Self> {
+        let file = path.clone();
+        let mut watcher =
+            notify::recommended_watcher(move |event: notify::Result<Event>| match event {
+                Ok(event)
+                    if matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_))
+                        && event.paths.iter().any(|p| p == &file) =>
+                {
+                    Self::reload(&file, &config, &logging)
+                }
+                Ok(_) => {}
+                Err(e) => log::error!("Config watcher failed: {}", e),
+            })?;
+        let dir = path.parent().map(PathBuf::from).unwrap_or_default();
+        std::fs::create_dir_all(&dir)?;
+        watcher.watch(&dir, RecursiveMode::NonRecursive)?;
+        log::info!("Watching config at {:?}", path);
+        Ok(ConfigWatcher { _watcher: watcher })
+    }
+
+    /// Reloads the configuration file and applies the changes.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path of the configuration file.
+    /// * `config` - The live configuration to update.
+    /// * `logging` - The handle used to change the log level at runtime.
+    fn reload(path: &Path, config: &RwLock<Config>, logging: &Logging) {
+        let updated: Config = match std::fs::read_to_string(path)
+            .map_err(|e| e.to_string())
+            .and_then(|raw| toml::from_str(&raw).map_err(|e| e.to_string()))
+        {
+            Ok(updated) => updated,
+            Err(e) => {
+                log::error!("Failed to reload config, keeping the previous one: {}", e);
+                return;
+            }
+        };
+        let mut current = config.write().unwrap_or_else(PoisonError::into_inner);
+        if current.logging.level != updated.logging.level {
+            if let Err(e) = logging.set_level(&updated.logging.level) {
+                log::error!("Failed to apply log level from config: {}", e);
+            }
+        }
+        *current = updated;
+        log::info!("Config reloaded");
+    }
+}
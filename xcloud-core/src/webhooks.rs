@@ -0,0 +1,194 @@
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use async_trait::async_trait;
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+
+use crate::db::Database;
+use crate::jobs::{Job, JobQueue};
+use crate::mqtt::MqttPublisher;
+
+/// The header carrying the HMAC-SHA256 signature of a webhook payload.
+pub const SIGNATURE_HEADER: &str = "X-xCLOUD-Signature";
+
+/// A struct representing a webhook registered for a table.
+#[derive(Serialize, Deserialize, Clone, sqlx::FromRow)]
+pub struct Webhook {
+    pub id: i64,
+    pub table_name: String,
+    pub url: String,
+    #[serde(skip_serializing)]
+    pub secret: String,
+}
+
+/// A struct representing the payload sent to a webhook when data changes.
+#[derive(Serialize, Clone)]
+pub struct WebhookEvent {
+    pub event: String,
+    pub table: String,
+    pub key: Option<String>,
+    pub value: Option<String>,
+    pub timestamp: u64,
+}
+
+/// Implementation of the `WebhookEvent` struct.
+impl WebhookEvent {
+    /// Creates a new instance of the WebhookEvent.
+    ///
+    /// # Arguments
+    ///
+    /// * `event` - The kind of change, e.g. `set`, `update` or `delete`.
+    /// * `table` - The table in which the change happened.
+    /// * `key` - The key that was changed, if any.
+    /// * `value` - The new value of the key, if any.
+    ///
+    /// # Returns
+    ///
+    /// * `WebhookEvent` - A new instance of the WebhookEvent.
+    pub fn new(event: &str, table: &str, key: Option<&str>, value: Option<&str>) -> Self {
+        WebhookEvent {
+            event: event.to_string(),
+            table: table.to_string(),
+            key: key.map(str::to_string),
+            value: value.map(str::to_string),
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or_default(),
+        }
+    }
+}
+
+/// A job delivering a single signed payload to a single webhook.
+struct WebhookDelivery {
+    client: reqwest::Client,
+    webhook: Webhook,
+    body: String,
+}
+
+#[async_trait]
+impl Job for WebhookDelivery {
+    fn name(&self) -> String {
+        format!("webhook #{} ({})", self.webhook.id, self.webhook.url)
+    }
+
+    async fn run(&self) -> Result<(), String> {
+        let response = self
+            .client
+            .post(&self.webhook.url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .header(
+                SIGNATURE_HEADER,
+                format!(
+                    "sha256={}",
+                    Webhooks::sign(&self.webhook.secret, &self.body)
+                ),
+            )
+            .body(self.body.clone())
+            .send()
+            .await
+            .map_err(|e| e.to_string())?;
+        if response.status().is_success() {
+            Ok(())
+        } else {
+            Err(format!("unexpected status {}", response.status()))
+        }
+    }
+}
+
+/// A struct dispatching change notifications to registered webhooks.
+pub struct Webhooks {
+    client: reqwest::Client,
+    queue: JobQueue,
+    mqtt: Option<MqttPublisher>,
+}
+
+/// Implementation of the `Webhooks` struct.
+impl Webhooks {
+    /// Creates a new instance of the Webhooks dispatcher.
+    ///
+    /// # Arguments
+    ///
+    /// * `queue` - The job queue used to deliver and retry payloads.
+    ///
+    /// # Returns
+    ///
+    /// * `Webhooks` - A new instance of the Webhooks dispatcher.
+    pub fn new(queue: JobQueue) -> Self {
+        Webhooks {
+            client: reqwest::Client::new(),
+            queue,
+            mqtt: None,
+        }
+    }
+
+    /// Publishes every notified change to an MQTT broker as well.
+    ///
+    /// # Arguments
+    ///
+    /// * `mqtt` - The publisher, or `None` to only notify the webhooks.
+    ///
+    /// # Returns
+    ///
+    /// * `Webhooks` - The dispatcher publishing the changes.
+    pub fn with_mqtt(mut self, mqtt: Option<MqttPublisher>) -> Self {
+        self.mqtt = mqtt;
+        self
+    }
+
+    /// Signs the given body with the given secret using HMAC-SHA256.
+    ///
+    /// # Arguments
+    ///
+    /// * `secret` - The secret shared with the webhook receiver.
+    /// * `body` - The payload to sign.
+    ///
+    /// # Returns
+    ///
+    /// * `String` - The hex encoded signature.
+    pub fn sign(secret: &str, body: &str) -> String {
+        let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes())
+            .expect("HMAC accepts keys of any size");
+        mac.update(body.as_bytes());
+        hex::encode(mac.finalize().into_bytes())
+    }
+
+    /// Notifies all webhooks registered for the table of the event, and publishes the event to
+    /// the MQTT broker if configured.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - The database holding the webhook registrations.
+    /// * `event` - The event to deliver.
+    pub async fn notify(&self, db: &Database, event: WebhookEvent) {
+        if let Some(mqtt) = &self.mqtt {
+            mqtt.publish(&event);
+        }
+        let webhooks = match db.get_webhooks(&event.table).await {
+            Ok(webhooks) => webhooks,
+            Err(e) => {
+                log::error!("Failed to load webhooks: {}", e);
+                return;
+            }
+        };
+        if webhooks.is_empty() {
+            return;
+        }
+        let body = match serde_json::to_string(&event) {
+            Ok(body) => body,
+            Err(e) => {
+                log::error!("Failed to serialize webhook event: {}", e);
+                return;
+            }
+        };
+        for webhook in webhooks {
+            self.queue.enqueue(Arc::new(WebhookDelivery {
+                client: self.client.clone(),
+                webhook,
+                body: body.clone(),
+            }));
+        }
+    }
+}
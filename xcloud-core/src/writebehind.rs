@@ -0,0 +1,200 @@
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::{Duration, Instant};
+
+use crate::config::WriteBehindConfig;
+use crate::db::Database;
+use crate::utils::Utils;
+
+/// A struct holding the writes of a table buffered since its last flush.
+struct PendingWrites {
+    /// The time of the first write buffered since the last flush.
+    since: Instant,
+    /// The latest value of every written key.
+    values: BTreeMap<String, String>,
+}
+
+/// A struct buffering the writes to the tables written behind until they are flushed.
+pub struct WriteBuffer {
+    /// The longest time a write of each table is buffered, keyed by sanitized table name.
+    tables: HashMap<String, Duration>,
+    max_pending: usize,
+    pending: Mutex<HashMap<String, PendingWrites>>,
+}
+
+/// Implementation of the `WriteBuffer` struct.
+impl WriteBuffer {
+    /// Creates a new instance of the WriteBuffer.
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - The write-behind configuration naming the tables written behind.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<WriteBuffer>` - The buffer, or `None` if no table is written behind.
+    pub fn new(config: &WriteBehindConfig) -> Option<Self> {
+        if config.tables.is_empty() {
+            return None;
+        }
+        Some(WriteBuffer {
+            tables: config
+                .tables
+                .iter()
+                .map(|(table, ms)| (Utils::sanitize(table), Duration::from_millis(*ms)))
+                .collect(),
+            max_pending: config.max_pending.max(1),
+            pending: Mutex::new(HashMap::new()),
+        })
+    }
+
+    /// Checks whether the writes to a table are buffered.
+    ///
+    /// # Arguments
+    ///
+    /// * `table` - The table.
+    ///
+    /// # Returns
+    ///
+    /// * `bool` - Whether the table is written behind.
+    pub fn buffers(&self, table: &str) -> bool {
+        self.tables.contains_key(&Utils::sanitize(table))
+    }
+
+    /// Buffers a write, replacing any buffered value of the key.
+    ///
+    /// # Arguments
+    ///
+    /// * `table` - The table written behind.
+    /// * `key` - The written key.
+    /// * `value` - The new value.
+    ///
+    /// # Returns
+    ///
+    /// * `bool` - Whether the buffer is full and should be flushed.
+    pub fn buffer(&self, table: &str, key: &str, value: &str) -> bool {
+        let mut pending = self.pending.lock().unwrap_or_else(PoisonError::into_inner);
+        pending
+            .entry(Utils::sanitize(table))
+            .or_insert_with(|| PendingWrites {
+                since: Instant::now(),
+                values: BTreeMap::new(),
+            })
+            .values
+            .insert(key.to_string(), value.to_string());
+        pending
+            .values()
+            .map(|writes| writes.values.len())
+            .sum::<usize>()
+            >= self.max_pending
+    }
+
+    /// Takes the buffered value of a key.
+    ///
+    /// # Arguments
+    ///
+    /// * `table` - The table holding the key.
+    /// * `key` - The key.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<String>` - The buffered value, or `None` if no write of the key is buffered.
+    pub fn take(&self, table: &str, key: &str) -> Option<String> {
+        let mut pending = self.pending.lock().unwrap_or_else(PoisonError::into_inner);
+        let table = Utils::sanitize(table);
+        let writes = pending.get_mut(&table)?;
+        let value = writes.values.remove(key);
+        if writes.values.is_empty() {
+            pending.remove(&table);
+        }
+        value
+    }
+
+    /// Takes the buffered writes of a table.
+    ///
+    /// # Arguments
+    ///
+    /// * `table` - The table.
+    ///
+    /// # Returns
+    ///
+    /// * `Vec<(String, String)>` - The buffered keys with their latest values.
+    pub fn take_table(&self, table: &str) -> Vec<(String, String)> {
+        self.pending
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .remove(&Utils::sanitize(table))
+            .map(|writes| writes.values.into_iter().collect())
+            .unwrap_or_default()
+    }
+
+    /// Takes the buffered writes of the tables whose oldest buffered write is due, or of every
+    /// table.
+    ///
+    /// # Arguments
+    ///
+    /// * `all` - Whether to take the writes of every table, due or not.
+    ///
+    /// # Returns
+    ///
+    /// * `Vec<(String, Vec<(String, String)>)>` - The tables with their buffered keys and
+    ///   latest values.
+    pub fn take_due(&self, all: bool) -> Vec<(String, Vec<(String, String)>)> {
+        let mut pending = self.pending.lock().unwrap_or_else(PoisonError::into_inner);
+        let due: Vec<String> = pending
+            .iter()
+            .filter(|(table, writes)| {
+                all || writes.since.elapsed()
+                    >= self.tables.get(*table).copied().unwrap_or_default()
+            })
+            .map(|(table, _)| table.clone())
+            .collect();
+        due.into_iter()
+            .filter_map(|table| {
+                let writes = pending.remove(&table)?;
+                Some((table, writes.values.into_iter().collect()))
+            })
+            .collect()
+    }
+}
+
+/// A struct periodically flushing the buffered writes to the database.
+pub struct WriteBehindFlusher {
+    db: Arc<tokio::sync::Mutex<Database>>,
+    config: WriteBehindConfig,
+}
+
+/// Implementation of the `WriteBehindFlusher` struct.
+impl WriteBehindFlusher {
+    /// Creates a new instance of the WriteBehindFlusher.
+    ///
+    /// # Arguments
+    ///
+    /// * `db` - A reference to the database wrapped in an Arc and Mutex for thread safety.
+    /// * `config` - The write-behind configuration holding the delays of the tables.
+    ///
+    /// # Returns
+    ///
+    /// * `WriteBehindFlusher` - A new instance of the WriteBehindFlusher.
+    pub fn new(db: Arc<tokio::sync::Mutex<Database>>, config: WriteBehindConfig) -> Self {
+        WriteBehindFlusher { db, config }
+    }
+
+    /// Flushes the due writes in the background, checking as often as the shortest delay, if
+    /// any table is written behind.
+    pub fn spawn(self) {
+        let Some(delay) = self.config.tables.values().min().copied() else {
+            return;
+        };
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(Duration::from_millis(delay.max(1)));
+            interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            loop {
+                interval.tick().await;
+                if let Err(e) = self.db.lock().await.flush_writes(false).await {
+                    log::error!("Failed to flush buffered writes: {}", e);
+                }
+            }
+        });
+    }
+}
@@ -1,25 +1,102 @@
-mod db;
-mod errors;
-mod middleware;
-mod server;
-mod utils;
-
-use db::Database;
-use errors::AppError;
-use server::Server;
+use xcloud_core::bench::{Bench, BenchOptions, USAGE};
+use xcloud_core::check::CheckReport;
+use xcloud_core::crypto::KeyRing;
+use xcloud_core::flags::FeatureFlags;
+use xcloud_core::logging::Logging;
+use xcloud_core::maintenance::Maintenance;
+use xcloud_core::seed::Seeder;
+use xcloud_core::{AppError, Config, Database, Server};
 
 /// Main function for the application.
 #[actix_web::main]
 async fn main() -> Result<(), AppError> {
-    env_logger::init_from_env(env_logger::Env::default().default_filter_or("debug"));
+    if std::env::args().nth(1).as_deref() == Some("check") {
+        let report = CheckReport::run(Config::load()).await;
+        println!("{}", report.to_json());
+        std::process::exit(if report.passed { 0 } else { 1 });
+    }
+    if std::env::args().nth(1).as_deref() == Some("bench") {
+        let report = match BenchOptions::parse(std::env::args().skip(2)) {
+            Ok(options) => Bench::new(options).run().await,
+            Err(e) => Err(format!("{}\n{}", e, USAGE)),
+        };
+        match report {
+            Ok(report) => println!("{}", report.to_json()),
+            Err(e) => {
+                eprintln!("{}", e);
+                std::process::exit(1);
+            }
+        }
+        return Ok(());
+    }
+    if std::env::args().nth(1).as_deref() == Some("seed") {
+        let config = Config::load()?;
+        let dir = std::env::args()
+            .nth(2)
+            .map(std::path::PathBuf::from)
+            .unwrap_or_else(|| Seeder::dir(&config.seeds));
+        let db = open_database(&config).await?;
+        let report = Seeder::run(&db, &dir).await?;
+        db.flush_writes(true).await?;
+        println!("{}", report.to_json());
+        return Ok(());
+    }
+    let config = Config::load()?;
+    let logging = Logging::init(&config.logging);
 
     log::info!("Starting database...");
+    let db = open_database(&config).await?;
+    if config.seeds.on_startup {
+        let report = Seeder::run(&db, &Seeder::dir(&config.seeds)).await?;
+        if report.files > 0 {
+            log::info!(
+                "Seeded {} table(s) and {} key(s) from {} file(s)",
+                report.tables_created,
+                report.keys_written,
+                report.files
+            );
+        }
+    }
+    let maintenance = Maintenance::load(&db).await?;
+    let flags = FeatureFlags::load(&db).await?;
     log::info!("Starting server...");
-    Server::new(Database::new().await?, "0.0.0.0:8080")
-        .run()
-        .await
-        .map_err(AppError::from)?;
+    let server = Server::new(db, config, logging)
+        .with_maintenance(maintenance)
+        .with_feature_flags(flags);
+    let _watcher = server.watch_config(Config::path())?;
+    server.run().await.map_err(AppError::from)?;
     log::info!("Database closed.");
     log::info!("Server closed.");
     Ok(())
 }
+
+/// Opens the database with the features of the configuration.
+///
+/// # Arguments
+///
+/// * `config` - The configuration of the application.
+///
+/// # Returns
+///
+/// * `Database` - The opened database.
+///
+/// # Errors
+///
+/// This function will return an error if the encryption keys are invalid or the database cannot
+/// be opened.
+async fn open_database(config: &Config) -> Result<Database, AppError> {
+    let keyring = KeyRing::from_config(&config.encryption)?;
+    Ok(Database::new(&config.database)
+        .await?
+        .with_keyring(keyring)
+        .with_activity(config.activity.clone())
+        .with_compression(config.compression.clone())
+        .with_dedup(config.dedup.clone())
+        .with_history(config.history.clone())
+        .with_key_stats(config.key_stats.clone())
+        .with_retry(config.retry.clone())
+        .with_slow_query_log(config.slow_queries.clone())
+        .with_tiering(config.tiering.clone())
+        .with_write_behind(config.write_behind.clone())
+        .with_outbox(config.replication.peer.is_some()))
+}